@@ -1,21 +1,24 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Attribute, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Order, Reply,
+    to_json_binary, Addr, Attribute, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Order, Reply,
     Response, StdResult,
 };
 use cw2::set_contract_version;
+use cw_storage_plus::Bound;
 use valence_account_utils::{
     error::ContractError,
     msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ValenceCallback},
 };
 
-use crate::state::APPROVED_LIBRARIES;
+use crate::state::{APPROVED_LIBRARIES, DENOM_ALLOWLIST};
 
 // version info for migration info
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const MAX_PAGE_LIMIT: u32 = 250;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -42,25 +45,42 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Funds sent along with any message must be of an allowed denom
+    execute::ensure_allowed_funds(deps.as_ref(), &info)?;
+
     match msg {
         ExecuteMsg::ApproveLibrary { library } => execute::approve_library(deps, info, library),
         ExecuteMsg::RemoveLibrary { library } => execute::remove_library(deps, info, library),
+        ExecuteMsg::ApproveLibraries { libraries } => {
+            execute::approve_libraries(deps, info, libraries)
+        }
+        ExecuteMsg::RemoveLibraries { libraries } => {
+            execute::remove_libraries(deps, info, libraries)
+        }
         ExecuteMsg::ExecuteMsg { msgs } => execute::execute_msg(deps, info, msgs),
         ExecuteMsg::UpdateOwnership(action) => execute::update_ownership(deps, env, info, action),
         ExecuteMsg::ExecuteSubmsgs { msgs, payload } => {
             execute::execute_submsgs(deps, info, msgs, payload)
         }
+        ExecuteMsg::UpdateDenomAllowlist { denoms } => {
+            execute::update_denom_allowlist(deps, info, denoms)
+        }
     }
 }
 
 mod execute {
-    use cosmwasm_std::{ensure, CosmosMsg, DepsMut, Empty, Env, MessageInfo, Response, SubMsg};
+    use std::collections::BTreeSet;
+
+    use cosmwasm_std::{
+        ensure, Addr, Api, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError,
+        StdResult, SubMsg,
+    };
     use valence_account_utils::{
         error::{ContractError, UnauthorizedReason},
         msg::VALENCE_PAYLOAD_KEY,
     };
 
-    use crate::state::APPROVED_LIBRARIES;
+    use crate::state::{APPROVED_LIBRARIES, DENOM_ALLOWLIST};
 
     pub fn approve_library(
         deps: DepsMut,
@@ -92,6 +112,58 @@ mod execute {
             .add_attribute("library", library_addr))
     }
 
+    pub fn approve_libraries(
+        deps: DepsMut,
+        info: MessageInfo,
+        libraries: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        // Validate all addresses first so that an invalid one rejects the whole batch
+        let library_addrs = validate_libraries(deps.api, &libraries)?;
+        for library_addr in &library_addrs {
+            APPROVED_LIBRARIES.save(deps.storage, library_addr.clone(), &Empty {})?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("method", "approve_libraries")
+            .add_attribute("libraries", join_addrs(&library_addrs)))
+    }
+
+    pub fn remove_libraries(
+        deps: DepsMut,
+        info: MessageInfo,
+        libraries: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        // Validate all addresses first so that an invalid one rejects the whole batch
+        let library_addrs = validate_libraries(deps.api, &libraries)?;
+        for library_addr in &library_addrs {
+            APPROVED_LIBRARIES.remove(deps.storage, library_addr.clone());
+        }
+
+        Ok(Response::new()
+            .add_attribute("method", "remove_libraries")
+            .add_attribute("libraries", join_addrs(&library_addrs)))
+    }
+
+    // Validate library addresses, dropping duplicates while preserving order
+    fn validate_libraries(api: &dyn Api, libraries: &[String]) -> StdResult<Vec<Addr>> {
+        let mut library_addrs: Vec<Addr> = Vec::with_capacity(libraries.len());
+        for library in libraries {
+            let library_addr = api.addr_validate(library)?;
+            if !library_addrs.contains(&library_addr) {
+                library_addrs.push(library_addr);
+            }
+        }
+        Ok(library_addrs)
+    }
+
+    fn join_addrs(addrs: &[Addr]) -> String {
+        addrs.iter().map(Addr::as_str).collect::<Vec<_>>().join(",")
+    }
+
     pub fn execute_submsgs(
         deps: DepsMut,
         info: MessageInfo,
@@ -130,6 +202,49 @@ mod execute {
             .add_attribute("sender", info.sender))
     }
 
+    pub fn update_denom_allowlist(
+        deps: DepsMut,
+        info: MessageInfo,
+        denoms: Option<Vec<String>>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let Some(denoms) = denoms else {
+            DENOM_ALLOWLIST.remove(deps.storage);
+            return Ok(Response::new()
+                .add_attribute("method", "update_denom_allowlist")
+                .add_attribute("denoms", "any"));
+        };
+
+        if denoms.iter().any(String::is_empty) {
+            return Err(StdError::generic_err("Allowed denoms cannot be empty").into());
+        }
+        let denoms: BTreeSet<String> = denoms.into_iter().collect();
+        DENOM_ALLOWLIST.save(deps.storage, &denoms)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "update_denom_allowlist")
+            .add_attribute("denoms", denoms.into_iter().collect::<Vec<_>>().join(",")))
+    }
+
+    // Funds sent by the bank module alone never reach the contract, so only the funds attached to messages can be rejected
+    pub fn ensure_allowed_funds(deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+        let Some(allowlist) = DENOM_ALLOWLIST.may_load(deps.storage)? else {
+            return Ok(());
+        };
+
+        match info
+            .funds
+            .iter()
+            .find(|coin| !allowlist.contains(&coin.denom))
+        {
+            Some(coin) => Err(ContractError::DenomNotAllowed {
+                denom: coin.denom.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
     pub fn update_ownership(
         deps: DepsMut,
         env: Env,
@@ -154,9 +269,33 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 .collect::<StdResult<Vec<_>>>()?;
             to_json_binary(&libraries)
         }
+        QueryMsg::Libraries { start_after, limit } => {
+            to_json_binary(&get_libraries(deps, start_after, limit)?)
+        }
+        QueryMsg::DenomAllowlist {} => {
+            let allowlist = DENOM_ALLOWLIST.may_load(deps.storage)?;
+            to_json_binary(&allowlist.map(|denoms| denoms.into_iter().collect::<Vec<_>>()))
+        }
     }
 }
 
+fn get_libraries(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Addr>> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+    let start = start_after
+        .map(|library| deps.api.addr_validate(&library))
+        .transpose()?
+        .map(Bound::exclusive);
+
+    APPROVED_LIBRARIES
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .collect()
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     // we relay the response back to the initiating library
@@ -1,5 +1,10 @@
+use std::collections::BTreeSet;
+
 use cosmwasm_std::{Addr, Empty};
-use cw_storage_plus::Map;
+use cw_storage_plus::{Item, Map};
 
 // Approved libraries that can execute actions on behalf of the account
 pub const APPROVED_LIBRARIES: Map<Addr, Empty> = Map::new("libraries");
+
+// Denoms of the funds accepted by the account, any denom is accepted if not set
+pub const DENOM_ALLOWLIST: Item<BTreeSet<String>> = Item::new("denom_allowlist");
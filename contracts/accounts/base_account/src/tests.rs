@@ -121,6 +121,14 @@ impl BaseAccountTestSuite {
         )
     }
 
+    fn approve_libraries(&mut self, addr: Addr, libraries: Vec<String>) -> AnyResult<AppResponse> {
+        self.contract_execute(addr, &ExecuteMsg::ApproveLibraries { libraries })
+    }
+
+    fn remove_libraries(&mut self, addr: Addr, libraries: Vec<String>) -> AnyResult<AppResponse> {
+        self.contract_execute(addr, &ExecuteMsg::RemoveLibraries { libraries })
+    }
+
     fn transfer_tokens(
         &mut self,
         account: Addr,
@@ -237,10 +245,48 @@ impl BaseAccountTestSuite {
         )
     }
 
+    fn update_denom_allowlist(
+        &mut self,
+        addr: Addr,
+        sender: Addr,
+        denoms: Option<Vec<String>>,
+    ) -> AnyResult<AppResponse> {
+        self.app_mut().execute_contract(
+            sender,
+            addr,
+            &ExecuteMsg::UpdateDenomAllowlist { denoms },
+            &[],
+        )
+    }
+
+    // Owner executes an empty message on the account, sending it the funds along
+    fn deposit_with_msg(&mut self, addr: Addr, funds: Vec<Coin>) -> AnyResult<AppResponse> {
+        let owner = self.owner().clone();
+        self.app_mut().execute_contract(
+            owner,
+            addr,
+            &ExecuteMsg::ExecuteMsg { msgs: vec![] },
+            &funds,
+        )
+    }
+
+    fn query_denom_allowlist(&mut self, addr: &Addr) -> Option<Vec<String>> {
+        self.query_wasm(addr, &QueryMsg::DenomAllowlist {})
+    }
+
     fn query_approved_libraries(&mut self, addr: &Addr) -> Vec<Addr> {
         self.query_wasm(addr, &QueryMsg::ListApprovedLibraries {})
     }
 
+    fn query_libraries(
+        &mut self,
+        addr: &Addr,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> Vec<Addr> {
+        self.query_wasm(addr, &QueryMsg::Libraries { start_after, limit })
+    }
+
     fn query_owership(&mut self, addr: &Addr) -> Ownership<Addr> {
         self.query_wasm(addr, &QueryMsg::Ownership {})
     }
@@ -392,6 +438,165 @@ fn remove_library_by_non_owner() {
     );
 }
 
+#[test]
+fn approve_libraries_by_owner() {
+    let mut suite = BaseAccountTestSuite::default();
+
+    let lib1 = suite.api().addr_make("library_1");
+    let lib2 = suite.api().addr_make("library_2");
+    let lib3 = suite.api().addr_make("library_3");
+
+    // Instantiate Base account contract with approved libraries
+    let acc = suite.account_init(vec![lib1.to_string()]);
+
+    // Owner approves new libraries on account in a single batch (with a duplicate)
+    suite
+        .approve_libraries(
+            acc.clone(),
+            vec![lib2.to_string(), lib3.to_string(), lib2.to_string()],
+        )
+        .unwrap();
+
+    // Verify approved libraries
+    let approved_libraries = sorted(suite.query_approved_libraries(&acc)).collect::<Vec<Addr>>();
+    assert_eq!(
+        approved_libraries,
+        sorted(vec![lib1, lib2, lib3]).collect::<Vec<Addr>>()
+    );
+}
+
+#[test]
+fn approve_libraries_with_invalid_address_rolls_back() {
+    let mut suite = BaseAccountTestSuite::default();
+
+    let lib1 = suite.api().addr_make("library_1");
+    let lib2 = suite.api().addr_make("library_2");
+
+    // Instantiate Base account contract with approved libraries
+    let acc = suite.account_init(vec![lib1.to_string()]);
+
+    // Owner approves a batch containing an invalid address
+    let res = suite.approve_libraries(
+        acc.clone(),
+        vec![lib2.to_string(), "invalid_library".to_string()],
+    );
+    assert!(res.is_err());
+
+    // Verify approved libraries: none of the batch should have been approved
+    let approved_libraries = suite.query_approved_libraries(&acc);
+    assert_eq!(approved_libraries, vec![lib1]);
+}
+
+#[test]
+fn remove_libraries_by_owner() {
+    let mut suite = BaseAccountTestSuite::default();
+
+    let lib1 = suite.api().addr_make("library_1");
+    let lib2 = suite.api().addr_make("library_2");
+    let lib3 = suite.api().addr_make("library_3");
+
+    // Instantiate Base account contract with approved libraries
+    let acc = suite.account_init(vec![lib1.to_string(), lib2.to_string(), lib3.to_string()]);
+
+    // Owner removes libraries from account in a single batch
+    suite
+        .remove_libraries(acc.clone(), vec![lib1.to_string(), lib3.to_string()])
+        .unwrap();
+
+    // Verify approved libraries
+    let approved_libraries = suite.query_approved_libraries(&acc);
+    assert_eq!(approved_libraries, vec![lib2]);
+}
+
+#[test]
+fn remove_libraries_with_invalid_address_rolls_back() {
+    let mut suite = BaseAccountTestSuite::default();
+
+    let lib1 = suite.api().addr_make("library_1");
+    let lib2 = suite.api().addr_make("library_2");
+
+    // Instantiate Base account contract with approved libraries
+    let acc = suite.account_init(vec![lib1.to_string(), lib2.to_string()]);
+
+    // Owner removes a batch containing an invalid address
+    let res = suite.remove_libraries(
+        acc.clone(),
+        vec![lib1.to_string(), "invalid_library".to_string()],
+    );
+    assert!(res.is_err());
+
+    // Verify approved libraries: none of the batch should have been removed
+    let approved_libraries = sorted(suite.query_approved_libraries(&acc)).collect::<Vec<Addr>>();
+    assert_eq!(
+        approved_libraries,
+        sorted(vec![lib1, lib2]).collect::<Vec<Addr>>()
+    );
+}
+
+#[test]
+fn approve_libraries_by_non_owner() {
+    let mut suite = BaseAccountTestSuite::default();
+
+    let lib1 = suite.api().addr_make("library_1");
+    let lib2 = suite.api().addr_make("library_2");
+    let non_owner = suite.api().addr_make("non_owner");
+
+    // Instantiate Base account contract with approved libraries
+    let acc = suite.account_init(vec![lib1.to_string()]);
+
+    // Non-owner approves new libraries on account
+    let res = suite.app_mut().execute_contract(
+        non_owner,
+        acc,
+        &ExecuteMsg::ApproveLibraries {
+            libraries: vec![lib2.to_string()],
+        },
+        &[],
+    );
+    assert!(res.is_err());
+
+    assert_eq!(
+        res.unwrap_err().downcast::<ContractError>().unwrap(),
+        ContractError::OwnershipError(cw_ownable::OwnershipError::NotOwner)
+    );
+}
+
+#[test]
+fn query_libraries_paginated() {
+    let mut suite = BaseAccountTestSuite::default();
+
+    let lib1 = suite.api().addr_make("library_1");
+    let lib2 = suite.api().addr_make("library_2");
+    let lib3 = suite.api().addr_make("library_3");
+
+    // Instantiate Base account contract and approve three libraries
+    let acc = suite.account_init(vec![]);
+    suite
+        .approve_libraries(
+            acc.clone(),
+            vec![lib1.to_string(), lib2.to_string(), lib3.to_string()],
+        )
+        .unwrap();
+
+    let expected = sorted(vec![lib1, lib2, lib3]).collect::<Vec<Addr>>();
+
+    // Verify all libraries are returned without pagination
+    let libraries = suite.query_libraries(&acc, None, None);
+    assert_eq!(libraries, expected);
+
+    // Verify first page
+    let page = suite.query_libraries(&acc, None, Some(2));
+    assert_eq!(page, expected[..2].to_vec());
+
+    // Verify second page, starting after the last library of the first page
+    let page = suite.query_libraries(&acc, Some(page[1].to_string()), Some(2));
+    assert_eq!(page, expected[2..].to_vec());
+
+    // Verify nothing is returned after the last library
+    let page = suite.query_libraries(&acc, Some(expected[2].to_string()), None);
+    assert!(page.is_empty());
+}
+
 #[test]
 fn transfer_native_tokens_by_owner() {
     let mut suite = BaseAccountTestSuite::new(Some(vec![(ONE_MILLION, NTRN.to_string())]));
@@ -816,3 +1021,70 @@ fn execute_submessages_without_payload() {
         }
     }
 }
+
+#[test]
+fn denom_allowlist_rejects_funds_of_other_denoms() {
+    let mut suite = BaseAccountTestSuite::default();
+    let acc = suite.account_init(vec![]);
+    let owner = suite.owner().clone();
+    suite.init_balance(
+        &owner,
+        vec![coin(ONE_THOUSAND, NTRN), coin(ONE_THOUSAND, MEME)],
+    );
+
+    // Any denom is accepted by default
+    assert_eq!(suite.query_denom_allowlist(&acc), None);
+
+    suite
+        .update_denom_allowlist(acc.clone(), owner.clone(), Some(vec![NTRN.to_string()]))
+        .unwrap();
+    assert_eq!(
+        suite.query_denom_allowlist(&acc),
+        Some(vec![NTRN.to_string()])
+    );
+
+    // Allowed denom is accepted
+    suite
+        .deposit_with_msg(acc.clone(), vec![coin(ONE_THOUSAND, NTRN)])
+        .unwrap();
+    suite.assert_balance(&acc, coin(ONE_THOUSAND, NTRN));
+
+    // Disallowed denom is rejected, and stays with the sender
+    let err = suite
+        .deposit_with_msg(acc.clone(), vec![coin(ONE_THOUSAND, MEME)])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DenomNotAllowed {
+            denom: MEME.to_string()
+        }
+    );
+    suite.assert_balance(&acc, coin(0, MEME));
+    suite.assert_balance(&owner, coin(ONE_THOUSAND, MEME));
+
+    // Removing the allowlist accepts any denom again
+    suite
+        .update_denom_allowlist(acc.clone(), owner, None)
+        .unwrap();
+    assert_eq!(suite.query_denom_allowlist(&acc), None);
+    suite
+        .deposit_with_msg(acc.clone(), vec![coin(ONE_THOUSAND, MEME)])
+        .unwrap();
+    suite.assert_balance(&acc, coin(ONE_THOUSAND, MEME));
+}
+
+#[test]
+fn update_denom_allowlist_by_non_owner() {
+    let mut suite = BaseAccountTestSuite::default();
+    let acc = suite.account_init(vec![]);
+
+    let non_owner = suite.api().addr_make("non_owner");
+    let err = suite
+        .update_denom_allowlist(acc.clone(), non_owner, Some(vec![NTRN.to_string()]))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OwnershipError(OwnershipError::NotOwner)
+    );
+    assert_eq!(suite.query_denom_allowlist(&acc), None);
+}
@@ -1,20 +1,29 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError,
-    StdResult,
+    from_json, to_json_binary, Addr, Binary, Coin, Deps, DepsMut, Empty, Env, MessageInfo, Order,
+    Reply, Response, StdError, StdResult, Storage, SubMsg, WasmMsg,
 };
 use cw2::set_contract_version;
 use neutron_sdk::{
-    bindings::{msg::NeutronMsg, query::NeutronQuery},
-    sudo::msg::SudoMsg,
+    bindings::{
+        msg::{MsgRegisterInterchainQueryResponse, MsgSubmitTxResponse, NeutronMsg},
+        query::NeutronQuery,
+    },
+    sudo::msg::{RequestPacket, SudoMsg},
 };
-use valence_account_utils::ica::{ExecuteMsg, IcaInformation, IcaState, InstantiateMsg, QueryMsg};
-use valence_ibc_utils::neutron::OpenAckVersion;
+use valence_account_utils::ica::{
+    BalancesQuery, ExecuteMsg, IcaCallbackMsg, IcaInformation, IcaState, IcaTxResult,
+    IcaTxSubmission, InstantiateMsg, QueryMsg,
+};
+use valence_ibc_utils::{address::RemoteAddress, neutron::OpenAckVersion};
 
 use crate::{
     error::ContractError,
-    state::{APPROVED_LIBRARIES, ICA_STATE, REMOTE_DOMAIN_INFO},
+    state::{
+        ACTIVE_QUERIES, APPROVED_LIBRARIES, BALANCES_QUERY, ICA_STATE, ICA_TX_CALLBACKS,
+        LAST_ICA_INFO, REMOTE_BALANCES, REMOTE_DOMAIN_INFO,
+    },
 };
 
 // version info for migration info
@@ -23,6 +32,9 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub const INTERCHAIN_ACCOUNT_ID: &str = "valence-ica";
 pub const NTRN_DENOM: &str = "untrn";
+pub const REGISTER_BALANCES_QUERY_REPLY_ID: u64 = 0;
+pub const SUBMIT_TX_CALLBACK_REPLY_ID: u64 = 1;
+pub const ICA_TX_CALLBACK_REPLY_ID: u64 = 2;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -58,29 +70,54 @@ pub fn execute(
         ExecuteMsg::ApproveLibrary { library } => execute::approve_library(deps, info, library),
         ExecuteMsg::RemoveLibrary { library } => execute::remove_library(deps, info, library),
         ExecuteMsg::ExecuteMsg { msgs } => execute::execute_msg(deps, info, msgs),
-        ExecuteMsg::ExecuteIcaMsg { msgs } => execute::execute_ica_msg(deps, env, info, msgs),
+        ExecuteMsg::ExecuteIcaMsg { msgs } => {
+            execute::execute_ica_msg(deps, env, info, msgs, false)
+        }
+        ExecuteMsg::ExecuteIcaMsgWithCallback { msgs } => {
+            execute::execute_ica_msg(deps, env, info, msgs, true)
+        }
         ExecuteMsg::RegisterIca {} => execute::try_register_ica(deps, env),
+        ExecuteMsg::ReopenChannel {} => execute::try_reopen_channel(deps, env),
+        ExecuteMsg::UpdateRemoteBalances { balances } => {
+            execute::update_remote_balances(deps, info, balances)
+        }
+        ExecuteMsg::DeductRemoteBalances { balances } => {
+            execute::deduct_remote_balances(deps, info, balances)
+        }
+        ExecuteMsg::RegisterBalancesQuery { denoms, period } => {
+            execute::register_balances_query(deps, info, denoms, period)
+        }
+        ExecuteMsg::RefreshRemoteBalances {} => execute::refresh_remote_balances(deps),
+        ExecuteMsg::RemoveQuery { query_id } => execute::remove_query(deps, info, query_id),
         ExecuteMsg::UpdateOwnership(action) => execute::update_ownership(deps, env, info, action),
     }
 }
 
 mod execute {
     use cosmwasm_std::{
-        ensure, AnyMsg, CosmosMsg, DepsMut, Empty, Env, MessageInfo, Response, StdError,
+        ensure, to_json_binary, AnyMsg, Coin, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo,
+        Order, Response, StdError, StdResult, SubMsg,
     };
     use neutron_sdk::{
         bindings::{msg::NeutronMsg, query::NeutronQuery},
+        interchain_queries::{
+            queries::get_raw_interchain_query_result, types::KVReconstruct,
+            v045::new_register_balances_query_msg, v047::types::Balances,
+        },
         query::min_ibc_fee::query_min_ibc_fee,
     };
-    use valence_account_utils::ica::IcaState;
+    use valence_account_utils::ica::{BalancesQuery, IcaState};
     use valence_ibc_utils::neutron::{
         flatten_ntrn_ibc_fee, min_ntrn_ibc_fee, query_ica_registration_fee, register_ica_msg,
     };
 
     use crate::{
-        contract::NTRN_DENOM,
+        contract::{NTRN_DENOM, REGISTER_BALANCES_QUERY_REPLY_ID, SUBMIT_TX_CALLBACK_REPLY_ID},
         error::{ContractError, UnauthorizedReason},
-        state::{APPROVED_LIBRARIES, ICA_STATE, REMOTE_DOMAIN_INFO},
+        state::{
+            ACTIVE_QUERIES, APPROVED_LIBRARIES, BALANCES_QUERY, ICA_STATE, LAST_ICA_INFO,
+            REMOTE_BALANCES, REMOTE_DOMAIN_INFO,
+        },
     };
 
     use super::INTERCHAIN_ACCOUNT_ID;
@@ -158,6 +195,39 @@ mod execute {
         }
 
         let remote_domain_info = REMOTE_DOMAIN_INFO.load(deps.storage)?;
+
+        Ok(register_ica(deps, env, remote_domain_info.connection_id)?
+            .add_attribute("method", "register_ica"))
+    }
+
+    pub fn try_reopen_channel(
+        deps: DepsMut<NeutronQuery>,
+        env: Env,
+    ) -> Result<Response<NeutronMsg>, ContractError> {
+        // The channel can only be reopened once it has been closed
+        let state = ICA_STATE.load(deps.storage)?;
+        if state != IcaState::Closed {
+            return Err(ContractError::InvalidIcaState {
+                current_state: state.to_string(),
+            });
+        }
+
+        // Registering again with the same interchain account id reuses the existing controller port
+        let ica_info = LAST_ICA_INFO
+            .may_load(deps.storage)?
+            .ok_or(ContractError::NoIcaToReopen)?;
+
+        // The channel is reopened over the connection the ICA was registered on
+        Ok(register_ica(deps, env, ica_info.controller_connection_id)?
+            .add_attribute("method", "reopen_channel")
+            .add_attribute("port_id", ica_info.port_id))
+    }
+
+    fn register_ica(
+        deps: DepsMut<NeutronQuery>,
+        env: Env,
+        connection_id: String,
+    ) -> Result<Response<NeutronMsg>, ContractError> {
         let ica_registration_fee = query_ica_registration_fee(deps.querier)?;
 
         // Check that we have enough to cover the registration fee
@@ -174,7 +244,7 @@ mod execute {
 
         let register_ica_msg = register_ica_msg(
             env.contract.address.into_string(),
-            remote_domain_info.connection_id,
+            connection_id.clone(),
             INTERCHAIN_ACCOUNT_ID.to_string(),
             fee_to_attach,
         );
@@ -184,7 +254,7 @@ mod execute {
 
         Ok(Response::new()
             .add_message(register_ica_msg)
-            .add_attribute("method", "register_ica"))
+            .add_attribute("connection_id", connection_id))
     }
 
     pub fn execute_ica_msg(
@@ -192,6 +262,7 @@ mod execute {
         env: Env,
         info: MessageInfo,
         msgs: Vec<AnyMsg>,
+        callback: bool,
     ) -> Result<Response<NeutronMsg>, ContractError> {
         // If not admin, check if it's an approved library
         ensure!(
@@ -241,11 +312,243 @@ mod execute {
         );
         let cosmos_msg = CosmosMsg::from(submit_tx_msg);
 
+        // The sequence of the packet is only known in the reply, where the sender is recorded to be called back
+        let submsg = match callback {
+            true => SubMsg::reply_on_success(cosmos_msg, SUBMIT_TX_CALLBACK_REPLY_ID)
+                .with_payload(to_json_binary(&info.sender)?),
+            false => SubMsg::new(cosmos_msg),
+        };
+
         // Send the message
         Ok(Response::new()
-            .add_message(cosmos_msg)
+            .add_submessage(submsg)
             .add_attribute("method", "execute_ica_msg")
-            .add_attribute("sender", info.sender))
+            .add_attribute("sender", info.sender)
+            .add_attribute("callback", callback.to_string()))
+    }
+
+    pub fn update_remote_balances(
+        deps: DepsMut<NeutronQuery>,
+        info: MessageInfo,
+        balances: Vec<Coin>,
+    ) -> Result<Response<NeutronMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        balances.iter().try_for_each(|coin| {
+            REMOTE_BALANCES.save(deps.storage, coin.denom.clone(), &coin.amount)
+        })?;
+
+        Ok(Response::new()
+            .add_attribute("method", "update_remote_balances")
+            .add_attribute(
+                "balances",
+                balances
+                    .iter()
+                    .map(|coin| coin.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ))
+    }
+
+    pub fn deduct_remote_balances(
+        deps: DepsMut<NeutronQuery>,
+        info: MessageInfo,
+        balances: Vec<Coin>,
+    ) -> Result<Response<NeutronMsg>, ContractError> {
+        // If not admin, check if it's an approved library
+        ensure!(
+            cw_ownable::is_owner(deps.storage, &info.sender)?
+                || APPROVED_LIBRARIES.has(deps.storage, info.sender.clone()),
+            ContractError::Unauthorized(UnauthorizedReason::NotAdminOrApprovedLibrary)
+        );
+
+        // Only the known balances are deducted, and they can't go below zero
+        for coin in &balances {
+            if let Some(amount) = REMOTE_BALANCES.may_load(deps.storage, coin.denom.clone())? {
+                REMOTE_BALANCES.save(
+                    deps.storage,
+                    coin.denom.clone(),
+                    &amount.saturating_sub(coin.amount),
+                )?;
+            }
+        }
+
+        Ok(Response::new()
+            .add_attribute("method", "deduct_remote_balances")
+            .add_attribute("sender", info.sender)
+            .add_attribute(
+                "balances",
+                balances
+                    .iter()
+                    .map(|coin| coin.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ))
+    }
+
+    /// Builds the registration of an ICQ for the balances of the ICA
+    fn register_ica_balances_query_msg(
+        deps: Deps<NeutronQuery>,
+        denoms: &[String],
+        update_period: u64,
+    ) -> Result<NeutronMsg, ContractError> {
+        if denoms.is_empty() {
+            return Err(StdError::generic_err("At least one denom must be queried").into());
+        }
+        if update_period == 0 {
+            return Err(StdError::generic_err("Update period cannot be zero").into());
+        }
+
+        // The balances can only be queried once the address of the ICA is known
+        let ica_info = match ICA_STATE.load(deps.storage)? {
+            IcaState::Created(ica_info) => ica_info,
+            _ => return Err(ContractError::IcaNotCreated),
+        };
+        let remote_domain_info = REMOTE_DOMAIN_INFO.load(deps.storage)?;
+
+        new_register_balances_query_msg(
+            remote_domain_info.connection_id,
+            ica_info.address,
+            denoms.to_vec(),
+            update_period,
+        )
+        .map_err(|e| StdError::generic_err(e.to_string()).into())
+    }
+
+    pub fn register_balances_query(
+        deps: DepsMut<NeutronQuery>,
+        info: MessageInfo,
+        denoms: Vec<String>,
+        update_period: u64,
+    ) -> Result<Response<NeutronMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let register_msg = register_ica_balances_query_msg(deps.as_ref(), &denoms, update_period)?;
+
+        // The query is only tracked once its id is assigned in the reply of the registration,
+        // and is kept until it is removed
+        let balances_query = BalancesQuery {
+            query_id: None,
+            denoms: denoms.clone(),
+            update_period,
+        };
+
+        Ok(Response::new()
+            .add_submessage(
+                SubMsg::reply_on_success(register_msg, REGISTER_BALANCES_QUERY_REPLY_ID)
+                    .with_payload(to_json_binary(&balances_query)?),
+            )
+            .add_attribute("method", "register_balances_query")
+            .add_attribute("denoms", denoms.join(",")))
+    }
+
+    pub fn remove_query(
+        deps: DepsMut<NeutronQuery>,
+        info: MessageInfo,
+        query_id: u64,
+    ) -> Result<Response<NeutronMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        if !ACTIVE_QUERIES.has(deps.storage, query_id) {
+            return Err(ContractError::UnknownQuery(query_id));
+        }
+        ACTIVE_QUERIES.remove(deps.storage, query_id);
+
+        // Balances can no longer be refreshed once the balances query is removed
+        if BALANCES_QUERY
+            .may_load(deps.storage)?
+            .is_some_and(|query| query.query_id == Some(query_id))
+        {
+            BALANCES_QUERY.remove(deps.storage);
+        }
+
+        // The interchainqueries module refunds the deposit of the query to the account
+        Ok(Response::new()
+            .add_message(NeutronMsg::remove_interchain_query(query_id))
+            .add_attribute("method", "remove_query")
+            .add_attribute("query_id", query_id.to_string()))
+    }
+
+    pub fn refresh_remote_balances(
+        mut deps: DepsMut<NeutronQuery>,
+    ) -> Result<Response<NeutronMsg>, ContractError> {
+        let active_queries = ACTIVE_QUERIES
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, query)| query))
+            .collect::<StdResult<Vec<_>>>()?;
+        if active_queries.is_empty() {
+            return Err(ContractError::NoBalancesQuery);
+        }
+
+        // Queries are applied in registration order, so the latest registered one wins for a denom queried twice
+        let mut balances = vec![];
+        for balances_query in active_queries {
+            balances.extend(update_remote_balances_from_query(
+                deps.branch(),
+                balances_query,
+            )?);
+        }
+
+        Ok(Response::new()
+            .add_attribute("method", "refresh_remote_balances")
+            .add_attribute(
+                "balances",
+                balances
+                    .iter()
+                    .map(|coin| coin.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ))
+    }
+
+    /// Stores the latest result of a balances interchain query as the last known balances of the ICA
+    pub fn update_remote_balances_from_query(
+        deps: DepsMut<NeutronQuery>,
+        balances_query: BalancesQuery,
+    ) -> Result<Vec<Coin>, ContractError> {
+        let query_id = balances_query
+            .query_id
+            .ok_or(ContractError::NoBalancesQuery)?;
+
+        let query_result = get_raw_interchain_query_result(deps.as_ref(), query_id)
+            .map_err(|e| ContractError::InvalidBalancesQueryResult(e.to_string()))?
+            .result;
+
+        // There is one result per registered denom, in the same order
+        if query_result.kv_results.len() != balances_query.denoms.len() {
+            return Err(ContractError::InvalidBalancesQueryResult(format!(
+                "expected {} results, got {}",
+                balances_query.denoms.len(),
+                query_result.kv_results.len()
+            )));
+        }
+
+        // The denom of every balance is decoded from its bank store key, and an empty value means no balance
+        let decoded: Balances = KVReconstruct::reconstruct(&query_result.kv_results)
+            .map_err(|e| ContractError::InvalidBalancesQueryResult(e.to_string()))?;
+
+        let balances = balances_query
+            .denoms
+            .into_iter()
+            .map(|denom| {
+                decoded
+                    .coins
+                    .iter()
+                    .find(|coin| coin.denom == denom)
+                    .cloned()
+                    .ok_or_else(|| {
+                        ContractError::InvalidBalancesQueryResult(format!(
+                            "no {denom} balance in the result"
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>, ContractError>>()?;
+
+        balances.iter().try_for_each(|coin| {
+            REMOTE_BALANCES.save(deps.storage, coin.denom.clone(), &coin.amount)
+        })?;
+
+        Ok(balances)
     }
 
     pub fn update_ownership(
@@ -281,23 +584,162 @@ pub fn query(deps: Deps<NeutronQuery>, _env: Env, msg: QueryMsg) -> StdResult<Bi
             let state = ICA_STATE.load(deps.storage)?;
             to_json_binary(&state)
         }
+        QueryMsg::IcaInformation {} => {
+            let ica_info = LAST_ICA_INFO.may_load(deps.storage)?;
+            to_json_binary(&ica_info)
+        }
         QueryMsg::RemoteDomainInfo {} => {
             let remote_domain_info = REMOTE_DOMAIN_INFO.load(deps.storage)?;
             to_json_binary(&remote_domain_info)
         }
+        QueryMsg::RemoteBalance { denom } => {
+            // Balances can only be known once the ICA exists on the remote domain
+            if !matches!(ICA_STATE.load(deps.storage)?, IcaState::Created(_)) {
+                return Err(StdError::generic_err("ICA not created"));
+            }
+            let amount = REMOTE_BALANCES
+                .may_load(deps.storage, denom.clone())?
+                .ok_or_else(|| {
+                    StdError::generic_err(format!("No known remote balance for denom {denom}"))
+                })?;
+            to_json_binary(&Coin { denom, amount })
+        }
+        QueryMsg::BalancesQuery {} => {
+            let balances_query = BALANCES_QUERY.may_load(deps.storage)?;
+            to_json_binary(&balances_query)
+        }
+        QueryMsg::ActiveQueries {} => {
+            let active_queries = ACTIVE_QUERIES
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(_, query)| query))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&active_queries)
+        }
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> StdResult<Response> {
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+    match msg.id {
+        REGISTER_BALANCES_QUERY_REPLY_ID => {
+            let submsg_response = msg.result.into_result().map_err(StdError::generic_err)?;
+
+            #[allow(deprecated)]
+            let data = submsg_response
+                .data
+                .ok_or_else(|| StdError::generic_err("No data in reply"))?;
+            let registration_response: MsgRegisterInterchainQueryResponse =
+                serde_json::from_slice(data.as_slice())
+                    .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+            let mut balances_query: BalancesQuery = from_json(&msg.payload)?;
+            balances_query.query_id = Some(registration_response.id);
+            ACTIVE_QUERIES.save(deps.storage, registration_response.id, &balances_query)?;
+            // The latest registered query is the one reported by the BalancesQuery query
+            BALANCES_QUERY.save(deps.storage, &balances_query)?;
+
+            Ok(Response::new()
+                .add_attribute("method", "register_balances_query_reply")
+                .add_attribute("query_id", registration_response.id.to_string()))
+        }
+        SUBMIT_TX_CALLBACK_REPLY_ID => {
+            let submsg_response = msg.result.into_result().map_err(StdError::generic_err)?;
+
+            #[allow(deprecated)]
+            let data = submsg_response
+                .data
+                .ok_or_else(|| StdError::generic_err("No data in reply"))?;
+            let submit_tx_response: MsgSubmitTxResponse =
+                serde_json::from_slice(data.as_slice())
+                    .map_err(|e| StdError::generic_err(e.to_string()))?;
+            let sender: Addr = from_json(&msg.payload)?;
+
+            ICA_TX_CALLBACKS.save(
+                deps.storage,
+                (
+                    submit_tx_response.channel.clone(),
+                    submit_tx_response.sequence_id,
+                ),
+                &sender,
+            )?;
+
+            // The packet is returned to the sender, so that it can match the callback with the tx it submitted
+            Ok(Response::new()
+                .set_data(to_json_binary(&IcaTxSubmission {
+                    channel_id: submit_tx_response.channel.clone(),
+                    sequence: submit_tx_response.sequence_id,
+                })?)
+                .add_attribute("method", "submit_tx_callback_reply")
+                .add_attribute("channel_id", submit_tx_response.channel)
+                .add_attribute("sequence", submit_tx_response.sequence_id.to_string())
+                .add_attribute("callback", sender))
+        }
+        // A failing callback must not revert the acknowledgement, so the error is only reported
+        ICA_TX_CALLBACK_REPLY_ID => {
+            let error = msg.result.into_result().err().unwrap_or_default();
+
+            Ok(Response::new()
+                .add_attribute("method", "ica_tx_callback_failed")
+                .add_attribute("error", error))
+        }
+        _ => Err(StdError::generic_err("Unknown reply id")),
+    }
+}
+
+/// Builds the callback to the library that submitted the acknowledged (or timed out) packet, if it asked for one.
+/// Callbacks are only sent to libraries that are still approved.
+fn ica_tx_callback(
+    storage: &mut dyn Storage,
+    request: &RequestPacket,
+    result: IcaTxResult,
+) -> StdResult<Option<SubMsg>> {
+    let (Some(channel_id), Some(sequence)) = (request.source_channel.clone(), request.sequence)
+    else {
+        return Ok(None);
+    };
+
+    let key = (channel_id.clone(), sequence);
+    let Some(library) = ICA_TX_CALLBACKS.may_load(storage, key.clone())? else {
+        return Ok(None);
+    };
+    ICA_TX_CALLBACKS.remove(storage, key);
+
+    if !APPROVED_LIBRARIES.has(storage, library.clone()) {
+        return Ok(None);
+    }
+
+    let callback_msg = WasmMsg::Execute {
+        contract_addr: library.to_string(),
+        msg: to_json_binary(&IcaCallbackMsg::IcaTxCallback {
+            channel_id,
+            sequence,
+            result,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Some(SubMsg::reply_on_error(
+        callback_msg,
+        ICA_TX_CALLBACK_REPLY_ID,
+    )))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut<NeutronQuery>, _env: Env, msg: SudoMsg) -> StdResult<Response> {
     match msg {
         SudoMsg::Response { request, data } => {
             // If the channel closed, we need to update the state to Closed to allow recreation
             if request.sequence.is_none() || request.source_channel.is_none() {
                 ICA_STATE.save(deps.storage, &IcaState::Closed)?;
             }
+            let callback = ica_tx_callback(
+                deps.storage,
+                &request,
+                IcaTxResult::Success { data: data.clone() },
+            )?;
 
             Ok(Response::new()
+                .add_submessages(callback)
                 .add_attribute("method", "sudo_response")
                 .add_attribute("data", data.to_string()))
         }
@@ -307,17 +749,28 @@ pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> StdResult<Response> {
             if request.sequence.is_none() || request.source_channel.is_none() {
                 ICA_STATE.save(deps.storage, &IcaState::Closed)?;
             }
+            let callback = ica_tx_callback(
+                deps.storage,
+                &request,
+                IcaTxResult::Error {
+                    details: details.clone(),
+                },
+            )?;
 
             Ok(Response::new()
+                .add_submessages(callback)
                 .add_attribute("method", "sudo_error")
                 .add_attribute("details", details))
         }
 
         // If it times out means the channel is closed
-        SudoMsg::Timeout { .. } => {
+        SudoMsg::Timeout { request } => {
             ICA_STATE.save(deps.storage, &IcaState::Closed)?;
+            let callback = ica_tx_callback(deps.storage, &request, IcaTxResult::Timeout {})?;
 
-            Ok(Response::new().add_attribute("method", "sudo_timeout"))
+            Ok(Response::new()
+                .add_submessages(callback)
+                .add_attribute("method", "sudo_timeout"))
         }
 
         // For handling successful registering of ICA
@@ -331,18 +784,57 @@ pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> StdResult<Response> {
                 serde_json::from_str(counterparty_version.as_str())
                     .map_err(|_| StdError::generic_err("Failed to parse counterparty version"))?;
 
-            ICA_STATE.save(
-                deps.storage,
-                &IcaState::Created(IcaInformation {
-                    address: parsed_version.address,
-                    port_id,
-                    controller_connection_id: parsed_version.controller_connection_id,
-                }),
-            )?;
+            // The ICA must be opened over the configured connection, not another connection to the remote chain
+            let remote_domain_info = REMOTE_DOMAIN_INFO.load(deps.storage)?;
+            if parsed_version.controller_connection_id != remote_domain_info.connection_id {
+                return Err(StdError::generic_err(format!(
+                    "ICA opened over connection {}, expected {}",
+                    parsed_version.controller_connection_id, remote_domain_info.connection_id
+                )));
+            }
+
+            // Reject malformed host addresses, which libraries would otherwise use to build ICA packets
+            let address = parsed_version.address.parse::<RemoteAddress>()?.to_string();
+
+            // When the controller port is reused after reopening the channel, the host must return the same ICA,
+            // as the funds and the libraries are tied to its remote address
+            if let Some(last_ica_info) = LAST_ICA_INFO.may_load(deps.storage)? {
+                if last_ica_info.port_id == port_id && last_ica_info.address != address {
+                    return Err(StdError::generic_err(format!(
+                        "ICA reopened with address {address}, expected {}",
+                        last_ica_info.address
+                    )));
+                }
+            }
+
+            let ica_info = IcaInformation {
+                address,
+                port_id,
+                controller_connection_id: parsed_version.controller_connection_id,
+            };
+            LAST_ICA_INFO.save(deps.storage, &ica_info)?;
+            ICA_STATE.save(deps.storage, &IcaState::Created(ica_info))?;
 
             Ok(Response::new().add_attribute("method", "sudo_open_ack"))
         }
 
+        // Keep the last known balances in sync whenever a new balances query result is delivered
+        SudoMsg::KVQueryResult { query_id } => {
+            let balances_query = match ACTIVE_QUERIES.may_load(deps.storage, query_id)? {
+                Some(balances_query) => Some(balances_query),
+                // Balances queries registered before the active queries were tracked
+                None => BALANCES_QUERY
+                    .may_load(deps.storage)?
+                    .filter(|balances_query| balances_query.query_id == Some(query_id)),
+            };
+            if let Some(balances_query) = balances_query {
+                execute::update_remote_balances_from_query(deps, balances_query)
+                    .map_err(|e| StdError::generic_err(e.to_string()))?;
+            }
+
+            Ok(Response::new().add_attribute("method", "sudo_kv_query_result"))
+        }
+
         _ => Ok(Response::default()),
     }
 }
@@ -16,11 +16,26 @@ pub enum ContractError {
     #[error("Cannot register ICA in {} state", current_state)]
     InvalidIcaState { current_state: String },
 
+    #[error("No previously registered ICA to reopen")]
+    NoIcaToReopen,
+
     #[error("Not enough balance to pay the ICA registration fee")]
     NotEnoughBalanceForIcaRegistration,
 
     #[error("Cannot cover IBC fee")]
     CannotCoverIbcFee,
+
+    #[error("ICA not created")]
+    IcaNotCreated,
+
+    #[error("No balances interchain query registered")]
+    NoBalancesQuery,
+
+    #[error("Invalid balances query result: {0}")]
+    InvalidBalancesQueryResult(String),
+
+    #[error("No active interchain query with id {0}")]
+    UnknownQuery(u64),
 }
 
 #[derive(Error, Debug, PartialEq)]
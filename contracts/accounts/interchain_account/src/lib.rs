@@ -1,3 +1,6 @@
 pub mod contract;
 pub mod error;
 pub mod state;
+
+#[cfg(test)]
+mod tests;
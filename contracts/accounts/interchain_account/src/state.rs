@@ -1,6 +1,6 @@
-use cosmwasm_std::{Addr, Empty};
+use cosmwasm_std::{Addr, Empty, Uint128};
 use cw_storage_plus::{Item, Map};
-use valence_account_utils::ica::{IcaState, RemoteDomainInfo};
+use valence_account_utils::ica::{BalancesQuery, IcaInformation, IcaState, RemoteDomainInfo};
 
 // Approved libraries that can execute actions on behalf of the account
 pub const APPROVED_LIBRARIES: Map<Addr, Empty> = Map::new("libraries");
@@ -8,3 +8,13 @@ pub const APPROVED_LIBRARIES: Map<Addr, Empty> = Map::new("libraries");
 pub const REMOTE_DOMAIN_INFO: Item<RemoteDomainInfo> = Item::new("remote_domain_info");
 // State of the ICA
 pub const ICA_STATE: Item<IcaState> = Item::new("ica_state");
+// Last known information of the ICA, kept after its channel closes so it can be reopened
+pub const LAST_ICA_INFO: Item<IcaInformation> = Item::new("last_ica_info");
+// Last known balances of the ICA on the remote domain, by denom
+pub const REMOTE_BALANCES: Map<String, Uint128> = Map::new("remote_balances");
+// Interchain query registered for the balances of the ICA
+pub const BALANCES_QUERY: Item<BalancesQuery> = Item::new("balances_query");
+// Interchain queries registered by the account that were not removed yet, by query id
+pub const ACTIVE_QUERIES: Map<u64, BalancesQuery> = Map::new("active_queries");
+// Libraries to call back with the outcome of the ICA txs they submitted, by source channel and sequence
+pub const ICA_TX_CALLBACKS: Map<(String, u64), Addr> = Map::new("ica_tx_callbacks");
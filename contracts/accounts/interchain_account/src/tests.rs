@@ -0,0 +1,900 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{
+    coin, from_json,
+    testing::{message_info, mock_env, MockApi, MockQuerier, MockStorage},
+    to_json_binary, Addr, Binary, Coin, ContractResult, CosmosMsg, Env, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, Reply, ReplyOn, Response, StdResult, SubMsgResponse, SubMsgResult,
+    SystemResult, Uint128, Uint64, WasmMsg,
+};
+use neutron_sdk::{
+    bindings::{
+        msg::NeutronMsg,
+        query::{NeutronQuery, QueryRegisteredQueryResultResponse},
+        types::{InterchainQueryResult, StorageValue},
+    },
+    interchain_queries::{
+        helpers::decode_and_convert, v047::helpers::create_account_denom_balance_key,
+    },
+    sudo::msg::SudoMsg,
+};
+use valence_account_utils::ica::{
+    BalancesQuery, ExecuteMsg, IcaCallbackMsg, IcaInformation, IcaState, IcaTxResult,
+    IcaTxSubmission, InstantiateMsg, QueryMsg, RemoteDomainInfo,
+};
+use valence_ibc_utils::neutron::{Params, QueryParamsResponse};
+
+use crate::{
+    contract::{
+        execute, instantiate, query, reply, sudo, ICA_TX_CALLBACK_REPLY_ID, INTERCHAIN_ACCOUNT_ID,
+        NTRN_DENOM, REGISTER_BALANCES_QUERY_REPLY_ID, SUBMIT_TX_CALLBACK_REPLY_ID,
+    },
+    error::ContractError,
+};
+
+const REMOTE_ADDRESS: &str = "cosmos1wz25uvw4glua95ga6wzjsjqmhulwk2teeqlh8jqd4a6vwhsv4spqz075su";
+
+// Mock querier that answers the interchaintxs params query with the ICA registration fee
+// and the interchain queries with a mocked result
+struct IcaMockQuerier {
+    base: MockQuerier<NeutronQuery>,
+    // Result returned for any registered interchain query
+    icq_result: Option<InterchainQueryResult>,
+}
+
+impl Querier for IcaMockQuerier {
+    #[allow(deprecated)]
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<NeutronQuery> = from_json(bin_request).unwrap();
+        match request {
+            QueryRequest::Stargate { path, .. }
+                if path == "/neutron.interchaintxs.v1.Query/Params" =>
+            {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&QueryParamsResponse {
+                        params: Params {
+                            msg_submit_tx_max_messages: Uint64::new(16),
+                            register_fee: vec![coin(1_000_000, NTRN_DENOM)],
+                        },
+                    })
+                    .unwrap(),
+                ))
+            }
+            QueryRequest::Custom(NeutronQuery::InterchainQueryResult { .. })
+                if self.icq_result.is_some() =>
+            {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&QueryRegisteredQueryResultResponse {
+                        result: self.icq_result.clone().unwrap(),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => self.base.raw_query(bin_request),
+        }
+    }
+}
+
+type IcaMockDeps = OwnedDeps<MockStorage, MockApi, IcaMockQuerier, NeutronQuery>;
+
+fn setup() -> (IcaMockDeps, Env) {
+    setup_with_connection("connection-0")
+}
+
+fn setup_with_connection(connection_id: &str) -> (IcaMockDeps, Env) {
+    let env = mock_env();
+    let mut deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: IcaMockQuerier {
+            base: MockQuerier::new(&[(
+                env.contract.address.as_str(),
+                &[coin(10_000_000, NTRN_DENOM)],
+            )]),
+            icq_result: None,
+        },
+        custom_query_type: PhantomData,
+    };
+
+    let admin = deps.api.addr_make("admin");
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        message_info(&admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            approved_libraries: vec![],
+            remote_domain_information: RemoteDomainInfo {
+                connection_id: connection_id.to_string(),
+                ica_timeout_seconds: Uint64::new(100),
+            },
+        },
+    )
+    .unwrap();
+
+    (deps, env)
+}
+
+fn execute_as_anyone(
+    deps: &mut IcaMockDeps,
+    env: &Env,
+    msg: ExecuteMsg,
+) -> Result<Response<NeutronMsg>, ContractError> {
+    let sender = deps.api.addr_make("anyone");
+    execute(deps.as_mut(), env.clone(), message_info(&sender, &[]), msg)
+}
+
+fn sudo_open_ack(deps: &mut IcaMockDeps, env: &Env, port_id: &str) {
+    sudo_open_ack_with_address(deps, env, port_id, REMOTE_ADDRESS).unwrap();
+}
+
+fn sudo_open_ack_with_address(
+    deps: &mut IcaMockDeps,
+    env: &Env,
+    port_id: &str,
+    address: &str,
+) -> StdResult<Response> {
+    sudo_open_ack_on_connection(deps, env, port_id, address, "connection-0")
+}
+
+fn sudo_open_ack_on_connection(
+    deps: &mut IcaMockDeps,
+    env: &Env,
+    port_id: &str,
+    address: &str,
+    controller_connection_id: &str,
+) -> StdResult<Response> {
+    let counterparty_version = serde_json::json!({
+        "version": "ics27-1",
+        "controller_connection_id": controller_connection_id,
+        "host_connection_id": "connection-1",
+        "address": address,
+        "encoding": "proto3",
+        "tx_type": "sdk_multi_msg",
+    });
+    let msg: SudoMsg = serde_json::from_value(serde_json::json!({
+        "open_ack": {
+            "port_id": port_id,
+            "channel_id": "channel-0",
+            "counterparty_channel_id": "channel-1",
+            "counterparty_version": counterparty_version.to_string(),
+        }
+    }))
+    .unwrap();
+    sudo(deps.as_mut(), env.clone(), msg)
+}
+
+fn sudo_timeout(deps: &mut IcaMockDeps, env: &Env) {
+    let msg: SudoMsg =
+        serde_json::from_value(serde_json::json!({ "timeout": { "request": {} } })).unwrap();
+    sudo(deps.as_mut(), env.clone(), msg).unwrap();
+}
+
+fn execute_as_admin(
+    deps: &mut IcaMockDeps,
+    env: &Env,
+    msg: ExecuteMsg,
+) -> Result<Response<NeutronMsg>, ContractError> {
+    let admin = deps.api.addr_make("admin");
+    execute(deps.as_mut(), env.clone(), message_info(&admin, &[]), msg)
+}
+
+// Replies to the registration of a balances query, with the payload of its submessage
+fn reply_register_balances_query(
+    deps: &mut IcaMockDeps,
+    env: &Env,
+    res: &Response<NeutronMsg>,
+    query_id: u64,
+) {
+    #[allow(deprecated)]
+    let result = SubMsgResult::Ok(SubMsgResponse {
+        events: vec![],
+        data: Some(to_json_binary(&serde_json::json!({ "id": query_id })).unwrap()),
+        msg_responses: vec![],
+    });
+    reply(
+        deps.as_mut().into_empty(),
+        env.clone(),
+        Reply {
+            id: REGISTER_BALANCES_QUERY_REPLY_ID,
+            payload: res.messages[0].payload.clone(),
+            gas_used: 0,
+            result,
+        },
+    )
+    .unwrap();
+}
+
+fn query_active_queries(deps: &IcaMockDeps, env: &Env) -> Vec<BalancesQuery> {
+    from_json(query(deps.as_ref(), env.clone(), QueryMsg::ActiveQueries {}).unwrap()).unwrap()
+}
+
+// Replies to the submission of an ICA tx with a callback, as if it was sent as packet `sequence` on channel-0
+fn reply_submit_tx_callback(
+    deps: &mut IcaMockDeps,
+    env: &Env,
+    sender: &Addr,
+    sequence: u64,
+) -> Response {
+    #[allow(deprecated)]
+    let result = SubMsgResult::Ok(SubMsgResponse {
+        events: vec![],
+        data: Some(
+            to_json_binary(&serde_json::json!({ "sequence_id": sequence, "channel": "channel-0" }))
+                .unwrap(),
+        ),
+        msg_responses: vec![],
+    });
+    reply(
+        deps.as_mut().into_empty(),
+        env.clone(),
+        Reply {
+            id: SUBMIT_TX_CALLBACK_REPLY_ID,
+            payload: to_json_binary(sender).unwrap(),
+            gas_used: 0,
+            result,
+        },
+    )
+    .unwrap()
+}
+
+fn packet_sudo_msg(kind: &str, sequence: u64) -> SudoMsg {
+    let request = serde_json::json!({ "sequence": sequence, "source_channel": "channel-0" });
+    let msg = match kind {
+        "response" => serde_json::json!({ "response": { "request": request, "data": "" } }),
+        "error" => {
+            serde_json::json!({ "error": { "request": request, "details": "insufficient funds" } })
+        }
+        _ => serde_json::json!({ "timeout": { "request": request } }),
+    };
+    serde_json::from_value(msg).unwrap()
+}
+
+// Bank store entries of the balances of the ICA, whose values are the amount strings, or empty if there is no balance
+fn mock_balances_query_result(balances: &[(&str, &str)]) -> InterchainQueryResult {
+    InterchainQueryResult {
+        kv_results: balances
+            .iter()
+            .map(|(denom, amount)| StorageValue {
+                storage_prefix: "bank".to_string(),
+                key: Binary::new(
+                    create_account_denom_balance_key(
+                        decode_and_convert(REMOTE_ADDRESS).unwrap(),
+                        denom,
+                    )
+                    .unwrap(),
+                ),
+                value: Binary::from(amount.as_bytes()),
+            })
+            .collect(),
+        height: 100,
+        revision: 1,
+    }
+}
+
+fn query_remote_balance(deps: &IcaMockDeps, env: &Env, denom: &str) -> Uint128 {
+    let balance: Coin = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::RemoteBalance {
+                denom: denom.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    balance.amount
+}
+
+fn query_ica_state(deps: &IcaMockDeps, env: &Env) -> IcaState {
+    from_json(query(deps.as_ref(), env.clone(), QueryMsg::IcaState {}).unwrap()).unwrap()
+}
+
+fn query_ica_information(deps: &IcaMockDeps, env: &Env) -> Option<IcaInformation> {
+    from_json(query(deps.as_ref(), env.clone(), QueryMsg::IcaInformation {}).unwrap()).unwrap()
+}
+
+#[test]
+fn reopen_channel_after_close_retains_port() {
+    let (mut deps, env) = setup();
+    let port_id = format!(
+        "icacontroller-{}.{INTERCHAIN_ACCOUNT_ID}",
+        env.contract.address
+    );
+
+    // Register the ICA and acknowledge the channel opening
+    execute_as_anyone(&mut deps, &env, ExecuteMsg::RegisterIca {}).unwrap();
+    sudo_open_ack(&mut deps, &env, &port_id);
+
+    let ica_info = IcaInformation {
+        address: REMOTE_ADDRESS.to_string(),
+        port_id: port_id.clone(),
+        controller_connection_id: "connection-0".to_string(),
+    };
+    assert_eq!(
+        query_ica_state(&deps, &env),
+        IcaState::Created(ica_info.clone())
+    );
+
+    // Simulate the channel closing after a packet timeout
+    sudo_timeout(&mut deps, &env);
+    assert_eq!(query_ica_state(&deps, &env), IcaState::Closed);
+
+    // The last known ICA information is retained while the channel is closed
+    assert_eq!(query_ica_information(&deps, &env), Some(ica_info.clone()));
+
+    // Reopen the channel on the existing port
+    let res = execute_as_anyone(&mut deps, &env, ExecuteMsg::ReopenChannel {}).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "port_id" && attr.value == port_id));
+    assert_eq!(query_ica_state(&deps, &env), IcaState::InProgress);
+
+    // Once the channel is acknowledged again, the ICA keeps its port and remote address
+    sudo_open_ack(&mut deps, &env, &port_id);
+    assert_eq!(query_ica_state(&deps, &env), IcaState::Created(ica_info));
+}
+
+#[test]
+fn reopen_channel_rejects_different_remote_address() {
+    let (mut deps, env) = setup();
+    let port_id = format!(
+        "icacontroller-{}.{INTERCHAIN_ACCOUNT_ID}",
+        env.contract.address
+    );
+
+    execute_as_anyone(&mut deps, &env, ExecuteMsg::RegisterIca {}).unwrap();
+    sudo_open_ack(&mut deps, &env, &port_id);
+    sudo_timeout(&mut deps, &env);
+    execute_as_anyone(&mut deps, &env, ExecuteMsg::ReopenChannel {}).unwrap();
+
+    // The host assigning another ICA on the reused port is rejected, the funds being on the previous one
+    let other_address = MockApi::default()
+        .with_prefix("cosmos")
+        .addr_make("other_ica")
+        .to_string();
+    let err = sudo_open_ack_with_address(&mut deps, &env, &port_id, &other_address).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Generic error: ICA reopened with address {other_address}, expected {REMOTE_ADDRESS}"
+        )
+    );
+    assert_eq!(query_ica_state(&deps, &env), IcaState::InProgress);
+
+    // The address returned by the host is kept when it is the previous one
+    sudo_open_ack_with_address(&mut deps, &env, &port_id, REMOTE_ADDRESS).unwrap();
+    assert_eq!(
+        query_ica_state(&deps, &env),
+        IcaState::Created(IcaInformation {
+            address: REMOTE_ADDRESS.to_string(),
+            port_id,
+            controller_connection_id: "connection-0".to_string(),
+        })
+    );
+}
+
+// Connection id carried by the registration message of a response
+#[allow(deprecated)]
+fn registered_connection_id(res: &Response<NeutronMsg>) -> String {
+    let CosmosMsg::Stargate { type_url, value } = &res.messages[0].msg else {
+        panic!("expected a stargate message");
+    };
+    assert_eq!(
+        type_url,
+        "/neutron.interchaintxs.v1.MsgRegisterInterchainAccount"
+    );
+    let attribute = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "connection_id")
+        .unwrap();
+    // The connection id is encoded as is in the protobuf message
+    assert!(value
+        .windows(attribute.value.len())
+        .any(|window| window == attribute.value.as_bytes()));
+    attribute.value.clone()
+}
+
+#[test]
+fn register_ica_over_configured_connection_is_reused_on_reopen() {
+    let (mut deps, env) = setup_with_connection("connection-3");
+    let port_id = format!(
+        "icacontroller-{}.{INTERCHAIN_ACCOUNT_ID}",
+        env.contract.address
+    );
+
+    // The configured connection is persisted
+    let remote_domain_info: RemoteDomainInfo =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::RemoteDomainInfo {}).unwrap())
+            .unwrap();
+    assert_eq!(remote_domain_info.connection_id, "connection-3");
+
+    // The ICA is registered over the configured connection
+    let res = execute_as_anyone(&mut deps, &env, ExecuteMsg::RegisterIca {}).unwrap();
+    assert_eq!(registered_connection_id(&res), "connection-3");
+
+    // The counterparty acknowledges the channel over the same connection
+    sudo_open_ack_on_connection(&mut deps, &env, &port_id, REMOTE_ADDRESS, "connection-3").unwrap();
+    let ica_info = IcaInformation {
+        address: REMOTE_ADDRESS.to_string(),
+        port_id: port_id.clone(),
+        controller_connection_id: "connection-3".to_string(),
+    };
+    assert_eq!(
+        query_ica_state(&deps, &env),
+        IcaState::Created(ica_info.clone())
+    );
+
+    // Once the channel is closed, it is reopened over the connection the ICA was registered on
+    sudo_timeout(&mut deps, &env);
+    let res = execute_as_anyone(&mut deps, &env, ExecuteMsg::ReopenChannel {}).unwrap();
+    assert_eq!(registered_connection_id(&res), "connection-3");
+
+    sudo_open_ack_on_connection(&mut deps, &env, &port_id, REMOTE_ADDRESS, "connection-3").unwrap();
+    assert_eq!(query_ica_state(&deps, &env), IcaState::Created(ica_info));
+}
+
+#[test]
+fn open_ack_rejects_unexpected_connection() {
+    let (mut deps, env) = setup_with_connection("connection-3");
+    let port_id = format!(
+        "icacontroller-{}.{INTERCHAIN_ACCOUNT_ID}",
+        env.contract.address
+    );
+
+    execute_as_anyone(&mut deps, &env, ExecuteMsg::RegisterIca {}).unwrap();
+
+    // A channel opened over another connection to the remote chain is rejected
+    let err =
+        sudo_open_ack_on_connection(&mut deps, &env, &port_id, REMOTE_ADDRESS, "connection-0")
+            .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: ICA opened over connection connection-0, expected connection-3"
+    );
+    assert_eq!(query_ica_state(&deps, &env), IcaState::InProgress);
+}
+
+#[test]
+fn remote_domain_info_rejects_invalid_connection_id() {
+    for connection_id in ["", "connection-", "channel-0", "connection-0a"] {
+        let remote_domain_info = RemoteDomainInfo {
+            connection_id: connection_id.to_string(),
+            ica_timeout_seconds: Uint64::new(100),
+        };
+        assert!(
+            remote_domain_info.validate().is_err(),
+            "{connection_id} should be rejected"
+        );
+    }
+
+    let remote_domain_info = RemoteDomainInfo {
+        connection_id: "connection-12".to_string(),
+        ica_timeout_seconds: Uint64::new(100),
+    };
+    remote_domain_info.validate().unwrap();
+}
+
+#[test]
+fn open_ack_rejects_malformed_remote_address() {
+    let (mut deps, env) = setup();
+    let port_id = format!(
+        "icacontroller-{}.{INTERCHAIN_ACCOUNT_ID}",
+        env.contract.address
+    );
+
+    execute_as_anyone(&mut deps, &env, ExecuteMsg::RegisterIca {}).unwrap();
+
+    // Corrupt the checksum of the remote address
+    let corrupted = format!("{}q", &REMOTE_ADDRESS[..REMOTE_ADDRESS.len() - 1]);
+    sudo_open_ack_with_address(&mut deps, &env, &port_id, &corrupted).unwrap_err();
+    assert_eq!(query_ica_state(&deps, &env), IcaState::InProgress);
+
+    // Any bech32 prefix is accepted
+    let remote_address = MockApi::default()
+        .with_prefix("mychain")
+        .addr_make("remote_ica")
+        .to_string();
+    sudo_open_ack_with_address(&mut deps, &env, &port_id, &remote_address).unwrap();
+    assert_eq!(
+        query_ica_state(&deps, &env),
+        IcaState::Created(IcaInformation {
+            address: remote_address,
+            port_id,
+            controller_connection_id: "connection-0".to_string(),
+        })
+    );
+}
+
+#[test]
+fn reopen_channel_requires_closed_channel() {
+    let (mut deps, env) = setup();
+
+    // Cannot reopen a channel that was never created
+    let err = execute_as_anyone(&mut deps, &env, ExecuteMsg::ReopenChannel {}).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidIcaState {
+            current_state: "NotCreated".to_string()
+        }
+    );
+
+    // Cannot reopen a channel whose registration never completed
+    execute_as_anyone(&mut deps, &env, ExecuteMsg::RegisterIca {}).unwrap();
+    sudo_timeout(&mut deps, &env);
+    assert_eq!(query_ica_state(&deps, &env), IcaState::Closed);
+
+    let err = execute_as_anyone(&mut deps, &env, ExecuteMsg::ReopenChannel {}).unwrap_err();
+    assert_eq!(err, ContractError::NoIcaToReopen);
+}
+
+#[test]
+fn register_balances_query_requires_created_ica() {
+    let (mut deps, env) = setup();
+
+    let err = execute_as_admin(
+        &mut deps,
+        &env,
+        ExecuteMsg::RegisterBalancesQuery {
+            denoms: vec!["uusdc".to_string()],
+            period: 10,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IcaNotCreated);
+
+    // The last known balances can't be queried either
+    let err = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::RemoteBalance {
+            denom: "uusdc".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("ICA not created"));
+}
+
+#[test]
+fn refresh_remote_balances_from_balances_query() {
+    let (mut deps, env) = setup();
+    let port_id = format!(
+        "icacontroller-{}.{INTERCHAIN_ACCOUNT_ID}",
+        env.contract.address
+    );
+    execute_as_anyone(&mut deps, &env, ExecuteMsg::RegisterIca {}).unwrap();
+    sudo_open_ack(&mut deps, &env, &port_id);
+
+    // Only the admin can register the balances query
+    let msg = ExecuteMsg::RegisterBalancesQuery {
+        denoms: vec!["uusdc".to_string(), "uatom".to_string()],
+        period: 10,
+    };
+    assert!(execute_as_anyone(&mut deps, &env, msg.clone()).is_err());
+
+    let res = execute_as_admin(&mut deps, &env, msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, REGISTER_BALANCES_QUERY_REPLY_ID);
+
+    // Balances can't be refreshed until the query id is known
+    let err = execute_as_anyone(&mut deps, &env, ExecuteMsg::RefreshRemoteBalances {}).unwrap_err();
+    assert_eq!(err, ContractError::NoBalancesQuery);
+
+    reply_register_balances_query(&mut deps, &env, &res, 7);
+    let balances_query: Option<BalancesQuery> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::BalancesQuery {}).unwrap()).unwrap();
+    assert_eq!(
+        balances_query,
+        Some(BalancesQuery {
+            query_id: Some(7),
+            denoms: vec!["uusdc".to_string(), "uatom".to_string()],
+            update_period: 10,
+        })
+    );
+
+    // An empty value means that the ICA holds none of that denom
+    deps.querier.icq_result = Some(mock_balances_query_result(&[
+        ("uusdc", "1000"),
+        ("uatom", ""),
+    ]));
+    execute_as_anyone(&mut deps, &env, ExecuteMsg::RefreshRemoteBalances {}).unwrap();
+    assert_eq!(
+        query_remote_balance(&deps, &env, "uusdc"),
+        Uint128::new(1000)
+    );
+    assert_eq!(query_remote_balance(&deps, &env, "uatom"), Uint128::zero());
+
+    // New results delivered for the balances query update the last known balances
+    deps.querier.icq_result = Some(mock_balances_query_result(&[
+        ("uatom", "30"),
+        ("uusdc", "250"),
+    ]));
+    sudo(
+        deps.as_mut(),
+        env.clone(),
+        SudoMsg::KVQueryResult { query_id: 7 },
+    )
+    .unwrap();
+    assert_eq!(
+        query_remote_balance(&deps, &env, "uusdc"),
+        Uint128::new(250)
+    );
+    assert_eq!(query_remote_balance(&deps, &env, "uatom"), Uint128::new(30));
+
+    // Results that don't match the registered denoms, or can't be decoded, are rejected
+    for result in [
+        mock_balances_query_result(&[("uusdc", "250")]),
+        mock_balances_query_result(&[("uusdc", "250"), ("uosmo", "30")]),
+        mock_balances_query_result(&[("uusdc", "250"), ("uatom", "not an amount")]),
+    ] {
+        deps.querier.icq_result = Some(result);
+        let err =
+            execute_as_anyone(&mut deps, &env, ExecuteMsg::RefreshRemoteBalances {}).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidBalancesQueryResult(_)));
+    }
+    assert_eq!(query_remote_balance(&deps, &env, "uatom"), Uint128::new(30));
+
+    // Registering another balances query keeps the previous one until it is removed
+    let res = execute_as_admin(
+        &mut deps,
+        &env,
+        ExecuteMsg::RegisterBalancesQuery {
+            denoms: vec!["uusdc".to_string()],
+            period: 20,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    reply_register_balances_query(&mut deps, &env, &res, 8);
+    assert_eq!(
+        query_active_queries(&deps, &env)
+            .into_iter()
+            .map(|query| query.query_id)
+            .collect::<Vec<_>>(),
+        vec![Some(7), Some(8)]
+    );
+}
+
+#[test]
+fn spent_balances_are_deducted_until_refreshed() {
+    let (mut deps, env) = setup();
+    let port_id = format!(
+        "icacontroller-{}.{INTERCHAIN_ACCOUNT_ID}",
+        env.contract.address
+    );
+    execute_as_anyone(&mut deps, &env, ExecuteMsg::RegisterIca {}).unwrap();
+    sudo_open_ack(&mut deps, &env, &port_id);
+    execute_as_admin(
+        &mut deps,
+        &env,
+        ExecuteMsg::UpdateRemoteBalances {
+            balances: vec![coin(1_000, "uusdc")],
+        },
+    )
+    .unwrap();
+
+    // Only the admin and the approved libraries can deduct balances
+    let msg = ExecuteMsg::DeductRemoteBalances {
+        balances: vec![coin(400, "uusdc"), coin(50, "uatom")],
+    };
+    let err = execute_as_anyone(&mut deps, &env, msg.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized(_)));
+
+    let library = deps.api.addr_make("library");
+    execute_as_admin(
+        &mut deps,
+        &env,
+        ExecuteMsg::ApproveLibrary {
+            library: library.to_string(),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        message_info(&library, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        query_remote_balance(&deps, &env, "uusdc"),
+        Uint128::new(600)
+    );
+
+    // Unknown balances stay unknown, and known ones can't go below zero
+    assert!(query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::RemoteBalance {
+            denom: "uatom".to_string(),
+        },
+    )
+    .is_err());
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        message_info(&library, &[]),
+        ExecuteMsg::DeductRemoteBalances {
+            balances: vec![coin(1_000, "uusdc")],
+        },
+    )
+    .unwrap();
+    assert_eq!(query_remote_balance(&deps, &env, "uusdc"), Uint128::zero());
+}
+
+#[test]
+fn acknowledgements_call_back_the_submitting_library() {
+    let (mut deps, env) = setup();
+    let library = deps.api.addr_make("library");
+    execute_as_admin(
+        &mut deps,
+        &env,
+        ExecuteMsg::ApproveLibrary {
+            library: library.to_string(),
+        },
+    )
+    .unwrap();
+
+    // The submitting library gets the packet of its tx back
+    let res = reply_submit_tx_callback(&mut deps, &env, &library, 1);
+    assert_eq!(
+        res.data,
+        Some(
+            to_json_binary(&IcaTxSubmission {
+                channel_id: "channel-0".to_string(),
+                sequence: 1,
+            })
+            .unwrap()
+        )
+    );
+    reply_submit_tx_callback(&mut deps, &env, &library, 2);
+
+    // The error acknowledgement is forwarded to the library, without reverting if the callback fails
+    let res = sudo(deps.as_mut(), env.clone(), packet_sudo_msg("error", 1)).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, ICA_TX_CALLBACK_REPLY_ID);
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Error);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: library.to_string(),
+            msg: to_json_binary(&IcaCallbackMsg::IcaTxCallback {
+                channel_id: "channel-0".to_string(),
+                sequence: 1,
+                result: IcaTxResult::Error {
+                    details: "insufficient funds".to_string(),
+                },
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // Each packet is only called back once
+    let res = sudo(deps.as_mut(), env.clone(), packet_sudo_msg("error", 1)).unwrap();
+    assert!(res.messages.is_empty());
+
+    // Packets sent without a callback are not forwarded
+    let res = sudo(deps.as_mut(), env.clone(), packet_sudo_msg("response", 3)).unwrap();
+    assert!(res.messages.is_empty());
+
+    // The timeout closes the channel and is forwarded to the library
+    let res = sudo(deps.as_mut(), env.clone(), packet_sudo_msg("timeout", 2)).unwrap();
+    assert_eq!(query_ica_state(&deps, &env), IcaState::Closed);
+    assert_eq!(res.messages.len(), 1);
+    let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[0].msg else {
+        panic!("expected a wasm execute callback");
+    };
+    assert_eq!(
+        from_json::<IcaCallbackMsg>(msg).unwrap(),
+        IcaCallbackMsg::IcaTxCallback {
+            channel_id: "channel-0".to_string(),
+            sequence: 2,
+            result: IcaTxResult::Timeout {},
+        }
+    );
+
+    // Libraries that are no longer approved are not called back
+    reply_submit_tx_callback(&mut deps, &env, &library, 4);
+    execute_as_admin(
+        &mut deps,
+        &env,
+        ExecuteMsg::RemoveLibrary {
+            library: library.to_string(),
+        },
+    )
+    .unwrap();
+    let res = sudo(deps.as_mut(), env.clone(), packet_sudo_msg("response", 4)).unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn balance_queries_are_tracked_until_removed() {
+    let (mut deps, env) = setup();
+    let port_id = format!(
+        "icacontroller-{}.{INTERCHAIN_ACCOUNT_ID}",
+        env.contract.address
+    );
+    execute_as_anyone(&mut deps, &env, ExecuteMsg::RegisterIca {}).unwrap();
+    sudo_open_ack(&mut deps, &env, &port_id);
+
+    // Register two balance queries, which are active once their ids are known
+    for (denom, query_id) in [("uusdc", 3), ("uatom", 4)] {
+        let res = execute_as_admin(
+            &mut deps,
+            &env,
+            ExecuteMsg::RegisterBalancesQuery {
+                denoms: vec![denom.to_string()],
+                period: 10,
+            },
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].id, REGISTER_BALANCES_QUERY_REPLY_ID);
+        reply_register_balances_query(&mut deps, &env, &res, query_id);
+    }
+    assert_eq!(
+        query_active_queries(&deps, &env),
+        vec![
+            BalancesQuery {
+                query_id: Some(3),
+                denoms: vec!["uusdc".to_string()],
+                update_period: 10,
+            },
+            BalancesQuery {
+                query_id: Some(4),
+                denoms: vec!["uatom".to_string()],
+                update_period: 10,
+            },
+        ]
+    );
+
+    // Only the admin can remove a query
+    let err =
+        execute_as_anyone(&mut deps, &env, ExecuteMsg::RemoveQuery { query_id: 3 }).unwrap_err();
+    assert!(matches!(err, ContractError::OwnershipError(_)));
+
+    // Removing a query removes it from the interchainqueries module, which refunds its deposit
+    let res = execute_as_admin(&mut deps, &env, ExecuteMsg::RemoveQuery { query_id: 3 }).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Custom(NeutronMsg::remove_interchain_query(3))
+    );
+    assert_eq!(
+        query_active_queries(&deps, &env),
+        vec![BalancesQuery {
+            query_id: Some(4),
+            denoms: vec!["uatom".to_string()],
+            update_period: 10,
+        }]
+    );
+
+    // A removed query can't be removed twice
+    let err =
+        execute_as_admin(&mut deps, &env, ExecuteMsg::RemoveQuery { query_id: 3 }).unwrap_err();
+    assert_eq!(err, ContractError::UnknownQuery(3));
+
+    // Results of the remaining query keep the balance of its denom up to date
+    deps.querier.icq_result = Some(mock_balances_query_result(&[("uatom", "42")]));
+    sudo(
+        deps.as_mut(),
+        env.clone(),
+        SudoMsg::KVQueryResult { query_id: 4 },
+    )
+    .unwrap();
+    assert_eq!(query_remote_balance(&deps, &env, "uatom"), Uint128::new(42));
+
+    // Balances can be refreshed until the last active query is removed
+    execute_as_anyone(&mut deps, &env, ExecuteMsg::RefreshRemoteBalances {}).unwrap();
+    execute_as_admin(&mut deps, &env, ExecuteMsg::RemoveQuery { query_id: 4 }).unwrap();
+    assert!(query_active_queries(&deps, &env).is_empty());
+    let balances_query: Option<BalancesQuery> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::BalancesQuery {}).unwrap()).unwrap();
+    assert_eq!(balances_query, None);
+    let err = execute_as_anyone(&mut deps, &env, ExecuteMsg::RefreshRemoteBalances {}).unwrap_err();
+    assert_eq!(err, ContractError::NoBalancesQuery);
+}
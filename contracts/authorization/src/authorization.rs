@@ -108,6 +108,7 @@ impl Validate for Authorization {
     /// - Message type compatibility
     /// - Library validity for Evm calls
     /// - Parameter restrictions
+    /// - Gas limit
     fn validate_functions<T: Function>(
         &self,
         store: &dyn Storage,
@@ -135,6 +136,18 @@ impl Validate for Authorization {
             self.validate_message_type(func, &external_domain)?;
             self.validate_evm_library(func, &querier)?;
             self.validate_param_restrictions(func)?;
+
+            if func.gas_limit() == Some(0) {
+                return Err(ContractError::Authorization(
+                    AuthorizationErrorReason::ZeroGasLimit {},
+                ));
+            }
+
+            if func.scope().is_some_and(|scope| scope.is_empty()) {
+                return Err(ContractError::Authorization(
+                    AuthorizationErrorReason::EmptyScope {},
+                ));
+            }
         }
         Ok(())
     }
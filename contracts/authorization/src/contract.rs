@@ -2,8 +2,8 @@ use cosmos_sdk_proto::{cosmos::base::v1beta1::Coin, traits::MessageExt};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coins, from_json, to_json_binary, Addr, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Empty, Env,
-    MessageInfo, Order, Response, StdResult, Storage, Uint64, WasmMsg,
+    coins, from_json, to_json_binary, Addr, BankMsg, Binary, BlockInfo, CosmosMsg, Deps, DepsMut,
+    Empty, Env, MessageInfo, Order, Response, StdResult, Storage, Uint64, WasmMsg,
 };
 use cw_ownable::{assert_owner, get_ownership, initialize_owner, is_owner};
 use cw_storage_plus::Bound;
@@ -149,6 +149,12 @@ pub fn execute(
                     queue_position,
                     priority,
                 } => evict_messages(deps, domain, queue_position, priority),
+                PermissionedMsg::RemoveMsg { domain, id } => remove_message(deps, domain, id),
+                PermissionedMsg::Flush { domain } => flush_queues(deps, domain),
+                PermissionedMsg::PurgeDeadLetters { domain, ids } => {
+                    purge_dead_letters(deps, domain, ids)
+                }
+                PermissionedMsg::RequeueDeadLetter { id } => requeue_dead_letter(deps, env, id),
                 PermissionedMsg::InsertMsgs {
                     label,
                     queue_position,
@@ -785,6 +791,105 @@ fn evict_messages(
         .add_attribute("action", "remove_messages"))
 }
 
+fn remove_message(deps: DepsMut, domain: Domain, id: u64) -> Result<Response, ContractError> {
+    let remove_msg =
+        ProcessorExecuteMsg::AuthorizationModuleAction(AuthorizationMsg::RemoveMsg { id });
+    let execute_msg_binary = encode_queue_management_msg(deps.as_ref(), &domain, &remove_msg)?;
+    let msg = create_msg_for_processor(deps.storage, execute_msg_binary, &domain, None)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "remove_message")
+        .add_attribute("execution_id", id.to_string()))
+}
+
+fn flush_queues(deps: DepsMut, domain: Domain) -> Result<Response, ContractError> {
+    let flush_msg = ProcessorExecuteMsg::AuthorizationModuleAction(AuthorizationMsg::Flush {});
+    let execute_msg_binary = encode_queue_management_msg(deps.as_ref(), &domain, &flush_msg)?;
+    let msg = create_msg_for_processor(deps.storage, execute_msg_binary, &domain, None)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "flush_queues"))
+}
+
+fn purge_dead_letters(
+    deps: DepsMut,
+    domain: Domain,
+    ids: Vec<u64>,
+) -> Result<Response, ContractError> {
+    let purged_ids = ids
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let purge_msg =
+        ProcessorExecuteMsg::AuthorizationModuleAction(AuthorizationMsg::PurgeDeadLetters { ids });
+    let execute_msg_binary = encode_queue_management_msg(deps.as_ref(), &domain, &purge_msg)?;
+    let msg = create_msg_for_processor(deps.storage, execute_msg_binary, &domain, None)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "purge_dead_letters")
+        .add_attribute("execution_ids", purged_ids))
+}
+
+fn requeue_dead_letter(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
+    let mut callback = PROCESSOR_CALLBACKS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::ExecutionIDNotFound { execution_id: id })?;
+    // Only the batches that exhausted their retries end up in the dead letter queue
+    if !matches!(
+        callback.execution_result,
+        ExecutionResult::Rejected(_) | ExecutionResult::PartiallyExecuted(_, _)
+    ) {
+        return Err(ContractError::NotDeadLettered { execution_id: id });
+    }
+
+    // The batch is in process again, so it gets a new callback from the processor once it finishes
+    callback.execution_result = ExecutionResult::InProcess;
+    callback.last_updated_at = env.block.time.seconds();
+    // The token of the initiator was already burned or sent back when the batch was dead-lettered
+    callback.initiator = OperationInitiator::Owner;
+    PROCESSOR_CALLBACKS.save(deps.storage, id, &callback)?;
+
+    let current_executions = CURRENT_EXECUTIONS
+        .may_load(deps.storage, callback.label.clone())?
+        .unwrap_or_default();
+    CURRENT_EXECUTIONS.save(
+        deps.storage,
+        callback.label.clone(),
+        &current_executions.checked_add(1).expect("Overflow"),
+    )?;
+
+    let requeue_msg =
+        ProcessorExecuteMsg::AuthorizationModuleAction(AuthorizationMsg::RequeueDeadLetter { id });
+    let execute_msg_binary =
+        encode_queue_management_msg(deps.as_ref(), &callback.domain, &requeue_msg)?;
+    let msg = create_msg_for_processor(deps.storage, execute_msg_binary, &callback.domain, None)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "requeue_dead_letter")
+        .add_attribute("execution_id", id.to_string()))
+}
+
+// Removing batches by ID or flushing the queues is only supported by the Cosmwasm processors, the processors of EVM domains execute immediately
+fn encode_queue_management_msg(
+    deps: Deps,
+    domain: &Domain,
+    msg: &ProcessorExecuteMsg,
+) -> Result<Binary, ContractError> {
+    if let Domain::External(external_domain_id) = domain {
+        let external_domain = EXTERNAL_DOMAINS.load(deps.storage, external_domain_id.clone())?;
+        if let ExecutionEnvironment::Evm(..) = external_domain.execution_environment {
+            return Err(ContractError::NoProcessorQueues {});
+        }
+    }
+
+    Ok(to_json_binary(msg)?)
+}
+
 fn send_msgs(
     deps: DepsMut,
     env: Env,
@@ -1619,7 +1724,7 @@ fn process_hyperlane_callback(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Ownership {} => to_json_binary(&get_ownership(deps.storage)?),
         QueryMsg::SubOwners {} => to_json_binary(&get_sub_owners(deps)?),
@@ -1631,6 +1736,9 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::Authorizations { start_after, limit } => {
             to_json_binary(&get_authorizations(deps, start_after, limit))
         }
+        QueryMsg::ActiveAuthorizations { start_after, limit } => to_json_binary(
+            &get_active_authorizations(deps, &env.block, start_after, limit),
+        ),
         QueryMsg::ZkAuthorizations { start_after, limit } => {
             to_json_binary(&get_zk_authorizations(deps, start_after, limit))
         }
@@ -1700,6 +1808,24 @@ fn get_authorizations(
         .collect()
 }
 
+fn get_active_authorizations(
+    deps: Deps,
+    block: &BlockInfo,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Vec<Authorization> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+    let start = start_after.map(Bound::exclusive);
+
+    AUTHORIZATIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .map(|(_, auth)| auth)
+        .filter(|auth| !auth.expiration.is_expired(block))
+        .take(limit as usize)
+        .collect()
+}
+
 fn get_zk_authorizations(
     deps: Deps,
     start_after: Option<String>,
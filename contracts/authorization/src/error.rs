@@ -44,6 +44,12 @@ pub enum ContractError {
 
     #[error("Bridge creation not required")]
     BridgeCreationNotRequired {},
+
+    #[error("The processors of EVM domains have no queues to remove messages from")]
+    NoProcessorQueues {},
+
+    #[error("Execution ID {execution_id} was not rejected by the processor, it can't be requeued")]
+    NotDeadLettered { execution_id: u64 },
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -80,6 +86,12 @@ pub enum AuthorizationErrorReason {
 
     #[error("Encoding for library in authorization does not exist")]
     InvalidLibraryName {},
+
+    #[error("The gas limit of a function can't be zero")]
+    ZeroGasLimit {},
+
+    #[error("The scope of a function can't be empty")]
+    EmptyScope {},
 }
 
 #[derive(Error, Debug, PartialEq)]
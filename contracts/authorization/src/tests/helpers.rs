@@ -14,7 +14,9 @@ use neutron_test_tube::{
 };
 use serde::Serialize;
 use valence_authorization_utils::msg::{ExecuteMsg, InstantiateMsg, PermissionedMsg};
+use valence_library_utils::{msg::InstantiateMsg as LibraryInstantiateMsg, LibraryAccountType};
 use valence_processor_utils::msg::InstantiateMsg as ProcessorInstantiateMsg;
+use valence_template_library::msg::LibraryConfig as TemplateLibraryConfig;
 use valence_test_library::msg::InstantiateMsg as TestLibraryInstantiateMsg;
 
 pub const ARTIFACTS_DIR: &str = "../../artifacts";
@@ -193,6 +195,41 @@ pub fn store_and_instantiate_test_library(
     .address
 }
 
+pub fn store_and_instantiate_template_library(
+    wasm: &Wasm<'_, NeutronTestApp>,
+    signer: &SigningAccount,
+    processor: &str,
+) -> String {
+    let wasm_byte_code =
+        std::fs::read(format!("{ARTIFACTS_DIR}/valence_template_library.wasm")).unwrap();
+
+    let code_id = wasm
+        .store_code(&wasm_byte_code, None, signer)
+        .unwrap()
+        .data
+        .code_id;
+
+    wasm.instantiate(
+        code_id,
+        &LibraryInstantiateMsg {
+            owner: signer.address(),
+            processor: processor.to_string(),
+            config: TemplateLibraryConfig {
+                skip_update_admin: LibraryAccountType::Addr(signer.address()),
+                optional: None,
+                optional2: "template".to_string(),
+            },
+        },
+        None,
+        "template_library".into(),
+        &[],
+        signer,
+    )
+    .unwrap()
+    .data
+    .address
+}
+
 pub fn wait_for_height(app: &NeutronTestApp, height: u64) {
     while (app.get_block_height() as u64) < height {
         // We can't increase blocks directly so we do it this way
@@ -15,7 +15,7 @@ use valence_authorization_utils::{
         NonAtomicFunctionBuilder, NonAtomicSubroutineBuilder,
     },
     domain::Domain,
-    function::{FunctionCallback, RetryLogic, RetryTimes},
+    function::{FunctionCallback, RetryBackoff, RetryLogic, RetryTimes},
     msg::{ExecuteMsg, Mint, OwnerMsg, PermissionedMsg, QueryMsg},
 };
 use valence_processor::error::ContractError as ProcessorContractError;
@@ -327,6 +327,7 @@ fn create_valid_authorizations() {
                             .with_retry_logic(RetryLogic {
                                 times: RetryTimes::Amount(5),
                                 interval: Duration::Time(10),
+                                backoff: RetryBackoff::Fixed,
                             })
                             .build(),
                     )
@@ -345,6 +346,7 @@ fn create_valid_authorizations() {
                             .with_retry_logic(RetryLogic {
                                 times: RetryTimes::Amount(10),
                                 interval: Duration::Height(5),
+                                backoff: RetryBackoff::Fixed,
                             })
                             .with_callback_confirmation(FunctionCallback {
                                 contract_address: Addr::unchecked("address"),
@@ -371,6 +373,7 @@ fn create_valid_authorizations() {
                     .with_retry_logic(RetryLogic {
                         times: RetryTimes::Amount(5),
                         interval: Duration::Time(10),
+                        backoff: RetryBackoff::Fixed,
                     })
                     .with_function(
                         AtomicFunctionBuilder::new()
@@ -823,6 +826,87 @@ fn modify_authorization() {
     ));
 }
 
+#[test]
+fn query_active_authorizations() {
+    let setup = NeutronTestAppBuilder::new().build().unwrap();
+
+    let wasm = Wasm::new(&setup.app);
+
+    let (authorization_contract, _) = store_and_instantiate_authorization_with_processor_contract(
+        &setup.app,
+        &setup.owner_accounts[0],
+        setup.owner_addr.to_string(),
+        vec![setup.subowner_addr.to_string()],
+    );
+
+    // One authorization that expires after 100 seconds and one that never expires
+    let authorizations = vec![
+        AuthorizationBuilder::new()
+            .with_label("time-boxed")
+            .with_duration(AuthorizationDuration::Seconds(100))
+            .with_subroutine(
+                AtomicSubroutineBuilder::new()
+                    .with_function(AtomicFunctionBuilder::new().build())
+                    .build(),
+            )
+            .build(),
+        AuthorizationBuilder::new()
+            .with_label("forever")
+            .with_subroutine(
+                AtomicSubroutineBuilder::new()
+                    .with_function(AtomicFunctionBuilder::new().build())
+                    .build(),
+            )
+            .build(),
+    ];
+
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionedAction(PermissionedMsg::CreateAuthorizations { authorizations }),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    let query_active_labels = || {
+        wasm.query::<QueryMsg, Vec<Authorization>>(
+            &authorization_contract,
+            &QueryMsg::ActiveAuthorizations {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap()
+        .into_iter()
+        .map(|a| a.label)
+        .collect::<Vec<String>>()
+    };
+
+    // Before expiry both authorizations are active
+    assert_eq!(
+        query_active_labels(),
+        vec!["forever".to_string(), "time-boxed".to_string()]
+    );
+
+    // Let's increase the time to expire the time-boxed authorization
+    setup.app.increase_time(101);
+
+    // The expired authorization is filtered out of the active ones
+    assert_eq!(query_active_labels(), vec!["forever".to_string()]);
+
+    // But it is still stored in the contract
+    let query_authorizations = wasm
+        .query::<QueryMsg, Vec<Authorization>>(
+            &authorization_contract,
+            &QueryMsg::Authorizations {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_authorizations.len(), 2);
+}
+
 #[test]
 fn mint_authorizations() {
     let setup = NeutronTestAppBuilder::new()
@@ -13,15 +13,24 @@ use valence_authorization_utils::{
     },
     callback::{ExecutionResult, ProcessorCallbackInfo},
     domain::Domain,
-    function::{FunctionCallback, RetryLogic, RetryTimes},
+    function::{FunctionCallback, RetryBackoff, RetryLogic, RetryTimes},
     msg::{ExecuteMsg, PermissionedMsg, PermissionlessMsg, ProcessorMessage, QueryMsg},
 };
-use valence_library_utils::LibraryAccountType;
-use valence_processor_utils::{msg::InternalProcessorMsg, processor::MessageBatch};
+use valence_library_utils::{
+    msg::{ExecuteMsg as LibraryExecuteMsg, LastErrorResponse},
+    LibraryAccountType,
+};
+use valence_processor_utils::{
+    msg::{InternalProcessorMsg, QueuesResponse},
+    processor::{DeadLetter, MessageBatch},
+};
 
 use crate::{
     contract::build_tokenfactory_denom,
-    error::{AuthorizationErrorReason, ContractError},
+    error::{
+        AuthorizationErrorReason, ContractError,
+        UnauthorizedReason as AuthorizationUnauthorizedReason,
+    },
     tests::helpers::{wait_for_height, ARTIFACTS_DIR},
 };
 use valence_processor_utils::msg::{
@@ -31,15 +40,20 @@ use valence_processor_utils::msg::{
 
 use valence_processor::error::{ContractError as ProcessorContractError, UnauthorizedReason};
 
+use valence_template_library::msg::{
+    FunctionMsgs as TemplateLibraryFunctionMsgs,
+    LibraryConfigUpdate as TemplateLibraryConfigUpdate, QueryMsg as TemplateLibraryQueryMsg,
+};
 use valence_test_library::msg::{
-    ExecuteMsg as TestLibraryExecuteMsg, QueryMsg as TestLibraryQueryMsg,
+    ExecuteMsg as TestLibraryExecuteMsg, FunctionMsgs as TestLibraryFunctionMsgs,
+    QueryMsg as TestLibraryQueryMsg,
 };
 
 use super::{
     builders::NeutronTestAppBuilder,
     helpers::{
         store_and_instantiate_authorization_with_processor_contract,
-        store_and_instantiate_test_library,
+        store_and_instantiate_template_library, store_and_instantiate_test_library,
     },
 };
 
@@ -1004,6 +1018,175 @@ fn invalid_msg_rejected() {
     ));
 }
 
+#[test]
+fn out_of_scope_msg_rejected() {
+    let setup = NeutronTestAppBuilder::new().build().unwrap();
+
+    let wasm = Wasm::new(&setup.app);
+
+    let (authorization_contract, processor_contract) =
+        store_and_instantiate_authorization_with_processor_contract(
+            &setup.app,
+            &setup.owner_accounts[0],
+            setup.owner_addr.to_string(),
+            vec![setup.subowner_addr.to_string()],
+        );
+    let test_library_contract =
+        store_and_instantiate_test_library(&wasm, &setup.owner_accounts[0], None);
+
+    let scoped_function = |scope: Vec<String>| {
+        AtomicFunctionBuilder::new()
+            .with_contract_address(LibraryAccountType::Addr(test_library_contract.clone()))
+            .with_message_details(MessageDetails {
+                message_type: MessageType::CosmwasmExecuteMsg,
+                message: Message {
+                    name: "process_function".to_string(),
+                    params_restrictions: None,
+                },
+            })
+            .with_scope(scope)
+            .build()
+    };
+
+    // An empty scope would not allow any call, so it is rejected
+    let error = wasm
+        .execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionedAction(PermissionedMsg::CreateAuthorizations {
+                authorizations: vec![AuthorizationBuilder::new()
+                    .with_label("empty-scope")
+                    .with_subroutine(
+                        AtomicSubroutineBuilder::new()
+                            .with_function(scoped_function(vec![]))
+                            .build(),
+                    )
+                    .build()],
+            }),
+            &[],
+            &setup.owner_accounts[0],
+        )
+        .unwrap_err();
+
+    assert!(error.to_string().contains(
+        ContractError::Authorization(AuthorizationErrorReason::EmptyScope {})
+            .to_string()
+            .as_str()
+    ));
+
+    // The authorization only allows the `succeed` function of the library
+    let authorizations = vec![AuthorizationBuilder::new()
+        .with_label("scoped")
+        .with_max_concurrent_executions(10)
+        .with_subroutine(
+            AtomicSubroutineBuilder::new()
+                .with_function(scoped_function(vec!["succeed".to_string()]))
+                .build(),
+        )
+        .build()];
+
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionedAction(PermissionedMsg::CreateAuthorizations { authorizations }),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    // Calling a function out of the scope is rejected by the processor
+    let out_of_scope_message = ProcessorMessage::CosmwasmExecuteMsg {
+        msg: Binary::from(
+            serde_json::to_vec(&TestLibraryExecuteMsg::ProcessFunction(
+                TestLibraryFunctionMsgs::Error {
+                    error: "failed".to_string(),
+                },
+            ))
+            .unwrap(),
+        ),
+    };
+
+    let error = wasm
+        .execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionlessAction(PermissionlessMsg::SendMsgs {
+                label: "scoped".to_string(),
+                messages: vec![out_of_scope_message],
+                ttl: None,
+            }),
+            &[],
+            &setup.user_accounts[0],
+        )
+        .unwrap_err();
+
+    assert!(error.to_string().contains(
+        ProcessorContractError::MessageOutOfScope {
+            index: 0,
+            variant: "error".to_string(),
+        }
+        .to_string()
+        .as_str()
+    ));
+
+    // Nothing was enqueued
+    let query_med_prio_queue = wasm
+        .query::<ProcessorQueryMsg, Vec<MessageBatch>>(
+            &processor_contract,
+            &ProcessorQueryMsg::GetQueue {
+                from: None,
+                to: None,
+                priority: Priority::Medium,
+            },
+        )
+        .unwrap();
+
+    assert!(query_med_prio_queue.is_empty());
+
+    // Calling the function in the scope goes through
+    let in_scope_message = ProcessorMessage::CosmwasmExecuteMsg {
+        msg: Binary::from(
+            serde_json::to_vec(&TestLibraryExecuteMsg::ProcessFunction(
+                TestLibraryFunctionMsgs::Succeed {},
+            ))
+            .unwrap(),
+        ),
+    };
+
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionlessAction(PermissionlessMsg::SendMsgs {
+            label: "scoped".to_string(),
+            messages: vec![in_scope_message],
+            ttl: None,
+        }),
+        &[],
+        &setup.user_accounts[0],
+    )
+    .unwrap();
+
+    wasm.execute::<ProcessorExecuteMsg>(
+        &processor_contract,
+        &ProcessorExecuteMsg::PermissionlessAction(ProcessorPermissionlessMsg::Tick {}),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    let query_callbacks = wasm
+        .query::<QueryMsg, Vec<ProcessorCallbackInfo>>(
+            &authorization_contract,
+            &QueryMsg::ProcessorCallbacks {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_callbacks.len(), 1);
+    assert_eq!(
+        query_callbacks[0].execution_result,
+        ExecutionResult::Success
+    );
+}
+
 #[test]
 fn queue_shifting_when_not_retriable() {
     let setup = NeutronTestAppBuilder::new().build().unwrap();
@@ -1045,6 +1228,7 @@ fn queue_shifting_when_not_retriable() {
                     .with_retry_logic(RetryLogic {
                         times: RetryTimes::Indefinitely,
                         interval: Duration::Height(50), // 50 blocks between retries
+                        backoff: RetryBackoff::Fixed,
                     })
                     .build(),
             )
@@ -1074,6 +1258,8 @@ fn queue_shifting_when_not_retriable() {
                             .with_retry_logic(RetryLogic {
                                 times: RetryTimes::Indefinitely,
                                 interval: Duration::Height(50), // 50 blocks between retries
+                                backoff: RetryBackoff::Fixed,
+                                backoff: RetryBackoff::Fixed,
                             })
                             .build(),
                     )
@@ -1510,6 +1696,170 @@ fn higher_priority_queue_is_processed_first() {
     }
 }
 
+#[test]
+fn interleaved_priorities_are_processed_in_order() {
+    let setup = NeutronTestAppBuilder::new().build().unwrap();
+
+    let wasm = Wasm::new(&setup.app);
+
+    let (authorization_contract, processor_contract) =
+        store_and_instantiate_authorization_with_processor_contract(
+            &setup.app,
+            &setup.owner_accounts[0],
+            setup.owner_addr.to_string(),
+            vec![setup.subowner_addr.to_string()],
+        );
+    let test_library_contract =
+        store_and_instantiate_test_library(&wasm, &setup.owner_accounts[0], None);
+
+    let subroutine = AtomicSubroutineBuilder::new()
+        .with_function(
+            AtomicFunctionBuilder::new()
+                .with_contract_address(LibraryAccountType::Addr(test_library_contract.clone()))
+                .with_message_details(MessageDetails {
+                    message_type: MessageType::CosmwasmExecuteMsg,
+                    message: Message {
+                        name: "will_succeed".to_string(),
+                        params_restrictions: None,
+                    },
+                })
+                .build(),
+        )
+        .build();
+
+    // One authorization for each priority, high priority ones can't be permissionless
+    let authorizations = vec![
+        AuthorizationBuilder::new()
+            .with_label("low")
+            .with_max_concurrent_executions(10)
+            .with_subroutine(subroutine.clone())
+            .with_priority(Priority::Low)
+            .build(),
+        AuthorizationBuilder::new()
+            .with_label("medium")
+            .with_max_concurrent_executions(10)
+            .with_subroutine(subroutine.clone())
+            .build(),
+        AuthorizationBuilder::new()
+            .with_label("high")
+            .with_max_concurrent_executions(10)
+            .with_mode(AuthorizationModeInfo::Permissioned(
+                PermissionTypeInfo::WithoutCallLimit(vec![setup.user_accounts[0]
+                    .address()
+                    .to_string()]),
+            ))
+            .with_subroutine(subroutine)
+            .with_priority(Priority::High)
+            .build(),
+    ];
+
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionedAction(PermissionedMsg::CreateAuthorizations { authorizations }),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    let binary = Binary::from(
+        serde_json::to_vec(&TestLibraryExecuteMsg::WillSucceed { execution_id: None }).unwrap(),
+    );
+    let message = ProcessorMessage::CosmwasmExecuteMsg { msg: binary };
+
+    // Interleave the priorities when enqueuing: execution IDs 0 and 3 are low, 1 and 4 are medium, 2 and 5 are high
+    for _ in 0..2 {
+        for label in ["low", "medium", "high"] {
+            wasm.execute::<ExecuteMsg>(
+                &authorization_contract,
+                &ExecuteMsg::PermissionlessAction(PermissionlessMsg::SendMsgs {
+                    label: label.to_string(),
+                    messages: vec![message.clone()],
+                    ttl: None,
+                }),
+                &[],
+                &setup.user_accounts[0],
+            )
+            .unwrap();
+        }
+    }
+
+    let query_queues = || {
+        wasm.query::<ProcessorQueryMsg, QueuesResponse>(
+            &processor_contract,
+            &ProcessorQueryMsg::GetQueues {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap()
+    };
+    let ids = |batches: &[MessageBatch]| batches.iter().map(|batch| batch.id).collect::<Vec<_>>();
+
+    let queues = query_queues();
+    assert_eq!(ids(&queues.high), vec![2, 5]);
+    assert_eq!(ids(&queues.medium), vec![1, 4]);
+    assert_eq!(ids(&queues.low), vec![0, 3]);
+
+    // Pages are taken across the queues in the order the batches will be processed
+    let query_page = |start_after: Option<u64>| {
+        wasm.query::<ProcessorQueryMsg, QueuesResponse>(
+            &processor_contract,
+            &ProcessorQueryMsg::GetQueues {
+                start_after,
+                limit: Some(3),
+            },
+        )
+        .unwrap()
+    };
+    let page = query_page(None);
+    assert_eq!(ids(&page.high), vec![2, 5]);
+    assert_eq!(ids(&page.medium), vec![1]);
+    assert!(page.low.is_empty());
+
+    let page = query_page(Some(2));
+    assert!(page.high.is_empty());
+    assert_eq!(ids(&page.medium), vec![4]);
+    assert_eq!(ids(&page.low), vec![0, 3]);
+
+    let page = query_page(Some(5));
+    assert!(page.high.is_empty() && page.medium.is_empty() && page.low.is_empty());
+
+    // High priority batches are drained first, then medium and then low, FIFO within each priority
+    let mut pending = vec![2, 5, 1, 4, 0, 3];
+    while !pending.is_empty() {
+        wasm.execute::<ProcessorExecuteMsg>(
+            &processor_contract,
+            &ProcessorExecuteMsg::PermissionlessAction(ProcessorPermissionlessMsg::Tick {}),
+            &[],
+            &setup.owner_accounts[0],
+        )
+        .unwrap();
+        pending.remove(0);
+
+        let queues = query_queues();
+        let mut remaining = ids(&queues.high);
+        remaining.extend(ids(&queues.medium));
+        remaining.extend(ids(&queues.low));
+        assert_eq!(remaining, pending);
+    }
+
+    // All of them executed successfully
+    let query_callbacks = wasm
+        .query::<QueryMsg, Vec<ProcessorCallbackInfo>>(
+            &authorization_contract,
+            &QueryMsg::ProcessorCallbacks {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_callbacks.len(), 6);
+    assert!(query_callbacks
+        .iter()
+        .all(|callback| callback.execution_result == ExecutionResult::Success));
+}
+
 #[test]
 fn retry_multi_function_atomic_batch_until_success() {
     let setup = NeutronTestAppBuilder::new().build().unwrap();
@@ -1534,6 +1884,7 @@ fn retry_multi_function_atomic_batch_until_success() {
                 .with_retry_logic(RetryLogic {
                     times: RetryTimes::Indefinitely,
                     interval: Duration::Time(2),
+                    backoff: RetryBackoff::Fixed,
                 })
                 .with_function(
                     AtomicFunctionBuilder::new()
@@ -1745,6 +2096,7 @@ fn retry_multi_function_non_atomic_batch_until_success() {
                         .with_retry_logic(RetryLogic {
                             times: RetryTimes::Indefinitely,
                             interval: Duration::Time(2),
+                            backoff: RetryBackoff::Fixed,
                         })
                         .with_message_details(MessageDetails {
                             message_type: MessageType::CosmwasmExecuteMsg,
@@ -1944,7 +2296,7 @@ fn retry_multi_function_non_atomic_batch_until_success() {
 }
 
 #[test]
-fn failed_atomic_batch_after_retries() {
+fn retry_atomic_batch_with_exponential_backoff_until_success() {
     let setup = NeutronTestAppBuilder::new().build().unwrap();
 
     let wasm = Wasm::new(&setup.app);
@@ -1959,14 +2311,15 @@ fn failed_atomic_batch_after_retries() {
     let test_library_contract =
         store_and_instantiate_test_library(&wasm, &setup.owner_accounts[0], None);
 
-    // We'll create an authorization with 3 functions, where the first one and third will always succeed but the second one will fail until we modify the contract to succeed
+    // The function will fail until we set the condition to true, with an interval that doubles after each retry
     let authorizations = vec![AuthorizationBuilder::new()
         .with_label("permissionless")
         .with_subroutine(
             AtomicSubroutineBuilder::new()
                 .with_retry_logic(RetryLogic {
-                    times: RetryTimes::Amount(5),
-                    interval: Duration::Time(2),
+                    times: RetryTimes::Amount(3),
+                    interval: Duration::Time(100),
+                    backoff: RetryBackoff::Exponential { max_interval: None },
                 })
                 .with_function(
                     AtomicFunctionBuilder::new()
@@ -1976,21 +2329,7 @@ fn failed_atomic_batch_after_retries() {
                         .with_message_details(MessageDetails {
                             message_type: MessageType::CosmwasmExecuteMsg,
                             message: Message {
-                                name: "will_succeed".to_string(),
-                                params_restrictions: None,
-                            },
-                        })
-                        .build(),
-                )
-                .with_function(
-                    AtomicFunctionBuilder::new()
-                        .with_contract_address(LibraryAccountType::Addr(
-                            test_library_contract.clone(),
-                        ))
-                        .with_message_details(MessageDetails {
-                            message_type: MessageType::CosmwasmExecuteMsg,
-                            message: Message {
-                                name: "will_error".to_string(),
+                                name: "will_succeed_if_true".to_string(),
                                 params_restrictions: None,
                             },
                         })
@@ -2008,24 +2347,15 @@ fn failed_atomic_batch_after_retries() {
     )
     .unwrap();
 
-    let binary = Binary::from(
-        serde_json::to_vec(&TestLibraryExecuteMsg::WillSucceed { execution_id: None }).unwrap(),
-    );
-    let message1 = ProcessorMessage::CosmwasmExecuteMsg { msg: binary };
-    let binary = Binary::from(
-        serde_json::to_vec(&TestLibraryExecuteMsg::WillError {
-            error: "failed".to_string(),
-        })
-        .unwrap(),
-    );
-    let message2 = ProcessorMessage::CosmwasmExecuteMsg { msg: binary };
+    let binary =
+        Binary::from(serde_json::to_vec(&TestLibraryExecuteMsg::WillSucceedIfTrue {}).unwrap());
+    let message = ProcessorMessage::CosmwasmExecuteMsg { msg: binary };
 
-    // Send the messages
     wasm.execute::<ExecuteMsg>(
         &authorization_contract,
         &ExecuteMsg::PermissionlessAction(PermissionlessMsg::SendMsgs {
             label: "permissionless".to_string(),
-            messages: vec![message1, message2],
+            messages: vec![message],
             ttl: None,
         }),
         &[],
@@ -2033,8 +2363,197 @@ fn failed_atomic_batch_after_retries() {
     )
     .unwrap();
 
-    // Trying to trigger the ExecuteAtomic entry point will fail because only the processor can call it
-    let error = wasm
+    let query_retry_amounts = || {
+        let queue = wasm
+            .query::<ProcessorQueryMsg, Vec<MessageBatch>>(
+                &processor_contract,
+                &ProcessorQueryMsg::GetQueue {
+                    from: None,
+                    to: None,
+                    priority: Priority::Medium,
+                },
+            )
+            .unwrap();
+        assert_eq!(queue.len(), 1);
+        queue[0]
+            .retry
+            .as_ref()
+            .map_or(0, |retry| retry.retry_amounts)
+    };
+
+    let tick = || {
+        wasm.execute::<ProcessorExecuteMsg>(
+            &processor_contract,
+            &ProcessorExecuteMsg::PermissionlessAction(ProcessorPermissionlessMsg::Tick {}),
+            &[],
+            &setup.owner_accounts[0],
+        )
+        .unwrap();
+    };
+
+    // First failure, next retry after 100 seconds
+    tick();
+    assert_eq!(query_retry_amounts(), 1);
+
+    // Second failure, next retry after 200 seconds
+    setup.app.increase_time(110);
+    tick();
+    assert_eq!(query_retry_amounts(), 2);
+
+    // With a fixed interval it would be retried now, but the backoff doubled the cooldown
+    setup.app.increase_time(150);
+    tick();
+    assert_eq!(query_retry_amounts(), 2);
+
+    // Set the condition to true to make it succeed on the next retry
+    wasm.execute::<TestLibraryExecuteMsg>(
+        &test_library_contract,
+        &TestLibraryExecuteMsg::SetCondition { condition: true },
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    setup.app.increase_time(100);
+    tick();
+
+    // Confirm that we have no messages in the queue
+    let query_med_prio_queue = wasm
+        .query::<ProcessorQueryMsg, Vec<MessageBatch>>(
+            &processor_contract,
+            &ProcessorQueryMsg::GetQueue {
+                from: None,
+                to: None,
+                priority: Priority::Medium,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_med_prio_queue.len(), 0);
+
+    // Confirm we got the successful callback and nothing was dead lettered
+    let query_callbacks = wasm
+        .query::<QueryMsg, Vec<ProcessorCallbackInfo>>(
+            &authorization_contract,
+            &QueryMsg::ProcessorCallbacks {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_callbacks.len(), 1);
+    assert_eq!(
+        query_callbacks[0].execution_result,
+        ExecutionResult::Success
+    );
+
+    let dead_letters = wasm
+        .query::<ProcessorQueryMsg, Vec<DeadLetter>>(
+            &processor_contract,
+            &ProcessorQueryMsg::DeadLetterQueue {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert!(dead_letters.is_empty());
+}
+
+#[test]
+fn failed_atomic_batch_after_retries() {
+    let setup = NeutronTestAppBuilder::new().build().unwrap();
+
+    let wasm = Wasm::new(&setup.app);
+
+    let (authorization_contract, processor_contract) =
+        store_and_instantiate_authorization_with_processor_contract(
+            &setup.app,
+            &setup.owner_accounts[0],
+            setup.owner_addr.to_string(),
+            vec![setup.subowner_addr.to_string()],
+        );
+    let test_library_contract =
+        store_and_instantiate_test_library(&wasm, &setup.owner_accounts[0], None);
+
+    // We'll create an authorization with 3 functions, where the first one and third will always succeed but the second one will fail until we modify the contract to succeed
+    let authorizations = vec![AuthorizationBuilder::new()
+        .with_label("permissionless")
+        .with_subroutine(
+            AtomicSubroutineBuilder::new()
+                .with_retry_logic(RetryLogic {
+                    times: RetryTimes::Amount(5),
+                    interval: Duration::Time(2),
+                    backoff: RetryBackoff::Fixed,
+                })
+                .with_function(
+                    AtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr(
+                            test_library_contract.clone(),
+                        ))
+                        .with_message_details(MessageDetails {
+                            message_type: MessageType::CosmwasmExecuteMsg,
+                            message: Message {
+                                name: "will_succeed".to_string(),
+                                params_restrictions: None,
+                            },
+                        })
+                        .build(),
+                )
+                .with_function(
+                    AtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr(
+                            test_library_contract.clone(),
+                        ))
+                        .with_message_details(MessageDetails {
+                            message_type: MessageType::CosmwasmExecuteMsg,
+                            message: Message {
+                                name: "will_error".to_string(),
+                                params_restrictions: None,
+                            },
+                        })
+                        .build(),
+                )
+                .build(),
+        )
+        .build()];
+
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionedAction(PermissionedMsg::CreateAuthorizations { authorizations }),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    let binary = Binary::from(
+        serde_json::to_vec(&TestLibraryExecuteMsg::WillSucceed { execution_id: None }).unwrap(),
+    );
+    let message1 = ProcessorMessage::CosmwasmExecuteMsg { msg: binary };
+    let binary = Binary::from(
+        serde_json::to_vec(&TestLibraryExecuteMsg::WillError {
+            error: "failed".to_string(),
+        })
+        .unwrap(),
+    );
+    let message2 = ProcessorMessage::CosmwasmExecuteMsg { msg: binary };
+
+    // Send the messages
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionlessAction(PermissionlessMsg::SendMsgs {
+            label: "permissionless".to_string(),
+            messages: vec![message1, message2],
+            ttl: None,
+        }),
+        &[],
+        &setup.user_accounts[0],
+    )
+    .unwrap();
+
+    // Trying to trigger the ExecuteAtomic entry point will fail because only the processor can call it
+    let error = wasm
         .execute::<ProcessorExecuteMsg>(
             &processor_contract,
             &ProcessorExecuteMsg::InternalProcessorAction(InternalProcessorMsg::ExecuteAtomic {
@@ -2105,6 +2624,25 @@ fn failed_atomic_batch_after_retries() {
         query_callbacks[0].execution_result,
         ExecutionResult::Rejected(_)
     ));
+
+    // The batch that exhausted its retries must be in the dead letter queue
+    let dead_letters = wasm
+        .query::<ProcessorQueryMsg, Vec<DeadLetter>>(
+            &processor_contract,
+            &ProcessorQueryMsg::DeadLetterQueue {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].batch.id, query_callbacks[0].execution_id);
+    assert_eq!(
+        dead_letters[0].batch.retry.as_ref().unwrap().retry_amounts,
+        5
+    );
+    assert_eq!(dead_letters[0].function_index, 0);
 }
 
 #[test]
@@ -2157,6 +2695,7 @@ fn failed_non_atomic_batch_after_retries() {
                         .with_retry_logic(RetryLogic {
                             times: RetryTimes::Amount(5),
                             interval: Duration::Time(2),
+                            backoff: RetryBackoff::Fixed,
                         })
                         .with_message_details(MessageDetails {
                             message_type: MessageType::CosmwasmExecuteMsg,
@@ -2251,6 +2790,20 @@ fn failed_non_atomic_batch_after_retries() {
         ExecutionResult::PartiallyExecuted(1, _)
     ));
 
+    // The batch is in the dead letter queue, pointing to the function that exhausted its retries
+    let dead_letters = wasm
+        .query::<ProcessorQueryMsg, Vec<DeadLetter>>(
+            &processor_contract,
+            &ProcessorQueryMsg::DeadLetterQueue {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].function_index, 1);
+
     // Verify that neither the contract nor the user has the token (it was burned)
     let bank = Bank::new(&setup.app);
     let balance = bank
@@ -2273,7 +2826,7 @@ fn failed_non_atomic_batch_after_retries() {
 }
 
 #[test]
-fn successful_non_atomic_and_atomic_batches_together() {
+fn library_records_error_of_failed_non_atomic_function() {
     let setup = NeutronTestAppBuilder::new().build().unwrap();
 
     let wasm = Wasm::new(&setup.app);
@@ -2285,77 +2838,199 @@ fn successful_non_atomic_and_atomic_batches_together() {
             setup.owner_addr.to_string(),
             vec![setup.subowner_addr.to_string()],
         );
-    let test_library_contract =
-        store_and_instantiate_test_library(&wasm, &setup.owner_accounts[0], None);
+    let library_contract = store_and_instantiate_template_library(
+        &wasm,
+        &setup.owner_accounts[0],
+        &processor_contract,
+    );
 
-    // We'll create two authorizations, one atomic and one non-atomic, with 2 functions each where both of them will succeed
-    let authorizations = vec![
-        AuthorizationBuilder::new()
-            .with_label("permissionless-atomic")
-            .with_subroutine(
-                AtomicSubroutineBuilder::new()
-                    .with_function(
-                        AtomicFunctionBuilder::new()
-                            .with_contract_address(LibraryAccountType::Addr(
-                                test_library_contract.clone(),
-                            ))
-                            .with_message_details(MessageDetails {
-                                message_type: MessageType::CosmwasmExecuteMsg,
-                                message: Message {
-                                    name: "will_succeed".to_string(),
-                                    params_restrictions: None,
-                                },
-                            })
-                            .build(),
-                    )
-                    .with_function(
-                        AtomicFunctionBuilder::new()
-                            .with_contract_address(LibraryAccountType::Addr(
-                                test_library_contract.clone(),
-                            ))
-                            .with_message_details(MessageDetails {
-                                message_type: MessageType::CosmwasmExecuteMsg,
-                                message: Message {
-                                    name: "will_succeed".to_string(),
-                                    params_restrictions: None,
-                                },
-                            })
-                            .build(),
-                    )
-                    .build(),
-            )
-            .build(),
-        AuthorizationBuilder::new()
-            .with_label("permissionless-non-atomic")
-            .with_subroutine(
-                NonAtomicSubroutineBuilder::new()
-                    .with_function(
-                        NonAtomicFunctionBuilder::new()
-                            .with_contract_address(LibraryAccountType::Addr(
-                                test_library_contract.clone(),
-                            ))
-                            .with_message_details(MessageDetails {
-                                message_type: MessageType::CosmwasmExecuteMsg,
-                                message: Message {
-                                    name: "will_succeed".to_string(),
-                                    params_restrictions: None,
-                                },
-                            })
-                            .build(),
-                    )
-                    .with_function(
-                        NonAtomicFunctionBuilder::new()
-                            .with_contract_address(LibraryAccountType::Addr(
-                                test_library_contract.clone(),
-                            ))
-                            .with_message_details(MessageDetails {
-                                message_type: MessageType::CosmwasmExecuteMsg,
-                                message: Message {
-                                    name: "will_succeed".to_string(),
-                                    params_restrictions: None,
-                                },
-                            })
-                            .build(),
+    // Pausing the library makes its functions fail
+    wasm.execute::<LibraryExecuteMsg<TemplateLibraryFunctionMsgs, TemplateLibraryConfigUpdate>>(
+        &library_contract,
+        &LibraryExecuteMsg::Pause {},
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    let authorizations = vec![AuthorizationBuilder::new()
+        .with_label("permissionless")
+        .with_subroutine(
+            NonAtomicSubroutineBuilder::new()
+                .with_function(
+                    NonAtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr(library_contract.clone()))
+                        .with_message_details(MessageDetails {
+                            message_type: MessageType::CosmwasmExecuteMsg,
+                            message: Message {
+                                name: "process_function".to_string(),
+                                params_restrictions: None,
+                            },
+                        })
+                        .build(),
+                )
+                .build(),
+        )
+        .build()];
+
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionedAction(PermissionedMsg::CreateAuthorizations { authorizations }),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    let binary = Binary::from(
+        serde_json::to_vec(&LibraryExecuteMsg::<
+            TemplateLibraryFunctionMsgs,
+            TemplateLibraryConfigUpdate,
+        >::ProcessFunction(
+            TemplateLibraryFunctionMsgs::NoOp {}
+        ))
+        .unwrap(),
+    );
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionlessAction(PermissionlessMsg::SendMsgs {
+            label: "permissionless".to_string(),
+            messages: vec![ProcessorMessage::CosmwasmExecuteMsg { msg: binary }],
+            ttl: None,
+        }),
+        &[],
+        &setup.user_accounts[0],
+    )
+    .unwrap();
+
+    // Nothing was recorded before the function fails
+    let last_error = wasm
+        .query::<TemplateLibraryQueryMsg, LastErrorResponse>(
+            &library_contract,
+            &TemplateLibraryQueryMsg::GetLastError {},
+        )
+        .unwrap();
+    assert_eq!(last_error, LastErrorResponse::default());
+
+    let tick_height = setup.app.get_block_height() as u64;
+    wasm.execute::<ProcessorExecuteMsg>(
+        &processor_contract,
+        &ProcessorExecuteMsg::PermissionlessAction(ProcessorPermissionlessMsg::Tick {}),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    // The function is rejected without retries
+    let query_callbacks = wasm
+        .query::<QueryMsg, Vec<ProcessorCallbackInfo>>(
+            &authorization_contract,
+            &QueryMsg::ProcessorCallbacks {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_callbacks.len(), 1);
+    let ExecutionResult::Rejected(error) = query_callbacks[0].execution_result.clone() else {
+        panic!("Expected the function to be rejected");
+    };
+
+    // The library recorded the same error, in the block of the tick
+    let last_error = wasm
+        .query::<TemplateLibraryQueryMsg, LastErrorResponse>(
+            &library_contract,
+            &TemplateLibraryQueryMsg::GetLastError {},
+        )
+        .unwrap();
+    assert_eq!(last_error.last_error, Some(error));
+    let last_error_height = last_error.last_error_height.unwrap();
+    assert!((tick_height..=setup.app.get_block_height() as u64).contains(&last_error_height));
+}
+
+#[test]
+fn successful_non_atomic_and_atomic_batches_together() {
+    let setup = NeutronTestAppBuilder::new().build().unwrap();
+
+    let wasm = Wasm::new(&setup.app);
+
+    let (authorization_contract, processor_contract) =
+        store_and_instantiate_authorization_with_processor_contract(
+            &setup.app,
+            &setup.owner_accounts[0],
+            setup.owner_addr.to_string(),
+            vec![setup.subowner_addr.to_string()],
+        );
+    let test_library_contract =
+        store_and_instantiate_test_library(&wasm, &setup.owner_accounts[0], None);
+
+    // We'll create two authorizations, one atomic and one non-atomic, with 2 functions each where both of them will succeed
+    let authorizations = vec![
+        AuthorizationBuilder::new()
+            .with_label("permissionless-atomic")
+            .with_subroutine(
+                AtomicSubroutineBuilder::new()
+                    .with_function(
+                        AtomicFunctionBuilder::new()
+                            .with_contract_address(LibraryAccountType::Addr(
+                                test_library_contract.clone(),
+                            ))
+                            .with_message_details(MessageDetails {
+                                message_type: MessageType::CosmwasmExecuteMsg,
+                                message: Message {
+                                    name: "will_succeed".to_string(),
+                                    params_restrictions: None,
+                                },
+                            })
+                            .build(),
+                    )
+                    .with_function(
+                        AtomicFunctionBuilder::new()
+                            .with_contract_address(LibraryAccountType::Addr(
+                                test_library_contract.clone(),
+                            ))
+                            .with_message_details(MessageDetails {
+                                message_type: MessageType::CosmwasmExecuteMsg,
+                                message: Message {
+                                    name: "will_succeed".to_string(),
+                                    params_restrictions: None,
+                                },
+                            })
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build(),
+        AuthorizationBuilder::new()
+            .with_label("permissionless-non-atomic")
+            .with_subroutine(
+                NonAtomicSubroutineBuilder::new()
+                    .with_function(
+                        NonAtomicFunctionBuilder::new()
+                            .with_contract_address(LibraryAccountType::Addr(
+                                test_library_contract.clone(),
+                            ))
+                            .with_message_details(MessageDetails {
+                                message_type: MessageType::CosmwasmExecuteMsg,
+                                message: Message {
+                                    name: "will_succeed".to_string(),
+                                    params_restrictions: None,
+                                },
+                            })
+                            .build(),
+                    )
+                    .with_function(
+                        NonAtomicFunctionBuilder::new()
+                            .with_contract_address(LibraryAccountType::Addr(
+                                test_library_contract.clone(),
+                            ))
+                            .with_message_details(MessageDetails {
+                                message_type: MessageType::CosmwasmExecuteMsg,
+                                message: Message {
+                                    name: "will_succeed".to_string(),
+                                    params_restrictions: None,
+                                },
+                            })
+                            .build(),
                     )
                     .build(),
             )
@@ -2533,6 +3208,7 @@ fn reject_and_confirm_non_atomic_function_with_callback() {
                         .with_retry_logic(RetryLogic {
                             times: RetryTimes::Indefinitely,
                             interval: Duration::Time(2),
+                            backoff: RetryBackoff::Fixed,
                         })
                         .with_message_details(MessageDetails {
                             message_type: MessageType::CosmwasmExecuteMsg,
@@ -3426,3 +4102,473 @@ fn expired_non_atomic_batch() {
         ExecutionResult::Expired(1)
     );
 }
+
+#[test]
+fn owner_removing_and_flushing_pending_batches() {
+    let setup = NeutronTestAppBuilder::new().build().unwrap();
+
+    let wasm = Wasm::new(&setup.app);
+
+    let (authorization_contract, processor_contract) =
+        store_and_instantiate_authorization_with_processor_contract(
+            &setup.app,
+            &setup.owner_accounts[0],
+            setup.owner_addr.to_string(),
+            vec![setup.subowner_addr.to_string()],
+        );
+    let test_library_contract =
+        store_and_instantiate_test_library(&wasm, &setup.owner_accounts[0], None);
+
+    let will_succeed = || {
+        NonAtomicFunctionBuilder::new()
+            .with_contract_address(LibraryAccountType::Addr(test_library_contract.clone()))
+            .with_message_details(MessageDetails {
+                message_type: MessageType::CosmwasmExecuteMsg,
+                message: Message {
+                    name: "will_succeed".to_string(),
+                    params_restrictions: None,
+                },
+            })
+            .build()
+    };
+
+    // Batches of two functions, so that we can have one partially executed
+    let authorizations = vec![AuthorizationBuilder::new()
+        .with_label("permissionless")
+        .with_max_concurrent_executions(10)
+        .with_subroutine(
+            NonAtomicSubroutineBuilder::new()
+                .with_function(will_succeed())
+                .with_function(will_succeed())
+                .build(),
+        )
+        .build()];
+
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionedAction(PermissionedMsg::CreateAuthorizations { authorizations }),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    let binary = Binary::from(
+        serde_json::to_vec(&TestLibraryExecuteMsg::WillSucceed { execution_id: None }).unwrap(),
+    );
+    let message = ProcessorMessage::CosmwasmExecuteMsg { msg: binary };
+
+    let send_msgs = || {
+        wasm.execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionlessAction(PermissionlessMsg::SendMsgs {
+                label: "permissionless".to_string(),
+                messages: vec![message.clone(), message.clone()],
+                ttl: None,
+            }),
+            &[],
+            &setup.user_accounts[0],
+        )
+        .unwrap();
+    };
+    let query_queue_ids = || {
+        wasm.query::<ProcessorQueryMsg, Vec<MessageBatch>>(
+            &processor_contract,
+            &ProcessorQueryMsg::GetQueue {
+                from: None,
+                to: None,
+                priority: Priority::Medium,
+            },
+        )
+        .unwrap()
+        .iter()
+        .map(|batch| batch.id)
+        .collect::<Vec<u64>>()
+    };
+
+    for _ in 0..3 {
+        send_msgs();
+    }
+    let ids = query_queue_ids();
+    assert_eq!(ids.len(), 3);
+
+    // Remove the one in the middle
+    let response = wasm
+        .execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionedAction(PermissionedMsg::RemoveMsg {
+                domain: Domain::Main,
+                id: ids[1],
+            }),
+            &[],
+            &setup.owner_accounts[0],
+        )
+        .unwrap();
+    assert!(response.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "removed_ids" && attr.value == ids[1].to_string())));
+    assert_eq!(query_queue_ids(), vec![ids[0], ids[2]]);
+
+    // It's not in the queues anymore, so it can't be removed again
+    let error = wasm
+        .execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionedAction(PermissionedMsg::RemoveMsg {
+                domain: Domain::Main,
+                id: ids[1],
+            }),
+            &[],
+            &setup.owner_accounts[0],
+        )
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains(&ProcessorContractError::BatchNotEnqueued(ids[1]).to_string()));
+
+    // Only the owner and subowners can remove batches
+    let error = wasm
+        .execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionedAction(PermissionedMsg::Flush {
+                domain: Domain::Main,
+            }),
+            &[],
+            &setup.user_accounts[0],
+        )
+        .unwrap_err();
+    assert!(error.to_string().contains(
+        ContractError::Unauthorized(AuthorizationUnauthorizedReason::NotAllowed {})
+            .to_string()
+            .as_str()
+    ));
+
+    // Flushing empties the queue
+    let response = wasm
+        .execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionedAction(PermissionedMsg::Flush {
+                domain: Domain::Main,
+            }),
+            &[],
+            &setup.owner_accounts[1],
+        )
+        .unwrap();
+    assert!(response
+        .events
+        .iter()
+        .any(|event| event.attributes.iter().any(
+            |attr| attr.key == "removed_ids" && attr.value == format!("{},{}", ids[0], ids[2])
+        )));
+    assert!(query_queue_ids().is_empty());
+
+    let query_callbacks = wasm
+        .query::<QueryMsg, Vec<ProcessorCallbackInfo>>(
+            &authorization_contract,
+            &QueryMsg::ProcessorCallbacks {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_callbacks.len(), 3);
+    assert!(query_callbacks
+        .iter()
+        .all(|callback| callback.execution_result == ExecutionResult::RemovedByOwner));
+
+    // A batch that already executed its first function is in flight and stays where it is
+    send_msgs();
+    send_msgs();
+    let ids = query_queue_ids();
+    wasm.execute::<ProcessorExecuteMsg>(
+        &processor_contract,
+        &ProcessorExecuteMsg::PermissionlessAction(ProcessorPermissionlessMsg::Tick {}),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+    assert_eq!(query_queue_ids(), vec![ids[1], ids[0]]);
+
+    let error = wasm
+        .execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionedAction(PermissionedMsg::RemoveMsg {
+                domain: Domain::Main,
+                id: ids[0],
+            }),
+            &[],
+            &setup.owner_accounts[0],
+        )
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains(&ProcessorContractError::BatchInFlight(ids[0]).to_string()));
+
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionedAction(PermissionedMsg::Flush {
+            domain: Domain::Main,
+        }),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+    assert_eq!(query_queue_ids(), vec![ids[0]]);
+}
+
+#[test]
+fn owner_purging_and_requeuing_dead_letters() {
+    let setup = NeutronTestAppBuilder::new().build().unwrap();
+
+    let wasm = Wasm::new(&setup.app);
+
+    let (authorization_contract, processor_contract) =
+        store_and_instantiate_authorization_with_processor_contract(
+            &setup.app,
+            &setup.owner_accounts[0],
+            setup.owner_addr.to_string(),
+            vec![setup.subowner_addr.to_string()],
+        );
+    let test_library_contract =
+        store_and_instantiate_test_library(&wasm, &setup.owner_accounts[0], None);
+
+    // The second function fails until we modify the contract to succeed and is only retried once
+    let authorizations = vec![AuthorizationBuilder::new()
+        .with_label("permissionless")
+        .with_max_concurrent_executions(10)
+        .with_subroutine(
+            NonAtomicSubroutineBuilder::new()
+                .with_function(
+                    NonAtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr(
+                            test_library_contract.clone(),
+                        ))
+                        .with_message_details(MessageDetails {
+                            message_type: MessageType::CosmwasmExecuteMsg,
+                            message: Message {
+                                name: "will_succeed".to_string(),
+                                params_restrictions: None,
+                            },
+                        })
+                        .build(),
+                )
+                .with_function(
+                    NonAtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr(
+                            test_library_contract.clone(),
+                        ))
+                        .with_retry_logic(RetryLogic {
+                            times: RetryTimes::Amount(1),
+                            interval: Duration::Time(2),
+                            backoff: RetryBackoff::Fixed,
+                        })
+                        .with_message_details(MessageDetails {
+                            message_type: MessageType::CosmwasmExecuteMsg,
+                            message: Message {
+                                name: "will_succeed_if_true".to_string(),
+                                params_restrictions: None,
+                            },
+                        })
+                        .build(),
+                )
+                .build(),
+        )
+        .build()];
+
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionedAction(PermissionedMsg::CreateAuthorizations { authorizations }),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    let binary = Binary::from(
+        serde_json::to_vec(&TestLibraryExecuteMsg::WillSucceed { execution_id: None }).unwrap(),
+    );
+    let message1 = ProcessorMessage::CosmwasmExecuteMsg { msg: binary };
+    let binary =
+        Binary::from(serde_json::to_vec(&TestLibraryExecuteMsg::WillSucceedIfTrue {}).unwrap());
+    let message2 = ProcessorMessage::CosmwasmExecuteMsg { msg: binary };
+
+    for _ in 0..2 {
+        wasm.execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionlessAction(PermissionlessMsg::SendMsgs {
+                label: "permissionless".to_string(),
+                messages: vec![message1.clone(), message2.clone()],
+                ttl: None,
+            }),
+            &[],
+            &setup.user_accounts[0],
+        )
+        .unwrap();
+    }
+
+    let tick = || {
+        wasm.execute::<ProcessorExecuteMsg>(
+            &processor_contract,
+            &ProcessorExecuteMsg::PermissionlessAction(ProcessorPermissionlessMsg::Tick {}),
+            &[],
+            &setup.owner_accounts[0],
+        )
+        .unwrap();
+    };
+    let query_dead_letters = || {
+        wasm.query::<ProcessorQueryMsg, Vec<DeadLetter>>(
+            &processor_contract,
+            &ProcessorQueryMsg::DeadLetterQueue {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap()
+    };
+    let query_callbacks = || {
+        wasm.query::<QueryMsg, Vec<ProcessorCallbackInfo>>(
+            &authorization_contract,
+            &QueryMsg::ProcessorCallbacks {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap()
+    };
+
+    // Each batch executes its first function, fails the second one and fails its retry
+    for _ in 0..6 {
+        tick();
+        setup.app.increase_time(5);
+    }
+
+    let dead_letters = query_dead_letters();
+    assert_eq!(dead_letters.len(), 2);
+    assert!(dead_letters
+        .iter()
+        .all(|dead_letter| dead_letter.function_index == 1));
+    let ids = dead_letters
+        .iter()
+        .map(|dead_letter| dead_letter.batch.id)
+        .collect::<Vec<u64>>();
+
+    // Only the owner and subowners can purge dead letters
+    let error = wasm
+        .execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionedAction(PermissionedMsg::PurgeDeadLetters {
+                domain: Domain::Main,
+                ids: vec![ids[0]],
+            }),
+            &[],
+            &setup.user_accounts[0],
+        )
+        .unwrap_err();
+    assert!(error.to_string().contains(
+        ContractError::Unauthorized(AuthorizationUnauthorizedReason::NotAllowed {})
+            .to_string()
+            .as_str()
+    ));
+
+    // Purge the first one
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionedAction(PermissionedMsg::PurgeDeadLetters {
+            domain: Domain::Main,
+            ids: vec![ids[0]],
+        }),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+    assert_eq!(query_dead_letters().len(), 1);
+
+    // It's not in the dead letter queue anymore, so it can't be purged or requeued
+    let error = wasm
+        .execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionedAction(PermissionedMsg::PurgeDeadLetters {
+                domain: Domain::Main,
+                ids: vec![ids[0]],
+            }),
+            &[],
+            &setup.owner_accounts[0],
+        )
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains(&ProcessorContractError::DeadLetterNotFound(ids[0]).to_string()));
+
+    let error = wasm
+        .execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionedAction(PermissionedMsg::RequeueDeadLetter { id: ids[0] }),
+            &[],
+            &setup.owner_accounts[0],
+        )
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains(&ProcessorContractError::DeadLetterNotFound(ids[0]).to_string()));
+
+    // Requeue the second one once the function can succeed, it resumes from the function that failed
+    wasm.execute::<TestLibraryExecuteMsg>(
+        &test_library_contract,
+        &TestLibraryExecuteMsg::SetCondition { condition: true },
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+
+    wasm.execute::<ExecuteMsg>(
+        &authorization_contract,
+        &ExecuteMsg::PermissionedAction(PermissionedMsg::RequeueDeadLetter { id: ids[1] }),
+        &[],
+        &setup.owner_accounts[0],
+    )
+    .unwrap();
+    assert!(query_dead_letters().is_empty());
+
+    let query_med_prio_queue = wasm
+        .query::<ProcessorQueryMsg, Vec<MessageBatch>>(
+            &processor_contract,
+            &ProcessorQueryMsg::GetQueue {
+                from: None,
+                to: None,
+                priority: Priority::Medium,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_med_prio_queue.len(), 1);
+    assert_eq!(query_med_prio_queue[0].id, ids[1]);
+    assert_eq!(query_med_prio_queue[0].retry, None);
+
+    let callback = query_callbacks()
+        .into_iter()
+        .find(|callback| callback.execution_id == ids[1])
+        .unwrap();
+    assert_eq!(callback.execution_result, ExecutionResult::InProcess);
+
+    tick();
+
+    let callbacks = query_callbacks();
+    let callback = callbacks
+        .iter()
+        .find(|callback| callback.execution_id == ids[1])
+        .unwrap();
+    assert_eq!(callback.execution_result, ExecutionResult::Success);
+
+    // A batch that didn't end up in the dead letter queue can't be requeued
+    let error = wasm
+        .execute::<ExecuteMsg>(
+            &authorization_contract,
+            &ExecuteMsg::PermissionedAction(PermissionedMsg::RequeueDeadLetter { id: ids[1] }),
+            &[],
+            &setup.owner_accounts[0],
+        )
+        .unwrap_err();
+    assert!(error.to_string().contains(
+        &ContractError::NotDeadLettered {
+            execution_id: ids[1]
+        }
+        .to_string()
+    ));
+}
@@ -85,7 +85,7 @@ fn encode_subroutine(subroutine: Subroutine) -> StdResult<solidity_types::Subrou
             }
 
             // Encode the retry logic
-            let retry_logic = encode_retry_logic(atomic_subroutine.retry_logic);
+            let retry_logic = encode_retry_logic(atomic_subroutine.retry_logic)?;
 
             let atomic_subroutine_encoded = solidity_types::AtomicSubroutine {
                 functions,
@@ -118,7 +118,7 @@ fn encode_subroutine(subroutine: Subroutine) -> StdResult<solidity_types::Subrou
                 functions.push(solidity_types::NonAtomicFunction {
                     contractAddress: Address::from_str(&function.contract_address.to_string()?)
                         .map_err(|e| StdError::generic_err(e.to_string()))?,
-                    retryLogic: encode_retry_logic(function.retry_logic),
+                    retryLogic: encode_retry_logic(function.retry_logic)?,
                     callbackConfirmation: callback_confirmation,
                 });
             }
@@ -136,8 +136,15 @@ fn encode_subroutine(subroutine: Subroutine) -> StdResult<solidity_types::Subrou
 
 fn encode_retry_logic(
     retry_logic: Option<valence_authorization_utils::function::RetryLogic>,
-) -> solidity_types::RetryLogic {
+) -> StdResult<solidity_types::RetryLogic> {
     if let Some(retry_logic) = retry_logic {
+        // The EVM processor only supports a fixed interval between retries
+        if retry_logic.backoff != valence_authorization_utils::function::RetryBackoff::Fixed {
+            return Err(StdError::generic_err(
+                "Exponential retry backoff is not supported by the EVM processor",
+            ));
+        }
+
         let times = match retry_logic.times {
             valence_authorization_utils::function::RetryTimes::Indefinitely => {
                 solidity_types::RetryTimes {
@@ -164,10 +171,10 @@ fn encode_retry_logic(
             },
         };
 
-        solidity_types::RetryLogic { times, interval }
+        Ok(solidity_types::RetryLogic { times, interval })
     } else {
         // Default retry logic when none is provided
-        solidity_types::RetryLogic {
+        Ok(solidity_types::RetryLogic {
             times: solidity_types::RetryTimes {
                 retryType: solidity_types::RetryTimesType::NoRetry,
                 amount: 0,
@@ -176,7 +183,7 @@ fn encode_retry_logic(
                 durationType: solidity_types::DurationType::Time,
                 value: 0,
             },
-        }
+        })
     }
 }
 
@@ -8,7 +8,10 @@ use valence_library_utils::{msg::ExecuteMsg, LibraryAccountType};
 
 use crate::parse_address;
 
-use super::{get_update_ownership_call, get_update_processor_call};
+use super::{
+    get_nonce_call, get_owner_threshold_call, get_pause_call, get_record_error_call,
+    get_update_ownership_call, get_update_processor_call,
+};
 
 // We need to define a config and functions for this library as we don't have a CosmWasm equivalent
 #[cw_serde]
@@ -70,7 +73,13 @@ pub fn encode(msg: &Binary) -> StdResult<Vec<u8>> {
             };
             Ok(call.abi_encode())
         }
+        ExecuteMsg::ProcessFunctionWithNonce { .. } => get_nonce_call(),
         ExecuteMsg::UpdateProcessor { processor } => get_update_processor_call(&processor),
         ExecuteMsg::UpdateOwnership(action) => get_update_ownership_call(action),
+        ExecuteMsg::Pause {} | ExecuteMsg::Unpause {} => get_pause_call(),
+        ExecuteMsg::UpdateOwnerThreshold { .. }
+        | ExecuteMsg::CancelApproval { .. }
+        | ExecuteMsg::AuthorizeMigration {} => get_owner_threshold_call(),
+        ExecuteMsg::RecordError { .. } => get_record_error_call(),
     }
 }
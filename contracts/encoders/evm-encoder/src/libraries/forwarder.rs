@@ -10,7 +10,10 @@ use valence_library_utils::{msg::ExecuteMsg, LibraryAccountType};
 
 use crate::parse_address;
 
-use super::{get_update_ownership_call, get_update_processor_call};
+use super::{
+    get_nonce_call, get_owner_threshold_call, get_pause_call, get_record_error_call,
+    get_update_ownership_call, get_update_processor_call,
+};
 
 // We need to define a new config that will be used to encode the message because the one from the CW library is not the same as the one from the Solidity library
 #[cw_serde]
@@ -51,6 +54,10 @@ pub fn encode(msg: &Binary) -> StdResult<Vec<u8>> {
                     // No need to ABI encode the selector as it is already the ABI encoded function signature
                     Ok(forwardCall::SELECTOR.to_vec())
                 }
+                // The EVM forwarder only moves fungible tokens
+                FunctionMsgs::ForwardNft { .. } => Err(StdError::generic_err(
+                    "ForwardNft is not supported".to_string(),
+                )),
             }
         }
         ExecuteMsg::UpdateConfig { new_config } => {
@@ -97,7 +104,13 @@ pub fn encode(msg: &Binary) -> StdResult<Vec<u8>> {
             };
             Ok(call.abi_encode())
         }
+        ExecuteMsg::ProcessFunctionWithNonce { .. } => get_nonce_call(),
         ExecuteMsg::UpdateProcessor { processor } => get_update_processor_call(&processor),
         ExecuteMsg::UpdateOwnership(action) => get_update_ownership_call(action),
+        ExecuteMsg::Pause {} | ExecuteMsg::Unpause {} => get_pause_call(),
+        ExecuteMsg::UpdateOwnerThreshold { .. }
+        | ExecuteMsg::CancelApproval { .. }
+        | ExecuteMsg::AuthorizeMigration {} => get_owner_threshold_call(),
+        ExecuteMsg::RecordError { .. } => get_record_error_call(),
     }
 }
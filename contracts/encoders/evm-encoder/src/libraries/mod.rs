@@ -26,6 +26,34 @@ pub fn get_update_processor_call(processor_addr: &str) -> StdResult<Vec<u8>> {
     Ok(call.abi_encode())
 }
 
+/// Pausing is not implemented by the EVM libraries, so there is no call to encode
+pub fn get_pause_call() -> StdResult<Vec<u8>> {
+    Err(StdError::generic_err(
+        "Pause and Unpause are not supported".to_string(),
+    ))
+}
+
+/// Execution nonces are not implemented by the EVM libraries, so there is no call to encode
+pub fn get_nonce_call() -> StdResult<Vec<u8>> {
+    Err(StdError::generic_err(
+        "ProcessFunctionWithNonce is not supported".to_string(),
+    ))
+}
+
+/// The EVM libraries are owned by a single account, so owner thresholds can't be encoded
+pub fn get_owner_threshold_call() -> StdResult<Vec<u8>> {
+    Err(StdError::generic_err(
+        "UpdateOwnerThreshold, CancelApproval and AuthorizeMigration are not supported".to_string(),
+    ))
+}
+
+/// The errors of the EVM libraries are not recorded by their processor
+pub fn get_record_error_call() -> StdResult<Vec<u8>> {
+    Err(StdError::generic_err(
+        "RecordError is not supported".to_string(),
+    ))
+}
+
 /// Gets the call to update the ownership of the library. The Ownable solidity contract does only implement the transferOwnership and renounceOwnership functions.
 pub fn get_update_ownership_call(action: cw_ownable::Action) -> StdResult<Vec<u8>> {
     match action {
@@ -9,7 +9,7 @@ use valence_encoder_utils::processor::solidity_types::{
 pub fn encode(queue_position: u64, priority: Priority) -> StdResult<Binary> {
     let message = EvictMsgs {
         queuePosition: queue_position,
-        priority: priority.into(),
+        priority: priority.try_into()?,
     };
 
     let processor_message = ProcessorMessage {
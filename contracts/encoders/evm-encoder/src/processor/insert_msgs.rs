@@ -19,7 +19,7 @@ pub fn encode(
     let message = InsertMsgs {
         executionId: execution_id,
         queuePosition: queue_position,
-        priority: priority.into(),
+        priority: priority.try_into()?,
         subroutine: encode_subroutine(subroutine)?,
         expirationTime: expiration_time.unwrap_or(0),
         messages: messages
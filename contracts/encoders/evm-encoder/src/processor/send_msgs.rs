@@ -17,7 +17,7 @@ pub fn encode(
 ) -> StdResult<Binary> {
     let message = SendMsgs {
         executionId: execution_id,
-        priority: priority.into(),
+        priority: priority.try_into()?,
         subroutine: encode_subroutine(subroutine)?,
         expirationTime: expiration_time.unwrap_or(0),
         messages: messages
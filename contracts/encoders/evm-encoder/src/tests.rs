@@ -10,7 +10,7 @@ use valence_authorization_utils::{
     authorization::{AtomicSubroutine, NonAtomicSubroutine, Priority, Subroutine},
     authorization_message::{Message, MessageDetails, MessageType},
     domain::Domain,
-    function::{AtomicFunction, NonAtomicFunction, RetryLogic, RetryTimes},
+    function::{AtomicFunction, NonAtomicFunction, RetryBackoff, RetryLogic, RetryTimes},
     msg::InternalAuthorizationMsg,
 };
 use valence_encoder_utils::{
@@ -149,11 +149,14 @@ fn test_send_msgs() {
                 params_restrictions: None,
             },
         },
+        gas_limit: None,
+        scope: None,
     };
 
     let retry_logic = Some(RetryLogic {
         times: RetryTimes::Amount(3),
         interval: Duration::Height(100),
+        backoff: RetryBackoff::Fixed,
     });
 
     let atomic_subroutine = AtomicSubroutine {
@@ -261,8 +264,11 @@ fn test_insert_msgs() {
         retry_logic: Some(RetryLogic {
             times: RetryTimes::Amount(3),
             interval: Duration::Height(100),
+            backoff: RetryBackoff::Fixed,
         }),
         callback_confirmation: None,
+        gas_limit: None,
+        scope: None,
     };
 
     let non_atomic_subroutine = NonAtomicSubroutine {
@@ -357,6 +363,34 @@ fn test_evict_msgs() {
     );
 }
 
+#[test]
+fn test_evict_msgs_low_priority_not_supported() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = message_info(&Addr::unchecked("any"), &[]);
+
+    instantiate(deps.as_mut(), env.clone(), info.clone(), Empty {}).unwrap();
+
+    // The EVM processor has no Low priority queue
+    let evict_msgs = ProcessorMessageToEncode::EvictMsgs {
+        queue_position: 0,
+        priority: Priority::Low,
+    };
+
+    let err = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Encode {
+            message: evict_msgs,
+        },
+    )
+    .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("Low priority is not supported by the EVM processor"));
+}
+
 #[test]
 fn test_resume() {
     let mut deps = mock_dependencies();
@@ -419,10 +453,12 @@ fn test_send_msgs_with_different_retry_logic() {
         RetryLogic {
             times: RetryTimes::Indefinitely,
             interval: Duration::Time(60),
+            backoff: RetryBackoff::Fixed,
         },
         RetryLogic {
             times: RetryTimes::Amount(65),
             interval: Duration::Height(10),
+            backoff: RetryBackoff::Fixed,
         },
     ];
 
@@ -439,6 +475,8 @@ fn test_send_msgs_with_different_retry_logic() {
                     params_restrictions: None,
                 },
             },
+            gas_limit: None,
+            scope: None,
         };
 
         let atomic_subroutine = AtomicSubroutine {
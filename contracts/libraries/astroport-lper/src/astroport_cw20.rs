@@ -1,45 +1,54 @@
 use crate::msg::Config;
 
-use cosmwasm_std::{coin, CosmosMsg, WasmMsg};
+use cosmwasm_std::{coin, Addr, CosmosMsg, WasmMsg};
 use cosmwasm_std::{to_json_binary, DepsMut, Uint128};
 use valence_astroport_utils::astroport_cw20_lp_token::{
-    Asset, AssetInfo, ExecuteMsg, PairType, PoolQueryMsg, SimulationResponse,
+    Asset, AssetInfo, ExecuteMsg, PoolQueryMsg, SimulationResponse,
 };
-use valence_astroport_utils::PoolType;
-use valence_library_utils::error::LibraryError;
+use valence_astroport_utils::{PoolKind, PoolType};
+use valence_library_utils::{error::LibraryError, liquidity_utils::AssetData};
 
 pub fn create_provide_liquidity_msg(
     cfg: &Config,
-    amount1: u128,
-    amount2: u128,
+    amounts: &[u128],
 ) -> Result<CosmosMsg, LibraryError> {
+    create_provide_liquidity_msg_to_pool(cfg, &cfg.pool_addr, &cfg.lp_config.asset_data, amounts)
+}
+
+/// Creates a provide liquidity message for one of the pools the provision is allocated to
+pub fn create_provide_liquidity_msg_to_pool(
+    cfg: &Config,
+    pool_addr: &Addr,
+    asset_data: &AssetData,
+    amounts: &[u128],
+) -> Result<CosmosMsg, LibraryError> {
+    // Amounts are given in the same order as the assets of the asset data
+    let assets = asset_data.assets();
+
     let execute_msg = ExecuteMsg::ProvideLiquidity {
-        assets: vec![
-            Asset {
+        assets: assets
+            .iter()
+            .zip(amounts)
+            .map(|(denom, amount)| Asset {
                 info: AssetInfo::NativeToken {
-                    denom: cfg.lp_config.asset_data.asset1.to_string(),
+                    denom: denom.to_string(),
                 },
-                amount: Uint128::new(amount1),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: cfg.lp_config.asset_data.asset2.to_string(),
-                },
-                amount: Uint128::new(amount2),
-            },
-        ],
-        slippage_tolerance: None,
+                amount: Uint128::new(*amount),
+            })
+            .collect(),
+        slippage_tolerance: cfg.lp_config.provide_slippage_tolerance(),
         auto_stake: Some(false),
         receiver: Some(cfg.output_addr.to_string()),
     };
 
     Ok(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: cfg.pool_addr.to_string(),
+        contract_addr: pool_addr.to_string(),
         msg: to_json_binary(&execute_msg)?,
-        funds: vec![
-            coin(amount1, &cfg.lp_config.asset_data.asset1),
-            coin(amount2, &cfg.lp_config.asset_data.asset2),
-        ],
+        funds: assets
+            .iter()
+            .zip(amounts)
+            .map(|(denom, amount)| coin(*amount, denom.as_str()))
+            .collect(),
     }))
 }
 
@@ -49,11 +58,11 @@ pub fn create_single_sided_liquidity_msg(
     asset_balance: &cosmwasm_std::Coin,
     other_asset: &cosmwasm_std::Coin,
 ) -> Result<Vec<CosmosMsg>, LibraryError> {
-    match cfg.lp_config.pool_type.clone() {
-        PoolType::Cw20LpToken(pair_type) => match pair_type {
-            PairType::Xyk {} => create_xyk_liquidity_msg(deps, cfg, asset_balance, other_asset),
-            PairType::Stable {} | PairType::Custom(_) => {
-                create_stable_or_custom_liquidity_msg(cfg, asset_balance, other_asset)
+    match &cfg.lp_config.pool_type {
+        PoolType::Cw20LpToken(_) => match cfg.lp_config.pool_type.kind()? {
+            PoolKind::Xyk => create_xyk_liquidity_msg(deps, cfg, asset_balance, other_asset),
+            PoolKind::Stable | PoolKind::ConcentratedLiquidity => {
+                create_stable_or_custom_liquidity_msg(cfg, asset_balance)
             }
         },
         _ => Err(LibraryError::ExecutionError(
@@ -112,7 +121,7 @@ fn create_xyk_liquidity_msg(
         contract_addr: cfg.pool_addr.to_string(),
         msg: to_json_binary(&ExecuteMsg::Swap {
             offer_asset: astroport_offer_asset.clone(),
-            max_spread: cfg.lp_config.max_spread,
+            max_spread: Some(cfg.lp_config.swap_max_spread()),
             belief_price: None,
             to: None,
             ask_asset_info: None,
@@ -132,7 +141,7 @@ fn create_xyk_liquidity_msg(
                     amount: Uint128::new(ask_asset.amount.u128()),
                 },
             ],
-            slippage_tolerance: None,
+            slippage_tolerance: cfg.lp_config.provide_slippage_tolerance(),
             auto_stake: Some(false),
             receiver: Some(cfg.output_addr.to_string()),
         })?,
@@ -145,31 +154,112 @@ fn create_xyk_liquidity_msg(
     Ok(vec![swap_msg, provide_liquidity_msg])
 }
 
-fn create_stable_or_custom_liquidity_msg(
+/// Swaps the offer asset for the other pool asset, then provides the remaining offer asset together with
+/// the other asset and the simulated amount received from the swap
+pub fn create_auto_balance_liquidity_msgs(
+    deps: &DepsMut,
     cfg: &Config,
-    asset_balance: &cosmwasm_std::Coin,
+    offer_asset: &cosmwasm_std::Coin,
+    remaining_offer_asset: &cosmwasm_std::Coin,
     other_asset: &cosmwasm_std::Coin,
 ) -> Result<Vec<CosmosMsg>, LibraryError> {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: asset_balance.denom.clone(),
-            },
-            amount: Uint128::new(asset_balance.amount.u128()),
+    let astroport_offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: offer_asset.denom.clone(),
         },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: other_asset.denom.clone(),
-            },
-            amount: Uint128::new(0),
+        amount: offer_asset.amount,
+    };
+
+    // We simulate the swap to know how much of the other asset we will receive
+    let simulation: SimulationResponse = deps.querier.query_wasm_smart(
+        &cfg.pool_addr,
+        &PoolQueryMsg::Simulation {
+            offer_asset: astroport_offer_asset.clone(),
+            ask_asset_info: None,
         },
-    ];
+    )?;
+
+    let other_amount = other_asset
+        .amount
+        .checked_add(simulation.return_amount)
+        .map_err(|error| LibraryError::ExecutionError(error.to_string()))?;
+
+    let swap_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: cfg.pool_addr.to_string(),
+        msg: to_json_binary(&ExecuteMsg::Swap {
+            offer_asset: astroport_offer_asset,
+            max_spread: Some(cfg.lp_config.swap_max_spread()),
+            belief_price: None,
+            to: None,
+            ask_asset_info: None,
+        })?,
+        funds: vec![offer_asset.clone()],
+    });
+
+    let provide_liquidity_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: cfg.pool_addr.to_string(),
+        msg: to_json_binary(&ExecuteMsg::ProvideLiquidity {
+            assets: vec![
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: remaining_offer_asset.denom.clone(),
+                    },
+                    amount: remaining_offer_asset.amount,
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: other_asset.denom.clone(),
+                    },
+                    amount: other_amount,
+                },
+            ],
+            slippage_tolerance: cfg.lp_config.provide_slippage_tolerance(),
+            auto_stake: Some(false),
+            receiver: Some(cfg.output_addr.to_string()),
+        })?,
+        // Zero amounts can't be sent as funds
+        funds: [
+            remaining_offer_asset.clone(),
+            coin(other_amount.u128(), other_asset.denom.clone()),
+        ]
+        .into_iter()
+        .filter(|c| !c.amount.is_zero())
+        .collect(),
+    });
+
+    Ok(vec![swap_msg, provide_liquidity_msg])
+}
+
+fn create_stable_or_custom_liquidity_msg(
+    cfg: &Config,
+    asset_balance: &cosmwasm_std::Coin,
+) -> Result<Vec<CosmosMsg>, LibraryError> {
+    // The provided asset goes first, followed by all the other pool assets with a zero amount
+    let mut assets = vec![Asset {
+        info: AssetInfo::NativeToken {
+            denom: asset_balance.denom.clone(),
+        },
+        amount: Uint128::new(asset_balance.amount.u128()),
+    }];
+    assets.extend(
+        cfg.lp_config
+            .asset_data
+            .assets()
+            .into_iter()
+            .filter(|denom| **denom != asset_balance.denom)
+            .map(|denom| Asset {
+                info: AssetInfo::NativeToken {
+                    denom: denom.to_string(),
+                },
+                amount: Uint128::new(0),
+            }),
+    );
 
     let msg = CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: cfg.pool_addr.to_string(),
         msg: to_json_binary(&ExecuteMsg::ProvideLiquidity {
             assets,
-            slippage_tolerance: None,
+            slippage_tolerance: cfg.lp_config.provide_slippage_tolerance(),
             auto_stake: Some(false),
             receiver: Some(cfg.output_addr.to_string()),
         })?,
@@ -1,17 +1,29 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    from_json, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError,
+    StdResult,
+};
 use valence_library_utils::{
     error::LibraryError,
-    msg::{ExecuteMsg, InstantiateMsg},
+    events::PoolUpdateEvent,
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg},
 };
 
-use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
+use crate::{
+    deposit_cap::{query_position, Valuation},
+    msg::{
+        Config, DepositCapResponse, FunctionMsgs, LibraryConfig, LibraryConfigUpdate,
+        ProvisionReply, QueryMsg,
+    },
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+pub const PROVISION_REPLY_ID: u64 = 1;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -24,18 +36,53 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
 ) -> Result<Response, LibraryError> {
-    valence_library_base::execute(
-        deps,
+    // Pool before a config update repointing the library, to record the change
+    let previous_pool = match &msg {
+        ExecuteMsg::UpdateConfig { new_config } if new_config.pool_addr.is_some() => {
+            Some(valence_library_base::load_config::<Config>(deps.storage)?.pool_addr)
+        }
+        _ => None,
+    };
+
+    let response = valence_library_base::execute(
+        deps.branch(),
         env,
         info,
         msg,
         functions::process_function,
         execute::update_config,
+    )?;
+
+    match previous_pool {
+        Some(old_pool) => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            if config.pool_addr == old_pool {
+                return Ok(response);
+            }
+            Ok(response.add_event(PoolUpdateEvent::new(old_pool, config.pool_addr)))
+        }
+        None => Ok(response),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
     )
 }
 
@@ -55,18 +102,28 @@ mod execute {
     }
 }
 
-mod functions {
-    use cosmwasm_std::{Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response, Uint128};
+pub(crate) mod functions {
+    use cosmwasm_std::{
+        to_json_vec, BankMsg, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+        SubMsg, Uint128, Uint256,
+    };
     use valence_astroport_utils::{
-        decimal_checked_ops::DecimalCheckedOps, get_pool_asset_amounts, query_pool, PoolType,
+        decimal_checked_ops::DecimalCheckedOps, get_pool_asset_amounts, get_pool_assets_amounts,
+        query_pool, PoolType,
     };
     use valence_library_utils::{
         error::LibraryError, execute_on_behalf_of, liquidity_utils::DecimalRange,
+        msg::SimulatedAmount, rounding::RoundingPolicy,
     };
 
     use crate::{
         astroport_cw20, astroport_native,
-        msg::{Config, FunctionMsgs},
+        contract::PROVISION_REPLY_ID,
+        deposit_cap::{ensure_within_deposit_cap, query_position},
+        msg::{
+            CheckedPoolAllocation, Config, FunctionMsgs, LpSharesCheck, ProvisionMode,
+            ProvisionReply,
+        },
     };
 
     pub fn process_function(
@@ -75,28 +132,575 @@ mod functions {
         _info: MessageInfo,
         msg: FunctionMsgs,
         cfg: Config,
+    ) -> Result<Response, LibraryError> {
+        // The LP tokens minted are checked against the minimum in the reply, allocated pools mint different LP tokens
+        let shares_check = match cfg.lp_config.min_lp_tokens_out {
+            Some(min_lp_tokens_out) if cfg.pool_allocations.is_empty() => Some(LpSharesCheck {
+                shares_before: query_position(deps.as_ref(), &cfg)?.shares,
+                min_lp_tokens_out,
+            }),
+            _ => None,
+        };
+
+        // The residuals of a provision of both assets are only known once Astroport has executed it
+        let refund = cfg.refund_account.is_some()
+            && provides_both_assets(&msg, &cfg.lp_config.provision_mode);
+
+        let response = provide_liquidity(deps, msg, cfg)?;
+        if shares_check.is_none() && !refund {
+            return Ok(response);
+        }
+        with_provision_reply(
+            response,
+            &ProvisionReply {
+                shares_check,
+                refund,
+            },
+        )
+    }
+
+    /// Whether the function provides the pool assets following the pool ratios, which leaves residuals
+    fn provides_both_assets(msg: &FunctionMsgs, provision_mode: &ProvisionMode) -> bool {
+        match msg {
+            FunctionMsgs::ProvideDoubleSidedLiquidity { .. } => true,
+            FunctionMsgs::ProvideSingleSidedLiquidity { .. } => false,
+            FunctionMsgs::ProvideLiquidity { .. } | FunctionMsgs::EnterPosition { .. } => {
+                matches!(provision_mode, ProvisionMode::Balanced)
+            }
+        }
+    }
+
+    /// Sends the provision to the input account as a submessage, so that the reply can check the LP tokens minted
+    /// and sweep the residuals
+    fn with_provision_reply(
+        mut response: Response,
+        provision_reply: &ProvisionReply,
+    ) -> Result<Response, LibraryError> {
+        if let Some(msg) = response.messages.pop() {
+            response.messages.push(
+                SubMsg::reply_on_success(msg.msg, PROVISION_REPLY_ID)
+                    .with_payload(to_json_vec(provision_reply)?),
+            );
+        }
+        Ok(response)
+    }
+
+    fn provide_liquidity(
+        deps: DepsMut,
+        msg: FunctionMsgs,
+        cfg: Config,
     ) -> Result<Response, LibraryError> {
         match msg {
             FunctionMsgs::ProvideDoubleSidedLiquidity {
                 expected_pool_ratio_range,
-            } => provide_double_sided_liquidity(deps, cfg, expected_pool_ratio_range),
+            } => provide_double_sided_liquidity(deps, cfg, expected_pool_ratio_range, &[]),
             FunctionMsgs::ProvideSingleSidedLiquidity {
                 asset,
                 limit,
                 expected_pool_ratio_range,
-            } => provide_single_sided_liquidity(deps, cfg, asset, limit, expected_pool_ratio_range),
+            } => provide_single_sided_liquidity(
+                deps,
+                cfg,
+                asset,
+                limit,
+                expected_pool_ratio_range,
+                &[],
+            ),
+            FunctionMsgs::ProvideLiquidity {
+                expected_pool_ratio_range,
+            } => provide_following_mode(deps, cfg, expected_pool_ratio_range, &[]),
+            FunctionMsgs::EnterPosition {
+                min_deposit,
+                expected_pool_ratio_range,
+            } => enter_position(deps, cfg, min_deposit, expected_pool_ratio_range),
         }
     }
 
+    /// Provides liquidity following the provision mode of the config.
+    /// The `deposits` are not held by the input account yet, but are moved to it before the provision.
+    fn provide_following_mode(
+        deps: DepsMut,
+        cfg: Config,
+        expected_pool_ratio_range: Option<DecimalRange>,
+        deposits: &[Coin],
+    ) -> Result<Response, LibraryError> {
+        match cfg.lp_config.provision_mode.clone() {
+            ProvisionMode::Balanced => {
+                provide_double_sided_liquidity(deps, cfg, expected_pool_ratio_range, deposits)
+            }
+            ProvisionMode::SingleSided { asset } => provide_single_sided_liquidity(
+                deps,
+                cfg,
+                asset,
+                None,
+                expected_pool_ratio_range,
+                deposits,
+            ),
+            ProvisionMode::AutoBalance => {
+                provide_auto_balanced_liquidity(deps, cfg, expected_pool_ratio_range, deposits)
+            }
+        }
+    }
+
+    /// Moves the pool assets of the deposit account to the input account and provides them in the same response,
+    /// so that the deposit is reverted along with the provision if the provision fails (e.g. on slippage)
+    fn enter_position(
+        deps: DepsMut,
+        cfg: Config,
+        min_deposit: Option<Coin>,
+        expected_pool_ratio_range: Option<DecimalRange>,
+    ) -> Result<Response, LibraryError> {
+        let deposit_addr = cfg.deposit_addr.clone().ok_or_else(|| {
+            LibraryError::ExecutionError(
+                "No deposit account configured to enter the position from".to_string(),
+            )
+        })?;
+
+        // Only the pool assets held by the deposit account are moved
+        let mut deposits = vec![];
+        for denom in cfg.lp_config.asset_data.assets() {
+            let balance = deps.querier.query_balance(&deposit_addr, denom)?;
+            if !balance.amount.is_zero() {
+                deposits.push(balance);
+            }
+        }
+
+        if let Some(min_deposit) = &min_deposit {
+            if !cfg.lp_config.asset_data.contains(&min_deposit.denom) {
+                return Err(LibraryError::ExecutionError(format!(
+                    "Minimum deposit denom {} is not part of the pool assets",
+                    min_deposit.denom
+                )));
+            }
+            let deposited = deposits
+                .iter()
+                .find(|deposit| deposit.denom == min_deposit.denom)
+                .map(|deposit| deposit.amount)
+                .unwrap_or_default();
+            if deposited < min_deposit.amount {
+                return Err(LibraryError::ExecutionError(format!(
+                    "Deposit account holds {deposited}{}, below the minimum deposit of {min_deposit}",
+                    min_deposit.denom
+                )));
+            }
+        }
+        if deposits.is_empty() {
+            return Err(LibraryError::ExecutionError(
+                "Deposit account holds none of the pool assets".to_string(),
+            ));
+        }
+
+        let deposit_msg = execute_on_behalf_of(
+            vec![BankMsg::Send {
+                to_address: cfg.input_addr.to_string(),
+                amount: deposits.clone(),
+            }
+            .into()],
+            &deposit_addr,
+        )?;
+        let mut response = provide_following_mode(deps, cfg, expected_pool_ratio_range, &deposits)?;
+
+        // The deposit is moved before the provision, which stays the last message
+        response.messages.insert(0, SubMsg::new(deposit_msg));
+        Ok(response.add_attribute(
+            "deposit",
+            deposits
+                .iter()
+                .map(|deposit| deposit.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ))
+    }
+
     fn provide_double_sided_liquidity(
         deps: DepsMut,
         cfg: Config,
         expected_pool_ratio_range: Option<DecimalRange>,
+        deposits: &[Coin],
+    ) -> Result<Response, LibraryError> {
+        // The provision is split across the allocated pools, if any
+        if !cfg.pool_allocations.is_empty() {
+            return provide_allocated_liquidity(deps, cfg, expected_pool_ratio_range, deposits);
+        }
+
+        // Pools with more than two assets are provided proportionally to all of them
+        if !cfg.lp_config.asset_data.additional_assets.is_empty() {
+            return provide_multi_asset_liquidity(deps, cfg, expected_pool_ratio_range, deposits);
+        }
+
+        let balances =
+            query_two_asset_balances(deps.as_ref(), &cfg, expected_pool_ratio_range, deposits)?;
+        let (asset1_provide_amount, asset2_provide_amount) = balances.provide_amounts()?;
+
+        let provide_amounts = [asset1_provide_amount, asset2_provide_amount];
+        ensure_within_deposit_cap(deps.as_ref(), &cfg, &provide_amounts)?;
+        let cosmos_msg = create_provide_liquidity_msg(&cfg, &provide_amounts)?;
+        let input_account_msgs = execute_on_behalf_of(vec![cosmos_msg], &cfg.input_addr)?;
+
+        Ok(Response::new()
+            .add_message(input_account_msgs)
+            .add_attribute("method", "provide_double_sided_liquidity")
+            .add_attribute("asset1_amount", asset1_provide_amount.to_string())
+            .add_attribute("asset2_amount", asset2_provide_amount.to_string()))
+    }
+
+    fn provide_multi_asset_liquidity(
+        deps: DepsMut,
+        cfg: Config,
+        expected_pool_ratio_range: Option<DecimalRange>,
+        deposits: &[Coin],
+    ) -> Result<Response, LibraryError> {
+        let provide_amounts =
+            multi_asset_provide_amounts(deps.as_ref(), &cfg, expected_pool_ratio_range, deposits)?;
+        ensure_within_deposit_cap(deps.as_ref(), &cfg, &provide_amounts)?;
+
+        let cosmos_msg = create_provide_liquidity_msg(&cfg, &provide_amounts)?;
+        let input_account_msgs = execute_on_behalf_of(vec![cosmos_msg], &cfg.input_addr)?;
+
+        let mut response = Response::new()
+            .add_message(input_account_msgs)
+            .add_attribute("method", "provide_double_sided_liquidity");
+        for (index, amount) in provide_amounts.iter().enumerate() {
+            response =
+                response.add_attribute(format!("asset{}_amount", index + 1), amount.to_string());
+        }
+
+        Ok(response)
+    }
+
+    /// Amounts of each asset of a pool with more than two assets that a provision would provide
+    fn multi_asset_provide_amounts(
+        deps: Deps,
+        cfg: &Config,
+        expected_pool_ratio_range: Option<DecimalRange>,
+        deposits: &[Coin],
+    ) -> Result<Vec<u128>, LibraryError> {
+        let balances = query_all_asset_balances(deps, cfg, deposits)?;
+        let pool_balances = query_all_pool_asset_amounts(deps, cfg)?;
+
+        // The expected pool ratio range applies to the ratio between the first two assets
+        if let Some(range) = expected_pool_ratio_range {
+            let pool_asset_ratios = Decimal::checked_from_ratio(pool_balances[0], pool_balances[1])
+                .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+            range.contains(pool_asset_ratios)?;
+        }
+
+        calculate_multi_asset_provide_amounts(
+            &balances
+                .iter()
+                .map(|balance| balance.amount.u128())
+                .collect::<Vec<_>>(),
+            &pool_balances,
+        )
+    }
+
+    fn provide_allocated_liquidity(
+        deps: DepsMut,
+        cfg: Config,
+        expected_pool_ratio_range: Option<DecimalRange>,
+        deposits: &[Coin],
     ) -> Result<Response, LibraryError> {
+        let balances = query_all_asset_balances(deps.as_ref(), &cfg, deposits)?;
+        let (provide_msgs, provided_amounts) = create_allocated_provide_msgs(
+            deps.as_ref(),
+            &cfg,
+            &balances,
+            expected_pool_ratio_range,
+        )?;
+        let input_account_msgs = execute_on_behalf_of(provide_msgs, &cfg.input_addr)?;
+
+        let mut response = Response::new()
+            .add_message(input_account_msgs)
+            .add_attribute("method", "provide_allocated_liquidity");
+        for (index, amount) in provided_amounts.iter().enumerate() {
+            response =
+                response.add_attribute(format!("asset{}_amount", index + 1), amount.to_string());
+        }
+
+        Ok(response)
+    }
+
+    /// Creates a provide liquidity message for each allocated pool, which is given its weight of each balance
+    /// and provides it following its own pool ratios.
+    /// Returns the messages along with the total provided of each asset, in the order of the assets of the config.
+    pub(crate) fn create_allocated_provide_msgs(
+        deps: Deps,
+        cfg: &Config,
+        balances: &[Coin],
+        expected_pool_ratio_range: Option<DecimalRange>,
+    ) -> Result<(Vec<CosmosMsg>, Vec<u128>), LibraryError> {
+        let mut msgs = vec![];
+        let mut provided_amounts = vec![0u128; balances.len()];
+
+        for allocation in &cfg.pool_allocations {
+            let assets = allocation.asset_data.assets();
+            let denoms: Vec<&str> = assets.iter().map(|denom| denom.as_str()).collect();
+            let pool_response =
+                query_pool(deps, allocation.pool.as_str(), &cfg.lp_config.pool_type)?;
+            let pool_balances = get_pool_assets_amounts(pool_response, &denoms)?;
+
+            // The expected pool ratio range applies to the ratio between the first two assets of every pool
+            if let Some(range) = &expected_pool_ratio_range {
+                let pool_asset_ratios =
+                    Decimal::checked_from_ratio(pool_balances[0], pool_balances[1])
+                        .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+                range.contains(pool_asset_ratios)?;
+            }
+
+            // Index of each of the pool assets in the balances
+            let balance_indexes = denoms
+                .iter()
+                .map(|denom| {
+                    balances
+                        .iter()
+                        .position(|balance| balance.denom == *denom)
+                        .ok_or_else(|| {
+                            LibraryError::ExecutionError(format!(
+                                "Asset {denom} of pool {} is not part of the LP config assets",
+                                allocation.pool
+                            ))
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let allocated_amounts = balance_indexes
+                .iter()
+                .map(|&index| {
+                    RoundingPolicy::Down
+                        .apply(balances[index].amount, allocation.weight)
+                        .map(|amount| amount.u128())
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let provide_amounts =
+                calculate_multi_asset_provide_amounts(&allocated_amounts, &pool_balances)?;
+
+            msgs.push(create_allocation_provide_liquidity_msg(
+                cfg,
+                allocation,
+                &provide_amounts,
+            )?);
+            for (index, amount) in balance_indexes.into_iter().zip(provide_amounts) {
+                provided_amounts[index] += amount;
+            }
+        }
+
+        Ok((msgs, provided_amounts))
+    }
+
+    /// Calculates how much of each asset can be provided following the pool ratios.
+    /// The asset with the lowest balance relative to its pool balance is provided entirely and limits the others.
+    pub(crate) fn calculate_multi_asset_provide_amounts(
+        balances: &[u128],
+        pool_balances: &[u128],
+    ) -> Result<Vec<u128>, LibraryError> {
+        let limiting_index = (0..balances.len())
+            .min_by(|&a, &b| {
+                Uint128::new(balances[a])
+                    .full_mul(pool_balances[b])
+                    .cmp(&Uint128::new(balances[b]).full_mul(pool_balances[a]))
+            })
+            .ok_or_else(|| LibraryError::ExecutionError("No assets to provide".to_string()))?;
+
+        if balances[limiting_index] == 0 {
+            return Err(LibraryError::ExecutionError(
+                "All pool assets must be available to provide liquidity".to_string(),
+            ));
+        }
+
+        pool_balances
+            .iter()
+            .enumerate()
+            .map(|(index, pool_balance)| {
+                if index == limiting_index {
+                    return Ok(balances[index]);
+                }
+                Uint128::new(*pool_balance)
+                    .checked_multiply_ratio(balances[limiting_index], pool_balances[limiting_index])
+                    .map(|amount| amount.u128())
+                    .map_err(|e| LibraryError::ExecutionError(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Amount of each pool asset that `ProvideLiquidity` would provide now following the provision mode,
+    /// taken from the input account. Fails like the provision would, e.g. above the deposit cap.
+    pub(crate) fn simulate_amounts(
+        deps: Deps,
+        cfg: &Config,
+    ) -> Result<Vec<SimulatedAmount>, LibraryError> {
+        let amounts = match &cfg.lp_config.provision_mode {
+            ProvisionMode::Balanced if !cfg.pool_allocations.is_empty() => {
+                let balances = query_all_asset_balances(deps, cfg, &[])?;
+                create_allocated_provide_msgs(deps, cfg, &balances, None)?.1
+            }
+            ProvisionMode::Balanced if !cfg.lp_config.asset_data.additional_assets.is_empty() => {
+                multi_asset_provide_amounts(deps, cfg, None, &[])?
+            }
+            ProvisionMode::Balanced => {
+                let (asset1_amount, asset2_amount) =
+                    query_two_asset_balances(deps, cfg, None, &[])?.provide_amounts()?;
+                vec![asset1_amount, asset2_amount]
+            }
+            // The whole balance of the asset is provided
+            ProvisionMode::SingleSided { asset } => query_all_asset_balances(deps, cfg, &[])?
+                .into_iter()
+                .map(|balance| match balance.denom == *asset {
+                    true => balance.amount.u128(),
+                    false => 0,
+                })
+                .collect(),
+            // Once the excess is swapped both balances are provided
+            ProvisionMode::AutoBalance => {
+                let balances = query_two_asset_balances(deps, cfg, None, &[])?;
+                match balances.auto_balance_swap()? {
+                    (swap1, swap2) if swap1.is_zero() && swap2.is_zero() => {
+                        let (asset1_amount, asset2_amount) = balances.provide_amounts()?;
+                        vec![asset1_amount, asset2_amount]
+                    }
+                    _ => vec![balances.asset1.amount.u128(), balances.asset2.amount.u128()],
+                }
+            }
+        };
+        ensure_within_deposit_cap(deps, cfg, &amounts)?;
+
+        Ok(cfg
+            .lp_config
+            .asset_data
+            .assets()
+            .into_iter()
+            .zip(amounts)
+            .map(|(denom, amount)| match amount {
+                0 => SimulatedAmount::skipped(denom, "nothing_to_provide"),
+                amount => SimulatedAmount::new(denom, Uint128::new(amount)),
+            })
+            .collect())
+    }
+
+    /// Creates the messages sending the pool assets left in the input account after a provision
+    /// to the refund account, if configured. Assets without residual balance are skipped.
+    pub(crate) fn create_refund_msgs(cfg: &Config, residuals: &[Coin]) -> Vec<CosmosMsg> {
+        let Some(refund_account) = &cfg.refund_account else {
+            return vec![];
+        };
+
+        residuals
+            .iter()
+            .filter(|residual| !residual.amount.is_zero())
+            .map(|residual| {
+                BankMsg::Send {
+                    to_address: refund_account.to_string(),
+                    amount: vec![residual.clone()],
+                }
+                .into()
+            })
+            .collect()
+    }
+
+    /// Sweeps the pool assets left in the input account once the provision has executed, including
+    /// the assets the pool sent back, to the refund account
+    pub(crate) fn refund_residuals(deps: Deps, cfg: &Config) -> Result<Response, LibraryError> {
+        let mut residuals = vec![];
+        for denom in cfg.lp_config.asset_data.assets() {
+            residuals.push(deps.querier.query_balance(&cfg.input_addr, denom)?);
+        }
+
+        let refund_msgs = create_refund_msgs(cfg, &residuals);
+        if refund_msgs.is_empty() {
+            return Ok(Response::new());
+        }
+
+        let refunded = residuals
+            .iter()
+            .filter(|residual| !residual.amount.is_zero())
+            .map(|residual| residual.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        Ok(Response::new()
+            .add_message(execute_on_behalf_of(refund_msgs, &cfg.input_addr)?)
+            .add_attribute("refund", refunded))
+    }
+
+    fn query_all_asset_balances(
+        deps: Deps,
+        cfg: &Config,
+        deposits: &[Coin],
+    ) -> Result<Vec<Coin>, LibraryError> {
+        cfg.lp_config
+            .asset_data
+            .assets()
+            .into_iter()
+            .map(|denom| query_input_balance(deps, cfg, &denom, deposits))
+            .collect()
+    }
+
+    /// Balance of the input account once the `deposits` are moved to it
+    fn query_input_balance(
+        deps: Deps,
+        cfg: &Config,
+        denom: &str,
+        deposits: &[Coin],
+    ) -> Result<Coin, LibraryError> {
+        let mut balance = deps.querier.query_balance(&cfg.input_addr, denom)?;
+        if let Some(deposit) = deposits.iter().find(|deposit| deposit.denom == denom) {
+            balance.amount = balance
+                .amount
+                .checked_add(deposit.amount)
+                .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+        }
+        Ok(balance)
+    }
+
+    fn query_all_pool_asset_amounts(deps: Deps, cfg: &Config) -> Result<Vec<u128>, LibraryError> {
+        let pool_response = query_pool(deps, cfg.pool_addr.as_ref(), &cfg.lp_config.pool_type)?;
+        let assets = cfg.lp_config.asset_data.assets();
+        let denoms: Vec<&str> = assets.iter().map(|denom| denom.as_str()).collect();
+        get_pool_assets_amounts(pool_response, &denoms)
+    }
+
+    /// Balances of the two assets of the config in the input account and in the pool
+    struct TwoAssetBalances {
+        asset1: Coin,
+        asset2: Coin,
+        pool_asset1: u128,
+        pool_asset2: u128,
+        pool_ratio: Decimal,
+    }
+
+    impl TwoAssetBalances {
+        fn provide_amounts(&self) -> Result<(u128, u128), LibraryError> {
+            calculate_provide_amounts(
+                self.asset1.amount.u128(),
+                self.asset2.amount.u128(),
+                self.pool_asset1,
+                self.pool_asset2,
+                self.pool_ratio,
+            )
+        }
+
+        fn auto_balance_swap(&self) -> Result<(Uint128, Uint128), LibraryError> {
+            calculate_auto_balance_swap(
+                self.asset1.amount.u128(),
+                self.asset2.amount.u128(),
+                self.pool_asset1,
+                self.pool_asset2,
+            )
+        }
+    }
+
+    /// Queries the balances of both assets, checking that the pool ratio is in the expected range if any
+    fn query_two_asset_balances(
+        deps: Deps,
+        cfg: &Config,
+        expected_pool_ratio_range: Option<DecimalRange>,
+        deposits: &[Coin],
+    ) -> Result<TwoAssetBalances, LibraryError> {
         // Get balances of both assets from input account
-        let (balance_asset1, balance_asset2) = query_asset_balances(&deps, &cfg)?;
+        let balance_asset1 =
+            query_input_balance(deps, cfg, &cfg.lp_config.asset_data.asset1, deposits)?;
+        let balance_asset2 =
+            query_input_balance(deps, cfg, &cfg.lp_config.asset_data.asset2, deposits)?;
         // Get assets in the pool
-        let pool_response = query_pool(&deps, cfg.pool_addr.as_ref(), &cfg.lp_config.pool_type)?;
+        let pool_response = query_pool(deps, cfg.pool_addr.as_ref(), &cfg.lp_config.pool_type)?;
 
         // Get the amounts of each of the assets of our config in the pool
         let (pool_asset1_balance, pool_asset2_balance) = get_pool_asset_amounts(
@@ -115,34 +719,13 @@ mod functions {
             range.contains(pool_asset_ratios)?;
         }
 
-        let (asset1_provide_amount, asset2_provide_amount) = calculate_provide_amounts(
-            balance_asset1.amount.u128(),
-            balance_asset2.amount.u128(),
-            pool_asset1_balance,
-            pool_asset2_balance,
-            pool_asset_ratios,
-        )?;
-
-        let cosmos_msg =
-            create_provide_liquidity_msg(&cfg, asset1_provide_amount, asset2_provide_amount)?;
-
-        let input_account_msgs = execute_on_behalf_of(vec![cosmos_msg], &cfg.input_addr)?;
-
-        Ok(Response::new()
-            .add_message(input_account_msgs)
-            .add_attribute("method", "provide_double_sided_liquidity")
-            .add_attribute("asset1_amount", asset1_provide_amount.to_string())
-            .add_attribute("asset2_amount", asset2_provide_amount.to_string()))
-    }
-
-    fn query_asset_balances(deps: &DepsMut, cfg: &Config) -> Result<(Coin, Coin), LibraryError> {
-        let balance_asset1 = deps
-            .querier
-            .query_balance(&cfg.input_addr, &cfg.lp_config.asset_data.asset1)?;
-        let balance_asset2 = deps
-            .querier
-            .query_balance(&cfg.input_addr, &cfg.lp_config.asset_data.asset2)?;
-        Ok((balance_asset1, balance_asset2))
+        Ok(TwoAssetBalances {
+            asset1: balance_asset1,
+            asset2: balance_asset2,
+            pool_asset1: pool_asset1_balance,
+            pool_asset2: pool_asset2_balance,
+            pool_ratio: pool_asset_ratios,
+        })
     }
 
     fn calculate_provide_amounts(
@@ -177,53 +760,185 @@ mod functions {
 
     fn create_provide_liquidity_msg(
         cfg: &Config,
-        amount1: u128,
-        amount2: u128,
+        amounts: &[u128],
     ) -> Result<CosmosMsg, LibraryError> {
         match &cfg.lp_config.pool_type {
             PoolType::NativeLpToken(_) => {
-                astroport_native::create_provide_liquidity_msg(cfg, amount1, amount2)
-            }
-            PoolType::Cw20LpToken(_) => {
-                astroport_cw20::create_provide_liquidity_msg(cfg, amount1, amount2)
+                astroport_native::create_provide_liquidity_msg(cfg, amounts)
             }
+            PoolType::Cw20LpToken(_) => astroport_cw20::create_provide_liquidity_msg(cfg, amounts),
         }
     }
 
-    fn provide_single_sided_liquidity(
+    fn create_allocation_provide_liquidity_msg(
+        cfg: &Config,
+        allocation: &CheckedPoolAllocation,
+        amounts: &[u128],
+    ) -> Result<CosmosMsg, LibraryError> {
+        match &cfg.lp_config.pool_type {
+            PoolType::NativeLpToken(_) => astroport_native::create_provide_liquidity_msg_to_pool(
+                cfg,
+                &allocation.pool,
+                &allocation.asset_data,
+                amounts,
+            ),
+            PoolType::Cw20LpToken(_) => astroport_cw20::create_provide_liquidity_msg_to_pool(
+                cfg,
+                &allocation.pool,
+                &allocation.asset_data,
+                amounts,
+            ),
+        }
+    }
+
+    fn provide_auto_balanced_liquidity(
         deps: DepsMut,
         cfg: Config,
-        asset: String,
-        limit: Option<Uint128>,
         expected_pool_ratio_range: Option<DecimalRange>,
+        deposits: &[Coin],
     ) -> Result<Response, LibraryError> {
-        // Query asset balances and pool
-        let (balance_asset1, balance_asset2) = query_asset_balances(&deps, &cfg)?;
-        let pool_response = query_pool(&deps, cfg.pool_addr.as_ref(), &cfg.lp_config.pool_type)?;
+        let balances =
+            query_two_asset_balances(deps.as_ref(), &cfg, expected_pool_ratio_range, deposits)?;
+        let (asset1_swap_amount, asset2_swap_amount) = balances.auto_balance_swap()?;
 
-        // Get pool asset amounts
-        let (pool_asset1_balance, pool_asset2_balance) = get_pool_asset_amounts(
-            pool_response,
-            &cfg.lp_config.asset_data.asset1,
-            &cfg.lp_config.asset_data.asset2,
+        // Swap the excess of one of the assets (if any) and provide both
+        let (offer_balance, other_balance, swap_amount) = if !asset1_swap_amount.is_zero() {
+            (
+                balances.asset1.clone(),
+                balances.asset2.clone(),
+                asset1_swap_amount,
+            )
+        } else if !asset2_swap_amount.is_zero() {
+            (
+                balances.asset2.clone(),
+                balances.asset1.clone(),
+                asset2_swap_amount,
+            )
+        } else {
+            // Balances already follow the pool ratio
+            let (asset1_provide_amount, asset2_provide_amount) = balances.provide_amounts()?;
+
+            let provide_amounts = [asset1_provide_amount, asset2_provide_amount];
+            ensure_within_deposit_cap(deps.as_ref(), &cfg, &provide_amounts)?;
+            let cosmos_msg = create_provide_liquidity_msg(&cfg, &provide_amounts)?;
+            let input_account_msgs = execute_on_behalf_of(vec![cosmos_msg], &cfg.input_addr)?;
+
+            return Ok(Response::new()
+                .add_message(input_account_msgs)
+                .add_attribute("method", "provide_auto_balanced_liquidity")
+                .add_attribute("swap_amount", Uint128::zero().to_string()));
+        };
+
+        // Both balances end up provided once the excess is swapped
+        ensure_within_deposit_cap(
+            deps.as_ref(),
+            &cfg,
+            &[balances.asset1.amount.u128(), balances.asset2.amount.u128()],
         )?;
 
-        // Check which asset is being provided and get its balance
-        let (mut asset_balance, other_asset) = if asset == cfg.lp_config.asset_data.asset1 {
-            (balance_asset1.clone(), balance_asset2.clone())
-        } else if asset == cfg.lp_config.asset_data.asset2 {
-            (balance_asset2.clone(), balance_asset1.clone())
+        let offer_asset = Coin::new(swap_amount, offer_balance.denom.clone());
+        let remaining_offer_asset = Coin::new(
+            offer_balance
+                .amount
+                .checked_sub(swap_amount)
+                .map_err(|e| LibraryError::ExecutionError(e.to_string()))?,
+            offer_balance.denom,
+        );
+
+        let messages = match cfg.lp_config.pool_type {
+            PoolType::NativeLpToken(_) => astroport_native::create_auto_balance_liquidity_msgs(
+                &deps,
+                &cfg,
+                &offer_asset,
+                &remaining_offer_asset,
+                &other_balance,
+            )?,
+            PoolType::Cw20LpToken(_) => astroport_cw20::create_auto_balance_liquidity_msgs(
+                &deps,
+                &cfg,
+                &offer_asset,
+                &remaining_offer_asset,
+                &other_balance,
+            )?,
+        };
+
+        let input_account_msgs = execute_on_behalf_of(messages, &cfg.input_addr)?;
+
+        Ok(Response::new()
+            .add_message(input_account_msgs)
+            .add_attribute("method", "provide_auto_balanced_liquidity")
+            .add_attribute("swap_asset", offer_asset.denom)
+            .add_attribute("swap_amount", offer_asset.amount.to_string()))
+    }
+
+    /// Calculates how much of each asset needs to be swapped so that the balances follow the pool ratio.
+    /// At most one of the returned amounts is non-zero. Price impact and fees of the swap are not taken into account.
+    pub(crate) fn calculate_auto_balance_swap(
+        balance1: u128,
+        balance2: u128,
+        pool_asset1_balance: u128,
+        pool_asset2_balance: u128,
+    ) -> Result<(Uint128, Uint128), LibraryError> {
+        // Compare balance1 / balance2 against pool1 / pool2 without losing precision
+        let weighted_balance1 = Uint128::new(balance1).full_mul(pool_asset2_balance);
+        let weighted_balance2 = Uint128::new(balance2).full_mul(pool_asset1_balance);
+
+        // Half of the excess value is swapped for the other asset, rounding down
+        let half_excess = |excess: Uint256, pool_balance: u128| {
+            Uint128::try_from(excess / (Uint256::from(pool_balance) * Uint256::from(2u128)))
+                .map_err(|e| LibraryError::ExecutionError(e.to_string()))
+        };
+
+        if weighted_balance1 > weighted_balance2 {
+            Ok((
+                half_excess(weighted_balance1 - weighted_balance2, pool_asset2_balance)?,
+                Uint128::zero(),
+            ))
+        } else if weighted_balance2 > weighted_balance1 {
+            Ok((
+                Uint128::zero(),
+                half_excess(weighted_balance2 - weighted_balance1, pool_asset1_balance)?,
+            ))
         } else {
+            Ok((Uint128::zero(), Uint128::zero()))
+        }
+    }
+
+    fn provide_single_sided_liquidity(
+        deps: DepsMut,
+        cfg: Config,
+        asset: String,
+        limit: Option<Uint128>,
+        expected_pool_ratio_range: Option<DecimalRange>,
+        deposits: &[Coin],
+    ) -> Result<Response, LibraryError> {
+        if !cfg.pool_allocations.is_empty() {
             return Err(LibraryError::ExecutionError(
-                "Asset to provide liquidity for is not part of the pool".to_string(),
+                "Single sided provision is not supported with pool allocations".to_string(),
             ));
-        };
+        }
+
+        // Query asset balances and pool asset amounts
+        let balances = query_all_asset_balances(deps.as_ref(), &cfg, deposits)?;
+        let pool_balances = query_all_pool_asset_amounts(deps.as_ref(), &cfg)?;
 
-        // Check pool ratio if range is provided
+        // Check which asset is being provided and get its balance
+        let index = balances
+            .iter()
+            .position(|balance| balance.denom == asset)
+            .ok_or_else(|| {
+                LibraryError::ExecutionError(
+                    "Asset to provide liquidity for is not part of the pool".to_string(),
+                )
+            })?;
+        let mut asset_balance = balances[index].clone();
+        // Only used by Xyk pools, which always have two assets
+        let other_asset = balances[if index == 0 { 1 } else { 0 }].clone();
+
+        // Check pool ratio if range is provided, it applies to the ratio between the first two assets
         if let Some(range) = expected_pool_ratio_range {
-            let pool_asset_ratios =
-                Decimal::checked_from_ratio(pool_asset1_balance, pool_asset2_balance)
-                    .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+            let pool_asset_ratios = Decimal::checked_from_ratio(pool_balances[0], pool_balances[1])
+                .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
             range.contains(pool_asset_ratios)?;
         }
 
@@ -234,6 +949,10 @@ mod functions {
             }
         }
 
+        let mut provide_amounts = vec![0; balances.len()];
+        provide_amounts[index] = asset_balance.amount.u128();
+        ensure_within_deposit_cap(deps.as_ref(), &cfg, &provide_amounts)?;
+
         // Create liquidity provision message based on pool type
         let messages = match cfg.lp_config.pool_type {
             PoolType::NativeLpToken(_) => astroport_native::create_single_sided_liquidity_msg(
@@ -260,7 +979,40 @@ mod functions {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, LibraryError> {
+    match msg.id {
+        PROVISION_REPLY_ID => {
+            let provision_reply: ProvisionReply = from_json(&msg.payload)?;
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+
+            let mut response = Response::new().add_attribute("method", "provision_reply");
+            if let Some(check) = provision_reply.shares_check {
+                let shares = query_position(deps.as_ref(), &config)?.shares;
+                let minted = shares.saturating_sub(check.shares_before);
+                if minted < check.min_lp_tokens_out {
+                    return Err(LibraryError::ExecutionError(format!(
+                        "Provision minted {minted} LP tokens, below the minimum of {}",
+                        check.min_lp_tokens_out
+                    )));
+                }
+                response = response.add_attribute("lp_tokens_minted", minted);
+            }
+
+            if provision_reply.refund {
+                let refund = functions::refund_residuals(deps.as_ref(), &config)?;
+                response = response
+                    .add_submessages(refund.messages)
+                    .add_attributes(refund.attributes);
+            }
+
+            Ok(response)
+        }
+        _ => Err(LibraryError::Std(StdError::generic_err("unknown reply id"))),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Ownership {} => {
             to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
@@ -268,6 +1020,26 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetProcessor {} => {
             to_json_binary(&valence_library_base::get_processor(deps.storage)?)
         }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        QueryMsg::IsApproved {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            // the library moves the deposits from the deposit account as well
+            let mut approved = true;
+            for account in std::iter::once(&config.input_addr).chain(&config.deposit_addr) {
+                approved &= valence_library_utils::is_library_approved(
+                    &deps.querier,
+                    account,
+                    &env.contract.address,
+                )?;
+            }
+            to_json_binary(&approved)
+        }
         QueryMsg::GetLibraryConfig {} => {
             let config: Config = valence_library_base::load_config(deps.storage)?;
             to_json_binary(&config)
@@ -277,5 +1049,29 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 valence_library_utils::raw_config::query_raw_library_config(deps.storage)?;
             to_json_binary(&raw_config)
         }
+        QueryMsg::SimulateAmount {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            let simulated_amounts = functions::simulate_amounts(deps, &config)
+                .map_err(|err| StdError::generic_err(err.to_string()))?;
+            to_json_binary(&simulated_amounts)
+        }
+        QueryMsg::DepositCap {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            // The position is valued in the stable denom of the deposit cap, so it can't be valued without one
+            let tvl = config
+                .deposit_cap
+                .as_ref()
+                .map(|deposit_cap| {
+                    query_position(deps, &config).and_then(|position| {
+                        Valuation::new(deps, &config, deposit_cap).position_value(&position)
+                    })
+                })
+                .transpose()
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+            to_json_binary(&DepositCapResponse {
+                deposit_cap: config.deposit_cap.clone(),
+                tvl,
+            })
+        }
     }
 }
@@ -0,0 +1,252 @@
+use cosmwasm_std::{Deps, QuerierWrapper, Uint128};
+use valence_astroport_utils::{
+    astroport_cw20_lp_token, astroport_native_lp_token, get_pool_assets_amounts, AssetTrait,
+    PoolType,
+};
+use valence_library_utils::{error::LibraryError, price_source::PriceOracle};
+
+use crate::msg::{Config, DepositCap, PriceUnavailablePolicy};
+
+/// Share of the pool held by the output account
+pub(crate) struct PoolPosition {
+    /// Pool balance of each of the assets, in the same order as the assets of the config
+    pub reserves: Vec<u128>,
+    pub total_share: Uint128,
+    /// LP tokens held by the output account
+    pub shares: Uint128,
+}
+
+pub(crate) fn query_position(deps: Deps, cfg: &Config) -> Result<PoolPosition, LibraryError> {
+    let (assets, total_share, shares) = match &cfg.lp_config.pool_type {
+        PoolType::NativeLpToken(_) => {
+            let pool: astroport_native_lp_token::PoolResponse = deps.querier.query_wasm_smart(
+                &cfg.pool_addr,
+                &astroport_native_lp_token::PoolQueryMsg::Pool {},
+            )?;
+            let pair: astroport_native_lp_token::PairInfo = deps.querier.query_wasm_smart(
+                &cfg.pool_addr,
+                &astroport_native_lp_token::PoolQueryMsg::Pair {},
+            )?;
+            let shares = deps
+                .querier
+                .query_balance(&cfg.output_addr, pair.liquidity_token)?
+                .amount;
+
+            let assets = pool
+                .assets
+                .into_iter()
+                .map(|asset| Box::new(asset) as Box<dyn AssetTrait>)
+                .collect::<Vec<_>>();
+            (assets, pool.total_share, shares)
+        }
+        PoolType::Cw20LpToken(_) => {
+            let pool: astroport_cw20_lp_token::PoolResponse = deps.querier.query_wasm_smart(
+                &cfg.pool_addr,
+                &astroport_cw20_lp_token::PoolQueryMsg::Pool {},
+            )?;
+            let pair: astroport_cw20_lp_token::PairInfo = deps.querier.query_wasm_smart(
+                &cfg.pool_addr,
+                &astroport_cw20_lp_token::PoolQueryMsg::Pair {},
+            )?;
+            let balance: cw20::BalanceResponse = deps.querier.query_wasm_smart(
+                pair.liquidity_token,
+                &cw20::Cw20QueryMsg::Balance {
+                    address: cfg.output_addr.to_string(),
+                },
+            )?;
+
+            let assets = pool
+                .assets
+                .into_iter()
+                .map(|asset| Box::new(asset) as Box<dyn AssetTrait>)
+                .collect::<Vec<_>>();
+            (assets, pool.total_share, balance.balance)
+        }
+    };
+
+    let denoms = cfg.lp_config.asset_data.assets();
+    let denoms: Vec<&str> = denoms.iter().map(|denom| denom.as_str()).collect();
+
+    Ok(PoolPosition {
+        reserves: get_pool_assets_amounts(assets, &denoms)?,
+        total_share,
+        shares,
+    })
+}
+
+/// Amounts of each of the assets held through the position, in the same order as the assets of the config
+pub(crate) fn position_underlying(position: &PoolPosition) -> Result<Vec<u128>, LibraryError> {
+    if position.total_share.is_zero() {
+        return Ok(vec![0; position.reserves.len()]);
+    }
+
+    position
+        .reserves
+        .iter()
+        .map(|reserve| {
+            Uint128::new(*reserve)
+                .checked_multiply_ratio(position.shares, position.total_share)
+                .map(|amount| amount.u128())
+                .map_err(|e| LibraryError::ExecutionError(e.to_string()))
+        })
+        .collect()
+}
+
+/// Value of the given amounts of each of the assets, in units of the asset at `stable_index`.
+/// Each asset is valued at the ratio of the reserve of the stable asset to its own reserve, and assets without an amount are worth nothing
+pub(crate) fn assets_value(
+    reserves: &[u128],
+    amounts: &[u128],
+    stable_index: usize,
+) -> Result<Uint128, LibraryError> {
+    reserves
+        .iter()
+        .zip(amounts)
+        .try_fold(Uint128::zero(), |value, (reserve, amount)| {
+            if *amount == 0 {
+                return Ok(value);
+            }
+            let asset_value = Uint128::new(*amount)
+                .checked_multiply_ratio(reserves[stable_index], *reserve)
+                .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+            value
+                .checked_add(asset_value)
+                .map_err(|e| LibraryError::ExecutionError(e.to_string()))
+        })
+}
+
+/// Values the pool assets in the stable denom of the deposit cap, with the prices of the price source of the config.
+/// Without a price source, each asset is valued at the ratio of the pool balance of the stable denom to its own pool balance,
+/// which is the spot price of the pool and can be moved within a block
+pub(crate) struct Valuation<'a> {
+    pub querier: QuerierWrapper<'a>,
+    pub price_oracle: Option<&'a dyn PriceOracle>,
+    /// Denoms of the pool assets, in the same order as the assets of the config
+    pub denoms: Vec<&'a str>,
+    /// Denom the pool assets are valued in
+    pub stable_denom: &'a str,
+}
+
+impl<'a> Valuation<'a> {
+    pub fn new(deps: Deps<'a>, cfg: &'a Config, deposit_cap: &'a DepositCap) -> Self {
+        Valuation {
+            querier: deps.querier,
+            price_oracle: cfg
+                .price_source
+                .as_ref()
+                .map(|price_source| price_source as &dyn PriceOracle),
+            denoms: cfg
+                .lp_config
+                .asset_data
+                .assets()
+                .into_iter()
+                .map(|denom| denom.as_str())
+                .collect(),
+            stable_denom: &deposit_cap.stable_denom,
+        }
+    }
+
+    /// Value of the given amounts of each of the assets, in the same order as the assets of the config
+    pub fn value(&self, reserves: &[u128], amounts: &[u128]) -> Result<Uint128, LibraryError> {
+        match self.price_oracle {
+            Some(price_oracle) => {
+                let amounts = self
+                    .denoms
+                    .iter()
+                    .zip(amounts)
+                    .map(|(denom, amount)| (*denom, Uint128::new(*amount)))
+                    .collect::<Vec<_>>();
+                price_oracle.total_value(&self.querier, &amounts)
+            }
+            None => {
+                let stable_index = self
+                    .denoms
+                    .iter()
+                    .position(|denom| *denom == self.stable_denom)
+                    .ok_or_else(|| {
+                        LibraryError::ConfigurationError(
+                            "The position can only be valued at the pool ratio to a stable pool asset"
+                                .to_string(),
+                        )
+                    })?;
+                assets_value(reserves, amounts, stable_index)
+            }
+        }
+    }
+
+    pub fn position_value(&self, position: &PoolPosition) -> Result<Uint128, LibraryError> {
+        self.value(&position.reserves, &position_underlying(position)?)
+    }
+
+    /// Value of the position once `amounts` are provided.
+    /// An empty pool is worth nothing, and takes the ratio of the first provision, at which the provided amounts are valued
+    pub fn value_after_provision(
+        &self,
+        position: &PoolPosition,
+        amounts: &[u128],
+    ) -> Result<Uint128, LibraryError> {
+        let reserves = if position.total_share.is_zero() {
+            amounts
+        } else {
+            &position.reserves
+        };
+
+        self.position_value(position)?
+            .checked_add(self.value(reserves, amounts)?)
+            .map_err(|e| LibraryError::ExecutionError(e.to_string()))
+    }
+}
+
+/// Rejects the provision of `amounts` if it would push the value of the position above the max TVL of the deposit cap.
+/// If the price source can't price the pool assets, the provision is rejected or let through depending on the
+/// price unavailable policy of the deposit cap
+pub(crate) fn ensure_below_deposit_cap(
+    valuation: &Valuation,
+    position: &PoolPosition,
+    amounts: &[u128],
+    deposit_cap: &DepositCap,
+) -> Result<(), LibraryError> {
+    let tvl = match valuation.value_after_provision(position, amounts) {
+        Ok(tvl) => tvl,
+        // Only a price source can be unavailable, the pool ratio is always known
+        Err(e) if valuation.price_oracle.is_some() => {
+            return match deposit_cap.price_unavailable_policy {
+                PriceUnavailablePolicy::Block => Err(LibraryError::ExecutionError(format!(
+                    "Deposit cap can't be checked, the pool assets can't be priced: {e}"
+                ))),
+                PriceUnavailablePolicy::Allow => Ok(()),
+            }
+        }
+        Err(e) => return Err(e),
+    };
+
+    if tvl > deposit_cap.max_tvl {
+        return Err(LibraryError::ExecutionError(format!(
+            "Deposit cap exceeded: the position would be worth {tvl}{denom} but the max TVL is {max_tvl}{denom}",
+            max_tvl = deposit_cap.max_tvl,
+            denom = deposit_cap.stable_denom,
+        )));
+    }
+
+    Ok(())
+}
+
+/// Rejects the provision of `amounts` (in the same order as the assets of the config) if it would push
+/// the value of the position above the configured deposit cap
+pub(crate) fn ensure_within_deposit_cap(
+    deps: Deps,
+    cfg: &Config,
+    amounts: &[u128],
+) -> Result<(), LibraryError> {
+    let Some(deposit_cap) = &cfg.deposit_cap else {
+        return Ok(());
+    };
+
+    let position = query_position(deps, cfg)?;
+    ensure_below_deposit_cap(
+        &Valuation::new(deps, cfg, deposit_cap),
+        &position,
+        amounts,
+        deposit_cap,
+    )
+}
@@ -1,6 +1,7 @@
 pub mod astroport_cw20;
 pub mod astroport_native;
 pub mod contract;
+mod deposit_cap;
 pub mod msg;
 #[cfg(test)]
 mod tests;
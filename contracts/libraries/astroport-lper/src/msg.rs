@@ -1,12 +1,13 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Decimal, Deps, DepsMut, Uint128};
+use cosmwasm_std::{Addr, Coin, Decimal, Deps, DepsMut, Uint128};
 use cw_ownable::cw_ownable_query;
-use valence_astroport_utils::PoolType;
+use valence_astroport_utils::{max_spread_or_default, validate_max_spread, PoolKind, PoolType};
 
 use valence_library_utils::{
     error::LibraryError,
     liquidity_utils::{AssetData, DecimalRange},
-    msg::LibraryConfigValidation,
+    msg::{LibraryConfigValidation, SimulatedAmount},
+    price_source::{CheckedPriceSource, PriceSource},
     LibraryAccountType,
 };
 use valence_macros::{valence_library_query, ValenceLibraryInterface};
@@ -21,13 +22,84 @@ pub enum FunctionMsgs {
         limit: Option<Uint128>,
         expected_pool_ratio_range: Option<DecimalRange>,
     },
+    // Provide liquidity following the provision mode of the config
+    ProvideLiquidity {
+        expected_pool_ratio_range: Option<DecimalRange>,
+    },
+    // Move the pool assets held by the deposit account to the input account and provide them along with the
+    // input account balances following the provision mode, all in one message that reverts entirely if the provision fails.
+    // If a minimum deposit is given, the deposit account must hold at least that much of one of the pool assets
+    EnterPosition {
+        min_deposit: Option<Coin>,
+        expected_pool_ratio_range: Option<DecimalRange>,
+    },
 }
 
 #[valence_library_query]
 #[cw_ownable_query]
 #[cw_serde]
 #[derive(QueryResponses)]
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    /// Returns the configured deposit cap along with the current value of the position
+    #[returns(DepositCapResponse)]
+    DepositCap {},
+    /// Returns the amount of each pool asset that `ProvideLiquidity` would provide now, without executing it
+    #[returns(Vec<SimulatedAmount>)]
+    SimulateAmount {},
+}
+
+/// LP tokens held by the output account before a provision, carried to the reply checking how many were minted
+#[cw_serde]
+pub struct LpSharesCheck {
+    pub shares_before: Uint128,
+    pub min_lp_tokens_out: Uint128,
+}
+
+/// Checks done in the reply of a provision, once it has executed
+#[cw_serde]
+pub struct ProvisionReply {
+    pub shares_check: Option<LpSharesCheck>,
+    // whether the pool assets left in the input account are swept to the refund account
+    pub refund: bool,
+}
+
+#[cw_serde]
+pub struct DepositCapResponse {
+    pub deposit_cap: Option<DepositCap>,
+    /// Value of the LP tokens held by the output account in units of the stable denom of the deposit cap, if there is one
+    pub tvl: Option<Uint128>,
+}
+
+/// Cap on the value of the position, which bounds how much can enter the pool
+#[cw_serde]
+pub struct DepositCap {
+    /// Maximum value of the position, in units of the stable denom. Provisions pushing the position above it are rejected
+    pub max_tvl: Uint128,
+    /// Denom the position is valued in (e.g. USDC). Without a price source it must be one of the pool assets,
+    /// and with an Astroport TWAP price source it must be its quote denom
+    pub stable_denom: String,
+    /// Whether provisions are rejected or let through when the price source can't value the position
+    #[serde(default)]
+    pub price_unavailable_policy: PriceUnavailablePolicy,
+}
+
+impl DepositCap {
+    pub fn new(max_tvl: Uint128, stable_denom: impl Into<String>) -> Self {
+        DepositCap {
+            max_tvl,
+            stable_denom: stable_denom.into(),
+            price_unavailable_policy: PriceUnavailablePolicy::default(),
+        }
+    }
+
+    pub fn with_price_unavailable_policy(
+        mut self,
+        price_unavailable_policy: PriceUnavailablePolicy,
+    ) -> Self {
+        self.price_unavailable_policy = price_unavailable_policy;
+        self
+    }
+}
 
 #[cw_serde]
 #[derive(ValenceLibraryInterface)]
@@ -36,6 +108,32 @@ pub struct LibraryConfig {
     pub output_addr: LibraryAccountType,
     pub pool_addr: String,
     pub lp_config: LiquidityProviderConfig,
+    // Account receiving the pool assets left in the input account after a balanced provision
+    pub refund_account: Option<LibraryAccountType>,
+    // Maximum value of the position in a stable denom. Provisions pushing the position above it are rejected
+    #[serde(default)]
+    pub deposit_cap: Option<DepositCap>,
+    // Pools across which balanced provisions are split by weight, instead of providing everything to the pool address
+    #[serde(default)]
+    pub pool_allocations: Option<Vec<PoolAllocation>>,
+    // Prices the pool assets to value the position in the stable denom of the deposit cap.
+    // If not set, the position is valued at the pool ratio between each asset and the stable denom
+    #[serde(default)]
+    pub price_source: Option<PriceSource>,
+    // Account receiving the deposits (e.g. over IBC), whose pool assets are provided by the EnterPosition function
+    #[serde(default)]
+    pub deposit_addr: Option<LibraryAccountType>,
+}
+
+/// What the deposit cap check does when the price source can't price the pool assets (e.g. the oracle is down)
+#[cw_serde]
+#[derive(Default)]
+pub enum PriceUnavailablePolicy {
+    /// Reject the provision, so that nothing enters the pool without being valued
+    #[default]
+    Block,
+    /// Let the provision through without checking the deposit cap
+    Allow,
 }
 
 impl LibraryConfig {
@@ -50,27 +148,262 @@ impl LibraryConfig {
             output_addr: output_addr.into(),
             pool_addr,
             lp_config,
+            refund_account: None,
+            deposit_cap: None,
+            pool_allocations: None,
+            price_source: None,
+            deposit_addr: None,
         }
     }
 
-    fn do_validate(&self, api: &dyn cosmwasm_std::Api) -> Result<(Addr, Addr, Addr), LibraryError> {
+    pub fn with_refund_account(mut self, refund_account: impl Into<LibraryAccountType>) -> Self {
+        self.refund_account = Some(refund_account.into());
+        self
+    }
+
+    pub fn with_deposit_cap(mut self, deposit_cap: DepositCap) -> Self {
+        self.deposit_cap = Some(deposit_cap);
+        self
+    }
+
+    pub fn with_pool_allocations(mut self, pool_allocations: Vec<PoolAllocation>) -> Self {
+        self.pool_allocations = Some(pool_allocations);
+        self
+    }
+
+    pub fn with_price_source(mut self, price_source: PriceSource) -> Self {
+        self.price_source = Some(price_source);
+        self
+    }
+
+    pub fn with_deposit_addr(mut self, deposit_addr: impl Into<LibraryAccountType>) -> Self {
+        self.deposit_addr = Some(deposit_addr.into());
+        self
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn do_validate(
+        &self,
+        api: &dyn cosmwasm_std::Api,
+    ) -> Result<
+        (
+            Addr,
+            Addr,
+            Addr,
+            Option<Addr>,
+            Vec<CheckedPoolAllocation>,
+            Option<CheckedPriceSource>,
+            Option<Addr>,
+        ),
+        LibraryError,
+    > {
         let input_addr = self.input_addr.to_addr(api)?;
         let output_addr = self.output_addr.to_addr(api)?;
         let pool_addr = api.addr_validate(&self.pool_addr)?;
+        let refund_account = self
+            .refund_account
+            .as_ref()
+            .map(|refund_account| refund_account.to_addr(api))
+            .transpose()?;
+
+        let pool_allocations = match &self.pool_allocations {
+            Some(pool_allocations) => {
+                if pool_allocations.is_empty() {
+                    return Err(LibraryError::ConfigurationError(
+                        "Pool allocations cannot be empty, unset them to provide to the pool address"
+                            .to_string(),
+                    ));
+                }
+                pool_allocations
+                    .iter()
+                    .map(|allocation| allocation.to_checked(api))
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            None => vec![],
+        };
+
+        let price_source = self
+            .price_source
+            .as_ref()
+            .map(|price_source| price_source.to_checked(api))
+            .transpose()?;
+
+        let deposit_addr = self
+            .deposit_addr
+            .as_ref()
+            .map(|deposit_addr| deposit_addr.to_addr(api))
+            .transpose()?;
+
+        self.lp_config.validate()?;
+        validate_deposit_cap(
+            self.deposit_cap.as_ref(),
+            price_source.as_ref(),
+            &self.lp_config,
+        )?;
+        validate_pool_allocations(
+            &pool_allocations,
+            &self.lp_config,
+            self.deposit_cap.is_some(),
+        )?;
+        validate_price_source(price_source.as_ref(), &self.lp_config)?;
+        validate_deposit_addr(deposit_addr.as_ref(), &input_addr, &output_addr)?;
+
+        Ok((
+            input_addr,
+            output_addr,
+            pool_addr,
+            refund_account,
+            pool_allocations,
+            price_source,
+            deposit_addr,
+        ))
+    }
+}
+
+#[cw_serde]
+/// Share of the input account balances provided to a pool when splitting the provision across several pools
+pub struct PoolAllocation {
+    pub pool: String,
+    /// Denoms of the pool assets, in the same order as in the pool. They must be the assets of the LP config
+    pub asset_data: AssetData,
+    /// Portion of each balance provided to the pool. The weights of all the allocations sum to one
+    pub weight: Decimal,
+}
+
+impl PoolAllocation {
+    pub fn new(pool: impl Into<String>, asset_data: AssetData, weight: Decimal) -> Self {
+        PoolAllocation {
+            pool: pool.into(),
+            asset_data,
+            weight,
+        }
+    }
 
-        Ok((input_addr, output_addr, pool_addr))
+    fn to_checked(
+        &self,
+        api: &dyn cosmwasm_std::Api,
+    ) -> Result<CheckedPoolAllocation, LibraryError> {
+        Ok(CheckedPoolAllocation {
+            pool: api.addr_validate(&self.pool)?,
+            asset_data: self.asset_data.clone(),
+            weight: self.weight,
+        })
     }
 }
 
+#[cw_serde]
+/// Validated pool allocation
+pub struct CheckedPoolAllocation {
+    pub pool: Addr,
+    pub asset_data: AssetData,
+    pub weight: Decimal,
+}
+
 #[cw_serde]
 pub struct LiquidityProviderConfig {
     /// Pool type, old Astroport pools use Cw20 lp tokens and new pools use native tokens, so we specify here what kind of token we are going to get.
     /// We also provide the PairType structure of the right Astroport version that we are going to use for each scenario
     pub pool_type: PoolType,
-    /// Denoms of both native assets we are going to provide liquidity for
+    /// Denoms of all the native assets we are going to provide liquidity for, in the same order as in the pool
     pub asset_data: AssetData,
-    /// Max spread used when swapping assets to provide single sided liquidity
+    /// Max spread used when swapping assets to provide single sided liquidity, between 0 and 1 (exclusive).
+    /// If not set, a default max spread of 0.5% is used
     pub max_spread: Option<Decimal>,
+    /// Slippage tolerance passed to Astroport when providing liquidity
+    pub slippage_tolerance: Option<Decimal>,
+    /// Minimum amount of LP tokens that must be minted when providing liquidity, otherwise the provision reverts.
+    /// On top of being passed to the pool, the LP tokens received by the output account are checked once the provision is done.
+    /// Only supported for pools that mint native LP tokens
+    pub min_lp_tokens_out: Option<Uint128>,
+    /// How the liquidity is provided when executing the ProvideLiquidity function
+    #[serde(default)]
+    pub provision_mode: ProvisionMode,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum ProvisionMode {
+    /// Provide both assets following the current pool ratio
+    #[default]
+    Balanced,
+    /// Provide only the given asset and let the pool handle the ratio. Not supported by Xyk pools
+    SingleSided { asset: String },
+    /// Swap the excess of one of the assets to reach the pool ratio, then provide both assets. Only supported by pools with two assets
+    AutoBalance,
+}
+
+impl LiquidityProviderConfig {
+    pub fn validate(&self) -> Result<(), LibraryError> {
+        self.asset_data.validate()?;
+        validate_max_spread(self.max_spread)?;
+        let pool_kind = self.pool_type.kind()?;
+
+        if let Some(slippage_tolerance) = self.slippage_tolerance {
+            if slippage_tolerance.is_zero() || slippage_tolerance > Decimal::percent(50) {
+                return Err(LibraryError::ConfigurationError(
+                    "Slippage tolerance must be greater than 0 and at most 0.5".to_string(),
+                ));
+            }
+
+            if !pool_kind.supports_slippage_tolerance() {
+                return Err(LibraryError::ConfigurationError(
+                    "Slippage tolerance is not supported by Stable pools".to_string(),
+                ));
+            }
+        }
+
+        if let Some(min_lp_tokens_out) = self.min_lp_tokens_out {
+            if min_lp_tokens_out.is_zero() {
+                return Err(LibraryError::ConfigurationError(
+                    "Minimum LP tokens out cannot be zero".to_string(),
+                ));
+            }
+
+            // Astroport only enforces the minimum amount of LP tokens to receive for pools minting native LP tokens
+            if let PoolType::Cw20LpToken(_) = self.pool_type {
+                return Err(LibraryError::ConfigurationError(
+                    "Minimum LP tokens out is only supported for pools with native LP tokens"
+                        .to_string(),
+                ));
+            }
+        }
+
+        if let ProvisionMode::SingleSided { asset } = &self.provision_mode {
+            if !self.asset_data.contains(asset) {
+                return Err(LibraryError::ConfigurationError(format!(
+                    "Single sided provision asset {asset} is not part of the pool assets"
+                )));
+            }
+
+            // Xyk pools require both assets to be provided
+            if pool_kind == PoolKind::Xyk {
+                return Err(LibraryError::ConfigurationError(
+                    "Single sided provision is not supported by Xyk pools, use AutoBalance instead"
+                        .to_string(),
+                ));
+            }
+        }
+
+        if self.provision_mode == ProvisionMode::AutoBalance {
+            self.asset_data.ensure_two_assets()?;
+        }
+
+        Ok(())
+    }
+
+    /// Max spread passed in the swap messages, the default max spread is used if none is configured
+    pub fn swap_max_spread(&self) -> Decimal {
+        max_spread_or_default(self.max_spread)
+    }
+
+    /// Slippage tolerance passed in the provide liquidity messages, omitted for pools ignoring it
+    pub fn provide_slippage_tolerance(&self) -> Option<Decimal> {
+        self.slippage_tolerance.filter(|_| {
+            self.pool_type
+                .kind()
+                .is_ok_and(|pool_kind| pool_kind.supports_slippage_tolerance())
+        })
+    }
 }
 
 #[cw_serde]
@@ -80,6 +413,15 @@ pub struct Config {
     pub output_addr: Addr,
     pub pool_addr: Addr,
     pub lp_config: LiquidityProviderConfig,
+    pub refund_account: Option<Addr>,
+    #[serde(default)]
+    pub deposit_cap: Option<DepositCap>,
+    #[serde(default)]
+    pub pool_allocations: Vec<CheckedPoolAllocation>,
+    #[serde(default)]
+    pub price_source: Option<CheckedPriceSource>,
+    #[serde(default)]
+    pub deposit_addr: Option<Addr>,
 }
 
 impl LibraryConfigValidation<Config> for LibraryConfig {
@@ -90,7 +432,15 @@ impl LibraryConfigValidation<Config> for LibraryConfig {
     }
 
     fn validate(&self, deps: Deps) -> Result<Config, LibraryError> {
-        let (input_addr, output_addr, pool_addr) = self.do_validate(deps.api)?;
+        let (
+            input_addr,
+            output_addr,
+            pool_addr,
+            refund_account,
+            pool_allocations,
+            price_source,
+            deposit_addr,
+        ) = self.do_validate(deps.api)?;
 
         ensure_correct_pool(
             self.pool_addr.to_string(),
@@ -98,12 +448,18 @@ impl LibraryConfigValidation<Config> for LibraryConfig {
             &self.lp_config.asset_data,
             &deps,
         )?;
+        ensure_correct_allocated_pools(&pool_allocations, &self.lp_config.pool_type, &deps)?;
 
         Ok(Config {
             input_addr,
             output_addr,
             pool_addr,
             lp_config: self.lp_config.clone(),
+            refund_account,
+            deposit_cap: self.deposit_cap.clone(),
+            pool_allocations,
+            price_source,
+            deposit_addr,
         })
     }
 }
@@ -125,28 +481,249 @@ impl LibraryConfigUpdate {
         }
 
         if let Some(lp_config) = self.lp_config {
+            lp_config.validate()?;
             config.lp_config = lp_config;
         }
 
+        if let OptionUpdate::Set(refund_account) = self.refund_account {
+            config.refund_account = refund_account
+                .map(|refund_account| refund_account.to_addr(deps.api))
+                .transpose()?;
+        }
+
+        if let OptionUpdate::Set(deposit_cap) = self.deposit_cap {
+            config.deposit_cap = deposit_cap;
+        }
+
+        if let OptionUpdate::Set(pool_allocations) = self.pool_allocations {
+            config.pool_allocations = pool_allocations
+                .unwrap_or_default()
+                .iter()
+                .map(|allocation| allocation.to_checked(deps.api))
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+
+        if let OptionUpdate::Set(price_source) = self.price_source {
+            config.price_source = price_source
+                .map(|price_source| price_source.to_checked(deps.api))
+                .transpose()?;
+        }
+
+        if let OptionUpdate::Set(deposit_addr) = self.deposit_addr {
+            config.deposit_addr = deposit_addr
+                .map(|deposit_addr| deposit_addr.to_addr(deps.api))
+                .transpose()?;
+        }
+
+        // The deposit cap, the allocations and the price source are checked once the LP config and the price source are (possibly) updated
+        validate_deposit_cap(
+            config.deposit_cap.as_ref(),
+            config.price_source.as_ref(),
+            &config.lp_config,
+        )?;
+        validate_pool_allocations(
+            &config.pool_allocations,
+            &config.lp_config,
+            config.deposit_cap.is_some(),
+        )?;
+        validate_price_source(config.price_source.as_ref(), &config.lp_config)?;
+        validate_deposit_addr(
+            config.deposit_addr.as_ref(),
+            &config.input_addr,
+            &config.output_addr,
+        )?;
+
         ensure_correct_pool(
             config.pool_addr.to_string(),
             &config.lp_config.pool_type,
             &config.lp_config.asset_data,
             &deps.as_ref(),
         )?;
+        ensure_correct_allocated_pools(
+            &config.pool_allocations,
+            &config.lp_config.pool_type,
+            &deps.as_ref(),
+        )?;
 
         valence_library_base::save_config(deps.storage, &config)?;
         Ok(())
     }
 }
 
+/// Checks that the deposit cap is not zero and that the position can be valued in its stable denom
+fn validate_deposit_cap(
+    deposit_cap: Option<&DepositCap>,
+    price_source: Option<&CheckedPriceSource>,
+    lp_config: &LiquidityProviderConfig,
+) -> Result<(), LibraryError> {
+    let Some(deposit_cap) = deposit_cap else {
+        return Ok(());
+    };
+
+    if deposit_cap.max_tvl.is_zero() {
+        return Err(LibraryError::ConfigurationError(
+            "Max TVL cannot be zero, unset the deposit cap to remove it".to_string(),
+        ));
+    }
+
+    let stable_denom = &deposit_cap.stable_denom;
+    match price_source {
+        // The pool assets are valued at the pool ratio to the stable denom
+        None => {
+            if !lp_config.asset_data.contains(stable_denom) {
+                return Err(LibraryError::ConfigurationError(format!(
+                    "Deposit cap stable denom {stable_denom} must be a pool asset to value the position without a price source"
+                )));
+            }
+        }
+        // The TWAP prices the pool assets in units of its quote denom
+        Some(CheckedPriceSource::AstroportTwap { quote_denom, .. }) => {
+            if quote_denom != stable_denom {
+                return Err(LibraryError::ConfigurationError(format!(
+                    "Deposit cap stable denom {stable_denom} must be the quote denom of the price source"
+                )));
+            }
+        }
+        // The oracle is trusted to quote its prices in the stable denom
+        Some(CheckedPriceSource::Oracle { .. }) => {}
+    }
+
+    Ok(())
+}
+
+/// Checks that the deposit account, if any, is neither the input nor the output account
+fn validate_deposit_addr(
+    deposit_addr: Option<&Addr>,
+    input_addr: &Addr,
+    output_addr: &Addr,
+) -> Result<(), LibraryError> {
+    if deposit_addr
+        .is_some_and(|deposit_addr| deposit_addr == input_addr || deposit_addr == output_addr)
+    {
+        return Err(LibraryError::ConfigurationError(
+            "Deposit account must be distinct from the input and output accounts".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the price source can price all the pool assets, when its priced denoms are known without querying it
+fn validate_price_source(
+    price_source: Option<&CheckedPriceSource>,
+    lp_config: &LiquidityProviderConfig,
+) -> Result<(), LibraryError> {
+    let Some(priced_denoms) = price_source.and_then(|price_source| price_source.priced_denoms())
+    else {
+        return Ok(());
+    };
+
+    for denom in lp_config.asset_data.assets() {
+        if !priced_denoms.contains(&denom.as_str()) {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Price source can't price pool asset {denom}"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the weights of the pool allocations and that each pool is provided the assets of the LP config.
+/// No allocations means that everything is provided to the pool address.
+pub(crate) fn validate_pool_allocations(
+    pool_allocations: &[CheckedPoolAllocation],
+    lp_config: &LiquidityProviderConfig,
+    has_deposit_cap: bool,
+) -> Result<(), LibraryError> {
+    if pool_allocations.is_empty() {
+        return Ok(());
+    }
+
+    // The deposit cap and the other provision modes only apply to the pool address
+    if lp_config.provision_mode != ProvisionMode::Balanced {
+        return Err(LibraryError::ConfigurationError(
+            "Pool allocations are only supported with the Balanced provision mode".to_string(),
+        ));
+    }
+    if has_deposit_cap {
+        return Err(LibraryError::ConfigurationError(
+            "Pool allocations are not supported with a deposit cap".to_string(),
+        ));
+    }
+
+    let mut expected_assets = lp_config.asset_data.assets();
+    expected_assets.sort();
+
+    let mut total_weight = Decimal::zero();
+    for (index, allocation) in pool_allocations.iter().enumerate() {
+        if pool_allocations[..index]
+            .iter()
+            .any(|other| other.pool == allocation.pool)
+        {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Pool {} is allocated more than once",
+                allocation.pool
+            )));
+        }
+
+        if allocation.weight.is_zero() || allocation.weight > Decimal::one() {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Weight of pool {} must be greater than 0 and at most 1",
+                allocation.pool
+            )));
+        }
+        total_weight = total_weight
+            .checked_add(allocation.weight)
+            .map_err(|e| LibraryError::ConfigurationError(e.to_string()))?;
+
+        allocation.asset_data.validate()?;
+        let mut assets = allocation.asset_data.assets();
+        assets.sort();
+        if assets != expected_assets {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Assets of pool {} must be the assets of the LP config",
+                allocation.pool
+            )));
+        }
+    }
+
+    if total_weight != Decimal::one() {
+        return Err(LibraryError::ConfigurationError(format!(
+            "Pool allocation weights must sum to 1, got {total_weight}"
+        )));
+    }
+
+    Ok(())
+}
+
+fn ensure_correct_allocated_pools(
+    pool_allocations: &[CheckedPoolAllocation],
+    pool_type: &PoolType,
+    deps: &Deps,
+) -> Result<(), LibraryError> {
+    for allocation in pool_allocations {
+        ensure_correct_pool(
+            allocation.pool.to_string(),
+            pool_type,
+            &allocation.asset_data,
+            deps,
+        )?;
+    }
+    Ok(())
+}
+
 fn ensure_correct_pool(
     pool_addr: String,
     pool_type: &PoolType,
     assets: &AssetData,
     deps: &Deps,
 ) -> Result<(), LibraryError> {
-    match pool_type {
+    use valence_astroport_utils::{
+        astroport_cw20_lp_token::AssetInfo as Cw20AssetInfo,
+        astroport_native_lp_token::AssetInfo as NativeAssetInfo,
+    };
+
+    let pool_denoms = match pool_type {
         PoolType::NativeLpToken(pair_type) => {
             let pool_response: valence_astroport_utils::astroport_native_lp_token::PairInfo =
                 deps.querier.query_wasm_smart(
@@ -160,27 +737,16 @@ fn ensure_correct_pool(
                 ));
             }
 
-            // Check that both assets in the pool are native and that they match our assets
-            for (pool_asset, expected_asset) in pool_response
+            pool_response
                 .asset_infos
-                .iter()
-                .zip([&assets.asset1, &assets.asset2].iter())
-            {
-                match pool_asset {
-                    valence_astroport_utils::astroport_native_lp_token::AssetInfo::Token { .. } => {
-                        return Err(LibraryError::ConfigurationError(
-                            "Pool asset is not a native token".to_string(),
-                        ))
-                    }
-                    valence_astroport_utils::astroport_native_lp_token::AssetInfo::NativeToken { denom } => {
-                        if denom != *expected_asset {
-                            return Err(LibraryError::ConfigurationError(
-                                "Pool asset does not match the expected asset".to_string(),
-                            ));
-                        }
-                    }
-                }
-            }
+                .into_iter()
+                .map(|asset_info| match asset_info {
+                    NativeAssetInfo::NativeToken { denom } => Ok(denom),
+                    NativeAssetInfo::Token { .. } => Err(LibraryError::ConfigurationError(
+                        "Pool asset is not a native token".to_string(),
+                    )),
+                })
+                .collect::<Result<Vec<String>, LibraryError>>()?
         }
         PoolType::Cw20LpToken(pair_type) => {
             let pool_response: valence_astroport_utils::astroport_cw20_lp_token::PairInfo =
@@ -195,33 +761,43 @@ fn ensure_correct_pool(
                 ));
             }
 
-            // Check that both assets in the pool are native and that they match our assets
-            for (pool_asset, expected_asset) in pool_response
+            pool_response
                 .asset_infos
-                .iter()
-                .zip([&assets.asset1, &assets.asset2].iter())
-            {
-                match pool_asset {
-                    valence_astroport_utils::astroport_cw20_lp_token::AssetInfo::Token {
-                        ..
-                    } => {
-                        return Err(LibraryError::ConfigurationError(
-                            "Pool asset is not a native token".to_string(),
-                        ))
-                    }
-                    valence_astroport_utils::astroport_cw20_lp_token::AssetInfo::NativeToken {
-                        denom,
-                    } => {
-                        if denom != *expected_asset {
-                            return Err(LibraryError::ConfigurationError(
-                                "Pool asset does not match the expected asset".to_string(),
-                            ));
-                        }
-                    }
-                }
-            }
+                .into_iter()
+                .map(|asset_info| match asset_info {
+                    Cw20AssetInfo::NativeToken { denom } => Ok(denom),
+                    Cw20AssetInfo::Token { .. } => Err(LibraryError::ConfigurationError(
+                        "Pool asset is not a native token".to_string(),
+                    )),
+                })
+                .collect::<Result<Vec<String>, LibraryError>>()?
         }
+    };
+
+    // Check that all assets in the pool are native and that they match our assets
+    ensure_same_asset_count(pool_denoms.len(), assets)?;
+    assets.ensure_matches_pool_assets(&pool_denoms)?;
+
+    // Assets are provided in the same order as in the pool
+    if pool_denoms
+        .iter()
+        .zip(assets.assets())
+        .any(|(pool_denom, expected_asset)| pool_denom != expected_asset)
+    {
+        return Err(LibraryError::ConfigurationError(
+            "Pool asset does not match the expected asset".to_string(),
+        ));
     }
 
     Ok(())
 }
+
+fn ensure_same_asset_count(pool_assets: usize, assets: &AssetData) -> Result<(), LibraryError> {
+    if pool_assets != assets.assets().len() {
+        return Err(LibraryError::ConfigurationError(format!(
+            "Pool has {pool_assets} assets but {} were configured",
+            assets.assets().len()
+        )));
+    }
+    Ok(())
+}
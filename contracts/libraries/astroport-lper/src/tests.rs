@@ -1,4 +1,10 @@
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{
+    from_json,
+    testing::{message_info, mock_dependencies, mock_env, MockApi, MockQuerier},
+    to_json_binary, BankMsg, ContractResult, CosmosMsg, Decimal, DepsMut, MemoryStorage, OwnedDeps,
+    QuerierWrapper, Reply, ReplyOn, Response, SubMsgResponse, SubMsgResult, SystemResult, Uint128,
+    WasmMsg, WasmQuery,
+};
 use neutron_test_tube::{
     neutron_std::types::cosmos::{
         bank::v1beta1::{MsgSend, QueryAllBalancesRequest, QueryBalanceRequest},
@@ -8,15 +14,33 @@ use neutron_test_tube::{
 };
 use valence_astroport_utils::{
     suite::{AstroportTestAppBuilder, AstroportTestAppSetup},
-    PoolType,
+    PoolKind, PoolType, DEFAULT_MAX_SPREAD,
 };
 use valence_library_utils::{
     error::{LibraryError, UnauthorizedReason},
+    events::POOL_UPDATE_EVENT_TYPE,
     liquidity_utils::{AssetData, DecimalRange},
-    msg::{ExecuteMsg, InstantiateMsg},
+    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation, SimulatedAmount},
+    price_source::{PriceOracle, PriceSource},
+    OptionUpdate,
 };
 
-use crate::msg::{FunctionMsgs, LibraryConfig, LibraryConfigUpdate, LiquidityProviderConfig};
+use crate::{
+    astroport_native,
+    contract::{
+        functions::{
+            calculate_auto_balance_swap, calculate_multi_asset_provide_amounts,
+            create_allocated_provide_msgs, create_refund_msgs, process_function,
+        },
+        query, reply, PROVISION_REPLY_ID,
+    },
+    deposit_cap::{assets_value, ensure_below_deposit_cap, PoolPosition, Valuation},
+    msg::{
+        validate_pool_allocations, CheckedPoolAllocation, Config, DepositCap, DepositCapResponse,
+        FunctionMsgs, LibraryConfig, LibraryConfigUpdate, LiquidityProviderConfig, LpSharesCheck,
+        PoolAllocation, PriceUnavailablePolicy, ProvisionMode, ProvisionReply, QueryMsg,
+    },
+};
 
 const CONTRACT_PATH: &str = "../../../artifacts";
 
@@ -25,6 +49,7 @@ struct LPerTestSuite {
     pub lper_addr: String,
     pub input_acc: String,
     pub output_acc: String,
+    pub account_code_id: u64,
 }
 
 impl Default for LPerTestSuite {
@@ -85,6 +110,7 @@ impl LPerTestSuite {
             lper_addr,
             input_acc,
             output_acc,
+            account_code_id: code_id,
         }
     }
 }
@@ -169,8 +195,12 @@ fn instantiate_lper_contract(
                     asset_data: AssetData {
                         asset1: setup.pool_asset1.clone(),
                         asset2: setup.pool_asset2.clone(),
+                        additional_assets: vec![],
                     },
                     max_spread: None,
+                    slippage_tolerance: None,
+                    min_lp_tokens_out: None,
+                    provision_mode: ProvisionMode::Balanced,
                 },
             ),
         },
@@ -210,9 +240,18 @@ pub fn only_owner_can_update_config() {
             asset_data: AssetData {
                 asset1: setup.inner.pool_asset1.clone(),
                 asset2: setup.inner.pool_asset2.clone(),
+                additional_assets: vec![],
             },
             max_spread: None,
+            slippage_tolerance: None,
+            min_lp_tokens_out: None,
+            provision_mode: ProvisionMode::Balanced,
         }),
+        refund_account: OptionUpdate::None,
+        deposit_cap: OptionUpdate::None,
+        pool_allocations: OptionUpdate::None,
+        price_source: OptionUpdate::None,
+        deposit_addr: OptionUpdate::None,
     };
 
     let error = wasm
@@ -241,6 +280,100 @@ pub fn only_owner_can_update_config() {
     .unwrap();
 }
 
+fn cw20_lp_config(asset1: &str, asset2: &str) -> LiquidityProviderConfig {
+    LiquidityProviderConfig {
+        pool_type: PoolType::Cw20LpToken(
+            valence_astroport_utils::astroport_cw20_lp_token::PairType::Xyk {},
+        ),
+        asset_data: AssetData {
+            asset1: asset1.to_string(),
+            asset2: asset2.to_string(),
+            additional_assets: vec![],
+        },
+        max_spread: None,
+        slippage_tolerance: None,
+        min_lp_tokens_out: None,
+        provision_mode: ProvisionMode::Balanced,
+    }
+}
+
+#[test]
+fn update_config_repoints_to_new_pool() {
+    let setup = LPerTestSuite::default();
+    let wasm = Wasm::new(&setup.inner.app);
+
+    let response = wasm
+        .execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+            &setup.lper_addr,
+            &ExecuteMsg::UpdateConfig {
+                new_config: LibraryConfigUpdate {
+                    pool_addr: Some(setup.inner.pool_cw20_addr.clone()),
+                    lp_config: Some(cw20_lp_config(
+                        &setup.inner.pool_asset1,
+                        &setup.inner.pool_asset2,
+                    )),
+                    ..Default::default()
+                },
+            },
+            &[],
+            setup.inner.owner_acc(),
+        )
+        .unwrap();
+
+    // The old and new pools are recorded
+    let event = response
+        .events
+        .iter()
+        .find(|event| event.ty == format!("wasm-{POOL_UPDATE_EVENT_TYPE}"))
+        .unwrap();
+    let attribute = |key: &str| {
+        event
+            .attributes
+            .iter()
+            .find(|attr| attr.key == key)
+            .unwrap()
+            .value
+            .clone()
+    };
+    assert_eq!(attribute("old_pool"), setup.inner.pool_native_addr);
+    assert_eq!(attribute("new_pool"), setup.inner.pool_cw20_addr);
+
+    let config: Config = wasm
+        .query(&setup.lper_addr, &QueryMsg::GetLibraryConfig {})
+        .unwrap();
+    assert_eq!(config.pool_addr.as_str(), setup.inner.pool_cw20_addr);
+    // The rest of the wiring is untouched
+    assert_eq!(config.input_addr.as_str(), setup.input_acc);
+    assert_eq!(config.output_addr.as_str(), setup.output_acc);
+}
+
+#[test]
+fn update_config_rejects_pool_with_mismatched_assets() {
+    let setup = LPerTestSuite::default();
+    let wasm = Wasm::new(&setup.inner.app);
+
+    let error = wasm
+        .execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+            &setup.lper_addr,
+            &ExecuteMsg::UpdateConfig {
+                new_config: LibraryConfigUpdate {
+                    pool_addr: Some(setup.inner.pool_cw20_addr.clone()),
+                    lp_config: Some(cw20_lp_config(&setup.inner.pool_asset1, "uatom")),
+                    ..Default::default()
+                },
+            },
+            &[],
+            setup.inner.owner_acc(),
+        )
+        .unwrap_err();
+    assert!(error.to_string().contains("do not match the pool assets"));
+
+    let config: Config = wasm
+        .query(&setup.lper_addr, &QueryMsg::GetLibraryConfig {})
+        .unwrap();
+    assert_eq!(config.pool_addr.as_str(), setup.inner.pool_native_addr);
+}
+
 #[test]
 fn only_owner_can_update_processor() {
     let setup = LPerTestSuite::default();
@@ -342,8 +475,12 @@ fn instantiate_with_wrong_assets() {
                         asset_data: AssetData {
                             asset1: setup.inner.pool_asset2.clone(),
                             asset2: setup.inner.pool_asset1.clone(),
+                            additional_assets: vec![],
                         },
                         max_spread: None,
+                        slippage_tolerance: None,
+                        min_lp_tokens_out: None,
+                        provision_mode: ProvisionMode::Balanced,
                     },
                 ),
             },
@@ -392,8 +529,12 @@ fn instantiate_with_wrong_pool_type() {
                         asset_data: AssetData {
                             asset1: setup.inner.pool_asset2.clone(),
                             asset2: setup.inner.pool_asset1.clone(),
+                            additional_assets: vec![],
                         },
                         max_spread: None,
+                        slippage_tolerance: None,
+                        min_lp_tokens_out: None,
+                        provision_mode: ProvisionMode::Balanced,
                     },
                 ),
             },
@@ -979,3 +1120,2254 @@ fn provide_single_sided_liquidity_native_lp_token_expected_decimal_range_validat
     )
     .unwrap();
 }
+
+fn native_xyk_config(
+    slippage_tolerance: Option<Decimal>,
+    min_lp_tokens_out: Option<Uint128>,
+) -> Config {
+    let api = MockApi::default();
+    Config {
+        input_addr: api.addr_make("input_account"),
+        output_addr: api.addr_make("output_account"),
+        pool_addr: api.addr_make("pool"),
+        lp_config: LiquidityProviderConfig {
+            pool_type: PoolType::NativeLpToken(
+                valence_astroport_utils::astroport_native_lp_token::PairType::Xyk {},
+            ),
+            asset_data: AssetData {
+                asset1: "untrn".to_string(),
+                asset2: "uusdc".to_string(),
+                additional_assets: vec![],
+            },
+            max_spread: Some(Decimal::percent(1)),
+            slippage_tolerance,
+            min_lp_tokens_out,
+            provision_mode: ProvisionMode::Balanced,
+        },
+        refund_account: None,
+        deposit_cap: None,
+        pool_allocations: vec![],
+        price_source: None,
+        deposit_addr: None,
+    }
+}
+
+fn decode_native_execute_msg(
+    msg: &CosmosMsg,
+) -> valence_astroport_utils::astroport_native_lp_token::ExecuteMsg {
+    match msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => from_json(msg).unwrap(),
+        _ => panic!("Expected a wasm execute message"),
+    }
+}
+
+#[test]
+fn provide_message_carries_slippage_parameters() {
+    let cfg = native_xyk_config(Some(Decimal::percent(2)), Some(Uint128::new(1_000)));
+
+    let msg = astroport_native::create_provide_liquidity_msg(&cfg, &[500_000, 1_000_000]).unwrap();
+
+    match decode_native_execute_msg(&msg) {
+        valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::ProvideLiquidity {
+            slippage_tolerance,
+            min_lp_to_receive,
+            receiver,
+            ..
+        } => {
+            assert_eq!(slippage_tolerance, Some(Decimal::percent(2)));
+            assert_eq!(min_lp_to_receive, Some(Uint128::new(1_000)));
+            assert_eq!(receiver, Some(cfg.output_addr.to_string()));
+        }
+        _ => panic!("Expected a provide liquidity message"),
+    }
+}
+
+#[test]
+fn single_sided_provide_message_uses_simulated_quote_and_slippage_parameters() {
+    let cfg = native_xyk_config(Some(Decimal::percent(2)), Some(Uint128::new(1_000)));
+
+    // Simulated quote returned by the pool for half of the offered asset
+    let mut deps = mock_dependencies();
+    let pool_addr = cfg.pool_addr.clone();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } if contract_addr.as_str() == pool_addr.as_str() => {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(
+                    &valence_astroport_utils::astroport_native_lp_token::SimulationResponse {
+                        return_amount: Uint128::new(990_000),
+                        spread_amount: Uint128::new(7_000),
+                        commission_amount: Uint128::new(3_000),
+                    },
+                )
+                .unwrap(),
+            ))
+        }
+        _ => panic!("Unexpected query"),
+    });
+
+    let msgs = astroport_native::create_single_sided_liquidity_msg(
+        &deps.as_mut(),
+        &cfg,
+        &cosmwasm_std::coin(1_000_000, "untrn"),
+        &cosmwasm_std::coin(0, "uusdc"),
+    )
+    .unwrap();
+    assert_eq!(msgs.len(), 2);
+
+    match decode_native_execute_msg(&msgs[0]) {
+        valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::Swap {
+            offer_asset,
+            max_spread,
+            ..
+        } => {
+            assert_eq!(offer_asset.amount, Uint128::new(500_000));
+            assert_eq!(max_spread, Some(Decimal::percent(1)));
+        }
+        _ => panic!("Expected a swap message"),
+    }
+
+    match decode_native_execute_msg(&msgs[1]) {
+        valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::ProvideLiquidity {
+            assets,
+            slippage_tolerance,
+            min_lp_to_receive,
+            ..
+        } => {
+            assert_eq!(assets[0].amount, Uint128::new(500_000));
+            assert_eq!(assets[1].amount, Uint128::new(990_000));
+            assert_eq!(slippage_tolerance, Some(Decimal::percent(2)));
+            assert_eq!(min_lp_to_receive, Some(Uint128::new(1_000)));
+        }
+        _ => panic!("Expected a provide liquidity message"),
+    }
+}
+
+#[test]
+fn provide_message_shape_follows_pool_kind() {
+    for (pool_kind, expected_slippage_tolerance) in [
+        (PoolKind::Xyk, Some(Decimal::percent(2))),
+        (PoolKind::ConcentratedLiquidity, Some(Decimal::percent(2))),
+        // Stable pools ignore the slippage tolerance, so it's not sent
+        (PoolKind::Stable, None),
+    ] {
+        let mut cfg = native_xyk_config(Some(Decimal::percent(2)), Some(Uint128::new(1_000)));
+        cfg.lp_config.pool_type = pool_kind.native_lp_token_pool_type();
+
+        let msg =
+            astroport_native::create_provide_liquidity_msg(&cfg, &[500_000, 1_000_000]).unwrap();
+
+        match decode_native_execute_msg(&msg) {
+            valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::ProvideLiquidity {
+                assets,
+                slippage_tolerance,
+                min_lp_to_receive,
+                ..
+            } => {
+                assert_eq!(assets.len(), 2, "{pool_kind:?}");
+                assert_eq!(
+                    slippage_tolerance, expected_slippage_tolerance,
+                    "{pool_kind:?}"
+                );
+                assert_eq!(
+                    min_lp_to_receive,
+                    Some(Uint128::new(1_000)),
+                    "{pool_kind:?}"
+                );
+            }
+            _ => panic!("Expected a provide liquidity message"),
+        }
+    }
+}
+
+#[test]
+fn single_sided_message_shape_follows_pool_kind() {
+    // Only Xyk pools need a swap before providing both assets
+    for (pool_kind, expected_msgs) in [
+        (PoolKind::Xyk, 2),
+        (PoolKind::ConcentratedLiquidity, 1),
+        (PoolKind::Stable, 1),
+    ] {
+        let mut cfg = native_xyk_config(None, None);
+        cfg.lp_config.pool_type = pool_kind.native_lp_token_pool_type();
+
+        let mut deps = mock_dependencies();
+        let pool_addr = cfg.pool_addr.clone();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, .. }
+                if contract_addr.as_str() == pool_addr.as_str() =>
+            {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(
+                        &valence_astroport_utils::astroport_native_lp_token::SimulationResponse {
+                            return_amount: Uint128::new(990_000),
+                            spread_amount: Uint128::zero(),
+                            commission_amount: Uint128::zero(),
+                        },
+                    )
+                    .unwrap(),
+                ))
+            }
+            _ => panic!("Unexpected query"),
+        });
+
+        let msgs = astroport_native::create_single_sided_liquidity_msg(
+            &deps.as_mut(),
+            &cfg,
+            &cosmwasm_std::coin(1_000_000, "untrn"),
+            &cosmwasm_std::coin(0, "uusdc"),
+        )
+        .unwrap();
+        assert_eq!(msgs.len(), expected_msgs, "{pool_kind:?}");
+
+        assert!(matches!(
+            decode_native_execute_msg(msgs.last().unwrap()),
+            valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::ProvideLiquidity { .. }
+        ));
+    }
+}
+
+#[test]
+fn single_sided_swap_uses_default_max_spread() {
+    let mut cfg = native_xyk_config(None, None);
+    cfg.lp_config.max_spread = None;
+
+    let mut deps = mock_dependencies();
+    let pool_addr = cfg.pool_addr.clone();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } if contract_addr.as_str() == pool_addr.as_str() => {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(
+                    &valence_astroport_utils::astroport_native_lp_token::SimulationResponse {
+                        return_amount: Uint128::new(990_000),
+                        spread_amount: Uint128::zero(),
+                        commission_amount: Uint128::zero(),
+                    },
+                )
+                .unwrap(),
+            ))
+        }
+        _ => panic!("Unexpected query"),
+    });
+
+    let msgs = astroport_native::create_single_sided_liquidity_msg(
+        &deps.as_mut(),
+        &cfg,
+        &cosmwasm_std::coin(1_000_000, "untrn"),
+        &cosmwasm_std::coin(0, "uusdc"),
+    )
+    .unwrap();
+
+    // Without a configured max spread the swap doesn't rely on the default of the pool
+    match decode_native_execute_msg(&msgs[0]) {
+        valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::Swap {
+            max_spread, ..
+        } => assert_eq!(max_spread, Some(DEFAULT_MAX_SPREAD)),
+        _ => panic!("Expected a swap message"),
+    }
+}
+
+#[test]
+fn lp_config_validates_max_spread() {
+    let mut lp_config = native_xyk_config(None, None).lp_config;
+
+    for max_spread in [Decimal::zero(), Decimal::one(), Decimal::percent(150)] {
+        lp_config.max_spread = Some(max_spread);
+        assert_eq!(
+            lp_config.validate().unwrap_err().to_string(),
+            LibraryError::ConfigurationError(format!(
+                "Max spread {max_spread} must be between 0 and 1 (exclusive)"
+            ))
+            .to_string()
+        );
+    }
+
+    lp_config.max_spread = Some(Decimal::percent(99));
+    lp_config.validate().unwrap();
+}
+
+#[test]
+fn lp_config_rejects_slippage_tolerance_for_stable_pools() {
+    let mut lp_config = native_xyk_config(Some(Decimal::percent(2)), None).lp_config;
+    lp_config.pool_type = PoolKind::Stable.native_lp_token_pool_type();
+    assert_eq!(
+        lp_config.validate().unwrap_err().to_string(),
+        LibraryError::ConfigurationError(
+            "Slippage tolerance is not supported by Stable pools".to_string()
+        )
+        .to_string()
+    );
+
+    lp_config.slippage_tolerance = None;
+    lp_config.validate().unwrap();
+}
+
+#[test]
+fn pool_kind_of_pair_types() {
+    assert_eq!(
+        PoolType::NativeLpToken(
+            valence_astroport_utils::astroport_native_lp_token::PairType::Custom(
+                "concentrated".to_string(),
+            ),
+        )
+        .kind()
+        .unwrap(),
+        PoolKind::ConcentratedLiquidity
+    );
+
+    for pool_kind in [
+        PoolKind::Xyk,
+        PoolKind::ConcentratedLiquidity,
+        PoolKind::Stable,
+    ] {
+        assert_eq!(
+            pool_kind.native_lp_token_pool_type().kind().unwrap(),
+            pool_kind
+        );
+        assert_eq!(
+            pool_kind.cw20_lp_token_pool_type().kind().unwrap(),
+            pool_kind
+        );
+    }
+}
+
+#[test]
+fn unknown_custom_pair_types_are_rejected() {
+    let mut lp_config = native_xyk_config(None, None).lp_config;
+    lp_config.pool_type = PoolType::Cw20LpToken(
+        valence_astroport_utils::astroport_cw20_lp_token::PairType::Custom(
+            "xyk_sale_tax".to_string(),
+        ),
+    );
+
+    assert_eq!(
+        lp_config.validate().unwrap_err().to_string(),
+        "Configuration error: Unsupported custom pair type xyk_sale_tax, only concentrated custom pairs are supported"
+    );
+}
+
+#[test]
+fn lp_config_validates_slippage_parameters() {
+    let mut lp_config = native_xyk_config(Some(Decimal::percent(51)), None).lp_config;
+    assert_eq!(
+        lp_config.validate().unwrap_err().to_string(),
+        LibraryError::ConfigurationError(
+            "Slippage tolerance must be greater than 0 and at most 0.5".to_string()
+        )
+        .to_string()
+    );
+
+    lp_config.slippage_tolerance = Some(Decimal::zero());
+    assert!(lp_config.validate().is_err());
+
+    lp_config.slippage_tolerance = Some(Decimal::percent(50));
+    lp_config.min_lp_tokens_out = Some(Uint128::zero());
+    assert_eq!(
+        lp_config.validate().unwrap_err().to_string(),
+        LibraryError::ConfigurationError("Minimum LP tokens out cannot be zero".to_string())
+            .to_string()
+    );
+
+    // Cw20 pools can't enforce a minimum amount of LP tokens
+    lp_config.min_lp_tokens_out = Some(Uint128::one());
+    lp_config.pool_type =
+        PoolType::Cw20LpToken(valence_astroport_utils::astroport_cw20_lp_token::PairType::Xyk {});
+    assert_eq!(
+        lp_config.validate().unwrap_err().to_string(),
+        LibraryError::ConfigurationError(
+            "Minimum LP tokens out is only supported for pools with native LP tokens".to_string()
+        )
+        .to_string()
+    );
+
+    lp_config.min_lp_tokens_out = None;
+    lp_config.validate().unwrap();
+}
+
+#[test]
+fn provide_liquidity_reverts_below_min_lp_tokens_out() {
+    let setup = LPerTestSuite::default();
+    let wasm = Wasm::new(&setup.inner.app);
+    let bank = Bank::new(&setup.inner.app);
+
+    // Require far more LP tokens than the deposit can mint
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::UpdateConfig {
+            new_config: LibraryConfigUpdate {
+                input_addr: None,
+                output_addr: None,
+                pool_addr: None,
+                lp_config: Some(LiquidityProviderConfig {
+                    pool_type: PoolType::NativeLpToken(
+                        valence_astroport_utils::astroport_native_lp_token::PairType::Xyk {},
+                    ),
+                    asset_data: AssetData {
+                        asset1: setup.inner.pool_asset1.clone(),
+                        asset2: setup.inner.pool_asset2.clone(),
+                        additional_assets: vec![],
+                    },
+                    max_spread: None,
+                    slippage_tolerance: Some(Decimal::percent(1)),
+                    min_lp_tokens_out: Some(Uint128::new(u128::MAX)),
+                    provision_mode: ProvisionMode::Balanced,
+                }),
+                refund_account: OptionUpdate::None,
+                deposit_cap: OptionUpdate::None,
+                pool_allocations: OptionUpdate::None,
+                price_source: OptionUpdate::None,
+                deposit_addr: OptionUpdate::None,
+            },
+        },
+        &[],
+        setup.inner.owner_acc(),
+    )
+    .unwrap();
+
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::ProcessFunction(FunctionMsgs::ProvideDoubleSidedLiquidity {
+            expected_pool_ratio_range: None,
+        }),
+        &[],
+        setup.inner.processor_acc(),
+    )
+    .unwrap_err();
+
+    // The whole provision reverted, so the input account keeps its funds
+    assert_eq!(
+        query_balance_amount(
+            &bank,
+            setup.input_acc.clone(),
+            setup.inner.pool_asset1.clone()
+        ),
+        1_000_000
+    );
+    assert_eq!(
+        query_balance_amount(
+            &bank,
+            setup.input_acc.clone(),
+            setup.inner.pool_asset2.clone()
+        ),
+        2_000_000
+    );
+}
+
+// Mocks the pool of the config, whose LP tokens are held by the output account
+fn mock_lp_shares(shares: u128) -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+    use valence_astroport_utils::astroport_native_lp_token::{
+        Asset, AssetInfo, PairInfo, PairType, PoolQueryMsg, PoolResponse,
+    };
+
+    let cfg = native_xyk_config(None, Some(Uint128::new(1_000)));
+    let mut deps = mock_dependencies();
+    valence_library_base::save_config(&mut deps.storage, &cfg).unwrap();
+
+    let pool = cfg.pool_addr.clone();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { msg, .. } => {
+            let response = match from_json(msg).unwrap() {
+                PoolQueryMsg::Pool {} => to_json_binary(&PoolResponse {
+                    assets: ["untrn", "uusdc"]
+                        .into_iter()
+                        .map(|denom| Asset {
+                            info: AssetInfo::NativeToken {
+                                denom: denom.to_string(),
+                            },
+                            amount: Uint128::new(1_000_000),
+                        })
+                        .collect(),
+                    total_share: Uint128::new(1_000_000),
+                }),
+                PoolQueryMsg::Pair {} => to_json_binary(&PairInfo {
+                    asset_infos: vec![],
+                    contract_addr: pool.clone(),
+                    liquidity_token: "factory/pool/astroport/share".to_string(),
+                    pair_type: PairType::Xyk {},
+                }),
+                _ => panic!("Unexpected query"),
+            };
+            SystemResult::Ok(ContractResult::Ok(response.unwrap()))
+        }
+        _ => panic!("Unexpected query"),
+    });
+    deps.querier.bank.update_balance(
+        cfg.output_addr,
+        vec![cosmwasm_std::coin(shares, "factory/pool/astroport/share")],
+    );
+
+    deps
+}
+
+fn lp_shares_reply(deps: DepsMut, shares_before: u128) -> Result<Response, LibraryError> {
+    provision_reply(
+        deps,
+        ProvisionReply {
+            shares_check: Some(LpSharesCheck {
+                shares_before: Uint128::new(shares_before),
+                min_lp_tokens_out: Uint128::new(1_000),
+            }),
+            refund: false,
+        },
+    )
+}
+
+fn provision_reply(
+    deps: DepsMut,
+    provision_reply: ProvisionReply,
+) -> Result<Response, LibraryError> {
+    #[allow(deprecated)]
+    let result = SubMsgResult::Ok(SubMsgResponse {
+        events: vec![],
+        data: None,
+        msg_responses: vec![],
+    });
+    reply(
+        deps,
+        mock_env(),
+        Reply {
+            id: PROVISION_REPLY_ID,
+            payload: to_json_binary(&provision_reply).unwrap(),
+            gas_used: 0,
+            result,
+        },
+    )
+}
+
+#[test]
+fn reply_accepts_lp_tokens_minted_above_minimum() {
+    // 1_500 LP tokens were minted on top of the 500 already held
+    let mut deps = mock_lp_shares(2_000);
+
+    let response = lp_shares_reply(deps.as_mut(), 500).unwrap();
+    assert!(response
+        .attributes
+        .iter()
+        .any(|a| a.key == "lp_tokens_minted" && a.value == "1500"));
+}
+
+#[test]
+fn reply_reverts_lp_tokens_minted_below_minimum() {
+    // Only 999 LP tokens were minted
+    let mut deps = mock_lp_shares(1_499);
+
+    let err = lp_shares_reply(deps.as_mut(), 500).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Provision minted 999 LP tokens, below the minimum of 1000"
+    );
+}
+
+#[test]
+fn provision_is_checked_in_reply_when_min_lp_tokens_out_is_set() {
+    let mut deps = mock_lp_shares(500);
+    let cfg = native_xyk_config(None, Some(Uint128::new(1_000)));
+    deps.querier.bank.update_balance(
+        cfg.input_addr.clone(),
+        vec![
+            cosmwasm_std::coin(1_000, "untrn"),
+            cosmwasm_std::coin(1_000, "uusdc"),
+        ],
+    );
+
+    let response = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&cfg.input_addr, &[]),
+        FunctionMsgs::ProvideDoubleSidedLiquidity {
+            expected_pool_ratio_range: None,
+        },
+        cfg,
+    )
+    .unwrap();
+
+    assert_eq!(response.messages.len(), 1);
+    assert_eq!(response.messages[0].id, PROVISION_REPLY_ID);
+    assert_eq!(response.messages[0].reply_on, ReplyOn::Success);
+    let provision_reply: ProvisionReply = from_json(&response.messages[0].payload).unwrap();
+    assert_eq!(
+        provision_reply.shares_check.unwrap().shares_before,
+        Uint128::new(500)
+    );
+    assert!(!provision_reply.refund);
+}
+
+// Provision mode tests
+
+fn update_provision_mode(setup: &LPerTestSuite, provision_mode: ProvisionMode) {
+    let wasm = Wasm::new(&setup.inner.app);
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::UpdateConfig {
+            new_config: LibraryConfigUpdate {
+                input_addr: None,
+                output_addr: None,
+                pool_addr: None,
+                lp_config: Some(LiquidityProviderConfig {
+                    pool_type: PoolType::NativeLpToken(
+                        valence_astroport_utils::astroport_native_lp_token::PairType::Xyk {},
+                    ),
+                    asset_data: AssetData {
+                        asset1: setup.inner.pool_asset1.clone(),
+                        asset2: setup.inner.pool_asset2.clone(),
+                        additional_assets: vec![],
+                    },
+                    max_spread: None,
+                    slippage_tolerance: None,
+                    min_lp_tokens_out: None,
+                    provision_mode,
+                }),
+                refund_account: OptionUpdate::None,
+                deposit_cap: OptionUpdate::None,
+                pool_allocations: OptionUpdate::None,
+                price_source: OptionUpdate::None,
+                deposit_addr: OptionUpdate::None,
+            },
+        },
+        &[],
+        setup.inner.owner_acc(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn auto_balance_swap_amounts_follow_pool_ratio() {
+    // Pool ratio is 1:2, excess of asset1
+    assert_eq!(
+        calculate_auto_balance_swap(2_000_000, 2_000_000, 1_000_000_000, 2_000_000_000).unwrap(),
+        (Uint128::new(500_000), Uint128::zero())
+    );
+
+    // Excess of asset2
+    assert_eq!(
+        calculate_auto_balance_swap(1_000_000, 4_000_000, 1_000_000_000, 2_000_000_000).unwrap(),
+        (Uint128::zero(), Uint128::new(1_000_000))
+    );
+
+    // Only one of the assets is available
+    assert_eq!(
+        calculate_auto_balance_swap(0, 3_000_001, 1_000_000_000, 2_000_000_000).unwrap(),
+        (Uint128::zero(), Uint128::new(1_500_000))
+    );
+
+    // Balances already follow the pool ratio
+    assert_eq!(
+        calculate_auto_balance_swap(1_000_000, 2_000_000, 1_000_000_000, 2_000_000_000).unwrap(),
+        (Uint128::zero(), Uint128::zero())
+    );
+}
+
+#[test]
+fn lp_config_validates_provision_mode() {
+    let mut lp_config = native_xyk_config(None, None).lp_config;
+
+    lp_config.provision_mode = ProvisionMode::SingleSided {
+        asset: "uatom".to_string(),
+    };
+    assert_eq!(
+        lp_config.validate().unwrap_err().to_string(),
+        LibraryError::ConfigurationError(
+            "Single sided provision asset uatom is not part of the pool assets".to_string()
+        )
+        .to_string()
+    );
+
+    lp_config.provision_mode = ProvisionMode::SingleSided {
+        asset: "untrn".to_string(),
+    };
+    assert_eq!(
+        lp_config.validate().unwrap_err().to_string(),
+        LibraryError::ConfigurationError(
+            "Single sided provision is not supported by Xyk pools, use AutoBalance instead"
+                .to_string()
+        )
+        .to_string()
+    );
+
+    lp_config.pool_type = PoolType::NativeLpToken(
+        valence_astroport_utils::astroport_native_lp_token::PairType::Custom(
+            "concentrated".to_string(),
+        ),
+    );
+    lp_config.validate().unwrap();
+
+    lp_config.provision_mode = ProvisionMode::AutoBalance;
+    lp_config.validate().unwrap();
+}
+
+#[test]
+fn single_sided_mode_provides_only_the_configured_asset() {
+    let mut cfg = native_xyk_config(Some(Decimal::percent(2)), None);
+    cfg.lp_config.pool_type = PoolType::NativeLpToken(
+        valence_astroport_utils::astroport_native_lp_token::PairType::Custom(
+            "concentrated".to_string(),
+        ),
+    );
+    cfg.lp_config.provision_mode = ProvisionMode::SingleSided {
+        asset: "untrn".to_string(),
+    };
+
+    let mut deps = mock_dependencies();
+    let msgs = astroport_native::create_single_sided_liquidity_msg(
+        &deps.as_mut(),
+        &cfg,
+        &cosmwasm_std::coin(1_000_000, "untrn"),
+        &cosmwasm_std::coin(0, "uusdc"),
+    )
+    .unwrap();
+    assert_eq!(msgs.len(), 1);
+
+    match &msgs[0] {
+        CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }) => {
+            assert_eq!(funds, &vec![cosmwasm_std::coin(1_000_000, "untrn")]);
+        }
+        _ => panic!("Expected a wasm execute message"),
+    }
+
+    match decode_native_execute_msg(&msgs[0]) {
+        valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::ProvideLiquidity {
+            assets,
+            slippage_tolerance,
+            ..
+        } => {
+            assert_eq!(assets[0].amount, Uint128::new(1_000_000));
+            assert_eq!(assets[1].amount, Uint128::zero());
+            assert_eq!(slippage_tolerance, Some(Decimal::percent(2)));
+        }
+        _ => panic!("Expected a provide liquidity message"),
+    }
+}
+
+#[test]
+fn auto_balance_mode_swaps_excess_before_providing() {
+    let cfg = native_xyk_config(None, None);
+
+    // Simulated quote returned by the pool for the excess of asset1
+    let mut deps = mock_dependencies();
+    let pool_addr = cfg.pool_addr.clone();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } if contract_addr.as_str() == pool_addr.as_str() => {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(
+                    &valence_astroport_utils::astroport_native_lp_token::SimulationResponse {
+                        return_amount: Uint128::new(995_000),
+                        spread_amount: Uint128::new(2_000),
+                        commission_amount: Uint128::new(3_000),
+                    },
+                )
+                .unwrap(),
+            ))
+        }
+        _ => panic!("Unexpected query"),
+    });
+
+    let msgs = astroport_native::create_auto_balance_liquidity_msgs(
+        &deps.as_mut(),
+        &cfg,
+        &cosmwasm_std::coin(500_000, "untrn"),
+        &cosmwasm_std::coin(1_500_000, "untrn"),
+        &cosmwasm_std::coin(2_000_000, "uusdc"),
+    )
+    .unwrap();
+    assert_eq!(msgs.len(), 2);
+
+    match decode_native_execute_msg(&msgs[0]) {
+        valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::Swap {
+            offer_asset,
+            ..
+        } => {
+            assert_eq!(offer_asset.amount, Uint128::new(500_000));
+        }
+        _ => panic!("Expected a swap message"),
+    }
+
+    match decode_native_execute_msg(&msgs[1]) {
+        valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::ProvideLiquidity {
+            assets,
+            ..
+        } => {
+            assert_eq!(assets[0].amount, Uint128::new(1_500_000));
+            assert_eq!(assets[1].amount, Uint128::new(2_995_000));
+        }
+        _ => panic!("Expected a provide liquidity message"),
+    }
+}
+
+#[test]
+fn provide_liquidity_balanced_mode() {
+    let setup = LPerTestSuite::default();
+    let wasm = Wasm::new(&setup.inner.app);
+    let bank = Bank::new(&setup.inner.app);
+
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::ProcessFunction(FunctionMsgs::ProvideLiquidity {
+            expected_pool_ratio_range: None,
+        }),
+        &[],
+        setup.inner.processor_acc(),
+    )
+    .unwrap();
+
+    // Output account should have the LP tokens
+    assert!(
+        query_balance_amount(
+            &bank,
+            setup.output_acc.clone(),
+            setup.inner.pool_native_liquidity_token.clone()
+        ) > 0
+    );
+}
+
+#[test]
+fn provide_liquidity_auto_balance_mode() {
+    // Funds don't follow the 1:2 pool ratio, there is an excess of asset1
+    let setup = LPerTestSuite::new(true, 2_000_000, 2_000_000);
+    let wasm = Wasm::new(&setup.inner.app);
+    let bank = Bank::new(&setup.inner.app);
+
+    update_provision_mode(&setup, ProvisionMode::AutoBalance);
+
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::ProcessFunction(FunctionMsgs::ProvideLiquidity {
+            expected_pool_ratio_range: None,
+        }),
+        &[],
+        setup.inner.processor_acc(),
+    )
+    .unwrap();
+
+    // Output account should have the LP tokens
+    assert!(
+        query_balance_amount(
+            &bank,
+            setup.output_acc.clone(),
+            setup.inner.pool_native_liquidity_token.clone()
+        ) > 0
+    );
+
+    // The remaining asset1 was provided together with the swapped amount
+    assert_eq!(
+        query_balance_amount(
+            &bank,
+            setup.input_acc.clone(),
+            setup.inner.pool_asset1.clone()
+        ),
+        0
+    );
+}
+
+fn native_stable_three_asset_config() -> Config {
+    let mut cfg = native_xyk_config(None, None);
+    cfg.lp_config.pool_type = PoolType::NativeLpToken(
+        valence_astroport_utils::astroport_native_lp_token::PairType::Stable {},
+    );
+    cfg.lp_config.asset_data =
+        AssetData::new("uusdc", "uusdt").with_additional_assets(vec!["udai".to_string()]);
+    cfg
+}
+
+#[test]
+fn lp_config_validates_three_asset_data() {
+    let mut lp_config = native_stable_three_asset_config().lp_config;
+    lp_config.validate().unwrap();
+
+    // Any of the three assets can be provided single sided
+    lp_config.provision_mode = ProvisionMode::SingleSided {
+        asset: "udai".to_string(),
+    };
+    lp_config.validate().unwrap();
+
+    // Auto balancing only works with two assets
+    lp_config.provision_mode = ProvisionMode::AutoBalance;
+    assert_eq!(
+        lp_config.validate().unwrap_err().to_string(),
+        LibraryError::ConfigurationError("Only pools with two assets are supported".to_string())
+            .to_string()
+    );
+
+    lp_config.provision_mode = ProvisionMode::Balanced;
+    lp_config.asset_data.additional_assets = vec!["uusdt".to_string()];
+    assert_eq!(
+        lp_config.validate().unwrap_err().to_string(),
+        LibraryError::ConfigurationError("Duplicate asset uusdt in asset data".to_string())
+            .to_string()
+    );
+}
+
+#[test]
+fn three_asset_config_must_match_pool_assets() {
+    use valence_astroport_utils::astroport_native_lp_token::{AssetInfo, PairInfo, PairType};
+
+    let cfg = native_stable_three_asset_config();
+    let library_config = LibraryConfig::new(
+        &cfg.input_addr,
+        &cfg.output_addr,
+        cfg.pool_addr.to_string(),
+        cfg.lp_config.clone(),
+    );
+
+    let pool_info = |denoms: &[&str]| PairInfo {
+        asset_infos: denoms
+            .iter()
+            .map(|denom| AssetInfo::NativeToken {
+                denom: denom.to_string(),
+            })
+            .collect(),
+        contract_addr: cfg.pool_addr.clone(),
+        liquidity_token: "factory/pool/astroport/share".to_string(),
+        pair_type: PairType::Stable {},
+    };
+
+    // The pool has the three configured assets
+    let mut deps = mock_dependencies();
+    let response = to_json_binary(&pool_info(&["uusdc", "uusdt", "udai"])).unwrap();
+    deps.querier
+        .update_wasm(move |_| SystemResult::Ok(ContractResult::Ok(response.clone())));
+    library_config.validate(deps.as_ref()).unwrap();
+
+    // The pool only has two of them
+    let mut deps = mock_dependencies();
+    let response = to_json_binary(&pool_info(&["uusdc", "uusdt"])).unwrap();
+    deps.querier
+        .update_wasm(move |_| SystemResult::Ok(ContractResult::Ok(response.clone())));
+    assert_eq!(
+        library_config
+            .validate(deps.as_ref())
+            .unwrap_err()
+            .to_string(),
+        LibraryError::ConfigurationError("Pool has 2 assets but 3 were configured".to_string())
+            .to_string()
+    );
+}
+
+#[test]
+fn multi_asset_provide_amounts_follow_pool_ratios() {
+    // The first asset has the lowest balance relative to the pool so it limits the rest
+    assert_eq!(
+        calculate_multi_asset_provide_amounts(
+            &[1_000, 3_000, 5_000],
+            &[1_000_000, 2_000_000, 4_000_000]
+        )
+        .unwrap(),
+        vec![1_000, 2_000, 4_000]
+    );
+
+    // The third asset limits the rest, amounts are rounded down
+    assert_eq!(
+        calculate_multi_asset_provide_amounts(
+            &[10_000, 10_000, 1_000],
+            &[3_000_000, 3_000_000, 1_000_000]
+        )
+        .unwrap(),
+        vec![3_000, 3_000, 1_000]
+    );
+
+    // Two asset path gives the same result as before
+    assert_eq!(
+        calculate_multi_asset_provide_amounts(
+            &[1_000_000, 4_000_000],
+            &[1_000_000_000, 2_000_000_000]
+        )
+        .unwrap(),
+        vec![1_000_000, 2_000_000]
+    );
+
+    // All the assets must be available
+    assert!(calculate_multi_asset_provide_amounts(
+        &[1_000, 0, 1_000],
+        &[1_000_000, 1_000_000, 1_000_000]
+    )
+    .is_err());
+}
+
+#[test]
+fn three_asset_provide_messages_include_all_assets() {
+    let cfg = native_stable_three_asset_config();
+
+    let msg = astroport_native::create_provide_liquidity_msg(&cfg, &[1_000, 2_000, 3_000]).unwrap();
+    match &msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }) => assert_eq!(
+            funds,
+            &vec![
+                cosmwasm_std::coin(1_000, "uusdc"),
+                cosmwasm_std::coin(2_000, "uusdt"),
+                cosmwasm_std::coin(3_000, "udai"),
+            ]
+        ),
+        _ => panic!("Expected a wasm execute message"),
+    }
+    match decode_native_execute_msg(&msg) {
+        valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::ProvideLiquidity {
+            assets,
+            ..
+        } => {
+            assert_eq!(assets.len(), 3);
+            assert_eq!(assets[2].amount, Uint128::new(3_000));
+        }
+        _ => panic!("Expected a provide liquidity message"),
+    }
+
+    // Single sided provision of the third asset passes the other two with zero amounts
+    let msgs = astroport_native::create_single_sided_liquidity_msg(
+        &mock_dependencies().as_mut(),
+        &cfg,
+        &cosmwasm_std::coin(5_000, "udai"),
+        &cosmwasm_std::coin(0, "uusdc"),
+    )
+    .unwrap();
+    assert_eq!(msgs.len(), 1);
+    match decode_native_execute_msg(&msgs[0]) {
+        valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::ProvideLiquidity {
+            assets,
+            ..
+        } => {
+            let amounts: Vec<(String, Uint128)> = assets
+                .into_iter()
+                .map(|asset| {
+                    match asset.info {
+                    valence_astroport_utils::astroport_native_lp_token::AssetInfo::NativeToken {
+                        denom,
+                    } => (denom, asset.amount),
+                    _ => panic!("Expected a native token"),
+                }
+                })
+                .collect();
+            assert_eq!(
+                amounts,
+                vec![
+                    ("udai".to_string(), Uint128::new(5_000)),
+                    ("uusdc".to_string(), Uint128::zero()),
+                    ("uusdt".to_string(), Uint128::zero()),
+                ]
+            );
+        }
+        _ => panic!("Expected a provide liquidity message"),
+    }
+}
+
+#[test]
+fn refund_msgs_sweep_nonzero_residuals() {
+    let mut cfg = native_xyk_config(None, None);
+    let residuals = [
+        cosmwasm_std::coin(0, "untrn"),
+        cosmwasm_std::coin(500, "uusdc"),
+    ];
+
+    // Without refund account the residuals stay in the input account
+    assert!(create_refund_msgs(&cfg, &residuals).is_empty());
+
+    // Only the asset with a residual balance is swept
+    let refund_account = MockApi::default().addr_make("refund_account");
+    cfg.refund_account = Some(refund_account.clone());
+    assert_eq!(
+        create_refund_msgs(&cfg, &residuals),
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            to_address: refund_account.to_string(),
+            amount: vec![cosmwasm_std::coin(500, "uusdc")],
+        })]
+    );
+
+    // Nothing is swept when everything is provided
+    assert!(create_refund_msgs(
+        &cfg,
+        &[
+            cosmwasm_std::coin(0, "untrn"),
+            cosmwasm_std::coin(0, "uusdc")
+        ]
+    )
+    .is_empty());
+}
+
+#[test]
+fn residuals_are_swept_in_the_reply_of_the_provision() {
+    let mut deps = mock_lp_shares(0);
+    let mut cfg = native_xyk_config(None, None);
+    let refund_account = MockApi::default().addr_make("refund_account");
+    cfg.refund_account = Some(refund_account.clone());
+    valence_library_base::save_config(&mut deps.storage, &cfg).unwrap();
+    deps.querier.bank.update_balance(
+        cfg.input_addr.clone(),
+        vec![
+            cosmwasm_std::coin(1_000, "untrn"),
+            cosmwasm_std::coin(1_500, "uusdc"),
+        ],
+    );
+
+    // The provision only sends the provide liquidity message, the residuals are swept once it has executed
+    let response = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&cfg.input_addr, &[]),
+        FunctionMsgs::ProvideDoubleSidedLiquidity {
+            expected_pool_ratio_range: None,
+        },
+        cfg.clone(),
+    )
+    .unwrap();
+    assert_eq!(response.messages.len(), 1);
+    assert_eq!(response.messages[0].id, PROVISION_REPLY_ID);
+    let payload: ProvisionReply = from_json(&response.messages[0].payload).unwrap();
+    assert_eq!(
+        payload,
+        ProvisionReply {
+            shares_check: None,
+            refund: true,
+        }
+    );
+
+    // After the provision the input account holds what the pool didn't take, including what it sent back
+    deps.querier.bank.update_balance(
+        cfg.input_addr.clone(),
+        vec![
+            cosmwasm_std::coin(0, "untrn"),
+            cosmwasm_std::coin(510, "uusdc"),
+        ],
+    );
+    let response = provision_reply(deps.as_mut(), payload.clone()).unwrap();
+    assert_eq!(response.messages.len(), 1);
+    match &response.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr, msg, ..
+        }) => {
+            assert_eq!(contract_addr, cfg.input_addr.as_str());
+            let msg: valence_account_utils::msg::ExecuteMsg = from_json(msg).unwrap();
+            assert_eq!(
+                msg,
+                valence_account_utils::msg::ExecuteMsg::ExecuteMsg {
+                    msgs: vec![CosmosMsg::Bank(BankMsg::Send {
+                        to_address: refund_account.to_string(),
+                        amount: vec![cosmwasm_std::coin(510, "uusdc")],
+                    })],
+                }
+            );
+        }
+        _ => panic!("Expected the refund to be executed on behalf of the input account"),
+    }
+    assert!(response
+        .attributes
+        .iter()
+        .any(|a| a.key == "refund" && a.value == "510uusdc"));
+
+    // Nothing is left to sweep when the pool took everything
+    deps.querier.bank.update_balance(
+        cfg.input_addr.clone(),
+        vec![
+            cosmwasm_std::coin(0, "untrn"),
+            cosmwasm_std::coin(0, "uusdc"),
+        ],
+    );
+    let response = provision_reply(deps.as_mut(), payload).unwrap();
+    assert!(response.messages.is_empty());
+}
+
+// Pool allocation tests
+
+fn allocated_xyk_config() -> Config {
+    let api = MockApi::default();
+    let mut cfg = native_xyk_config(None, None);
+    // The second pool lists its assets in the reverse order
+    cfg.pool_allocations = vec![
+        CheckedPoolAllocation {
+            pool: api.addr_make("pool_a"),
+            asset_data: AssetData::new("untrn", "uusdc"),
+            weight: Decimal::percent(60),
+        },
+        CheckedPoolAllocation {
+            pool: api.addr_make("pool_b"),
+            asset_data: AssetData::new("uusdc", "untrn"),
+            weight: Decimal::percent(40),
+        },
+    ];
+    cfg
+}
+
+#[test]
+fn allocated_provision_splits_deposit_across_pools() {
+    use valence_astroport_utils::astroport_native_lp_token::{Asset, AssetInfo, PoolResponse};
+
+    let cfg = allocated_xyk_config();
+    let pool_a = cfg.pool_allocations[0].pool.clone();
+    let pool_b = cfg.pool_allocations[1].pool.clone();
+
+    // Pool A trades 1 untrn for 2 uusdc, pool B 1 untrn for 4 uusdc
+    let mut deps = mock_dependencies();
+    let reserves = move |contract_addr: &str| {
+        let (untrn, uusdc) = if contract_addr == pool_a.as_str() {
+            (1_000_000u128, 2_000_000u128)
+        } else {
+            (1_000_000u128, 4_000_000u128)
+        };
+        PoolResponse {
+            assets: vec![
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "untrn".to_string(),
+                    },
+                    amount: Uint128::new(untrn),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusdc".to_string(),
+                    },
+                    amount: Uint128::new(uusdc),
+                },
+            ],
+            total_share: Uint128::new(1_000_000),
+        }
+    };
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } => SystemResult::Ok(ContractResult::Ok(
+            to_json_binary(&reserves(contract_addr)).unwrap(),
+        )),
+        _ => panic!("Unexpected query"),
+    });
+
+    let balances = [
+        cosmwasm_std::coin(1_000_000, "untrn"),
+        cosmwasm_std::coin(2_000_000, "uusdc"),
+    ];
+    let (msgs, provided_amounts) =
+        create_allocated_provide_msgs(deps.as_ref(), &cfg, &balances, None).unwrap();
+    assert_eq!(msgs.len(), 2);
+
+    // Pool A is given 60% of both balances, which follow its ratio
+    match &msgs[0] {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            funds,
+            ..
+        }) => {
+            assert_eq!(contract_addr, cfg.pool_allocations[0].pool.as_str());
+            assert_eq!(
+                funds,
+                &vec![
+                    cosmwasm_std::coin(600_000, "untrn"),
+                    cosmwasm_std::coin(1_200_000, "uusdc"),
+                ]
+            );
+        }
+        _ => panic!("Expected a wasm execute message"),
+    }
+
+    // Pool B is given the remaining 40%, of which the uusdc limits the untrn provided at its ratio
+    match &msgs[1] {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            funds,
+            ..
+        }) => {
+            assert_eq!(contract_addr, cfg.pool_allocations[1].pool.as_str());
+            assert_eq!(
+                funds,
+                &vec![
+                    cosmwasm_std::coin(800_000, "uusdc"),
+                    cosmwasm_std::coin(200_000, "untrn"),
+                ]
+            );
+        }
+        _ => panic!("Expected a wasm execute message"),
+    }
+    match decode_native_execute_msg(&msgs[1]) {
+        valence_astroport_utils::astroport_native_lp_token::ExecuteMsg::ProvideLiquidity {
+            assets,
+            receiver,
+            ..
+        } => {
+            assert_eq!(assets[0].amount, Uint128::new(800_000));
+            assert_eq!(assets[1].amount, Uint128::new(200_000));
+            assert_eq!(receiver, Some(cfg.output_addr.to_string()));
+        }
+        _ => panic!("Expected a provide liquidity message"),
+    }
+
+    // Totals are given in the order of the assets of the config
+    assert_eq!(provided_amounts, vec![800_000, 2_000_000]);
+}
+
+#[test]
+fn pool_allocations_are_validated() {
+    let cfg = allocated_xyk_config();
+    validate_pool_allocations(&cfg.pool_allocations, &cfg.lp_config, false).unwrap();
+
+    // Weights must sum to one
+    let mut allocations = cfg.pool_allocations.clone();
+    allocations[1].weight = Decimal::percent(30);
+    assert_eq!(
+        validate_pool_allocations(&allocations, &cfg.lp_config, false)
+            .unwrap_err()
+            .to_string(),
+        "Configuration error: Pool allocation weights must sum to 1, got 0.9"
+    );
+
+    // Each pool must be provided the assets of the LP config
+    let mut allocations = cfg.pool_allocations.clone();
+    allocations[1].asset_data = AssetData::new("uusdc", "uatom");
+    assert!(
+        validate_pool_allocations(&allocations, &cfg.lp_config, false)
+            .unwrap_err()
+            .to_string()
+            .contains("must be the assets of the LP config")
+    );
+
+    // Pools can't be allocated twice
+    let mut allocations = cfg.pool_allocations.clone();
+    allocations[1].pool = allocations[0].pool.clone();
+    assert!(
+        validate_pool_allocations(&allocations, &cfg.lp_config, false)
+            .unwrap_err()
+            .to_string()
+            .contains("is allocated more than once")
+    );
+
+    // Allocations only split balanced provisions, without deposit cap
+    assert!(validate_pool_allocations(&cfg.pool_allocations, &cfg.lp_config, true).is_err());
+    let mut lp_config = cfg.lp_config.clone();
+    lp_config.provision_mode = ProvisionMode::AutoBalance;
+    assert!(validate_pool_allocations(&cfg.pool_allocations, &lp_config, false).is_err());
+
+    // Allocations are checked when validating the library config
+    let api = MockApi::default();
+    let library_config = LibraryConfig::new(
+        &api.addr_make("input_account"),
+        &api.addr_make("output_account"),
+        api.addr_make("pool").to_string(),
+        cfg.lp_config.clone(),
+    );
+    library_config
+        .clone()
+        .with_pool_allocations(vec![
+            PoolAllocation::new(
+                api.addr_make("pool_a"),
+                AssetData::new("untrn", "uusdc"),
+                Decimal::percent(50),
+            ),
+            PoolAllocation::new(
+                api.addr_make("pool_b"),
+                AssetData::new("uusdc", "untrn"),
+                Decimal::percent(50),
+            ),
+        ])
+        .pre_validate(&api)
+        .unwrap();
+
+    // An empty list is rejected, the allocations must be unset instead
+    assert!(library_config
+        .with_pool_allocations(vec![])
+        .pre_validate(&api)
+        .is_err());
+}
+
+// Deposit cap tests
+
+// Caps the position at `max_tvl` units of the first pool asset
+fn update_deposit_cap(setup: &LPerTestSuite, max_tvl: Option<Uint128>) {
+    let wasm = Wasm::new(&setup.inner.app);
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::UpdateConfig {
+            new_config: LibraryConfigUpdate {
+                deposit_cap: OptionUpdate::Set(
+                    max_tvl.map(|max_tvl| DepositCap::new(max_tvl, &setup.inner.pool_asset1)),
+                ),
+                pool_allocations: OptionUpdate::None,
+                ..Default::default()
+            },
+        },
+        &[],
+        setup.inner.owner_acc(),
+    )
+    .unwrap();
+}
+
+fn query_deposit_cap(setup: &LPerTestSuite) -> DepositCapResponse {
+    let wasm = Wasm::new(&setup.inner.app);
+    wasm.query::<QueryMsg, DepositCapResponse>(&setup.lper_addr, &QueryMsg::DepositCap {})
+        .unwrap()
+}
+
+#[test]
+fn position_value_is_measured_in_stable_denom() {
+    let deps = mock_dependencies();
+    let valuation = Valuation {
+        querier: QuerierWrapper::new(&deps.querier),
+        price_oracle: None,
+        denoms: vec!["untrn", "uusdc"],
+        stable_denom: "uusdc",
+    };
+
+    // Pool ratio is 1:2, so 1 unit of asset1 is worth 2 units of the stable asset2
+    let reserves = [1_000_000, 2_000_000];
+    assert_eq!(
+        assets_value(&reserves, &[1_000, 2_000], 1).unwrap(),
+        Uint128::new(4_000)
+    );
+
+    // Holding 10% of the pool
+    let position = PoolPosition {
+        reserves: reserves.to_vec(),
+        total_share: Uint128::new(1_000),
+        shares: Uint128::new(100),
+    };
+    assert_eq!(
+        valuation.position_value(&position).unwrap(),
+        Uint128::new(400_000)
+    );
+
+    // A pool ratio can only be taken to a pool asset
+    let other = Valuation {
+        stable_denom: "uatom",
+        ..valuation
+    };
+    assert!(matches!(
+        other.position_value(&position).unwrap_err(),
+        LibraryError::ConfigurationError(_)
+    ));
+}
+
+#[test]
+fn empty_pool_is_worth_nothing() {
+    let deps = mock_dependencies();
+    let valuation = Valuation {
+        querier: QuerierWrapper::new(&deps.querier),
+        price_oracle: None,
+        denoms: vec!["untrn", "uusdc"],
+        stable_denom: "uusdc",
+    };
+    let empty = PoolPosition {
+        reserves: vec![0, 0],
+        total_share: Uint128::zero(),
+        shares: Uint128::zero(),
+    };
+    assert_eq!(valuation.position_value(&empty).unwrap(), Uint128::zero());
+
+    // The first provision sets the pool ratio it is valued at
+    assert_eq!(
+        valuation
+            .value_after_provision(&empty, &[1_000, 2_000])
+            .unwrap(),
+        Uint128::new(4_000)
+    );
+    // And a single sided provision is only worth its stable amount
+    assert_eq!(
+        valuation
+            .value_after_provision(&empty, &[0, 2_000])
+            .unwrap(),
+        Uint128::new(2_000)
+    );
+}
+
+#[test]
+fn deposit_cap_is_validated() {
+    let api = MockApi::default();
+    let cfg = LibraryConfig::new(
+        api.addr_make("input_account").as_str(),
+        api.addr_make("output_account").as_str(),
+        api.addr_make("pool").to_string(),
+        native_xyk_config(None, None).lp_config,
+    );
+    cfg.pre_validate(&api).unwrap();
+    cfg.clone()
+        .with_deposit_cap(DepositCap::new(Uint128::new(1_000_000), "uusdc"))
+        .pre_validate(&api)
+        .unwrap();
+
+    assert_eq!(
+        cfg.clone()
+            .with_deposit_cap(DepositCap::new(Uint128::zero(), "uusdc"))
+            .pre_validate(&api)
+            .unwrap_err()
+            .to_string(),
+        "Configuration error: Max TVL cannot be zero, unset the deposit cap to remove it"
+    );
+
+    // Without a price source, the position is valued at the pool ratio to the stable denom
+    assert_eq!(
+        cfg.clone()
+            .with_deposit_cap(DepositCap::new(Uint128::new(1_000_000), "uatom"))
+            .pre_validate(&api)
+            .unwrap_err()
+            .to_string(),
+        "Configuration error: Deposit cap stable denom uatom must be a pool asset to value the position without a price source"
+    );
+
+    // A TWAP price source quotes its prices in the stable denom
+    let twap = PriceSource::AstroportTwap {
+        pool_addr: api.addr_make("twap_pool").to_string(),
+        base_denom: "untrn".to_string(),
+        quote_denom: "uusdc".to_string(),
+        seconds_ago: 3_600,
+    };
+    assert_eq!(
+        cfg.with_price_source(twap)
+            .with_deposit_cap(DepositCap::new(Uint128::new(1_000_000), "untrn"))
+            .pre_validate(&api)
+            .unwrap_err()
+            .to_string(),
+        "Configuration error: Deposit cap stable denom untrn must be the quote denom of the price source"
+    );
+}
+
+// Price source quoting fixed prices, in USD
+struct MockPriceOracle;
+
+impl PriceOracle for MockPriceOracle {
+    fn price(&self, _querier: &QuerierWrapper, denom: &str) -> Result<Decimal, LibraryError> {
+        match denom {
+            "untrn" => Ok(Decimal::percent(50)),
+            "uusdc" => Ok(Decimal::one()),
+            _ => Err(LibraryError::ExecutionError(format!(
+                "No price for {denom}"
+            ))),
+        }
+    }
+}
+
+#[test]
+fn price_source_values_the_position_for_the_deposit_cap() {
+    let deps = mock_dependencies();
+    let oracle = MockPriceOracle;
+    let valuation = Valuation {
+        querier: QuerierWrapper::new(&deps.querier),
+        price_oracle: Some(&oracle),
+        denoms: vec!["untrn", "uusdc"],
+        stable_denom: "uusdc",
+    };
+
+    // Holding 10% of a pool with a 1:2 ratio
+    let position = PoolPosition {
+        reserves: vec![1_000_000, 2_000_000],
+        total_share: Uint128::new(1_000),
+        shares: Uint128::new(100),
+    };
+
+    // 100_000untrn at 0.5 and 200_000uusdc at 1
+    assert_eq!(
+        valuation.position_value(&position).unwrap(),
+        Uint128::new(250_000)
+    );
+    let tvl = valuation
+        .value_after_provision(&position, &[1_000, 2_000])
+        .unwrap();
+    assert_eq!(tvl, Uint128::new(252_500));
+
+    ensure_below_deposit_cap(
+        &valuation,
+        &position,
+        &[1_000, 2_000],
+        &DepositCap::new(Uint128::new(252_500), "uusdc"),
+    )
+    .unwrap();
+    assert_eq!(
+        ensure_below_deposit_cap(
+            &valuation,
+            &position,
+            &[1_000, 2_000],
+            &DepositCap::new(Uint128::new(252_499), "uusdc"),
+        )
+        .unwrap_err()
+        .to_string(),
+        "Execution error: Deposit cap exceeded: the position would be worth 252500uusdc but the max TVL is 252499uusdc"
+    );
+
+    // Each asset is valued at its own price: 5_000untrn are worth as much as 2_500uusdc
+    ensure_below_deposit_cap(
+        &valuation,
+        &position,
+        &[5_000, 0],
+        &DepositCap::new(Uint128::new(252_500), "uusdc"),
+    )
+    .unwrap();
+
+    // Without a price source, the position is valued at the pool ratio to the stable denom
+    let valuation = Valuation {
+        price_oracle: None,
+        ..valuation
+    };
+    assert_eq!(
+        valuation
+            .value_after_provision(&position, &[1_000, 2_000])
+            .unwrap(),
+        Uint128::new(404_000)
+    );
+}
+
+#[test]
+fn price_source_must_price_the_pool_assets() {
+    let api = MockApi::default();
+    let cfg = LibraryConfig::new(
+        api.addr_make("input_account").as_str(),
+        api.addr_make("output_account").as_str(),
+        api.addr_make("pool").to_string(),
+        native_xyk_config(None, None).lp_config,
+    );
+    let twap = |quote_denom: &str| PriceSource::AstroportTwap {
+        pool_addr: api.addr_make("twap_pool").to_string(),
+        base_denom: "untrn".to_string(),
+        quote_denom: quote_denom.to_string(),
+        seconds_ago: 3_600,
+    };
+
+    cfg.clone()
+        .with_price_source(twap("uusdc"))
+        .pre_validate(&api)
+        .unwrap();
+    assert_eq!(
+        cfg.clone()
+            .with_price_source(twap("uatom"))
+            .pre_validate(&api)
+            .unwrap_err()
+            .to_string(),
+        "Configuration error: Price source can't price pool asset uusdc"
+    );
+
+    // The denoms priced by an oracle contract are only known when querying it
+    cfg.with_price_source(PriceSource::Oracle {
+        contract_addr: api.addr_make("oracle").to_string(),
+    })
+    .pre_validate(&api)
+    .unwrap();
+}
+
+#[test]
+fn unavailable_prices_block_or_allow_the_deposit_cap_check() {
+    let deps = mock_dependencies();
+    let oracle = MockPriceOracle;
+    // The oracle has no price for uatom
+    let valuation = Valuation {
+        querier: QuerierWrapper::new(&deps.querier),
+        price_oracle: Some(&oracle),
+        denoms: vec!["untrn", "uatom"],
+        stable_denom: "uusdc",
+    };
+    let position = PoolPosition {
+        reserves: vec![1_000_000, 2_000_000],
+        total_share: Uint128::new(1_000),
+        shares: Uint128::new(100),
+    };
+    let ensure_with_policy = |valuation: &Valuation, policy| {
+        ensure_below_deposit_cap(
+            valuation,
+            &position,
+            &[1_000, 2_000],
+            &DepositCap::new(Uint128::new(1_000_000), "uusdc")
+                .with_price_unavailable_policy(policy),
+        )
+    };
+
+    assert_eq!(
+        ensure_with_policy(&valuation, PriceUnavailablePolicy::Block)
+            .unwrap_err()
+            .to_string(),
+        "Execution error: Deposit cap can't be checked, the pool assets can't be priced: Execution error: No price for uatom"
+    );
+    ensure_with_policy(&valuation, PriceUnavailablePolicy::Allow).unwrap();
+
+    // Without a price source the pool ratio is always known, so the policy doesn't let a misconfigured cap through
+    let valuation = Valuation {
+        price_oracle: None,
+        ..valuation
+    };
+    assert!(matches!(
+        ensure_with_policy(&valuation, PriceUnavailablePolicy::Allow).unwrap_err(),
+        LibraryError::ConfigurationError(_)
+    ));
+}
+
+#[test]
+fn provide_liquidity_under_deposit_cap() {
+    let setup = LPerTestSuite::default();
+    let wasm = Wasm::new(&setup.inner.app);
+    let bank = Bank::new(&setup.inner.app);
+
+    // The whole deposit is worth 1_000_000 asset1 + 2_000_000 asset2 at a 1:2 pool ratio
+    update_deposit_cap(&setup, Some(Uint128::new(2_000_000)));
+    assert_eq!(
+        query_deposit_cap(&setup),
+        DepositCapResponse {
+            deposit_cap: Some(DepositCap::new(
+                Uint128::new(2_000_000),
+                &setup.inner.pool_asset1
+            )),
+            tvl: Some(Uint128::zero()),
+        }
+    );
+
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::ProcessFunction(FunctionMsgs::ProvideDoubleSidedLiquidity {
+            expected_pool_ratio_range: None,
+        }),
+        &[],
+        setup.inner.processor_acc(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_balance_amount(
+            &bank,
+            setup.input_acc.clone(),
+            setup.inner.pool_asset1.clone()
+        ),
+        0
+    );
+
+    // The position is now worth the deposit, minus the rounding of the LP shares
+    let deposit_cap = query_deposit_cap(&setup);
+    let tvl = deposit_cap.tvl.unwrap();
+    assert!(tvl <= Uint128::new(2_000_000));
+    assert!(tvl >= Uint128::new(1_999_990));
+}
+
+#[test]
+fn provide_liquidity_above_deposit_cap_is_rejected() {
+    let setup = LPerTestSuite::default();
+    let wasm = Wasm::new(&setup.inner.app);
+    let bank = Bank::new(&setup.inner.app);
+
+    update_deposit_cap(&setup, Some(Uint128::new(1_999_999)));
+
+    let error = wasm
+        .execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+            &setup.lper_addr,
+            &ExecuteMsg::ProcessFunction(FunctionMsgs::ProvideDoubleSidedLiquidity {
+                expected_pool_ratio_range: None,
+            }),
+            &[],
+            setup.inner.processor_acc(),
+        )
+        .unwrap_err();
+
+    assert!(error.to_string().contains(&format!(
+        "Deposit cap exceeded: the position would be worth 2000000{denom} but the max TVL is 1999999{denom}",
+        denom = setup.inner.pool_asset1
+    )));
+
+    // The input account keeps its funds
+    assert_eq!(
+        query_balance_amount(
+            &bank,
+            setup.input_acc.clone(),
+            setup.inner.pool_asset1.clone()
+        ),
+        1_000_000
+    );
+
+    // Removing the cap allows the provision again
+    update_deposit_cap(&setup, None);
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::ProcessFunction(FunctionMsgs::ProvideDoubleSidedLiquidity {
+            expected_pool_ratio_range: None,
+        }),
+        &[],
+        setup.inner.processor_acc(),
+    )
+    .unwrap();
+}
+
+// Enter position tests
+
+// Creates a deposit account approving the library, holding the given pool assets, and configures it on the library
+fn setup_deposit_account(
+    setup: &LPerTestSuite,
+    amount_asset1: u128,
+    amount_asset2: u128,
+) -> String {
+    let deposit_acc = instantiate_base_account(setup.account_code_id, &setup.inner);
+    approve_library(&setup.inner, deposit_acc.clone(), setup.lper_addr.clone());
+
+    let bank = Bank::new(&setup.inner.app);
+    bank.send(
+        MsgSend {
+            from_address: setup.inner.owner_acc().address(),
+            to_address: deposit_acc.clone(),
+            amount: vec![
+                BankCoin {
+                    denom: setup.inner.pool_asset2.clone(),
+                    amount: amount_asset2.to_string(),
+                },
+                BankCoin {
+                    denom: setup.inner.pool_asset1.clone(),
+                    amount: amount_asset1.to_string(),
+                },
+            ],
+        },
+        setup.inner.owner_acc(),
+    )
+    .unwrap();
+
+    let wasm = Wasm::new(&setup.inner.app);
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::UpdateConfig {
+            new_config: LibraryConfigUpdate {
+                input_addr: None,
+                output_addr: None,
+                pool_addr: None,
+                lp_config: None,
+                refund_account: OptionUpdate::None,
+                deposit_cap: OptionUpdate::None,
+                pool_allocations: OptionUpdate::None,
+                price_source: OptionUpdate::None,
+                deposit_addr: OptionUpdate::Set(Some(deposit_acc.as_str().into())),
+            },
+        },
+        &[],
+        setup.inner.owner_acc(),
+    )
+    .unwrap();
+
+    deposit_acc
+}
+
+#[test]
+fn enter_position_provides_deposit_atomically() {
+    let setup = LPerTestSuite::default();
+    let wasm = Wasm::new(&setup.inner.app);
+    let bank = Bank::new(&setup.inner.app);
+    let deposit_acc = setup_deposit_account(&setup, 500_000, 1_000_000);
+
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::ProcessFunction(FunctionMsgs::EnterPosition {
+            min_deposit: Some(cosmwasm_std::coin(
+                1_000_000,
+                setup.inner.pool_asset2.clone(),
+            )),
+            expected_pool_ratio_range: None,
+        }),
+        &[],
+        setup.inner.processor_acc(),
+    )
+    .unwrap();
+
+    // Both the deposit and the input account balances were provided in the same message
+    for account in [&deposit_acc, &setup.input_acc] {
+        let balances = bank
+            .query_all_balances(&QueryAllBalancesRequest {
+                address: account.clone(),
+                pagination: None,
+                resolve_denom: false,
+            })
+            .unwrap();
+        assert!(balances.balances.is_empty());
+    }
+
+    // The output account holds the LP tokens of the whole position
+    assert!(
+        query_balance_amount(
+            &bank,
+            setup.output_acc.clone(),
+            setup.inner.pool_native_liquidity_token.clone()
+        ) > 0
+    );
+}
+
+#[test]
+fn enter_position_reverts_deposit_when_provision_fails() {
+    let setup = LPerTestSuite::default();
+    let wasm = Wasm::new(&setup.inner.app);
+    let bank = Bank::new(&setup.inner.app);
+    let deposit_acc = setup_deposit_account(&setup, 500_000, 1_000_000);
+
+    // Require far more LP tokens than the position can mint
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::UpdateConfig {
+            new_config: LibraryConfigUpdate {
+                input_addr: None,
+                output_addr: None,
+                pool_addr: None,
+                lp_config: Some(LiquidityProviderConfig {
+                    pool_type: PoolType::NativeLpToken(
+                        valence_astroport_utils::astroport_native_lp_token::PairType::Xyk {},
+                    ),
+                    asset_data: AssetData {
+                        asset1: setup.inner.pool_asset1.clone(),
+                        asset2: setup.inner.pool_asset2.clone(),
+                        additional_assets: vec![],
+                    },
+                    max_spread: None,
+                    slippage_tolerance: Some(Decimal::percent(1)),
+                    min_lp_tokens_out: Some(Uint128::new(u128::MAX)),
+                    provision_mode: ProvisionMode::Balanced,
+                }),
+                refund_account: OptionUpdate::None,
+                deposit_cap: OptionUpdate::None,
+                pool_allocations: OptionUpdate::None,
+                price_source: OptionUpdate::None,
+                deposit_addr: OptionUpdate::None,
+            },
+        },
+        &[],
+        setup.inner.owner_acc(),
+    )
+    .unwrap();
+
+    wasm.execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+        &setup.lper_addr,
+        &ExecuteMsg::ProcessFunction(FunctionMsgs::EnterPosition {
+            min_deposit: None,
+            expected_pool_ratio_range: None,
+        }),
+        &[],
+        setup.inner.processor_acc(),
+    )
+    .unwrap_err();
+
+    // The deposit was rolled back along with the provision
+    for (account, amount_asset1, amount_asset2) in [
+        (&deposit_acc, 500_000, 1_000_000),
+        (&setup.input_acc, 1_000_000, 2_000_000),
+    ] {
+        assert_eq!(
+            query_balance_amount(&bank, account.clone(), setup.inner.pool_asset1.clone()),
+            amount_asset1
+        );
+        assert_eq!(
+            query_balance_amount(&bank, account.clone(), setup.inner.pool_asset2.clone()),
+            amount_asset2
+        );
+    }
+}
+
+#[test]
+fn enter_position_moves_deposit_before_provision() {
+    let mut deps = mock_lp_shares(500);
+    let mut cfg = native_xyk_config(None, Some(Uint128::new(1_000)));
+    let deposit_addr = MockApi::default().addr_make("deposit_account");
+    cfg.deposit_addr = Some(deposit_addr.clone());
+    deps.querier.bank.update_balance(
+        cfg.input_addr.clone(),
+        vec![cosmwasm_std::coin(1_000, "untrn")],
+    );
+    deps.querier.bank.update_balance(
+        deposit_addr.clone(),
+        vec![cosmwasm_std::coin(1_000, "uusdc")],
+    );
+
+    let response = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&cfg.input_addr, &[]),
+        FunctionMsgs::EnterPosition {
+            min_deposit: Some(cosmwasm_std::coin(1_000, "uusdc")),
+            expected_pool_ratio_range: None,
+        },
+        cfg.clone(),
+    )
+    .unwrap();
+
+    // The deposit account sends its balance to the input account first
+    assert_eq!(response.messages.len(), 2);
+    match &response.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => {
+            assert_eq!(contract_addr, deposit_addr.as_str())
+        }
+        _ => panic!("Expected the deposit to be executed on behalf of the deposit account"),
+    }
+    assert_eq!(response.messages[0].reply_on, ReplyOn::Never);
+
+    // The provision of the input account includes the deposit and is still checked in the reply
+    match &response.messages[1].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => {
+            assert_eq!(contract_addr, cfg.input_addr.as_str())
+        }
+        _ => panic!("Expected the provision to be executed on behalf of the input account"),
+    }
+    assert_eq!(response.messages[1].id, PROVISION_REPLY_ID);
+    assert!(response
+        .attributes
+        .iter()
+        .any(|a| a.key == "asset2_amount" && a.value == "1000"));
+    assert!(response
+        .attributes
+        .iter()
+        .any(|a| a.key == "deposit" && a.value == "1000uusdc"));
+}
+
+#[test]
+fn enter_position_validates_the_deposit() {
+    let mut deps = mock_lp_shares(0);
+    let mut cfg = native_xyk_config(None, None);
+    let enter_position = |min_deposit| FunctionMsgs::EnterPosition {
+        min_deposit,
+        expected_pool_ratio_range: None,
+    };
+
+    // A deposit account must be configured
+    let err = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&cfg.input_addr, &[]),
+        enter_position(None),
+        cfg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: No deposit account configured to enter the position from"
+    );
+
+    let deposit_addr = MockApi::default().addr_make("deposit_account");
+    cfg.deposit_addr = Some(deposit_addr.clone());
+    let err = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&cfg.input_addr, &[]),
+        enter_position(None),
+        cfg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Deposit account holds none of the pool assets"
+    );
+
+    deps.querier
+        .bank
+        .update_balance(deposit_addr, vec![cosmwasm_std::coin(999, "uusdc")]);
+    let err = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&cfg.input_addr, &[]),
+        enter_position(Some(cosmwasm_std::coin(1_000, "uusdc"))),
+        cfg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Deposit account holds 999uusdc, below the minimum deposit of 1000uusdc"
+    );
+}
+
+#[test]
+fn deposit_account_must_be_distinct_from_input_and_output() {
+    let api = MockApi::default();
+    let cfg = LibraryConfig::new(
+        api.addr_make("input_account").as_str(),
+        api.addr_make("output_account").as_str(),
+        api.addr_make("pool").to_string(),
+        native_xyk_config(None, None).lp_config,
+    );
+
+    cfg.clone()
+        .with_deposit_addr(api.addr_make("deposit_account").as_str())
+        .pre_validate(&api)
+        .unwrap();
+    for account in ["input_account", "output_account"] {
+        let err = cfg
+            .clone()
+            .with_deposit_addr(api.addr_make(account).as_str())
+            .pre_validate(&api)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: Deposit account must be distinct from the input and output accounts"
+        );
+    }
+}
+
+// Simulated amount tests
+
+// Mocks the untrn/uusdc pool of the saved config, whose swaps return the offered amount
+fn mock_pool(
+    cfg: &Config,
+    input_balances: Vec<cosmwasm_std::Coin>,
+    pool_balances: [u128; 2],
+) -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+    use valence_astroport_utils::astroport_native_lp_token::{
+        Asset, AssetInfo, PairInfo, PairType, PoolQueryMsg, PoolResponse, SimulationResponse,
+    };
+
+    let mut deps = mock_dependencies();
+    valence_library_base::save_config(&mut deps.storage, cfg).unwrap();
+    deps.querier
+        .bank
+        .update_balance(cfg.input_addr.clone(), input_balances);
+
+    let pool = cfg.pool_addr.clone();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { msg, .. } => {
+            let response = match from_json(msg).unwrap() {
+                PoolQueryMsg::Pool {} => to_json_binary(&PoolResponse {
+                    assets: ["untrn", "uusdc"]
+                        .into_iter()
+                        .zip(pool_balances)
+                        .map(|(denom, amount)| Asset {
+                            info: AssetInfo::NativeToken {
+                                denom: denom.to_string(),
+                            },
+                            amount: Uint128::new(amount),
+                        })
+                        .collect(),
+                    total_share: Uint128::new(1_000_000),
+                }),
+                PoolQueryMsg::Pair {} => to_json_binary(&PairInfo {
+                    asset_infos: vec![],
+                    contract_addr: pool.clone(),
+                    liquidity_token: "factory/pool/astroport/share".to_string(),
+                    pair_type: PairType::Xyk {},
+                }),
+                PoolQueryMsg::Simulation { offer_asset, .. } => {
+                    to_json_binary(&SimulationResponse {
+                        return_amount: offer_asset.amount,
+                        spread_amount: Uint128::zero(),
+                        commission_amount: Uint128::zero(),
+                    })
+                }
+                _ => panic!("Unexpected query"),
+            };
+            SystemResult::Ok(ContractResult::Ok(response.unwrap()))
+        }
+        _ => panic!("Unexpected query"),
+    });
+
+    deps
+}
+
+fn simulate_amounts(
+    deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+) -> Result<Vec<SimulatedAmount>, cosmwasm_std::StdError> {
+    query(deps.as_ref(), mock_env(), QueryMsg::SimulateAmount {}).map(|res| from_json(res).unwrap())
+}
+
+fn provide_following_mode(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    cfg: &Config,
+) -> Response {
+    process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&cfg.input_addr, &[]),
+        FunctionMsgs::ProvideLiquidity {
+            expected_pool_ratio_range: None,
+        },
+        cfg.clone(),
+    )
+    .unwrap()
+}
+
+fn attribute(response: &Response, key: &str) -> String {
+    response
+        .attributes
+        .iter()
+        .find(|a| a.key == key)
+        .map(|a| a.value.clone())
+        .unwrap_or_else(|| panic!("Missing attribute {key}"))
+}
+
+#[test]
+fn simulated_amounts_match_the_balanced_provision() {
+    let cfg = native_xyk_config(None, None);
+    // The pool holds 2 uusdc per untrn, so only 2_000 uusdc go along with the 1_000 untrn
+    let mut deps = mock_pool(
+        &cfg,
+        vec![
+            cosmwasm_std::coin(1_000, "untrn"),
+            cosmwasm_std::coin(5_000, "uusdc"),
+        ],
+        [1_000_000, 2_000_000],
+    );
+
+    let simulated = simulate_amounts(&deps).unwrap();
+    assert_eq!(
+        simulated,
+        vec![
+            SimulatedAmount::new("untrn", Uint128::new(1_000)),
+            SimulatedAmount::new("uusdc", Uint128::new(2_000)),
+        ]
+    );
+
+    let response = provide_following_mode(&mut deps, &cfg);
+    assert_eq!(
+        attribute(&response, "asset1_amount"),
+        simulated[0].amount.to_string()
+    );
+    assert_eq!(
+        attribute(&response, "asset2_amount"),
+        simulated[1].amount.to_string()
+    );
+}
+
+#[test]
+fn simulated_amounts_match_the_single_sided_provision() {
+    // Single sided provisions are only configurable for non Xyk pools
+    let mut cfg = native_xyk_config(None, None);
+    cfg.lp_config.pool_type = PoolType::NativeLpToken(
+        valence_astroport_utils::astroport_native_lp_token::PairType::Stable {},
+    );
+    cfg.lp_config.provision_mode = ProvisionMode::SingleSided {
+        asset: "untrn".to_string(),
+    };
+    // The whole untrn balance is provided, the uusdc balance is left untouched
+    let mut deps = mock_pool(
+        &cfg,
+        vec![
+            cosmwasm_std::coin(1_001, "untrn"),
+            cosmwasm_std::coin(300, "uusdc"),
+        ],
+        [1_000_000, 1_000_000],
+    );
+
+    let simulated = simulate_amounts(&deps).unwrap();
+    assert_eq!(
+        simulated,
+        vec![
+            SimulatedAmount::new("untrn", Uint128::new(1_001)),
+            SimulatedAmount::skipped("uusdc", "nothing_to_provide"),
+        ]
+    );
+
+    let response = provide_following_mode(&mut deps, &cfg);
+    assert_eq!(
+        attribute(&response, "asset_amount"),
+        simulated[0].amount.to_string()
+    );
+}
+
+#[test]
+fn simulated_amounts_match_the_auto_balanced_provision() {
+    let mut cfg = native_xyk_config(None, None);
+    cfg.lp_config.provision_mode = ProvisionMode::AutoBalance;
+    // The excess untrn is swapped, after which both balances are provided entirely
+    let mut deps = mock_pool(
+        &cfg,
+        vec![
+            cosmwasm_std::coin(3_000, "untrn"),
+            cosmwasm_std::coin(1_000, "uusdc"),
+        ],
+        [1_000_000, 1_000_000],
+    );
+
+    let simulated = simulate_amounts(&deps).unwrap();
+    assert_eq!(
+        simulated,
+        vec![
+            SimulatedAmount::new("untrn", Uint128::new(3_000)),
+            SimulatedAmount::new("uusdc", Uint128::new(1_000)),
+        ]
+    );
+
+    // The untrn swapped and the untrn provided add up to the simulated amount
+    let response = provide_following_mode(&mut deps, &cfg);
+    let msgs = match &response.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match from_json(msg).unwrap() {
+            valence_account_utils::msg::ExecuteMsg::ExecuteMsg { msgs } => msgs,
+            _ => panic!("Expected the provision to be executed on behalf of the input account"),
+        },
+        _ => panic!("Expected a wasm execute message"),
+    };
+    let untrn_sent: u128 = msgs
+        .iter()
+        .flat_map(|msg| match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }) => funds.clone(),
+            _ => vec![],
+        })
+        .filter(|coin| coin.denom == "untrn")
+        .map(|coin| coin.amount.u128())
+        .sum();
+    assert_eq!(untrn_sent, simulated[0].amount.u128());
+    assert_eq!(attribute(&response, "swap_amount"), "1000");
+}
@@ -0,0 +1,207 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use valence_library_utils::{
+    error::LibraryError,
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg},
+};
+
+use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
+
+// version info for migration info
+const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg<LibraryConfig>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::instantiate(deps, CONTRACT_NAME, CONTRACT_VERSION, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::execute(
+        deps,
+        env,
+        info,
+        msg,
+        functions::process_function,
+        execute::update_config,
+    )
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
+    )
+}
+
+mod functions {
+    use cosmwasm_std::{
+        coin, to_json_binary, CosmosMsg, DepsMut, Env, MessageInfo, Response, Uint128, WasmMsg,
+    };
+    use valence_astroport_utils::{
+        astroport_native_lp_token::{
+            Asset, AssetInfo, ExecuteMsg, PoolQueryMsg, SimulationResponse,
+        },
+        max_spread_or_default,
+    };
+    use valence_library_utils::{error::LibraryError, execute_on_behalf_of};
+
+    use crate::msg::{Config, FunctionMsgs};
+
+    pub fn process_function(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: FunctionMsgs,
+        cfg: Config,
+    ) -> Result<Response, LibraryError> {
+        match msg {
+            FunctionMsgs::Swap { min_amount_out } => swap(deps, &cfg, min_amount_out),
+        }
+    }
+
+    fn swap(
+        deps: DepsMut,
+        cfg: &Config,
+        min_amount_out: Uint128,
+    ) -> Result<Response, LibraryError> {
+        let swap_config = &cfg.swap_config;
+
+        if min_amount_out.is_zero() {
+            return Err(LibraryError::ExecutionError(
+                "Min amount out must be greater than zero".to_string(),
+            ));
+        }
+
+        let offer_amount = deps
+            .querier
+            .query_balance(&cfg.input_addr, &swap_config.offer_denom)?
+            .amount;
+        if offer_amount.is_zero() {
+            return Err(LibraryError::ExecutionError(format!(
+                "Nothing to swap: the input account holds no {}",
+                swap_config.offer_denom
+            )));
+        }
+
+        let offer_asset = Asset {
+            info: AssetInfo::NativeToken {
+                denom: swap_config.offer_denom.clone(),
+            },
+            amount: offer_amount,
+        };
+        let ask_asset_info = AssetInfo::NativeToken {
+            denom: swap_config.ask_denom.clone(),
+        };
+
+        // The quote is done in the same transaction as the swap, so it is the amount the pool returns
+        let simulation: SimulationResponse = deps.querier.query_wasm_smart(
+            &cfg.pool_addr,
+            &PoolQueryMsg::Simulation {
+                offer_asset: offer_asset.clone(),
+                ask_asset_info: Some(ask_asset_info.clone()),
+            },
+        )?;
+        if simulation.return_amount < min_amount_out {
+            return Err(LibraryError::ExecutionError(format!(
+                "Slippage exceeded: swapping {offer_amount}{} returns {}{} but at least {min_amount_out}{} is expected",
+                swap_config.offer_denom,
+                simulation.return_amount,
+                swap_config.ask_denom,
+                swap_config.ask_denom
+            )));
+        }
+
+        let swap_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: cfg.pool_addr.to_string(),
+            msg: to_json_binary(&ExecuteMsg::Swap {
+                offer_asset,
+                ask_asset_info: Some(ask_asset_info),
+                belief_price: None,
+                max_spread: Some(max_spread_or_default(swap_config.max_spread)),
+                to: Some(cfg.output_addr.to_string()),
+            })?,
+            funds: vec![coin(offer_amount.u128(), &swap_config.offer_denom)],
+        });
+        let input_account_msg = execute_on_behalf_of(vec![swap_msg], &cfg.input_addr)?;
+
+        Ok(Response::new()
+            .add_message(input_account_msg)
+            .add_attribute("method", "swap")
+            .add_attribute("offer_amount", offer_amount)
+            .add_attribute("expected_return_amount", simulation.return_amount))
+    }
+}
+
+mod execute {
+    use cosmwasm_std::{DepsMut, Env, MessageInfo};
+    use valence_library_utils::error::LibraryError;
+
+    use crate::msg::LibraryConfigUpdate;
+
+    pub fn update_config(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        new_config: LibraryConfigUpdate,
+    ) -> Result<(), LibraryError> {
+        new_config.update_config(deps)
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Ownership {} => {
+            to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
+        }
+        QueryMsg::GetProcessor {} => {
+            to_json_binary(&valence_library_base::get_processor(deps.storage)?)
+        }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        QueryMsg::IsApproved {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&valence_library_utils::is_library_approved(
+                &deps.querier,
+                &config.input_addr,
+                &env.contract.address,
+            )?)
+        }
+        QueryMsg::GetLibraryConfig {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&config)
+        }
+        QueryMsg::GetRawLibraryConfig {} => {
+            let raw_config: LibraryConfig =
+                valence_library_utils::raw_config::query_raw_library_config(deps.storage)?;
+            to_json_binary(&raw_config)
+        }
+    }
+}
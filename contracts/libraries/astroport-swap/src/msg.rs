@@ -0,0 +1,170 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Api, Decimal, Deps, DepsMut, Uint128};
+use cw_ownable::cw_ownable_query;
+use valence_astroport_utils::query_pool_denoms;
+use valence_library_utils::{
+    error::LibraryError, msg::LibraryConfigValidation, LibraryAccountType,
+};
+use valence_macros::{valence_library_query, ValenceLibraryInterface};
+
+#[cw_serde]
+pub enum FunctionMsgs {
+    // Swap the whole balance of the offer denom held by the input account, sending the ask denom received to the output account.
+    // Reverts if the pool returns less than `min_amount_out` of the ask denom
+    Swap { min_amount_out: Uint128 },
+}
+
+#[valence_library_query]
+#[cw_ownable_query]
+#[cw_serde]
+#[derive(QueryResponses)]
+/// Enum representing the different query messages that can be sent.
+pub enum QueryMsg {}
+
+#[cw_serde]
+#[derive(ValenceLibraryInterface)]
+pub struct LibraryConfig {
+    // Account holding the offer denom
+    pub input_addr: LibraryAccountType,
+    // Account receiving the ask denom
+    pub output_addr: LibraryAccountType,
+    // Astroport pool in which the swap is done
+    pub pool_addr: String,
+    pub swap_config: SwapConfig,
+}
+
+#[cw_serde]
+pub struct SwapConfig {
+    // Denom swapped
+    pub offer_denom: String,
+    // Denom received
+    pub ask_denom: String,
+    // Max spread passed to the pool, which rejects the swap if the price moves more than that.
+    // If not set, a default max spread of 0.5% is passed
+    pub max_spread: Option<Decimal>,
+}
+
+impl LibraryConfig {
+    pub fn new(
+        input_addr: impl Into<LibraryAccountType>,
+        output_addr: impl Into<LibraryAccountType>,
+        pool_addr: String,
+        swap_config: SwapConfig,
+    ) -> Self {
+        LibraryConfig {
+            input_addr: input_addr.into(),
+            output_addr: output_addr.into(),
+            pool_addr,
+            swap_config,
+        }
+    }
+
+    fn do_validate(&self, api: &dyn Api) -> Result<(Addr, Addr, Addr), LibraryError> {
+        let input_addr = self.input_addr.to_addr(api)?;
+        let output_addr = self.output_addr.to_addr(api)?;
+        let pool_addr = api.addr_validate(&self.pool_addr)?;
+        self.swap_config.validate()?;
+
+        Ok((input_addr, output_addr, pool_addr))
+    }
+}
+
+impl SwapConfig {
+    fn validate(&self) -> Result<(), LibraryError> {
+        if self.offer_denom.is_empty() || self.ask_denom.is_empty() {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid swap config: offer and ask denoms cannot be empty.".to_string(),
+            ));
+        }
+
+        if self.offer_denom == self.ask_denom {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid swap config: offer and ask denoms must be different.".to_string(),
+            ));
+        }
+
+        if let Some(max_spread) = self.max_spread {
+            if max_spread.is_zero() || max_spread >= Decimal::one() {
+                return Err(LibraryError::ConfigurationError(format!(
+                    "Invalid swap config: max spread {max_spread} must be between 0 and 1 (exclusive)."
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that both denoms are assets of the pool, so that it can route the swap
+    fn validate_against_pool(&self, deps: Deps, pool_addr: &Addr) -> Result<(), LibraryError> {
+        let pool_denoms = query_pool_denoms(deps, pool_addr.as_str())?;
+
+        for denom in [&self.offer_denom, &self.ask_denom] {
+            if !pool_denoms.contains(denom) {
+                return Err(LibraryError::ConfigurationError(format!(
+                    "Invalid swap config: denom {denom} is not an asset of pool {pool_addr}."
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl LibraryConfigValidation<Config> for LibraryConfig {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pre_validate(&self, api: &dyn Api) -> Result<(), LibraryError> {
+        self.do_validate(api)?;
+        Ok(())
+    }
+
+    fn validate(&self, deps: Deps) -> Result<Config, LibraryError> {
+        let (input_addr, output_addr, pool_addr) = self.do_validate(deps.api)?;
+        self.swap_config.validate_against_pool(deps, &pool_addr)?;
+
+        Ok(Config {
+            input_addr,
+            output_addr,
+            pool_addr,
+            swap_config: self.swap_config.clone(),
+        })
+    }
+}
+
+impl LibraryConfigUpdate {
+    pub fn update_config(self, deps: DepsMut) -> Result<(), LibraryError> {
+        let mut config: Config = valence_library_base::load_config(deps.storage)?;
+
+        if let Some(input_addr) = self.input_addr {
+            config.input_addr = input_addr.to_addr(deps.api)?;
+        }
+
+        if let Some(output_addr) = self.output_addr {
+            config.output_addr = output_addr.to_addr(deps.api)?;
+        }
+
+        if let Some(pool_addr) = self.pool_addr {
+            config.pool_addr = deps.api.addr_validate(&pool_addr)?;
+        }
+
+        if let Some(swap_config) = self.swap_config {
+            swap_config.validate()?;
+            config.swap_config = swap_config;
+        }
+
+        // The denoms are checked against the pool once both are (possibly) updated
+        config
+            .swap_config
+            .validate_against_pool(deps.as_ref(), &config.pool_addr)?;
+
+        valence_library_base::save_config(deps.storage, &config)?;
+        Ok(())
+    }
+}
+
+#[cw_serde]
+pub struct Config {
+    pub input_addr: Addr,
+    pub output_addr: Addr,
+    pub pool_addr: Addr,
+    pub swap_config: SwapConfig,
+}
@@ -0,0 +1,425 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    coin, from_json, to_json_binary, to_json_vec, Addr, BankMsg, Binary, Coin, Decimal, Deps,
+    DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult, Uint128,
+};
+use cw_multi_test::{error::AnyResult, App, AppResponse, ContractWrapper, Executor};
+use cw_ownable::Ownership;
+use valence_astroport_utils::{
+    astroport_native_lp_token::{
+        AssetInfo, ExecuteMsg as PoolExecuteMsg, PairInfo, PairType, PoolQueryMsg,
+        SimulationResponse,
+    },
+    DEFAULT_MAX_SPREAD,
+};
+use valence_library_utils::{
+    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation},
+    testing::{LibraryTestSuite, LibraryTestSuiteBase},
+};
+
+use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg, SwapConfig};
+
+const NTRN: &str = "untrn";
+const USDC: &str = "uusdc";
+const ATOM: &str = "uatom";
+const QUOTE_KEY: &[u8] = b"quote";
+
+// The mocked pool quotes (and swaps at) a fixed price of USDC per NTRN
+#[cw_serde]
+struct MockPoolInstantiateMsg {
+    price: Decimal,
+}
+
+fn mock_pool_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: MockPoolInstantiateMsg,
+) -> StdResult<Response> {
+    deps.storage.set(QUOTE_KEY, &to_json_vec(&msg.price)?);
+    Ok(Response::default())
+}
+
+fn mock_return_amount(deps: Deps, offer_amount: Uint128) -> StdResult<Uint128> {
+    let price: Decimal = from_json(deps.storage.get(QUOTE_KEY).unwrap())?;
+    Ok(offer_amount.mul_floor(price))
+}
+
+fn mock_pool_execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: PoolExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        PoolExecuteMsg::Swap {
+            offer_asset,
+            max_spread,
+            to,
+            ..
+        } => {
+            if info.funds != vec![offer_asset.as_coin()?] {
+                return Err(StdError::generic_err("Offer asset not sent"));
+            }
+            let return_amount = mock_return_amount(deps.as_ref(), offer_asset.amount)?;
+
+            Ok(Response::new()
+                .add_message(BankMsg::Send {
+                    to_address: to.unwrap_or(info.sender.to_string()),
+                    amount: vec![coin(return_amount.u128(), USDC)],
+                })
+                .add_attribute(
+                    "max_spread",
+                    max_spread
+                        .map(|spread| spread.to_string())
+                        .unwrap_or_default(),
+                ))
+        }
+        _ => unimplemented!(),
+    }
+}
+
+fn mock_pool_query(deps: Deps, env: Env, msg: PoolQueryMsg) -> StdResult<Binary> {
+    match msg {
+        PoolQueryMsg::Pair {} => to_json_binary(&PairInfo {
+            asset_infos: vec![
+                AssetInfo::NativeToken {
+                    denom: NTRN.to_string(),
+                },
+                AssetInfo::NativeToken {
+                    denom: USDC.to_string(),
+                },
+            ],
+            contract_addr: env.contract.address,
+            liquidity_token: "factory/pool/lp".to_string(),
+            pair_type: PairType::Xyk {},
+        }),
+        PoolQueryMsg::Simulation { offer_asset, .. } => to_json_binary(&SimulationResponse {
+            return_amount: mock_return_amount(deps, offer_asset.amount)?,
+            spread_amount: Uint128::zero(),
+            commission_amount: Uint128::zero(),
+        }),
+        _ => unimplemented!(),
+    }
+}
+
+struct AstroportSwapTestSuite {
+    inner: LibraryTestSuiteBase,
+    swap_code_id: u64,
+    input_addr: Addr,
+    output_addr: Addr,
+    pool_addr: Addr,
+}
+
+impl Default for AstroportSwapTestSuite {
+    fn default() -> Self {
+        Self::new(Decimal::from_ratio(3u128, 2u128))
+    }
+}
+
+impl AstroportSwapTestSuite {
+    pub fn new(price: Decimal) -> Self {
+        let mut inner = LibraryTestSuiteBase::new();
+
+        let input_addr = inner.get_contract_addr(inner.account_code_id(), "input_account");
+        let output_addr = inner.api().addr_make("output_account");
+
+        let swap_code = ContractWrapper::new(
+            crate::contract::execute,
+            crate::contract::instantiate,
+            crate::contract::query,
+        );
+        let swap_code_id = inner.app_mut().store_code(Box::new(swap_code));
+
+        let mock_pool_code =
+            ContractWrapper::new(mock_pool_execute, mock_pool_instantiate, mock_pool_query);
+        let mock_pool_code_id = inner.app_mut().store_code(Box::new(mock_pool_code));
+        let pool_addr = inner.contract_init(
+            mock_pool_code_id,
+            "mock_pool",
+            &MockPoolInstantiateMsg { price },
+            &[],
+        );
+        inner.init_balance(&pool_addr, vec![coin(1_000_000_000, USDC)]);
+
+        Self {
+            inner,
+            swap_code_id,
+            input_addr,
+            output_addr,
+            pool_addr,
+        }
+    }
+
+    // Instantiates the library, along with the input account holding `balances`
+    pub fn swap_init(&mut self, cfg: &LibraryConfig, balances: Vec<Coin>) -> Addr {
+        let init_msg = InstantiateMsg {
+            owner: self.owner().to_string(),
+            processor: self.processor().to_string(),
+            config: cfg.clone(),
+        };
+        let addr = self.contract_init(self.swap_code_id, "astroport_swap", &init_msg, &[]);
+
+        let input_addr = self.account_init("input_account", vec![addr.to_string()]);
+        assert_eq!(input_addr, self.input_addr);
+
+        if !balances.is_empty() {
+            self.init_balance(&input_addr, balances);
+        }
+
+        addr
+    }
+
+    fn swap_config(&self, max_spread: Option<Decimal>) -> LibraryConfig {
+        LibraryConfig::new(
+            &self.input_addr,
+            &self.output_addr,
+            self.pool_addr.to_string(),
+            SwapConfig {
+                offer_denom: NTRN.to_string(),
+                ask_denom: USDC.to_string(),
+                max_spread,
+            },
+        )
+    }
+
+    fn execute_swap(&mut self, addr: Addr, min_amount_out: u128) -> AnyResult<AppResponse> {
+        self.contract_execute(
+            addr,
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::ProcessFunction(FunctionMsgs::Swap {
+                min_amount_out: Uint128::new(min_amount_out),
+            }),
+        )
+    }
+
+    fn update_config(
+        &mut self,
+        addr: Addr,
+        new_config: LibraryConfigUpdate,
+    ) -> AnyResult<AppResponse> {
+        let owner = self.owner().clone();
+        self.app_mut().execute_contract(
+            owner,
+            addr,
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::UpdateConfig { new_config },
+            &[],
+        )
+    }
+}
+
+impl LibraryTestSuite<Empty, Empty> for AstroportSwapTestSuite {
+    fn app(&self) -> &App {
+        self.inner.app()
+    }
+
+    fn app_mut(&mut self) -> &mut App {
+        self.inner.app_mut()
+    }
+
+    fn owner(&self) -> &Addr {
+        self.inner.owner()
+    }
+
+    fn processor(&self) -> &Addr {
+        self.inner.processor()
+    }
+
+    fn account_code_id(&self) -> u64 {
+        self.inner.account_code_id()
+    }
+
+    fn cw20_code_id(&self) -> u64 {
+        self.inner.cw20_code_id()
+    }
+}
+
+#[test]
+fn instantiate_with_valid_config() {
+    let mut suite = AstroportSwapTestSuite::default();
+    let cfg = suite.swap_config(Some(Decimal::percent(1)));
+    let lib = suite.swap_init(&cfg, vec![]);
+
+    let owner_res: Ownership<Addr> = suite.query_wasm(&lib, &QueryMsg::Ownership {});
+    assert_eq!(owner_res.owner, Some(suite.owner().clone()));
+
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        lib_cfg,
+        Config {
+            input_addr: suite.input_addr.clone(),
+            output_addr: suite.output_addr.clone(),
+            pool_addr: suite.pool_addr.clone(),
+            swap_config: cfg.swap_config,
+        }
+    );
+}
+
+#[test]
+fn swap_sends_ask_denom_to_output_with_max_spread() {
+    let mut suite = AstroportSwapTestSuite::default();
+    let cfg = suite.swap_config(Some(Decimal::percent(1)));
+    let lib = suite.swap_init(&cfg, vec![coin(10_000, NTRN), coin(50, ATOM)]);
+
+    // The pool quotes 15_000 USDC for the 10_000 NTRN, which is exactly the min amount out
+    let res = suite.execute_swap(lib, 15_000).unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "expected_return_amount" && attr.value == "15000")));
+    // The max spread is passed to the pool
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "max_spread" && attr.value == "0.01")));
+
+    // The whole offer balance is swapped, other denoms stay on the input account
+    let input_addr = suite.input_addr.clone();
+    let output_addr = suite.output_addr.clone();
+    suite.assert_balance(&input_addr, 0, NTRN);
+    suite.assert_balance(&input_addr, 50, ATOM);
+    suite.assert_balance(&output_addr, 15_000, USDC);
+}
+
+#[test]
+fn swap_passes_default_max_spread_when_not_configured() {
+    let mut suite = AstroportSwapTestSuite::default();
+    let cfg = suite.swap_config(None);
+    let lib = suite.swap_init(&cfg, vec![coin(10_000, NTRN)]);
+
+    let res = suite.execute_swap(lib, 15_000).unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "max_spread" && attr.value == DEFAULT_MAX_SPREAD.to_string())));
+}
+
+#[test]
+fn swap_reverts_when_min_amount_out_cannot_be_met() {
+    let mut suite = AstroportSwapTestSuite::default();
+    let cfg = suite.swap_config(None);
+    let lib = suite.swap_init(&cfg, vec![coin(10_000, NTRN)]);
+
+    let err = suite.execute_swap(lib.clone(), 15_001).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Execution error: Slippage exceeded: swapping 10000untrn returns 15000uusdc but at least 15001uusdc is expected"
+    );
+
+    // A min amount out of zero would not protect the swap
+    let err = suite.execute_swap(lib, 0).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Execution error: Min amount out must be greater than zero"
+    );
+
+    // Nothing was swapped
+    let input_addr = suite.input_addr.clone();
+    let output_addr = suite.output_addr.clone();
+    suite.assert_balance(&input_addr, 10_000, NTRN);
+    suite.assert_balance(&output_addr, 0, USDC);
+}
+
+#[test]
+fn swap_fails_without_offer_balance() {
+    let mut suite = AstroportSwapTestSuite::default();
+    let cfg = suite.swap_config(None);
+    let lib = suite.swap_init(&cfg, vec![coin(50, ATOM)]);
+
+    let err = suite.execute_swap(lib, 1).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Execution error: Nothing to swap: the input account holds no untrn"
+    );
+}
+
+#[test]
+fn pre_validate_rejects_invalid_swap_config() {
+    let suite = AstroportSwapTestSuite::default();
+
+    for (offer_denom, ask_denom, max_spread, error) in [
+        (
+            NTRN,
+            "",
+            None,
+            "Invalid swap config: offer and ask denoms cannot be empty.",
+        ),
+        (
+            NTRN,
+            NTRN,
+            None,
+            "Invalid swap config: offer and ask denoms must be different.",
+        ),
+        (
+            NTRN,
+            USDC,
+            Some(Decimal::zero()),
+            "Invalid swap config: max spread 0 must be between 0 and 1 (exclusive).",
+        ),
+        (
+            NTRN,
+            USDC,
+            Some(Decimal::one()),
+            "Invalid swap config: max spread 1 must be between 0 and 1 (exclusive).",
+        ),
+    ] {
+        let mut cfg = suite.swap_config(max_spread);
+        cfg.swap_config.offer_denom = offer_denom.to_string();
+        cfg.swap_config.ask_denom = ask_denom.to_string();
+
+        let err = cfg.pre_validate(suite.api()).unwrap_err();
+        assert_eq!(err.to_string(), format!("Configuration error: {error}"));
+    }
+}
+
+#[test]
+#[should_panic(expected = "Invalid swap config: denom uatom is not an asset of pool")]
+fn instantiate_fails_when_pool_cannot_route_the_swap() {
+    let mut suite = AstroportSwapTestSuite::default();
+    let mut cfg = suite.swap_config(None);
+    cfg.swap_config.ask_denom = ATOM.to_string();
+
+    suite.swap_init(&cfg, vec![]);
+}
+
+#[test]
+fn update_config_validates_denoms_against_pool() {
+    let mut suite = AstroportSwapTestSuite::default();
+    let cfg = suite.swap_config(None);
+    let lib = suite.swap_init(&cfg, vec![]);
+
+    let err = suite
+        .update_config(
+            lib.clone(),
+            LibraryConfigUpdate {
+                swap_config: Some(SwapConfig {
+                    offer_denom: ATOM.to_string(),
+                    ask_denom: USDC.to_string(),
+                    max_spread: None,
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("Invalid swap config: denom uatom is not an asset of pool"));
+
+    // Swapping in the other direction is fine
+    let reversed = SwapConfig {
+        offer_denom: USDC.to_string(),
+        ask_denom: NTRN.to_string(),
+        max_spread: Some(Decimal::percent(5)),
+    };
+    suite
+        .update_config(
+            lib.clone(),
+            LibraryConfigUpdate {
+                swap_config: Some(reversed.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(lib_cfg.swap_config, reversed);
+}
@@ -1,11 +1,11 @@
-use cosmwasm_std::{to_json_binary, Addr, Coin, CosmosMsg, DepsMut};
+use cosmwasm_std::{to_json_binary, Addr, Coin, CosmosMsg, Deps, Uint128};
 use cw20::{BalanceResponse, Cw20ExecuteMsg};
-use valence_astroport_utils::astroport_cw20_lp_token::{Asset, PairInfo};
+use valence_astroport_utils::astroport_cw20_lp_token::{Asset, PairInfo, PoolResponse};
 use valence_library_utils::error::LibraryError;
 
 use crate::msg::Config;
 
-pub fn query_liquidity_token(deps: &DepsMut, cfg: &Config) -> Result<Addr, LibraryError> {
+pub fn query_liquidity_token(deps: Deps, cfg: &Config) -> Result<Addr, LibraryError> {
     let pair_info: PairInfo = deps.querier.query_wasm_smart(
         cfg.pool_addr.clone(),
         &valence_astroport_utils::astroport_cw20_lp_token::PoolQueryMsg::Pair {},
@@ -14,11 +14,9 @@ pub fn query_liquidity_token(deps: &DepsMut, cfg: &Config) -> Result<Addr, Libra
     Ok(pair_info.liquidity_token)
 }
 
-pub fn create_withdraw_liquidity_msgs(
-    deps: &DepsMut,
-    cfg: &Config,
-) -> Result<Vec<CosmosMsg>, LibraryError> {
-    // Get the token factory token that represents the liquidity token
+/// Returns the LP token contract and the LP token balance of the input account
+pub fn query_lp_balance(deps: Deps, cfg: &Config) -> Result<(Addr, Uint128), LibraryError> {
+    // Get the cw20 token that represents the liquidity token
     let token_addr = query_liquidity_token(deps, cfg)?;
 
     // Query the balance of the account that is going to withdraw
@@ -28,18 +26,47 @@ pub fn create_withdraw_liquidity_msgs(
             address: cfg.input_addr.to_string(),
         },
     )?;
-    if balance_response.balance.is_zero() {
-        return Err(LibraryError::ExecutionError(
-            "Nothing to withdraw".to_string(),
-        ));
-    }
+
+    Ok((token_addr, balance_response.balance))
+}
+
+/// Returns the reserves of each of the pool assets
+pub fn query_pool_reserves(deps: Deps, cfg: &Config) -> Result<Vec<Coin>, LibraryError> {
+    let pool: PoolResponse = deps.querier.query_wasm_smart(
+        cfg.pool_addr.clone(),
+        &valence_astroport_utils::astroport_cw20_lp_token::PoolQueryMsg::Pool {},
+    )?;
+
+    Ok(pool
+        .assets
+        .into_iter()
+        .map(|asset| asset.to_coin())
+        .collect::<Result<Vec<Coin>, _>>()?)
+}
+
+/// Returns the LP token contract and the amount of it that a withdrawal would redeem
+pub fn resolve_withdraw_amount(deps: Deps, cfg: &Config) -> Result<(Addr, Uint128), LibraryError> {
+    let (token_addr, balance) = query_lp_balance(deps, cfg)?;
+
+    // Get the portion of the balance that we are going to withdraw
+    let amount = cfg
+        .withdrawer_config
+        .withdraw_amount
+        .resolve(balance, cfg.withdrawer_config.rounding)?;
+
+    Ok((token_addr, amount))
+}
+
+pub fn create_withdraw_liquidity_msgs(
+    deps: Deps,
+    cfg: &Config,
+) -> Result<(Vec<CosmosMsg>, Vec<Coin>), LibraryError> {
+    let (token_addr, amount) = resolve_withdraw_amount(deps, cfg)?;
 
     // Calculate how much we are going to get when we withdraw
     let withdrawn_assets: Vec<Asset> = deps.querier.query_wasm_smart(
         cfg.pool_addr.clone(),
-        &valence_astroport_utils::astroport_cw20_lp_token::PoolQueryMsg::Share {
-            amount: balance_response.balance,
-        },
+        &valence_astroport_utils::astroport_cw20_lp_token::PoolQueryMsg::Share { amount },
     )?;
 
     // Create the withdraw and send messages
@@ -47,7 +74,7 @@ pub fn create_withdraw_liquidity_msgs(
         contract_addr: token_addr.to_string(),
         msg: to_json_binary(&Cw20ExecuteMsg::Send {
             contract: cfg.pool_addr.to_string(),
-            amount: balance_response.balance,
+            amount,
             msg: to_json_binary(
                 &valence_astroport_utils::astroport_cw20_lp_token::Cw20HookMsg::WithdrawLiquidity {
                     assets: vec![],
@@ -65,8 +92,8 @@ pub fn create_withdraw_liquidity_msgs(
 
     let send_msg = CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
         to_address: cfg.output_addr.to_string(),
-        amount: withdrawn_coins,
+        amount: withdrawn_coins.clone(),
     });
 
-    Ok(vec![withdraw_msg, send_msg])
+    Ok((vec![withdraw_msg, send_msg], withdrawn_coins))
 }
@@ -1,9 +1,9 @@
 use crate::msg::Config;
-use cosmwasm_std::{to_json_binary, Coin, CosmosMsg, DepsMut};
-use valence_astroport_utils::astroport_native_lp_token::{Asset, PairInfo};
+use cosmwasm_std::{to_json_binary, Coin, CosmosMsg, Deps, Uint128};
+use valence_astroport_utils::astroport_native_lp_token::{Asset, PairInfo, PoolResponse};
 use valence_library_utils::error::LibraryError;
 
-pub fn query_liquidity_token(deps: &DepsMut, cfg: &Config) -> Result<String, LibraryError> {
+pub fn query_liquidity_token(deps: Deps, cfg: &Config) -> Result<String, LibraryError> {
     let pair_info: PairInfo = deps.querier.query_wasm_smart(
         cfg.pool_addr.clone(),
         &valence_astroport_utils::astroport_native_lp_token::PoolQueryMsg::Pair {},
@@ -12,27 +12,57 @@ pub fn query_liquidity_token(deps: &DepsMut, cfg: &Config) -> Result<String, Lib
     Ok(pair_info.liquidity_token)
 }
 
-pub fn create_withdraw_liquidity_msgs(
-    deps: &DepsMut,
-    cfg: &Config,
-) -> Result<Vec<CosmosMsg>, LibraryError> {
+/// Returns the LP token denom and the LP token balance of the input account
+pub fn query_lp_balance(deps: Deps, cfg: &Config) -> Result<(String, Uint128), LibraryError> {
     // Get the token factory token that represents the liquidity token
     let token = query_liquidity_token(deps, cfg)?;
 
     // Query the balance of the account that is going to withdraw
     let balance = deps.querier.query_balance(&cfg.input_addr, token)?;
-    if balance.amount.is_zero() {
-        return Err(LibraryError::ExecutionError(
-            "Nothing to withdraw".to_string(),
-        ));
-    }
+
+    Ok((balance.denom, balance.amount))
+}
+
+/// Returns the reserves of each of the pool assets
+pub fn query_pool_reserves(deps: Deps, cfg: &Config) -> Result<Vec<Coin>, LibraryError> {
+    let pool: PoolResponse = deps.querier.query_wasm_smart(
+        cfg.pool_addr.clone(),
+        &valence_astroport_utils::astroport_native_lp_token::PoolQueryMsg::Pool {},
+    )?;
+
+    Ok(pool
+        .assets
+        .into_iter()
+        .map(|asset| asset.as_coin())
+        .collect::<Result<Vec<Coin>, _>>()?)
+}
+
+/// Returns the LP token denom and the amount of it that a withdrawal would redeem
+pub fn resolve_withdraw_amount(
+    deps: Deps,
+    cfg: &Config,
+) -> Result<(String, Uint128), LibraryError> {
+    let (lp_denom, balance) = query_lp_balance(deps, cfg)?;
+
+    // Get the portion of the balance that we are going to withdraw
+    let amount = cfg
+        .withdrawer_config
+        .withdraw_amount
+        .resolve(balance, cfg.withdrawer_config.rounding)?;
+
+    Ok((lp_denom, amount))
+}
+
+pub fn create_withdraw_liquidity_msgs(
+    deps: Deps,
+    cfg: &Config,
+) -> Result<(Vec<CosmosMsg>, Vec<Coin>), LibraryError> {
+    let (lp_denom, amount) = resolve_withdraw_amount(deps, cfg)?;
 
     // Calculate how much we are going to get when we withdraw
     let withdrawn_assets: Vec<Asset> = deps.querier.query_wasm_smart(
         cfg.pool_addr.clone(),
-        &valence_astroport_utils::astroport_native_lp_token::PoolQueryMsg::Share {
-            amount: balance.amount,
-        },
+        &valence_astroport_utils::astroport_native_lp_token::PoolQueryMsg::Share { amount },
     )?;
 
     // Create the withdraw and send messages
@@ -44,7 +74,7 @@ pub fn create_withdraw_liquidity_msgs(
                 min_assets_to_receive: Some(withdrawn_assets.clone()),
             },
         )?,
-        funds: vec![balance],
+        funds: vec![Coin::new(amount, lp_denom)],
     });
 
     // Send the withdrawn assets to the output account
@@ -55,8 +85,8 @@ pub fn create_withdraw_liquidity_msgs(
 
     let send_msg = CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
         to_address: cfg.output_addr.to_string(),
-        amount: withdrawn_coins,
+        amount: withdrawn_coins.clone(),
     });
 
-    Ok(vec![withdraw_msg, send_msg])
+    Ok((vec![withdraw_msg, send_msg], withdrawn_coins))
 }
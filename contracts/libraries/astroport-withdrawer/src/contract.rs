@@ -1,17 +1,29 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    from_json, to_json_binary, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Reply, Response,
+    StdError, StdResult,
+};
 use valence_library_utils::{
     error::LibraryError,
-    msg::{ExecuteMsg, InstantiateMsg},
+    events::PoolUpdateEvent,
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg},
 };
 
-use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
+use crate::{
+    msg::{
+        Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg, RealizedPnlResponse,
+        WithdrawSettlementCheck,
+    },
+    state::{LIQUIDATIONS_COUNT, REALIZED_PNL},
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+pub const WITHDRAW_SETTLEMENT_REPLY_ID: u64 = 1;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -24,18 +36,53 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
 ) -> Result<Response, LibraryError> {
-    valence_library_base::execute(
-        deps,
+    // Pool before a config update repointing the library, to record the change
+    let previous_pool = match &msg {
+        ExecuteMsg::UpdateConfig { new_config } if new_config.pool_addr.is_some() => {
+            Some(valence_library_base::load_config::<Config>(deps.storage)?.pool_addr)
+        }
+        _ => None,
+    };
+
+    let response = valence_library_base::execute(
+        deps.branch(),
         env,
         info,
         msg,
         functions::process_function,
         execute::update_config,
+    )?;
+
+    match previous_pool {
+        Some(old_pool) => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            if config.pool_addr == old_pool {
+                return Ok(response);
+            }
+            Ok(response.add_event(PoolUpdateEvent::new(old_pool, config.pool_addr)))
+        }
+        None => Ok(response),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
     )
 }
 
@@ -55,18 +102,27 @@ mod execute {
     }
 }
 
-mod functions {
-    use cosmwasm_std::{CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response};
-    use valence_astroport_utils::{get_pool_asset_amounts, query_pool, PoolType};
+pub(crate) mod functions {
+    use cosmwasm_std::{
+        to_json_binary, to_json_vec, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, Int128,
+        MessageInfo, Response, Storage, SubMsg, Uint128,
+    };
+    use valence_astroport_utils::{
+        get_pool_asset_amounts, get_pool_assets_amounts, query_pool, PoolType,
+    };
     use valence_library_utils::{
         error::LibraryError, execute_on_behalf_of, liquidity_utils::DecimalRange,
+        msg::SimulatedAmount, nft::PositionNft,
     };
 
     use crate::{
         astroport_cw20, astroport_native,
-        msg::{Config, FunctionMsgs},
+        msg::{Config, FunctionMsgs, PositionNftHookMsg, WithdrawSettlementCheck},
+        state::{LIQUIDATIONS_COUNT, REALIZED_PNL},
     };
 
+    use super::{query_asset_balances, WITHDRAW_SETTLEMENT_REPLY_ID};
+
     pub fn process_function(
         deps: DepsMut,
         _env: Env,
@@ -77,7 +133,9 @@ mod functions {
         match msg {
             FunctionMsgs::WithdrawLiquidity {
                 expected_pool_ratio_range,
-            } => withdraw_liquidity(deps, cfg, expected_pool_ratio_range),
+                value_provided,
+            } => withdraw_liquidity(deps, cfg, expected_pool_ratio_range, value_provided),
+            FunctionMsgs::WithdrawPosition { position } => withdraw_position(deps, cfg, position),
         }
     }
 
@@ -85,22 +143,26 @@ mod functions {
         deps: DepsMut,
         cfg: Config,
         expected_pool_ratio_range: Option<DecimalRange>,
+        value_provided: Option<Uint128>,
     ) -> Result<Response, LibraryError> {
+        if let Some(skip_reason) = withdraw_skip_reason(deps.as_ref(), &cfg)? {
+            return Ok(Response::new()
+                .add_attribute("method", "withdraw_liquidity")
+                .add_attribute("nothing_to_withdraw", skip_reason));
+        }
+
         // If we have an expected pool ratio range, we need to check if the pool is within that range
         if let Some(range) = expected_pool_ratio_range {
-            // Get assets in the pool
-            let pool_response = query_pool(
-                &deps,
-                cfg.pool_addr.as_ref(),
-                &cfg.withdrawer_config.pool_type,
-            )?;
-
             // Get the amounts of each of the assets of our config in the pool
-            let (pool_asset1_balance, pool_asset2_balance) = get_pool_asset_amounts(
-                pool_response,
-                &cfg.withdrawer_config.asset_data.asset1,
-                &cfg.withdrawer_config.asset_data.asset2,
-            )?;
+            let (pool_asset1_balance, pool_asset2_balance) = query_pool_asset_amounts(&deps, &cfg)?;
+
+            // The pool ratio of a pool holding no asset2 can't be computed, so it can't be in the range
+            if pool_asset2_balance == 0 {
+                return Err(LibraryError::ExecutionError(format!(
+                    "Pool ratio can't be checked, the pool holds no {}",
+                    cfg.withdrawer_config.asset_data.asset2
+                )));
+            }
 
             // Get the pool asset ratios
             let pool_asset_ratios =
@@ -110,30 +172,297 @@ mod functions {
             range.contains(pool_asset_ratios)?;
         }
 
-        let msgs = create_withdraw_liquidity_msgs(&deps, &cfg)?;
+        let (msgs, withdrawn_coins) = create_withdraw_liquidity_msgs(&deps, &cfg)?;
 
-        let input_account_msgs = execute_on_behalf_of(msgs, &cfg.input_addr)?;
+        let mut response = Response::new()
+            .add_submessage(settled_withdraw_submsg(deps.as_ref(), &cfg, msgs)?)
+            .add_attribute("method", "withdraw_liquidity");
+
+        // If the value provided for the liquidated shares is known, we track the realized pnl.
+        // It can't be valued if the pool holds no asset2, in which case it is not tracked
+        if let Some(value_provided) = value_provided {
+            match compute_value_recovered(&deps, &cfg, &withdrawn_coins)? {
+                Some(value_recovered) => {
+                    let pnl = track_realized_pnl(deps.storage, value_provided, value_recovered)?;
+
+                    response = response
+                        .add_attribute("value_provided", value_provided)
+                        .add_attribute("value_recovered", value_recovered)
+                        .add_attribute("realized_pnl", pnl.to_string());
+                }
+                None => {
+                    response = response
+                        .add_attribute("value_provided", value_provided)
+                        .add_attribute("realized_pnl_skipped", "pool_has_no_reserves");
+                }
+            }
+        }
+
+        Ok(response)
+    }
+
+    fn withdraw_position(
+        deps: DepsMut,
+        cfg: Config,
+        position: PositionNft,
+    ) -> Result<Response, LibraryError> {
+        let position = position.to_checked(deps.api, &deps.querier, &cfg.input_addr)?;
+
+        // The pool withdraws the liquidity of the position directly to the output account
+        let withdraw_msg = position.send_msg(
+            &cfg.pool_addr,
+            to_json_binary(&PositionNftHookMsg::WithdrawLiquidity {
+                receiver: cfg.output_addr.to_string(),
+            })?,
+        )?;
 
         Ok(Response::new()
-            .add_message(input_account_msgs)
-            .add_attribute("method", "withdraw_liquidity"))
+            .add_submessage(settled_withdraw_submsg(
+                deps.as_ref(),
+                &cfg,
+                vec![withdraw_msg],
+            )?)
+            .add_attribute("method", "withdraw_position")
+            .add_attribute("nft_contract", position.contract_addr)
+            .add_attribute("token_id", position.token_id))
+    }
+
+    /// Wraps the withdrawal `msgs` to be executed on behalf of the input account, replying with the
+    /// balances of the pool assets in the output account so that what arrived is checked in the reply
+    fn settled_withdraw_submsg(
+        deps: Deps,
+        cfg: &Config,
+        msgs: Vec<CosmosMsg>,
+    ) -> Result<SubMsg, LibraryError> {
+        let input_account_msgs = execute_on_behalf_of(msgs, &cfg.input_addr)?;
+
+        let settlement_check = WithdrawSettlementCheck {
+            output_addr: cfg.output_addr.clone(),
+            balances_before: query_asset_balances(deps, &cfg.output_addr, cfg)?,
+        };
+
+        Ok(
+            SubMsg::reply_on_success(input_account_msgs, WITHDRAW_SETTLEMENT_REPLY_ID)
+                .with_payload(to_json_vec(&settlement_check)?),
+        )
+    }
+
+    /// Why a withdrawal would be a no-op, if it would. An empty position or a pool drained of all the assets redeem nothing,
+    /// so they are skipped instead of aborting the liquidation cycle. A pool with a single empty side still redeems the other one.
+    fn withdraw_skip_reason(
+        deps: Deps,
+        cfg: &Config,
+    ) -> Result<Option<&'static str>, LibraryError> {
+        let (_, lp_balance) = query_lp_balance(deps, cfg)?;
+        if lp_balance.is_zero() {
+            return Ok(Some("zero_lp_balance"));
+        }
+
+        let reserves = match &cfg.withdrawer_config.pool_type {
+            PoolType::NativeLpToken(_) => astroport_native::query_pool_reserves(deps, cfg)?,
+            PoolType::Cw20LpToken(_) => astroport_cw20::query_pool_reserves(deps, cfg)?,
+        };
+        let is_drained = cfg
+            .withdrawer_config
+            .asset_data
+            .assets()
+            .iter()
+            .all(|denom| {
+                !reserves
+                    .iter()
+                    .any(|reserve| reserve.denom == **denom && !reserve.amount.is_zero())
+            });
+        if is_drained {
+            return Ok(Some("pool_has_no_reserves"));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the LP token (its contract address for CW20 LP tokens) and the LP token balance of the input account
+    fn query_lp_balance(deps: Deps, cfg: &Config) -> Result<(String, Uint128), LibraryError> {
+        match &cfg.withdrawer_config.pool_type {
+            PoolType::NativeLpToken(_) => astroport_native::query_lp_balance(deps, cfg),
+            PoolType::Cw20LpToken(_) => astroport_cw20::query_lp_balance(deps, cfg)
+                .map(|(token_addr, balance)| (token_addr.to_string(), balance)),
+        }
+    }
+
+    fn query_pool_asset_amounts(
+        deps: &DepsMut,
+        cfg: &Config,
+    ) -> Result<(u128, u128), LibraryError> {
+        // Get assets in the pool
+        let pool_response = query_pool(
+            deps.as_ref(),
+            cfg.pool_addr.as_ref(),
+            &cfg.withdrawer_config.pool_type,
+        )?;
+
+        get_pool_asset_amounts(
+            pool_response,
+            &cfg.withdrawer_config.asset_data.asset1,
+            &cfg.withdrawer_config.asset_data.asset2,
+        )
+    }
+
+    /// Values the withdrawn assets in terms of asset2, pricing every other asset at the current pool ratio.
+    /// Returns `None` if the pool holds no asset2 to price them in
+    fn compute_value_recovered(
+        deps: &DepsMut,
+        cfg: &Config,
+        withdrawn_coins: &[Coin],
+    ) -> Result<Option<Uint128>, LibraryError> {
+        let pool_response = query_pool(
+            deps.as_ref(),
+            cfg.pool_addr.as_ref(),
+            &cfg.withdrawer_config.pool_type,
+        )?;
+        let assets = cfg.withdrawer_config.asset_data.assets();
+        let denoms: Vec<&str> = assets.iter().map(|denom| denom.as_str()).collect();
+        let pool_balances = get_pool_assets_amounts(pool_response, &denoms)?;
+        // asset2 is always the second asset
+        let pool_asset2_balance = pool_balances[1];
+        if pool_asset2_balance == 0 {
+            return Ok(None);
+        }
+
+        let mut value_recovered = Uint128::zero();
+        for coin in withdrawn_coins {
+            let Some(index) = denoms.iter().position(|denom| coin.denom == *denom) else {
+                continue;
+            };
+            // Nothing is withdrawn from an empty side of the pool
+            if coin.amount.is_zero() || pool_balances[index] == 0 {
+                continue;
+            }
+
+            let value = coin
+                .amount
+                .checked_multiply_ratio(pool_asset2_balance, pool_balances[index])
+                .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+
+            value_recovered = value_recovered
+                .checked_add(value)
+                .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+        }
+
+        Ok(Some(value_recovered))
+    }
+
+    /// Accumulates the pnl of a liquidation into the cumulative realized pnl
+    fn track_realized_pnl(
+        storage: &mut dyn Storage,
+        value_provided: Uint128,
+        value_recovered: Uint128,
+    ) -> Result<Int128, LibraryError> {
+        let provided = Int128::try_from(value_provided)
+            .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+        let recovered = Int128::try_from(value_recovered)
+            .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+        let pnl = recovered
+            .checked_sub(provided)
+            .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+
+        let cumulative_pnl = REALIZED_PNL
+            .may_load(storage)?
+            .unwrap_or_default()
+            .checked_add(pnl)
+            .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+        REALIZED_PNL.save(storage, &cumulative_pnl)?;
+
+        let liquidations = LIQUIDATIONS_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+        LIQUIDATIONS_COUNT.save(storage, &liquidations)?;
+
+        Ok(pnl)
     }
 
     fn create_withdraw_liquidity_msgs(
         deps: &DepsMut,
         cfg: &Config,
-    ) -> Result<Vec<CosmosMsg>, LibraryError> {
+    ) -> Result<(Vec<CosmosMsg>, Vec<Coin>), LibraryError> {
         match &cfg.withdrawer_config.pool_type {
             PoolType::NativeLpToken(_) => {
-                astroport_native::create_withdraw_liquidity_msgs(deps, cfg)
+                astroport_native::create_withdraw_liquidity_msgs(deps.as_ref(), cfg)
             }
-            PoolType::Cw20LpToken(_) => astroport_cw20::create_withdraw_liquidity_msgs(deps, cfg),
+            PoolType::Cw20LpToken(_) => {
+                astroport_cw20::create_withdraw_liquidity_msgs(deps.as_ref(), cfg)
+            }
+        }
+    }
+
+    /// Amount of LP tokens that a withdrawal would redeem now, which fails like the withdrawal would.
+    /// The denom is the LP token contract address for pools with CW20 LP tokens.
+    pub fn simulate_amount(deps: Deps, cfg: &Config) -> Result<SimulatedAmount, LibraryError> {
+        if let Some(skip_reason) = withdraw_skip_reason(deps, cfg)? {
+            let (lp_token, _) = query_lp_balance(deps, cfg)?;
+            return Ok(SimulatedAmount::skipped(lp_token, skip_reason));
+        }
+
+        match &cfg.withdrawer_config.pool_type {
+            PoolType::NativeLpToken(_) => astroport_native::resolve_withdraw_amount(deps, cfg)
+                .map(|(lp_denom, amount)| SimulatedAmount::new(lp_denom, amount)),
+            PoolType::Cw20LpToken(_) => astroport_cw20::resolve_withdraw_amount(deps, cfg)
+                .map(|(token_addr, amount)| SimulatedAmount::new(token_addr, amount)),
+        }
+    }
+}
+
+/// Returns the balances of all the pool assets held by `account`
+fn query_asset_balances(
+    deps: Deps,
+    account: &cosmwasm_std::Addr,
+    cfg: &Config,
+) -> Result<Vec<Coin>, LibraryError> {
+    cfg.withdrawer_config
+        .asset_data
+        .assets()
+        .into_iter()
+        .map(|denom| Ok(deps.querier.query_balance(account, denom)?))
+        .collect()
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, LibraryError> {
+    match msg.id {
+        WITHDRAW_SETTLEMENT_REPLY_ID => {
+            let check: WithdrawSettlementCheck = from_json(&msg.payload)?;
+
+            let mut received = Vec::with_capacity(check.balances_before.len());
+            for before in &check.balances_before {
+                let after = deps
+                    .querier
+                    .query_balance(&check.output_addr, &before.denom)?;
+                received.push(Coin::new(
+                    after.amount.saturating_sub(before.amount),
+                    &before.denom,
+                ));
+            }
+
+            // A withdrawal that delivered nothing is reverted instead of letting the next steps run on stale balances
+            if received.iter().all(|coin| coin.amount.is_zero()) {
+                return Err(LibraryError::ExecutionError(format!(
+                    "Withdrawal settled nothing: none of the pool assets reached the output account {}",
+                    check.output_addr
+                )));
+            }
+
+            let received = received
+                .iter()
+                .map(|coin| coin.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+
+            Ok(Response::new()
+                .add_attribute("method", "check_withdraw_settlement")
+                .add_attribute("received", received))
         }
+        _ => Err(LibraryError::Std(StdError::generic_err("unknown reply id"))),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Ownership {} => {
             to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
@@ -141,6 +470,21 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetProcessor {} => {
             to_json_binary(&valence_library_base::get_processor(deps.storage)?)
         }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        QueryMsg::IsApproved {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&valence_library_utils::is_library_approved(
+                &deps.querier,
+                &config.input_addr,
+                &env.contract.address,
+            )?)
+        }
         QueryMsg::GetLibraryConfig {} => {
             let config: Config = valence_library_base::load_config(deps.storage)?;
             to_json_binary(&config)
@@ -150,5 +494,17 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 valence_library_utils::raw_config::query_raw_library_config(deps.storage)?;
             to_json_binary(&raw_config)
         }
+        QueryMsg::RealizedPnl {} => to_json_binary(&RealizedPnlResponse {
+            cumulative_pnl: REALIZED_PNL.may_load(deps.storage)?.unwrap_or_default(),
+            liquidations: LIQUIDATIONS_COUNT
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+        }),
+        QueryMsg::SimulateAmount {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            let simulated_amount = functions::simulate_amount(deps, &config)
+                .map_err(|err| StdError::generic_err(err.to_string()))?;
+            to_json_binary(&simulated_amount)
+        }
     }
 }
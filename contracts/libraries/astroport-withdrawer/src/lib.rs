@@ -2,5 +2,6 @@ pub mod astroport_cw20;
 pub mod astroport_native;
 pub mod contract;
 pub mod msg;
+pub mod state;
 #[cfg(test)]
 mod tests;
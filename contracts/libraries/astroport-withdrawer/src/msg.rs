@@ -1,11 +1,13 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Deps, DepsMut};
+use cosmwasm_std::{Addr, Coin, Decimal, Deps, DepsMut, Int128, Uint128};
 use cw_ownable::cw_ownable_query;
-use valence_astroport_utils::PoolType;
+use valence_astroport_utils::{ensure_pool_type, PoolAssetsValidation, PoolType};
 use valence_library_utils::{
     error::LibraryError,
     liquidity_utils::{AssetData, DecimalRange},
-    msg::LibraryConfigValidation,
+    msg::{LibraryConfigValidation, SimulatedAmount},
+    nft::PositionNft,
+    rounding::RoundingPolicy,
     LibraryAccountType,
 };
 use valence_macros::{valence_library_query, ValenceLibraryInterface};
@@ -14,14 +16,49 @@ use valence_macros::{valence_library_query, ValenceLibraryInterface};
 pub enum FunctionMsgs {
     WithdrawLiquidity {
         expected_pool_ratio_range: Option<DecimalRange>,
+        /// Value originally provided for the shares being liquidated, denominated in asset2.
+        /// If specified, the difference between the recovered value and this amount
+        /// is accumulated into the realized profit/loss of the library.
+        value_provided: Option<Uint128>,
     },
+    /// Withdraws the liquidity of a position represented by an NFT instead of fungible LP tokens.
+    /// The NFT must be owned by the input account, it is sent to the pool which withdraws to the output account.
+    WithdrawPosition { position: PositionNft },
+}
+
+/// Hook sent to the pool along with a position NFT, withdrawing all the liquidity of the position to the receiver
+#[cw_serde]
+pub enum PositionNftHookMsg {
+    WithdrawLiquidity { receiver: String },
 }
 
 #[valence_library_query]
 #[cw_ownable_query]
 #[cw_serde]
 #[derive(QueryResponses)]
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    /// Returns the cumulative realized profit/loss across all tracked liquidations
+    #[returns(RealizedPnlResponse)]
+    RealizedPnl {},
+    /// Returns the amount of LP tokens that a withdrawal would redeem now, without executing it
+    #[returns(SimulatedAmount)]
+    SimulateAmount {},
+}
+
+#[cw_serde]
+pub struct RealizedPnlResponse {
+    /// Signed cumulative profit/loss, denominated in asset2
+    pub cumulative_pnl: Int128,
+    /// Number of liquidations that were tracked
+    pub liquidations: u64,
+}
+
+/// Balances of the pool assets in the output account before a withdrawal, carried to the reply checking what arrived
+#[cw_serde]
+pub struct WithdrawSettlementCheck {
+    pub output_addr: Addr,
+    pub balances_before: Vec<Coin>,
+}
 
 #[cw_serde]
 #[derive(ValenceLibraryInterface)]
@@ -52,6 +89,9 @@ impl LibraryConfig {
         let output_addr = self.output_addr.to_addr(api)?;
         let pool_addr = api.addr_validate(&self.pool_addr)?;
 
+        self.withdrawer_config.asset_data.validate()?;
+        self.withdrawer_config.withdraw_amount.validate()?;
+
         Ok((input_addr, output_addr, pool_addr))
     }
 }
@@ -63,8 +103,79 @@ pub struct LiquidityWithdrawerConfig {
     /// We also provide the PairType structure of the right Astroport version that we are
     /// going to use for each scenario
     pub pool_type: PoolType,
-    /// Denoms of the underlying assets to be withdrawn
+    /// Denoms of the underlying assets to be withdrawn, all the assets of the pool are withdrawn
     pub asset_data: AssetData,
+    /// Amount of LP tokens of the input account to withdraw
+    #[serde(default)]
+    pub withdraw_amount: WithdrawAmount,
+    /// How percentages of the LP token balance are rounded, down by default
+    #[serde(default)]
+    pub rounding: RoundingPolicy,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum WithdrawAmount {
+    /// Withdraw the whole LP token balance
+    #[default]
+    All,
+    /// Withdraw a fixed amount of LP tokens
+    Fixed(Uint128),
+    /// Withdraw a percentage of the LP token balance, rounded with the configured policy
+    Percentage(Decimal),
+}
+
+impl WithdrawAmount {
+    pub fn validate(&self) -> Result<(), LibraryError> {
+        match self {
+            WithdrawAmount::All => Ok(()),
+            WithdrawAmount::Fixed(amount) => {
+                if amount.is_zero() {
+                    return Err(LibraryError::ConfigurationError(
+                        "Withdraw amount cannot be zero".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            WithdrawAmount::Percentage(percentage) => {
+                if percentage.is_zero() || *percentage > Decimal::one() {
+                    return Err(LibraryError::ConfigurationError(
+                        "Withdraw percentage must be greater than 0 and at most 1".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Computes the amount of LP tokens to withdraw out of the live LP token balance
+    pub fn resolve(
+        &self,
+        balance: Uint128,
+        rounding: RoundingPolicy,
+    ) -> Result<Uint128, LibraryError> {
+        let amount = match self {
+            WithdrawAmount::All => balance,
+            WithdrawAmount::Fixed(amount) => {
+                if *amount > balance {
+                    return Err(LibraryError::ExecutionError(format!(
+                        "Insufficient LP token balance (required: {amount}, available: {balance})"
+                    )));
+                }
+                *amount
+            }
+            // Percentages are at most 1, so this never exceeds the balance, whatever the rounding
+            WithdrawAmount::Percentage(percentage) => rounding.apply(balance, *percentage)?,
+        };
+
+        if amount.is_zero() {
+            return Err(LibraryError::ExecutionError(
+                "Nothing to withdraw".to_string(),
+            ));
+        }
+
+        Ok(amount)
+    }
 }
 
 #[cw_serde]
@@ -86,6 +197,11 @@ impl LibraryConfigValidation<Config> for LibraryConfig {
     fn validate(&self, deps: Deps) -> Result<Config, LibraryError> {
         let (input_addr, output_addr, pool_addr) = self.do_validate(deps.api)?;
 
+        ensure_pool_type(deps, pool_addr.as_str(), &self.withdrawer_config.pool_type)?;
+        self.withdrawer_config
+            .asset_data
+            .validate_against_pool(deps, pool_addr.as_str())?;
+
         Ok(Config {
             input_addr,
             output_addr,
@@ -107,14 +223,31 @@ impl LibraryConfigUpdate {
             config.output_addr = output_addr.to_addr(deps.api)?;
         }
 
+        // The pool type and assets are checked against the pool whenever either of them changes
+        let pool_or_assets_updated = self.pool_addr.is_some() || self.withdrawer_config.is_some();
+
         if let Some(pool_addr) = self.pool_addr {
             config.pool_addr = deps.api.addr_validate(&pool_addr)?;
         }
 
         if let Some(withdrawer_config) = self.withdrawer_config {
+            withdrawer_config.asset_data.validate()?;
+            withdrawer_config.withdraw_amount.validate()?;
             config.withdrawer_config = withdrawer_config;
         }
 
+        if pool_or_assets_updated {
+            ensure_pool_type(
+                deps.as_ref(),
+                config.pool_addr.as_str(),
+                &config.withdrawer_config.pool_type,
+            )?;
+            config
+                .withdrawer_config
+                .asset_data
+                .validate_against_pool(deps.as_ref(), config.pool_addr.as_str())?;
+        }
+
         valence_library_base::save_config(deps.storage, &config)?;
         Ok(())
     }
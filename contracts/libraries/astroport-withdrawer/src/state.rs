@@ -0,0 +1,7 @@
+use cosmwasm_std::Int128;
+use cw_storage_plus::Item;
+
+/// Cumulative realized profit/loss across all liquidations, denominated in asset2
+pub const REALIZED_PNL: Item<Int128> = Item::new("realized_pnl");
+/// Number of liquidations that contributed to the realized profit/loss
+pub const LIQUIDATIONS_COUNT: Item<u64> = Item::new("liquidations_count");
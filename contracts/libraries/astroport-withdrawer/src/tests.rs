@@ -1,5 +1,17 @@
-use cosmwasm_std::{Decimal, Uint128};
+use std::str::FromStr;
+
+use cosmwasm_std::{
+    coin, from_json,
+    testing::{
+        message_info, mock_dependencies, mock_dependencies_with_balances, mock_env, MockApi,
+        MockQuerier, MockStorage,
+    },
+    to_json_binary, Addr, BankMsg, ContractResult, CosmosMsg, Decimal, Empty, Int128, OwnedDeps,
+    Reply, ReplyOn, Response, SubMsgResponse, SubMsgResult, SystemResult, Uint128, WasmMsg,
+    WasmQuery,
+};
 use cw20::Cw20ExecuteMsg;
+use cw721::msg::{Cw721ExecuteMsg, OwnerOfResponse};
 use neutron_test_tube::{
     neutron_std::types::cosmos::{
         bank::v1beta1::{MsgSend, QueryAllBalancesRequest},
@@ -9,17 +21,28 @@ use neutron_test_tube::{
 };
 use valence_astroport_utils::{
     suite::{AstroportTestAppBuilder, AstroportTestAppSetup},
-    PoolType,
+    PoolKind, PoolType,
 };
 use valence_library_utils::{
     error::{LibraryError, UnauthorizedReason},
+    events::POOL_UPDATE_EVENT_TYPE,
     liquidity_utils::{AssetData, DecimalRange},
-    msg::{ExecuteMsg, InstantiateMsg},
+    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation, SimulatedAmount},
+    nft::PositionNft,
+    rounding::RoundingPolicy,
 };
 
-use crate::msg::{FunctionMsgs, LibraryConfig, LibraryConfigUpdate, LiquidityWithdrawerConfig};
+use crate::{
+    astroport_native,
+    contract::{functions::process_function, query, reply, WITHDRAW_SETTLEMENT_REPLY_ID},
+    msg::{
+        Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, LiquidityWithdrawerConfig,
+        PositionNftHookMsg, QueryMsg, RealizedPnlResponse, WithdrawAmount, WithdrawSettlementCheck,
+    },
+};
 
 const CONTRACT_PATH: &str = "../../../artifacts";
+const MOCKED_LP_TOKEN: &str = "factory/pool/astroport/share";
 
 struct WithdrawerTestSuite {
     pub inner: AstroportTestAppSetup,
@@ -177,7 +200,10 @@ fn instantiate_withdrawer_contract(
                     asset_data: AssetData {
                         asset1: setup.pool_asset1.to_string(),
                         asset2: setup.pool_asset2.to_string(),
+                        additional_assets: vec![],
                     },
+                    withdraw_amount: WithdrawAmount::All,
+                    rounding: RoundingPolicy::Down,
                 },
             ),
         },
@@ -207,7 +233,10 @@ pub fn only_owner_can_update_config() {
             asset_data: AssetData {
                 asset1: setup.inner.pool_asset1.to_string(),
                 asset2: setup.inner.pool_asset2.to_string(),
+                additional_assets: vec![],
             },
+            withdraw_amount: WithdrawAmount::All,
+            rounding: RoundingPolicy::Down,
         }),
     };
 
@@ -237,6 +266,99 @@ pub fn only_owner_can_update_config() {
     .unwrap();
 }
 
+fn cw20_withdrawer_config(asset1: &str, asset2: &str) -> LiquidityWithdrawerConfig {
+    LiquidityWithdrawerConfig {
+        pool_type: PoolType::Cw20LpToken(
+            valence_astroport_utils::astroport_cw20_lp_token::PairType::Xyk {},
+        ),
+        asset_data: AssetData {
+            asset1: asset1.to_string(),
+            asset2: asset2.to_string(),
+            additional_assets: vec![],
+        },
+        withdraw_amount: WithdrawAmount::All,
+        rounding: RoundingPolicy::Down,
+    }
+}
+
+#[test]
+fn update_config_repoints_to_new_pool() {
+    let setup = WithdrawerTestSuite::default();
+    let wasm = Wasm::new(&setup.inner.app);
+
+    let response = wasm
+        .execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+            &setup.withdrawer_addr,
+            &ExecuteMsg::UpdateConfig {
+                new_config: LibraryConfigUpdate {
+                    pool_addr: Some(setup.inner.pool_cw20_addr.clone()),
+                    withdrawer_config: Some(cw20_withdrawer_config(
+                        &setup.inner.pool_asset1,
+                        &setup.inner.pool_asset2,
+                    )),
+                    ..Default::default()
+                },
+            },
+            &[],
+            setup.inner.owner_acc(),
+        )
+        .unwrap();
+
+    // The old and new pools are recorded
+    let event = response
+        .events
+        .iter()
+        .find(|event| event.ty == format!("wasm-{POOL_UPDATE_EVENT_TYPE}"))
+        .unwrap();
+    let attribute = |key: &str| {
+        event
+            .attributes
+            .iter()
+            .find(|attr| attr.key == key)
+            .unwrap()
+            .value
+            .clone()
+    };
+    assert_eq!(attribute("old_pool"), setup.inner.pool_native_addr);
+    assert_eq!(attribute("new_pool"), setup.inner.pool_cw20_addr);
+
+    let config: Config = wasm
+        .query(&setup.withdrawer_addr, &QueryMsg::GetLibraryConfig {})
+        .unwrap();
+    assert_eq!(config.pool_addr.as_str(), setup.inner.pool_cw20_addr);
+    assert_eq!(config.input_addr.as_str(), setup.input_acc);
+}
+
+#[test]
+fn update_config_rejects_pool_with_mismatched_assets() {
+    let setup = WithdrawerTestSuite::default();
+    let wasm = Wasm::new(&setup.inner.app);
+
+    let error = wasm
+        .execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+            &setup.withdrawer_addr,
+            &ExecuteMsg::UpdateConfig {
+                new_config: LibraryConfigUpdate {
+                    pool_addr: Some(setup.inner.pool_cw20_addr.clone()),
+                    withdrawer_config: Some(cw20_withdrawer_config(
+                        &setup.inner.pool_asset1,
+                        "uatom",
+                    )),
+                    ..Default::default()
+                },
+            },
+            &[],
+            setup.inner.owner_acc(),
+        )
+        .unwrap_err();
+    assert!(error.to_string().contains("do not match the pool assets"));
+
+    let config: Config = wasm
+        .query(&setup.withdrawer_addr, &QueryMsg::GetLibraryConfig {})
+        .unwrap();
+    assert_eq!(config.pool_addr.as_str(), setup.inner.pool_native_addr);
+}
+
 #[test]
 fn only_owner_can_update_processor() {
     let setup = WithdrawerTestSuite::default();
@@ -315,6 +437,7 @@ fn only_processor_can_execute_functions() {
             &setup.withdrawer_addr,
             &ExecuteMsg::ProcessFunction(FunctionMsgs::WithdrawLiquidity {
                 expected_pool_ratio_range: None,
+                value_provided: None,
             }),
             &[],
             setup.inner.owner_acc(),
@@ -331,6 +454,7 @@ fn only_processor_can_execute_functions() {
         &setup.withdrawer_addr,
         &ExecuteMsg::ProcessFunction(FunctionMsgs::WithdrawLiquidity {
             expected_pool_ratio_range: None,
+            value_provided: None,
         }),
         &[],
         setup.inner.processor_acc(),
@@ -348,6 +472,7 @@ fn withdraw_liquidity_native_lp_token() {
         &setup.withdrawer_addr,
         &ExecuteMsg::ProcessFunction(FunctionMsgs::WithdrawLiquidity {
             expected_pool_ratio_range: None,
+            value_provided: None,
         }),
         &[],
         setup.inner.processor_acc(),
@@ -374,6 +499,63 @@ fn withdraw_liquidity_native_lp_token() {
         .any(|c| c.denom == setup.inner.pool_asset2));
 }
 
+#[test]
+fn withdraw_liquidity_tracks_realized_pnl() {
+    let setup = WithdrawerTestSuite::default();
+    let wasm = Wasm::new(&setup.inner.app);
+    let bank = Bank::new(&setup.inner.app);
+
+    let realized_pnl_of = |value_provided: u128| -> Int128 {
+        let response = wasm
+            .execute::<ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>>(
+                &setup.withdrawer_addr,
+                &ExecuteMsg::ProcessFunction(FunctionMsgs::WithdrawLiquidity {
+                    expected_pool_ratio_range: None,
+                    value_provided: Some(Uint128::new(value_provided)),
+                }),
+                &[],
+                setup.inner.processor_acc(),
+            )
+            .unwrap();
+
+        let attribute = response
+            .events
+            .iter()
+            .flat_map(|e| e.attributes.iter())
+            .find(|a| a.key == "realized_pnl")
+            .unwrap();
+        Int128::from_str(&attribute.value).unwrap()
+    };
+
+    // 10000 LP tokens are worth around 28284 of asset2, so this liquidation is profitable
+    let profit = realized_pnl_of(20000);
+    assert!(profit > Int128::zero());
+
+    // Give the input account more LP tokens to liquidate at a loss
+    bank.send(
+        MsgSend {
+            from_address: setup.inner.owner_acc().address(),
+            to_address: setup.input_acc.clone(),
+            amount: vec![BankCoin {
+                denom: setup.inner.pool_native_liquidity_token.clone(),
+                amount: "10000".to_string(),
+            }],
+        },
+        setup.inner.owner_acc(),
+    )
+    .unwrap();
+
+    let loss = realized_pnl_of(40000);
+    assert!(loss < Int128::zero());
+
+    let realized_pnl: RealizedPnlResponse = wasm
+        .query(&setup.withdrawer_addr, &QueryMsg::RealizedPnl {})
+        .unwrap();
+
+    assert_eq!(realized_pnl.cumulative_pnl, profit + loss);
+    assert_eq!(realized_pnl.liquidations, 2);
+}
+
 #[test]
 fn withdraw_liquidity_cw20_lp_token() {
     let setup = WithdrawerTestSuite::new(false);
@@ -384,6 +566,7 @@ fn withdraw_liquidity_cw20_lp_token() {
         &setup.withdrawer_addr,
         &ExecuteMsg::ProcessFunction(FunctionMsgs::WithdrawLiquidity {
             expected_pool_ratio_range: None,
+            value_provided: None,
         }),
         &[],
         setup.inner.processor_acc(),
@@ -423,6 +606,7 @@ fn withdraw_liquidity_expected_decimal_range_validation_succeeds() {
                 Decimal::from_ratio(1u128, 10u128),
                 Decimal::from_ratio(11u128, 10u128),
             )),
+            value_provided: None,
         }),
         &[],
         setup.inner.processor_acc(),
@@ -462,9 +646,785 @@ fn withdraw_liquidity_expected_decimal_range_validation_fails() {
                 Decimal::from_ratio(15u128, 1u128),
                 Decimal::from_ratio(17u128, 1u128),
             )),
+            value_provided: None,
         }),
         &[],
         setup.inner.processor_acc(),
     )
     .unwrap();
 }
+
+// Withdraw amount tests
+
+#[test]
+fn withdraw_amount_resolves_against_lp_balance() {
+    let balance = Uint128::new(1_000);
+
+    assert_eq!(
+        WithdrawAmount::All
+            .resolve(balance, RoundingPolicy::Down)
+            .unwrap(),
+        balance
+    );
+    assert_eq!(
+        WithdrawAmount::Fixed(Uint128::new(400))
+            .resolve(balance, RoundingPolicy::Down)
+            .unwrap(),
+        Uint128::new(400)
+    );
+    // Percentages are rounded with the configured policy
+    for (rounding, expected) in [
+        (RoundingPolicy::Down, 333),
+        (RoundingPolicy::Nearest, 333),
+        (RoundingPolicy::Up, 334),
+    ] {
+        assert_eq!(
+            WithdrawAmount::Percentage(Decimal::from_str("0.3333").unwrap())
+                .resolve(balance, rounding)
+                .unwrap(),
+            Uint128::new(expected)
+        );
+    }
+    assert_eq!(
+        WithdrawAmount::Percentage(Decimal::one())
+            .resolve(balance, RoundingPolicy::Down)
+            .unwrap(),
+        balance
+    );
+
+    // A fixed amount can't exceed the live balance
+    assert_eq!(
+        WithdrawAmount::Fixed(Uint128::new(1_001))
+            .resolve(balance, RoundingPolicy::Down)
+            .unwrap_err()
+            .to_string(),
+        LibraryError::ExecutionError(
+            "Insufficient LP token balance (required: 1001, available: 1000)".to_string()
+        )
+        .to_string()
+    );
+
+    // Nothing to withdraw if the computed amount is zero
+    assert_eq!(
+        WithdrawAmount::Percentage(Decimal::percent(10))
+            .resolve(Uint128::new(9), RoundingPolicy::Down)
+            .unwrap_err()
+            .to_string(),
+        LibraryError::ExecutionError("Nothing to withdraw".to_string()).to_string()
+    );
+    assert!(WithdrawAmount::All
+        .resolve(Uint128::zero(), RoundingPolicy::Down)
+        .is_err());
+}
+
+#[test]
+fn withdraw_amount_validation() {
+    WithdrawAmount::All.validate().unwrap();
+    WithdrawAmount::Fixed(Uint128::one()).validate().unwrap();
+    WithdrawAmount::Percentage(Decimal::one())
+        .validate()
+        .unwrap();
+
+    assert!(WithdrawAmount::Fixed(Uint128::zero()).validate().is_err());
+    assert!(WithdrawAmount::Percentage(Decimal::zero())
+        .validate()
+        .is_err());
+    assert!(WithdrawAmount::Percentage(Decimal::percent(101))
+        .validate()
+        .is_err());
+}
+
+#[test]
+fn withdraw_percentage_of_mocked_lp_balance() {
+    use valence_astroport_utils::astroport_native_lp_token::{
+        Asset, AssetInfo, PairInfo, PairType, PoolQueryMsg,
+    };
+
+    let api = MockApi::default();
+    let lp_token = "factory/pool/astroport/share";
+    let cfg = Config {
+        input_addr: api.addr_make("input_account"),
+        output_addr: api.addr_make("output_account"),
+        pool_addr: api.addr_make("pool"),
+        withdrawer_config: LiquidityWithdrawerConfig {
+            pool_type: PoolType::NativeLpToken(PairType::Xyk {}),
+            asset_data: AssetData {
+                asset1: "untrn".to_string(),
+                asset2: "uusdc".to_string(),
+                additional_assets: vec![],
+            },
+            withdraw_amount: WithdrawAmount::Percentage(Decimal::percent(25)),
+            rounding: RoundingPolicy::Down,
+        },
+    };
+
+    // Mocked LP balance of the input account
+    let mut deps =
+        mock_dependencies_with_balances(&[(cfg.input_addr.as_str(), &[coin(1_001, lp_token)])]);
+    let pool_addr = cfg.pool_addr.clone();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr.as_str() == pool_addr.as_str() => {
+            let response = match from_json::<PoolQueryMsg>(msg).unwrap() {
+                PoolQueryMsg::Pair {} => to_json_binary(&PairInfo {
+                    asset_infos: vec![],
+                    contract_addr: pool_addr.clone(),
+                    liquidity_token: lp_token.to_string(),
+                    pair_type: PairType::Xyk {},
+                }),
+                // Each LP token is worth 2 untrn and 4 uusdc
+                PoolQueryMsg::Share { amount } => to_json_binary(&vec![
+                    Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "untrn".to_string(),
+                        },
+                        amount: amount * Uint128::new(2),
+                    },
+                    Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uusdc".to_string(),
+                        },
+                        amount: amount * Uint128::new(4),
+                    },
+                ]),
+                _ => panic!("Unexpected pool query"),
+            };
+            SystemResult::Ok(ContractResult::Ok(response.unwrap()))
+        }
+        _ => panic!("Unexpected query"),
+    });
+
+    let (msgs, withdrawn_coins) =
+        astroport_native::create_withdraw_liquidity_msgs(deps.as_ref(), &cfg).unwrap();
+
+    // 25% of 1001 LP tokens, rounded down
+    match &msgs[0] {
+        CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }) => {
+            assert_eq!(funds, &vec![coin(250, lp_token)]);
+        }
+        _ => panic!("Expected a withdraw liquidity message"),
+    }
+    assert_eq!(
+        withdrawn_coins,
+        vec![coin(500, "untrn"), coin(1_000, "uusdc")]
+    );
+    assert_eq!(
+        msgs[1],
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: cfg.output_addr.to_string(),
+            amount: withdrawn_coins,
+        })
+    );
+}
+
+fn mocked_native_xyk_config(withdraw_amount: WithdrawAmount) -> Config {
+    use valence_astroport_utils::astroport_native_lp_token::PairType;
+
+    let api = MockApi::default();
+    Config {
+        input_addr: api.addr_make("input_account"),
+        output_addr: api.addr_make("output_account"),
+        pool_addr: api.addr_make("pool"),
+        withdrawer_config: LiquidityWithdrawerConfig {
+            pool_type: PoolType::NativeLpToken(PairType::Xyk {}),
+            asset_data: AssetData::new("untrn", "uusdc"),
+            withdraw_amount,
+            rounding: RoundingPolicy::Down,
+        },
+    }
+}
+
+// Mocks an untrn/uusdc pool with the given reserves, and the LP token balance of the input account.
+// The config is saved so that the library can be queried.
+fn mock_native_pool_deps(
+    cfg: &Config,
+    lp_balance: u128,
+    reserves: [u128; 2],
+) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    use valence_astroport_utils::astroport_native_lp_token::{
+        Asset, AssetInfo, PairInfo, PairType, PoolQueryMsg, PoolResponse,
+    };
+
+    let mut deps = mock_dependencies_with_balances(&[(
+        cfg.input_addr.as_str(),
+        &[coin(lp_balance, MOCKED_LP_TOKEN)],
+    )]);
+    let pool_addr = cfg.pool_addr.clone();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { msg, .. } => {
+            let response = match from_json::<PoolQueryMsg>(msg).unwrap() {
+                PoolQueryMsg::Pair {} => to_json_binary(&PairInfo {
+                    asset_infos: vec![],
+                    contract_addr: pool_addr.clone(),
+                    liquidity_token: MOCKED_LP_TOKEN.to_string(),
+                    pair_type: PairType::Xyk {},
+                }),
+                PoolQueryMsg::Pool {} => to_json_binary(&PoolResponse {
+                    assets: ["untrn", "uusdc"]
+                        .into_iter()
+                        .zip(reserves)
+                        .map(|(denom, amount)| Asset {
+                            info: AssetInfo::NativeToken {
+                                denom: denom.to_string(),
+                            },
+                            amount: Uint128::new(amount),
+                        })
+                        .collect(),
+                    total_share: Uint128::new(reserves[0]),
+                }),
+                PoolQueryMsg::Share { .. } => to_json_binary(&Vec::<()>::new()),
+                _ => panic!("Unexpected pool query"),
+            };
+            SystemResult::Ok(ContractResult::Ok(response.unwrap()))
+        }
+        _ => panic!("Unexpected query"),
+    });
+    valence_library_base::save_config(deps.as_mut().storage, cfg).unwrap();
+    deps
+}
+
+fn process_withdraw(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    cfg: &Config,
+    expected_pool_ratio_range: Option<DecimalRange>,
+    value_provided: Option<Uint128>,
+) -> Result<Response, LibraryError> {
+    process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::WithdrawLiquidity {
+            expected_pool_ratio_range,
+            value_provided,
+        },
+        cfg.clone(),
+    )
+}
+
+#[test]
+fn simulated_amount_matches_withdrawn_lp_tokens() {
+    for (withdraw_amount, expected_amount) in [
+        (WithdrawAmount::Fixed(Uint128::new(400)), 400),
+        (WithdrawAmount::Percentage(Decimal::percent(25)), 250),
+        (WithdrawAmount::All, 1_001),
+    ] {
+        let cfg = mocked_native_xyk_config(withdraw_amount);
+        let deps = mock_native_pool_deps(&cfg, 1_001, [1_000_000, 2_000_000]);
+
+        let simulated: SimulatedAmount =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::SimulateAmount {}).unwrap())
+                .unwrap();
+        assert_eq!(
+            simulated,
+            SimulatedAmount::new(MOCKED_LP_TOKEN, Uint128::new(expected_amount))
+        );
+
+        let (msgs, _) =
+            astroport_native::create_withdraw_liquidity_msgs(deps.as_ref(), &cfg).unwrap();
+        match &msgs[0] {
+            CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }) => {
+                assert_eq!(funds, &vec![coin(simulated.amount.u128(), MOCKED_LP_TOKEN)]);
+            }
+            _ => panic!("Expected a withdraw liquidity message"),
+        }
+    }
+}
+
+#[test]
+fn withdraw_with_zero_lp_balance_is_a_noop() {
+    // A fixed amount can't be withdrawn from an empty position either
+    for withdraw_amount in [
+        WithdrawAmount::All,
+        WithdrawAmount::Fixed(Uint128::new(400)),
+        WithdrawAmount::Percentage(Decimal::percent(25)),
+    ] {
+        let cfg = mocked_native_xyk_config(withdraw_amount);
+        let mut deps = mock_native_pool_deps(&cfg, 0, [1_000_000, 2_000_000]);
+
+        let res = process_withdraw(&mut deps, &cfg, None, None).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(
+            res.attributes
+                .iter()
+                .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("method", "withdraw_liquidity"),
+                ("nothing_to_withdraw", "zero_lp_balance")
+            ]
+        );
+
+        let simulated: SimulatedAmount =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::SimulateAmount {}).unwrap())
+                .unwrap();
+        assert_eq!(
+            simulated,
+            SimulatedAmount::skipped(MOCKED_LP_TOKEN, "zero_lp_balance")
+        );
+    }
+}
+
+#[test]
+fn withdraw_from_pool_with_zero_reserves_is_a_noop() {
+    let cfg = mocked_native_xyk_config(WithdrawAmount::All);
+    let mut deps = mock_native_pool_deps(&cfg, 1_001, [0, 0]);
+
+    // Neither the pool ratio nor the value recovered can be computed, so both are skipped along with the withdrawal
+    let res = process_withdraw(
+        &mut deps,
+        &cfg,
+        Some(DecimalRange::new(
+            Decimal::percent(40),
+            Decimal::percent(60),
+        )),
+        Some(Uint128::new(1_000)),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "nothing_to_withdraw" && attr.value == "pool_has_no_reserves"));
+
+    // No liquidation was tracked
+    let pnl: RealizedPnlResponse =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::RealizedPnl {}).unwrap()).unwrap();
+    assert_eq!(pnl.liquidations, 0);
+
+    let simulated: SimulatedAmount =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::SimulateAmount {}).unwrap()).unwrap();
+    assert_eq!(
+        simulated,
+        SimulatedAmount::skipped(MOCKED_LP_TOKEN, "pool_has_no_reserves")
+    );
+}
+
+#[test]
+fn withdraw_from_pool_with_one_empty_side_redeems_the_shares() {
+    let cfg = mocked_native_xyk_config(WithdrawAmount::All);
+    let mut deps = mock_native_pool_deps(&cfg, 1_001, [1_000_000, 0]);
+
+    // The pool ratio can't be computed without any asset2 in the pool
+    let err = process_withdraw(
+        &mut deps,
+        &cfg,
+        Some(DecimalRange::new(
+            Decimal::percent(40),
+            Decimal::percent(60),
+        )),
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Pool ratio can't be checked, the pool holds no uusdc"
+    );
+
+    // Without a ratio check the LP shares are redeemed, the realized pnl can't be valued in asset2 though
+    let res = process_withdraw(&mut deps, &cfg, None, Some(Uint128::new(1_000))).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert!(!res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "nothing_to_withdraw"));
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "realized_pnl_skipped"));
+
+    let pnl: RealizedPnlResponse =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::RealizedPnl {}).unwrap()).unwrap();
+    assert_eq!(pnl.liquidations, 0);
+
+    let simulated: SimulatedAmount =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::SimulateAmount {}).unwrap()).unwrap();
+    assert_eq!(simulated.amount, Uint128::new(1_001));
+}
+
+fn settlement_reply(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    payload: cosmwasm_std::Binary,
+) -> Result<Response, LibraryError> {
+    #[allow(deprecated)]
+    let result = SubMsgResult::Ok(SubMsgResponse {
+        events: vec![],
+        data: None,
+        msg_responses: vec![],
+    });
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: WITHDRAW_SETTLEMENT_REPLY_ID,
+            payload,
+            gas_used: 0,
+            result,
+        },
+    )
+}
+
+#[test]
+fn withdraw_is_settled_with_the_assets_received() {
+    let cfg = mocked_native_xyk_config(WithdrawAmount::All);
+    let mut deps = mock_native_pool_deps(&cfg, 1_001, [1_000_000, 2_000_000]);
+    deps.querier
+        .bank
+        .update_balance(cfg.output_addr.clone(), vec![coin(50, "untrn")]);
+
+    let res = process_withdraw(&mut deps, &cfg, None, None).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, WITHDRAW_SETTLEMENT_REPLY_ID);
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Success);
+    let check: WithdrawSettlementCheck = from_json(&res.messages[0].payload).unwrap();
+    assert_eq!(
+        check,
+        WithdrawSettlementCheck {
+            output_addr: cfg.output_addr.clone(),
+            balances_before: vec![coin(50, "untrn"), coin(0, "uusdc")],
+        }
+    );
+
+    // Both pool assets arrived in the output account
+    deps.querier.bank.update_balance(
+        cfg.output_addr.clone(),
+        vec![coin(150, "untrn"), coin(250, "uusdc")],
+    );
+
+    let response = settlement_reply(&mut deps, res.messages[0].payload.clone()).unwrap();
+    assert!(response
+        .attributes
+        .iter()
+        .any(|a| a.key == "received" && a.value == "100untrn,250uusdc"));
+}
+
+#[test]
+fn withdraw_settlement_reverts_when_nothing_arrived() {
+    let cfg = mocked_native_xyk_config(WithdrawAmount::All);
+    let mut deps = mock_native_pool_deps(&cfg, 1_001, [1_000_000, 2_000_000]);
+    deps.querier
+        .bank
+        .update_balance(cfg.output_addr.clone(), vec![coin(50, "untrn")]);
+
+    let res = process_withdraw(&mut deps, &cfg, None, None).unwrap();
+
+    // The output account balances didn't move
+    let err = settlement_reply(&mut deps, res.messages[0].payload.clone()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Execution error: Withdrawal settled nothing: none of the pool assets reached the output account {}",
+            cfg.output_addr
+        )
+    );
+}
+
+fn validate_against_mocked_pool(
+    pool_denoms: &[&str],
+    asset_data: AssetData,
+) -> Result<Config, LibraryError> {
+    validate_against_mocked_pool_of_kind(pool_denoms, asset_data, PoolKind::Xyk, PoolKind::Xyk)
+}
+
+// Validates a config for a `config_kind` pool against a mocked pool of `pool_kind`
+fn validate_against_mocked_pool_of_kind(
+    pool_denoms: &[&str],
+    asset_data: AssetData,
+    config_kind: PoolKind,
+    pool_kind: PoolKind,
+) -> Result<Config, LibraryError> {
+    use valence_astroport_utils::astroport_native_lp_token::{AssetInfo, PairInfo};
+
+    let pool_pair_type = match pool_kind.native_lp_token_pool_type() {
+        PoolType::NativeLpToken(pair_type) => pair_type,
+        PoolType::Cw20LpToken(_) => unreachable!(),
+    };
+
+    let api = MockApi::default();
+    let pool_addr = api.addr_make("pool");
+    let library_config = LibraryConfig::new(
+        api.addr_make("input_account").as_str(),
+        api.addr_make("output_account").as_str(),
+        pool_addr.to_string(),
+        LiquidityWithdrawerConfig {
+            pool_type: config_kind.native_lp_token_pool_type(),
+            asset_data,
+            withdraw_amount: WithdrawAmount::All,
+            rounding: RoundingPolicy::Down,
+        },
+    );
+
+    let mut deps = mock_dependencies();
+    let response = to_json_binary(&PairInfo {
+        asset_infos: pool_denoms
+            .iter()
+            .map(|denom| AssetInfo::NativeToken {
+                denom: denom.to_string(),
+            })
+            .collect(),
+        contract_addr: pool_addr,
+        liquidity_token: "factory/pool/astroport/share".to_string(),
+        pair_type: pool_pair_type,
+    })
+    .unwrap();
+    deps.querier
+        .update_wasm(move |_| SystemResult::Ok(ContractResult::Ok(response.clone())));
+
+    library_config.validate(deps.as_ref())
+}
+
+#[test]
+fn instantiate_validates_assets_against_matching_pool() {
+    validate_against_mocked_pool(&["untrn", "uusdc"], AssetData::new("untrn", "uusdc")).unwrap();
+
+    // The order of the assets doesn't matter for withdrawals
+    validate_against_mocked_pool(&["uusdc", "untrn"], AssetData::new("untrn", "uusdc")).unwrap();
+}
+
+#[test]
+fn instantiate_fails_for_assets_mismatching_pool() {
+    assert_eq!(
+        validate_against_mocked_pool(&["untrn", "uatom"], AssetData::new("untrn", "uusdc"))
+            .unwrap_err()
+            .to_string(),
+        LibraryError::ConfigurationError(
+            "Configured assets [untrn, uusdc] do not match the pool assets [untrn, uatom]"
+                .to_string()
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn instantiate_validates_pool_kind_against_pool() {
+    for pool_kind in [
+        PoolKind::Xyk,
+        PoolKind::ConcentratedLiquidity,
+        PoolKind::Stable,
+    ] {
+        validate_against_mocked_pool_of_kind(
+            &["untrn", "uusdc"],
+            AssetData::new("untrn", "uusdc"),
+            pool_kind,
+            pool_kind,
+        )
+        .unwrap();
+    }
+
+    // A config for a concentrated liquidity pool can't point at an Xyk pool
+    assert_eq!(
+        validate_against_mocked_pool_of_kind(
+            &["untrn", "uusdc"],
+            AssetData::new("untrn", "uusdc"),
+            PoolKind::ConcentratedLiquidity,
+            PoolKind::Xyk,
+        )
+        .unwrap_err()
+        .to_string(),
+        LibraryError::ConfigurationError(
+            "Pool type does not match the expected pair type".to_string()
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn withdraw_message_shape_per_pool_kind() {
+    use valence_astroport_utils::astroport_native_lp_token::{
+        Asset, AssetInfo, ExecuteMsg as PoolExecuteMsg, PairInfo, PoolQueryMsg,
+    };
+
+    for pool_kind in [
+        PoolKind::Xyk,
+        PoolKind::ConcentratedLiquidity,
+        PoolKind::Stable,
+    ] {
+        let api = MockApi::default();
+        let lp_token = "factory/pool/astroport/share";
+        let cfg = Config {
+            input_addr: api.addr_make("input_account"),
+            output_addr: api.addr_make("output_account"),
+            pool_addr: api.addr_make("pool"),
+            withdrawer_config: LiquidityWithdrawerConfig {
+                pool_type: pool_kind.native_lp_token_pool_type(),
+                asset_data: AssetData::new("untrn", "uusdc"),
+                withdraw_amount: WithdrawAmount::All,
+                rounding: RoundingPolicy::Down,
+            },
+        };
+        let pair_type = match &cfg.withdrawer_config.pool_type {
+            PoolType::NativeLpToken(pair_type) => pair_type.clone(),
+            PoolType::Cw20LpToken(_) => unreachable!(),
+        };
+        let share = vec![
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "untrn".to_string(),
+                },
+                amount: Uint128::new(200),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusdc".to_string(),
+                },
+                amount: Uint128::new(400),
+            },
+        ];
+
+        let mut deps =
+            mock_dependencies_with_balances(&[(cfg.input_addr.as_str(), &[coin(100, lp_token)])]);
+        let pool_addr = cfg.pool_addr.clone();
+        let share_response = share.clone();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let response = match from_json::<PoolQueryMsg>(msg).unwrap() {
+                    PoolQueryMsg::Pair {} => to_json_binary(&PairInfo {
+                        asset_infos: vec![],
+                        contract_addr: pool_addr.clone(),
+                        liquidity_token: lp_token.to_string(),
+                        pair_type: pair_type.clone(),
+                    }),
+                    PoolQueryMsg::Share { .. } => to_json_binary(&share_response),
+                    _ => panic!("Unexpected pool query"),
+                };
+                SystemResult::Ok(ContractResult::Ok(response.unwrap()))
+            }
+            _ => panic!("Unexpected query"),
+        });
+
+        let (msgs, _) =
+            astroport_native::create_withdraw_liquidity_msgs(deps.as_ref(), &cfg).unwrap();
+
+        // Balanced withdrawals, protected by the simulated share of the pool
+        match &msgs[0] {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, funds, .. }) => {
+                assert_eq!(funds, &vec![coin(100, lp_token)], "{pool_kind:?}");
+                assert_eq!(
+                    from_json::<PoolExecuteMsg>(msg).unwrap(),
+                    PoolExecuteMsg::WithdrawLiquidity {
+                        assets: vec![],
+                        min_assets_to_receive: Some(share.clone()),
+                    },
+                    "{pool_kind:?}"
+                );
+            }
+            _ => panic!("Expected a withdraw liquidity message"),
+        }
+    }
+}
+
+// Mocks the cw721 contract of the positions, in which token "1" is owned by `owner`
+fn mock_position_nft_deps(
+    nft_addr: &Addr,
+    owner: &Addr,
+) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    let nft_addr = nft_addr.clone();
+    let owner = owner.clone();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } if *contract_addr == nft_addr.to_string() => {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&OwnerOfResponse {
+                    owner: owner.to_string(),
+                    approvals: vec![],
+                })
+                .unwrap(),
+            ))
+        }
+        _ => panic!("Unexpected query"),
+    });
+    deps
+}
+
+#[test]
+fn withdraw_position_sends_the_nft_to_the_pool() {
+    let cfg = mocked_native_xyk_config(WithdrawAmount::All);
+    let nft_addr = MockApi::default().addr_make("positions");
+    let mut deps = mock_position_nft_deps(&nft_addr, &cfg.input_addr);
+
+    let res = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::WithdrawPosition {
+            position: PositionNft::new(nft_addr.to_string(), "1"),
+        },
+        cfg.clone(),
+    )
+    .unwrap();
+
+    // The withdrawal against the position is settled like any other
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, WITHDRAW_SETTLEMENT_REPLY_ID);
+    let CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr, msg, ..
+    }) = &res.messages[0].msg
+    else {
+        panic!("Expected the input account to be executed");
+    };
+    assert_eq!(*contract_addr, cfg.input_addr.to_string());
+    let valence_account_utils::msg::ExecuteMsg::ExecuteMsg { msgs } = from_json(msg).unwrap()
+    else {
+        panic!("Expected the withdrawal to be executed on behalf of the input account");
+    };
+
+    // The NFT is sent to the pool, withdrawing the liquidity of the position to the output account
+    assert_eq!(
+        msgs,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: nft_addr.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::<Empty, Empty, Empty>::SendNft {
+                contract: cfg.pool_addr.to_string(),
+                token_id: "1".to_string(),
+                msg: to_json_binary(&PositionNftHookMsg::WithdrawLiquidity {
+                    receiver: cfg.output_addr.to_string(),
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+}
+
+#[test]
+fn withdraw_position_validates_the_nft() {
+    let cfg = mocked_native_xyk_config(WithdrawAmount::All);
+    let nft_addr = MockApi::default().addr_make("positions");
+    // The position belongs to the output account, not to the input account
+    let mut deps = mock_position_nft_deps(&nft_addr, &cfg.output_addr);
+
+    for (position, error) in [
+        (
+            PositionNft::new(nft_addr.to_string(), ""),
+            "Execution error: Invalid position NFT: token id cannot be empty".to_string(),
+        ),
+        (
+            PositionNft::new(nft_addr.to_string(), "1"),
+            format!(
+                "Execution error: Invalid position NFT: token 1 is not owned by {}",
+                cfg.input_addr
+            ),
+        ),
+    ] {
+        let err = process_function(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&Addr::unchecked("processor"), &[]),
+            FunctionMsgs::WithdrawPosition { position },
+            cfg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), error);
+    }
+
+    // The token contract must be a valid address
+    process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::WithdrawPosition {
+            position: PositionNft::new("not a contract", "1"),
+        },
+        cfg,
+    )
+    .unwrap_err();
+}
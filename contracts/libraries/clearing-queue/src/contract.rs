@@ -4,7 +4,7 @@ use cosmwasm_std::entry_point;
 use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 use valence_library_utils::{
     error::LibraryError,
-    msg::{ExecuteMsg, InstantiateMsg},
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg},
 };
 
 use crate::{
@@ -43,6 +43,22 @@ pub fn execute(
     )
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
+    )
+}
+
 mod execute {
     use cosmwasm_std::{DepsMut, Env, MessageInfo};
     use valence_library_utils::error::LibraryError;
@@ -69,7 +85,8 @@ mod functions {
     use crate::{
         msg::{Config, FunctionMsgs},
         state::{
-            ObligationStatus, WithdrawalObligation, CLEARING_QUEUE, OBLIGATION_ID_TO_STATUS_MAP,
+            ObligationStatus, SettlementCadence, WithdrawalObligation, CLEARING_QUEUE,
+            OBLIGATION_ID_TO_STATUS_MAP, SETTLEMENT_CADENCE,
         },
     };
 
@@ -86,7 +103,7 @@ mod functions {
                 payout_amount,
                 id,
             } => try_register_withdraw_obligation(deps, env, cfg, recipient, payout_amount, id),
-            FunctionMsgs::SettleNextObligation {} => try_settle_next_obligation(deps, cfg),
+            FunctionMsgs::SettleNextObligation {} => try_settle_next_obligation(deps, env, cfg),
         }
     }
 
@@ -169,7 +186,11 @@ mod functions {
         Ok(Response::new())
     }
 
-    fn try_settle_next_obligation(deps: DepsMut, cfg: Config) -> Result<Response, LibraryError> {
+    fn try_settle_next_obligation(
+        deps: DepsMut,
+        env: Env,
+        cfg: Config,
+    ) -> Result<Response, LibraryError> {
         // pop the head of the queue (oldest obligation)
         let obligations_head = CLEARING_QUEUE.pop_front(deps.storage)?;
 
@@ -212,16 +233,28 @@ mod functions {
             &ObligationStatus::Processed,
         )?;
 
+        // record the settlement to keep track of the settlement cadence
+        let cadence = match SETTLEMENT_CADENCE.may_load(deps.storage)? {
+            Some(mut cadence) => {
+                cadence.record_settlement(env.block);
+                cadence
+            }
+            None => SettlementCadence::new(env.block),
+        };
+        SETTLEMENT_CADENCE.save(deps.storage, &cadence)?;
+
         Ok(Response::new().add_message(input_account_msg))
     }
 }
 
 mod query {
     use crate::{
-        msg::{ObligationsResponse, QueueInfoResponse},
-        state::CLEARING_QUEUE,
+        msg::{Config, ObligationsResponse, QueueInfoResponse, SettlementEstimate},
+        state::{
+            ObligationStatus, CLEARING_QUEUE, OBLIGATION_ID_TO_STATUS_MAP, SETTLEMENT_CADENCE,
+        },
     };
-    use cosmwasm_std::{Deps, StdResult};
+    use cosmwasm_std::{Deps, Env, StdResult, Uint128};
 
     pub fn get_queue_info(deps: Deps) -> StdResult<QueueInfoResponse> {
         let queue_length = CLEARING_QUEUE.len(deps.storage)?;
@@ -239,10 +272,82 @@ mod query {
 
         Ok(ObligationsResponse { obligations })
     }
+
+    pub fn get_settlement_estimate(
+        deps: Deps,
+        env: Env,
+        request_id: u64,
+    ) -> StdResult<SettlementEstimate> {
+        // only obligations that are still in the queue can be estimated
+        match OBLIGATION_ID_TO_STATUS_MAP.may_load(deps.storage, request_id)? {
+            Some(ObligationStatus::InQueue) => (),
+            _ => return Ok(SettlementEstimate::Unknown),
+        }
+
+        // without any observed settlement interval we have nothing to base the estimate on
+        let cadence = match SETTLEMENT_CADENCE.may_load(deps.storage)? {
+            Some(cadence) => cadence,
+            None => return Ok(SettlementEstimate::Unknown),
+        };
+        let (avg_blocks, avg_seconds) = match cadence.average_interval() {
+            Some(interval) => interval,
+            None => return Ok(SettlementEstimate::Unknown),
+        };
+
+        // find the position of the obligation in the queue, along with
+        // the total amount required to settle the queue up to (and including) it.
+        // the queue is iterated lazily from its head and the iteration stops at the
+        // obligation, so the cost is bounded by its position rather than by the queue length
+        let mut required_amount = Uint128::zero();
+        let mut queue_position = None;
+        for (position, obligation) in CLEARING_QUEUE
+            .iter(deps.storage, cosmwasm_std::Order::Ascending)?
+            .enumerate()
+        {
+            let obligation = obligation?;
+            required_amount = required_amount.checked_add(obligation.payout_coin.amount)?;
+            if obligation.id.u64() == request_id {
+                queue_position = Some(position as u64);
+                break;
+            }
+        }
+        let queue_position = match queue_position {
+            Some(position) => position,
+            None => return Ok(SettlementEstimate::Unknown),
+        };
+
+        // if the settlement account can't cover the queue up to this obligation,
+        // the settlement depends on future inflows which we can't estimate
+        let cfg: Config = valence_library_base::load_config(deps.storage)?;
+        let settlement_acc_bal = deps
+            .querier
+            .query_balance(cfg.settlement_acc_addr.as_str(), cfg.denom)?;
+        if settlement_acc_bal.amount < required_amount {
+            return Ok(SettlementEstimate::Unknown);
+        }
+
+        // project the settlement from the latest one at the average cadence
+        let settlements_needed = queue_position.saturating_add(1);
+        let last_block = cadence.last_settlement_block;
+        let height = last_block
+            .height
+            .saturating_add(avg_blocks.saturating_mul(settlements_needed))
+            .max(env.block.height);
+        let time = last_block
+            .time
+            .plus_seconds(avg_seconds.saturating_mul(settlements_needed))
+            .max(env.block.time);
+
+        Ok(SettlementEstimate::Estimated {
+            queue_position,
+            height,
+            time,
+        })
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Ownership {} => {
             to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
@@ -250,6 +355,21 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetProcessor {} => {
             to_json_binary(&valence_library_base::get_processor(deps.storage)?)
         }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        QueryMsg::IsApproved {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&valence_library_utils::is_library_approved(
+                &deps.querier,
+                &config.settlement_acc_addr,
+                &env.contract.address,
+            )?)
+        }
         QueryMsg::GetLibraryConfig {} => {
             let config: Config = valence_library_base::load_config(deps.storage)?;
             to_json_binary(&config)
@@ -267,5 +387,8 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             let obligation_status = OBLIGATION_ID_TO_STATUS_MAP.load(deps.storage, id)?;
             to_json_binary(&obligation_status)
         }
+        QueryMsg::EstimatedSettlement { request_id } => {
+            to_json_binary(&query::get_settlement_estimate(deps, env, request_id)?)
+        }
     }
 }
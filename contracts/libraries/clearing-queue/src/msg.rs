@@ -1,5 +1,5 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{ensure, Addr, Deps, DepsMut, Uint128, Uint64};
+use cosmwasm_std::{ensure, Addr, Deps, DepsMut, Timestamp, Uint128, Uint64};
 use cw_ownable::cw_ownable_query;
 use valence_library_utils::{
     error::LibraryError, msg::LibraryConfigValidation, LibraryAccountType,
@@ -141,6 +141,11 @@ pub enum QueryMsg {
     /// (if obligation is in the queue then it is not yet settled).
     #[returns(crate::state::ObligationStatus)]
     ObligationStatus { id: u64 },
+    /// coarse estimate of when a pending obligation will be settled.
+    /// estimate is based on the position of the obligation in the queue,
+    /// the settlement account balance, and the historical settlement cadence.
+    #[returns(SettlementEstimate)]
+    EstimatedSettlement { request_id: u64 },
 }
 
 #[cw_serde]
@@ -153,3 +158,21 @@ pub struct QueueInfoResponse {
 pub struct ObligationsResponse {
     pub obligations: Vec<WithdrawalObligation>,
 }
+
+#[cw_serde]
+pub enum SettlementEstimate {
+    /// obligation is expected to be settled around the given block/time
+    Estimated {
+        /// number of obligations ahead of this one in the queue
+        queue_position: u64,
+        /// estimated block height of the settlement
+        height: u64,
+        /// estimated block time of the settlement
+        time: Timestamp,
+    },
+    /// not enough data to produce an estimate. this is the case if
+    /// the obligation is not pending, the settlement account does not
+    /// hold enough funds to settle the queue up to this obligation, or
+    /// no settlement cadence has been observed yet
+    Unknown,
+}
@@ -1,6 +1,6 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, BlockInfo, Coin, Uint64};
-use cw_storage_plus::Map;
+use cw_storage_plus::{Item, Map};
 use valence_processor_utils::queue::QueueMap;
 
 /// map of registered obligation id -> settlement status.
@@ -15,6 +15,10 @@ pub const CLEARING_QUEUE: QueueMap<WithdrawalObligation> = QueueMap::new(
     "clearing_queue_end_index",
 );
 
+/// historical cadence of obligation settlements, used to
+/// estimate when pending obligations will be settled
+pub const SETTLEMENT_CADENCE: Item<SettlementCadence> = Item::new("settlement_cadence");
+
 /// obligation status enum
 #[cw_serde]
 pub enum ObligationStatus {
@@ -35,3 +39,56 @@ pub struct WithdrawalObligation {
     /// block when registration was enqueued
     pub enqueue_block: BlockInfo,
 }
+
+/// running record of the intervals between obligation settlements
+#[cw_serde]
+pub struct SettlementCadence {
+    /// block of the latest settlement
+    pub last_settlement_block: BlockInfo,
+    /// number of intervals observed between consecutive settlements
+    pub intervals: u64,
+    /// total number of blocks elapsed across all observed intervals
+    pub total_blocks: u64,
+    /// total number of seconds elapsed across all observed intervals
+    pub total_seconds: u64,
+}
+
+impl SettlementCadence {
+    pub fn new(block: BlockInfo) -> Self {
+        Self {
+            last_settlement_block: block,
+            intervals: 0,
+            total_blocks: 0,
+            total_seconds: 0,
+        }
+    }
+
+    /// records a new settlement at the given block
+    pub fn record_settlement(&mut self, block: BlockInfo) {
+        self.intervals = self.intervals.saturating_add(1);
+        self.total_blocks = self.total_blocks.saturating_add(
+            block
+                .height
+                .saturating_sub(self.last_settlement_block.height),
+        );
+        self.total_seconds = self.total_seconds.saturating_add(
+            block
+                .time
+                .seconds()
+                .saturating_sub(self.last_settlement_block.time.seconds()),
+        );
+        self.last_settlement_block = block;
+    }
+
+    /// average (blocks, seconds) elapsed between consecutive settlements.
+    /// returns `None` if no interval has been observed yet.
+    pub fn average_interval(&self) -> Option<(u64, u64)> {
+        if self.intervals == 0 {
+            return None;
+        }
+        Some((
+            self.total_blocks / self.intervals,
+            self.total_seconds / self.intervals,
+        ))
+    }
+}
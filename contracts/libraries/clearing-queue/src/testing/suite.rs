@@ -9,7 +9,7 @@ use valence_library_utils::{
 use crate::{
     msg::{
         FunctionMsgs, LibraryConfig, LibraryConfigUpdate, ObligationsResponse, QueryMsg,
-        QueueInfoResponse,
+        QueueInfoResponse, SettlementEstimate,
     },
     state::ObligationStatus,
 };
@@ -112,6 +112,13 @@ impl ClearingQueueTestingSuite {
             &QueryMsg::PendingObligations { from, to },
         )
     }
+
+    pub fn query_settlement_estimate(&self, request_id: u64) -> SettlementEstimate {
+        self.inner.query_wasm(
+            &self.clearing_queue,
+            &QueryMsg::EstimatedSettlement { request_id },
+        )
+    }
 }
 
 impl LibraryTestSuite<Empty, Empty> for ClearingQueueTestingSuite {
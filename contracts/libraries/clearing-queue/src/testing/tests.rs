@@ -1,8 +1,8 @@
 use cosmwasm_std::{coin, Uint128};
-use valence_library_utils::LibraryAccountType;
+use valence_library_utils::{testing::LibraryTestSuite, LibraryAccountType};
 
 use crate::{
-    msg::LibraryConfig,
+    msg::{LibraryConfig, SettlementEstimate},
     state::ObligationStatus,
     testing::{builder::ClearingQueueTestingSuiteBuilder, suite::DENOM_1},
 };
@@ -291,3 +291,96 @@ fn test_registering_obligations_validates_order() {
         .register_new_obligation(suite.user_2.to_string(), 300u128.into(), 3)
         .unwrap();
 }
+
+#[test]
+fn test_settlement_estimate_front_and_back_of_queue() {
+    let mut suite = ClearingQueueTestingSuiteBuilder::default()
+        .with_input_balance(coin(1_000, DENOM_1))
+        .build();
+
+    for id in 1..=5 {
+        suite
+            .register_new_obligation(suite.user_1.to_string(), 100u128.into(), id)
+            .unwrap();
+    }
+
+    // no settlement cadence observed yet, so no estimate can be produced
+    assert_eq!(
+        suite.query_settlement_estimate(1),
+        SettlementEstimate::Unknown
+    );
+
+    // settle two obligations 10 blocks (60 seconds) apart
+    suite.settle_next_obligation().unwrap();
+    suite.app_mut().update_block(|b| {
+        b.height += 10;
+        b.time = b.time.plus_seconds(60);
+    });
+    suite.settle_next_obligation().unwrap();
+
+    let last_settlement = suite.app().block_info();
+
+    // obligation 3 is at the front of the queue and should be settled
+    // one settlement interval after the latest settlement
+    assert_eq!(
+        suite.query_settlement_estimate(3),
+        SettlementEstimate::Estimated {
+            queue_position: 0,
+            height: last_settlement.height + 10,
+            time: last_settlement.time.plus_seconds(60),
+        }
+    );
+
+    // obligation 5 is at the back of the queue, behind two other obligations
+    assert_eq!(
+        suite.query_settlement_estimate(5),
+        SettlementEstimate::Estimated {
+            queue_position: 2,
+            height: last_settlement.height + 30,
+            time: last_settlement.time.plus_seconds(180),
+        }
+    );
+
+    // settled obligations are no longer estimated
+    assert_eq!(
+        suite.query_settlement_estimate(1),
+        SettlementEstimate::Unknown
+    );
+}
+
+#[test]
+fn test_settlement_estimate_unknown_for_underfunded_queue() {
+    let mut suite = ClearingQueueTestingSuiteBuilder::default()
+        .with_input_balance(coin(1_000, DENOM_1))
+        .build();
+
+    suite
+        .register_new_obligation(suite.user_1.to_string(), 100u128.into(), 1)
+        .unwrap();
+    suite
+        .register_new_obligation(suite.user_2.to_string(), 100u128.into(), 2)
+        .unwrap();
+    suite
+        .register_new_obligation(suite.user_3.to_string(), 500u128.into(), 3)
+        .unwrap();
+    suite
+        .register_new_obligation(suite.user_1.to_string(), 500u128.into(), 4)
+        .unwrap();
+
+    suite.settle_next_obligation().unwrap();
+    suite.next_block();
+    suite.settle_next_obligation().unwrap();
+
+    // remaining 800 in the settlement account covers obligation 3 but not obligation 4
+    assert!(matches!(
+        suite.query_settlement_estimate(3),
+        SettlementEstimate::Estimated {
+            queue_position: 0,
+            ..
+        }
+    ));
+    assert_eq!(
+        suite.query_settlement_estimate(4),
+        SettlementEstimate::Unknown
+    );
+}
@@ -0,0 +1,186 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use valence_library_utils::{
+    error::LibraryError,
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg},
+};
+
+use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
+
+// version info for migration info
+const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg<LibraryConfig>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::instantiate(deps, CONTRACT_NAME, CONTRACT_VERSION, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::execute(
+        deps,
+        env,
+        info,
+        msg,
+        functions::process_function,
+        execute::update_config,
+    )
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
+    )
+}
+
+pub(crate) mod functions {
+    use std::collections::BTreeMap;
+
+    use cosmwasm_std::{coins, BankMsg, CosmosMsg, DepsMut, Env, MessageInfo, Response};
+    use valence_library_utils::{error::LibraryError, events::TransferEvent, execute_on_behalf_of};
+
+    use crate::msg::{CheckedDestination, Config, FunctionMsgs};
+
+    pub fn process_function(
+        deps: DepsMut,
+        env: Env,
+        _info: MessageInfo,
+        msg: FunctionMsgs,
+        cfg: Config,
+    ) -> Result<Response, LibraryError> {
+        match msg {
+            FunctionMsgs::Route {} => route(deps, env, &cfg),
+        }
+    }
+
+    fn route(deps: DepsMut, env: Env, cfg: &Config) -> Result<Response, LibraryError> {
+        let mut response = Response::new().add_attribute("method", "route");
+        let mut transfer_msgs = vec![];
+
+        // One transfer per routed denom, each sending the whole balance of the denom to its destination
+        for (denom, destination) in &cfg.routes {
+            let balance = deps.querier.query_balance(&cfg.input_addr, denom)?.amount;
+            let transfer_event = TransferEvent::new("route", denom).with_input_balance(balance);
+
+            if balance.is_zero() {
+                response = response.add_event(transfer_event.skipped("no balance to transfer"));
+                continue;
+            }
+
+            let transfer_msg = match destination {
+                CheckedDestination::Local { receiver } => {
+                    response = response.add_event(transfer_event.with_amount(balance));
+                    CosmosMsg::Bank(BankMsg::Send {
+                        to_address: receiver.to_string(),
+                        amount: coins(balance.u128(), denom),
+                    })
+                }
+                CheckedDestination::Ibc {
+                    channel_id,
+                    receiver,
+                    ibc_transfer_timeout,
+                    ..
+                } => {
+                    response = response.add_event(
+                        transfer_event
+                            .with_amount(balance)
+                            .with_channel_id(channel_id),
+                    );
+                    valence_ibc_utils::generic::ibc_send_message(
+                        env.clone(),
+                        channel_id.clone(),
+                        receiver.clone(),
+                        denom.clone(),
+                        balance.u128(),
+                        "".to_string(),
+                        ibc_transfer_timeout.map(Into::into),
+                        BTreeMap::new(),
+                    )?
+                }
+            };
+            transfer_msgs.push(transfer_msg);
+        }
+
+        // Nothing is sent when none of the routed denoms has a balance
+        if !transfer_msgs.is_empty() {
+            let input_account_msg = execute_on_behalf_of(transfer_msgs, &cfg.input_addr)?;
+            response = response.add_message(input_account_msg);
+        }
+
+        Ok(response)
+    }
+}
+
+mod execute {
+    use cosmwasm_std::{DepsMut, Env, MessageInfo};
+    use valence_library_utils::error::LibraryError;
+
+    use crate::msg::LibraryConfigUpdate;
+
+    pub fn update_config(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        new_config: LibraryConfigUpdate,
+    ) -> Result<(), LibraryError> {
+        new_config.update_config(deps)
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Ownership {} => {
+            to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
+        }
+        QueryMsg::GetProcessor {} => {
+            to_json_binary(&valence_library_base::get_processor(deps.storage)?)
+        }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        QueryMsg::IsApproved {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&valence_library_utils::is_library_approved(
+                &deps.querier,
+                &config.input_addr,
+                &env.contract.address,
+            )?)
+        }
+        QueryMsg::GetLibraryConfig {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&config)
+        }
+        QueryMsg::GetRawLibraryConfig {} => {
+            let raw_config: LibraryConfig =
+                valence_library_utils::raw_config::query_raw_library_config(deps.storage)?;
+            to_json_binary(&raw_config)
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Api, Deps, DepsMut, Uint64};
+use cw_ownable::cw_ownable_query;
+use valence_library_utils::{
+    error::LibraryError, msg::LibraryConfigValidation, LibraryAccountType,
+};
+use valence_macros::{valence_library_query, ValenceLibraryInterface};
+
+#[cw_serde]
+pub enum FunctionMsgs {
+    // Send every nonzero balance of the input account whose denom is routed to the destination of its route
+    Route {},
+}
+
+#[valence_library_query]
+#[cw_ownable_query]
+#[cw_serde]
+#[derive(QueryResponses)]
+/// Enum representing the different query messages that can be sent.
+pub enum QueryMsg {}
+
+#[cw_serde]
+/// Where the balance of a routed denom is sent
+pub enum Destination {
+    // Account on the chain of the library, the balance is sent to it with a bank transfer
+    Local {
+        receiver: LibraryAccountType,
+    },
+    // Account on a remote chain, the balance is sent to it with an IBC transfer over the channel
+    Ibc {
+        // Name of the remote chain, used to identify the destination
+        chain: String,
+        // Transfer channel from the chain of the library to the remote chain
+        channel_id: String,
+        // Address of the receiver on the remote chain
+        receiver: String,
+        // Timeout of the IBC transfer in seconds, the default timeout is used if not set
+        ibc_transfer_timeout: Option<Uint64>,
+    },
+}
+
+impl Destination {
+    pub fn local(receiver: impl Into<LibraryAccountType>) -> Self {
+        Destination::Local {
+            receiver: receiver.into(),
+        }
+    }
+
+    pub fn ibc(
+        chain: impl Into<String>,
+        channel_id: impl Into<String>,
+        receiver: impl Into<String>,
+        ibc_transfer_timeout: Option<Uint64>,
+    ) -> Self {
+        Destination::Ibc {
+            chain: chain.into(),
+            channel_id: channel_id.into(),
+            receiver: receiver.into(),
+            ibc_transfer_timeout,
+        }
+    }
+
+    fn to_checked(&self, denom: &str, api: &dyn Api) -> Result<CheckedDestination, LibraryError> {
+        match self {
+            Destination::Local { receiver } => Ok(CheckedDestination::Local {
+                receiver: receiver.to_addr(api)?,
+            }),
+            Destination::Ibc {
+                chain,
+                channel_id,
+                receiver,
+                ibc_transfer_timeout,
+            } => {
+                for (field, value) in [
+                    ("chain", chain),
+                    ("channel_id", channel_id),
+                    ("receiver", receiver),
+                ] {
+                    if value.is_empty() {
+                        return Err(LibraryError::ConfigurationError(format!(
+                            "Invalid denom router config: {field} of the destination of {denom} cannot be empty."
+                        )));
+                    }
+                }
+                if ibc_transfer_timeout.is_some_and(|timeout| timeout.is_zero()) {
+                    return Err(LibraryError::ConfigurationError(format!(
+                        "Invalid denom router config: ibc_transfer_timeout of the destination of {denom} cannot be zero."
+                    )));
+                }
+
+                // Can't validate the receiver as it's on another chain
+                Ok(CheckedDestination::Ibc {
+                    chain: chain.clone(),
+                    channel_id: channel_id.clone(),
+                    receiver: receiver.clone(),
+                    ibc_transfer_timeout: *ibc_transfer_timeout,
+                })
+            }
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(ValenceLibraryInterface)]
+pub struct LibraryConfig {
+    // Account from which the routed denoms are sent
+    pub input_addr: LibraryAccountType,
+    // Destination of every routed denom, denoms without a route stay on the input account
+    pub routes: BTreeMap<String, Destination>,
+}
+
+impl LibraryConfig {
+    pub fn new(
+        input_addr: impl Into<LibraryAccountType>,
+        routes: BTreeMap<String, Destination>,
+    ) -> Self {
+        LibraryConfig {
+            input_addr: input_addr.into(),
+            routes,
+        }
+    }
+
+    fn do_validate(
+        &self,
+        api: &dyn Api,
+    ) -> Result<(Addr, BTreeMap<String, CheckedDestination>), LibraryError> {
+        let input_addr = self.input_addr.to_addr(api)?;
+        let routes = validate_routes(api, &self.routes)?;
+        ensure_no_route_to_input(&input_addr, &routes)?;
+
+        Ok((input_addr, routes))
+    }
+}
+
+impl LibraryConfigValidation<Config> for LibraryConfig {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pre_validate(&self, api: &dyn Api) -> Result<(), LibraryError> {
+        self.do_validate(api)?;
+        Ok(())
+    }
+
+    fn validate(&self, deps: Deps) -> Result<Config, LibraryError> {
+        let (input_addr, routes) = self.do_validate(deps.api)?;
+
+        Ok(Config { input_addr, routes })
+    }
+}
+
+impl LibraryConfigUpdate {
+    pub fn update_config(self, deps: DepsMut) -> Result<(), LibraryError> {
+        let mut config: Config = valence_library_base::load_config(deps.storage)?;
+
+        if let Some(input_addr) = self.input_addr {
+            config.input_addr = input_addr.to_addr(deps.api)?;
+        }
+
+        if let Some(routes) = self.routes {
+            config.routes = validate_routes(deps.api, &routes)?;
+        }
+
+        // The routes are checked against the input account once both are (possibly) updated
+        ensure_no_route_to_input(&config.input_addr, &config.routes)?;
+
+        valence_library_base::save_config(deps.storage, &config)?;
+        Ok(())
+    }
+}
+
+#[cw_serde]
+/// Validated destination of a routed denom
+pub enum CheckedDestination {
+    Local {
+        receiver: Addr,
+    },
+    Ibc {
+        chain: String,
+        channel_id: String,
+        receiver: String,
+        ibc_transfer_timeout: Option<Uint64>,
+    },
+}
+
+#[cw_serde]
+pub struct Config {
+    pub input_addr: Addr,
+    pub routes: BTreeMap<String, CheckedDestination>,
+}
+
+impl Config {
+    pub fn new(input_addr: Addr, routes: BTreeMap<String, CheckedDestination>) -> Self {
+        Config { input_addr, routes }
+    }
+}
+
+/// Checks that at least one denom is routed, and that every routed denom has a complete destination
+fn validate_routes(
+    api: &dyn Api,
+    routes: &BTreeMap<String, Destination>,
+) -> Result<BTreeMap<String, CheckedDestination>, LibraryError> {
+    if routes.is_empty() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid denom router config: at least one route must be configured.".to_string(),
+        ));
+    }
+
+    routes
+        .iter()
+        .map(|(denom, destination)| {
+            if denom.is_empty() {
+                return Err(LibraryError::ConfigurationError(
+                    "Invalid denom router config: routed denoms cannot be empty.".to_string(),
+                ));
+            }
+            Ok((denom.clone(), destination.to_checked(denom, api)?))
+        })
+        .collect()
+}
+
+fn ensure_no_route_to_input(
+    input_addr: &Addr,
+    routes: &BTreeMap<String, CheckedDestination>,
+) -> Result<(), LibraryError> {
+    for (denom, destination) in routes {
+        if matches!(destination, CheckedDestination::Local { receiver } if receiver == input_addr) {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Invalid denom router config: {denom} is routed to the input account."
+            )));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,401 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{
+    coin, coins, from_json,
+    testing::{message_info, mock_dependencies_with_balances, mock_env, MockApi},
+    Addr, BankMsg, Coin, CosmosMsg, Empty, IbcMsg, IbcTimeout, Uint128, Uint64, WasmMsg,
+};
+use cw_multi_test::{error::AnyResult, App, AppResponse, ContractWrapper, Executor};
+use cw_ownable::Ownership;
+use valence_library_utils::{
+    events::TransferEvent,
+    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation},
+    testing::{LibraryTestSuite, LibraryTestSuiteBase},
+};
+
+use crate::{
+    contract::functions::process_function,
+    msg::{
+        CheckedDestination, Config, Destination, FunctionMsgs, LibraryConfig, LibraryConfigUpdate,
+        QueryMsg,
+    },
+};
+
+const NTRN: &str = "untrn";
+const USDC: &str = "uusdc";
+const ATOM: &str = "uatom";
+const GAIA_CHANNEL: &str = "channel-1";
+const GAIA_RECEIVER: &str = "cosmos1receiver";
+
+struct DenomRouterTestSuite {
+    inner: LibraryTestSuiteBase,
+    denom_router_code_id: u64,
+    input_addr: Addr,
+    output_addr: Addr,
+}
+
+impl Default for DenomRouterTestSuite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DenomRouterTestSuite {
+    pub fn new() -> Self {
+        let mut inner = LibraryTestSuiteBase::new();
+
+        let input_addr = inner.get_contract_addr(inner.account_code_id(), "input_account");
+        let output_addr = inner.get_contract_addr(inner.account_code_id(), "output_account");
+
+        let denom_router_code = ContractWrapper::new(
+            crate::contract::execute,
+            crate::contract::instantiate,
+            crate::contract::query,
+        );
+        let denom_router_code_id = inner.app_mut().store_code(Box::new(denom_router_code));
+
+        Self {
+            inner,
+            denom_router_code_id,
+            input_addr,
+            output_addr,
+        }
+    }
+
+    // Instantiates the library, along with the input account holding `balances` and the output account
+    pub fn denom_router_init(&mut self, cfg: &LibraryConfig, balances: Vec<Coin>) -> Addr {
+        let init_msg = InstantiateMsg {
+            owner: self.owner().to_string(),
+            processor: self.processor().to_string(),
+            config: cfg.clone(),
+        };
+        let addr = self.contract_init(self.denom_router_code_id, "denom_router", &init_msg, &[]);
+
+        let input_addr = self.account_init("input_account", vec![addr.to_string()]);
+        assert_eq!(input_addr, self.input_addr);
+        let output_addr = self.account_init("output_account", vec![]);
+        assert_eq!(output_addr, self.output_addr);
+
+        if !balances.is_empty() {
+            self.init_balance(&input_addr, balances);
+        }
+
+        addr
+    }
+
+    // Routes USDC to the output account
+    fn denom_router_config(&self) -> LibraryConfig {
+        LibraryConfig::new(
+            &self.input_addr,
+            BTreeMap::from([(USDC.to_string(), Destination::local(&self.output_addr))]),
+        )
+    }
+
+    fn execute_route(&mut self, addr: Addr) -> AnyResult<AppResponse> {
+        self.contract_execute(
+            addr,
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::ProcessFunction(
+                FunctionMsgs::Route {},
+            ),
+        )
+    }
+
+    fn update_config(
+        &mut self,
+        addr: Addr,
+        new_config: LibraryConfigUpdate,
+    ) -> AnyResult<AppResponse> {
+        let owner = self.owner().clone();
+        self.app_mut().execute_contract(
+            owner,
+            addr,
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::UpdateConfig { new_config },
+            &[],
+        )
+    }
+}
+
+impl LibraryTestSuite<Empty, Empty> for DenomRouterTestSuite {
+    fn app(&self) -> &App {
+        self.inner.app()
+    }
+
+    fn app_mut(&mut self) -> &mut App {
+        self.inner.app_mut()
+    }
+
+    fn owner(&self) -> &Addr {
+        self.inner.owner()
+    }
+
+    fn processor(&self) -> &Addr {
+        self.inner.processor()
+    }
+
+    fn account_code_id(&self) -> u64 {
+        self.inner.account_code_id()
+    }
+
+    fn cw20_code_id(&self) -> u64 {
+        self.inner.cw20_code_id()
+    }
+}
+
+#[test]
+fn instantiate_with_valid_config() {
+    let mut suite = DenomRouterTestSuite::default();
+    let cfg = suite.denom_router_config();
+    let lib = suite.denom_router_init(&cfg, vec![]);
+
+    let owner_res: Ownership<Addr> = suite.query_wasm(&lib, &QueryMsg::Ownership {});
+    assert_eq!(owner_res.owner, Some(suite.owner().clone()));
+
+    let processor_addr: Addr = suite.query_wasm(&lib, &QueryMsg::GetProcessor {});
+    assert_eq!(processor_addr, suite.processor().clone());
+
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        lib_cfg,
+        Config::new(
+            suite.input_addr.clone(),
+            BTreeMap::from([(
+                USDC.to_string(),
+                CheckedDestination::Local {
+                    receiver: suite.output_addr.clone(),
+                },
+            )]),
+        )
+    );
+}
+
+#[test]
+fn route_sends_each_denom_to_its_destination() {
+    let api = MockApi::default();
+    let input_addr = api.addr_make("input_account");
+    let output_addr = api.addr_make("output_account");
+    let cfg = Config::new(
+        input_addr.clone(),
+        BTreeMap::from([
+            (
+                USDC.to_string(),
+                CheckedDestination::Local {
+                    receiver: output_addr.clone(),
+                },
+            ),
+            (
+                NTRN.to_string(),
+                CheckedDestination::Ibc {
+                    chain: "gaia".to_string(),
+                    channel_id: GAIA_CHANNEL.to_string(),
+                    receiver: GAIA_RECEIVER.to_string(),
+                    ibc_transfer_timeout: Some(Uint64::new(600)),
+                },
+            ),
+            // Routed, but without any balance to send
+            (
+                ATOM.to_string(),
+                CheckedDestination::Local {
+                    receiver: output_addr.clone(),
+                },
+            ),
+        ]),
+    );
+
+    let mut deps = mock_dependencies_with_balances(&[(
+        input_addr.as_str(),
+        &[
+            coin(1_000, USDC),
+            coin(2_000, NTRN),
+            coin(300, "factory/airdrop/token"),
+        ],
+    )]);
+    let env = mock_env();
+    let res = process_function(
+        deps.as_mut(),
+        env.clone(),
+        message_info(&api.addr_make("processor"), &[]),
+        FunctionMsgs::Route {},
+        cfg,
+    )
+    .unwrap();
+
+    // Every transfer is executed by the input account in a single message
+    assert_eq!(res.messages.len(), 1);
+    let CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr, msg, ..
+    }) = &res.messages[0].msg
+    else {
+        panic!("expected the input account to be executed");
+    };
+    assert_eq!(contract_addr, input_addr.as_str());
+    let valence_account_utils::msg::ExecuteMsg::ExecuteMsg { msgs } = from_json(msg).unwrap()
+    else {
+        panic!("expected the transfers to be executed on behalf of the input account");
+    };
+
+    // Routes are processed in denom order, unrouted denoms stay on the input account
+    assert_eq!(
+        msgs,
+        vec![
+            CosmosMsg::Ibc(IbcMsg::Transfer {
+                channel_id: GAIA_CHANNEL.to_string(),
+                to_address: GAIA_RECEIVER.to_string(),
+                amount: coin(2_000, NTRN),
+                timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(600)),
+                memo: Some("".to_string()),
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: output_addr.to_string(),
+                amount: coins(1_000, USDC),
+            }),
+        ]
+    );
+
+    // The empty route is reported as skipped
+    assert!(res.events.contains(
+        &TransferEvent::new("route", ATOM)
+            .with_input_balance(Uint128::zero())
+            .skipped("no balance to transfer")
+            .into()
+    ));
+}
+
+#[test]
+fn route_moves_local_balances_and_skips_empty_routes() {
+    let mut suite = DenomRouterTestSuite::default();
+    let cfg = suite.denom_router_config();
+    let lib = suite.denom_router_init(&cfg, vec![coin(1_000, USDC), coin(2_000, NTRN)]);
+
+    suite.execute_route(lib.clone()).unwrap();
+
+    let input_addr = suite.input_addr.clone();
+    let output_addr = suite.output_addr.clone();
+    suite.assert_balance(&input_addr, 0, USDC);
+    suite.assert_balance(&input_addr, 2_000, NTRN);
+    assert_eq!(
+        suite.query_all_balances(&output_addr),
+        vec![coin(1_000, USDC)]
+    );
+
+    // Without any routed balance, only the library is executed
+    let res = suite.execute_route(lib).unwrap();
+    assert_eq!(
+        res.events
+            .iter()
+            .filter(|event| event.ty == "execute")
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn pre_validate_rejects_incomplete_destinations() {
+    let suite = DenomRouterTestSuite::default();
+
+    for (routes, error) in [
+        (
+            vec![],
+            "Invalid denom router config: at least one route must be configured.",
+        ),
+        (
+            vec![("", Destination::local(&suite.output_addr))],
+            "Invalid denom router config: routed denoms cannot be empty.",
+        ),
+        (
+            vec![(NTRN, Destination::ibc("", GAIA_CHANNEL, GAIA_RECEIVER, None))],
+            "Invalid denom router config: chain of the destination of untrn cannot be empty.",
+        ),
+        (
+            vec![(NTRN, Destination::ibc("gaia", "", GAIA_RECEIVER, None))],
+            "Invalid denom router config: channel_id of the destination of untrn cannot be empty.",
+        ),
+        (
+            vec![(NTRN, Destination::ibc("gaia", GAIA_CHANNEL, "", None))],
+            "Invalid denom router config: receiver of the destination of untrn cannot be empty.",
+        ),
+        (
+            vec![(
+                NTRN,
+                Destination::ibc("gaia", GAIA_CHANNEL, GAIA_RECEIVER, Some(Uint64::zero())),
+            )],
+            "Invalid denom router config: ibc_transfer_timeout of the destination of untrn cannot be zero.",
+        ),
+        (
+            vec![(USDC, Destination::local(&suite.input_addr))],
+            "Invalid denom router config: uusdc is routed to the input account.",
+        ),
+    ] {
+        let routes = routes
+            .into_iter()
+            .map(|(denom, destination)| (denom.to_string(), destination))
+            .collect();
+        let err = LibraryConfig::new(&suite.input_addr, routes)
+            .pre_validate(suite.api())
+            .unwrap_err();
+        assert_eq!(err.to_string(), format!("Configuration error: {error}"));
+    }
+}
+
+#[test]
+fn update_config_validates_routes_and_input() {
+    let mut suite = DenomRouterTestSuite::default();
+    let cfg = suite.denom_router_config();
+    let lib = suite.denom_router_init(&cfg, vec![]);
+
+    let err = suite
+        .update_config(
+            lib.clone(),
+            LibraryConfigUpdate {
+                routes: Some(BTreeMap::from([(
+                    NTRN.to_string(),
+                    Destination::ibc("gaia", "", GAIA_RECEIVER, None),
+                )])),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Configuration error: Invalid denom router config: channel_id of the destination of untrn cannot be empty."
+    );
+
+    // The USDC route already goes to the output account
+    let err = suite
+        .update_config(
+            lib.clone(),
+            LibraryConfigUpdate {
+                input_addr: Some((&suite.output_addr).into()),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Configuration error: Invalid denom router config: uusdc is routed to the input account."
+    );
+
+    let mut routes = cfg.routes.clone();
+    routes.insert(
+        NTRN.to_string(),
+        Destination::ibc("gaia", GAIA_CHANNEL, GAIA_RECEIVER, None),
+    );
+    suite
+        .update_config(
+            lib.clone(),
+            LibraryConfigUpdate {
+                routes: Some(routes),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        lib_cfg.routes.get(NTRN),
+        Some(&CheckedDestination::Ibc {
+            chain: "gaia".to_string(),
+            channel_id: GAIA_CHANNEL.to_string(),
+            receiver: GAIA_RECEIVER.to_string(),
+            ibc_transfer_timeout: None,
+        })
+    );
+}
@@ -134,6 +134,8 @@ fn ensure_correct_pool(
     assets: &AssetData,
     deps: &Deps,
 ) -> Result<(), LibraryError> {
+    assets.ensure_two_assets()?;
+
     // Query the pool configuration
     let pool_config: valence_duality_utils::utils::PoolConfig = deps
         .querier
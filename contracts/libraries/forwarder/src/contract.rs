@@ -1,9 +1,12 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult,
+};
 use valence_library_utils::{
+    balance_snapshot::{reply_balance_snapshots, BALANCE_SNAPSHOT_REPLY_ID},
     error::LibraryError,
-    msg::{ExecuteMsg, InstantiateMsg},
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg},
 };
 
 use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
@@ -39,19 +42,46 @@ pub fn execute(
     )
 }
 
-mod functions {
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
+    )
+}
+
+pub(crate) mod functions {
     use cosmwasm_std::{
-        Addr, CosmosMsg, DepsMut, Env, MessageInfo, QuerierWrapper, Response, StdResult,
+        Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, QuerierWrapper, Response, Storage,
+        Uint128, WasmQuery,
+    };
+    use valence_account_utils::msg::QueryMsg as AccountQueryMsg;
+    use valence_astroport_utils::astroport_native_lp_token::{
+        AssetInfo, PoolQueryMsg, PoolResponse,
+    };
+    use valence_library_utils::{
+        balance_snapshot::with_balance_snapshots, denoms::CheckedDenom, error::LibraryError,
+        execute_on_behalf_of, msg::SimulatedAmount, nft::PositionNft, rounding::split_amount,
     };
-    use valence_library_utils::{error::LibraryError, execute_on_behalf_of};
 
     use crate::{
-        msg::{Config, FunctionMsgs},
+        msg::{
+            CheckedLpRedemptionConfig, Config, EarlyForwardBehavior, FunctionMsgs,
+            TargetAmountSource,
+        },
         state::LAST_SUCCESSFUL_FORWARD,
     };
 
     pub fn process_function(
-        deps: DepsMut,
+        mut deps: DepsMut,
         env: Env,
         _info: MessageInfo,
         msg: FunctionMsgs,
@@ -59,32 +89,166 @@ mod functions {
     ) -> Result<Response, LibraryError> {
         match msg {
             FunctionMsgs::Forward {} => {
-                ensure_forwarding_interval(&cfg, &deps, &env)?;
+                let response = forward(deps.branch(), &env, &cfg)?;
+                if !*cfg.snapshot_balances() {
+                    return Ok(response);
+                }
 
-                // Determine the amount to transfer for each denom
-                let transfer_amounts = prepare_transfer_amounts(&cfg, &deps.querier);
+                // The balances of the inputs and of the outputs receiving the forwarded funds
+                let mut accounts: Vec<&Addr> = std::iter::once(cfg.input_addr())
+                    .chain(cfg.additional_input_addrs())
+                    .collect();
+                if cfg.split_outputs().is_empty() {
+                    accounts.push(cfg.output_addr());
+                } else {
+                    accounts.extend(cfg.split_outputs().iter().map(|output| output.account()));
+                }
+                with_balance_snapshots(&deps.querier, response, &accounts)
+            }
+            FunctionMsgs::ForwardNft { position } => forward_nft(deps, &cfg, position),
+        }
+    }
 
-                // Prepare messages to send the coins to the output account
-                let transfer_messages =
-                    prepare_transfer_messages(transfer_amounts, cfg.output_addr())?;
+    /// Moves a position NFT of the input account to the output account. NFTs can't be split,
+    /// so the split outputs don't apply, and the forwarding constraints only pace fungible forwards
+    fn forward_nft(
+        deps: DepsMut,
+        cfg: &Config,
+        position: PositionNft,
+    ) -> Result<Response, LibraryError> {
+        // Nothing is forwarded while the precondition is not met
+        if !precondition_met(cfg, &deps.querier)? {
+            return Ok(Response::new()
+                .add_attribute("method", "forward_nft")
+                .add_attribute("skipped", "precondition_not_met"));
+        }
+
+        let position = position.to_checked(deps.api, &deps.querier, cfg.input_addr())?;
+        let transfer_msg = position.transfer_msg(cfg.output_addr())?;
+        let input_account_msg = execute_on_behalf_of(vec![transfer_msg], cfg.input_addr())?;
 
-                // Wrap the transfer messages to be executed on behalf of the input account
-                let input_account_msgs = execute_on_behalf_of(transfer_messages, cfg.input_addr())?;
+        Ok(Response::new()
+            .add_attribute("method", "forward_nft")
+            .add_attribute("nft_contract", position.contract_addr)
+            .add_attribute("token_id", position.token_id)
+            .add_message(input_account_msg))
+    }
 
-                // Save last successful forward
-                LAST_SUCCESSFUL_FORWARD.save(deps.storage, &env.block)?;
+    fn forward(deps: DepsMut, env: &Env, cfg: &Config) -> Result<Response, LibraryError> {
+        // Nothing is forwarded while the precondition is not met
+        if !precondition_met(cfg, &deps.querier)? {
+            return Ok(Response::new()
+                .add_attribute("method", "forward")
+                .add_attribute("skipped", "precondition_not_met"));
+        }
 
-                Ok(Response::new()
+        if !forwarding_interval_elapsed(cfg, deps.storage, env)? {
+            return match cfg.forwarding_constraints().early_forward_behavior() {
+                EarlyForwardBehavior::Error => Err(LibraryError::ExecutionError(
+                    "Forwarding constraint not met.".to_string(),
+                )),
+                EarlyForwardBehavior::Skip => Ok(Response::new()
                     .add_attribute("method", "forward")
-                    .add_message(input_account_msgs))
+                    .add_attribute("skipped", "forwarding_constraint_not_met")),
+            };
+        }
+
+        if !cfg.additional_input_addrs().is_empty() {
+            ensure_approved_on_inputs(cfg, &deps.querier, &env.contract.address)?;
+        }
+
+        // Determine the amount to transfer from each input account for each denom
+        let transfer_amounts = prepare_transfer_amounts(cfg, &deps.querier)?;
+
+        // Prepare messages to send the coins of each input account to the output account,
+        // wrapped to be executed on behalf of that input account
+        let mut input_account_msgs = vec![];
+        for (input_addr, amounts) in transfer_amounts {
+            let transfer_messages = prepare_transfer_messages(amounts, cfg)?;
+            if !transfer_messages.is_empty() {
+                input_account_msgs.push(execute_on_behalf_of(transfer_messages, input_addr)?);
             }
         }
+
+        // Nothing to forward, so the last successful forward is left untouched
+        if input_account_msgs.is_empty() {
+            return Ok(Response::new()
+                .add_attribute("method", "forward")
+                .add_attribute("skipped", "nothing_to_forward"));
+        }
+
+        // Save last successful forward
+        LAST_SUCCESSFUL_FORWARD.save(deps.storage, &env.block)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "forward")
+            .add_messages(input_account_msgs))
     }
 
-    // Prepare transfer messages for each denom
+    /// Amount of each configured denom that a forward would move now, summed over the input accounts.
+    /// Fails like the forward would, and reports the same skip reasons when it would be a no-op.
+    pub(crate) fn simulate_amounts(
+        deps: Deps,
+        env: &Env,
+        cfg: &Config,
+    ) -> Result<Vec<SimulatedAmount>, LibraryError> {
+        if !precondition_met(cfg, &deps.querier)? {
+            return Ok(cfg
+                .forwarding_configs()
+                .iter()
+                .map(|fwd_cfg| {
+                    SimulatedAmount::skipped(fwd_cfg.denom().to_string(), "precondition_not_met")
+                })
+                .collect());
+        }
+
+        if !forwarding_interval_elapsed(cfg, deps.storage, env)? {
+            return match cfg.forwarding_constraints().early_forward_behavior() {
+                EarlyForwardBehavior::Error => Err(LibraryError::ExecutionError(
+                    "Forwarding constraint not met.".to_string(),
+                )),
+                EarlyForwardBehavior::Skip => Ok(cfg
+                    .forwarding_configs()
+                    .iter()
+                    .map(|fwd_cfg| {
+                        SimulatedAmount::skipped(
+                            fwd_cfg.denom().to_string(),
+                            "forwarding_constraint_not_met",
+                        )
+                    })
+                    .collect()),
+            };
+        }
+
+        if !cfg.additional_input_addrs().is_empty() {
+            ensure_approved_on_inputs(cfg, &deps.querier, &env.contract.address)?;
+        }
+
+        let transfer_amounts = prepare_transfer_amounts(cfg, &deps.querier)?;
+
+        Ok(cfg
+            .forwarding_configs()
+            .iter()
+            .map(|fwd_cfg| {
+                let amount: Uint128 = transfer_amounts
+                    .iter()
+                    .flat_map(|(_, amounts)| amounts)
+                    .filter(|(_, denom)| denom == fwd_cfg.denom())
+                    .map(|(amount, _)| *amount)
+                    .sum();
+                if amount.is_zero() {
+                    SimulatedAmount::skipped(fwd_cfg.denom().to_string(), "nothing_to_forward")
+                } else {
+                    SimulatedAmount::new(fwd_cfg.denom().to_string(), amount)
+                }
+            })
+            .collect())
+    }
+
+    // Prepare transfer messages for each denom (and each output if splitting)
     fn prepare_transfer_messages<I>(
         coins_to_transfer: I,
-        output_addr: &Addr,
+        cfg: &Config,
     ) -> Result<Vec<CosmosMsg>, LibraryError>
     where
         I: IntoIterator<
@@ -94,60 +258,236 @@ mod functions {
             ),
         >,
     {
-        let transfer_messages = coins_to_transfer
-            .into_iter()
-            .map(|(amount, denom)| denom.get_transfer_to_message(output_addr, amount))
-            .collect::<StdResult<Vec<CosmosMsg>>>()?;
+        let mut transfer_messages = vec![];
+        for (amount, denom) in coins_to_transfer {
+            // The fee is skimmed to the fee account first, only the remainder is sent to the outputs
+            let mut remainder = amount;
+            if let Some(fee_skim) = cfg.fee_skim() {
+                let fee = fee_skim.fee_amount(amount, *cfg.rounding())?;
+                if !fee.is_zero() {
+                    transfer_messages
+                        .push(denom.get_transfer_to_message(fee_skim.fee_account(), fee)?);
+                    remainder -= fee;
+                }
+            }
+            if remainder.is_zero() {
+                continue;
+            }
+
+            for (output_addr, leg_amount) in split_transfer_amount(remainder, cfg)? {
+                transfer_messages.push(denom.get_transfer_to_message(output_addr, leg_amount)?);
+            }
+        }
         Ok(transfer_messages)
     }
 
-    // Prepare transfer amounts for each denom
-    fn prepare_transfer_amounts<C>(
+    // Split the amount to transfer between the outputs according to their ratios.
+    // Each leg is rounded with the configured policy, and the last output receives the remainder so nothing is left behind.
+    fn split_transfer_amount(
+        amount: Uint128,
         cfg: &Config,
+    ) -> Result<Vec<(&Addr, Uint128)>, LibraryError> {
+        let Some((last_output, split_outputs)) = cfg.split_outputs().split_last() else {
+            return Ok(vec![(cfg.output_addr(), amount)]);
+        };
+
+        let ratios: Vec<_> = split_outputs.iter().map(|output| *output.ratio()).collect();
+        let legs = split_amount(amount, &ratios, *cfg.rounding())?;
+        let remainder = legs.iter().fold(amount, |remaining, leg| remaining - leg);
+
+        Ok(split_outputs
+            .iter()
+            .map(|output| output.account())
+            .zip(legs)
+            .chain([(last_output.account(), remainder)])
+            .filter(|(_, leg_amount)| !leg_amount.is_zero())
+            .collect())
+    }
+
+    // Prepare transfer amounts for each input account and denom.
+    // The max amount of each denom (and the LP shares needed for the redemption) caps the total pulled from all the input accounts,
+    // which are drained in order.
+    fn prepare_transfer_amounts<'a, C>(
+        cfg: &'a Config,
         querier: &QuerierWrapper<C>,
-    ) -> Vec<(
-        cosmwasm_std::Uint128,
-        valence_library_utils::denoms::CheckedDenom,
-    )>
+    ) -> Result<Vec<(&'a Addr, Vec<(Uint128, CheckedDenom)>)>, LibraryError>
     where
         C: cosmwasm_std::CustomQuery,
     {
-        cfg.forwarding_configs()
+        // LP shares needed to redeem the target amount of underlying tokens, if configured
+        let lp_redemption_shares = cfg
+            .lp_redemption()
+            .as_ref()
+            .map(|lp_redemption| {
+                query_required_lp_shares(lp_redemption, querier).map(|shares| {
+                    (
+                        CheckedDenom::Native(lp_redemption.lp_denom().clone()),
+                        shares,
+                    )
+                })
+            })
+            .transpose()?;
+
+        // Amount left to transfer for each denom
+        let mut remaining_amounts: Vec<Uint128> = cfg
+            .forwarding_configs()
             .iter()
-            .filter_map(|fwd_cfg| {
-                fwd_cfg
-                    .denom()
-                    .query_balance(querier, cfg.input_addr())
-                    .ok()
-                    .filter(|balance| !balance.is_zero())
-                    .map(|balance| {
-                        // Take minimum of input account balance and configured max amount for denom
-                        let amount = balance.min(*fwd_cfg.max_amount());
-                        (amount, fwd_cfg.denom().clone())
+            .map(|fwd_cfg| {
+                let mut amount = *fwd_cfg.max_amount();
+                // The LP shares are also capped at the amount needed for the redemption
+                if let Some((lp_denom, shares)) = &lp_redemption_shares {
+                    if fwd_cfg.denom() == lp_denom {
+                        amount = amount.min(*shares);
+                    }
+                }
+                amount
+            })
+            .collect();
+
+        Ok(std::iter::once(cfg.input_addr())
+            .chain(cfg.additional_input_addrs())
+            .map(|input_addr| {
+                let amounts = cfg
+                    .forwarding_configs()
+                    .iter()
+                    .zip(remaining_amounts.iter_mut())
+                    .filter_map(|(fwd_cfg, remaining)| {
+                        fwd_cfg
+                            .denom()
+                            .query_balance(querier, input_addr)
+                            .ok()
+                            .filter(|balance| !balance.is_zero())
+                            .map(|balance| {
+                                // The gas reserve is put aside before anything else
+                                let balance = match cfg.gas_reserve() {
+                                    Some(gas_reserve) => {
+                                        gas_reserve.available_balance(fwd_cfg.denom(), balance)
+                                    }
+                                    None => balance,
+                                };
+                                // Take minimum of input account balance and amount left to transfer for denom
+                                let amount = balance.min(*remaining);
+                                *remaining -= amount;
+                                (amount, fwd_cfg.denom().clone())
+                            })
+                            .filter(|(amount, _)| !amount.is_zero())
                     })
+                    .collect::<Vec<_>>();
+                (input_addr, amounts)
+            })
+            .collect())
+    }
+
+    // Ensure the library is approved on every input account, so that a missing approval is reported with the account
+    fn ensure_approved_on_inputs<C>(
+        cfg: &Config,
+        querier: &QuerierWrapper<C>,
+        library_addr: &Addr,
+    ) -> Result<(), LibraryError>
+    where
+        C: cosmwasm_std::CustomQuery,
+    {
+        for input_addr in std::iter::once(cfg.input_addr()).chain(cfg.additional_input_addrs()) {
+            let approved_libraries: Vec<String> =
+                querier.query_wasm_smart(input_addr, &AccountQueryMsg::ListApprovedLibraries {})?;
+            if !approved_libraries.contains(&library_addr.to_string()) {
+                return Err(LibraryError::ExecutionError(format!(
+                    "Library is not approved on input account {input_addr}."
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // Query the target amount and the pool reserves to compute the LP shares needed for the redemption
+    fn query_required_lp_shares<C>(
+        lp_redemption: &CheckedLpRedemptionConfig,
+        querier: &QuerierWrapper<C>,
+    ) -> Result<Uint128, LibraryError>
+    where
+        C: cosmwasm_std::CustomQuery,
+    {
+        let target_amount = match lp_redemption.target_amount() {
+            TargetAmountSource::Fixed(amount) => *amount,
+            TargetAmountSource::Query { contract_addr, msg } => querier.query(
+                &WasmQuery::Smart {
+                    contract_addr: contract_addr.clone(),
+                    msg: msg.clone(),
+                }
+                .into(),
+            )?,
+        };
+
+        let pool: PoolResponse =
+            querier.query_wasm_smart(lp_redemption.pool_addr(), &PoolQueryMsg::Pool {})?;
+        let target_reserve = pool
+            .assets
+            .iter()
+            .find_map(|asset| match &asset.info {
+                AssetInfo::NativeToken { denom } if denom == lp_redemption.target_denom() => {
+                    Some(asset.amount)
+                }
+                _ => None,
             })
-            .collect::<Vec<_>>()
+            .unwrap_or_default();
+
+        required_lp_shares(target_amount, target_reserve, pool.total_share)
+    }
+
+    /// Computes the LP shares that redeem at least `target_amount` of an asset of the pool.
+    /// Rounded up, since the pool rounds the withdrawn assets down.
+    pub(crate) fn required_lp_shares(
+        target_amount: Uint128,
+        target_reserve: Uint128,
+        total_share: Uint128,
+    ) -> Result<Uint128, LibraryError> {
+        if target_amount.is_zero() {
+            return Ok(Uint128::zero());
+        }
+        if target_reserve.is_zero() || total_share.is_zero() {
+            return Err(LibraryError::ExecutionError(
+                "Pool has no reserves of the target denom.".to_string(),
+            ));
+        }
+
+        target_amount
+            .checked_mul_ceil((total_share, target_reserve))
+            .map_err(|e| LibraryError::ExecutionError(e.to_string()))
+    }
+
+    // Check whether the balance precondition is met, forwards being always allowed without one
+    fn precondition_met<C>(cfg: &Config, querier: &QuerierWrapper<C>) -> Result<bool, LibraryError>
+    where
+        C: cosmwasm_std::CustomQuery,
+    {
+        match cfg.precondition() {
+            Some(precondition) => precondition.is_met(querier),
+            None => Ok(true),
+        }
     }
 
-    // Ensure the forwarding interval constraint is met
-    fn ensure_forwarding_interval(
+    // Check whether the forwarding interval constraint is met
+    fn forwarding_interval_elapsed(
         cfg: &Config,
-        deps: &DepsMut<'_>,
+        storage: &dyn Storage,
         env: &Env,
-    ) -> Result<(), LibraryError> {
+    ) -> Result<bool, LibraryError> {
         if let Some(min_interval) = cfg.forwarding_constraints().min_interval() {
-            if let Some(last_successful_forward) = LAST_SUCCESSFUL_FORWARD.may_load(deps.storage)? {
-                if !min_interval
+            if let Some(last_successful_forward) = LAST_SUCCESSFUL_FORWARD.may_load(storage)? {
+                return Ok(min_interval
                     .after(&last_successful_forward)
-                    .is_expired(&env.block)
-                {
-                    return Err(LibraryError::ExecutionError(
-                        "Forwarding constraint not met.".to_string(),
-                    ));
-                }
+                    .is_expired(&env.block));
             }
         };
-        Ok(())
+        Ok(true)
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, LibraryError> {
+    match msg.id {
+        BALANCE_SNAPSHOT_REPLY_ID => reply_balance_snapshots(&deps.querier, msg),
+        _ => Err(LibraryError::Std(StdError::generic_err("unknown reply id"))),
     }
 }
 
@@ -168,7 +508,7 @@ mod execute {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Ownership {} => {
             to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
@@ -176,6 +516,28 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetProcessor {} => {
             to_json_binary(&valence_library_base::get_processor(deps.storage)?)
         }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        QueryMsg::IsApproved {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            // the library pulls from the additional input accounts as well
+            let mut approved = true;
+            for input_addr in
+                std::iter::once(config.input_addr()).chain(config.additional_input_addrs())
+            {
+                approved &= valence_library_utils::is_library_approved(
+                    &deps.querier,
+                    input_addr,
+                    &env.contract.address,
+                )?;
+            }
+            to_json_binary(&approved)
+        }
         QueryMsg::GetLibraryConfig {} => {
             let config: Config = valence_library_base::load_config(deps.storage)?;
             to_json_binary(&config)
@@ -185,5 +547,11 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 valence_library_utils::raw_config::query_raw_library_config(deps.storage)?;
             to_json_binary(&raw_config)
         }
+        QueryMsg::SimulateAmount {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            let simulated_amounts = functions::simulate_amounts(deps, &env, &config)
+                .map_err(|err| StdError::generic_err(err.to_string()))?;
+            to_json_binary(&simulated_amounts)
+        }
     }
 }
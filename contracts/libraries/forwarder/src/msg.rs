@@ -1,5 +1,5 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Deps, DepsMut, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, Deps, DepsMut, Uint128};
 use cw_ownable::cw_ownable_query;
 use cw_utils::Duration;
 use getset::{Getters, Setters};
@@ -7,16 +7,23 @@ use std::collections::HashMap;
 use valence_library_utils::{
     denoms::{CheckedDenom, DenomError, UncheckedDenom},
     error::LibraryError,
-    msg::LibraryConfigValidation,
+    msg::{LibraryConfigValidation, SimulatedAmount},
+    nft::PositionNft,
+    predicate::{BalancePredicate, CheckedBalancePredicate},
+    rounding::RoundingPolicy,
     LibraryAccountType,
 };
 use valence_macros::{valence_library_query, ValenceLibraryInterface};
 
+pub use valence_library_utils::gas_reserve::{GasFeeEstimate, GasReserve};
+
 #[cw_serde]
 /// Enum representing the different function messages that can be sent.
 pub enum FunctionMsgs {
     /// Message to forward tokens.
     Forward {},
+    /// Message to forward a position NFT of the input account to the output account.
+    ForwardNft { position: PositionNft },
 }
 
 #[valence_library_query]
@@ -24,7 +31,11 @@ pub enum FunctionMsgs {
 #[cw_serde]
 #[derive(QueryResponses)]
 /// Enum representing the different query messages that can be sent.
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    /// Returns the amount of each configured denom that a forward would move now, without executing it
+    #[returns(Vec<SimulatedAmount>)]
+    SimulateAmount {},
+}
 
 // Forwarding configuration per denom
 type ForwardingConfigs = Vec<ForwardingConfig>;
@@ -57,14 +68,37 @@ pub struct ForwardingConstraints {
     /// The minimum interval between forwarding operations.
     #[getset(get = "pub", set)]
     min_interval: Option<Duration>,
+    /// What to do when a forward is attempted before the minimum interval has elapsed.
+    #[serde(default)]
+    #[getset(get = "pub", set)]
+    early_forward_behavior: EarlyForwardBehavior,
 }
 
 impl ForwardingConstraints {
     pub fn new(min_interval: Option<Duration>) -> Self {
-        ForwardingConstraints { min_interval }
+        ForwardingConstraints {
+            min_interval,
+            early_forward_behavior: EarlyForwardBehavior::default(),
+        }
+    }
+
+    pub fn with_early_forward_behavior(mut self, behavior: EarlyForwardBehavior) -> Self {
+        self.early_forward_behavior = behavior;
+        self
     }
 }
 
+#[cw_serde]
+#[derive(Default, Copy)]
+/// Enum representing how a forward attempted before the minimum interval has elapsed is handled.
+pub enum EarlyForwardBehavior {
+    /// Reject the forward operation with an error.
+    #[default]
+    Error,
+    /// Treat the forward operation as a no-op.
+    Skip,
+}
+
 #[cw_serde]
 /// Struct representing an unchecked forwarding configuration.
 pub struct UncheckedForwardingConfig {
@@ -83,18 +117,213 @@ impl From<(UncheckedDenom, u128)> for UncheckedForwardingConfig {
     }
 }
 
+#[cw_serde]
+/// Struct representing an output account receiving a share of the forwarded funds.
+pub struct ForwardingOutput {
+    /// The output account.
+    pub account: LibraryAccountType,
+    /// The share of the forwarded amount sent to this account.
+    pub ratio: Decimal,
+}
+
+impl ForwardingOutput {
+    pub fn new(account: impl Into<LibraryAccountType>, ratio: Decimal) -> Self {
+        ForwardingOutput {
+            account: account.into(),
+            ratio,
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Getters, Setters)]
+/// Struct representing a validated output account receiving a share of the forwarded funds.
+pub struct CheckedForwardingOutput {
+    /// The output account.
+    #[getset(get = "pub", set)]
+    account: Addr,
+    /// The share of the forwarded amount sent to this account.
+    #[getset(get = "pub", set)]
+    ratio: Decimal,
+}
+
+#[cw_serde]
+/// Enum representing where the target amount of underlying tokens to redeem comes from.
+pub enum TargetAmountSource {
+    /// A fixed amount of underlying tokens.
+    Fixed(Uint128),
+    /// The amount (`Uint128`) returned by a smart query, e.g. the pending withdrawals of a vault.
+    Query { contract_addr: String, msg: Binary },
+}
+
+#[cw_serde]
+/// Struct representing the configuration to forward only the LP shares needed to redeem a target amount of underlying tokens.
+pub struct LpRedemptionConfig {
+    /// The Astroport pool that issued the LP shares.
+    pub pool_addr: String,
+    /// The LP token denom forwarded, which must be one of the forwarding configs denoms.
+    pub lp_denom: String,
+    /// The underlying denom to be redeemed from the pool.
+    pub target_denom: String,
+    /// Where the target amount of underlying tokens comes from.
+    pub target_amount: TargetAmountSource,
+}
+
+#[cw_serde]
+#[derive(Getters, Setters)]
+/// Struct representing a validated LP redemption configuration.
+pub struct CheckedLpRedemptionConfig {
+    /// The Astroport pool that issued the LP shares.
+    #[getset(get = "pub", set)]
+    pool_addr: Addr,
+    /// The LP token denom forwarded.
+    #[getset(get = "pub", set)]
+    lp_denom: String,
+    /// The underlying denom to be redeemed from the pool.
+    #[getset(get = "pub", set)]
+    target_denom: String,
+    /// Where the target amount of underlying tokens comes from.
+    #[getset(get = "pub", set)]
+    target_amount: TargetAmountSource,
+}
+
+#[cw_serde]
+/// Enum representing the fee skimmed from a forwarded amount, in units of the forwarded denom.
+pub enum Fee {
+    /// A flat amount of tokens.
+    Flat(Uint128),
+    /// A share of the forwarded amount, rounded with the rounding policy of the library.
+    Percentage(Decimal),
+}
+
+#[cw_serde]
+/// Struct representing the fee skimmed to a fee account before the remainder of a forwarded amount is sent to the outputs.
+pub struct FeeSkim {
+    /// The account receiving the fee.
+    pub fee_account: LibraryAccountType,
+    /// The fee skimmed from the amount forwarded from each input account for each denom.
+    pub fee: Fee,
+    /// Optional lower bound of the fee.
+    pub min_fee: Option<Uint128>,
+    /// Optional upper bound of the fee.
+    pub max_fee: Option<Uint128>,
+    /// Forwarded amounts below this threshold are forwarded without skimming a fee.
+    #[serde(default)]
+    pub threshold: Uint128,
+}
+
+impl FeeSkim {
+    pub fn new(fee_account: impl Into<LibraryAccountType>, fee: Fee) -> Self {
+        FeeSkim {
+            fee_account: fee_account.into(),
+            fee,
+            min_fee: None,
+            max_fee: None,
+            threshold: Uint128::zero(),
+        }
+    }
+
+    pub fn with_min_fee(mut self, min_fee: impl Into<Uint128>) -> Self {
+        self.min_fee = Some(min_fee.into());
+        self
+    }
+
+    pub fn with_max_fee(mut self, max_fee: impl Into<Uint128>) -> Self {
+        self.max_fee = Some(max_fee.into());
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: impl Into<Uint128>) -> Self {
+        self.threshold = threshold.into();
+        self
+    }
+}
+
+#[cw_serde]
+#[derive(Getters, Setters)]
+/// Struct representing a validated fee skim.
+pub struct CheckedFeeSkim {
+    /// The account receiving the fee.
+    #[getset(get = "pub", set)]
+    fee_account: Addr,
+    /// The fee skimmed from each forwarded amount.
+    #[getset(get = "pub", set)]
+    fee: Fee,
+    /// The lower bound of the fee.
+    #[getset(get = "pub", set)]
+    min_fee: Option<Uint128>,
+    /// The upper bound of the fee.
+    #[getset(get = "pub", set)]
+    max_fee: Option<Uint128>,
+    /// The forwarded amount below which no fee is skimmed.
+    #[getset(get = "pub", set)]
+    threshold: Uint128,
+}
+
+impl CheckedFeeSkim {
+    /// Returns the fee skimmed from `amount`, bounded by the min and max fees and never more than `amount`.
+    pub fn fee_amount(
+        &self,
+        amount: Uint128,
+        rounding: RoundingPolicy,
+    ) -> Result<Uint128, LibraryError> {
+        if amount < self.threshold {
+            return Ok(Uint128::zero());
+        }
+
+        let mut fee = match self.fee {
+            Fee::Flat(fee) => fee,
+            Fee::Percentage(percentage) => rounding.apply(amount, percentage)?,
+        };
+        if let Some(min_fee) = self.min_fee {
+            fee = fee.max(min_fee);
+        }
+        if let Some(max_fee) = self.max_fee {
+            fee = fee.min(max_fee);
+        }
+
+        Ok(fee.min(amount))
+    }
+}
+
 #[cw_serde]
 #[derive(ValenceLibraryInterface)]
 /// Struct representing the library configuration.
 pub struct LibraryConfig {
     /// The input address for the library.
     pub input_addr: LibraryAccountType,
+    /// Optional input accounts pulled from along with the input address.
+    /// When set, the balances of all the input accounts are forwarded to the output.
+    pub additional_input_addrs: Option<Vec<LibraryAccountType>>,
     /// The output address for the library.
     pub output_addr: LibraryAccountType,
     /// The forwarding configurations for the library.
     pub forwarding_configs: Vec<UncheckedForwardingConfig>,
     /// The forwarding constraints for the library.
     pub forwarding_constraints: ForwardingConstraints,
+    /// Optional output accounts to split the forwarded funds between.
+    /// When set, the funds are sent to these accounts instead of the output address.
+    pub split_outputs: Option<Vec<ForwardingOutput>>,
+    /// How the amounts sent to the split outputs are rounded, down by default.
+    #[serde(default)]
+    pub rounding: RoundingPolicy,
+    /// Optional LP redemption configuration.
+    /// When set, the LP shares forwarded are computed from the pool reserves and the target amount of underlying tokens.
+    pub lp_redemption: Option<LpRedemptionConfig>,
+    /// Optional gas reserve.
+    /// When set, the reserve is never forwarded if the forwarded denom is the gas denom.
+    pub gas_reserve: Option<GasReserve>,
+    /// Whether the balances of the input and output accounts are emitted before and after every forward, off by default.
+    #[serde(default)]
+    pub snapshot_balances: bool,
+    /// Optional precondition on the balance of an account.
+    /// When set, forwards are no-ops while the predicate is not met.
+    #[serde(default)]
+    pub precondition: Option<BalancePredicate>,
+    /// Optional fee skimmed to a fee account.
+    /// When set, the fee is sent to the fee account first and only the remainder of every forwarded amount is sent to the outputs.
+    #[serde(default)]
+    pub fee_skim: Option<FeeSkim>,
 }
 
 impl LibraryConfig {
@@ -106,18 +335,141 @@ impl LibraryConfig {
     ) -> Self {
         LibraryConfig {
             input_addr: input_addr.into(),
+            additional_input_addrs: None,
             output_addr: output_addr.into(),
             forwarding_configs,
             forwarding_constraints,
+            split_outputs: None,
+            rounding: RoundingPolicy::default(),
+            lp_redemption: None,
+            gas_reserve: None,
+            snapshot_balances: false,
+            precondition: None,
+            fee_skim: None,
         }
     }
 
-    fn do_validate(&self, api: &dyn cosmwasm_std::Api) -> Result<(Addr, Addr), LibraryError> {
+    pub fn with_additional_input_addrs(
+        mut self,
+        additional_input_addrs: Vec<LibraryAccountType>,
+    ) -> Self {
+        self.additional_input_addrs = Some(additional_input_addrs);
+        self
+    }
+
+    pub fn with_split_outputs(mut self, split_outputs: Vec<ForwardingOutput>) -> Self {
+        self.split_outputs = Some(split_outputs);
+        self
+    }
+
+    pub fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    pub fn with_lp_redemption(mut self, lp_redemption: LpRedemptionConfig) -> Self {
+        self.lp_redemption = Some(lp_redemption);
+        self
+    }
+
+    pub fn with_gas_reserve(mut self, gas_reserve: GasReserve) -> Self {
+        self.gas_reserve = Some(gas_reserve);
+        self
+    }
+
+    pub fn with_snapshot_balances(mut self, snapshot_balances: bool) -> Self {
+        self.snapshot_balances = snapshot_balances;
+        self
+    }
+
+    pub fn with_precondition(mut self, precondition: BalancePredicate) -> Self {
+        self.precondition = Some(precondition);
+        self
+    }
+
+    pub fn with_fee_skim(mut self, fee_skim: FeeSkim) -> Self {
+        self.fee_skim = Some(fee_skim);
+        self
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn do_validate(
+        &self,
+        api: &dyn cosmwasm_std::Api,
+    ) -> Result<
+        (
+            Addr,
+            Vec<Addr>,
+            Addr,
+            Vec<CheckedForwardingOutput>,
+            Option<CheckedLpRedemptionConfig>,
+            Option<CheckedBalancePredicate>,
+            Option<CheckedFeeSkim>,
+        ),
+        LibraryError,
+    > {
         let input_addr = self.input_addr.to_addr(api)?;
+        // Ensure the additional input accounts are distinct from each other and from the input address
+        let additional_input_addrs = match &self.additional_input_addrs {
+            Some(additional_input_addrs) => {
+                convert_to_checked_inputs(&input_addr, additional_input_addrs, api)?
+            }
+            None => vec![],
+        };
         let output_addr = self.output_addr.to_addr(api)?;
         // Ensure denoms are unique in forwarding configs
         ensure_denom_uniqueness(&self.forwarding_configs)?;
-        Ok((input_addr, output_addr))
+        // Ensure split outputs are distinct and their ratios add up to 1
+        let split_outputs = match &self.split_outputs {
+            Some(split_outputs) => convert_to_checked_outputs(split_outputs, api)?,
+            None => vec![],
+        };
+        // Ensure the LP redemption targets a forwarded denom
+        let lp_redemption = match &self.lp_redemption {
+            Some(lp_redemption) => {
+                let lp_denom = UncheckedDenom::Native(lp_redemption.lp_denom.clone());
+                ensure_lp_denom_forwarded(
+                    &lp_redemption.lp_denom,
+                    self.forwarding_configs
+                        .iter()
+                        .any(|cfg| cfg.denom == lp_denom),
+                )?;
+                Some(convert_to_checked_lp_redemption(lp_redemption, api)?)
+            }
+            None => None,
+        };
+        if let Some(gas_reserve) = &self.gas_reserve {
+            gas_reserve.validate()?;
+        }
+        let precondition = self
+            .precondition
+            .as_ref()
+            .map(|precondition| precondition.to_checked(api))
+            .transpose()?;
+        // Ensure the fee account is distinct from the input and output accounts
+        let fee_skim = match &self.fee_skim {
+            Some(fee_skim) => {
+                let fee_skim = convert_to_checked_fee_skim(fee_skim, api)?;
+                ensure_fee_account_distinct(
+                    fee_skim.fee_account(),
+                    &input_addr,
+                    &additional_input_addrs,
+                    &output_addr,
+                    &split_outputs,
+                )?;
+                Some(fee_skim)
+            }
+            None => None,
+        };
+        Ok((
+            input_addr,
+            additional_input_addrs,
+            output_addr,
+            split_outputs,
+            lp_redemption,
+            precondition,
+            fee_skim,
+        ))
     }
 }
 
@@ -129,7 +481,15 @@ impl LibraryConfigValidation<Config> for LibraryConfig {
     }
 
     fn validate(&self, deps: Deps) -> Result<Config, LibraryError> {
-        let (input_addr, output_addr) = self.do_validate(deps.api)?;
+        let (
+            input_addr,
+            additional_input_addrs,
+            output_addr,
+            split_outputs,
+            lp_redemption,
+            precondition,
+            fee_skim,
+        ) = self.do_validate(deps.api)?;
 
         // Convert the unchecked denoms to checked denoms
         let checked_fwd_configs = convert_to_checked_configs(&self.forwarding_configs, deps)?;
@@ -139,7 +499,15 @@ impl LibraryConfigValidation<Config> for LibraryConfig {
             output_addr,
             checked_fwd_configs,
             self.forwarding_constraints.clone(),
-        ))
+        )
+        .with_additional_input_addrs(additional_input_addrs)
+        .with_split_outputs(split_outputs)
+        .with_rounding(self.rounding)
+        .with_lp_redemption(lp_redemption)
+        .with_gas_reserve(self.gas_reserve.clone())
+        .with_snapshot_balances(self.snapshot_balances)
+        .with_precondition(precondition)
+        .with_fee_skim(fee_skim))
     }
 }
 
@@ -161,6 +529,178 @@ fn ensure_denom_uniqueness(
     Ok(())
 }
 
+/// Validate the additional input accounts and convert them to checked addresses
+fn convert_to_checked_inputs(
+    input_addr: &Addr,
+    additional_input_addrs: &[LibraryAccountType],
+    api: &dyn cosmwasm_std::Api,
+) -> Result<Vec<Addr>, LibraryError> {
+    if additional_input_addrs.is_empty() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid forwarding config: additional input accounts cannot be empty.".to_string(),
+        ));
+    }
+
+    let mut checked_inputs: Vec<Addr> = Vec::with_capacity(additional_input_addrs.len());
+    for account in additional_input_addrs {
+        let account = account.to_addr(api)?;
+        if account == *input_addr || checked_inputs.contains(&account) {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Duplicate input account '{account}' in forwarding config."
+            )));
+        }
+        checked_inputs.push(account);
+    }
+
+    Ok(checked_inputs)
+}
+
+/// Validate the split outputs and convert them to checked outputs
+fn convert_to_checked_outputs(
+    split_outputs: &[ForwardingOutput],
+    api: &dyn cosmwasm_std::Api,
+) -> Result<Vec<CheckedForwardingOutput>, LibraryError> {
+    if split_outputs.is_empty() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid forwarding config: split outputs cannot be empty.".to_string(),
+        ));
+    }
+
+    let mut checked_outputs: Vec<CheckedForwardingOutput> = Vec::with_capacity(split_outputs.len());
+    let mut ratio_sum = Decimal::zero();
+    for output in split_outputs {
+        let account = output.account.to_addr(api)?;
+        if checked_outputs
+            .iter()
+            .any(|checked| checked.account == account)
+        {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Duplicate output account '{account}' in split outputs."
+            )));
+        }
+        if output.ratio.is_zero() {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid forwarding config: split output ratio cannot be zero.".to_string(),
+            ));
+        }
+        ratio_sum = ratio_sum
+            .checked_add(output.ratio)
+            .map_err(|err| LibraryError::ConfigurationError(err.to_string()))?;
+        checked_outputs.push(CheckedForwardingOutput {
+            account,
+            ratio: output.ratio,
+        });
+    }
+
+    if ratio_sum != Decimal::one() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid forwarding config: sum of split output ratios is not equal to 1.".to_string(),
+        ));
+    }
+
+    Ok(checked_outputs)
+}
+
+/// Ensure the LP denom of the redemption config is forwarded
+fn ensure_lp_denom_forwarded(lp_denom: &str, is_forwarded: bool) -> Result<(), LibraryError> {
+    if !is_forwarded {
+        return Err(LibraryError::ConfigurationError(format!(
+            "Invalid LP redemption config: LP denom '{lp_denom}' is not in the forwarding configs."
+        )));
+    }
+    Ok(())
+}
+
+/// Validate the LP redemption config and convert it to a checked config
+fn convert_to_checked_lp_redemption(
+    lp_redemption: &LpRedemptionConfig,
+    api: &dyn cosmwasm_std::Api,
+) -> Result<CheckedLpRedemptionConfig, LibraryError> {
+    let pool_addr = api.addr_validate(&lp_redemption.pool_addr)?;
+
+    if lp_redemption.target_denom.is_empty() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid LP redemption config: target denom cannot be empty.".to_string(),
+        ));
+    }
+
+    match &lp_redemption.target_amount {
+        TargetAmountSource::Fixed(amount) if amount.is_zero() => {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid LP redemption config: target amount cannot be zero.".to_string(),
+            ));
+        }
+        TargetAmountSource::Query { contract_addr, .. } => {
+            api.addr_validate(contract_addr)?;
+        }
+        _ => {}
+    }
+
+    Ok(CheckedLpRedemptionConfig {
+        pool_addr,
+        lp_denom: lp_redemption.lp_denom.clone(),
+        target_denom: lp_redemption.target_denom.clone(),
+        target_amount: lp_redemption.target_amount.clone(),
+    })
+}
+
+/// Validate the fee skim and convert it to a checked fee skim
+fn convert_to_checked_fee_skim(
+    fee_skim: &FeeSkim,
+    api: &dyn cosmwasm_std::Api,
+) -> Result<CheckedFeeSkim, LibraryError> {
+    match fee_skim.fee {
+        Fee::Flat(fee) if fee.is_zero() => {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid fee skim: flat fee cannot be zero.".to_string(),
+            ));
+        }
+        Fee::Percentage(percentage) if percentage.is_zero() || percentage >= Decimal::one() => {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid fee skim: fee percentage must be between 0 and 1 (exclusive).".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    if let (Some(min_fee), Some(max_fee)) = (fee_skim.min_fee, fee_skim.max_fee) {
+        if min_fee > max_fee {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid fee skim: min fee cannot be greater than max fee.".to_string(),
+            ));
+        }
+    }
+
+    Ok(CheckedFeeSkim {
+        fee_account: fee_skim.fee_account.to_addr(api)?,
+        fee: fee_skim.fee.clone(),
+        min_fee: fee_skim.min_fee,
+        max_fee: fee_skim.max_fee,
+        threshold: fee_skim.threshold,
+    })
+}
+
+/// Ensure the fee account is none of the input and output accounts
+fn ensure_fee_account_distinct(
+    fee_account: &Addr,
+    input_addr: &Addr,
+    additional_input_addrs: &[Addr],
+    output_addr: &Addr,
+    split_outputs: &[CheckedForwardingOutput],
+) -> Result<(), LibraryError> {
+    let is_input = fee_account == input_addr || additional_input_addrs.contains(fee_account);
+    let is_output = fee_account == output_addr
+        || split_outputs
+            .iter()
+            .any(|output| output.account() == fee_account);
+    if is_input || is_output {
+        return Err(LibraryError::ConfigurationError(format!(
+            "Invalid fee skim: fee account '{fee_account}' cannot be an input or output account."
+        )));
+    }
+    Ok(())
+}
+
 fn convert_to_checked_configs(
     fwd_configs: &[UncheckedForwardingConfig],
     deps: Deps<'_>,
@@ -188,6 +728,23 @@ impl LibraryConfigUpdate {
             config.input_addr = input_addr.to_addr(deps.api)?;
         }
 
+        if let OptionUpdate::Set(additional_input_addrs) = self.additional_input_addrs {
+            config.additional_input_addrs = additional_input_addrs
+                .map(|additional_input_addrs| {
+                    convert_to_checked_inputs(&config.input_addr, &additional_input_addrs, deps.api)
+                })
+                .transpose()?
+                .unwrap_or_default();
+        }
+
+        // The input address might have been updated to one of the additional input accounts
+        if config.additional_input_addrs.contains(&config.input_addr) {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Duplicate input account '{}' in forwarding config.",
+                config.input_addr
+            )));
+        }
+
         if let Some(output_addr) = self.output_addr {
             config.output_addr = output_addr.to_addr(deps.api)?;
         }
@@ -207,6 +764,69 @@ impl LibraryConfigUpdate {
             config.forwarding_constraints = forwarding_constraints;
         }
 
+        if let OptionUpdate::Set(split_outputs) = self.split_outputs {
+            config.split_outputs = match split_outputs {
+                Some(split_outputs) => convert_to_checked_outputs(&split_outputs, deps.api)?,
+                None => vec![],
+            };
+        }
+
+        if let Some(rounding) = self.rounding {
+            config.rounding = rounding;
+        }
+
+        if let OptionUpdate::Set(lp_redemption) = self.lp_redemption {
+            config.lp_redemption = lp_redemption
+                .map(|lp_redemption| convert_to_checked_lp_redemption(&lp_redemption, deps.api))
+                .transpose()?;
+        }
+
+        if let OptionUpdate::Set(gas_reserve) = self.gas_reserve {
+            if let Some(gas_reserve) = &gas_reserve {
+                gas_reserve.validate()?;
+            }
+            config.gas_reserve = gas_reserve;
+        }
+
+        if let Some(snapshot_balances) = self.snapshot_balances {
+            config.snapshot_balances = snapshot_balances;
+        }
+
+        if let OptionUpdate::Set(precondition) = self.precondition {
+            config.precondition = precondition
+                .map(|precondition| precondition.to_checked(deps.api))
+                .transpose()?;
+        }
+
+        if let OptionUpdate::Set(fee_skim) = self.fee_skim {
+            config.fee_skim = fee_skim
+                .map(|fee_skim| convert_to_checked_fee_skim(&fee_skim, deps.api))
+                .transpose()?;
+        }
+
+        // The fee account must still be distinct from the (possibly updated) input and output accounts
+        if let Some(fee_skim) = &config.fee_skim {
+            ensure_fee_account_distinct(
+                fee_skim.fee_account(),
+                &config.input_addr,
+                &config.additional_input_addrs,
+                &config.output_addr,
+                &config.split_outputs,
+            )?;
+        }
+
+        // The LP denom must still be forwarded with the (possibly updated) forwarding configs
+        if let Some(lp_redemption) = &config.lp_redemption {
+            let lp_denom = CheckedDenom::Native(lp_redemption.lp_denom.clone());
+            ensure_lp_denom_forwarded(
+                &lp_redemption.lp_denom,
+                config
+                    .forwarding_configs
+                    .iter()
+                    .any(|cfg| cfg.denom == lp_denom),
+            )?;
+        }
+
         valence_library_base::save_config(deps.storage, &config)?;
 
         Ok(())
@@ -220,6 +840,10 @@ pub struct Config {
     /// The input address for the library.
     #[getset(get = "pub", set)]
     input_addr: Addr,
+    /// The additional input accounts pulled from along with the input address (empty if there is a single input).
+    #[serde(default)]
+    #[getset(get = "pub", set)]
+    additional_input_addrs: Vec<Addr>,
     /// The output address for the library.
     #[getset(get = "pub", set)]
     output_addr: Addr,
@@ -229,6 +853,34 @@ pub struct Config {
     /// The forwarding constraints for the library.
     #[getset(get = "pub", set)]
     forwarding_constraints: ForwardingConstraints,
+    /// The output accounts to split the forwarded funds between (empty if not splitting).
+    #[serde(default)]
+    #[getset(get = "pub", set)]
+    split_outputs: Vec<CheckedForwardingOutput>,
+    /// How the amounts sent to the split outputs are rounded.
+    #[serde(default)]
+    #[getset(get = "pub", set)]
+    rounding: RoundingPolicy,
+    /// The LP redemption configuration, if the forwarded LP shares are computed from a target amount of underlying tokens.
+    #[serde(default)]
+    #[getset(get = "pub", set)]
+    lp_redemption: Option<CheckedLpRedemptionConfig>,
+    /// The gas reserve, if the gas denom must never be entirely forwarded.
+    #[serde(default)]
+    #[getset(get = "pub", set)]
+    gas_reserve: Option<GasReserve>,
+    /// Whether the balances of the input and output accounts are emitted before and after every forward.
+    #[serde(default)]
+    #[getset(get = "pub", set)]
+    snapshot_balances: bool,
+    /// The precondition on the balance of an account, if forwards are gated on it.
+    #[serde(default)]
+    #[getset(get = "pub", set)]
+    precondition: Option<CheckedBalancePredicate>,
+    /// The fee skimmed to a fee account before forwarding, if any.
+    #[serde(default)]
+    #[getset(get = "pub", set)]
+    fee_skim: Option<CheckedFeeSkim>,
 }
 
 impl Config {
@@ -240,9 +892,57 @@ impl Config {
     ) -> Self {
         Config {
             input_addr,
+            additional_input_addrs: vec![],
             output_addr,
             forwarding_configs,
             forwarding_constraints,
+            split_outputs: vec![],
+            rounding: RoundingPolicy::default(),
+            lp_redemption: None,
+            gas_reserve: None,
+            snapshot_balances: false,
+            precondition: None,
+            fee_skim: None,
         }
     }
+
+    pub fn with_additional_input_addrs(mut self, additional_input_addrs: Vec<Addr>) -> Self {
+        self.additional_input_addrs = additional_input_addrs;
+        self
+    }
+
+    pub fn with_split_outputs(mut self, split_outputs: Vec<CheckedForwardingOutput>) -> Self {
+        self.split_outputs = split_outputs;
+        self
+    }
+
+    pub fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    pub fn with_lp_redemption(mut self, lp_redemption: Option<CheckedLpRedemptionConfig>) -> Self {
+        self.lp_redemption = lp_redemption;
+        self
+    }
+
+    pub fn with_gas_reserve(mut self, gas_reserve: Option<GasReserve>) -> Self {
+        self.gas_reserve = gas_reserve;
+        self
+    }
+
+    pub fn with_snapshot_balances(mut self, snapshot_balances: bool) -> Self {
+        self.snapshot_balances = snapshot_balances;
+        self
+    }
+
+    pub fn with_precondition(mut self, precondition: Option<CheckedBalancePredicate>) -> Self {
+        self.precondition = precondition;
+        self
+    }
+
+    pub fn with_fee_skim(mut self, fee_skim: Option<CheckedFeeSkim>) -> Self {
+        self.fee_skim = fee_skim;
+        self
+    }
 }
@@ -1,17 +1,138 @@
-use crate::msg::{Config, ForwardingConstraints, FunctionMsgs, LibraryConfig, QueryMsg};
-use cosmwasm_std::{coin, Addr, Coin, Empty, Uint128};
+use crate::{
+    contract::functions::required_lp_shares,
+    msg::{
+        Config, EarlyForwardBehavior, Fee, FeeSkim, ForwardingConstraints, ForwardingOutput,
+        FunctionMsgs, GasReserve, LibraryConfig, LibraryConfigUpdate, LpRedemptionConfig, QueryMsg,
+        TargetAmountSource,
+    },
+};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    coin, to_json_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128,
+};
 use cw20::Cw20Coin;
+use cw721::msg::OwnerOfResponse;
 use cw_multi_test::{error::AnyResult, App, AppResponse, ContractWrapper, Executor};
 use cw_ownable::Ownership;
 use cw_utils::Duration;
 use getset::{Getters, Setters};
+use valence_astroport_utils::astroport_native_lp_token::{
+    Asset, AssetInfo, PoolQueryMsg, PoolResponse,
+};
 use valence_library_utils::{
     denoms::{CheckedDenom, UncheckedDenom},
-    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation},
+    events::{ACCOUNT_KEY, BALANCES_KEY, BALANCE_SNAPSHOT_EVENT_TYPE, STAGE_KEY},
+    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation, SimulatedAmount},
+    nft::PositionNft,
+    predicate::{BalancePredicate, Comparator},
+    rounding::RoundingPolicy,
     testing::{LibraryTestSuite, LibraryTestSuiteBase},
+    OptionUpdate,
 };
 
 const NTRN: &str = "untrn";
+const USDC: &str = "uusdc";
+const LP_TOKEN: &str = "factory/pool/astroport/share";
+
+// Reserves of the mocked Astroport pool: 1 share redeems 2 USDC
+const POOL_USDC_RESERVE: u128 = 1_000_000;
+const POOL_NTRN_RESERVE: u128 = 4_000_000;
+const POOL_TOTAL_SHARE: u128 = 500_000;
+
+fn mock_pool_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::default())
+}
+
+fn mock_pool_execute(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::default())
+}
+
+// Positions NFT contract holding the single token "1", owned by the address it is instantiated with
+#[cw_serde]
+enum MockNftExecuteMsg {
+    TransferNft { recipient: String, token_id: String },
+}
+
+#[cw_serde]
+enum MockNftQueryMsg {
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+}
+
+const MOCK_NFT_OWNER_KEY: &[u8] = b"owner";
+
+fn mock_nft_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    owner: String,
+) -> StdResult<Response> {
+    deps.storage.set(MOCK_NFT_OWNER_KEY, owner.as_bytes());
+    Ok(Response::default())
+}
+
+fn mock_nft_execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: MockNftExecuteMsg,
+) -> StdResult<Response> {
+    let MockNftExecuteMsg::TransferNft { recipient, .. } = msg;
+    let owner = deps.storage.get(MOCK_NFT_OWNER_KEY).unwrap();
+    if owner != info.sender.as_bytes() {
+        return Err(StdError::generic_err("Not the owner of the NFT"));
+    }
+    deps.storage.set(MOCK_NFT_OWNER_KEY, recipient.as_bytes());
+    Ok(Response::default())
+}
+
+fn mock_nft_query(deps: Deps, _env: Env, msg: MockNftQueryMsg) -> StdResult<Binary> {
+    let MockNftQueryMsg::OwnerOf { token_id, .. } = msg;
+    if token_id != "1" {
+        return Err(StdError::not_found("token"));
+    }
+    let owner = deps.storage.get(MOCK_NFT_OWNER_KEY).unwrap();
+    to_json_binary(&OwnerOfResponse {
+        owner: String::from_utf8(owner).unwrap(),
+        approvals: vec![],
+    })
+}
+
+fn mock_pool_query(_deps: Deps, _env: Env, msg: PoolQueryMsg) -> StdResult<Binary> {
+    match msg {
+        PoolQueryMsg::Pool {} => to_json_binary(&PoolResponse {
+            assets: vec![
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: NTRN.to_string(),
+                    },
+                    amount: Uint128::new(POOL_NTRN_RESERVE),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: USDC.to_string(),
+                    },
+                    amount: Uint128::new(POOL_USDC_RESERVE),
+                },
+            ],
+            total_share: Uint128::new(POOL_TOTAL_SHARE),
+        }),
+        _ => unimplemented!(),
+    }
+}
 
 #[derive(Getters, Setters)]
 struct ForwarderTestSuite {
@@ -46,7 +167,8 @@ impl ForwarderTestSuite {
             crate::contract::execute,
             crate::contract::instantiate,
             crate::contract::query,
-        );
+        )
+        .with_reply(crate::contract::reply);
 
         let forwarder_code_id = inner.app_mut().store_code(Box::new(forwarder_code));
 
@@ -97,6 +219,40 @@ impl ForwarderTestSuite {
         )
     }
 
+    fn mock_pool_init(&mut self) -> Addr {
+        let mock_pool_code =
+            ContractWrapper::new(mock_pool_execute, mock_pool_instantiate, mock_pool_query);
+        let mock_pool_code_id = self.app_mut().store_code(Box::new(mock_pool_code));
+
+        self.contract_init(mock_pool_code_id, "mock_pool", &Empty {}, &[])
+    }
+
+    fn mock_nft_init(&mut self, owner: &Addr) -> Addr {
+        let mock_nft_code =
+            ContractWrapper::new(mock_nft_execute, mock_nft_instantiate, mock_nft_query);
+        let mock_nft_code_id = self.app_mut().store_code(Box::new(mock_nft_code));
+
+        self.contract_init(mock_nft_code_id, "mock_nft", &owner.to_string(), &[])
+    }
+
+    fn nft_owner(&self, nft_addr: &Addr) -> String {
+        let owner: OwnerOfResponse = self.query_wasm(
+            nft_addr,
+            &MockNftQueryMsg::OwnerOf {
+                token_id: "1".to_string(),
+                include_expired: None,
+            },
+        );
+        owner.owner
+    }
+
+    fn execute_forward_nft(&mut self, addr: Addr, position: PositionNft) -> AnyResult<AppResponse> {
+        self.contract_execute(
+            addr,
+            &ExecuteMsg::<_, LibraryConfig>::ProcessFunction(FunctionMsgs::ForwardNft { position }),
+        )
+    }
+
     fn execute_forward(&mut self, addr: Addr) -> AnyResult<AppResponse> {
         self.contract_execute(
             addr,
@@ -109,7 +265,9 @@ impl ForwarderTestSuite {
         self.app_mut().execute_contract(
             owner,
             addr,
-            &ExecuteMsg::<FunctionMsgs, LibraryConfig>::UpdateConfig { new_config },
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::UpdateConfig {
+                new_config: new_config.into_update(),
+            },
             &[],
         )
     }
@@ -259,6 +417,74 @@ fn forward_native_token_full_amount() {
     assert_eq!(output_balance, coin(1_000_000_000_000, NTRN));
 }
 
+// (stage, account, balances) of the balance snapshots emitted by the forward, in order
+fn balance_snapshots(res: &AppResponse) -> Vec<(String, String, String)> {
+    res.events
+        .iter()
+        .filter(|event| event.ty == format!("wasm-{BALANCE_SNAPSHOT_EVENT_TYPE}"))
+        .map(|event| {
+            let attr = |key: &str| {
+                event
+                    .attributes
+                    .iter()
+                    .find(|attr| attr.key == key)
+                    .map(|attr| attr.value.clone())
+                    .unwrap()
+            };
+            (attr(STAGE_KEY), attr(ACCOUNT_KEY), attr(BALANCES_KEY))
+        })
+        .collect()
+}
+
+#[test]
+fn forward_snapshots_balances_when_enabled() {
+    // Initialize input account with 1_000_000 NTRN
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, NTRN.into())]));
+
+    // Forward 1_000 NTRN, snapshotting the balances of the input and output accounts
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+            Default::default(),
+        )
+        .with_snapshot_balances(true);
+    let lib = suite.forwarder_init(&cfg);
+
+    let res = suite.execute_forward(lib).unwrap();
+
+    let (input, output) = (suite.input_addr.to_string(), suite.output_addr.to_string());
+    assert_eq!(
+        balance_snapshots(&res),
+        vec![
+            (
+                "before".to_string(),
+                input.clone(),
+                "1000000000000untrn".to_string()
+            ),
+            ("before".to_string(), output.clone(), String::new()),
+            ("after".to_string(), input, "999000000000untrn".to_string()),
+            ("after".to_string(), output, "1000000000untrn".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn forward_does_not_snapshot_balances_by_default() {
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, NTRN.into())]));
+
+    let cfg = suite.forwarder_config(
+        vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+        Default::default(),
+    );
+    let lib = suite.forwarder_init(&cfg);
+
+    let res = suite.execute_forward(lib).unwrap();
+
+    assert!(balance_snapshots(&res).is_empty());
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(1_000_000_000, NTRN));
+}
+
 #[test]
 fn forward_native_token_partial_amount() {
     // Initialize input account with 1_000_000 NTRN
@@ -389,6 +615,125 @@ fn forward_cw20_partial_amount() {
     assert_eq!(output_balance, Uint128::from(1_000_000_000_u128));
 }
 
+#[test]
+fn forward_native_token_two_way_split() {
+    // Initialize input account with 1_000_000 NTRN
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, NTRN.into())]));
+    let fee_addr = suite.api().addr_make("fee_account");
+
+    // Set max amount to be forwarded to 1_001 NTRN (and no constraints),
+    // split 70% to the output account & 30% to the fee account.
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_001_000_001_u128)],
+            Default::default(),
+        )
+        .with_split_outputs(vec![
+            ForwardingOutput::new(suite.output_addr(), Decimal::percent(70)),
+            ForwardingOutput::new(&fee_addr, Decimal::percent(30)),
+        ]);
+
+    // Instantiate Forwarder contract
+    let lib = suite.forwarder_init(&cfg);
+
+    // Execute forward function
+    suite.execute_forward(lib).unwrap();
+
+    // Verify input account's balance: should be 1_000_000 - 1_001 NTRN
+    let input_balance = suite.query_balance(&suite.input_addr, NTRN);
+    assert_eq!(input_balance, coin(998_998_999_999_u128, NTRN));
+
+    // Verify output account's balance: should be 70% of the forwarded amount, rounded down
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(700_700_000, NTRN));
+
+    // Verify fee account's balance: should be the remainder of the forwarded amount
+    let fee_balance = suite.query_balance(&fee_addr, NTRN);
+    assert_eq!(fee_balance, coin(300_300_001, NTRN));
+}
+
+#[test]
+fn forward_native_token_split_rounded_up() {
+    // Initialize input account with 1_000_000 NTRN
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, NTRN.into())]));
+    let fee_addr = suite.api().addr_make("fee_account");
+
+    // Same 70% / 30% split of 1_001 NTRN, rounding the legs up
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_001_000_001_u128)],
+            Default::default(),
+        )
+        .with_split_outputs(vec![
+            ForwardingOutput::new(suite.output_addr(), Decimal::percent(70)),
+            ForwardingOutput::new(&fee_addr, Decimal::percent(30)),
+        ])
+        .with_rounding(RoundingPolicy::Up);
+
+    // Instantiate Forwarder contract
+    let lib = suite.forwarder_init(&cfg);
+
+    // Execute forward function
+    suite.execute_forward(lib).unwrap();
+
+    // Verify output account's balance: should be 70% of the forwarded amount, rounded up
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(700_700_001, NTRN));
+
+    // Verify fee account's balance: the remainder still adds up to the forwarded amount
+    let fee_balance = suite.query_balance(&fee_addr, NTRN);
+    assert_eq!(fee_balance, coin(300_300_000, NTRN));
+}
+
+#[test]
+#[should_panic(
+    expected = "Configuration error: Invalid forwarding config: sum of split output ratios is not equal to 1."
+)]
+fn instantiate_fails_for_split_ratios_not_summing_to_one() {
+    let mut suite = ForwarderTestSuite::default();
+    let fee_addr = suite.api().addr_make("fee_account");
+
+    // Split 70% to the output account & 20% to the fee account
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+            Default::default(),
+        )
+        .with_split_outputs(vec![
+            ForwardingOutput::new(suite.output_addr(), Decimal::percent(70)),
+            ForwardingOutput::new(&fee_addr, Decimal::percent(20)),
+        ]);
+
+    // Instantiate Forwarder contract
+    suite.forwarder_init(&cfg);
+}
+
+#[test]
+fn pre_validate_fails_for_duplicate_split_outputs() {
+    let suite = ForwarderTestSuite::default();
+
+    // Split 50% twice to the output account
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+            Default::default(),
+        )
+        .with_split_outputs(vec![
+            ForwardingOutput::new(suite.output_addr(), Decimal::percent(50)),
+            ForwardingOutput::new(suite.output_addr(), Decimal::percent(50)),
+        ]);
+
+    // Pre-validate config
+    let err = cfg.pre_validate(suite.api()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Configuration error: Duplicate output account '{}' in split outputs.",
+            suite.output_addr()
+        )
+    );
+}
+
 #[test]
 fn forward_with_height_interval_constraint() {
     // Initialize input account with 1_000_000 NTRN
@@ -495,6 +840,100 @@ fn forward_with_time_interval_constraint() {
     assert_eq!(output_balance, coin(2_000_000_000, NTRN));
 }
 
+#[test]
+fn forward_with_time_interval_constraint_skips_early_forward() {
+    // Initialize input account with 1_000_000 NTRN
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, NTRN.into())]));
+
+    // Set max amount to be forwarded to 1_000 NTRN,
+    // and constrain forward operation to once every 60 seconds, skipping early forwards.
+    let cfg = suite.forwarder_config(
+        vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+        ForwardingConstraints::new(Duration::Time(60).into())
+            .with_early_forward_behavior(EarlyForwardBehavior::Skip),
+    );
+
+    // Instantiate Forwarder contract
+    let lib = suite.forwarder_init(&cfg);
+
+    // T
+    // Execute forward function shoud succeed
+    suite.execute_forward(lib.clone()).unwrap();
+
+    // T+30s
+    suite
+        .app_mut()
+        .update_block(|block| block.time = block.time.plus_seconds(30));
+    // Execute forward function shoud be a no-op
+    let res = suite.execute_forward(lib.clone()).unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "skipped" && attr.value == "forwarding_constraint_not_met")));
+
+    // Verify output account's balance: should be 1_000 NTRN because the second forward was skipped
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(1_000_000_000, NTRN));
+
+    // T+60s
+    suite
+        .app_mut()
+        .update_block(|block| block.time = block.time.plus_seconds(30));
+    // Execute forward function shoud succeed
+    suite.execute_forward(lib.clone()).unwrap();
+
+    // Verify output account's balance: should be 2_000 NTRN because of 2 successful forwards
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(2_000_000_000, NTRN));
+}
+
+#[test]
+fn forward_with_nothing_to_forward_does_not_reset_interval() {
+    // Initialize input account with no balance
+    let mut suite = ForwarderTestSuite::default();
+
+    let owner_addr = suite.owner().clone();
+    let input_addr = suite.input_addr.clone();
+
+    // Set max amount to be forwarded to 1_000 NTRN,
+    // and constrain forward operation to once every 3 blocks.
+    let cfg = suite.forwarder_config(
+        vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+        ForwardingConstraints::new(Duration::Height(3).into()),
+    );
+
+    // Instantiate Forwarder contract
+    let lib = suite.forwarder_init(&cfg);
+
+    // Initialize owner account with 1_000_000 NTRN
+    suite.init_balance(&owner_addr, vec![coin(1_000_000_000_000_u128, NTRN)]);
+
+    // BLOCK N
+    // Execute forward function shoud succeed without transferring anything
+    suite.execute_forward(lib.clone()).unwrap();
+
+    // Transfer 2_000 NTRN to input account
+    let _ = suite.send_tokens(&owner_addr, &input_addr, &[coin(2_000_000_000_u128, NTRN)]);
+
+    // BLOCK N+1
+    suite.next_block();
+    // Execute forward function shoud succeed because the previous forward transferred nothing
+    suite.execute_forward(lib.clone()).unwrap();
+
+    // BLOCK N+2
+    suite.next_block();
+    // Execute forward function shoud fail
+    let res = suite.execute_forward(lib.clone());
+    assert_eq!(
+        res.unwrap_err().root_cause().to_string(),
+        "Execution error: Forwarding constraint not met."
+    );
+
+    // Verify output account's balance: should be 1_000 NTRN because of 1 successful forward
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(1_000_000_000, NTRN));
+}
+
 #[test]
 fn forward_multiple_tokens_continuously() {
     // Initialize input account with 2000 NTRN
@@ -657,3 +1096,814 @@ fn update_config() {
         )
     );
 }
+
+#[test]
+fn required_lp_shares_rounds_up() {
+    // 1 share redeems 2 USDC
+    let shares = |target: u128| {
+        required_lp_shares(
+            Uint128::new(target),
+            Uint128::new(POOL_USDC_RESERVE),
+            Uint128::new(POOL_TOTAL_SHARE),
+        )
+        .unwrap()
+    };
+    assert_eq!(shares(0), Uint128::zero());
+    assert_eq!(shares(10_000), Uint128::new(5_000));
+    // Partial shares are rounded up so that the redemption covers the target
+    assert_eq!(shares(10_001), Uint128::new(5_001));
+
+    // Nothing can be redeemed from a pool without reserves of the target denom
+    required_lp_shares(
+        Uint128::new(10_000),
+        Uint128::zero(),
+        Uint128::new(POOL_TOTAL_SHARE),
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn forward_lp_shares_for_target_redemption() {
+    // Initialize input account with 100_000 LP shares
+    let mut suite = ForwarderTestSuite::new(Some(vec![(100_000_u128, LP_TOKEN.into())]));
+    let pool_addr = suite.mock_pool_init();
+
+    // Forward the shares needed to redeem 10_000 USDC
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(LP_TOKEN.into()), u128::MAX)],
+            Default::default(),
+        )
+        .with_lp_redemption(LpRedemptionConfig {
+            pool_addr: pool_addr.to_string(),
+            lp_denom: LP_TOKEN.to_string(),
+            target_denom: USDC.to_string(),
+            target_amount: TargetAmountSource::Fixed(Uint128::new(10_000)),
+        });
+    let lib = suite.forwarder_init(&cfg);
+
+    suite.execute_forward(lib.clone()).unwrap();
+
+    // Only the 5_000 shares redeeming 10_000 USDC are forwarded
+    let input_balance = suite.query_balance(&suite.input_addr, LP_TOKEN);
+    assert_eq!(input_balance, coin(95_000, LP_TOKEN));
+    let output_balance = suite.query_balance(&suite.output_addr, LP_TOKEN);
+    assert_eq!(output_balance, coin(5_000, LP_TOKEN));
+
+    // The shares forwarded are capped at the available balance
+    let owner = suite.owner().clone();
+    suite
+        .app_mut()
+        .execute_contract(
+            owner,
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::UpdateConfig {
+                new_config: LibraryConfigUpdate {
+                    input_addr: None,
+                    additional_input_addrs: OptionUpdate::None,
+                    output_addr: None,
+                    forwarding_configs: None,
+                    forwarding_constraints: None,
+                    split_outputs: OptionUpdate::None,
+                    rounding: None,
+                    lp_redemption: OptionUpdate::Set(Some(LpRedemptionConfig {
+                        pool_addr: pool_addr.to_string(),
+                        lp_denom: LP_TOKEN.to_string(),
+                        target_denom: USDC.to_string(),
+                        target_amount: TargetAmountSource::Fixed(Uint128::new(POOL_USDC_RESERVE)),
+                    })),
+                    gas_reserve: OptionUpdate::None,
+                    snapshot_balances: None,
+                    precondition: OptionUpdate::None,
+                    fee_skim: OptionUpdate::None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    suite.execute_forward(lib).unwrap();
+
+    let input_balance = suite.query_balance(&suite.input_addr, LP_TOKEN);
+    assert_eq!(input_balance, coin(0, LP_TOKEN));
+    let output_balance = suite.query_balance(&suite.output_addr, LP_TOKEN);
+    assert_eq!(output_balance, coin(100_000, LP_TOKEN));
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid LP redemption config: LP denom 'factory/pool/astroport/share' is not in the forwarding configs."
+)]
+fn instantiate_fails_for_lp_redemption_of_unforwarded_denom() {
+    let mut suite = ForwarderTestSuite::default();
+    let pool_addr = suite.mock_pool_init();
+
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), u128::MAX)],
+            Default::default(),
+        )
+        .with_lp_redemption(LpRedemptionConfig {
+            pool_addr: pool_addr.to_string(),
+            lp_denom: LP_TOKEN.to_string(),
+            target_denom: USDC.to_string(),
+            target_amount: TargetAmountSource::Fixed(Uint128::new(10_000)),
+        });
+
+    suite.forwarder_init(&cfg);
+}
+
+#[test]
+fn forward_is_skipped_while_precondition_is_not_met() {
+    // Initialize input account with 1_000_000 NTRN
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, NTRN.into())]));
+    let sentinel_addr = suite.api().addr_make("withdraw_queue");
+
+    // Only forward while the withdraw queue holds a sentinel balance
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+            Default::default(),
+        )
+        .with_precondition(BalancePredicate::new(
+            &sentinel_addr,
+            USDC,
+            Comparator::GreaterThan,
+            0u128,
+        ));
+
+    // Instantiate Forwarder contract
+    let lib = suite.forwarder_init(&cfg);
+
+    // Execute forward function should be a no-op
+    let res = suite.execute_forward(lib.clone()).unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "skipped" && attr.value == "precondition_not_met")));
+
+    let simulated: Vec<SimulatedAmount> = suite.query_wasm(&lib, &QueryMsg::SimulateAmount {});
+    assert_eq!(
+        simulated,
+        vec![SimulatedAmount::skipped(NTRN, "precondition_not_met")]
+    );
+
+    // Verify output account's balance: nothing was forwarded
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(0, NTRN));
+}
+
+#[test]
+fn forward_executes_once_precondition_is_met() {
+    // Initialize input account with 1_000_000 NTRN
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, NTRN.into())]));
+    let sentinel_addr = suite.api().addr_make("withdraw_queue");
+
+    // Only forward while the withdraw queue holds a sentinel balance
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+            Default::default(),
+        )
+        .with_precondition(BalancePredicate::new(
+            &sentinel_addr,
+            USDC,
+            Comparator::GreaterThan,
+            0u128,
+        ));
+
+    // Instantiate Forwarder contract
+    let lib = suite.forwarder_init(&cfg);
+
+    // The withdraw queue has pending obligations
+    suite.init_balance(&sentinel_addr, vec![coin(1, USDC)]);
+
+    // Execute forward function
+    suite.execute_forward(lib).unwrap();
+
+    // Verify output account's balance: should be 1_000 NTRN
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(1_000_000_000, NTRN));
+}
+
+#[test]
+fn pre_validate_fails_for_precondition_without_denom() {
+    let suite = ForwarderTestSuite::default();
+    let sentinel_addr = suite.api().addr_make("withdraw_queue");
+
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+            Default::default(),
+        )
+        .with_precondition(BalancePredicate::new(
+            &sentinel_addr,
+            "",
+            Comparator::GreaterThan,
+            0u128,
+        ));
+
+    let err = cfg.pre_validate(suite.api()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Invalid precondition: denom cannot be empty."
+    );
+}
+
+#[test]
+fn forward_gas_denom_keeps_gas_reserve() {
+    // Initialize input account with 1_000_000 NTRN
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, NTRN.into())]));
+
+    // Forward the full NTRN balance, keeping 1 NTRN to pay fees
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), u128::MAX)],
+            Default::default(),
+        )
+        .with_gas_reserve(GasReserve::new(NTRN, 1_000_000_u128));
+
+    // Instantiate Forwarder contract
+    let lib = suite.forwarder_init(&cfg);
+
+    // Execute forward function
+    suite.execute_forward(lib).unwrap();
+
+    // Verify input account's balance: should be the gas reserve
+    let input_balance = suite.query_balance(&suite.input_addr, NTRN);
+    assert_eq!(input_balance, coin(1_000_000, NTRN));
+
+    // Verify output account's balance: should be 999_999 NTRN
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(999_999_000_000, NTRN));
+}
+
+#[test]
+fn forward_non_gas_denom_ignores_gas_reserve() {
+    // Initialize input account with 1_000_000 USDC
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, USDC.into())]));
+
+    // The gas reserve only applies to NTRN
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(USDC.into()), u128::MAX)],
+            Default::default(),
+        )
+        .with_gas_reserve(GasReserve::new(NTRN, 1_000_000_u128));
+
+    // Instantiate Forwarder contract
+    let lib = suite.forwarder_init(&cfg);
+
+    // Execute forward function
+    suite.execute_forward(lib).unwrap();
+
+    // Verify input account's balance: should be zero
+    let input_balance = suite.query_balance(&suite.input_addr, USDC);
+    assert_eq!(input_balance, coin(0, USDC));
+
+    // Verify output account's balance: should be 1_000_000 USDC
+    let output_balance = suite.query_balance(&suite.output_addr, USDC);
+    assert_eq!(output_balance, coin(1_000_000_000_000, USDC));
+}
+
+#[test]
+fn full_gas_denom_sweep_reserves_estimated_fee() {
+    // Initialize input account with 1_000_000 NTRN
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, NTRN.into())]));
+
+    // Sweep the full NTRN balance, only keeping the fee of the forward: 150_001 gas at 0.0055 untrn
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), u128::MAX)],
+            Default::default(),
+        )
+        .with_gas_reserve(
+            GasReserve::new(NTRN, 0_u128)
+                .with_estimated_fee(150_001, Decimal::from_ratio(55_u128, 10_000_u128)),
+        );
+
+    // Instantiate Forwarder contract
+    let lib = suite.forwarder_init(&cfg);
+
+    // Execute forward function
+    suite.execute_forward(lib).unwrap();
+
+    // Verify input account's balance: should be the estimated fee, 825.0055 untrn rounded up
+    let input_balance = suite.query_balance(&suite.input_addr, NTRN);
+    assert_eq!(input_balance, coin(826, NTRN));
+
+    // Verify output account's balance: should be the rest of the balance
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(999_999_999_174, NTRN));
+}
+
+#[test]
+fn full_non_gas_denom_sweep_ignores_estimated_fee() {
+    // Initialize input account with 1_000_000 USDC
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, USDC.into())]));
+
+    // The estimated fee is only paid in NTRN
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(USDC.into()), u128::MAX)],
+            Default::default(),
+        )
+        .with_gas_reserve(
+            GasReserve::new(NTRN, 1_000_000_u128)
+                .with_estimated_fee(150_001, Decimal::from_ratio(55_u128, 10_000_u128)),
+        );
+
+    // Instantiate Forwarder contract
+    let lib = suite.forwarder_init(&cfg);
+
+    // Execute forward function
+    suite.execute_forward(lib).unwrap();
+
+    // Verify the full USDC balance was swept
+    let input_balance = suite.query_balance(&suite.input_addr, USDC);
+    assert_eq!(input_balance, coin(0, USDC));
+    let output_balance = suite.query_balance(&suite.output_addr, USDC);
+    assert_eq!(output_balance, coin(1_000_000_000_000, USDC));
+}
+
+#[test]
+#[should_panic(expected = "Invalid gas reserve: estimated gas limit and gas price cannot be zero.")]
+fn instantiate_fails_for_zero_estimated_gas_price() {
+    let mut suite = ForwarderTestSuite::default();
+
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), u128::MAX)],
+            Default::default(),
+        )
+        .with_gas_reserve(
+            GasReserve::new(NTRN, 0_u128).with_estimated_fee(150_000, Decimal::zero()),
+        );
+
+    suite.forwarder_init(&cfg);
+}
+
+#[test]
+#[should_panic(expected = "Invalid gas reserve: amount cannot be zero.")]
+fn instantiate_fails_for_zero_gas_reserve() {
+    let mut suite = ForwarderTestSuite::default();
+
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), u128::MAX)],
+            Default::default(),
+        )
+        .with_gas_reserve(GasReserve::new(NTRN, 0_u128));
+
+    suite.forwarder_init(&cfg);
+}
+
+#[test]
+fn forward_skims_percentage_fee_within_bounds() {
+    // 1% fee, between 1_000 and 50_000 untrn, skipped below 10_000 untrn
+    // (balance, expected fee, expected remainder)
+    let cases = [
+        (5_000_u128, 0_u128, 5_000_u128),
+        (50_000, 1_000, 49_000),
+        (1_000_000, 10_000, 990_000),
+        (10_000_000, 50_000, 9_950_000),
+    ];
+
+    for (balance, expected_fee, expected_remainder) in cases {
+        let mut suite = ForwarderTestSuite::new(Some(vec![(balance, NTRN.into())]));
+        let fee_addr = suite.api().addr_make("fee_account");
+
+        let cfg = suite
+            .forwarder_config(
+                vec![(UncheckedDenom::Native(NTRN.into()), u128::MAX)],
+                Default::default(),
+            )
+            .with_fee_skim(
+                FeeSkim::new(&fee_addr, Fee::Percentage(Decimal::percent(1)))
+                    .with_min_fee(1_000_u128)
+                    .with_max_fee(50_000_u128)
+                    .with_threshold(10_000_u128),
+            );
+
+        // Instantiate Forwarder contract
+        let lib = suite.forwarder_init(&cfg);
+
+        // Execute forward function
+        suite.execute_forward(lib).unwrap();
+
+        // Verify fee account's balance: should be the bounded fee
+        let fee_balance = suite.query_balance(&fee_addr, NTRN);
+        assert_eq!(fee_balance, coin(expected_fee, NTRN), "balance {balance}");
+
+        // Verify output account's balance: should be the remainder of the forwarded amount
+        let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+        assert_eq!(
+            output_balance,
+            coin(expected_remainder, NTRN),
+            "balance {balance}"
+        );
+
+        // Verify input account's balance: should be zero
+        let input_balance = suite.query_balance(&suite.input_addr, NTRN);
+        assert_eq!(input_balance, coin(0, NTRN), "balance {balance}");
+    }
+}
+
+#[test]
+fn forward_skims_flat_fee_up_to_forwarded_amount() {
+    // Flat fee of 2_000 untrn, skipped below 1_000 untrn
+    // (balance, expected fee, expected remainder)
+    let cases = [
+        (500_u128, 0_u128, 500_u128),
+        (1_500, 1_500, 0),
+        (10_000, 2_000, 8_000),
+    ];
+
+    for (balance, expected_fee, expected_remainder) in cases {
+        let mut suite = ForwarderTestSuite::new(Some(vec![(balance, NTRN.into())]));
+        let fee_addr = suite.api().addr_make("fee_account");
+
+        let cfg = suite
+            .forwarder_config(
+                vec![(UncheckedDenom::Native(NTRN.into()), u128::MAX)],
+                Default::default(),
+            )
+            .with_fee_skim(
+                FeeSkim::new(&fee_addr, Fee::Flat(2_000_u128.into())).with_threshold(1_000_u128),
+            );
+
+        // Instantiate Forwarder contract
+        let lib = suite.forwarder_init(&cfg);
+
+        // Execute forward function
+        suite.execute_forward(lib).unwrap();
+
+        // Verify fee account's balance: the fee never exceeds the forwarded amount
+        let fee_balance = suite.query_balance(&fee_addr, NTRN);
+        assert_eq!(fee_balance, coin(expected_fee, NTRN), "balance {balance}");
+
+        // Verify output account's balance: should be the remainder of the forwarded amount
+        let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+        assert_eq!(
+            output_balance,
+            coin(expected_remainder, NTRN),
+            "balance {balance}"
+        );
+    }
+}
+
+#[test]
+fn pre_validate_fails_for_invalid_fee_skim() {
+    let suite = ForwarderTestSuite::default();
+    let fee_addr = suite.api().addr_make("fee_account");
+
+    let cases = [
+        (
+            FeeSkim::new(&fee_addr, Fee::Flat(Uint128::zero())),
+            "Configuration error: Invalid fee skim: flat fee cannot be zero.".to_string(),
+        ),
+        (
+            FeeSkim::new(&fee_addr, Fee::Percentage(Decimal::one())),
+            "Configuration error: Invalid fee skim: fee percentage must be between 0 and 1 (exclusive)."
+                .to_string(),
+        ),
+        (
+            FeeSkim::new(&fee_addr, Fee::Percentage(Decimal::percent(1)))
+                .with_min_fee(2_000_u128)
+                .with_max_fee(1_000_u128),
+            "Configuration error: Invalid fee skim: min fee cannot be greater than max fee."
+                .to_string(),
+        ),
+        (
+            FeeSkim::new(suite.output_addr(), Fee::Flat(1_000_u128.into())),
+            format!(
+                "Configuration error: Invalid fee skim: fee account '{}' cannot be an input or output account.",
+                suite.output_addr()
+            ),
+        ),
+    ];
+
+    for (fee_skim, expected) in cases {
+        let cfg = suite
+            .forwarder_config(
+                vec![(UncheckedDenom::Native(NTRN.into()), u128::MAX)],
+                Default::default(),
+            )
+            .with_fee_skim(fee_skim);
+
+        // Pre-validate config
+        let err = cfg.pre_validate(suite.api()).unwrap_err();
+        assert_eq!(err.to_string(), expected);
+    }
+}
+
+#[test]
+#[should_panic(expected = "cannot be an input or output account.")]
+fn update_config_fails_for_output_set_to_fee_account() {
+    let mut suite = ForwarderTestSuite::default();
+    let fee_addr = suite.api().addr_make("fee_account");
+
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), u128::MAX)],
+            Default::default(),
+        )
+        .with_fee_skim(FeeSkim::new(&fee_addr, Fee::Flat(1_000_u128.into())));
+
+    // Instantiate Forwarder contract
+    let lib = suite.forwarder_init(&cfg);
+
+    // Update the output account to the fee account
+    let mut new_config = cfg.clone();
+    new_config.output_addr = (&fee_addr).into();
+    suite.update_config(lib, new_config).unwrap();
+}
+
+#[test]
+fn forward_native_token_from_two_inputs() {
+    // Initialize the first input account with 1_000 NTRN, and the second with 500 NTRN
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_u128, NTRN.into())]));
+    let second_input_addr = suite.get_contract_addr(suite.account_code_id(), "second_input");
+
+    // Set max amount to be forwarded to 2_000 NTRN (and no constraints), pulling from both input accounts
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 2_000_000_000_u128)],
+            Default::default(),
+        )
+        .with_additional_input_addrs(vec![(&second_input_addr).into()]);
+
+    // Instantiate Forwarder contract, approved on both input accounts
+    let lib = suite.forwarder_init(&cfg);
+    let account_addr = suite.account_init("second_input", vec![lib.to_string()]);
+    assert_eq!(account_addr, second_input_addr);
+    suite.init_balance(&second_input_addr, vec![coin(500_000_000, NTRN)]);
+
+    // Execute forward function
+    suite.execute_forward(lib).unwrap();
+
+    // Verify both input accounts were emptied
+    let input_balance = suite.query_balance(&suite.input_addr, NTRN);
+    assert_eq!(input_balance, coin(0, NTRN));
+    let second_input_balance = suite.query_balance(&second_input_addr, NTRN);
+    assert_eq!(second_input_balance, coin(0, NTRN));
+
+    // Verify output account's balance: should be the sum of both balances, 1_500 NTRN
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(1_500_000_000, NTRN));
+}
+
+#[test]
+fn forward_from_two_inputs_is_capped_by_max_amount() {
+    // Initialize the first input account with 1_000 NTRN, and the second with 500 NTRN
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_u128, NTRN.into())]));
+    let second_input_addr = suite.get_contract_addr(suite.account_code_id(), "second_input");
+
+    // Set max amount to be forwarded to 1_200 NTRN: the first input account is drained before the second one
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_200_000_000_u128)],
+            Default::default(),
+        )
+        .with_additional_input_addrs(vec![(&second_input_addr).into()]);
+
+    // Instantiate Forwarder contract, approved on both input accounts
+    let lib = suite.forwarder_init(&cfg);
+    suite.account_init("second_input", vec![lib.to_string()]);
+    suite.init_balance(&second_input_addr, vec![coin(500_000_000, NTRN)]);
+
+    // Execute forward function
+    suite.execute_forward(lib).unwrap();
+
+    // Verify input accounts' balances: 1_000 NTRN taken from the first one, 200 NTRN from the second one
+    let input_balance = suite.query_balance(&suite.input_addr, NTRN);
+    assert_eq!(input_balance, coin(0, NTRN));
+    let second_input_balance = suite.query_balance(&second_input_addr, NTRN);
+    assert_eq!(second_input_balance, coin(300_000_000, NTRN));
+
+    // Verify output account's balance: should be the max amount
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(1_200_000_000, NTRN));
+}
+
+#[test]
+fn forward_fails_when_not_approved_on_an_input() {
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_u128, NTRN.into())]));
+    let second_input_addr = suite.get_contract_addr(suite.account_code_id(), "second_input");
+
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 2_000_000_000_u128)],
+            Default::default(),
+        )
+        .with_additional_input_addrs(vec![(&second_input_addr).into()]);
+
+    // The second input account doesn't approve the library
+    let lib = suite.forwarder_init(&cfg);
+    suite.account_init("second_input", vec![]);
+    suite.init_balance(&second_input_addr, vec![coin(500_000_000, NTRN)]);
+
+    // Execute forward function
+    let err = suite.execute_forward(lib).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("Execution error: Library is not approved on input account {second_input_addr}.")
+    );
+
+    // Nothing was forwarded
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance, coin(0, NTRN));
+}
+
+#[test]
+fn is_approved_when_approved_on_every_input() {
+    let mut suite = ForwarderTestSuite::default();
+    let second_input_addr = suite.get_contract_addr(suite.account_code_id(), "second_input");
+
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+            Default::default(),
+        )
+        .with_additional_input_addrs(vec![(&second_input_addr).into()]);
+
+    // The library is approved on both input accounts
+    let lib = suite.forwarder_init(&cfg);
+    suite.account_init("second_input", vec![lib.to_string()]);
+
+    let approved: bool = suite.query_wasm(&lib, &QueryMsg::IsApproved {});
+    assert!(approved);
+}
+
+#[test]
+fn is_not_approved_when_an_input_does_not_approve() {
+    let mut suite = ForwarderTestSuite::default();
+    let second_input_addr = suite.get_contract_addr(suite.account_code_id(), "second_input");
+
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+            Default::default(),
+        )
+        .with_additional_input_addrs(vec![(&second_input_addr).into()]);
+
+    // The second input account doesn't approve the library
+    let lib = suite.forwarder_init(&cfg);
+    suite.account_init("second_input", vec![]);
+
+    let approved: bool = suite.query_wasm(&lib, &QueryMsg::IsApproved {});
+    assert!(!approved);
+}
+
+#[test]
+fn pre_validate_fails_for_duplicate_input_accounts() {
+    let suite = ForwarderTestSuite::default();
+
+    // The input address is also listed as an additional input account
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+            Default::default(),
+        )
+        .with_additional_input_addrs(vec![suite.input_addr().into()]);
+
+    // Pre-validate config
+    let err = cfg.pre_validate(suite.api()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Configuration error: Duplicate input account '{}' in forwarding config.",
+            suite.input_addr()
+        )
+    );
+}
+
+#[test]
+fn simulated_amount_matches_forwarded_amount() {
+    // The max amount is above the balance for a full forward, and below it for a partial one
+    for (max_amount, expected_amount) in [
+        (2_000_000_000_000_u128, 1_000_000_000_000_u128),
+        (1_000_000_000_u128, 1_000_000_000_u128),
+    ] {
+        let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, NTRN.into())]));
+
+        let cfg = suite.forwarder_config(
+            vec![
+                (UncheckedDenom::Native(NTRN.into()), max_amount),
+                (UncheckedDenom::Native(USDC.into()), max_amount),
+            ],
+            Default::default(),
+        );
+        let lib = suite.forwarder_init(&cfg);
+
+        let simulated: Vec<SimulatedAmount> = suite.query_wasm(&lib, &QueryMsg::SimulateAmount {});
+        assert_eq!(
+            simulated,
+            vec![
+                SimulatedAmount::new(NTRN, Uint128::new(expected_amount)),
+                SimulatedAmount::skipped(USDC, "nothing_to_forward"),
+            ]
+        );
+
+        suite.execute_forward(lib).unwrap();
+
+        let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+        assert_eq!(output_balance.amount, simulated[0].amount);
+    }
+}
+
+#[test]
+fn simulated_amount_sums_input_accounts() {
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_u128, NTRN.into())]));
+    let second_input_addr = suite.get_contract_addr(suite.account_code_id(), "second_input");
+
+    let cfg = suite
+        .forwarder_config(
+            vec![(UncheckedDenom::Native(NTRN.into()), 1_200_000_000_u128)],
+            Default::default(),
+        )
+        .with_additional_input_addrs(vec![(&second_input_addr).into()]);
+    let lib = suite.forwarder_init(&cfg);
+    suite.account_init("second_input", vec![lib.to_string()]);
+    suite.init_balance(&second_input_addr, vec![coin(500_000_000, NTRN)]);
+
+    let simulated: Vec<SimulatedAmount> = suite.query_wasm(&lib, &QueryMsg::SimulateAmount {});
+    assert_eq!(
+        simulated,
+        vec![SimulatedAmount::new(NTRN, Uint128::new(1_200_000_000))]
+    );
+
+    suite.execute_forward(lib).unwrap();
+
+    let output_balance = suite.query_balance(&suite.output_addr, NTRN);
+    assert_eq!(output_balance.amount, simulated[0].amount);
+}
+
+#[test]
+fn simulated_amount_is_skipped_before_forwarding_interval() {
+    let mut suite = ForwarderTestSuite::new(Some(vec![(1_000_000_000_000_u128, NTRN.into())]));
+
+    let cfg = suite.forwarder_config(
+        vec![(UncheckedDenom::Native(NTRN.into()), 1_000_000_000_u128)],
+        ForwardingConstraints::new(Duration::Time(60).into())
+            .with_early_forward_behavior(EarlyForwardBehavior::Skip),
+    );
+    let lib = suite.forwarder_init(&cfg);
+    suite.execute_forward(lib.clone()).unwrap();
+
+    let simulated: Vec<SimulatedAmount> = suite.query_wasm(&lib, &QueryMsg::SimulateAmount {});
+    assert_eq!(
+        simulated,
+        vec![SimulatedAmount::skipped(
+            NTRN,
+            "forwarding_constraint_not_met"
+        )]
+    );
+
+    // Once the interval elapsed, the next forward is simulated again
+    suite
+        .app_mut()
+        .update_block(|block| block.time = block.time.plus_seconds(60));
+    let simulated: Vec<SimulatedAmount> = suite.query_wasm(&lib, &QueryMsg::SimulateAmount {});
+    assert_eq!(
+        simulated,
+        vec![SimulatedAmount::new(NTRN, Uint128::new(1_000_000_000))]
+    );
+}
+
+#[test]
+fn forward_nft_moves_the_position_to_the_output() {
+    let mut suite = ForwarderTestSuite::default();
+    let cfg = suite.forwarder_config(
+        vec![(UncheckedDenom::Native(NTRN.into()), 1_000)],
+        ForwardingConstraints::default(),
+    );
+    let lib = suite.forwarder_init(&cfg);
+    let input_addr = suite.input_addr().clone();
+    let nft_addr = suite.mock_nft_init(&input_addr);
+
+    let res = suite
+        .execute_forward_nft(lib.clone(), PositionNft::new(nft_addr.to_string(), "1"))
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "token_id" && a.value == "1")));
+    assert_eq!(suite.nft_owner(&nft_addr), suite.output_addr().to_string());
+
+    // The position is no longer owned by the input account
+    let err = suite
+        .execute_forward_nft(lib.clone(), PositionNft::new(nft_addr.to_string(), "1"))
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("Execution error: Invalid position NFT: token 1 is not owned by {input_addr}")
+    );
+
+    let err = suite
+        .execute_forward_nft(lib, PositionNft::new(nft_addr.to_string(), "2"))
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("Execution error: Invalid position NFT: token 2 does not exist in {nft_addr}")
+    );
+}
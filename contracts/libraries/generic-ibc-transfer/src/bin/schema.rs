@@ -3,12 +3,13 @@ use cosmwasm_schema::write_api;
 use valence_generic_ibc_transfer_library::msg::{
     FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg,
 };
-use valence_library_utils::msg::{ExecuteMsg, InstantiateMsg};
+use valence_library_utils::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg<LibraryConfig>,
         execute: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
+        migrate: MigrateMsg<LibraryConfigUpdate>,
         query: QueryMsg,
     }
 }
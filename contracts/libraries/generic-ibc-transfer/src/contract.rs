@@ -3,7 +3,7 @@ use cosmwasm_std::entry_point;
 use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 use valence_library_utils::{
     error::LibraryError,
-    msg::{ExecuteMsg, InstantiateMsg},
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg},
 };
 
 use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
@@ -39,11 +39,29 @@ pub fn execute(
     )
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
+    )
+}
+
 mod functions {
     use std::collections::BTreeMap;
 
     use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
-    use valence_library_utils::{error::LibraryError, execute_on_behalf_of};
+    use valence_library_utils::{
+        error::LibraryError, execute_on_behalf_of, stats::record_transfers,
+    };
 
     use crate::msg::{Config, FunctionMsgs, IbcTransferAmount};
 
@@ -54,31 +72,79 @@ mod functions {
         msg: FunctionMsgs,
         cfg: Config,
     ) -> Result<Response, LibraryError> {
+        if cfg.denom_selection().is_some() {
+            return Err(LibraryError::ExecutionError(
+                "Denom selection is only supported by the Neutron IBC transfer library."
+                    .to_string(),
+            ));
+        }
+
+        // Skip the transfer while the precondition is not met
+        if let Some(precondition) = cfg.precondition() {
+            if !precondition.is_met(&deps.querier)? {
+                return Ok(Response::new()
+                    .add_attribute("method", "ibc-transfer-skipped")
+                    .add_attribute("reason", "precondition not met"));
+            }
+        }
+
         let balance = cfg.denom().query_balance(&deps.querier, cfg.input_addr())?;
 
+        // Skip the transfer while the balance is below the configured minimum
+        if let Some(min_transfer_amount) = cfg.min_transfer_amount() {
+            if balance < *min_transfer_amount {
+                return Ok(Response::new()
+                    .add_attribute("method", "ibc-transfer-skipped")
+                    .add_attribute("reason", "balance below min_transfer_amount")
+                    .add_attribute("balance", balance.to_string())
+                    .add_attribute("min_transfer_amount", min_transfer_amount.to_string()));
+            }
+        }
+
+        // The gas reserve is left in the input account so it can keep paying fees
+        let available = match cfg.gas_reserve() {
+            Some(gas_reserve) => gas_reserve.available_balance(cfg.denom(), balance),
+            None => balance,
+        };
+
         let amount = match cfg.amount() {
-            IbcTransferAmount::FullAmount => balance,
+            IbcTransferAmount::FullAmount => available,
             IbcTransferAmount::FixedAmount(amount) => {
-                if balance < *amount {
+                if available < *amount {
                     return Err(LibraryError::ExecutionError(format!(
                                 "Insufficient balance for denom '{}' in config (required: {}, available: {}).",
-                                cfg.denom(), amount, balance,
+                                cfg.denom(), amount, available,
                             )));
                 }
                 *amount
             }
         };
 
+        // Nothing is left to transfer once the gas reserve is put aside
+        if amount.is_zero() && available < balance {
+            return Ok(Response::new()
+                .add_attribute("method", "ibc-transfer-skipped")
+                .add_attribute("reason", "balance within gas_reserve"));
+        }
+
+        record_transfers(
+            deps.storage,
+            env.block.height,
+            &[(cfg.denom().to_string(), amount)],
+        )?;
+
+        let output_addr = cfg.resolve_output_addr(&deps.querier)?;
+
         match msg {
             FunctionMsgs::IbcTransfer {} => {
                 let ibc_send_msg = valence_ibc_utils::generic::ibc_send_message(
                     env,
                     cfg.remote_chain_info().channel_id.clone(),
-                    cfg.output_addr().to_string(),
+                    output_addr,
                     cfg.denom().to_string(),
                     amount.u128(),
                     cfg.memo().clone(),
-                    cfg.remote_chain_info().ibc_transfer_timeout.map(Into::into),
+                    Some(cfg.ibc_transfer_timeout()),
                     cfg.denom_to_pfm_map().clone(),
                 )
                 .map_err(|err| LibraryError::ExecutionError(err.to_string()))?;
@@ -102,7 +168,7 @@ mod functions {
 
                 let eureka_memo = valence_ibc_utils::generic::build_eureka_memo(
                     &env,
-                    cfg.output_addr().clone(),
+                    output_addr,
                     eureka_fee,
                     eureka_config.clone(),
                 )?;
@@ -114,7 +180,7 @@ mod functions {
                     cfg.denom().to_string(),
                     amount.u128(),
                     eureka_memo,
-                    cfg.remote_chain_info().ibc_transfer_timeout.map(Into::into),
+                    Some(cfg.ibc_transfer_timeout()),
                     BTreeMap::default(),
                 )
                 .map_err(|err| LibraryError::ExecutionError(err.to_string()))?;
@@ -147,7 +213,7 @@ mod execute {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Ownership {} => {
             to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
@@ -155,6 +221,21 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetProcessor {} => {
             to_json_binary(&valence_library_base::get_processor(deps.storage)?)
         }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        QueryMsg::IsApproved {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&valence_library_utils::is_library_approved(
+                &deps.querier,
+                config.input_addr(),
+                &env.contract.address,
+            )?)
+        }
         QueryMsg::GetLibraryConfig {} => {
             let config: Config = valence_library_base::load_config(deps.storage)?;
             to_json_binary(&config)
@@ -164,5 +245,8 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 valence_library_utils::raw_config::query_raw_library_config(deps.storage)?;
             to_json_binary(&raw_config)
         }
+        QueryMsg::Stats {} => to_json_binary(&valence_library_utils::stats::query_transfer_stats(
+            deps.storage,
+        )?),
     }
 }
@@ -1,14 +1,21 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, CustomQuery, Deps, DepsMut, Uint128, Uint64};
+use cosmwasm_std::{Addr, Api, CustomQuery, Deps, DepsMut, QuerierWrapper, Uint128, Uint64};
 use cw_ownable::cw_ownable_query;
 use getset::{Getters, Setters};
-use valence_ibc_utils::types::{EurekaConfig, EurekaFee, PacketForwardMiddlewareConfig};
+use valence_ibc_utils::types::{
+    EurekaConfig, EurekaFee, PacketForwardMiddlewareConfig, TransferDefaults,
+};
 use valence_library_utils::{
     denoms::{CheckedDenom, UncheckedDenom},
     error::LibraryError,
+    gas_reserve::GasReserve,
+    ica::query_remote_ica_address,
     msg::LibraryConfigValidation,
+    pfm::find_overlapping_pfm_route,
+    predicate::{BalancePredicate, CheckedBalancePredicate},
+    stats::TransferStats,
     LibraryAccountType,
 };
 use valence_macros::{valence_library_query, ValenceLibraryInterface};
@@ -24,7 +31,11 @@ pub enum FunctionMsgs {
 #[cw_serde]
 #[derive(QueryResponses)]
 /// Enum representing the different query messages that can be sent.
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    /// Returns the cumulative amounts transferred by the library
+    #[returns(TransferStats)]
+    Stats {},
+}
 
 #[cw_serde]
 #[derive(ValenceLibraryInterface)]
@@ -37,6 +48,17 @@ pub struct LibraryConfig {
     pub remote_chain_info: RemoteChainInfo,
     pub denom_to_pfm_map: BTreeMap<String, PacketForwardMiddlewareConfig>,
     pub eureka_config: Option<EurekaConfig>,
+    // If set, transfers are skipped while the input account balance is below this amount
+    pub min_transfer_amount: Option<Uint128>,
+    // If set, the denoms to transfer are taken from this selection instead of `denom`
+    pub denom_selection: Option<DenomSelection>,
+    // If set, this amount of the chain gas denom is always left in the input account
+    pub gas_reserve: Option<GasReserve>,
+    // If set, transfers are skipped while the balance of this account doesn't meet the predicate
+    pub precondition: Option<BalancePredicate>,
+    // If set, replaces the crate defaults applied to the transfers that don't set their own parameters
+    // (e.g. the timeout of the transfers whose remote chain info doesn't set one)
+    pub transfer_defaults: Option<TransferDefaults>,
 }
 
 #[cw_serde]
@@ -45,6 +67,66 @@ pub enum IbcTransferAmount {
     FixedAmount(Uint128),
 }
 
+/// Native denoms transferred on every execution, one IBC transfer per denom.
+/// The amount of each transfer is resolved against the balance of its own denom.
+/// Transfers are sent in ascending order of denom, the order in which `denom_to_pfm_map` is iterated,
+/// whatever the order of the selected denoms, so the same balances always produce the same packets.
+#[cw_serde]
+pub enum DenomSelection {
+    Single(String),
+    /// Denoms without balance in the input account are not transferred
+    List(Vec<String>),
+    /// Every denom held by the input account, except the one reserved to pay for fees
+    All {
+        fee_reserve_denom: Option<String>,
+    },
+}
+
+impl DenomSelection {
+    /// Returns the selected denoms along with their balance in `account`, in ascending order of denom
+    pub fn query_balances<C: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<C>,
+        account: &Addr,
+    ) -> StdResult<Vec<(String, Uint128)>> {
+        let mut balances = match self {
+            DenomSelection::Single(denom) => {
+                let balance = querier.query_balance(account, denom)?;
+                vec![(balance.denom, balance.amount)]
+            }
+            DenomSelection::List(denoms) => {
+                let mut balances = vec![];
+                for denom in denoms {
+                    let balance = querier.query_balance(account, denom)?;
+                    if !balance.amount.is_zero() {
+                        balances.push((balance.denom, balance.amount));
+                    }
+                }
+                balances
+            }
+            DenomSelection::All { fee_reserve_denom } => {
+                #[allow(deprecated)]
+                let balances = querier.query_all_balances(account)?;
+                balances
+                    .into_iter()
+                    .filter(|coin| {
+                        !coin.amount.is_zero() && Some(&coin.denom) != fee_reserve_denom.as_ref()
+                    })
+                    .map(|coin| (coin.denom, coin.amount))
+                    .collect()
+            }
+        };
+
+        balances.sort_by(|(denom, _), (other, _)| denom.cmp(other));
+        Ok(balances)
+    }
+
+    /// Returns true if more than one denom can be transferred per execution
+    pub fn is_multi_denom(&self) -> bool {
+        !matches!(self, DenomSelection::Single(_))
+    }
+}
+
 #[cw_serde]
 pub struct RemoteChainInfo {
     pub channel_id: String,
@@ -78,6 +160,11 @@ impl LibraryConfig {
             remote_chain_info,
             denom_to_pfm_map: BTreeMap::default(),
             eureka_config: None,
+            min_transfer_amount: None,
+            denom_selection: None,
+            gas_reserve: None,
+            precondition: None,
+            transfer_defaults: None,
         }
     }
 
@@ -99,10 +186,38 @@ impl LibraryConfig {
             remote_chain_info,
             denom_to_pfm_map,
             eureka_config: None,
+            min_transfer_amount: None,
+            denom_selection: None,
+            gas_reserve: None,
+            precondition: None,
+            transfer_defaults: None,
         }
     }
 
-    fn do_validate(&self, api: &dyn cosmwasm_std::Api) -> Result<Addr, LibraryError> {
+    pub fn with_denom_selection(mut self, denom_selection: DenomSelection) -> Self {
+        self.denom_selection = Some(denom_selection);
+        self
+    }
+
+    pub fn with_gas_reserve(mut self, gas_reserve: GasReserve) -> Self {
+        self.gas_reserve = Some(gas_reserve);
+        self
+    }
+
+    pub fn with_precondition(mut self, precondition: BalancePredicate) -> Self {
+        self.precondition = Some(precondition);
+        self
+    }
+
+    pub fn with_transfer_defaults(mut self, transfer_defaults: TransferDefaults) -> Self {
+        self.transfer_defaults = Some(transfer_defaults);
+        self
+    }
+
+    fn do_validate(
+        &self,
+        api: &dyn cosmwasm_std::Api,
+    ) -> Result<(Addr, Option<CheckedBalancePredicate>), LibraryError> {
         let input_addr = self.input_addr.to_addr(api)?;
 
         match self.amount {
@@ -131,7 +246,34 @@ impl LibraryConfig {
             }
         }
 
-        Ok(input_addr)
+        validate_pfm_map(&self.denom_to_pfm_map)?;
+
+        validate_min_transfer_amount(self.min_transfer_amount, &self.amount)?;
+
+        if let Some(denom_selection) = &self.denom_selection {
+            validate_denom_selection(denom_selection)?;
+        }
+
+        if let Some(gas_reserve) = &self.gas_reserve {
+            gas_reserve.validate()?;
+        }
+
+        validate_denom_selection_combinations(
+            self.denom_selection.as_ref(),
+            &self.amount,
+            self.min_transfer_amount,
+            self.gas_reserve.as_ref(),
+        )?;
+
+        validate_transfer_defaults(self.transfer_defaults.as_ref())?;
+
+        let precondition = self
+            .precondition
+            .as_ref()
+            .map(|precondition| precondition.to_checked(api))
+            .transpose()?;
+
+        Ok((input_addr, precondition))
     }
 }
 
@@ -143,11 +285,12 @@ impl LibraryConfigValidation<Config> for LibraryConfig {
     }
 
     fn validate(&self, deps: Deps) -> Result<Config, LibraryError> {
-        let input_addr = self.do_validate(deps.api)?;
+        let (input_addr, precondition) = self.do_validate(deps.api)?;
+        let (output_addr, output_ica_library) = check_output_addr(deps.api, &self.output_addr)?;
         Ok(Config {
             input_addr,
-            // Can't validate output address as it's on another chain
-            output_addr: self.output_addr.to_string()?,
+            output_addr,
+            output_ica_library,
             denom: self
                 .denom
                 .clone()
@@ -158,6 +301,11 @@ impl LibraryConfigValidation<Config> for LibraryConfig {
             remote_chain_info: self.remote_chain_info.clone(),
             denom_to_pfm_map: self.denom_to_pfm_map.clone(),
             eureka_config: self.eureka_config.clone(),
+            min_transfer_amount: self.min_transfer_amount,
+            denom_selection: self.denom_selection.clone(),
+            gas_reserve: self.gas_reserve.clone(),
+            precondition,
+            transfer_defaults: self.transfer_defaults.clone(),
         })
     }
 }
@@ -174,7 +322,8 @@ impl LibraryConfigUpdate {
         }
 
         if let Some(output_addr) = self.output_addr {
-            config.output_addr = output_addr.to_string()?;
+            (config.output_addr, config.output_ica_library) =
+                check_output_addr(deps.api, &output_addr)?;
         }
 
         if let Some(denom) = self.denom {
@@ -218,6 +367,7 @@ impl LibraryConfigUpdate {
         }
 
         if let Some(denom_to_pfm_map) = self.denom_to_pfm_map {
+            validate_pfm_map(&denom_to_pfm_map)?;
             config.denom_to_pfm_map = denom_to_pfm_map;
         }
 
@@ -225,6 +375,44 @@ impl LibraryConfigUpdate {
             config.eureka_config = eureka_config;
         }
 
+        if let OptionUpdate::Set(min_transfer_amount) = self.min_transfer_amount {
+            config.min_transfer_amount = min_transfer_amount;
+        }
+
+        if let OptionUpdate::Set(denom_selection) = self.denom_selection {
+            if let Some(denom_selection) = &denom_selection {
+                validate_denom_selection(denom_selection)?;
+            }
+            config.denom_selection = denom_selection;
+        }
+
+        if let OptionUpdate::Set(gas_reserve) = self.gas_reserve {
+            if let Some(gas_reserve) = &gas_reserve {
+                gas_reserve.validate()?;
+            }
+            config.gas_reserve = gas_reserve;
+        }
+
+        if let OptionUpdate::Set(precondition) = self.precondition {
+            config.precondition = precondition
+                .map(|precondition| precondition.to_checked(deps.api))
+                .transpose()?;
+        }
+
+        if let OptionUpdate::Set(transfer_defaults) = self.transfer_defaults {
+            validate_transfer_defaults(transfer_defaults.as_ref())?;
+            config.transfer_defaults = transfer_defaults;
+        }
+
+        // The minimum and the denom selection are validated against the (possibly updated) amount
+        validate_min_transfer_amount(config.min_transfer_amount, &config.amount)?;
+        validate_denom_selection_combinations(
+            config.denom_selection.as_ref(),
+            &config.amount,
+            config.min_transfer_amount,
+            config.gas_reserve.as_ref(),
+        )?;
+
         valence_library_base::save_config(deps.storage, &config)?;
 
         Ok(())
@@ -238,6 +426,9 @@ pub struct Config {
     input_addr: Addr,
     #[getset(get = "pub", set)]
     output_addr: String,
+    // If set, the output is the remote address of the ICA of this library, which `output_addr` holds
+    #[getset(get = "pub", set)]
+    output_ica_library: Option<Addr>,
     #[getset(get = "pub", set)]
     denom: CheckedDenom,
     #[getset(get = "pub", set)]
@@ -250,6 +441,16 @@ pub struct Config {
     denom_to_pfm_map: BTreeMap<String, PacketForwardMiddlewareConfig>,
     #[getset(get = "pub", set)]
     eureka_config: Option<EurekaConfig>,
+    #[getset(get = "pub", set)]
+    min_transfer_amount: Option<Uint128>,
+    #[getset(get = "pub", set)]
+    denom_selection: Option<DenomSelection>,
+    #[getset(get = "pub", set)]
+    gas_reserve: Option<GasReserve>,
+    #[getset(get = "pub", set)]
+    precondition: Option<CheckedBalancePredicate>,
+    #[getset(get = "pub", set)]
+    transfer_defaults: Option<TransferDefaults>,
 }
 
 impl Config {
@@ -264,12 +465,18 @@ impl Config {
         Config {
             input_addr,
             output_addr,
+            output_ica_library: None,
             denom,
             amount,
             memo,
             remote_chain_info,
             denom_to_pfm_map: BTreeMap::default(),
             eureka_config: None,
+            min_transfer_amount: None,
+            denom_selection: None,
+            gas_reserve: None,
+            precondition: None,
+            transfer_defaults: None,
         }
     }
 
@@ -285,12 +492,18 @@ impl Config {
         Config {
             input_addr,
             output_addr,
+            output_ica_library: None,
             denom,
             amount,
             memo,
             remote_chain_info,
             denom_to_pfm_map,
             eureka_config: None,
+            min_transfer_amount: None,
+            denom_selection: None,
+            gas_reserve: None,
+            precondition: None,
+            transfer_defaults: None,
         }
     }
 
@@ -306,12 +519,200 @@ impl Config {
         Config {
             input_addr,
             output_addr,
+            output_ica_library: None,
             denom,
             amount,
             memo,
             remote_chain_info,
             denom_to_pfm_map: BTreeMap::default(),
             eureka_config: Some(eureka_config),
+            min_transfer_amount: None,
+            denom_selection: None,
+            gas_reserve: None,
+            precondition: None,
+            transfer_defaults: None,
         }
     }
+
+    pub fn with_denom_selection(mut self, denom_selection: DenomSelection) -> Self {
+        self.denom_selection = Some(denom_selection);
+        self
+    }
+
+    pub fn with_gas_reserve(mut self, gas_reserve: GasReserve) -> Self {
+        self.gas_reserve = Some(gas_reserve);
+        self
+    }
+
+    pub fn with_precondition(mut self, precondition: CheckedBalancePredicate) -> Self {
+        self.precondition = Some(precondition);
+        self
+    }
+
+    pub fn with_transfer_defaults(mut self, transfer_defaults: TransferDefaults) -> Self {
+        self.transfer_defaults = Some(transfer_defaults);
+        self
+    }
+
+    /// Timeout in seconds of the transfers: the one of the remote chain info if it's set,
+    /// otherwise the one of the transfer defaults of the library, or of the crate if it has none
+    pub fn ibc_transfer_timeout(&self) -> u64 {
+        self.transfer_defaults
+            .clone()
+            .unwrap_or_default()
+            .ibc_transfer_timeout_or(self.remote_chain_info.ibc_transfer_timeout.map(Into::into))
+    }
+
+    pub fn with_output_ica_library(mut self, ica_library: Addr) -> Self {
+        self.output_addr = ica_library.to_string();
+        self.output_ica_library = Some(ica_library);
+        self
+    }
+
+    /// Returns the address receiving the transfers on the remote chain, querying the current
+    /// remote address of the ICA if the output is an ICA remote address
+    pub fn resolve_output_addr<C: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<C>,
+    ) -> Result<String, LibraryError> {
+        match &self.output_ica_library {
+            Some(ica_library) => {
+                query_remote_ica_address(querier, ica_library.as_str()).map_err(|err| {
+                    LibraryError::ExecutionError(format!(
+                        "Failed to resolve the remote address of ICA {ica_library}: {err}"
+                    ))
+                })
+            }
+            None => Ok(self.output_addr.clone()),
+        }
+    }
+}
+
+/// Returns the output address along with the ICA library to resolve it from, if it's an ICA remote address
+fn check_output_addr(
+    api: &dyn Api,
+    output_addr: &LibraryAccountType,
+) -> Result<(String, Option<Addr>), LibraryError> {
+    match output_addr {
+        LibraryAccountType::IcaRemote { ica_library } => {
+            let ica_library = api.addr_validate(ica_library.as_str())?;
+            Ok((ica_library.to_string(), Some(ica_library)))
+        }
+        // Can't validate output address as it's on another chain
+        _ => Ok((output_addr.to_string()?, None)),
+    }
+}
+
+fn validate_min_transfer_amount(
+    min_transfer_amount: Option<Uint128>,
+    amount: &IbcTransferAmount,
+) -> Result<(), LibraryError> {
+    match (min_transfer_amount, amount) {
+        (Some(min_transfer_amount), IbcTransferAmount::FixedAmount(amount))
+            if min_transfer_amount > *amount =>
+        {
+            Err(LibraryError::ConfigurationError(
+                "Invalid IBC transfer config: min_transfer_amount cannot be larger than the fixed amount.".to_string(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Rejects the settings that can't be applied along with the denom selection: a fixed amount or a minimum
+/// would apply to each of the selected denoms, and the gas reserve is only kept for the single `denom`
+fn validate_denom_selection_combinations(
+    denom_selection: Option<&DenomSelection>,
+    amount: &IbcTransferAmount,
+    min_transfer_amount: Option<Uint128>,
+    gas_reserve: Option<&GasReserve>,
+) -> Result<(), LibraryError> {
+    let Some(denom_selection) = denom_selection else {
+        return Ok(());
+    };
+
+    if denom_selection.is_multi_denom() {
+        if matches!(amount, IbcTransferAmount::FixedAmount(_)) {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid IBC transfer config: a fixed amount cannot be used with a multi-denom denom_selection.".to_string(),
+            ));
+        }
+        if min_transfer_amount.is_some() {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid IBC transfer config: min_transfer_amount cannot be used with a multi-denom denom_selection.".to_string(),
+            ));
+        }
+    }
+
+    if gas_reserve.is_some() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid IBC transfer config: gas_reserve cannot be used with denom_selection."
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_transfer_defaults(
+    transfer_defaults: Option<&TransferDefaults>,
+) -> Result<(), LibraryError> {
+    if transfer_defaults.is_some_and(|defaults| defaults.ibc_transfer_timeout == 0) {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid IBC transfer config: transfer_defaults' ibc_transfer_timeout cannot be zero."
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validates every PFM entry, and checks that no denom is routed more than once
+fn validate_pfm_map(
+    denom_to_pfm_map: &BTreeMap<String, PacketForwardMiddlewareConfig>,
+) -> Result<(), LibraryError> {
+    for (denom, pfm_config) in denom_to_pfm_map {
+        pfm_config.validate().map_err(|e| {
+            LibraryError::ConfigurationError(format!("Invalid PFM route for denom {denom}: {e}."))
+        })?;
+    }
+
+    if let Some((denom, other)) = find_overlapping_pfm_route(denom_to_pfm_map) {
+        return Err(LibraryError::ConfigurationError(format!(
+            "Invalid PFM route for denom {denom}: overlaps with the route of denom {other}."
+        )));
+    }
+
+    Ok(())
+}
+
+fn validate_denom_selection(denom_selection: &DenomSelection) -> Result<(), LibraryError> {
+    let denoms: Vec<&String> = match denom_selection {
+        DenomSelection::Single(denom) => vec![denom],
+        DenomSelection::List(denoms) => {
+            if denoms.is_empty() {
+                return Err(LibraryError::ConfigurationError(
+                    "Invalid IBC transfer config: denom_selection list cannot be empty."
+                        .to_string(),
+                ));
+            }
+            denoms.iter().collect()
+        }
+        DenomSelection::All { fee_reserve_denom } => fee_reserve_denom.iter().collect(),
+    };
+
+    let mut seen = BTreeSet::new();
+    for denom in denoms {
+        if denom.is_empty() {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid IBC transfer config: denom_selection denoms cannot be empty.".to_string(),
+            ));
+        }
+        if !seen.insert(denom) {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Invalid IBC transfer config: denom_selection contains duplicate denom '{denom}'."
+            )));
+        }
+    }
+
+    Ok(())
 }
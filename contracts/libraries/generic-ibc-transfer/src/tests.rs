@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use crate::msg::{
     Config, FunctionMsgs, IbcTransferAmount, LibraryConfig, LibraryConfigUpdate, QueryMsg,
     RemoteChainInfo,
@@ -6,10 +8,15 @@ use cosmwasm_std::{coin, Addr, Empty, Uint128, Uint64};
 use cw_multi_test::{error::AnyResult, App, AppResponse, ContractWrapper, Executor};
 use cw_ownable::Ownership;
 use getset::{Getters, Setters};
-use valence_ibc_utils::types::EurekaFee;
+use valence_ibc_utils::types::{
+    EurekaFee, PacketForwardMiddlewareConfig, TransferDefaults,
+    DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS,
+};
 use valence_library_utils::{
     denoms::CheckedDenom,
-    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation},
+    gas_reserve::GasReserve,
+    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation, MigrateMsg},
+    predicate::{BalancePredicate, Comparator},
     testing::{LibraryTestSuite, LibraryTestSuiteBase},
     LibraryAccountType,
 };
@@ -50,7 +57,8 @@ impl IbcTransferTestSuite {
             crate::contract::execute,
             crate::contract::instantiate,
             crate::contract::query,
-        );
+        )
+        .with_migrate(crate::contract::migrate);
 
         let ibc_transfer_code_id = inner.app_mut().store_code(Box::new(ibc_transfer_code));
 
@@ -137,6 +145,15 @@ impl IbcTransferTestSuite {
             remote_chain_info: Some(new_config.remote_chain_info),
             denom_to_pfm_map: Some(new_config.denom_to_pfm_map),
             eureka_config: valence_library_utils::OptionUpdate::Set(new_config.eureka_config),
+            min_transfer_amount: valence_library_utils::OptionUpdate::Set(
+                new_config.min_transfer_amount,
+            ),
+            denom_selection: valence_library_utils::OptionUpdate::Set(new_config.denom_selection),
+            gas_reserve: valence_library_utils::OptionUpdate::Set(new_config.gas_reserve),
+            precondition: valence_library_utils::OptionUpdate::Set(new_config.precondition),
+            transfer_defaults: valence_library_utils::OptionUpdate::Set(
+                new_config.transfer_defaults,
+            ),
         };
         self.app_mut().execute_contract(
             owner,
@@ -147,6 +164,26 @@ impl IbcTransferTestSuite {
             &[],
         )
     }
+
+    fn migrate(
+        &mut self,
+        addr: Addr,
+        stored_version: &str,
+        config_update: Option<LibraryConfigUpdate>,
+    ) -> AnyResult<AppResponse> {
+        // Simulate a library deployed with another version of the code
+        cw2::set_contract_version(
+            self.app_mut().contract_storage_mut(&addr).as_mut(),
+            env!("CARGO_PKG_NAME"),
+            stored_version,
+        )
+        .unwrap();
+
+        let owner = self.owner().clone();
+        let code_id = self.ibc_transfer_code_id;
+        self.app_mut()
+            .migrate_contract(owner, addr, &MigrateMsg { config_update }, code_id)
+    }
 }
 
 impl LibraryTestSuite<Empty, Empty> for IbcTransferTestSuite {
@@ -282,6 +319,102 @@ fn instantiate_fails_for_invalid_ibc_transfer_timeout() {
     suite.ibc_transfer_init(&cfg);
 }
 
+#[test]
+fn transfer_defaults_apply_to_transfers_without_timeout() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    // Without transfer defaults, the crate default applies
+    let cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), None),
+    );
+    let lib = suite.ibc_transfer_init(&cfg);
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        lib_cfg.ibc_transfer_timeout(),
+        DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS
+    );
+
+    // The transfer defaults set on instantiation replace it
+    let cfg = cfg.with_transfer_defaults(TransferDefaults::new(1_800));
+    let lib = suite.ibc_transfer_init(&cfg);
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        lib_cfg.transfer_defaults(),
+        &Some(TransferDefaults::new(1_800))
+    );
+    assert_eq!(lib_cfg.ibc_transfer_timeout(), 1_800);
+}
+
+#[test]
+fn transfer_timeout_overrides_transfer_defaults() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FullAmount,
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(300u64.into())),
+        )
+        .with_transfer_defaults(TransferDefaults::new(1_800));
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(lib_cfg.ibc_transfer_timeout(), 300);
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid IBC transfer config: transfer_defaults' ibc_transfer_timeout cannot be zero."
+)]
+fn instantiate_fails_for_zero_default_ibc_transfer_timeout() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FullAmount,
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), None),
+        )
+        .with_transfer_defaults(TransferDefaults::new(0));
+
+    // Instantiate IBC transfer contract
+    suite.ibc_transfer_init(&cfg);
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid PFM route for denom ibc/abc123: overlaps with the route of denom ibc/ABC123."
+)]
+fn instantiate_fails_for_overlapping_pfm_routes() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let mut cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+    // The same IBC denom, routed through two different hops
+    cfg.denom_to_pfm_map = BTreeMap::from([
+        (
+            "ibc/ABC123".to_string(),
+            PacketForwardMiddlewareConfig::new("channel-2", "channel-3", None),
+        ),
+        (
+            "ibc/abc123".to_string(),
+            PacketForwardMiddlewareConfig::new("channel-4", "channel-5", None),
+        ),
+    ]);
+
+    // Instantiate IBC transfer contract
+    suite.ibc_transfer_init(&cfg);
+}
+
 // Config update tests
 
 #[test]
@@ -354,6 +487,37 @@ fn update_config_validates_ibc_timeout() {
     suite.update_config(lib.clone(), cfg).unwrap();
 }
 
+#[test]
+#[should_panic(
+    expected = "Invalid PFM route for denom ibc/abc123: overlaps with the route of denom ibc/ABC123."
+)]
+fn update_config_validates_pfm_routes() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let mut cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+    cfg.denom_to_pfm_map = BTreeMap::from([(
+        "ibc/ABC123".to_string(),
+        PacketForwardMiddlewareConfig::new("channel-2", "channel-3", None),
+    )]);
+
+    // Instantiate IBC transfer contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Update config and route the same denom a second time
+    cfg.denom_to_pfm_map.insert(
+        "ibc/abc123".to_string(),
+        PacketForwardMiddlewareConfig::new("channel-4", "channel-5", None),
+    );
+
+    // Execute update config action
+    suite.update_config(lib.clone(), cfg).unwrap();
+}
+
 #[test]
 fn update_config_with_valid_config() {
     let mut suite = IbcTransferTestSuite::default();
@@ -435,3 +599,310 @@ fn eureka_ibc_transfer_fails_no_eureka_config() {
     // Execute IBC transfer
     suite.execute_eureka_ibc_transfer(lib).unwrap();
 }
+
+// Minimum transfer amount tests
+
+#[test]
+#[should_panic(
+    expected = "Invalid IBC transfer config: min_transfer_amount cannot be larger than the fixed amount."
+)]
+fn instantiate_fails_for_min_transfer_amount_above_fixed_amount() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let mut cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FixedAmount(ONE_MILLION.into()),
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+    cfg.min_transfer_amount = Some((ONE_MILLION + 1).into());
+
+    // Instantiate IBC transfer contract
+    suite.ibc_transfer_init(&cfg);
+}
+
+#[test]
+fn ibc_transfer_skipped_below_min_transfer_amount() {
+    let mut suite = IbcTransferTestSuite::new(Some((ONE_MILLION, NTRN.to_string())));
+
+    let mut cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+    cfg.min_transfer_amount = Some((ONE_MILLION + 1).into());
+
+    // Instantiate contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Execute IBC transfer: it should be a no-op
+    let res = suite.execute_ibc_transfer(lib).unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "method" && a.value == "ibc-transfer-skipped")));
+
+    // Verify the input account balance is untouched
+    suite.assert_balance(suite.input_addr(), ONE_MILLION, NTRN);
+}
+
+#[test]
+#[should_panic(expected = "No Eureka config provided.")]
+fn ibc_transfer_proceeds_at_min_transfer_amount() {
+    let mut suite = IbcTransferTestSuite::new(Some((ONE_MILLION, NTRN.to_string())));
+
+    let mut cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+    cfg.min_transfer_amount = Some(ONE_MILLION.into());
+
+    // Instantiate contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Execute Eureka transfer: the guard lets it through, so it fails on the missing config
+    suite.execute_eureka_ibc_transfer(lib).unwrap();
+}
+
+// Gas reserve tests
+
+#[test]
+fn ibc_transfer_skipped_when_balance_within_gas_reserve() {
+    let mut suite = IbcTransferTestSuite::new(Some((ONE_MILLION, NTRN.to_string())));
+
+    let cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FullAmount,
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+        )
+        .with_gas_reserve(GasReserve::new(NTRN, ONE_MILLION));
+
+    // Instantiate contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Execute IBC transfer: the whole balance is reserved, so it should be a no-op
+    let res = suite.execute_ibc_transfer(lib).unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "reason" && a.value == "balance within gas_reserve")));
+
+    // Verify the input account balance is untouched
+    suite.assert_balance(suite.input_addr(), ONE_MILLION, NTRN);
+}
+
+#[test]
+#[should_panic(
+    expected = "Insufficient balance for denom 'untrn' in config (required: 1000000000000, available: 999999999999)."
+)]
+fn ibc_transfer_fixed_amount_fails_within_gas_reserve() {
+    let mut suite = IbcTransferTestSuite::new(Some((ONE_MILLION, NTRN.to_string())));
+
+    let cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FixedAmount(ONE_MILLION.into()),
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+        )
+        .with_gas_reserve(GasReserve::new(NTRN, 1_u128));
+
+    // Instantiate contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Execute IBC transfer: the reserve leaves less than the fixed amount
+    suite.execute_ibc_transfer(lib).unwrap();
+}
+
+#[test]
+fn ibc_transfer_skipped_while_precondition_is_not_met() {
+    let mut suite = IbcTransferTestSuite::new(Some((ONE_MILLION, NTRN.to_string())));
+    let sentinel_addr = suite.api().addr_make("withdraw_queue");
+
+    let cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FullAmount,
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+        )
+        .with_precondition(BalancePredicate::new(
+            &sentinel_addr,
+            NTRN,
+            Comparator::GreaterThanOrEqual,
+            1_000u128,
+        ));
+
+    // Instantiate contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // The sentinel balance is below the threshold
+    suite.init_balance(&sentinel_addr, vec![coin(999, NTRN)]);
+
+    // Execute IBC transfer: it should be a no-op
+    let res = suite.execute_ibc_transfer(lib).unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "reason" && a.value == "precondition not met")));
+
+    // Verify the input account balance is untouched
+    suite.assert_balance(suite.input_addr(), ONE_MILLION, NTRN);
+}
+
+#[test]
+#[should_panic(expected = "No Eureka config provided.")]
+fn ibc_transfer_proceeds_once_precondition_is_met() {
+    let mut suite = IbcTransferTestSuite::new(Some((ONE_MILLION, NTRN.to_string())));
+    let sentinel_addr = suite.api().addr_make("withdraw_queue");
+
+    let cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FullAmount,
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+        )
+        .with_precondition(BalancePredicate::new(
+            &sentinel_addr,
+            NTRN,
+            Comparator::GreaterThanOrEqual,
+            1_000u128,
+        ));
+
+    // Instantiate contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // The sentinel balance reaches the threshold
+    suite.init_balance(&sentinel_addr, vec![coin(1_000, NTRN)]);
+
+    // Execute Eureka transfer: the precondition lets it through, so it fails on the missing config
+    suite.execute_eureka_ibc_transfer(lib).unwrap();
+}
+
+// Migration tests
+
+#[test]
+fn migrate_bumps_version_and_preserves_config() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FixedAmount(ONE_MILLION.into()),
+        "memo".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+
+    // Instantiate IBC transfer contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Migrate from a previous version without patching the config
+    suite.migrate(lib.clone(), "0.1.0", None).unwrap();
+
+    // Verify the stored version is bumped
+    let version = cw2::get_contract_version(suite.app().contract_storage(&lib).as_ref()).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // Verify owner and processor are preserved
+    let owner_res: Ownership<Addr> = suite.query_wasm(&lib, &QueryMsg::Ownership {});
+    assert_eq!(owner_res.owner, Some(suite.owner().clone()));
+    let processor_addr: Addr = suite.query_wasm(&lib, &QueryMsg::GetProcessor {});
+    assert_eq!(processor_addr, suite.processor().clone());
+
+    // Verify library config is preserved
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        lib_cfg,
+        Config::new(
+            suite.input_addr().clone(),
+            suite.output_addr().clone(),
+            CheckedDenom::Native(NTRN.into()),
+            IbcTransferAmount::FixedAmount(ONE_MILLION.into()),
+            "memo".to_string(),
+            cfg.remote_chain_info.clone()
+        )
+    );
+}
+
+#[test]
+fn migrate_patches_config_fields() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FixedAmount(ONE_MILLION.into()),
+        "memo".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+
+    // Instantiate IBC transfer contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Migrate and rotate the channel, leaving the other fields untouched
+    suite
+        .migrate(
+            lib.clone(),
+            "0.1.0",
+            Some(LibraryConfigUpdate {
+                input_addr: None,
+                output_addr: None,
+                amount: None,
+                denom: None,
+                memo: None,
+                remote_chain_info: Some(RemoteChainInfo::new(
+                    "channel-2".to_string(),
+                    Some(600u64.into()),
+                )),
+                denom_to_pfm_map: None,
+                eureka_config: valence_library_utils::OptionUpdate::None,
+                min_transfer_amount: valence_library_utils::OptionUpdate::None,
+                denom_selection: valence_library_utils::OptionUpdate::None,
+                gas_reserve: valence_library_utils::OptionUpdate::None,
+                precondition: valence_library_utils::OptionUpdate::None,
+                transfer_defaults: valence_library_utils::OptionUpdate::None,
+            }),
+        )
+        .unwrap();
+
+    // Verify library config
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        lib_cfg,
+        Config::new(
+            suite.input_addr().clone(),
+            suite.output_addr().clone(),
+            CheckedDenom::Native(NTRN.into()),
+            IbcTransferAmount::FixedAmount(ONE_MILLION.into()),
+            "memo".to_string(),
+            RemoteChainInfo::new("channel-2".to_string(), Some(600u64.into())),
+        )
+    );
+
+    // Verify the raw config is patched as well
+    let raw_cfg: LibraryConfig = suite.query_wasm(&lib, &QueryMsg::GetRawLibraryConfig {});
+    assert_eq!(raw_cfg.remote_chain_info.channel_id, "channel-2");
+}
+
+#[test]
+#[should_panic(expected = "Cannot migrate from newer version")]
+fn migrate_rejects_downgrade() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+
+    // Instantiate IBC transfer contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // The stored version is newer than the code
+    suite.migrate(lib, "99.0.0", None).unwrap();
+}
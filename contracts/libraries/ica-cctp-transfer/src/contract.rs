@@ -1,12 +1,17 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult,
+};
 use valence_library_utils::{
     error::LibraryError,
-    msg::{ExecuteMsg, InstantiateMsg},
+    msg::{InstantiateMsg, MigrateMsg},
 };
 
-use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
+use crate::{
+    msg::{Config, ExecuteMsg, LibraryConfig, LibraryConfigUpdate, QueryMsg},
+    state::{BURNS, LAST_EXPECTED_COMPLETION},
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
@@ -27,8 +32,16 @@ pub fn execute(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    msg: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
+    msg: ExecuteMsg,
 ) -> Result<Response, LibraryError> {
+    let msg = match msg {
+        ExecuteMsg::Library(msg) => msg,
+        // Callbacks are handled even while the library is paused, so that no nonce is lost
+        ExecuteMsg::IcaCallback(callback) => {
+            return callbacks::handle_ica_callback(deps, env, info, callback)
+        }
+    };
+
     valence_library_base::execute(
         deps,
         env,
@@ -39,53 +52,598 @@ pub fn execute(
     )
 }
 
-mod functions {
-    use cosmwasm_std::{AnyMsg, Binary, DepsMut, Env, MessageInfo, Response};
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, LibraryError> {
+    match msg.id {
+        callbacks::BURN_SUBMITTED_REPLY_ID => callbacks::handle_burn_submitted(deps, msg),
+        _ => Err(LibraryError::Std(StdError::generic_err("Unknown reply id"))),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    migrations::migrate_legacy_config(deps.storage)?;
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
+    )
+}
+
+pub(crate) mod functions {
+    use cosmwasm_std::{
+        to_json_binary, AnyMsg, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, SubMsg,
+        Timestamp, Uint128,
+    };
     use prost::{Message, Name};
     use valence_library_utils::{
         error::LibraryError,
-        ica::{execute_on_behalf_of, get_remote_ica_address},
+        events::TransferEvent,
+        ica::{execute_on_behalf_of_with_callback, get_remote_ica_address, get_remote_ica_balance},
+        is_library_approved,
+        msg::SimulatedAmount,
     };
 
     use crate::{
-        msg::{Config, FunctionMsgs},
-        proto::MsgDepositForBurn,
+        msg::{
+            BurnRecord, Config, CooldownBehavior, ExpectedCompletion, FunctionMsgs,
+            MaxAmountBehavior, TransferAmount,
+        },
+        proto::{MsgDepositForBurn, MsgDepositForBurnWithCaller},
+        state::{LAST_EXPECTED_COMPLETION, LAST_SUCCESSFUL_BURNS, PENDING_BURNS},
     };
 
+    use super::callbacks::BURN_SUBMITTED_REPLY_ID;
+
     pub fn process_function(
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         _info: MessageInfo,
         msg: FunctionMsgs,
         cfg: Config,
     ) -> Result<Response, LibraryError> {
+        // The input account can be any ICA account, not only the outbound one of the program,
+        // so make sure it approved the library before burning from it
+        if !is_library_approved(&deps.querier, &cfg.input_addr, &env.contract.address)? {
+            return Err(LibraryError::ExecutionError(format!(
+                "Library is not approved on input account {}.",
+                cfg.input_addr
+            )));
+        }
+
         let remote_address = get_remote_ica_address(deps.as_ref(), cfg.input_addr.as_str())?;
 
         match msg {
             FunctionMsgs::Transfer {} => {
-                // Create the proto message
-                let proto_msg = MsgDepositForBurn {
-                    from: remote_address,
-                    amount: cfg.amount.to_string(),
-                    destination_domain: cfg.destination_domain_id,
-                    mint_recipient: cfg.mint_recipient.to_vec(),
-                    burn_token: cfg.denom,
-                };
+                if let Some((destination_domain_id, cooldown_end)) =
+                    active_burn_cooldown_end(deps.as_ref(), &env, &cfg)?
+                {
+                    return match cfg.burn_cooldown.as_ref().map(|cooldown| cooldown.behavior) {
+                        Some(CooldownBehavior::Skip) => Ok(Response::new()
+                            .add_attribute("method", "cctp_transfer")
+                            .add_attribute("skipped", "burn_cooldown_active")),
+                        _ => Err(burn_cooldown_error(destination_domain_id, cooldown_end)),
+                    };
+                }
 
-                // Create the Any
-                let any_msg = AnyMsg {
-                    type_url: MsgDepositForBurn::type_url(),
-                    value: Binary::from(proto_msg.encode_to_vec()),
+                let (amount, input_balance) = resolve_transfer(deps.as_ref(), &cfg)?;
+                let burns = resolve_burns(&cfg, amount, env.block.time)?;
+                let burnt_amount: Uint128 = burns.iter().map(|burn| burn.amount).sum();
+
+                // Don't burn into a destination domain that won't mint within the SLA
+                let expected_completion = match &cfg.attestation_sla {
+                    Some(attestation_sla) => {
+                        for burn in &burns {
+                            attestation_sla.check(burn.destination_domain_id)?;
+                        }
+                        // All the burns share the same deadline, the last one is recorded
+                        let expected_completion = burns.last().map(|burn| ExpectedCompletion {
+                            amount: burn.amount,
+                            destination_domain_id: burn.destination_domain_id,
+                            deadline: env
+                                .block
+                                .time
+                                .plus_seconds(attestation_sla.completion_seconds),
+                        });
+                        if let Some(expected_completion) = &expected_completion {
+                            LAST_EXPECTED_COMPLETION.save(deps.storage, expected_completion)?;
+                        }
+                        expected_completion
+                    }
+                    None => None,
                 };
 
-                let input_account_msgs = execute_on_behalf_of(vec![any_msg], &cfg.input_addr)?;
+                let mut response = Response::new()
+                    .add_attribute("method", "cctp_transfer")
+                    .add_attribute("amount", burnt_amount)
+                    .add_attribute("burn_token", &cfg.denom);
+
+                for burn in burns {
+                    // Each burn is executed in its own ICA transaction, so that the input account
+                    // reports the outcome of each burn with its own callback
+                    let any_msg = create_burn_msg(&cfg, remote_address.clone(), &burn);
+                    let input_account_msg =
+                        execute_on_behalf_of_with_callback(vec![any_msg], &cfg.input_addr)?;
 
-                Ok(Response::new()
-                    .add_message(input_account_msgs)
-                    .add_attribute("method", "cctp_transfer"))
+                    let transfer_event = TransferEvent {
+                        input_balance,
+                        ..TransferEvent::new("cctp_transfer", &cfg.denom)
+                            .with_amount(burn.amount)
+                            .with_destination_domain(burn.destination_domain_id)
+                    };
+
+                    // The nonce of the burn is assigned on Noble once the ICA executes it, so the burn waits,
+                    // under the ICA packet returned by the input account, for its callback to be indexed by its nonce
+                    response = response
+                        .add_submessage(
+                            SubMsg::reply_on_success(input_account_msg, BURN_SUBMITTED_REPLY_ID)
+                                .with_payload(to_json_binary(&burn)?),
+                        )
+                        .add_attribute(
+                            "destination_domain_id",
+                            burn.destination_domain_id.to_string(),
+                        )
+                        .add_attribute(
+                            "mint_recipient",
+                            format!("0x{}", hex::encode(&burn.mint_recipient)),
+                        )
+                        .add_event(transfer_event);
+                }
+
+                match expected_completion {
+                    Some(expected_completion) => Ok(response.add_attribute(
+                        "expected_completion",
+                        expected_completion.deadline.seconds().to_string(),
+                    )),
+                    None => Ok(response),
+                }
             }
         }
     }
+
+    /// Computes the amount to burn, querying the balance of the ICA if the amount depends on it
+    pub(crate) fn resolve_transfer_amount(
+        deps: Deps,
+        cfg: &Config,
+    ) -> Result<Uint128, LibraryError> {
+        resolve_transfer(deps, cfg).map(|(amount, _)| amount)
+    }
+
+    /// Amount that a transfer would burn now, which fails like the transfer would
+    pub(crate) fn simulate_amount(
+        deps: Deps,
+        env: &Env,
+        cfg: &Config,
+    ) -> Result<SimulatedAmount, LibraryError> {
+        if let Some((destination_domain_id, cooldown_end)) =
+            active_burn_cooldown_end(deps, env, cfg)?
+        {
+            return match cfg.burn_cooldown.as_ref().map(|cooldown| cooldown.behavior) {
+                Some(CooldownBehavior::Skip) => {
+                    Ok(SimulatedAmount::skipped(&cfg.denom, "burn_cooldown_active"))
+                }
+                _ => Err(burn_cooldown_error(destination_domain_id, cooldown_end)),
+            };
+        }
+
+        let amount = resolve_transfer_amount(deps, cfg)?;
+        let burns = resolve_burns(cfg, amount, env.block.time)?;
+        Ok(SimulatedAmount::new(
+            &cfg.denom,
+            burns.iter().map(|burn| burn.amount).sum(),
+        ))
+    }
+
+    /// First destination domain of the transfer whose burn cooldown is still active, along with the end
+    /// of the cooldown. The cooldown is started by the last successful burn to the domain and by the burns
+    /// to it still waiting for their outcome, so that a failed burn doesn't delay the next one
+    pub(crate) fn active_burn_cooldown_end(
+        deps: Deps,
+        env: &Env,
+        cfg: &Config,
+    ) -> Result<Option<(u32, Timestamp)>, LibraryError> {
+        let Some(burn_cooldown) = &cfg.burn_cooldown else {
+            return Ok(None);
+        };
+
+        let pending_burns = PENDING_BURNS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, burn)| burn))
+            .collect::<Result<Vec<BurnRecord>, _>>()?;
+
+        for destination_domain_id in cfg.destination_domain_ids() {
+            let last_successful_burn =
+                LAST_SUCCESSFUL_BURNS.may_load(deps.storage, destination_domain_id)?;
+            let last_burn = pending_burns
+                .iter()
+                .filter(|burn| burn.destination_domain_id == destination_domain_id)
+                .map(|burn| burn.sent_at)
+                .chain(last_successful_burn)
+                .max();
+
+            if let Some(cooldown_end) = last_burn
+                .map(|burn_time| burn_cooldown.end(burn_time))
+                .filter(|cooldown_end| env.block.time < *cooldown_end)
+            {
+                return Ok(Some((destination_domain_id, cooldown_end)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn burn_cooldown_error(destination_domain_id: u32, cooldown_end: Timestamp) -> LibraryError {
+        LibraryError::ExecutionError(format!(
+            "Burn cooldown of destination domain {} is active until {}.",
+            destination_domain_id,
+            cooldown_end.seconds()
+        ))
+    }
+
+    /// Computes the amount to burn along with the ICA balance, if it had to be queried
+    fn resolve_transfer(
+        deps: Deps,
+        cfg: &Config,
+    ) -> Result<(Uint128, Option<Uint128>), LibraryError> {
+        let (amount, input_balance) = match &cfg.amount {
+            TransferAmount::Fixed(amount) => (*amount, None),
+            balance_based => {
+                let balance = get_remote_ica_balance(deps, cfg.input_addr.as_str(), &cfg.denom)?;
+                // The burns waiting for their outcome are still in the last known balance, but already spent
+                let pending_amount = PENDING_BURNS
+                    .range(deps.storage, None, None, Order::Ascending)
+                    .map(|item| item.map(|(_, burn)| burn))
+                    .collect::<Result<Vec<BurnRecord>, _>>()?
+                    .into_iter()
+                    .filter(|burn| burn.burn_token == cfg.denom)
+                    .fold(Uint128::zero(), |total, burn| {
+                        total.saturating_add(burn.amount)
+                    });
+                let available = balance.amount.saturating_sub(pending_amount);
+                (
+                    balance_based.resolve(available, cfg.rounding)?,
+                    Some(available),
+                )
+            }
+        };
+
+        if amount.is_zero() {
+            return Err(LibraryError::ExecutionError(format!(
+                "Nothing to transfer: no {} balance available in the input account.",
+                cfg.denom
+            )));
+        }
+
+        // Cap the amount burnt in a single transfer
+        let amount = match cfg.max_amount {
+            Some(max_amount) if amount > max_amount => match cfg.max_amount_behavior {
+                MaxAmountBehavior::Clamp => max_amount,
+                MaxAmountBehavior::Error => {
+                    return Err(LibraryError::ExecutionError(format!(
+                        "Transfer amount {amount} exceeds the max amount {max_amount}."
+                    )))
+                }
+            },
+            _ => amount,
+        };
+
+        Ok((amount, input_balance))
+    }
+
+    /// Splits the transfer amount into the burns sent to the input account: one per burn leg,
+    /// or a single burn of the whole amount to the destination domain of the config
+    pub(crate) fn resolve_burns(
+        cfg: &Config,
+        amount: Uint128,
+        sent_at: Timestamp,
+    ) -> Result<Vec<BurnRecord>, LibraryError> {
+        if cfg.burn_legs.is_empty() {
+            return Ok(vec![BurnRecord {
+                amount,
+                burn_token: cfg.denom.clone(),
+                destination_domain_id: cfg.destination_domain_id,
+                mint_recipient: cfg.mint_recipient.clone(),
+                sent_at,
+            }]);
+        }
+
+        let mut burns = Vec::with_capacity(cfg.burn_legs.len());
+        for leg in &cfg.burn_legs {
+            let leg_amount = leg.amount_or_ratio.resolve(amount, cfg.rounding)?;
+            if leg_amount.is_zero() {
+                return Err(LibraryError::ExecutionError(format!(
+                    "Nothing to burn to destination domain {} out of the transfer amount {amount}.",
+                    leg.destination_domain_id
+                )));
+            }
+
+            burns.push(BurnRecord {
+                amount: leg_amount,
+                burn_token: cfg.denom.clone(),
+                destination_domain_id: leg.destination_domain_id,
+                mint_recipient: leg.mint_recipient.clone(),
+                sent_at,
+            });
+        }
+
+        let legs_amount: Uint128 = burns.iter().map(|burn| burn.amount).sum();
+        if legs_amount > amount {
+            return Err(LibraryError::ExecutionError(format!(
+                "Burn legs add up to {legs_amount}, more than the transfer amount {amount}."
+            )));
+        }
+
+        Ok(burns)
+    }
+
+    /// Creates the message of `burn`: the legacy message, unless a destination caller is configured.
+    pub(crate) fn create_burn_msg(cfg: &Config, from: String, burn: &BurnRecord) -> AnyMsg {
+        if let Some(destination_caller) = &cfg.destination_caller {
+            // Create the proto message that only the destination caller can receive
+            let proto_msg = MsgDepositForBurnWithCaller {
+                from,
+                amount: burn.amount.to_string(),
+                destination_domain: burn.destination_domain_id,
+                mint_recipient: burn.mint_recipient.to_vec(),
+                burn_token: cfg.denom.clone(),
+                destination_caller: destination_caller.to_vec(),
+            };
+
+            // Create the Any
+            AnyMsg {
+                type_url: MsgDepositForBurnWithCaller::type_url(),
+                value: Binary::from(proto_msg.encode_to_vec()),
+            }
+        } else {
+            // Create the proto message
+            let proto_msg = MsgDepositForBurn {
+                from,
+                amount: burn.amount.to_string(),
+                destination_domain: burn.destination_domain_id,
+                mint_recipient: burn.mint_recipient.to_vec(),
+                burn_token: cfg.denom.clone(),
+            };
+
+            // Create the Any
+            AnyMsg {
+                type_url: MsgDepositForBurn::type_url(),
+                value: Binary::from(proto_msg.encode_to_vec()),
+            }
+        }
+    }
+}
+
+pub(crate) mod callbacks {
+    use cosmwasm_std::{
+        from_json, Coin, DepsMut, Env, Event, MessageInfo, Reply, Response, SubMsgResult,
+    };
+    use cw_utils::parse_execute_response_data;
+    use valence_account_utils::ica::{IcaCallbackMsg, IcaTxResult, IcaTxSubmission};
+    use valence_library_utils::{
+        error::{LibraryError, UnauthorizedReason},
+        ica::deduct_remote_ica_balances,
+        stats::record_transfers,
+    };
+
+    use crate::{
+        msg::{BurnRecord, Config},
+        state::{BURNS, LAST_SUCCESSFUL_BURNS, PENDING_BURNS},
+        utils::burn_nonce,
+    };
+
+    /// Id of the reply of the input account once it submitted a burn, with the ICA packet carrying it
+    pub const BURN_SUBMITTED_REPLY_ID: u64 = 0;
+
+    /// Type of the event emitted once the outcome of a burn is reported by the input account,
+    /// with the outcome, the burn, the CCTP nonce it was assigned and the ICA packet that carried it.
+    /// Once emitted by a contract, the event type is prefixed with `wasm-`.
+    pub const BURN_RESULT_EVENT_TYPE: &str = "valence.ica_cctp_transfer.burn_result";
+
+    /// Keeps the submitted burn pending under the ICA packet that carries it, so that the outcome
+    /// reported for that packet is matched to it whatever the order the packets are acknowledged in
+    pub fn handle_burn_submitted(deps: DepsMut, msg: Reply) -> Result<Response, LibraryError> {
+        let SubMsgResult::Ok(response) = msg.result else {
+            // Only successful submissions reply
+            return Err(LibraryError::ExecutionError(
+                "Burn submission failed.".to_string(),
+            ));
+        };
+        #[allow(deprecated)]
+        let data = response.data.ok_or_else(|| {
+            LibraryError::ExecutionError(
+                "Input account did not return the ICA packet of the burn.".to_string(),
+            )
+        })?;
+        let execute_data = parse_execute_response_data(&data)
+            .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+        let submission: IcaTxSubmission = from_json(execute_data.data.unwrap_or_default())?;
+        let burn: BurnRecord = from_json(&msg.payload)?;
+
+        let packet = (submission.channel_id, submission.sequence);
+        PENDING_BURNS.save(deps.storage, packet.clone(), &burn)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "burn_submitted")
+            .add_attribute("ica_channel_id", packet.0)
+            .add_attribute("ica_sequence", packet.1.to_string()))
+    }
+
+    pub fn handle_ica_callback(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: IcaCallbackMsg,
+    ) -> Result<Response, LibraryError> {
+        let cfg: Config = valence_library_base::load_config(deps.storage)?;
+        // Only the input account reports the outcome of the burns it executed
+        if info.sender != cfg.input_addr {
+            return Err(LibraryError::Unauthorized(
+                UnauthorizedReason::NotAllowed {},
+            ));
+        }
+
+        let IcaCallbackMsg::IcaTxCallback {
+            channel_id,
+            sequence,
+            result,
+        } = msg;
+
+        // The burn stops waiting whatever its outcome, failed and timed out burns are never assigned a nonce
+        let packet = (channel_id.clone(), sequence);
+        let burn = PENDING_BURNS
+            .may_load(deps.storage, packet.clone())?
+            .ok_or_else(|| {
+                LibraryError::ExecutionError(format!(
+                    "No burn waiting for the outcome of ICA packet {sequence} on {channel_id}."
+                ))
+            })?;
+        PENDING_BURNS.remove(deps.storage, packet);
+
+        let mut event = Event::new(BURN_RESULT_EVENT_TYPE)
+            .add_attribute("amount", burn.amount)
+            .add_attribute("burn_token", &burn.burn_token)
+            .add_attribute(
+                "destination_domain_id",
+                burn.destination_domain_id.to_string(),
+            )
+            .add_attribute(
+                "mint_recipient",
+                format!("0x{}", hex::encode(&burn.mint_recipient)),
+            )
+            .add_attribute("ica_channel_id", channel_id)
+            .add_attribute("ica_sequence", sequence.to_string());
+
+        // Everything that depends on the burn having moved funds happens on success only, failed and
+        // timed out burns are sent again by a later execution and take effect then
+        let mut response = Response::new();
+        let outcome = match result {
+            IcaTxResult::Success { data } => {
+                // A host that doesn't return the burn response leaves the burn unindexed, it still succeeded
+                if let Some(nonce) = burn_nonce(&data) {
+                    BURNS.save(deps.storage, nonce, &burn)?;
+                    event = event.add_attribute("nonce", nonce.to_string());
+                }
+                // Only successful burns reset the burn cooldown of their destination domain
+                LAST_SUCCESSFUL_BURNS.save(
+                    deps.storage,
+                    burn.destination_domain_id,
+                    &burn.sent_at,
+                )?;
+                // The burnt amount left the ICA, so that the next balance based amount doesn't burn it again
+                // before the last known balance of the input account is refreshed
+                response = response.add_message(deduct_remote_ica_balances(
+                    vec![Coin {
+                        denom: burn.burn_token.clone(),
+                        amount: burn.amount,
+                    }],
+                    &cfg.input_addr,
+                )?);
+                // Failed and timed out burns never moved any funds, so they aren't counted
+                record_transfers(
+                    deps.storage,
+                    env.block.height,
+                    &[(burn.burn_token.clone(), burn.amount)],
+                )?;
+                "succeeded"
+            }
+            IcaTxResult::Error { details } => {
+                event = event.add_attribute("reason", details);
+                "failed"
+            }
+            IcaTxResult::Timeout {} => "timed_out",
+        };
+
+        Ok(response
+            .add_attribute("method", "ica_tx_callback")
+            .add_attribute("result", outcome)
+            .add_event(event.add_attribute("result", outcome)))
+    }
+}
+
+pub(crate) mod migrations {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{Addr, Binary, StdResult, Storage, Uint128};
+    use valence_library_utils::{
+        raw_config::{load_raw_library_config, save_raw_library_config},
+        LibraryAccountType,
+    };
+
+    use crate::{
+        domain::DestinationDomain,
+        msg::{Config, LibraryConfig, TransferAmount},
+    };
+
+    // Config of the versions of the library that burnt a fixed amount to a numeric domain id
+    #[cw_serde]
+    struct LegacyConfig {
+        input_addr: Addr,
+        amount: Uint128,
+        denom: String,
+        destination_domain_id: u32,
+        mint_recipient: Binary,
+    }
+
+    #[cw_serde]
+    struct LegacyLibraryConfig {
+        input_addr: LibraryAccountType,
+        amount: Uint128,
+        denom: String,
+        destination_domain_id: u32,
+        mint_recipient: Binary,
+    }
+
+    /// Converts the configs stored by the versions of the library with a fixed `amount` into configs
+    /// burning that `TransferAmount::Fixed` amount. Configs already in the current format are kept.
+    pub fn migrate_legacy_config(storage: &mut dyn Storage) -> StdResult<()> {
+        if valence_library_base::load_config::<Config>(storage).is_ok() {
+            return Ok(());
+        }
+        let Ok(legacy) = valence_library_base::load_config::<LegacyConfig>(storage) else {
+            return Ok(());
+        };
+
+        // Domain ids that aren't known could only be configured as numeric ids
+        let known_domain = DestinationDomain::from_domain_id(legacy.destination_domain_id);
+        let allow_unknown_domain = known_domain.is_none();
+        valence_library_base::save_config(
+            storage,
+            &Config::new(
+                legacy.input_addr,
+                TransferAmount::Fixed(legacy.amount),
+                legacy.denom,
+                legacy.destination_domain_id,
+                allow_unknown_domain,
+                legacy.mint_recipient,
+                None,
+                None,
+            ),
+        )?;
+
+        if let Ok(legacy) = load_raw_library_config::<LegacyLibraryConfig>(storage) {
+            save_raw_library_config(
+                storage,
+                &LibraryConfig::new(
+                    legacy.input_addr,
+                    TransferAmount::Fixed(legacy.amount),
+                    legacy.denom,
+                    known_domain.unwrap_or(DestinationDomain::Custom(legacy.destination_domain_id)),
+                    allow_unknown_domain,
+                    legacy.mint_recipient,
+                    None,
+                    None,
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
 }
 
 mod execute {
@@ -105,7 +663,7 @@ mod execute {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Ownership {} => {
             to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
@@ -113,6 +671,21 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetProcessor {} => {
             to_json_binary(&valence_library_base::get_processor(deps.storage)?)
         }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        QueryMsg::IsApproved {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&valence_library_utils::is_library_approved(
+                &deps.querier,
+                &config.input_addr,
+                &env.contract.address,
+            )?)
+        }
         QueryMsg::GetLibraryConfig {} => {
             let config: Config = valence_library_base::load_config(deps.storage)?;
             to_json_binary(&config)
@@ -122,5 +695,18 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 valence_library_utils::raw_config::query_raw_library_config(deps.storage)?;
             to_json_binary(&raw_config)
         }
+        QueryMsg::SimulateAmount {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            let simulated_amount = functions::simulate_amount(deps, &env, &config)
+                .map_err(|err| StdError::generic_err(err.to_string()))?;
+            to_json_binary(&simulated_amount)
+        }
+        QueryMsg::ExpectedCompletion {} => {
+            to_json_binary(&LAST_EXPECTED_COMPLETION.may_load(deps.storage)?)
+        }
+        QueryMsg::Stats {} => to_json_binary(&valence_library_utils::stats::query_transfer_stats(
+            deps.storage,
+        )?),
+        QueryMsg::Burn { nonce } => to_json_binary(&BURNS.may_load(deps.storage, nonce)?),
     }
 }
@@ -0,0 +1,94 @@
+use cosmwasm_schema::cw_serde;
+
+use crate::error::CctpError;
+
+/// CCTP destination domain, either one of the known domains or a numeric domain id
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum DestinationDomain {
+    Ethereum,
+    Avalanche,
+    Optimism,
+    Arbitrum,
+    Noble,
+    Solana,
+    Base,
+    Polygon,
+    Sui,
+    Aptos,
+    Unichain,
+    Linea,
+    // Numeric domain id override
+    Custom(u32),
+}
+
+/// Registry of the known CCTP domains.
+/// For more information, check https://developers.circle.com/stablecoins/supported-domains
+pub const KNOWN_DOMAINS: [DestinationDomain; 12] = [
+    DestinationDomain::Ethereum,
+    DestinationDomain::Avalanche,
+    DestinationDomain::Optimism,
+    DestinationDomain::Arbitrum,
+    DestinationDomain::Noble,
+    DestinationDomain::Solana,
+    DestinationDomain::Base,
+    DestinationDomain::Polygon,
+    DestinationDomain::Sui,
+    DestinationDomain::Aptos,
+    DestinationDomain::Unichain,
+    DestinationDomain::Linea,
+];
+
+impl DestinationDomain {
+    /// Returns the domain id, without checking it against the registry
+    pub fn id(&self) -> u32 {
+        match self {
+            DestinationDomain::Ethereum => 0,
+            DestinationDomain::Avalanche => 1,
+            DestinationDomain::Optimism => 2,
+            DestinationDomain::Arbitrum => 3,
+            DestinationDomain::Noble => 4,
+            DestinationDomain::Solana => 5,
+            DestinationDomain::Base => 6,
+            DestinationDomain::Polygon => 7,
+            DestinationDomain::Sui => 8,
+            DestinationDomain::Aptos => 9,
+            DestinationDomain::Unichain => 10,
+            DestinationDomain::Linea => 11,
+            DestinationDomain::Custom(domain_id) => *domain_id,
+        }
+    }
+
+    /// Returns the known domain registered for the given domain id, if any
+    pub fn from_domain_id(domain_id: u32) -> Option<Self> {
+        KNOWN_DOMAINS
+            .into_iter()
+            .find(|domain| domain.id() == domain_id)
+    }
+
+    /// Whether the domain is an EVM chain, on which the mint recipient is a 20 bytes address
+    pub fn is_evm(&self) -> bool {
+        matches!(
+            self,
+            DestinationDomain::Ethereum
+                | DestinationDomain::Avalanche
+                | DestinationDomain::Optimism
+                | DestinationDomain::Arbitrum
+                | DestinationDomain::Base
+                | DestinationDomain::Polygon
+                | DestinationDomain::Unichain
+                | DestinationDomain::Linea
+        )
+    }
+
+    /// Resolves the domain to its domain id, rejecting ids that are not in the
+    /// registry unless `allow_unknown_domain` is set
+    pub fn domain_id(&self, allow_unknown_domain: bool) -> Result<u32, CctpError> {
+        let domain_id = self.id();
+        if !allow_unknown_domain && Self::from_domain_id(domain_id).is_none() {
+            return Err(CctpError::UnknownDomain(domain_id));
+        }
+
+        Ok(domain_id)
+    }
+}
@@ -0,0 +1,77 @@
+use cosmwasm_std::{Decimal, Uint128};
+use thiserror::Error;
+use valence_library_utils::error::LibraryError;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CctpError {
+    #[error("Unknown CCTP destination domain id: {0}. Set allow_unknown_domain to use it anyway.")]
+    UnknownDomain(u32),
+
+    #[error("Invalid EVM address: not a valid hex string ({0})")]
+    InvalidHexAddress(String),
+
+    #[error("Invalid EVM address: expected 20 bytes, got {0}")]
+    InvalidAddressLength(usize),
+
+    #[error("Invalid EVM address: {0} does not match its EIP-55 checksum")]
+    InvalidChecksum(String),
+
+    #[error("Invalid mint recipient: expected {expected} bytes, got {got}")]
+    InvalidMintRecipientLength { expected: usize, got: usize },
+
+    #[error("Invalid mint recipient: {0} is a bech32 address, but CCTP mints to the bytes representation of the destination address")]
+    Bech32MintRecipient(String),
+
+    #[error("Invalid mint recipient: destination domain {0} is an EVM chain, so the recipient must be a 20 bytes address left-padded with zeroes")]
+    NotAnEvmMintRecipient(u32),
+
+    #[error("Invalid hook data: expected between 1 and {max} bytes, got {got}")]
+    InvalidHookDataLength { max: usize, got: usize },
+
+    #[error("Invalid destination caller: expected {expected} bytes, got {got}")]
+    InvalidDestinationCallerLength { expected: usize, got: usize },
+
+    #[error(
+        "Invalid destination caller: it cannot be zero, any caller is allowed by not setting it"
+    )]
+    ZeroDestinationCaller,
+
+    #[error("Unsupported CCTP v2 config: max_fee and min_finality_threshold can't be set until Noble accepts circle.cctp.v2 burns")]
+    CctpV2Unsupported,
+
+    #[error("Invalid transfer config: amount {amount} exceeds the max amount {max_amount}")]
+    AmountExceedsMaxAmount {
+        amount: Uint128,
+        max_amount: Uint128,
+    },
+
+    #[error("Invalid transfer amount: percentage {0} must be greater than zero and at most one")]
+    InvalidPercentage(Decimal),
+
+    #[error("Unsupported CCTP token: {0} can't be burnt from Noble")]
+    UnsupportedToken(String),
+
+    #[error("Unsupported CCTP token: {denom} is not minted on destination domain {domain_id}")]
+    UnsupportedTokenDomain { denom: String, domain_id: u32 },
+
+    #[error("Invalid burn leg: ratio {0} must be greater than zero and at most one")]
+    InvalidBurnLegRatio(Decimal),
+
+    #[error("Invalid burn legs: ratios add up to {0}, more than one")]
+    BurnLegRatiosExceedOne(Decimal),
+
+    #[error("Invalid burn leg: amount burnt to destination domain {0} cannot be zero")]
+    ZeroBurnLegAmount(u32),
+
+    #[error("Invalid burn legs: fixed amounts add up to {legs_amount}, more than the transfer amount {amount}")]
+    BurnLegsExceedAmount {
+        legs_amount: Uint128,
+        amount: Uint128,
+    },
+}
+
+impl From<CctpError> for LibraryError {
+    fn from(error: CctpError) -> Self {
+        LibraryError::ConfigurationError(error.to_string())
+    }
+}
@@ -1,3 +1,11 @@
 pub mod contract;
+pub mod domain;
+pub mod error;
 pub mod msg;
 pub mod proto;
+pub mod state;
+pub mod token;
+pub mod utils;
+
+#[cfg(test)]
+mod tests;
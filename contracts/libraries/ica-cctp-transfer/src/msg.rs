@@ -1,64 +1,470 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Binary, Deps, DepsMut, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, Deps, DepsMut, Timestamp, Uint128};
 use cw_ownable::cw_ownable_query;
-use valence_library_utils::LibraryAccountType;
-use valence_library_utils::{error::LibraryError, msg::LibraryConfigValidation};
+use valence_account_utils::ica::IcaCallbackMsg;
+use valence_library_utils::{
+    error::LibraryError,
+    msg::{LibraryConfigValidation, SimulatedAmount},
+    stats::TransferStats,
+    units::to_base_units,
+};
+use valence_library_utils::{rounding::RoundingPolicy, LibraryAccountType};
 use valence_macros::{valence_library_query, ValenceLibraryInterface};
 
+use crate::{
+    domain::DestinationDomain,
+    error::CctpError,
+    token::CctpToken,
+    utils::{validate_destination_caller, validate_mint_recipient},
+};
+
 #[cw_serde]
 pub enum FunctionMsgs {
     Transfer {},
 }
 
+/// Messages accepted by the library: the regular library messages, and the callbacks
+/// of the input account reporting the outcome of the burns
+#[cw_serde]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum ExecuteMsg {
+    Library(valence_library_utils::msg::ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>),
+    IcaCallback(IcaCallbackMsg),
+}
+
 #[valence_library_query]
 #[cw_ownable_query]
 #[cw_serde]
 #[derive(QueryResponses)]
 /// Enum representing the different query messages that can be sent.
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    /// Returns the amount that a transfer would burn now, without executing it
+    #[returns(SimulatedAmount)]
+    SimulateAmount {},
+    /// Returns the expected completion of the last burn, if an attestation SLA is configured
+    #[returns(Option<ExpectedCompletion>)]
+    ExpectedCompletion {},
+    /// Returns the cumulative amounts burnt by the library
+    #[returns(TransferStats)]
+    Stats {},
+    /// Returns the burn that was assigned the CCTP `nonce` on Noble, if it was executed by the library
+    #[returns(Option<BurnRecord>)]
+    Burn { nonce: u64 },
+}
+
+#[cw_serde]
+pub enum TransferAmount {
+    // Transfer a fixed amount
+    Fixed(Uint128),
+    // Transfer the entire balance of the input account at execution time
+    FullBalance,
+    // Transfer a share, in (0,1], of the balance of the input account at execution time
+    Percentage(Decimal),
+    // Transfer a fixed amount in display units (e.g. 1.5 for 1.5 USDC), converted to a fixed amount
+    // in base units with the configured decimals when the config is validated
+    Display(Decimal),
+}
+
+impl TransferAmount {
+    pub fn validate(&self) -> Result<(), LibraryError> {
+        match self {
+            TransferAmount::Fixed(amount) if amount.is_zero() => {
+                Err(LibraryError::ConfigurationError(
+                    "Invalid transfer config: amount cannot be zero.".to_string(),
+                ))
+            }
+            TransferAmount::Percentage(percentage)
+                if percentage.is_zero() || *percentage > Decimal::one() =>
+            {
+                Err(CctpError::InvalidPercentage(*percentage).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Computes the amount to transfer from the current balance of the input account,
+    /// percentages of the balance being rounded with `rounding`
+    pub fn resolve(
+        &self,
+        balance: Uint128,
+        rounding: RoundingPolicy,
+    ) -> Result<Uint128, LibraryError> {
+        match self {
+            TransferAmount::Fixed(amount) => Ok(*amount),
+            TransferAmount::FullBalance => Ok(balance),
+            TransferAmount::Percentage(percentage) => rounding.apply(balance, *percentage),
+            TransferAmount::Display(amount) => Err(LibraryError::ExecutionError(format!(
+                "Amount {amount} in display units was not converted to base units."
+            ))),
+        }
+    }
+
+    /// Converts an amount in display units into a fixed amount in base units with `decimals`,
+    /// other amounts being returned as they are
+    pub fn to_base_units(&self, decimals: Option<u8>) -> Result<TransferAmount, LibraryError> {
+        match self {
+            TransferAmount::Display(amount) => {
+                let decimals = decimals.ok_or_else(|| {
+                    LibraryError::ConfigurationError(
+                        "Invalid transfer config: decimals must be set to configure an amount in display units."
+                            .to_string(),
+                    )
+                })?;
+                Ok(TransferAmount::Fixed(to_base_units(*amount, decimals)?))
+            }
+            amount => Ok(amount.clone()),
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Default, Copy)]
+/// How a computed amount above the configured max amount is handled
+pub enum MaxAmountBehavior {
+    // Burn the max amount instead
+    #[default]
+    Clamp,
+    // Reject the transfer with an error
+    Error,
+}
+
+#[cw_serde]
+/// Operational guard against burning into a destination domain that won't mint soon
+pub struct AttestationSla {
+    // Time within which a burn is expected to be attested and minted on the destination domain
+    pub completion_seconds: u64,
+    // Destination domains on which mints are currently not happening (e.g. paused attestations), burns to them are rejected
+    pub disabled_domains: Vec<u32>,
+    // Current time taken to attest and mint a burn on each destination domain, burns to domains slower than the SLA are rejected.
+    // Burns to domains without an attestation time are expected to meet the SLA
+    #[serde(default)]
+    pub attestation_times: Vec<DomainAttestationTime>,
+    // Skip the checks (e.g. for testing), the expected completion is still recorded
+    #[serde(default)]
+    pub skip_checks: bool,
+}
+
+#[cw_serde]
+/// Time taken to attest and mint a burn on a destination domain
+pub struct DomainAttestationTime {
+    pub domain_id: u32,
+    pub attestation_seconds: u64,
+}
+
+impl AttestationSla {
+    pub fn validate(&self) -> Result<(), LibraryError> {
+        if self.completion_seconds == 0 {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid attestation SLA: completion seconds cannot be zero.".to_string(),
+            ));
+        }
+        for (index, attestation_time) in self.attestation_times.iter().enumerate() {
+            if self.attestation_times[..index]
+                .iter()
+                .any(|other| other.domain_id == attestation_time.domain_id)
+            {
+                return Err(LibraryError::ConfigurationError(format!(
+                    "Invalid attestation SLA: duplicate attestation time for domain {}.",
+                    attestation_time.domain_id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects burns that can't plausibly be minted within the SLA
+    pub fn check(&self, destination_domain_id: u32) -> Result<(), LibraryError> {
+        if self.skip_checks {
+            return Ok(());
+        }
+
+        if self.disabled_domains.contains(&destination_domain_id) {
+            return Err(LibraryError::ExecutionError(format!(
+                "Destination domain {destination_domain_id} is disabled: the burn would not be minted within the attestation SLA of {} seconds.",
+                self.completion_seconds
+            )));
+        }
+
+        if let Some(attestation_time) = self
+            .attestation_times
+            .iter()
+            .find(|attestation_time| attestation_time.domain_id == destination_domain_id)
+        {
+            if attestation_time.attestation_seconds > self.completion_seconds {
+                return Err(LibraryError::ExecutionError(format!(
+                    "Destination domain {destination_domain_id} takes {} seconds to mint: the burn would not be minted within the attestation SLA of {} seconds.",
+                    attestation_time.attestation_seconds, self.completion_seconds
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cw_serde]
+#[derive(Default, Copy)]
+/// How a transfer attempted while the burn cooldown of its destination domain is active is handled
+pub enum CooldownBehavior {
+    // Reject the transfer with an error
+    #[default]
+    Error,
+    // Treat the transfer as a no-op
+    Skip,
+}
+
+#[cw_serde]
+/// Minimum time between two burns to the same destination domain, e.g. to stay below the per-address
+/// rate limits of Circle or to spread out large withdrawals
+pub struct BurnCooldown {
+    pub seconds: u64,
+    #[serde(default)]
+    pub behavior: CooldownBehavior,
+}
+
+impl BurnCooldown {
+    pub fn new(seconds: u64, behavior: CooldownBehavior) -> Self {
+        BurnCooldown { seconds, behavior }
+    }
+
+    pub fn validate(&self) -> Result<(), LibraryError> {
+        if self.seconds == 0 {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid burn cooldown: seconds cannot be zero.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Time at which the cooldown started by a burn sent at `burn_time` ends
+    pub fn end(&self, burn_time: Timestamp) -> Timestamp {
+        burn_time.plus_seconds(self.seconds)
+    }
+}
+
+#[cw_serde]
+/// Burn executed by the library, indexed by its CCTP nonce once the input account reports it
+pub struct BurnRecord {
+    pub amount: Uint128,
+    pub burn_token: String,
+    pub destination_domain_id: u32,
+    pub mint_recipient: Binary,
+    // Time at which the burn was sent to the input account
+    pub sent_at: Timestamp,
+}
+
+#[cw_serde]
+/// Expected completion of a burn, recorded when an attestation SLA is configured
+pub struct ExpectedCompletion {
+    pub amount: Uint128,
+    pub destination_domain_id: u32,
+    // Time by which the burn should be minted on the destination domain
+    pub deadline: Timestamp,
+}
+
+#[cw_serde]
+#[derive(Copy)]
+/// Share of a transfer burnt by a burn leg
+pub enum BurnLegAmount {
+    // Burn a fixed amount
+    Fixed(Uint128),
+    // Burn a share, in (0,1], of the transfer amount
+    Ratio(Decimal),
+}
+
+impl BurnLegAmount {
+    /// Computes the amount burnt by the leg out of the transfer amount, ratios being rounded with `rounding`
+    pub fn resolve(
+        &self,
+        transfer_amount: Uint128,
+        rounding: RoundingPolicy,
+    ) -> Result<Uint128, LibraryError> {
+        match self {
+            BurnLegAmount::Fixed(amount) => Ok(*amount),
+            BurnLegAmount::Ratio(ratio) => rounding.apply(transfer_amount, *ratio),
+        }
+    }
+}
+
+#[cw_serde]
+/// Part of a transfer burnt to its own destination domain and mint recipient
+pub struct BurnLeg {
+    pub destination_domain_id: u32,
+    // Encoded like the mint recipient of the config
+    pub mint_recipient: Binary,
+    pub amount_or_ratio: BurnLegAmount,
+}
+
+impl BurnLeg {
+    pub fn new(
+        destination_domain_id: u32,
+        mint_recipient: Binary,
+        amount_or_ratio: BurnLegAmount,
+    ) -> Self {
+        BurnLeg {
+            destination_domain_id,
+            mint_recipient,
+            amount_or_ratio,
+        }
+    }
+}
 
 #[cw_serde]
 #[derive(ValenceLibraryInterface)]
 pub struct LibraryConfig {
     // Address of the input account (Valence interchain account)
     pub input_addr: LibraryAccountType,
-    // Amount that is going to be transferred
-    pub amount: Uint128,
-    // Denom that is going to be transferred
+    // Amount that is going to be transferred, either fixed or based on the input account balance
+    pub amount: TransferAmount,
+    // Denom that is going to be transferred, which must be a supported CCTP token minted on the destination domain
     pub denom: String,
-    // Destination domain id
-    pub destination_domain_id: u32,
+    // Destination domain, either a known domain or a numeric domain id
+    pub destination_domain: DestinationDomain,
+    // Allow numeric domain ids that are not part of the known domains registry
+    pub allow_unknown_domain: bool,
     // This address is the bytes representation of the address (with 32 length and padded zeroes)
     // For more information, check https://docs.noble.xyz/cctp/mint#example
+    // On EVM destination domains, it must be a 20 bytes address (see `utils::evm_address_to_mint_recipient`)
     pub mint_recipient: Binary,
+    // CCTP v2 fast transfer: maximum fee (in the transferred denom) that can be paid for the transfer.
+    // Rejected until Noble accepts the `circle.cctp.v2` burn messages
+    pub max_fee: Option<Uint128>,
+    // CCTP v2 fast transfer: minimum finality threshold at which the burn will be attested.
+    // Rejected until Noble accepts the `circle.cctp.v2` burn messages
+    pub min_finality_threshold: Option<u32>,
+    // Only address that can receive the burns on the destination domain, in the same 32 bytes representation
+    // as the mint recipient. Any address can when not set
+    #[serde(default)]
+    pub destination_caller: Option<Binary>,
+    // Maximum amount burnt by a single transfer, regardless of the input account balance
+    pub max_amount: Option<Uint128>,
+    // Whether computed amounts above the max amount are clamped to it or rejected
+    pub max_amount_behavior: MaxAmountBehavior,
+    // How percentages of the input account balance are rounded, down by default
+    #[serde(default)]
+    pub rounding: RoundingPolicy,
+    // Attestation SLA that burns must be able to meet, if any
+    #[serde(default)]
+    pub attestation_sla: Option<AttestationSla>,
+    // Decimals of the denom (6 for USDC and EURC), required to configure the amount in display units
+    #[serde(default)]
+    pub decimals: Option<u8>,
+    // Minimum time between two burns to the same destination domain, if any
+    #[serde(default)]
+    pub burn_cooldown: Option<BurnCooldown>,
+    // Split of the transfer amount across several destination domains, with one burn per leg. When set,
+    // the destination domain and mint recipient above are not burnt to, and what the legs don't burn
+    // stays in the input account
+    #[serde(default)]
+    pub burn_legs: Vec<BurnLeg>,
 }
 
 impl LibraryConfig {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         input_addr: impl Into<LibraryAccountType>,
-        amount: Uint128,
+        amount: TransferAmount,
         denom: String,
-        destination_domain_id: u32,
+        destination_domain: DestinationDomain,
+        allow_unknown_domain: bool,
         mint_recipient: Binary,
+        max_fee: Option<Uint128>,
+        min_finality_threshold: Option<u32>,
     ) -> Self {
         LibraryConfig {
             input_addr: input_addr.into(),
             amount,
             denom,
-            destination_domain_id,
+            destination_domain,
+            allow_unknown_domain,
             mint_recipient,
+            max_fee,
+            min_finality_threshold,
+            destination_caller: None,
+            max_amount: None,
+            max_amount_behavior: MaxAmountBehavior::default(),
+            rounding: RoundingPolicy::default(),
+            attestation_sla: None,
+            decimals: None,
+            burn_cooldown: None,
+            burn_legs: vec![],
         }
     }
 
-    fn do_validate(&self, api: &dyn cosmwasm_std::Api) -> Result<Addr, LibraryError> {
+    pub fn with_max_amount(mut self, max_amount: Uint128, behavior: MaxAmountBehavior) -> Self {
+        self.max_amount = Some(max_amount);
+        self.max_amount_behavior = behavior;
+        self
+    }
+
+    pub fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    pub fn with_attestation_sla(mut self, attestation_sla: AttestationSla) -> Self {
+        self.attestation_sla = Some(attestation_sla);
+        self
+    }
+
+    pub fn with_destination_caller(mut self, destination_caller: Binary) -> Self {
+        self.destination_caller = Some(destination_caller);
+        self
+    }
+
+    pub fn with_decimals(mut self, decimals: u8) -> Self {
+        self.decimals = Some(decimals);
+        self
+    }
+
+    pub fn with_burn_cooldown(mut self, burn_cooldown: BurnCooldown) -> Self {
+        self.burn_cooldown = Some(burn_cooldown);
+        self
+    }
+
+    pub fn with_burn_legs(mut self, burn_legs: Vec<BurnLeg>) -> Self {
+        self.burn_legs = burn_legs;
+        self
+    }
+
+    fn do_validate(
+        &self,
+        api: &dyn cosmwasm_std::Api,
+    ) -> Result<(Addr, u32, TransferAmount), LibraryError> {
         let input_addr = self.input_addr.to_addr(api)?;
-        if self.amount.is_zero() {
-            return Err(LibraryError::ConfigurationError(
-                "Invalid transfer config: amount cannot be zero.".to_string(),
-            ));
+        let amount = self.amount.to_base_units(self.decimals)?;
+        amount.validate()?;
+
+        let destination_domain_id = self
+            .destination_domain
+            .domain_id(self.allow_unknown_domain)?;
+        CctpToken::for_destination(&self.denom, destination_domain_id)?;
+        validate_mint_recipient(&self.mint_recipient, destination_domain_id)?;
+
+        validate_cctp_v2_unsupported(self.max_fee, self.min_finality_threshold)?;
+
+        if let Some(destination_caller) = &self.destination_caller {
+            validate_destination_caller(destination_caller)?;
         }
 
-        Ok(input_addr)
+        validate_max_amount(self.max_amount, &amount)?;
+
+        if let Some(attestation_sla) = &self.attestation_sla {
+            attestation_sla.validate()?;
+        }
+
+        if let Some(burn_cooldown) = &self.burn_cooldown {
+            burn_cooldown.validate()?;
+        }
+
+        validate_burn_legs(
+            &self.burn_legs,
+            &self.denom,
+            self.allow_unknown_domain,
+            &amount,
+        )?;
+
+        Ok((input_addr, destination_domain_id, amount))
     }
 }
 
@@ -70,14 +476,25 @@ impl LibraryConfigValidation<Config> for LibraryConfig {
     }
 
     fn validate(&self, deps: Deps) -> Result<Config, LibraryError> {
-        let input_addr = self.do_validate(deps.api)?;
+        let (input_addr, destination_domain_id, amount) = self.do_validate(deps.api)?;
 
         Ok(Config {
             input_addr,
-            amount: self.amount,
+            amount,
             denom: self.denom.clone(),
-            destination_domain_id: self.destination_domain_id,
+            destination_domain_id,
+            allow_unknown_domain: self.allow_unknown_domain,
             mint_recipient: self.mint_recipient.clone(),
+            max_fee: self.max_fee,
+            min_finality_threshold: self.min_finality_threshold,
+            destination_caller: self.destination_caller.clone(),
+            max_amount: self.max_amount,
+            max_amount_behavior: self.max_amount_behavior,
+            rounding: self.rounding,
+            attestation_sla: self.attestation_sla.clone(),
+            decimals: self.decimals,
+            burn_cooldown: self.burn_cooldown.clone(),
+            burn_legs: self.burn_legs.clone(),
         })
     }
 }
@@ -91,13 +508,15 @@ impl LibraryConfigUpdate {
             config.input_addr = input_addr.to_addr(deps.api)?;
         }
 
+        // Next update the decimals (if needed), before the amount that could be converted with them
+        if let OptionUpdate::Set(decimals) = self.decimals {
+            config.decimals = decimals;
+        }
+
         // Next update the amount (if needed)
         if let Some(amount) = self.amount {
-            if amount.is_zero() {
-                return Err(LibraryError::ConfigurationError(
-                    "Invalid IBC transfer config: amount cannot be zero.".to_string(),
-                ));
-            }
+            let amount = amount.to_base_units(config.decimals)?;
+            amount.validate()?;
             config.amount = amount;
         }
 
@@ -106,16 +525,91 @@ impl LibraryConfigUpdate {
             config.denom = denom;
         }
 
-        // Next update the destination domain id (if needed)
-        if let Some(destination_domain_id) = self.destination_domain_id {
-            config.destination_domain_id = destination_domain_id;
+        // Next update whether unknown domains are allowed (if needed)
+        if let Some(allow_unknown_domain) = self.allow_unknown_domain {
+            config.allow_unknown_domain = allow_unknown_domain;
         }
 
+        // Next update the destination domain (if needed) and validate it against the registry
+        let destination_domain = self
+            .destination_domain
+            .unwrap_or(DestinationDomain::Custom(config.destination_domain_id));
+        config.destination_domain_id = destination_domain.domain_id(config.allow_unknown_domain)?;
+
+        // The (possibly updated) denom must be minted on the (possibly updated) destination domain
+        CctpToken::for_destination(&config.denom, config.destination_domain_id)?;
+
         // Next update the mint recipient (if needed)
         if let Some(mint_recipient) = self.mint_recipient {
             config.mint_recipient = mint_recipient;
         }
 
+        // Next update the destination caller (if needed)
+        if let OptionUpdate::Set(destination_caller) = self.destination_caller {
+            if let Some(destination_caller) = &destination_caller {
+                validate_destination_caller(destination_caller)?;
+            }
+            config.destination_caller = destination_caller;
+        }
+
+        // Next update the CCTP v2 fields (if needed)
+        if let OptionUpdate::Set(max_fee) = self.max_fee {
+            config.max_fee = max_fee;
+        }
+
+        if let OptionUpdate::Set(min_finality_threshold) = self.min_finality_threshold {
+            config.min_finality_threshold = min_finality_threshold;
+        }
+
+        // Next update the max amount (if needed)
+        if let OptionUpdate::Set(max_amount) = self.max_amount {
+            config.max_amount = max_amount;
+        }
+
+        if let Some(max_amount_behavior) = self.max_amount_behavior {
+            config.max_amount_behavior = max_amount_behavior;
+        }
+
+        if let Some(rounding) = self.rounding {
+            config.rounding = rounding;
+        }
+
+        // Next update the attestation SLA (if needed)
+        if let OptionUpdate::Set(attestation_sla) = self.attestation_sla {
+            if let Some(attestation_sla) = &attestation_sla {
+                attestation_sla.validate()?;
+            }
+            config.attestation_sla = attestation_sla;
+        }
+
+        // Next update the burn cooldown (if needed)
+        if let OptionUpdate::Set(burn_cooldown) = self.burn_cooldown {
+            if let Some(burn_cooldown) = &burn_cooldown {
+                burn_cooldown.validate()?;
+            }
+            config.burn_cooldown = burn_cooldown;
+        }
+
+        // Next update the burn legs (if needed)
+        if let Some(burn_legs) = self.burn_legs {
+            config.burn_legs = burn_legs;
+        }
+
+        // The (possibly updated) mint recipient must be an address of the (possibly updated) destination domain
+        validate_mint_recipient(&config.mint_recipient, config.destination_domain_id)?;
+
+        // The (possibly updated) CCTP v2 fields can't be set yet, and the max amount must fit the (possibly updated) amount
+        validate_cctp_v2_unsupported(config.max_fee, config.min_finality_threshold)?;
+        validate_max_amount(config.max_amount, &config.amount)?;
+
+        // The burn legs are validated against the (possibly updated) denom, domains registry and amount
+        validate_burn_legs(
+            &config.burn_legs,
+            &config.denom,
+            config.allow_unknown_domain,
+            &config.amount,
+        )?;
+
         valence_library_base::save_config(deps.storage, &config)?;
         Ok(())
     }
@@ -124,26 +618,178 @@ impl LibraryConfigUpdate {
 #[cw_serde]
 pub struct Config {
     pub input_addr: Addr,
-    pub amount: Uint128,
+    pub amount: TransferAmount,
     pub denom: String,
     pub destination_domain_id: u32,
+    pub allow_unknown_domain: bool,
     pub mint_recipient: Binary,
+    pub max_fee: Option<Uint128>,
+    pub min_finality_threshold: Option<u32>,
+    #[serde(default)]
+    pub destination_caller: Option<Binary>,
+    #[serde(default)]
+    pub max_amount: Option<Uint128>,
+    #[serde(default)]
+    pub max_amount_behavior: MaxAmountBehavior,
+    #[serde(default)]
+    pub rounding: RoundingPolicy,
+    #[serde(default)]
+    pub attestation_sla: Option<AttestationSla>,
+    #[serde(default)]
+    pub decimals: Option<u8>,
+    #[serde(default)]
+    pub burn_cooldown: Option<BurnCooldown>,
+    #[serde(default)]
+    pub burn_legs: Vec<BurnLeg>,
 }
 
 impl Config {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         input_addr: Addr,
-        amount: Uint128,
+        amount: TransferAmount,
         denom: String,
         destination_domain_id: u32,
+        allow_unknown_domain: bool,
         mint_recipient: Binary,
+        max_fee: Option<Uint128>,
+        min_finality_threshold: Option<u32>,
     ) -> Self {
         Config {
             input_addr,
             amount,
             denom,
             destination_domain_id,
+            allow_unknown_domain,
             mint_recipient,
+            max_fee,
+            min_finality_threshold,
+            destination_caller: None,
+            max_amount: None,
+            max_amount_behavior: MaxAmountBehavior::default(),
+            rounding: RoundingPolicy::default(),
+            attestation_sla: None,
+            decimals: None,
+            burn_cooldown: None,
+            burn_legs: vec![],
+        }
+    }
+
+    pub fn with_max_amount(mut self, max_amount: Uint128, behavior: MaxAmountBehavior) -> Self {
+        self.max_amount = Some(max_amount);
+        self.max_amount_behavior = behavior;
+        self
+    }
+
+    pub fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    pub fn with_attestation_sla(mut self, attestation_sla: AttestationSla) -> Self {
+        self.attestation_sla = Some(attestation_sla);
+        self
+    }
+
+    pub fn with_burn_cooldown(mut self, burn_cooldown: BurnCooldown) -> Self {
+        self.burn_cooldown = Some(burn_cooldown);
+        self
+    }
+
+    pub fn with_destination_caller(mut self, destination_caller: Binary) -> Self {
+        self.destination_caller = Some(destination_caller);
+        self
+    }
+
+    pub fn with_burn_legs(mut self, burn_legs: Vec<BurnLeg>) -> Self {
+        self.burn_legs = burn_legs;
+        self
+    }
+
+    /// Destination domains burnt to by a transfer: those of the burn legs, if any
+    pub fn destination_domain_ids(&self) -> Vec<u32> {
+        if self.burn_legs.is_empty() {
+            return vec![self.destination_domain_id];
+        }
+        self.burn_legs
+            .iter()
+            .map(|leg| leg.destination_domain_id)
+            .collect()
+    }
+}
+
+// Noble doesn't register the `circle.cctp.v2` burn messages yet, so any burn using their fields would be rejected on the host chain
+fn validate_cctp_v2_unsupported(
+    max_fee: Option<Uint128>,
+    min_finality_threshold: Option<u32>,
+) -> Result<(), LibraryError> {
+    if max_fee.is_some() || min_finality_threshold.is_some() {
+        return Err(CctpError::CctpV2Unsupported.into());
+    }
+    Ok(())
+}
+
+// Fixed amounts above the cap would never be transferred as configured, so they are rejected here
+fn validate_max_amount(
+    max_amount: Option<Uint128>,
+    amount: &TransferAmount,
+) -> Result<(), LibraryError> {
+    match (max_amount, amount) {
+        (Some(max_amount), _) if max_amount.is_zero() => Err(LibraryError::ConfigurationError(
+            "Invalid transfer config: max amount cannot be zero.".to_string(),
+        )),
+        (Some(max_amount), TransferAmount::Fixed(amount)) if *amount > max_amount => {
+            Err(CctpError::AmountExceedsMaxAmount {
+                amount: *amount,
+                max_amount,
+            }
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
+
+// Each leg must be a valid burn on its own, and the legs can't burn more than the transfer amount.
+// As for the max fee, fixed legs can only be checked against fixed amounts here
+fn validate_burn_legs(
+    burn_legs: &[BurnLeg],
+    denom: &str,
+    allow_unknown_domain: bool,
+    amount: &TransferAmount,
+) -> Result<(), LibraryError> {
+    let mut total_ratio = Decimal::zero();
+    let mut total_fixed = Uint128::zero();
+
+    for leg in burn_legs {
+        let destination_domain_id =
+            DestinationDomain::Custom(leg.destination_domain_id).domain_id(allow_unknown_domain)?;
+        CctpToken::for_destination(denom, destination_domain_id)?;
+        validate_mint_recipient(&leg.mint_recipient, destination_domain_id)?;
+
+        match leg.amount_or_ratio {
+            BurnLegAmount::Fixed(amount) if amount.is_zero() => {
+                return Err(CctpError::ZeroBurnLegAmount(destination_domain_id).into())
+            }
+            BurnLegAmount::Fixed(amount) => total_fixed = total_fixed.saturating_add(amount),
+            BurnLegAmount::Ratio(ratio) if ratio.is_zero() || ratio > Decimal::one() => {
+                return Err(CctpError::InvalidBurnLegRatio(ratio).into())
+            }
+            BurnLegAmount::Ratio(ratio) => total_ratio = total_ratio.saturating_add(ratio),
+        }
+    }
+
+    if total_ratio > Decimal::one() {
+        return Err(CctpError::BurnLegRatiosExceedOne(total_ratio).into());
+    }
+
+    match amount {
+        TransferAmount::Fixed(amount) if total_fixed > *amount => {
+            Err(CctpError::BurnLegsExceedAmount {
+                legs_amount: total_fixed,
+                amount: *amount,
+            }
+            .into())
         }
+        _ => Ok(()),
     }
 }
@@ -29,3 +29,69 @@ impl ::prost::Name for MsgDepositForBurn {
         "/circle.cctp.v1.MsgDepositForBurn".into()
     }
 }
+
+// The proto message that is going to be executed on Noble when a destination caller is configured.
+// On top of the MsgDepositForBurn fields, it carries the only address that can receive the burn on the destination domain.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgDepositForBurnWithCaller {
+    /// the signer address
+    #[prost(string, tag = "1")]
+    pub from: ::prost::alloc::string::String,
+    /// the amount to bridge
+    #[prost(string, tag = "2")]
+    pub amount: ::prost::alloc::string::String,
+    /// the destination domain
+    #[prost(uint32, tag = "3")]
+    pub destination_domain: u32,
+    /// the mint recipient address
+    #[prost(bytes, tag = "4")]
+    pub mint_recipient: ::prost::alloc::vec::Vec<u8>,
+    /// the token denom that is being bridged
+    #[prost(string, tag = "5")]
+    pub burn_token: ::prost::alloc::string::String,
+    /// the address allowed to receive the message on the destination domain
+    #[prost(bytes, tag = "6")]
+    pub destination_caller: ::prost::alloc::vec::Vec<u8>,
+}
+
+impl ::prost::Name for MsgDepositForBurnWithCaller {
+    const NAME: &'static str = "MsgDepositForBurnWithCaller";
+    const PACKAGE: &'static str = "circle.cctp.v1";
+    fn full_name() -> ::prost::alloc::string::String {
+        "circle.cctp.v1.MsgDepositForBurnWithCaller".into()
+    }
+    fn type_url() -> ::prost::alloc::string::String {
+        "/circle.cctp.v1.MsgDepositForBurnWithCaller".into()
+    }
+}
+
+// Response of MsgDepositForBurn and MsgDepositForBurnWithCaller, carrying the nonce assigned to the burn
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgDepositForBurnResponse {
+    /// the nonce of the burn message
+    #[prost(uint64, tag = "1")]
+    pub nonce: u64,
+}
+
+// Data of the acknowledgement of an ICA transaction, with the responses of its messages in order
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TxMsgData {
+    /// the responses of the messages of the transaction
+    #[prost(message, repeated, tag = "2")]
+    pub msg_responses: ::prost::alloc::vec::Vec<ProtoAny>,
+}
+
+// Protobuf Any wrapping a message response
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProtoAny {
+    /// the type url of the message response
+    #[prost(string, tag = "1")]
+    pub type_url: ::prost::alloc::string::String,
+    /// the encoded message response
+    #[prost(bytes, tag = "2")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+}
@@ -0,0 +1,15 @@
+use cosmwasm_std::Timestamp;
+use cw_storage_plus::{Item, Map};
+
+use crate::msg::{BurnRecord, ExpectedCompletion};
+
+// Expected completion of the last burn, only recorded when an attestation SLA is configured
+pub const LAST_EXPECTED_COMPLETION: Item<ExpectedCompletion> =
+    Item::new("last_expected_completion");
+// Burns sent to the input account whose outcome wasn't reported yet, keyed by the ICA packet (channel and sequence)
+// that carried them, so that each callback of the input account is matched to its own burn
+pub const PENDING_BURNS: Map<(String, u64), BurnRecord> = Map::new("pending_burns_by_packet");
+// Burns executed on Noble, by the CCTP nonce they were assigned
+pub const BURNS: Map<u64, BurnRecord> = Map::new("burns");
+// Time at which the last successful burn to each destination domain was sent, which starts its burn cooldown
+pub const LAST_SUCCESSFUL_BURNS: Map<u32, Timestamp> = Map::new("last_successful_burns");
@@ -0,0 +1,1838 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use cosmwasm_std::{
+    from_json,
+    testing::{message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+    to_json_binary, to_json_string, Addr, AnyMsg, Binary, Coin, ContractResult, CosmosMsg, Decimal,
+    Env, Event, Order, OwnedDeps, Reply, Response, Storage, SubMsgResponse, SubMsgResult,
+    SystemError, SystemResult, Timestamp, Uint128, WasmMsg, WasmQuery,
+};
+use cw_storage_plus::Item;
+use prost::{Message, Name};
+use valence_account_utils::ica::{
+    ExecuteMsg as IcaExecuteMsg, IcaCallbackMsg, IcaInformation, IcaState, IcaTxResult,
+    IcaTxSubmission, QueryMsg as IcaQueryMsg,
+};
+use valence_library_utils::{
+    error::LibraryError,
+    events::{TransferEvent, TRANSFER_EVENT_TYPE},
+    msg::{LibraryConfigValidation, SimulatedAmount},
+    rounding::RoundingPolicy,
+    stats::TransferStats,
+    LibraryAccountType,
+};
+
+use crate::{
+    contract::{
+        callbacks::{BURN_RESULT_EVENT_TYPE, BURN_SUBMITTED_REPLY_ID},
+        execute,
+        functions::{create_burn_msg, process_function, resolve_burns, resolve_transfer_amount},
+        migrations::migrate_legacy_config,
+        query, reply,
+    },
+    domain::{DestinationDomain, KNOWN_DOMAINS},
+    error::CctpError,
+    msg::{
+        AttestationSla, BurnCooldown, BurnLeg, BurnLegAmount, BurnRecord, Config, CooldownBehavior,
+        DomainAttestationTime, ExecuteMsg, ExpectedCompletion, FunctionMsgs, LibraryConfig,
+        MaxAmountBehavior, QueryMsg, TransferAmount,
+    },
+    proto::{
+        MsgDepositForBurn, MsgDepositForBurnResponse, MsgDepositForBurnWithCaller, ProtoAny,
+        TxMsgData,
+    },
+    state::PENDING_BURNS,
+    token::CctpToken,
+    utils::{evm_address_to_mint_recipient, validate_hook_data, MAX_HOOK_DATA_LENGTH},
+};
+
+fn cctp_transfer_config(
+    api: &MockApi,
+    destination_domain: DestinationDomain,
+    allow_unknown_domain: bool,
+) -> LibraryConfig {
+    LibraryConfig::new(
+        LibraryAccountType::Addr(api.addr_make("input_account").to_string()),
+        TransferAmount::Fixed(Uint128::new(1_000_000)),
+        "uusdc".to_string(),
+        destination_domain,
+        allow_unknown_domain,
+        padded_mint_recipient(&[0x01; 20]),
+        None,
+        None,
+    )
+}
+
+#[test]
+fn named_domains_round_trip_through_json() {
+    for domain in KNOWN_DOMAINS {
+        let json = to_json_string(&domain).unwrap();
+        let deserialized: DestinationDomain = from_json(&json).unwrap();
+        assert_eq!(deserialized, domain);
+        assert_eq!(DestinationDomain::from_domain_id(domain.id()), Some(domain));
+    }
+
+    assert_eq!(
+        to_json_string(&DestinationDomain::Ethereum).unwrap(),
+        "\"ethereum\""
+    );
+    assert_eq!(
+        from_json::<DestinationDomain>("{\"custom\":3}").unwrap(),
+        DestinationDomain::Custom(3)
+    );
+}
+
+#[test]
+fn known_domains_resolve_to_registered_ids() {
+    assert_eq!(DestinationDomain::Ethereum.domain_id(false), Ok(0));
+    assert_eq!(DestinationDomain::Avalanche.domain_id(false), Ok(1));
+    assert_eq!(DestinationDomain::Optimism.domain_id(false), Ok(2));
+    assert_eq!(DestinationDomain::Arbitrum.domain_id(false), Ok(3));
+    assert_eq!(DestinationDomain::Noble.domain_id(false), Ok(4));
+    // Numeric overrides of registered ids are accepted
+    assert_eq!(DestinationDomain::Custom(3).domain_id(false), Ok(3));
+}
+
+#[test]
+fn pre_validate_rejects_unknown_domain_id() {
+    let api = MockApi::default();
+
+    let cfg = cctp_transfer_config(&api, DestinationDomain::Custom(9999), false);
+    let err = cfg.pre_validate(&api).unwrap_err();
+
+    assert!(matches!(err, LibraryError::ConfigurationError(_)));
+    assert_eq!(
+        err.to_string(),
+        format!("Configuration error: {}", CctpError::UnknownDomain(9999))
+    );
+}
+
+#[test]
+fn pre_validate_accepts_unknown_domain_id_when_allowed() {
+    let api = MockApi::default();
+
+    let cfg = cctp_transfer_config(&api, DestinationDomain::Custom(9999), true);
+    cfg.pre_validate(&api).unwrap();
+
+    let cfg = cctp_transfer_config(&api, DestinationDomain::Ethereum, false);
+    cfg.pre_validate(&api).unwrap();
+}
+
+#[test]
+fn pre_validate_accepts_tokens_minted_on_the_destination_domain() {
+    let api = MockApi::default();
+
+    for (denom, domain) in [
+        ("uusdc", DestinationDomain::Linea),
+        ("ueurc", DestinationDomain::Ethereum),
+        ("ueurc", DestinationDomain::Base),
+    ] {
+        let mut cfg = cctp_transfer_config(&api, domain, false);
+        cfg.denom = denom.to_string();
+        cfg.pre_validate(&api).unwrap();
+    }
+
+    // Tokens can't be checked against domains that are not in the registry
+    let mut cfg = cctp_transfer_config(&api, DestinationDomain::Custom(9999), true);
+    cfg.denom = "ueurc".to_string();
+    cfg.pre_validate(&api).unwrap();
+
+    assert_eq!(CctpToken::from_denom("ueurc"), Some(CctpToken::Eurc));
+    assert_eq!(CctpToken::Usdc.denom(), "uusdc");
+}
+
+#[test]
+fn pre_validate_rejects_unsupported_tokens() {
+    let api = MockApi::default();
+
+    // EURC is not minted on Arbitrum
+    let mut cfg = cctp_transfer_config(&api, DestinationDomain::Arbitrum, false);
+    cfg.denom = "ueurc".to_string();
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        format!(
+            "Configuration error: {}",
+            CctpError::UnsupportedTokenDomain {
+                denom: "ueurc".to_string(),
+                domain_id: 3,
+            }
+        )
+    );
+
+    // Tokens that can't be burnt are rejected whatever the domain
+    let mut cfg = cctp_transfer_config(&api, DestinationDomain::Custom(9999), true);
+    cfg.denom = "uatom".to_string();
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        format!(
+            "Configuration error: {}",
+            CctpError::UnsupportedToken("uatom".to_string())
+        )
+    );
+}
+
+#[test]
+fn pre_validate_rejects_bech32_mint_recipient() {
+    let api = MockApi::default();
+
+    let bech32_recipient = api.addr_make("recipient").to_string();
+    let mut cfg = cctp_transfer_config(&api, DestinationDomain::Ethereum, false);
+    cfg.mint_recipient = Binary::from(bech32_recipient.as_bytes());
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        format!(
+            "Configuration error: {}",
+            CctpError::Bech32MintRecipient(bech32_recipient.clone())
+        )
+    );
+
+    // Also on domains whose addresses are not EVM addresses
+    cfg.destination_domain = DestinationDomain::Solana;
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        format!(
+            "Configuration error: {}",
+            CctpError::Bech32MintRecipient(bech32_recipient)
+        )
+    );
+}
+
+#[test]
+fn pre_validate_rejects_mint_recipients_that_are_not_evm_addresses() {
+    let api = MockApi::default();
+
+    // Unpadded EVM address
+    let mut cfg = cctp_transfer_config(&api, DestinationDomain::Ethereum, false);
+    cfg.mint_recipient = Binary::from(&[0x01; 20]);
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        format!(
+            "Configuration error: {}",
+            CctpError::InvalidMintRecipientLength {
+                expected: 32,
+                got: 20
+            }
+        )
+    );
+
+    // 32 bytes addresses can't be minted to on EVM domains
+    cfg.mint_recipient = Binary::from(&[0x01; 32]);
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        format!(
+            "Configuration error: {}",
+            CctpError::NotAnEvmMintRecipient(0)
+        )
+    );
+
+    // But they are the native address format of other domains
+    cfg.destination_domain = DestinationDomain::Solana;
+    cfg.pre_validate(&api).unwrap();
+
+    cfg.destination_domain = DestinationDomain::Base;
+    cfg.mint_recipient =
+        evm_address_to_mint_recipient("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap();
+    cfg.pre_validate(&api).unwrap();
+}
+
+fn padded_mint_recipient(addr_bytes: &[u8]) -> Binary {
+    let mut mint_recipient = vec![0u8; 12];
+    mint_recipient.extend_from_slice(addr_bytes);
+    Binary::from(mint_recipient)
+}
+
+#[test]
+fn mint_recipient_from_lowercase_address() {
+    let expected = padded_mint_recipient(&[
+        0x5a, 0xae, 0xb6, 0x05, 0x3f, 0x3e, 0x94, 0xc9, 0xb9, 0xa0, 0x9f, 0x33, 0x66, 0x94, 0x35,
+        0xe7, 0xef, 0x1b, 0xea, 0xed,
+    ]);
+
+    let mint_recipient =
+        evm_address_to_mint_recipient("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+    assert_eq!(mint_recipient.len(), 32);
+    assert_eq!(mint_recipient, expected);
+
+    // The 0x prefix is optional
+    let mint_recipient =
+        evm_address_to_mint_recipient("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+    assert_eq!(mint_recipient, expected);
+}
+
+#[test]
+fn mint_recipient_from_checksummed_address() {
+    let checksummed =
+        evm_address_to_mint_recipient("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap();
+    let lowercase =
+        evm_address_to_mint_recipient("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+    assert_eq!(checksummed, lowercase);
+
+    evm_address_to_mint_recipient("0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359").unwrap();
+}
+
+#[test]
+fn mint_recipient_rejects_malformed_addresses() {
+    // Wrong EIP-55 checksum
+    assert_eq!(
+        evm_address_to_mint_recipient("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+        Err(CctpError::InvalidChecksum(
+            "0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string()
+        ))
+    );
+
+    // Too short
+    assert_eq!(
+        evm_address_to_mint_recipient("0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea"),
+        Err(CctpError::InvalidAddressLength(19))
+    );
+
+    // Too long
+    assert_eq!(
+        evm_address_to_mint_recipient(
+            "0x0000000000000000000000005aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+        ),
+        Err(CctpError::InvalidAddressLength(32))
+    );
+
+    // Non-hex characters and odd length
+    assert!(matches!(
+        evm_address_to_mint_recipient("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beazz"),
+        Err(CctpError::InvalidHexAddress(_))
+    ));
+    assert!(matches!(
+        evm_address_to_mint_recipient("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beae"),
+        Err(CctpError::InvalidHexAddress(_))
+    ));
+}
+
+fn cctp_config(
+    amount: TransferAmount,
+    max_fee: Option<Uint128>,
+    min_finality_threshold: Option<u32>,
+) -> Config {
+    Config::new(
+        Addr::unchecked("input_account"),
+        amount,
+        "uusdc".to_string(),
+        0,
+        false,
+        Binary::from(&[0x01; 32]),
+        max_fee,
+        min_finality_threshold,
+    )
+}
+
+// Burn message of `amount` to the destination domain of `cfg`
+fn create_deposit_for_burn_msg(cfg: &Config, from: String, amount: Uint128) -> AnyMsg {
+    let burns = resolve_burns(cfg, amount, Timestamp::default()).unwrap();
+    create_burn_msg(cfg, from, &burns[0])
+}
+
+#[test]
+fn v1_config_produces_legacy_deposit_for_burn() {
+    let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None);
+
+    let any_msg =
+        create_deposit_for_burn_msg(&cfg, "noble_ica".to_string(), Uint128::new(1_000_000));
+    assert_eq!(any_msg.type_url, "/circle.cctp.v1.MsgDepositForBurn");
+    assert_eq!(any_msg.type_url, MsgDepositForBurn::type_url());
+
+    let msg = MsgDepositForBurn::decode(any_msg.value.as_slice()).unwrap();
+    assert_eq!(
+        msg,
+        MsgDepositForBurn {
+            from: "noble_ica".to_string(),
+            amount: "1000000".to_string(),
+            destination_domain: 0,
+            mint_recipient: vec![0x01; 32],
+            burn_token: "uusdc".to_string(),
+        }
+    );
+}
+
+#[test]
+fn destination_caller_produces_deposit_for_burn_with_caller() {
+    let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None)
+        .with_destination_caller(Binary::from(&[0x02; 32]));
+
+    let any_msg =
+        create_deposit_for_burn_msg(&cfg, "noble_ica".to_string(), Uint128::new(1_000_000));
+    assert_eq!(
+        any_msg.type_url,
+        "/circle.cctp.v1.MsgDepositForBurnWithCaller"
+    );
+    assert_eq!(any_msg.type_url, MsgDepositForBurnWithCaller::type_url());
+
+    let msg = MsgDepositForBurnWithCaller::decode(any_msg.value.as_slice()).unwrap();
+    assert_eq!(
+        msg,
+        MsgDepositForBurnWithCaller {
+            from: "noble_ica".to_string(),
+            amount: "1000000".to_string(),
+            destination_domain: 0,
+            mint_recipient: vec![0x01; 32],
+            burn_token: "uusdc".to_string(),
+            destination_caller: vec![0x02; 32],
+        }
+    );
+}
+
+#[test]
+fn pre_validate_rejects_invalid_destination_caller() {
+    let api = MockApi::default();
+
+    cctp_transfer_config(&api, DestinationDomain::Ethereum, false)
+        .with_destination_caller(padded_mint_recipient(&[0x02; 20]))
+        .pre_validate(&api)
+        .unwrap();
+
+    let err = cctp_transfer_config(&api, DestinationDomain::Ethereum, false)
+        .with_destination_caller(Binary::from(&[0x02; 20]))
+        .pre_validate(&api)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        LibraryError::from(CctpError::InvalidDestinationCallerLength {
+            expected: 32,
+            got: 20,
+        })
+        .to_string()
+    );
+
+    // Any caller is allowed by not setting it, Noble rejects a zero destination caller
+    let err = cctp_transfer_config(&api, DestinationDomain::Ethereum, false)
+        .with_destination_caller(Binary::from(&[0x00; 32]))
+        .pre_validate(&api)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        LibraryError::from(CctpError::ZeroDestinationCaller).to_string()
+    );
+}
+
+#[test]
+fn hook_data_of_invalid_length_is_rejected() {
+    validate_hook_data(&Binary::from(vec![0x01; MAX_HOOK_DATA_LENGTH])).unwrap();
+
+    for len in [0, MAX_HOOK_DATA_LENGTH + 1] {
+        assert_eq!(
+            validate_hook_data(&Binary::from(vec![0x01; len])),
+            Err(CctpError::InvalidHookDataLength {
+                max: MAX_HOOK_DATA_LENGTH,
+                got: len,
+            })
+        );
+    }
+}
+
+#[test]
+fn pre_validate_rejects_cctp_v2_fields() {
+    let api = MockApi::default();
+    let expected_err = LibraryError::from(CctpError::CctpV2Unsupported).to_string();
+
+    let mut cfg = cctp_transfer_config(&api, DestinationDomain::Ethereum, false);
+    cfg.max_fee = Some(Uint128::new(500));
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        expected_err
+    );
+
+    let mut cfg = cctp_transfer_config(&api, DestinationDomain::Ethereum, false);
+    cfg.min_finality_threshold = Some(1000);
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        expected_err
+    );
+
+    // Legacy burns and burns with a destination caller are accepted
+    cctp_transfer_config(&api, DestinationDomain::Ethereum, false)
+        .pre_validate(&api)
+        .unwrap();
+    cctp_transfer_config(&api, DestinationDomain::Ethereum, false)
+        .with_destination_caller(padded_mint_recipient(&[0x02; 20]))
+        .pre_validate(&api)
+        .unwrap();
+}
+
+#[test]
+fn pre_validate_rejects_fixed_amount_above_max_amount() {
+    let api = MockApi::default();
+
+    let mut cfg = cctp_transfer_config(&api, DestinationDomain::Ethereum, false)
+        .with_max_amount(Uint128::new(999_999), MaxAmountBehavior::Clamp);
+
+    let err = cfg.pre_validate(&api).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Configuration error: {}",
+            CctpError::AmountExceedsMaxAmount {
+                amount: Uint128::new(1_000_000),
+                max_amount: Uint128::new(999_999),
+            }
+        )
+    );
+
+    cfg.max_amount = Some(Uint128::zero());
+    let err = cfg.pre_validate(&api).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Invalid transfer config: max amount cannot be zero."
+    );
+
+    // Balance based amounts are accepted with any max amount, as they are capped at execution time
+    cfg.amount = TransferAmount::FullBalance;
+    cfg.max_amount = Some(Uint128::one());
+    cfg.pre_validate(&api).unwrap();
+}
+
+#[test]
+fn pre_validate_rejects_percentage_out_of_range() {
+    let api = MockApi::default();
+    let mut cfg = cctp_transfer_config(&api, DestinationDomain::Ethereum, false);
+
+    for percentage in [Decimal::zero(), Decimal::from_str("1.01").unwrap()] {
+        cfg.amount = TransferAmount::Percentage(percentage);
+        let err = cfg.pre_validate(&api).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Configuration error: {}",
+                CctpError::InvalidPercentage(percentage)
+            )
+        );
+    }
+
+    for percentage in [Decimal::percent(1), Decimal::one()] {
+        cfg.amount = TransferAmount::Percentage(percentage);
+        cfg.pre_validate(&api).unwrap();
+    }
+}
+
+fn mock_deps_with_ica_balance(balance: Uint128) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    mock_deps_with_input_account("input_account", "noble_ica", balance, true)
+}
+
+// Mocks the ICA `input_account`, holding `balance` on its remote address. Queries to any other
+// account fail, so that burns can only read from the configured input account
+fn mock_deps_with_input_account(
+    input_account: &'static str,
+    remote_address: &'static str,
+    balance: Uint128,
+    approved: bool,
+) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    let library = mock_env().contract.address.to_string();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr == input_account => {
+            match from_json(msg).unwrap() {
+                IcaQueryMsg::ListApprovedLibraries {} => {
+                    let approved_libraries = if approved {
+                        vec![library.clone()]
+                    } else {
+                        vec![]
+                    };
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_json_binary(&approved_libraries).unwrap(),
+                    ))
+                }
+                IcaQueryMsg::RemoteBalance { denom } => SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&Coin {
+                        denom,
+                        amount: balance,
+                    })
+                    .unwrap(),
+                )),
+                IcaQueryMsg::IcaState {} => SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&IcaState::Created(IcaInformation {
+                        address: remote_address.to_string(),
+                        port_id: format!("icacontroller-{input_account}"),
+                        controller_connection_id: "connection-0".to_string(),
+                    }))
+                    .unwrap(),
+                )),
+                _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                    kind: "ica query".to_string(),
+                }),
+            }
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm query".to_string(),
+        }),
+    });
+    deps
+}
+
+#[test]
+fn fixed_amount_ignores_ica_balance() {
+    // No balance handler is registered, so any balance query would fail
+    let deps = mock_dependencies();
+    let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None);
+
+    let amount = resolve_transfer_amount(deps.as_ref(), &cfg).unwrap();
+    assert_eq!(amount, Uint128::new(1_000_000));
+}
+
+#[test]
+fn full_balance_sweeps_ica_balance() {
+    let deps = mock_deps_with_ica_balance(Uint128::new(1_234_567));
+    let cfg = cctp_config(TransferAmount::FullBalance, None, None);
+
+    let amount = resolve_transfer_amount(deps.as_ref(), &cfg).unwrap();
+    assert_eq!(amount, Uint128::new(1_234_567));
+
+    let any_msg = create_deposit_for_burn_msg(&cfg, "noble_ica".to_string(), amount);
+    let msg = MsgDepositForBurn::decode(any_msg.value.as_slice()).unwrap();
+    assert_eq!(msg.amount, "1234567");
+}
+
+#[test]
+fn percentage_of_ica_balance_rounds_down() {
+    let deps = mock_deps_with_ica_balance(Uint128::new(1_000_001));
+    let cfg = cctp_config(TransferAmount::Percentage(Decimal::percent(50)), None, None);
+
+    let amount = resolve_transfer_amount(deps.as_ref(), &cfg).unwrap();
+    assert_eq!(amount, Uint128::new(500_000));
+}
+
+#[test]
+fn percentage_of_ica_balance_follows_rounding_policy() {
+    let deps = mock_deps_with_ica_balance(Uint128::new(1_000_001));
+
+    for (rounding, expected) in [
+        (RoundingPolicy::Down, 500_000),
+        (RoundingPolicy::Nearest, 500_001),
+        (RoundingPolicy::Up, 500_001),
+    ] {
+        let cfg = cctp_config(TransferAmount::Percentage(Decimal::percent(50)), None, None)
+            .with_rounding(rounding);
+
+        let amount = resolve_transfer_amount(deps.as_ref(), &cfg).unwrap();
+        assert_eq!(amount, Uint128::new(expected));
+    }
+
+    // A third of the balance is closer to the amount below it
+    let deps = mock_deps_with_ica_balance(Uint128::new(1_000_000));
+    let cfg = cctp_config(
+        TransferAmount::Percentage(Decimal::from_ratio(1u128, 3u128)),
+        None,
+        None,
+    )
+    .with_rounding(RoundingPolicy::Nearest);
+    let amount = resolve_transfer_amount(deps.as_ref(), &cfg).unwrap();
+    assert_eq!(amount, Uint128::new(333_333));
+}
+
+#[test]
+fn split_ratio_burns_share_of_ica_balance() {
+    let deps = mock_deps_with_ica_balance(Uint128::new(2_000_000));
+    let cfg = cctp_config(TransferAmount::Percentage(Decimal::percent(25)), None, None);
+
+    let amount = resolve_transfer_amount(deps.as_ref(), &cfg).unwrap();
+    assert_eq!(amount, Uint128::new(500_000));
+
+    let any_msg = create_deposit_for_burn_msg(&cfg, "noble_ica".to_string(), amount);
+    let msg = MsgDepositForBurn::decode(any_msg.value.as_slice()).unwrap();
+    assert_eq!(msg.amount, "500000");
+}
+
+#[test]
+fn balance_based_amount_errors_on_empty_balance() {
+    let deps = mock_deps_with_ica_balance(Uint128::zero());
+    let cfg = cctp_config(TransferAmount::FullBalance, None, None);
+
+    let err = resolve_transfer_amount(deps.as_ref(), &cfg).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Nothing to transfer: no uusdc balance available in the input account."
+    );
+}
+
+#[test]
+fn balance_based_amount_above_max_amount_is_clamped() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::new(5_000_000));
+    let cfg = cctp_config(TransferAmount::FullBalance, None, None)
+        .with_max_amount(Uint128::new(1_000_000), MaxAmountBehavior::Clamp);
+
+    let amount = resolve_transfer_amount(deps.as_ref(), &cfg).unwrap();
+    assert_eq!(amount, Uint128::new(1_000_000));
+
+    let res = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::Transfer {},
+        cfg,
+    )
+    .unwrap();
+
+    let event = res
+        .events
+        .iter()
+        .find(|event| event.ty == TRANSFER_EVENT_TYPE)
+        .unwrap();
+    assert_eq!(
+        *event,
+        Event::from(
+            TransferEvent::new("cctp_transfer", "uusdc")
+                .with_input_balance(Uint128::new(5_000_000))
+                .with_amount(Uint128::new(1_000_000))
+                .with_destination_domain(0)
+        )
+    );
+}
+
+#[test]
+fn balance_based_amount_under_max_amount_is_unchanged() {
+    let deps = mock_deps_with_ica_balance(Uint128::new(2_000_000));
+    let cfg = cctp_config(TransferAmount::Percentage(Decimal::percent(25)), None, None)
+        .with_max_amount(Uint128::new(1_000_000), MaxAmountBehavior::Error);
+
+    let amount = resolve_transfer_amount(deps.as_ref(), &cfg).unwrap();
+    assert_eq!(amount, Uint128::new(500_000));
+}
+
+#[test]
+fn balance_based_amount_above_max_amount_errors_when_configured() {
+    let deps = mock_deps_with_ica_balance(Uint128::new(5_000_000));
+    let cfg = cctp_config(TransferAmount::FullBalance, None, None)
+        .with_max_amount(Uint128::new(1_000_000), MaxAmountBehavior::Error);
+
+    let err = resolve_transfer_amount(deps.as_ref(), &cfg).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Transfer amount 5000000 exceeds the max amount 1000000."
+    );
+}
+
+#[test]
+fn transfer_emits_valence_transfer_event() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::new(2_000_000));
+    let cfg = cctp_config(TransferAmount::Percentage(Decimal::percent(25)), None, None);
+
+    let res = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::Transfer {},
+        cfg,
+    )
+    .unwrap();
+
+    let event = res
+        .events
+        .iter()
+        .find(|event| event.ty == TRANSFER_EVENT_TYPE)
+        .unwrap();
+    assert_eq!(
+        *event,
+        Event::from(
+            TransferEvent::new("cctp_transfer", "uusdc")
+                .with_input_balance(Uint128::new(2_000_000))
+                .with_amount(Uint128::new(500_000))
+                .with_destination_domain(0)
+        )
+    );
+    assert_eq!(
+        event
+            .attributes
+            .iter()
+            .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("method", "cctp_transfer"),
+            ("status", "transferred"),
+            ("denom", "uusdc"),
+            ("input_balance", "2000000"),
+            ("amount", "500000"),
+            ("destination_domain", "0"),
+        ]
+    );
+}
+
+#[test]
+fn transfer_attributes_match_configured_route() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::zero());
+    let mint_recipient =
+        evm_address_to_mint_recipient("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap();
+    let mut cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None);
+    cfg.destination_domain_id = DestinationDomain::Base.id();
+    cfg.mint_recipient = mint_recipient;
+
+    let res = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::Transfer {},
+        cfg,
+    )
+    .unwrap();
+
+    assert_eq!(
+        res.attributes
+            .iter()
+            .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("method", "cctp_transfer"),
+            ("amount", "1000000"),
+            ("burn_token", "uusdc"),
+            ("destination_domain_id", "6"),
+            (
+                "mint_recipient",
+                "0x0000000000000000000000005aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn transfer_burns_from_the_configured_input_account() {
+    // An ICA account that is not the outbound ICA of the program
+    let mut deps = mock_deps_with_input_account(
+        "vault_ica",
+        "noble_vault_ica",
+        Uint128::new(3_000_000),
+        true,
+    );
+    let mut cfg = cctp_config(TransferAmount::FullBalance, None, None);
+    cfg.input_addr = Addr::unchecked("vault_ica");
+
+    let res = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::Transfer {},
+        cfg,
+    )
+    .unwrap();
+
+    let CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr, msg, ..
+    }) = &res.messages[0].msg
+    else {
+        panic!("expected the burn to be executed by the input account");
+    };
+    assert_eq!(contract_addr, "vault_ica");
+    let IcaExecuteMsg::ExecuteIcaMsgWithCallback { msgs } = from_json(msg).unwrap() else {
+        panic!("expected an ICA message with callback");
+    };
+    let burn = MsgDepositForBurn::decode(msgs[0].value.as_slice()).unwrap();
+    assert_eq!(burn.from, "noble_vault_ica");
+    assert_eq!(burn.amount, "3000000");
+}
+
+#[test]
+fn transfer_fails_when_library_is_not_approved_on_input_account() {
+    let mut deps = mock_deps_with_input_account(
+        "vault_ica",
+        "noble_vault_ica",
+        Uint128::new(3_000_000),
+        false,
+    );
+    let mut cfg = cctp_config(TransferAmount::FullBalance, None, None);
+    cfg.input_addr = Addr::unchecked("vault_ica");
+
+    let err = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::Transfer {},
+        cfg,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Library is not approved on input account vault_ica."
+    );
+}
+
+#[test]
+fn transfer_is_split_across_burn_legs() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::new(3_000_000));
+    let ethereum_recipient = padded_mint_recipient(&[0x02; 20]);
+    let arbitrum_recipient = padded_mint_recipient(&[0x03; 20]);
+    let cfg = cctp_config(TransferAmount::FullBalance, None, None).with_burn_legs(vec![
+        BurnLeg::new(
+            0,
+            ethereum_recipient.clone(),
+            BurnLegAmount::Ratio(Decimal::percent(50)),
+        ),
+        BurnLeg::new(
+            3,
+            arbitrum_recipient.clone(),
+            BurnLegAmount::Fixed(Uint128::new(1_000_000)),
+        ),
+    ]);
+
+    let res = execute_transfer(&mut deps, &mock_env(), &cfg).unwrap();
+
+    // One burn per leg, each in its own ICA transaction
+    let burns: Vec<MsgDepositForBurn> = res
+        .messages
+        .iter()
+        .map(|msg| {
+            let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &msg.msg else {
+                panic!("expected the burn to be executed by the input account");
+            };
+            let IcaExecuteMsg::ExecuteIcaMsgWithCallback { msgs } = from_json(msg).unwrap() else {
+                panic!("expected an ICA message with callback");
+            };
+            assert_eq!(msgs.len(), 1);
+            MsgDepositForBurn::decode(msgs[0].value.as_slice()).unwrap()
+        })
+        .collect();
+    let legs: Vec<_> = burns
+        .iter()
+        .map(|burn| {
+            (
+                burn.destination_domain,
+                burn.amount.as_str(),
+                Binary::from(burn.mint_recipient.clone()),
+            )
+        })
+        .collect();
+    assert_eq!(
+        legs,
+        vec![
+            (0, "1500000", ethereum_recipient),
+            (3, "1000000", arbitrum_recipient)
+        ]
+    );
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "amount" && attr.value == "2500000"));
+
+    // Each burn waits for the callback of its own ICA packet
+    let pending_burns: Vec<_> = PENDING_BURNS
+        .range(deps.as_ref().storage, None, None, Order::Ascending)
+        .map(|item| {
+            let ((channel_id, sequence), burn) = item.unwrap();
+            (
+                channel_id,
+                sequence,
+                burn.destination_domain_id,
+                burn.amount,
+            )
+        })
+        .collect();
+    assert_eq!(
+        pending_burns,
+        vec![
+            ("channel-0".to_string(), 1, 0, Uint128::new(1_500_000)),
+            ("channel-0".to_string(), 2, 3, Uint128::new(1_000_000))
+        ]
+    );
+}
+
+#[test]
+fn burn_legs_above_the_transfer_amount_are_rejected() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::new(3_000_000));
+    let cfg = cctp_config(TransferAmount::FullBalance, None, None).with_burn_legs(vec![
+        BurnLeg::new(
+            0,
+            padded_mint_recipient(&[0x02; 20]),
+            BurnLegAmount::Ratio(Decimal::percent(50)),
+        ),
+        BurnLeg::new(
+            3,
+            padded_mint_recipient(&[0x03; 20]),
+            BurnLegAmount::Fixed(Uint128::new(2_000_000)),
+        ),
+    ]);
+
+    let err = execute_transfer(&mut deps, &mock_env(), &cfg).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Burn legs add up to 3500000, more than the transfer amount 3000000."
+    );
+}
+
+#[test]
+fn pre_validate_rejects_invalid_burn_legs() {
+    let api = MockApi::default();
+    let leg = |domain_id, ratio| {
+        BurnLeg::new(
+            domain_id,
+            padded_mint_recipient(&[0x02; 20]),
+            BurnLegAmount::Ratio(Decimal::percent(ratio)),
+        )
+    };
+
+    let cfg = cctp_transfer_config(&api, DestinationDomain::Ethereum, false)
+        .with_burn_legs(vec![leg(0, 60), leg(3, 40)]);
+    cfg.pre_validate(&api).unwrap();
+
+    let cfg = cctp_transfer_config(&api, DestinationDomain::Ethereum, false)
+        .with_burn_legs(vec![leg(0, 60), leg(3, 50)]);
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        format!(
+            "Configuration error: {}",
+            CctpError::BurnLegRatiosExceedOne(Decimal::percent(110))
+        )
+    );
+
+    // Each leg is validated against the domains registry
+    let cfg = cctp_transfer_config(&api, DestinationDomain::Ethereum, false)
+        .with_burn_legs(vec![leg(0, 60), leg(9999, 40)]);
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        format!("Configuration error: {}", CctpError::UnknownDomain(9999))
+    );
+}
+
+fn attestation_sla(disabled_domains: Vec<u32>, skip_checks: bool) -> AttestationSla {
+    AttestationSla {
+        completion_seconds: 1_200,
+        disabled_domains,
+        attestation_times: vec![],
+        skip_checks,
+    }
+}
+
+#[test]
+fn transfer_within_attestation_sla_records_expected_completion() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::zero());
+    let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None)
+        .with_attestation_sla(attestation_sla(vec![DestinationDomain::Base.id()], false));
+    let env = mock_env();
+
+    let res = process_function(
+        deps.as_mut(),
+        env.clone(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::Transfer {},
+        cfg,
+    )
+    .unwrap();
+
+    let deadline = env.block.time.plus_seconds(1_200);
+    assert_eq!(res.messages.len(), 1);
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "expected_completion"
+            && attr.value == deadline.seconds().to_string()));
+
+    let expected_completion: Option<ExpectedCompletion> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::ExpectedCompletion {}).unwrap())
+            .unwrap();
+    assert_eq!(
+        expected_completion,
+        Some(ExpectedCompletion {
+            amount: Uint128::new(1_000_000),
+            destination_domain_id: 0,
+            deadline,
+        })
+    );
+}
+
+#[test]
+fn transfer_to_disabled_domain_is_rejected() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::zero());
+    let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None)
+        .with_attestation_sla(attestation_sla(vec![0], false));
+
+    let err = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::Transfer {},
+        cfg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Destination domain 0 is disabled: the burn would not be minted within the attestation SLA of 1200 seconds."
+    );
+    // Nothing is recorded for rejected burns
+    let expected_completion: Option<ExpectedCompletion> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::ExpectedCompletion {}).unwrap())
+            .unwrap();
+    assert_eq!(expected_completion, None);
+
+    // The check can be skipped, e.g. for testing
+    let cfg = cfg.with_attestation_sla(attestation_sla(vec![0], true));
+    process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::Transfer {},
+        cfg,
+    )
+    .unwrap();
+}
+
+#[test]
+fn transfer_to_domain_slower_than_attestation_sla_is_rejected() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::zero());
+    let attestation_time = |attestation_seconds| DomainAttestationTime {
+        domain_id: 0,
+        attestation_seconds,
+    };
+    let mut sla = attestation_sla(vec![], false);
+    sla.attestation_times = vec![attestation_time(1_201)];
+    let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None)
+        .with_attestation_sla(sla.clone());
+
+    let err = process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::Transfer {},
+        cfg,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Destination domain 0 takes 1201 seconds to mint: the burn would not be minted within the attestation SLA of 1200 seconds."
+    );
+
+    // A domain minting within the SLA is allowed
+    sla.attestation_times = vec![attestation_time(1_200)];
+    let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None)
+        .with_attestation_sla(sla);
+    process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::Transfer {},
+        cfg,
+    )
+    .unwrap();
+}
+
+#[test]
+fn pre_validate_rejects_duplicate_attestation_times() {
+    let api = MockApi::default();
+
+    let mut sla = attestation_sla(vec![], false);
+    sla.attestation_times = vec![
+        DomainAttestationTime {
+            domain_id: 0,
+            attestation_seconds: 60,
+        },
+        DomainAttestationTime {
+            domain_id: 0,
+            attestation_seconds: 900,
+        },
+    ];
+    let cfg =
+        cctp_transfer_config(&api, DestinationDomain::Ethereum, false).with_attestation_sla(sla);
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        "Configuration error: Invalid attestation SLA: duplicate attestation time for domain 0."
+    );
+}
+
+#[test]
+fn pre_validate_rejects_zero_attestation_sla() {
+    let api = MockApi::default();
+
+    let mut sla = attestation_sla(vec![], false);
+    sla.completion_seconds = 0;
+    let cfg =
+        cctp_transfer_config(&api, DestinationDomain::Ethereum, false).with_attestation_sla(sla);
+    assert_eq!(
+        cfg.pre_validate(&api).unwrap_err().to_string(),
+        "Configuration error: Invalid attestation SLA: completion seconds cannot be zero."
+    );
+}
+
+#[test]
+fn simulated_amount_matches_transferred_amount() {
+    for amount in [
+        TransferAmount::Fixed(Uint128::new(1_000_000)),
+        TransferAmount::Percentage(Decimal::percent(25)),
+        TransferAmount::FullBalance,
+    ] {
+        let mut deps = mock_deps_with_ica_balance(Uint128::new(2_000_000));
+        let cfg = cctp_config(amount, None, None);
+        valence_library_base::save_config(deps.as_mut().storage, &cfg).unwrap();
+
+        let simulated: SimulatedAmount =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::SimulateAmount {}).unwrap())
+                .unwrap();
+
+        let res = process_function(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&Addr::unchecked("processor"), &[]),
+            FunctionMsgs::Transfer {},
+            cfg,
+        )
+        .unwrap();
+        let transferred = res
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "amount")
+            .unwrap();
+
+        assert_eq!(simulated.denom, "uusdc");
+        assert_eq!(simulated.amount.to_string(), transferred.value);
+        assert_eq!(simulated.skip_reason, None);
+    }
+}
+
+#[test]
+fn simulate_amount_fails_like_the_transfer() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::zero());
+    let cfg = cctp_config(TransferAmount::FullBalance, None, None);
+    valence_library_base::save_config(deps.as_mut().storage, &cfg).unwrap();
+
+    let err = query(deps.as_ref(), mock_env(), QueryMsg::SimulateAmount {}).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Nothing to transfer: no uusdc balance available in the input account."));
+}
+
+#[test]
+fn only_successful_burns_accumulate_in_stats() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::new(2_000_000));
+    let mut env = mock_env();
+    let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None);
+    valence_library_base::save_config(deps.as_mut().storage, &cfg).unwrap();
+
+    let stats: TransferStats =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(stats, TransferStats::default());
+
+    for (amount, height) in [(1_000_000, 100), (300_000, 105), (200_000, 110)] {
+        env.block.height = height;
+        let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(amount)), None, None);
+        execute_transfer(&mut deps, &env, &cfg).unwrap();
+    }
+
+    // Burns are only counted once the input account reports their outcome
+    let stats: TransferStats =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(stats, TransferStats::default());
+
+    env.block.height = 120;
+    for (sequence, result) in [
+        (
+            1,
+            IcaTxResult::Error {
+                details: "insufficient funds".to_string(),
+            },
+        ),
+        (2, IcaTxResult::Timeout {}),
+        (
+            3,
+            IcaTxResult::Success {
+                data: burn_ack_data(42),
+            },
+        ),
+    ] {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&cfg.input_addr, &[]),
+            burn_callback(sequence, result),
+        )
+        .unwrap();
+    }
+
+    let stats: TransferStats =
+        from_json(query(deps.as_ref(), env, QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(
+        stats,
+        TransferStats {
+            total_amounts: BTreeMap::from([("uusdc".to_string(), Uint128::new(200_000))]),
+            executions: 1,
+            last_execution_height: Some(120),
+        }
+    );
+}
+
+// Acknowledgement data of an ICA transaction that executed a burn assigned `nonce`
+fn burn_ack_data(nonce: u64) -> Binary {
+    Binary::from(
+        TxMsgData {
+            msg_responses: vec![ProtoAny {
+                type_url: "/circle.cctp.v1.MsgDepositForBurnResponse".to_string(),
+                value: MsgDepositForBurnResponse { nonce }.encode_to_vec(),
+            }],
+        }
+        .encode_to_vec(),
+    )
+}
+
+fn burn_callback(sequence: u64, result: IcaTxResult) -> ExecuteMsg {
+    ExecuteMsg::IcaCallback(IcaCallbackMsg::IcaTxCallback {
+        channel_id: "channel-0".to_string(),
+        sequence,
+        result,
+    })
+}
+
+#[test]
+fn burn_nonce_is_recorded_from_callback() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::new(2_000_000));
+    let env = mock_env();
+    let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None);
+    valence_library_base::save_config(deps.as_mut().storage, &cfg).unwrap();
+
+    for _ in 0..2 {
+        execute_transfer(&mut deps, &env, &cfg).unwrap();
+    }
+
+    // Only the input account reports the outcome of the burns
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        message_info(&Addr::unchecked("other"), &[]),
+        burn_callback(
+            1,
+            IcaTxResult::Success {
+                data: burn_ack_data(42),
+            },
+        ),
+    )
+    .unwrap_err();
+    assert!(matches!(err, LibraryError::Unauthorized(_)));
+
+    let input_account = message_info(&cfg.input_addr, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        input_account.clone(),
+        burn_callback(
+            1,
+            IcaTxResult::Success {
+                data: burn_ack_data(42),
+            },
+        ),
+    )
+    .unwrap();
+
+    let event = res
+        .events
+        .iter()
+        .find(|event| event.ty == BURN_RESULT_EVENT_TYPE)
+        .unwrap();
+    let nonce = event
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "nonce")
+        .unwrap();
+    assert_eq!(nonce.value, "42");
+
+    let burn: Option<BurnRecord> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Burn { nonce: 42 }).unwrap())
+            .unwrap();
+    assert_eq!(
+        burn,
+        Some(BurnRecord {
+            amount: Uint128::new(1_000_000),
+            burn_token: "uusdc".to_string(),
+            destination_domain_id: 0,
+            mint_recipient: Binary::from(&[0x01; 32]),
+            sent_at: env.block.time,
+        })
+    );
+
+    // A failed burn is not assigned a nonce
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        input_account.clone(),
+        burn_callback(
+            2,
+            IcaTxResult::Error {
+                details: "insufficient funds".to_string(),
+            },
+        ),
+    )
+    .unwrap();
+    let event = res
+        .events
+        .iter()
+        .find(|event| event.ty == BURN_RESULT_EVENT_TYPE)
+        .unwrap();
+    assert!(!event.attributes.iter().any(|attr| attr.key == "nonce"));
+
+    let burn: Option<BurnRecord> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Burn { nonce: 43 }).unwrap())
+            .unwrap();
+    assert_eq!(burn, None);
+
+    // Every burn sent got its callback
+    let err = execute(
+        deps.as_mut(),
+        env,
+        input_account,
+        burn_callback(3, IcaTxResult::Timeout {}),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: No burn waiting for the outcome of ICA packet 3 on channel-0."
+    );
+}
+
+#[test]
+fn burn_callbacks_are_matched_to_their_ica_packet() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::new(2_000_000));
+    let env = mock_env();
+    let cfg = cctp_config(TransferAmount::FullBalance, None, None).with_burn_legs(vec![
+        BurnLeg::new(
+            0,
+            padded_mint_recipient(&[0x02; 20]),
+            BurnLegAmount::Fixed(Uint128::new(1_000_000)),
+        ),
+        BurnLeg::new(
+            3,
+            padded_mint_recipient(&[0x03; 20]),
+            BurnLegAmount::Fixed(Uint128::new(1_000_000)),
+        ),
+    ]);
+    valence_library_base::save_config(deps.as_mut().storage, &cfg).unwrap();
+    execute_transfer(&mut deps, &env, &cfg).unwrap();
+
+    // The burn of the second packet is acknowledged first, and gets its own nonce
+    let input_account = message_info(&cfg.input_addr, &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        input_account.clone(),
+        burn_callback(
+            2,
+            IcaTxResult::Success {
+                data: burn_ack_data(7),
+            },
+        ),
+    )
+    .unwrap();
+    let burn: Option<BurnRecord> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Burn { nonce: 7 }).unwrap()).unwrap();
+    assert_eq!(burn.unwrap().destination_domain_id, 3);
+
+    // The timed out burn stops waiting, and its packet can't be reported twice
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        input_account.clone(),
+        burn_callback(1, IcaTxResult::Timeout {}),
+    )
+    .unwrap();
+    assert!(PENDING_BURNS.is_empty(deps.as_ref().storage));
+
+    let err = execute(
+        deps.as_mut(),
+        env,
+        input_account,
+        burn_callback(
+            1,
+            IcaTxResult::Success {
+                data: burn_ack_data(8),
+            },
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: No burn waiting for the outcome of ICA packet 1 on channel-0."
+    );
+}
+
+// Sequence of the last ICA packet sent by the mocked input account
+const MOCK_ICA_SEQUENCE: Item<u64> = Item::new("mock_ica_sequence");
+
+// Encoding of the response of the input account to the execution of the library message
+#[derive(Clone, PartialEq, prost::Message)]
+struct MsgExecuteContractResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    data: Vec<u8>,
+}
+
+// Replies to each burn of `res` like the input account does once it submitted the burn in its own ICA packet
+fn submit_burns(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, res: &Response) {
+    for sub_msg in &res.messages {
+        assert_eq!(sub_msg.id, BURN_SUBMITTED_REPLY_ID);
+        let sequence = MOCK_ICA_SEQUENCE
+            .may_load(deps.as_ref().storage)
+            .unwrap()
+            .unwrap_or_default()
+            + 1;
+        MOCK_ICA_SEQUENCE
+            .save(deps.as_mut().storage, &sequence)
+            .unwrap();
+
+        let submission = IcaTxSubmission {
+            channel_id: "channel-0".to_string(),
+            sequence,
+        };
+        let data = MsgExecuteContractResponse {
+            data: to_json_binary(&submission).unwrap().to_vec(),
+        }
+        .encode_to_vec();
+        #[allow(deprecated)]
+        let result = SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(Binary::from(data)),
+            msg_responses: vec![],
+        });
+        reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: sub_msg.id,
+                payload: sub_msg.payload.clone(),
+                gas_used: 0,
+                result,
+            },
+        )
+        .unwrap();
+    }
+}
+
+fn execute_transfer(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    env: &Env,
+    cfg: &Config,
+) -> Result<Response, LibraryError> {
+    let res = process_function(
+        deps.as_mut(),
+        env.clone(),
+        message_info(&Addr::unchecked("processor"), &[]),
+        FunctionMsgs::Transfer {},
+        cfg.clone(),
+    )?;
+    submit_burns(deps, &res);
+    Ok(res)
+}
+
+#[test]
+fn burns_to_a_domain_wait_for_the_cooldown() {
+    let api = MockApi::default();
+    let err = cctp_transfer_config(&api, DestinationDomain::Ethereum, false)
+        .with_burn_cooldown(BurnCooldown::new(0, CooldownBehavior::Error))
+        .pre_validate(&api)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Invalid burn cooldown: seconds cannot be zero."
+    );
+
+    let mut deps = mock_deps_with_ica_balance(Uint128::new(2_000_000));
+    let mut env = mock_env();
+    let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None)
+        .with_burn_cooldown(BurnCooldown::new(3_600, CooldownBehavior::Error));
+    valence_library_base::save_config(deps.as_mut().storage, &cfg).unwrap();
+    let burn_time = env.block.time;
+
+    execute_transfer(&mut deps, &env, &cfg).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        message_info(&cfg.input_addr, &[]),
+        burn_callback(
+            1,
+            IcaTxResult::Success {
+                data: burn_ack_data(42),
+            },
+        ),
+    )
+    .unwrap();
+
+    // A second burn to the same domain is rejected within the cooldown, and so is its simulation
+    env.block.time = burn_time.plus_seconds(1_800);
+    let err = execute_transfer(&mut deps, &env, &cfg).unwrap_err();
+    let expected_error = format!(
+        "Execution error: Burn cooldown of destination domain 0 is active until {}.",
+        burn_time.plus_seconds(3_600).seconds()
+    );
+    assert_eq!(err.to_string(), expected_error);
+    let err = query(deps.as_ref(), env.clone(), QueryMsg::SimulateAmount {}).unwrap_err();
+    assert!(err.to_string().contains(&expected_error));
+
+    // Burns are allowed again once the cooldown elapsed
+    env.block.time = burn_time.plus_seconds(3_600);
+    let res = execute_transfer(&mut deps, &env, &cfg).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "amount" && attr.value == "1000000"));
+}
+
+#[test]
+fn only_successful_burns_start_the_cooldown() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::new(2_000_000));
+    let mut env = mock_env();
+    let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None)
+        .with_burn_cooldown(BurnCooldown::new(3_600, CooldownBehavior::Skip));
+    valence_library_base::save_config(deps.as_mut().storage, &cfg).unwrap();
+
+    execute_transfer(&mut deps, &env, &cfg).unwrap();
+
+    // The burn waiting for its outcome counts as a burn to the domain, the next one is skipped
+    env.block.time = env.block.time.plus_seconds(10);
+    let res = execute_transfer(&mut deps, &env, &cfg).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "skipped" && attr.value == "burn_cooldown_active"));
+    let simulated: SimulatedAmount =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::SimulateAmount {}).unwrap()).unwrap();
+    assert_eq!(
+        simulated,
+        SimulatedAmount::skipped("uusdc", "burn_cooldown_active")
+    );
+
+    // Once the burn failed, the cooldown no longer applies
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        message_info(&cfg.input_addr, &[]),
+        burn_callback(
+            1,
+            IcaTxResult::Error {
+                details: "insufficient funds".to_string(),
+            },
+        ),
+    )
+    .unwrap();
+    let res = execute_transfer(&mut deps, &env, &cfg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+}
+
+#[test]
+fn balance_based_amounts_do_not_burn_the_same_balance_twice() {
+    let mut deps = mock_deps_with_ica_balance(Uint128::new(2_000_000));
+    let env = mock_env();
+    let cfg = cctp_config(TransferAmount::FullBalance, None, None);
+    valence_library_base::save_config(deps.as_mut().storage, &cfg).unwrap();
+
+    execute_transfer(&mut deps, &env, &cfg).unwrap();
+
+    // The last known balance still includes the burn waiting for its outcome
+    let err = execute_transfer(&mut deps, &env, &cfg).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: Nothing to transfer: no uusdc balance available in the input account."
+    );
+
+    // A failed burn didn't spend the balance, which can be burnt again
+    let input_account = message_info(&cfg.input_addr, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        input_account.clone(),
+        burn_callback(
+            1,
+            IcaTxResult::Error {
+                details: "insufficient funds".to_string(),
+            },
+        ),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    let res = execute_transfer(&mut deps, &env, &cfg).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "amount" && attr.value == "2000000"));
+
+    // A successful burn is deducted from the last known balance of the input account
+    let res = execute(
+        deps.as_mut(),
+        env,
+        input_account,
+        burn_callback(
+            2,
+            IcaTxResult::Success {
+                data: burn_ack_data(42),
+            },
+        ),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr, msg, ..
+    }) = &res.messages[0].msg
+    else {
+        panic!("expected the burn to be deducted by the input account");
+    };
+    assert_eq!(contract_addr, cfg.input_addr.as_str());
+    assert_eq!(
+        from_json::<IcaExecuteMsg>(msg).unwrap(),
+        IcaExecuteMsg::DeductRemoteBalances {
+            balances: vec![Coin {
+                denom: "uusdc".to_string(),
+                amount: Uint128::new(2_000_000),
+            }],
+        }
+    );
+}
+
+#[test]
+fn burn_outcomes_take_effect_only_on_success() {
+    for (result, succeeded) in [
+        (
+            IcaTxResult::Success {
+                data: burn_ack_data(42),
+            },
+            true,
+        ),
+        (
+            IcaTxResult::Error {
+                details: "insufficient funds".to_string(),
+            },
+            false,
+        ),
+        (IcaTxResult::Timeout {}, false),
+    ] {
+        let mut deps = mock_deps_with_ica_balance(Uint128::new(2_000_000));
+        let env = mock_env();
+        let cfg = cctp_config(TransferAmount::Fixed(Uint128::new(1_000_000)), None, None)
+            .with_burn_cooldown(BurnCooldown::new(3_600, CooldownBehavior::Skip));
+        valence_library_base::save_config(deps.as_mut().storage, &cfg).unwrap();
+
+        execute_transfer(&mut deps, &env, &cfg).unwrap();
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&cfg.input_addr, &[]),
+            burn_callback(1, result),
+        )
+        .unwrap();
+
+        // The nonce, the balance deduction, the stats and the cooldown all come from the same outcome
+        let event = res
+            .events
+            .iter()
+            .find(|event| event.ty == BURN_RESULT_EVENT_TYPE)
+            .unwrap();
+        assert_eq!(
+            event.attributes.iter().any(|attr| attr.key == "nonce"),
+            succeeded
+        );
+        let burn: Option<BurnRecord> =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::Burn { nonce: 42 }).unwrap())
+                .unwrap();
+        assert_eq!(burn.is_some(), succeeded);
+        assert_eq!(res.messages.len(), usize::from(succeeded));
+        let stats: TransferStats =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::Stats {}).unwrap()).unwrap();
+        assert_eq!(stats.executions, u64::from(succeeded));
+        let res = execute_transfer(&mut deps, &env, &cfg).unwrap();
+        assert_eq!(res.messages.is_empty(), succeeded);
+    }
+}
+
+#[test]
+fn display_amount_is_converted_to_base_units_on_instantiation() {
+    let deps = mock_dependencies();
+    let mut cfg = cctp_transfer_config(&deps.api, DestinationDomain::Ethereum, false)
+        .with_decimals(6)
+        .with_max_amount(Uint128::new(2_000_000), MaxAmountBehavior::Error);
+    cfg.amount = TransferAmount::Display(Decimal::from_str("1.5").unwrap());
+
+    let config = cfg.validate(deps.as_ref()).unwrap();
+    assert_eq!(
+        config.amount,
+        TransferAmount::Fixed(Uint128::new(1_500_000))
+    );
+    assert_eq!(config.decimals, Some(6));
+
+    // The max amount is checked against the converted amount
+    cfg.amount = TransferAmount::Display(Decimal::from_str("2.5").unwrap());
+    let err = cfg.pre_validate(&deps.api).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Configuration error: {}",
+            CctpError::AmountExceedsMaxAmount {
+                amount: Uint128::new(2_500_000),
+                max_amount: Uint128::new(2_000_000),
+            }
+        )
+    );
+}
+
+#[test]
+fn lossy_display_amount_is_rejected() {
+    let api = MockApi::default();
+    let mut cfg = cctp_transfer_config(&api, DestinationDomain::Ethereum, false).with_decimals(6);
+    cfg.amount = TransferAmount::Display(Decimal::from_str("1.0000001").unwrap());
+
+    let err = cfg.pre_validate(&api).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Invalid amount: 1.0000001 has more than 6 decimals, it can't be converted to base units without losing precision."
+    );
+
+    // Display amounts can't be converted without the decimals of the denom
+    cfg.decimals = None;
+    cfg.amount = TransferAmount::Display(Decimal::from_str("1.5").unwrap());
+    let err = cfg.pre_validate(&api).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Invalid transfer config: decimals must be set to configure an amount in display units."
+    );
+}
+
+#[test]
+fn legacy_fixed_amount_config_is_migrated() {
+    let mut deps = mock_dependencies();
+    let input_addr = deps.api.addr_make("input_account");
+    let mint_recipient = Binary::from(&[0x01; 32]);
+    // Configs of the versions of the library burning a fixed amount to a numeric domain id
+    let legacy_config = |input_addr: String| {
+        format!(
+            r#"{{"input_addr":{input_addr},"amount":"1000000","denom":"uusdc","destination_domain_id":0,"mint_recipient":"{mint_recipient}"}}"#
+        )
+    };
+    deps.storage.set(
+        b"config",
+        legacy_config(to_json_string(&input_addr).unwrap()).as_bytes(),
+    );
+    deps.storage.set(
+        b"raw_library_config",
+        legacy_config(to_json_string(&LibraryAccountType::Addr(input_addr.to_string())).unwrap())
+            .as_bytes(),
+    );
+
+    migrate_legacy_config(deps.as_mut().storage).unwrap();
+
+    let cfg: Config = valence_library_base::load_config(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        cfg,
+        Config::new(
+            input_addr.clone(),
+            TransferAmount::Fixed(Uint128::new(1_000_000)),
+            "uusdc".to_string(),
+            0,
+            false,
+            mint_recipient.clone(),
+            None,
+            None,
+        )
+    );
+    let raw_cfg: LibraryConfig =
+        valence_library_base::load_raw_config(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        raw_cfg,
+        LibraryConfig::new(
+            LibraryAccountType::Addr(input_addr.to_string()),
+            TransferAmount::Fixed(Uint128::new(1_000_000)),
+            "uusdc".to_string(),
+            DestinationDomain::Ethereum,
+            false,
+            mint_recipient,
+            None,
+            None,
+        )
+    );
+
+    // Configs in the current format are left untouched
+    migrate_legacy_config(deps.as_mut().storage).unwrap();
+    let migrated: Config = valence_library_base::load_config(deps.as_ref().storage).unwrap();
+    assert_eq!(migrated, cfg);
+}
@@ -0,0 +1,66 @@
+use cosmwasm_schema::cw_serde;
+
+use crate::{domain::DestinationDomain, error::CctpError};
+
+/// Token that can be burnt with CCTP from the ICA on Noble
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum CctpToken {
+    Usdc,
+    Eurc,
+}
+
+/// Registry of the tokens supported by the library
+pub const SUPPORTED_TOKENS: [CctpToken; 2] = [CctpToken::Usdc, CctpToken::Eurc];
+
+/// Known domains on which EURC is minted.
+/// For more information, check https://developers.circle.com/stablecoins/supported-domains
+const EURC_DOMAINS: [DestinationDomain; 4] = [
+    DestinationDomain::Ethereum,
+    DestinationDomain::Avalanche,
+    DestinationDomain::Solana,
+    DestinationDomain::Base,
+];
+
+impl CctpToken {
+    /// Denom of the token on Noble, used as the burn token of the CCTP messages.
+    /// The token minted on the destination domain is the one paired with it by the CCTP token minters.
+    pub fn denom(&self) -> &'static str {
+        match self {
+            CctpToken::Usdc => "uusdc",
+            CctpToken::Eurc => "ueurc",
+        }
+    }
+
+    /// Returns the supported token burnt as the given denom, if any
+    pub fn from_denom(denom: &str) -> Option<Self> {
+        SUPPORTED_TOKENS
+            .into_iter()
+            .find(|token| token.denom() == denom)
+    }
+
+    /// Whether the token can be minted on the known domain with the given domain id
+    pub fn is_supported_on(&self, domain_id: u32) -> bool {
+        match self {
+            CctpToken::Usdc => DestinationDomain::from_domain_id(domain_id).is_some(),
+            CctpToken::Eurc => EURC_DOMAINS.iter().any(|domain| domain.id() == domain_id),
+        }
+    }
+
+    /// Resolves the configured denom to a supported token, rejecting tokens that are not minted on the destination domain.
+    /// Domain ids that are not in the registry (only accepted with `allow_unknown_domain`) can't be checked against it.
+    pub fn for_destination(denom: &str, destination_domain_id: u32) -> Result<Self, CctpError> {
+        let token = Self::from_denom(denom)
+            .ok_or_else(|| CctpError::UnsupportedToken(denom.to_string()))?;
+
+        let is_known_domain = DestinationDomain::from_domain_id(destination_domain_id).is_some();
+        if is_known_domain && !token.is_supported_on(destination_domain_id) {
+            return Err(CctpError::UnsupportedTokenDomain {
+                denom: denom.to_string(),
+                domain_id: destination_domain_id,
+            });
+        }
+
+        Ok(token)
+    }
+}
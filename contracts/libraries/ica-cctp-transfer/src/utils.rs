@@ -0,0 +1,151 @@
+use cosmwasm_std::Binary;
+use prost::Message;
+use sha3::{Digest, Keccak256};
+use valence_ibc_utils::address::RemoteAddress;
+
+use crate::{
+    domain::DestinationDomain,
+    error::CctpError,
+    proto::{MsgDepositForBurnResponse, TxMsgData},
+};
+
+/// Length in bytes of an EVM address
+pub const EVM_ADDRESS_LENGTH: usize = 20;
+/// Length in bytes of the CCTP mint recipient
+pub const MINT_RECIPIENT_LENGTH: usize = 32;
+/// Length in bytes of the CCTP destination caller
+pub const DESTINATION_CALLER_LENGTH: usize = 32;
+/// Maximum length in bytes of the hook data of a burn, which is relayed in the message body of the burn
+pub const MAX_HOOK_DATA_LENGTH: usize = 1024;
+
+/// Encodes an EVM address (with or without the `0x` prefix) into the 32 bytes, left-padded with zeroes,
+/// mint recipient expected by CCTP. Mixed-case addresses are verified against their EIP-55 checksum.
+/// For more information, check https://docs.noble.xyz/cctp/mint#example
+pub fn evm_address_to_mint_recipient(addr: &str) -> Result<Binary, CctpError> {
+    let hex_addr = addr
+        .strip_prefix("0x")
+        .or_else(|| addr.strip_prefix("0X"))
+        .unwrap_or(addr);
+
+    let addr_bytes =
+        hex::decode(hex_addr).map_err(|e| CctpError::InvalidHexAddress(e.to_string()))?;
+
+    if addr_bytes.len() != EVM_ADDRESS_LENGTH {
+        return Err(CctpError::InvalidAddressLength(addr_bytes.len()));
+    }
+
+    // All lowercase or all uppercase addresses carry no checksum
+    let is_mixed_case = hex_addr.chars().any(|c| c.is_ascii_lowercase())
+        && hex_addr.chars().any(|c| c.is_ascii_uppercase());
+    if is_mixed_case && eip55_checksum(hex_addr) != hex_addr {
+        return Err(CctpError::InvalidChecksum(addr.to_string()));
+    }
+
+    let mut mint_recipient = vec![0u8; MINT_RECIPIENT_LENGTH];
+    mint_recipient[(MINT_RECIPIENT_LENGTH - EVM_ADDRESS_LENGTH)..].copy_from_slice(&addr_bytes);
+
+    Ok(Binary::from(mint_recipient))
+}
+
+/// Checks that the mint recipient is the 32 bytes representation of an address on the destination domain.
+/// Bech32 addresses passed as raw bytes are rejected, and recipients on known EVM domains must be left-padded 20 bytes addresses.
+pub fn validate_mint_recipient(
+    mint_recipient: &Binary,
+    destination_domain_id: u32,
+) -> Result<(), CctpError> {
+    if let Ok(addr) = std::str::from_utf8(mint_recipient) {
+        if addr.parse::<RemoteAddress>().is_ok() {
+            return Err(CctpError::Bech32MintRecipient(addr.to_string()));
+        }
+    }
+
+    if mint_recipient.len() != MINT_RECIPIENT_LENGTH {
+        return Err(CctpError::InvalidMintRecipientLength {
+            expected: MINT_RECIPIENT_LENGTH,
+            got: mint_recipient.len(),
+        });
+    }
+
+    let is_evm_domain = DestinationDomain::from_domain_id(destination_domain_id)
+        .is_some_and(|domain| domain.is_evm());
+    let is_padded = mint_recipient[..(MINT_RECIPIENT_LENGTH - EVM_ADDRESS_LENGTH)]
+        .iter()
+        .all(|byte| *byte == 0);
+    if is_evm_domain && !is_padded {
+        return Err(CctpError::NotAnEvmMintRecipient(destination_domain_id));
+    }
+
+    Ok(())
+}
+
+/// Checks that the destination caller is the 32 bytes representation of an address. Noble rejects burns
+/// with a zero destination caller, the legacy burn being the one that any caller can receive
+pub fn validate_destination_caller(destination_caller: &Binary) -> Result<(), CctpError> {
+    if destination_caller.len() != DESTINATION_CALLER_LENGTH {
+        return Err(CctpError::InvalidDestinationCallerLength {
+            expected: DESTINATION_CALLER_LENGTH,
+            got: destination_caller.len(),
+        });
+    }
+
+    if destination_caller.iter().all(|byte| *byte == 0) {
+        return Err(CctpError::ZeroDestinationCaller);
+    }
+
+    Ok(())
+}
+
+/// Checks that the hook data is not empty and that it fits in the message body of a burn.
+/// The `circle.cctp.v1` burns registered on Noble carry no hook data, so the library doesn't attach any yet
+pub fn validate_hook_data(hook_data: &Binary) -> Result<(), CctpError> {
+    if hook_data.is_empty() || hook_data.len() > MAX_HOOK_DATA_LENGTH {
+        return Err(CctpError::InvalidHookDataLength {
+            max: MAX_HOOK_DATA_LENGTH,
+            got: hook_data.len(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the nonce assigned to the burn on Noble, decoded from the acknowledgement data of the ICA
+/// transaction that executed it. Returns None if the data holds no burn response
+pub fn burn_nonce(ack_data: &[u8]) -> Option<u64> {
+    let tx_msg_data = TxMsgData::decode(ack_data).ok()?;
+
+    tx_msg_data
+        .msg_responses
+        .iter()
+        // Responses of the burns, with or without destination caller
+        .find(|response| {
+            response.type_url.ends_with(".MsgDepositForBurnResponse")
+                || response
+                    .type_url
+                    .ends_with(".MsgDepositForBurnWithCallerResponse")
+        })
+        .and_then(|response| MsgDepositForBurnResponse::decode(response.value.as_slice()).ok())
+        .map(|response| response.nonce)
+}
+
+/// Returns the EIP-55 checksummed representation of a hex address without the `0x` prefix
+fn eip55_checksum(hex_addr: &str) -> String {
+    let lowercase_addr = hex_addr.to_ascii_lowercase();
+    let hash = Keccak256::digest(lowercase_addr.as_bytes());
+
+    lowercase_addr
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = if i % 2 == 0 {
+                hash[i / 2] >> 4
+            } else {
+                hash[i / 2] & 0x0f
+            };
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
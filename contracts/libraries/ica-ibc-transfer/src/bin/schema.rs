@@ -1,12 +1,13 @@
 use cosmwasm_schema::write_api;
 
-use valence_ica_ibc_transfer::msg::{FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
-use valence_library_utils::msg::{ExecuteMsg, InstantiateMsg};
+use valence_ica_ibc_transfer::msg::{ExecuteMsg, LibraryConfig, LibraryConfigUpdate, QueryMsg};
+use valence_library_utils::msg::{InstantiateMsg, MigrateMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg<LibraryConfig>,
-        execute: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
+        execute: ExecuteMsg,
+        migrate: MigrateMsg<LibraryConfigUpdate>,
         query: QueryMsg,
     }
 }
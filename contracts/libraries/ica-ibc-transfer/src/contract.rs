@@ -1,17 +1,25 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_json_binary, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response,
+    StdError, StdResult, SubMsgResult,
+};
 use valence_library_utils::{
     error::LibraryError,
-    msg::{ExecuteMsg, InstantiateMsg},
+    msg::{InstantiateMsg, MigrateMsg},
 };
 
-use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
+use crate::{
+    msg::{
+        Config, ExecuteMsg, LibraryConfig, LibraryConfigUpdate, PostTransferCallbackMsg, QueryMsg,
+        TransfersResponse,
+    },
+    state::{CLOSED_CHANNELS, IN_FLIGHT_TRANSFERS, LAST_TRANSFER, PENDING_RETRY_AMOUNTS},
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
-const DEFAULT_IBC_TIMEOUT: u64 = 600; // 10 minutes
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -25,40 +33,201 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    msg: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
+    msg: ExecuteMsg,
 ) -> Result<Response, LibraryError> {
-    valence_library_base::execute(
-        deps,
+    let msg = match msg {
+        ExecuteMsg::Library(msg) => msg,
+        // Callbacks are handled even while the library is paused, so that no outcome is lost
+        ExecuteMsg::IcaCallback(callback) => {
+            return callbacks::handle_ica_callback(deps, env, info, callback)
+        }
+        ExecuteMsg::PostTransferCallback(PostTransferCallbackMsg::EnqueueArrivedTransfers {}) => {
+            return callbacks::handle_enqueue_arrived_transfers(deps)
+        }
+    };
+
+    // Config updates are audited against the config they replace
+    let previous_config: Option<Config> = match msg {
+        valence_library_utils::msg::ExecuteMsg::UpdateConfig { .. } => {
+            Some(valence_library_base::load_config(deps.storage)?)
+        }
+        _ => None,
+    };
+
+    let response = valence_library_base::execute(
+        deps.branch(),
         env,
         info,
         msg,
         functions::process_function,
         execute::update_config,
+    )?;
+
+    match previous_config {
+        Some(previous_config) => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            Ok(response.add_event(execute::config_update_event(&previous_config, &config)?))
+        }
+        None => Ok(response),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, LibraryError> {
+    match msg.id {
+        callbacks::TRANSFER_SUBMITTED_REPLY_ID => callbacks::handle_transfer_submitted(deps, msg),
+        // The post-transfer callback only replies once it failed. The failure is reported instead of
+        // reverting the ICA callback, so that the outcome of the transfer is still recorded
+        callbacks::POST_TRANSFER_CALLBACK_REPLY_ID => match msg.result {
+            SubMsgResult::Err(reason) => Ok(Response::new()
+                .add_attribute("method", "post_transfer_callback_failed")
+                .add_attribute("reason", reason)),
+            SubMsgResult::Ok(_) => Ok(Response::new()),
+        },
+        _ => Err(LibraryError::Std(StdError::generic_err("Unknown reply id"))),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
     )
 }
 
-mod functions {
+pub(crate) mod functions {
     use std::collections::BTreeMap;
 
     use cosmos_sdk_proto::{cosmos::base::v1beta1::Coin, prost::Name, traits::MessageExt};
     use cosmwasm_std::{
-        to_json_string, AnyMsg, Binary, DepsMut, Env, MessageInfo, Response, StdResult,
+        to_json_binary, to_json_string, AnyMsg, Binary, Deps, DepsMut, Empty, Env, MessageInfo,
+        Order, QuerierWrapper, QueryRequest, Response, Storage, SubMsg, Uint128,
     };
-    use ibc_proto::ibc::apps::transfer::v1::MsgTransfer;
-    use valence_ibc_utils::types::{
-        ForwardMetadata, PacketForwardMiddlewareConfig, PacketMetadata,
+    use ibc_proto::ibc::{
+        apps::transfer::v1::MsgTransfer,
+        core::{channel::v1::QueryChannelRequest, client::v1::Height},
     };
+    use serde::Deserialize;
+    use valence_ibc_utils::types::{PacketForwardMiddlewareConfig, PacketMetadata};
     use valence_library_utils::{
         error::LibraryError,
-        ica::{execute_on_behalf_of, get_remote_ica_address},
+        events::TransferEvent,
+        ica::{execute_on_behalf_of_with_callback, get_remote_ica_address, get_remote_ica_balance},
     };
 
-    use crate::msg::{Config, FunctionMsgs};
+    use crate::{
+        contract::callbacks::TRANSFER_SUBMITTED_REPLY_ID,
+        msg::{
+            is_json_object, Config, FunctionMsgs, RemoteChainInfo, TimeoutPolicy, TransferRecord,
+            TransferStatus, DEFAULT_IBC_TIMEOUT_SECONDS, MAX_IN_FLIGHT_TRANSFERS,
+        },
+        state::{CLOSED_CHANNELS, IN_FLIGHT_TRANSFERS, LAST_TRANSFER},
+    };
 
-    use super::DEFAULT_IBC_TIMEOUT;
+    /// Returns the known remote balance of the input account, only queried when a minimum transfer amount is configured.
+    /// A denom without a known remote balance counts as an empty balance.
+    fn query_input_balance(deps: Deps, cfg: &Config) -> Option<Uint128> {
+        cfg.min_transfer_amount?;
+
+        Some(
+            get_remote_ica_balance(deps, cfg.input_addr.as_str(), &cfg.denom)
+                .map(|balance| balance.amount)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Returns a skip response while the known remote balance of the input account is below the configured minimum
+    fn skip_below_min_transfer_amount(
+        cfg: &Config,
+        method: &str,
+        input_balance: Option<Uint128>,
+    ) -> Option<Response> {
+        let (Some(min_transfer_amount), Some(balance)) = (cfg.min_transfer_amount, input_balance)
+        else {
+            return None;
+        };
+
+        if balance >= min_transfer_amount {
+            return None;
+        }
+
+        let transfer_event = TransferEvent::new(method, &cfg.denom)
+            .with_input_balance(balance)
+            .skipped("balance below min_transfer_amount");
+
+        Some(
+            Response::new()
+                .add_attribute("method", "ica_ibc_transfer_skipped")
+                .add_attribute("balance", balance.to_string())
+                .add_attribute("min_transfer_amount", min_transfer_amount.to_string())
+                .add_event(transfer_event),
+        )
+    }
+
+    /// Event describing a transfer sent through the selected channel
+    fn transfer_event(
+        cfg: &Config,
+        method: &str,
+        input_balance: Option<Uint128>,
+        channel_id: &str,
+    ) -> TransferEvent {
+        TransferEvent {
+            input_balance,
+            ..TransferEvent::new(method, &cfg.denom)
+                .with_amount(cfg.amount)
+                .with_channel_id(channel_id)
+        }
+    }
+
+    /// Rejects new transfers while too many transfers are waiting for their outcome
+    fn ensure_in_flight_capacity(storage: &dyn Storage) -> Result<(), LibraryError> {
+        let in_flight = IN_FLIGHT_TRANSFERS
+            .keys(storage, None, None, Order::Ascending)
+            .take(MAX_IN_FLIGHT_TRANSFERS)
+            .count();
+        if in_flight >= MAX_IN_FLIGHT_TRANSFERS {
+            return Err(LibraryError::ExecutionError(format!(
+                "Too many transfers waiting for their outcome (max {MAX_IN_FLIGHT_TRANSFERS})."
+            )));
+        }
+        Ok(())
+    }
+
+    /// Records a sent transfer as the last one, and returns the submessage executing it on the input account.
+    /// The transfer is kept in flight, under the ICA packet returned by the input account, until the input
+    /// account calls back with its outcome
+    fn submit_transfer(
+        storage: &mut dyn Storage,
+        cfg: &Config,
+        any_msg: AnyMsg,
+        channel_id: &str,
+    ) -> Result<SubMsg, LibraryError> {
+        let record = TransferRecord {
+            amount: cfg.amount,
+            denom: cfg.denom.clone(),
+            channel_id: channel_id.to_string(),
+            status: TransferStatus::Pending,
+        };
+        LAST_TRANSFER.save(storage, &record)?;
+
+        let input_account_msg = execute_on_behalf_of_with_callback(vec![any_msg], &cfg.input_addr)?;
+        Ok(
+            SubMsg::reply_on_success(input_account_msg, TRANSFER_SUBMITTED_REPLY_ID)
+                .with_payload(to_json_binary(&record)?),
+        )
+    }
 
     pub fn process_function(
         deps: DepsMut,
@@ -67,23 +236,47 @@ mod functions {
         msg: FunctionMsgs,
         cfg: Config,
     ) -> Result<Response, LibraryError> {
-        let remote_address = get_remote_ica_address(deps.as_ref(), cfg.input_addr.as_str())?;
-
         match msg {
             FunctionMsgs::Transfer {} => {
+                // The input account must exist remotely before its balance is looked at
+                let remote_address =
+                    get_remote_ica_address(deps.as_ref(), cfg.input_addr.as_str())?;
+                let input_balance = query_input_balance(deps.as_ref(), &cfg);
+                if let Some(skipped) =
+                    skip_below_min_transfer_amount(&cfg, "ica_ibc_transfer", input_balance)
+                {
+                    return Ok(skipped);
+                }
+                ensure_in_flight_capacity(deps.storage)?;
+                let channel_id = select_channel(deps.as_ref(), &cfg.remote_chain_info)?;
+
+                let memo = cfg.transfer_memo();
+                // The rendered values could have broken the JSON forwarded by PFM
+                if cfg.memo_template.is_some()
+                    && cfg.denom_to_pfm_map.contains_key(&cfg.denom)
+                    && !is_json_object(&memo)
+                {
+                    return Err(LibraryError::ExecutionError(
+                        "Rendered memo_template is not a JSON object.".to_string(),
+                    ));
+                }
+
                 // Create the proto message
                 let proto_msg = create_msg_transfer(
                     &env,
-                    cfg.remote_chain_info.channel_id,
+                    channel_id,
                     cfg.receiver.clone(),
                     cfg.denom.clone(),
                     cfg.amount,
-                    cfg.memo.clone(),
-                    cfg.remote_chain_info.ibc_transfer_timeout,
+                    memo,
+                    cfg.remote_chain_info.ibc_transfer_timeout.as_ref(),
                     &cfg.denom_to_pfm_map,
                     remote_address,
                 )?;
 
+                check_packet_memo(&proto_msg.memo, cfg.max_memo_bytes())?;
+                let selected_channel_id = proto_msg.source_channel.clone();
+
                 // Create the Any
                 let any_msg = AnyMsg {
                     type_url: MsgTransfer::type_url(),
@@ -92,14 +285,35 @@ mod functions {
                     })?),
                 };
 
-                let input_account_msgs = execute_on_behalf_of(vec![any_msg], &cfg.input_addr)?;
+                let input_account_msg =
+                    submit_transfer(deps.storage, &cfg, any_msg, &selected_channel_id)?;
+                let transfer_event = transfer_event(
+                    &cfg,
+                    "ica_ibc_transfer",
+                    input_balance,
+                    &selected_channel_id,
+                );
 
                 Ok(Response::new()
-                    .add_message(input_account_msgs)
-                    .add_attribute("method", "ica_ibc_transfer"))
+                    .add_submessage(input_account_msg)
+                    .add_attribute("method", "ica_ibc_transfer")
+                    .add_attribute("selected_channel_id", selected_channel_id)
+                    .add_event(transfer_event))
             }
             FunctionMsgs::EurekaTransfer { eureka_fee } => {
-                let eureka_config = match cfg.eureka_config {
+                // The input account must exist remotely before its balance is looked at
+                let remote_address =
+                    get_remote_ica_address(deps.as_ref(), cfg.input_addr.as_str())?;
+                let input_balance = query_input_balance(deps.as_ref(), &cfg);
+                if let Some(skipped) =
+                    skip_below_min_transfer_amount(&cfg, "ica_eureka_ibc_transfer", input_balance)
+                {
+                    return Ok(skipped);
+                }
+                ensure_in_flight_capacity(deps.storage)?;
+                let channel_id = select_channel(deps.as_ref(), &cfg.remote_chain_info)?;
+
+                let eureka_config = match cfg.eureka_config.clone() {
                     Some(config) => config,
                     None => {
                         return Err(LibraryError::ExecutionError(
@@ -118,16 +332,19 @@ mod functions {
                 // Create the proto message
                 let proto_msg = create_msg_transfer(
                     &env,
-                    cfg.remote_chain_info.channel_id,
+                    channel_id,
                     eureka_config.callback_contract,
                     cfg.denom.clone(),
                     cfg.amount,
                     eureka_memo,
-                    cfg.remote_chain_info.ibc_transfer_timeout,
+                    cfg.remote_chain_info.ibc_transfer_timeout.as_ref(),
                     &BTreeMap::default(),
                     remote_address,
                 )?;
 
+                check_packet_memo(&proto_msg.memo, cfg.max_memo_bytes())?;
+                let selected_channel_id = proto_msg.source_channel.clone();
+
                 // Create the Any
                 let any_msg = AnyMsg {
                     type_url: MsgTransfer::type_url(),
@@ -136,11 +353,171 @@ mod functions {
                     })?),
                 };
 
-                let input_account_msgs = execute_on_behalf_of(vec![any_msg], &cfg.input_addr)?;
+                let input_account_msg =
+                    submit_transfer(deps.storage, &cfg, any_msg, &selected_channel_id)?;
+                let transfer_event = transfer_event(
+                    &cfg,
+                    "ica_eureka_ibc_transfer",
+                    input_balance,
+                    &selected_channel_id,
+                );
 
                 Ok(Response::new()
-                    .add_message(input_account_msgs)
-                    .add_attribute("method", "ica_eureka_ibc_transfer"))
+                    .add_submessage(input_account_msg)
+                    .add_attribute("method", "ica_eureka_ibc_transfer")
+                    .add_attribute("selected_channel_id", selected_channel_id)
+                    .add_event(transfer_event))
+            }
+            FunctionMsgs::UpdateChannelStatus { channel_id, open } => {
+                update_channel_status(deps, &cfg, channel_id, open)
+            }
+        }
+    }
+
+    fn update_channel_status(
+        deps: DepsMut,
+        cfg: &Config,
+        channel_id: String,
+        open: bool,
+    ) -> Result<Response, LibraryError> {
+        if !cfg
+            .remote_chain_info
+            .channel_routes()
+            .any(|id| *id == channel_id)
+        {
+            return Err(LibraryError::ExecutionError(format!(
+                "Channel {channel_id} is not part of the configured routes."
+            )));
+        }
+
+        if open {
+            CLOSED_CHANNELS.remove(deps.storage, channel_id.clone());
+        } else {
+            CLOSED_CHANNELS.save(deps.storage, channel_id.clone(), &Empty {})?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("method", "update_channel_status")
+            .add_attribute("channel_id", channel_id)
+            .add_attribute("open", open.to_string()))
+    }
+
+    /// Checks the memo of a packet before it reaches the relayers: it must fit in `max_memo_bytes`,
+    /// and a memo meant for a middleware (PFM, Eureka or wasm hooks), which starts with `{`, must be valid JSON.
+    /// Memos are strings, so they are always valid UTF-8
+    pub(crate) fn check_packet_memo(memo: &str, max_memo_bytes: u32) -> Result<(), LibraryError> {
+        if memo.len() > max_memo_bytes as usize {
+            return Err(LibraryError::ExecutionError(format!(
+                "Packet memo of {} bytes exceeds the max memo size of {max_memo_bytes} bytes.",
+                memo.len()
+            )));
+        }
+
+        if memo.trim_start().starts_with('{')
+            && serde_json::from_str::<serde_json::Value>(memo).is_err()
+        {
+            return Err(LibraryError::ExecutionError(
+                "Packet memo is not valid JSON.".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Picks the first route, in order of preference, whose channel is not flagged as closed
+    /// and whose counterparty channel on this chain, if configured, is open
+    pub(crate) fn select_channel(
+        deps: Deps,
+        remote_chain_info: &RemoteChainInfo,
+    ) -> Result<String, LibraryError> {
+        for channel_id in remote_chain_info.channel_routes() {
+            if CLOSED_CHANNELS.has(deps.storage, channel_id.to_string()) {
+                continue;
+            }
+            match remote_chain_info.counterparty_channel_ids.get(channel_id) {
+                Some(counterparty_channel_id)
+                    if !is_channel_open(&deps.querier, counterparty_channel_id)? => {}
+                _ => return Ok(channel_id.clone()),
+            }
+        }
+
+        Err(LibraryError::ExecutionError(
+            "No open channel available in the configured routes.".to_string(),
+        ))
+    }
+
+    // Response of the channel query of the IBC module, of which only the state of the channel is read
+    #[derive(Deserialize)]
+    pub(crate) struct QueryChannelResponse {
+        pub channel: Option<ChannelEnd>,
+    }
+
+    #[derive(Deserialize)]
+    pub(crate) struct ChannelEnd {
+        pub state: String,
+    }
+
+    /// Queries the IBC module of this chain for the state of a transfer channel
+    pub(crate) fn is_channel_open(
+        querier: &QuerierWrapper,
+        channel_id: &str,
+    ) -> Result<bool, LibraryError> {
+        let request = QueryChannelRequest {
+            port_id: "transfer".to_string(),
+            channel_id: channel_id.to_string(),
+        };
+        #[allow(deprecated)]
+        let query = QueryRequest::Stargate {
+            path: "/ibc.core.channel.v1.Query/Channel".to_string(),
+            data: Binary::from(request.to_bytes().map_err(|e| {
+                LibraryError::ExecutionError(format!("Failed to encode QueryChannelRequest: {e}"))
+            })?),
+        };
+
+        let response: QueryChannelResponse = querier.query(&query).map_err(|e| {
+            LibraryError::ExecutionError(format!(
+                "Failed to query the state of channel {channel_id}: {e}"
+            ))
+        })?;
+
+        Ok(response
+            .channel
+            .is_some_and(|channel| channel.state == "STATE_OPEN"))
+    }
+
+    /// Computes the timeout height and timestamp (in nanoseconds) of the packet from the timeout policy
+    pub(crate) fn packet_timeout(
+        env: &Env,
+        timeout: Option<&TimeoutPolicy>,
+    ) -> Result<(Option<Height>, u64), LibraryError> {
+        match timeout {
+            None => Ok((
+                None,
+                env.block
+                    .time
+                    .plus_seconds(DEFAULT_IBC_TIMEOUT_SECONDS)
+                    .nanos(),
+            )),
+            Some(TimeoutPolicy::Relative(seconds)) => {
+                Ok((None, env.block.time.plus_seconds(*seconds).nanos()))
+            }
+            Some(TimeoutPolicy::BlockHeight {
+                revision_number,
+                revision_height,
+            }) => Ok((
+                Some(Height {
+                    revision_number: *revision_number,
+                    revision_height: *revision_height,
+                }),
+                0,
+            )),
+            Some(TimeoutPolicy::Absolute(timestamp)) => {
+                if *timestamp <= env.block.time {
+                    return Err(LibraryError::ExecutionError(format!(
+                        "IBC transfer timeout {timestamp} is not in the future."
+                    )));
+                }
+                Ok((None, timestamp.nanos()))
             }
         }
     }
@@ -153,15 +530,11 @@ mod functions {
         denom: String,
         amount: impl ToString,
         memo: String,
-        timeout_seconds: Option<u64>,
+        timeout: Option<&TimeoutPolicy>,
         denom_to_pfm_map: &BTreeMap<String, PacketForwardMiddlewareConfig>,
         sender: String,
-    ) -> StdResult<MsgTransfer> {
-        let timeout_timestamp = env
-            .block
-            .time
-            .plus_seconds(timeout_seconds.unwrap_or(DEFAULT_IBC_TIMEOUT))
-            .nanos();
+    ) -> Result<MsgTransfer, LibraryError> {
+        let (timeout_height, timeout_timestamp) = packet_timeout(env, timeout)?;
 
         let msg = match denom_to_pfm_map.get(&denom) {
             // No PFM config for the denom sent
@@ -174,7 +547,7 @@ mod functions {
                 }),
                 sender,
                 receiver,
-                timeout_height: None,
+                timeout_height,
                 timeout_timestamp,
                 memo,
             },
@@ -182,12 +555,11 @@ mod functions {
             Some(pfm_config) => {
                 // Create the packet metadata for forwarding
                 let pfm_memo = to_json_string(&PacketMetadata {
-                    forward: Some(ForwardMetadata {
-                        receiver: receiver.clone(),
-                        port: "transfer".to_string(),
-                        // hop chain to final receiver chain channel
-                        channel: pfm_config.hop_to_destination_chain_channel_id.to_string(),
-                    }),
+                    forward: Some(pfm_config.forward_metadata(
+                        receiver.clone(),
+                        // Only JSON memos can be carried by the forwarded packet
+                        is_json_object(&memo).then_some(memo),
+                    )),
                 })?;
 
                 MsgTransfer {
@@ -202,7 +574,7 @@ mod functions {
                         .hop_chain_receiver_address
                         .clone()
                         .unwrap_or("pfm".to_string()),
-                    timeout_height: None,
+                    timeout_height,
                     timeout_timestamp,
                     memo: pfm_memo,
                 }
@@ -213,11 +585,264 @@ mod functions {
     }
 }
 
-mod execute {
-    use cosmwasm_std::{DepsMut, Env, MessageInfo};
+pub(crate) mod callbacks {
+    use cosmwasm_std::{
+        from_json, DepsMut, Env, Event, MessageInfo, Order, Reply, Response, StdError, StdResult,
+        SubMsg, SubMsgResult, Uint128, WasmMsg,
+    };
+    use cw_utils::parse_execute_response_data;
+    use valence_account_utils::ica::{IcaCallbackMsg, IcaTxResult, IcaTxSubmission};
+    use valence_library_utils::{
+        error::{LibraryError, UnauthorizedReason},
+        stats::record_transfers,
+    };
+
+    use crate::{
+        msg::{
+            CheckedPostTransferCallback, Config, TransferRecord, TransferStatus,
+            MAX_IN_FLIGHT_TRANSFERS,
+        },
+        state::{
+            COUNTED_ARRIVAL_BALANCE, IN_FLIGHT_TRANSFERS, LAST_TRANSFER, LAST_TRANSFER_PACKET,
+            PENDING_POST_TRANSFER_CALLBACKS, PENDING_RETRY_AMOUNTS,
+        },
+    };
+
+    /// Id of the reply of the post-transfer callback, only received when it fails
+    pub const POST_TRANSFER_CALLBACK_REPLY_ID: u64 = 0;
+    /// Id of the reply of the input account once it submitted a transfer, with the ICA packet carrying it
+    pub const TRANSFER_SUBMITTED_REPLY_ID: u64 = 1;
+
+    /// Type of the event emitted once the outcome of a transfer is reported by the input account,
+    /// with the outcome, the transfer and the ICA packet that carried it.
+    /// Once emitted by a contract, the event type is prefixed with `wasm-`.
+    pub const TRANSFER_RESULT_EVENT_TYPE: &str = "valence.ica_ibc_transfer.transfer_result";
+
+    /// Keeps the submitted transfer in flight under the ICA packet that carries it, so that the outcome
+    /// reported for that packet is matched to it whatever the order the packets are acknowledged in
+    pub fn handle_transfer_submitted(deps: DepsMut, msg: Reply) -> Result<Response, LibraryError> {
+        let SubMsgResult::Ok(response) = msg.result else {
+            // Only successful submissions reply
+            return Err(LibraryError::ExecutionError(
+                "Transfer submission failed.".to_string(),
+            ));
+        };
+        #[allow(deprecated)]
+        let data = response.data.ok_or_else(|| {
+            LibraryError::ExecutionError(
+                "Input account did not return the ICA packet of the transfer.".to_string(),
+            )
+        })?;
+        let execute_data = parse_execute_response_data(&data)
+            .map_err(|e| LibraryError::ExecutionError(e.to_string()))?;
+        let submission: IcaTxSubmission = from_json(execute_data.data.unwrap_or_default())?;
+        let transfer: TransferRecord = from_json(&msg.payload)?;
+
+        let packet = (submission.channel_id, submission.sequence);
+        IN_FLIGHT_TRANSFERS.save(deps.storage, packet.clone(), &transfer)?;
+        LAST_TRANSFER_PACKET.save(deps.storage, &packet)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "transfer_submitted")
+            .add_attribute("ica_channel_id", packet.0)
+            .add_attribute("ica_sequence", packet.1.to_string()))
+    }
+
+    pub fn handle_ica_callback(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: IcaCallbackMsg,
+    ) -> Result<Response, LibraryError> {
+        let cfg: Config = valence_library_base::load_config(deps.storage)?;
+        // Only the input account reports the outcome of the transfers it executed
+        if info.sender != cfg.input_addr {
+            return Err(LibraryError::Unauthorized(
+                UnauthorizedReason::NotAllowed {},
+            ));
+        }
+
+        let IcaCallbackMsg::IcaTxCallback {
+            channel_id: ica_channel_id,
+            sequence,
+            result,
+        } = msg;
+
+        let packet = (ica_channel_id, sequence);
+        let mut transfer = IN_FLIGHT_TRANSFERS
+            .may_load(deps.storage, packet.clone())?
+            .ok_or_else(|| {
+                LibraryError::ExecutionError(format!(
+                    "No transfer waiting for the outcome of ICA packet {} on {}.",
+                    packet.1, packet.0
+                ))
+            })?;
+        IN_FLIGHT_TRANSFERS.remove(deps.storage, packet.clone());
+
+        let mut pending_retry_amount = PENDING_RETRY_AMOUNTS
+            .may_load(deps.storage, transfer.denom.clone())?
+            .unwrap_or_default();
+        let (outcome, status) = match result {
+            IcaTxResult::Success { .. } => {
+                // Only transfers that reached the remote chain are counted, so retries of failed transfers aren't counted twice
+                record_transfers(
+                    deps.storage,
+                    env.block.height,
+                    &[(transfer.denom.clone(), transfer.amount)],
+                )?;
+                // A successful transfer settles the amount re-credited by the failed ones
+                pending_retry_amount = pending_retry_amount.saturating_sub(transfer.amount);
+                ("succeeded", TransferStatus::Succeeded)
+            }
+            IcaTxResult::Error { details } => {
+                ("failed", TransferStatus::Failed { reason: details })
+            }
+            IcaTxResult::Timeout {} => ("timed_out", TransferStatus::TimedOut),
+        };
+        let failed = !matches!(status, TransferStatus::Succeeded);
+        if failed && cfg.recredit_failed_transfers {
+            pending_retry_amount = pending_retry_amount
+                .checked_add(transfer.amount)
+                .map_err(StdError::from)?;
+        }
+        if pending_retry_amount.is_zero() {
+            PENDING_RETRY_AMOUNTS.remove(deps.storage, transfer.denom.clone());
+        } else {
+            PENDING_RETRY_AMOUNTS.save(
+                deps.storage,
+                transfer.denom.clone(),
+                &pending_retry_amount,
+            )?;
+        }
+
+        let mut event = Event::new(TRANSFER_RESULT_EVENT_TYPE)
+            .add_attribute("result", outcome)
+            .add_attribute("amount", transfer.amount)
+            .add_attribute("denom", &transfer.denom)
+            .add_attribute("channel_id", &transfer.channel_id)
+            .add_attribute("ica_channel_id", &packet.0)
+            .add_attribute("ica_sequence", packet.1.to_string())
+            .add_attribute("pending_retry_amount", pending_retry_amount);
+        if let TransferStatus::Failed { reason } = &status {
+            event = event.add_attribute("reason", reason);
+        }
+
+        let mut response = Response::new();
+        // The ack only means that the transfer was sent by the ICA, so the follow-up waits for its funds
+        // to reach the arrival account
+        if let (TransferStatus::Succeeded, Some(post_transfer_callback)) =
+            (&status, &cfg.post_transfer_callback)
+        {
+            PENDING_POST_TRANSFER_CALLBACKS.save(deps.storage, packet.clone(), &transfer.amount)?;
+            match enqueue_arrived_transfers(&mut deps, post_transfer_callback)? {
+                Some(enqueue_msg) => {
+                    // Reverted on its own if it fails, so that the outcome of the transfer is still recorded
+                    response = response.add_submessage(SubMsg::reply_on_error(
+                        enqueue_msg,
+                        POST_TRANSFER_CALLBACK_REPLY_ID,
+                    ));
+                    event = event.add_attribute("post_transfer_callback", "enqueued");
+                }
+                None => {
+                    event = event.add_attribute("post_transfer_callback", "waiting_for_arrival");
+                }
+            }
+        }
+
+        transfer.status = status;
+        // Outcomes of older transfers don't override the one of the last transfer sent
+        if LAST_TRANSFER_PACKET.may_load(deps.storage)? == Some(packet) {
+            LAST_TRANSFER.save(deps.storage, &transfer)?;
+        }
+
+        Ok(response
+            .add_attribute("method", "ica_tx_callback")
+            .add_attribute("result", outcome)
+            .add_event(event))
+    }
+
+    /// Enqueues the post-transfer callback of the transfers waiting for their funds, once the
+    /// arrival account holds the funds of at least the oldest of them
+    pub fn handle_enqueue_arrived_transfers(mut deps: DepsMut) -> Result<Response, LibraryError> {
+        if valence_library_base::is_paused(deps.storage)? {
+            return Err(LibraryError::LibraryPaused {});
+        }
+        let cfg: Config = valence_library_base::load_config(deps.storage)?;
+        let post_transfer_callback = cfg.post_transfer_callback.ok_or_else(|| {
+            LibraryError::ExecutionError("No post_transfer_callback configured.".to_string())
+        })?;
+
+        let enqueue_msg = enqueue_arrived_transfers(&mut deps, &post_transfer_callback)?
+            .ok_or_else(|| {
+                LibraryError::ExecutionError(
+                    "No transfer waiting for its funds has arrived.".to_string(),
+                )
+            })?;
+
+        Ok(Response::new()
+            .add_message(enqueue_msg)
+            .add_attribute("method", "enqueue_arrived_transfers"))
+    }
+
+    /// Returns the message enqueuing the post-transfer callback if the funds of at least one transfer
+    /// waiting for them arrived, and stops waiting for those. The balance of the arrival account is
+    /// consumed by the oldest transfers first, so that funds are never counted for two transfers
+    fn enqueue_arrived_transfers(
+        deps: &mut DepsMut,
+        post_transfer_callback: &CheckedPostTransferCallback,
+    ) -> Result<Option<WasmMsg>, LibraryError> {
+        let balance = deps
+            .querier
+            .query_balance(
+                &post_transfer_callback.arrival_account,
+                &post_transfer_callback.arrival_denom,
+            )?
+            .amount;
+        // Funds counted for previous transfers are no longer held once their follow-ups consumed them
+        let counted_balance = COUNTED_ARRIVAL_BALANCE
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            .min(balance);
+        let mut remaining_balance = balance - counted_balance;
+
+        let pending = PENDING_POST_TRANSFER_CALLBACKS
+            .range(deps.storage, None, None, Order::Ascending)
+            .take(MAX_IN_FLIGHT_TRANSFERS)
+            .collect::<StdResult<Vec<((String, u64), Uint128)>>>()?;
+        let mut arrived = vec![];
+        for (packet, amount) in pending {
+            match remaining_balance.checked_sub(amount) {
+                Ok(balance) => {
+                    remaining_balance = balance;
+                    arrived.push(packet);
+                }
+                Err(_) => break,
+            }
+        }
+
+        if arrived.is_empty() {
+            COUNTED_ARRIVAL_BALANCE.save(deps.storage, &counted_balance)?;
+            return Ok(None);
+        }
+        for packet in arrived {
+            PENDING_POST_TRANSFER_CALLBACKS.remove(deps.storage, packet);
+        }
+        COUNTED_ARRIVAL_BALANCE.save(deps.storage, &(balance - remaining_balance))?;
+
+        Ok(Some(post_transfer_callback.enqueue_msg()?))
+    }
+}
+
+pub(crate) mod execute {
+    use cosmwasm_std::{to_json_string, DepsMut, Env, Event, MessageInfo, StdResult};
     use valence_library_utils::error::LibraryError;
 
-    use crate::msg::LibraryConfigUpdate;
+    use crate::msg::{Config, LibraryConfigUpdate};
+
+    /// Type of the event emitted on config updates, with the old and new values of the
+    /// remote chain info, memo and receiver that were changed.
+    /// Once emitted by a contract, the event type is prefixed with `wasm-`.
+    pub const CONFIG_UPDATE_EVENT_TYPE: &str = "valence.ica_ibc_transfer.config_update";
 
     pub fn update_config(
         deps: DepsMut,
@@ -227,10 +852,38 @@ mod execute {
     ) -> Result<(), LibraryError> {
         new_config.update_config(deps)
     }
+
+    pub fn config_update_event(previous_config: &Config, config: &Config) -> StdResult<Event> {
+        let mut event = Event::new(CONFIG_UPDATE_EVENT_TYPE);
+
+        if previous_config.remote_chain_info != config.remote_chain_info {
+            event = event
+                .add_attribute(
+                    "old_remote_chain_info",
+                    to_json_string(&previous_config.remote_chain_info)?,
+                )
+                .add_attribute(
+                    "new_remote_chain_info",
+                    to_json_string(&config.remote_chain_info)?,
+                );
+        }
+        if previous_config.memo != config.memo {
+            event = event
+                .add_attribute("old_memo", &previous_config.memo)
+                .add_attribute("new_memo", &config.memo);
+        }
+        if previous_config.receiver != config.receiver {
+            event = event
+                .add_attribute("old_receiver", &previous_config.receiver)
+                .add_attribute("new_receiver", &config.receiver);
+        }
+
+        Ok(event)
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Ownership {} => {
             to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
@@ -238,6 +891,21 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetProcessor {} => {
             to_json_binary(&valence_library_base::get_processor(deps.storage)?)
         }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        QueryMsg::IsApproved {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&valence_library_utils::is_library_approved(
+                &deps.querier,
+                &config.input_addr,
+                &env.contract.address,
+            )?)
+        }
         QueryMsg::GetLibraryConfig {} => {
             let config: Config = valence_library_base::load_config(deps.storage)?;
             to_json_binary(&config)
@@ -247,5 +915,30 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 valence_library_utils::raw_config::query_raw_library_config(deps.storage)?;
             to_json_binary(&raw_config)
         }
+        QueryMsg::ClosedChannels {} => {
+            let closed_channels = CLOSED_CHANNELS
+                .keys(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<String>>>()?;
+            to_json_binary(&closed_channels)
+        }
+        QueryMsg::Transfers {} => {
+            // Bounded by MAX_IN_FLIGHT_TRANSFERS
+            let in_flight = IN_FLIGHT_TRANSFERS
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(_, transfer)| transfer))
+                .collect::<StdResult<Vec<_>>>()?;
+            let pending_retry_amounts = PENDING_RETRY_AMOUNTS
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&TransfersResponse {
+                last_transfer: LAST_TRANSFER.may_load(deps.storage)?,
+                in_flight,
+                pending_retry_amounts,
+            })
+        }
+        QueryMsg::Stats {} => to_json_binary(&valence_library_utils::stats::query_transfer_stats(
+            deps.storage,
+        )?),
     }
 }
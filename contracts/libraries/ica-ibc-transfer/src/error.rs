@@ -0,0 +1,72 @@
+use thiserror::Error;
+use valence_library_utils::error::LibraryError;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RouteError {
+    #[error("Invalid ICA IBC transfer config: channel_id cannot be empty. No PFM route is configured for denom {0} either.")]
+    NoRoute(String),
+
+    #[error("Invalid ICA IBC transfer config: '{0}' is not a valid channel id.")]
+    InvalidChannel(String),
+
+    #[error("Invalid counterparty channel of channel {channel_id}: {reason}.")]
+    InvalidCounterpartyChannel { channel_id: String, reason: String },
+
+    #[error("Invalid PFM route: denom cannot be empty.")]
+    EmptyPfmDenom,
+
+    #[error(
+        "Invalid PFM route for denom {denom}: {field} '{channel_id}' is not a valid channel id."
+    )]
+    InvalidPfmChannel {
+        denom: String,
+        field: &'static str,
+        channel_id: String,
+    },
+
+    #[error("Invalid PFM route for denom {0}: hop_chain_receiver_address cannot be empty.")]
+    EmptyPfmReceiver(String),
+
+    #[error("Invalid PFM route for denom {denom}: {reason}.")]
+    InvalidPfmForwarding { denom: String, reason: String },
+
+    #[error("Invalid PFM route for denom {denom}: overlaps with the route of denom {other}.")]
+    OverlappingPfmRoutes { denom: String, other: String },
+
+    #[error("Invalid Eureka config: {0}")]
+    InvalidEurekaConfig(String),
+
+    #[error(
+        "Ambiguous route for denom {0}: it is routed both by the PFM map and by the Eureka config."
+    )]
+    ConflictingRoutes(String),
+}
+
+impl From<RouteError> for LibraryError {
+    fn from(error: RouteError) -> Self {
+        LibraryError::ConfigurationError(error.to_string())
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DenomTraceError {
+    #[error("Invalid expected denom trace: base_denom cannot be empty.")]
+    EmptyBaseDenom,
+
+    #[error("Invalid expected denom trace: path '{0}' is not a sequence of port/channel pairs.")]
+    InvalidPath(String),
+
+    #[error("Denom {denom} does not resolve to base denom {base_denom} through path '{path}', which is {expected_denom}.")]
+    Mismatch {
+        denom: String,
+        base_denom: String,
+        path: String,
+        expected_denom: String,
+    },
+}
+
+impl From<DenomTraceError> for LibraryError {
+    fn from(error: DenomTraceError) -> Self {
+        LibraryError::ConfigurationError(error.to_string())
+    }
+}
@@ -1,5 +1,7 @@
 pub mod contract;
+pub mod error;
 pub mod msg;
+pub mod state;
 
 #[cfg(test)]
 mod tests;
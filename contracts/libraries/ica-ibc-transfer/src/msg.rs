@@ -1,17 +1,52 @@
 use std::collections::BTreeMap;
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Deps, DepsMut, Uint128};
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Binary, Coin, Deps, DepsMut, Empty, Timestamp, Uint128,
+    Uint64, WasmMsg,
+};
 use cw_ownable::cw_ownable_query;
-use valence_ibc_utils::types::{EurekaConfig, EurekaFee, PacketForwardMiddlewareConfig};
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+use valence_account_utils::ica::IcaCallbackMsg;
+use valence_authorization_utils::msg::{PermissionlessMsg, ProcessorMessage};
+use valence_ibc_utils::{
+    address::RemoteAddress,
+    types::{EurekaConfig, EurekaFee, PacketForwardMiddlewareConfig},
+};
 use valence_library_utils::LibraryAccountType;
-use valence_library_utils::{error::LibraryError, msg::LibraryConfigValidation};
+use valence_library_utils::{
+    error::LibraryError, msg::LibraryConfigValidation, pfm::find_overlapping_pfm_route,
+    stats::TransferStats,
+};
 use valence_macros::{valence_library_query, ValenceLibraryInterface};
 
+use crate::error::{DenomTraceError, RouteError};
+
 #[cw_serde]
 pub enum FunctionMsgs {
     Transfer {},
     EurekaTransfer { eureka_fee: EurekaFee },
+    // Flag a channel of the configured routes as open or closed, so that transfers fail over to the next open route
+    UpdateChannelStatus { channel_id: String, open: bool },
+}
+
+/// Messages accepted by the library: the regular library messages, the callbacks
+/// of the input account reporting the outcome of the transfers, and the permissionless
+/// enqueuing of the post-transfer callbacks of the transfers that arrived
+#[cw_serde]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum ExecuteMsg {
+    Library(valence_library_utils::msg::ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>),
+    IcaCallback(IcaCallbackMsg),
+    PostTransferCallback(PostTransferCallbackMsg),
+}
+
+#[cw_serde]
+pub enum PostTransferCallbackMsg {
+    // Enqueue the post-transfer callback of the successful transfers whose funds reached the arrival account (permissionless)
+    EnqueueArrivedTransfers {},
 }
 
 #[valence_library_query]
@@ -19,7 +54,43 @@ pub enum FunctionMsgs {
 #[cw_serde]
 #[derive(QueryResponses)]
 /// Enum representing the different query messages that can be sent.
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    #[returns(Vec<String>)]
+    ClosedChannels {}, // Get the list of channels currently flagged as closed
+    #[returns(TransfersResponse)]
+    Transfers {}, // Get the last transfer, the transfers waiting for their outcome and the amount to transfer again
+    #[returns(TransferStats)]
+    Stats {}, // Get the cumulative amounts transferred by the library
+}
+
+#[cw_serde]
+pub enum TransferStatus {
+    // Sent, waiting for the acknowledgement of the ICA tx
+    Pending,
+    Succeeded,
+    // The ICA tx failed on the remote chain
+    Failed { reason: String },
+    // The ICA packet timed out, closing the ICA channel
+    TimedOut,
+}
+
+#[cw_serde]
+pub struct TransferRecord {
+    pub amount: Uint128,
+    pub denom: String,
+    // Source channel selected for the transfer
+    pub channel_id: String,
+    pub status: TransferStatus,
+}
+
+#[cw_serde]
+pub struct TransfersResponse {
+    pub last_transfer: Option<TransferRecord>,
+    // Transfers sent but not acknowledged yet, in order of the ICA packets that carried them
+    pub in_flight: Vec<TransferRecord>,
+    // Amount of the failed transfers of each denom that still has to be transferred again, if failed transfers are re-credited
+    pub pending_retry_amounts: Vec<Coin>,
+}
 
 #[cw_serde]
 #[derive(ValenceLibraryInterface)]
@@ -34,27 +105,295 @@ pub struct LibraryConfig {
     pub receiver: String,
     // Memo to be passed in the IBC transfer message.
     pub memo: String,
+    // Memo rendered at execution time, replacing the {amount}, {receiver} and {denom} placeholders.
+    // Cannot be combined with a plain memo. For PFM transfers, it's the memo of the forwarded packet and must be a JSON object.
+    pub memo_template: Option<String>,
     // Remote chain info
     pub remote_chain_info: RemoteChainInfo,
     // Denom map for the Packet-Forwarding Middleware, to perform a multi-hop transfer.
     pub denom_to_pfm_map: BTreeMap<String, PacketForwardMiddlewareConfig>,
     // Optional Eureka config for Eureka IBC transfers
     pub eureka_config: Option<EurekaConfig>,
+    // If set, transfers are skipped while the remote balance of the input account is below this amount
+    pub min_transfer_amount: Option<Uint128>,
+    // If set, the amount of transfers that fail or time out is re-credited to the pending retry amount
+    #[serde(default)]
+    pub recredit_failed_transfers: bool,
+    // If set, the denom must resolve to this base denom through this path, so that a look-alike ibc/ denom is never transferred
+    pub expected_base_denom: Option<DenomTrace>,
+    // Maximum size in bytes of the memo of the packet sent, DEFAULT_MAX_MEMO_BYTES if not specified
+    pub max_memo_bytes: Option<u32>,
+    // If set, the function of this library is enqueued on the processor once the funds of a successful transfer arrived
+    #[serde(default)]
+    pub post_transfer_callback: Option<PostTransferCallback>,
+}
+
+/// Function of a library enqueued once the funds of a successful transfer arrived, e.g. providing liquidity with them.
+/// It is sent to the processor through a permissionless authorization, like any other message of the program
+#[cw_serde]
+pub struct PostTransferCallback {
+    // Authorization contract of the program
+    pub authorization_contract: String,
+    // Label of the permissionless authorization of the function
+    pub authorization_label: String,
+    // Library executing the function
+    pub library: LibraryAccountType,
+    // JSON object of the function message of the library, e.g. {"provide_double_sided_liquidity":{}}
+    pub function: Binary,
+    // Account on this chain receiving the funds of the transfers, e.g. the input account of the library
+    pub arrival_account: LibraryAccountType,
+    // Denom of the funds once they reached the arrival account
+    pub arrival_denom: String,
+}
+
+impl PostTransferCallback {
+    pub fn new(
+        authorization_contract: String,
+        authorization_label: String,
+        library: impl Into<LibraryAccountType>,
+        function: Binary,
+        arrival_account: impl Into<LibraryAccountType>,
+        arrival_denom: String,
+    ) -> Self {
+        PostTransferCallback {
+            authorization_contract,
+            authorization_label,
+            library: library.into(),
+            function,
+            arrival_account: arrival_account.into(),
+            arrival_denom,
+        }
+    }
+
+    fn to_checked(
+        &self,
+        api: &dyn cosmwasm_std::Api,
+    ) -> Result<CheckedPostTransferCallback, LibraryError> {
+        let authorization_contract = api.addr_validate(&self.authorization_contract)?;
+        if self.authorization_label.is_empty() {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid ICA IBC transfer config: authorization_label of the post_transfer_callback cannot be empty."
+                    .to_string(),
+            ));
+        }
+        let library = self.library.to_addr(api)?;
+        if !serde_json::from_slice::<serde_json::Value>(&self.function)
+            .is_ok_and(|value| value.is_object())
+        {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid ICA IBC transfer config: function of the post_transfer_callback is not a JSON object."
+                    .to_string(),
+            ));
+        }
+        let arrival_account = self.arrival_account.to_addr(api)?;
+        if self.arrival_denom.is_empty() {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid ICA IBC transfer config: arrival_denom of the post_transfer_callback cannot be empty."
+                    .to_string(),
+            ));
+        }
+
+        Ok(CheckedPostTransferCallback {
+            authorization_contract,
+            authorization_label: self.authorization_label.clone(),
+            library,
+            function: self.function.clone(),
+            arrival_account,
+            arrival_denom: self.arrival_denom.clone(),
+        })
+    }
+}
+
+#[cw_serde]
+pub struct CheckedPostTransferCallback {
+    pub authorization_contract: Addr,
+    pub authorization_label: String,
+    pub library: Addr,
+    pub function: Binary,
+    pub arrival_account: Addr,
+    pub arrival_denom: String,
+}
+
+impl CheckedPostTransferCallback {
+    /// Message of the authorization contract sending the function of the library to the processor
+    pub fn enqueue_msg(&self) -> StdResult<WasmMsg> {
+        let process_function =
+            valence_library_utils::msg::ExecuteMsg::<serde_json::Value, Empty>::ProcessFunction(
+                from_json(&self.function)?,
+            );
+        let send_msgs = valence_authorization_utils::msg::ExecuteMsg::PermissionlessAction(
+            PermissionlessMsg::SendMsgs {
+                label: self.authorization_label.clone(),
+                messages: vec![ProcessorMessage::CosmwasmExecuteMsg {
+                    msg: to_json_binary(&process_function)?,
+                }],
+                ttl: None,
+            },
+        );
+
+        Ok(WasmMsg::Execute {
+            contract_addr: self.authorization_contract.to_string(),
+            msg: to_json_binary(&send_msgs)?,
+            funds: vec![],
+        })
+    }
+}
+
+/// Trace of a denom received over IBC, as tracked by the IBC transfer module
+#[cw_serde]
+pub struct DenomTrace {
+    // Ports and channels the denom went through to reach the chain of the input account, e.g. transfer/channel-0.
+    // Empty for a denom native to that chain
+    pub path: String,
+    // Denom on its origin chain
+    pub base_denom: String,
+}
+
+impl DenomTrace {
+    /// Resolves the trace to the denom it's held as: `ibc/{hash}`, with the uppercase hex SHA-256 hash
+    /// of `{path}/{base_denom}`, or the base denom itself for a native denom
+    pub fn denom(&self) -> String {
+        if self.path.is_empty() {
+            return self.base_denom.clone();
+        }
+
+        let hash = Sha256::digest(format!("{}/{}", self.path, self.base_denom));
+        let hex_hash: String = hash.iter().map(|byte| format!("{byte:02X}")).collect();
+        format!("ibc/{hex_hash}")
+    }
+
+    fn validate(&self) -> Result<(), DenomTraceError> {
+        if self.base_denom.is_empty() {
+            return Err(DenomTraceError::EmptyBaseDenom);
+        }
+
+        if !self.path.is_empty() {
+            let segments: Vec<&str> = self.path.split('/').collect();
+            // Every hop is a port followed by a channel, so an odd number of segments is invalid
+            let is_valid_path = segments.chunks(2).all(|hop| {
+                matches!(hop, [port, channel] if !port.is_empty() && is_valid_channel_id(channel))
+            });
+            if !is_valid_path {
+                return Err(DenomTraceError::InvalidPath(self.path.clone()));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[cw_serde]
 pub struct RemoteChainInfo {
     // Channel ID to be used
     pub channel_id: String,
-    // Timeout for the IBC transfer in seconds. If not specified, a default 600 seconds will be used will be used
-    pub ibc_transfer_timeout: Option<u64>,
+    // Timeout policy for the IBC transfer. If not specified, a relative timeout of DEFAULT_IBC_TIMEOUT_SECONDS will be used
+    #[serde(default, deserialize_with = "deserialize_timeout_policy")]
+    pub ibc_transfer_timeout: Option<TimeoutPolicy>,
+    // Ordered list of fallback channel IDs, used when the primary channel is not open
+    #[serde(default)]
+    pub fallback_channel_ids: Vec<String>,
+    // Channels of this chain at the other end of the channels of the routes (e.g. the Neutron end of a Noble to Neutron channel),
+    // keyed by the channel of the route. Their state is queried when selecting the route, skipping the routes whose channel is not open
+    #[serde(default)]
+    pub counterparty_channel_ids: BTreeMap<String, String>,
 }
 
 impl RemoteChainInfo {
-    pub fn new(channel_id: String, ibc_transfer_timeout: Option<u64>) -> Self {
+    pub fn new(channel_id: String, ibc_transfer_timeout: Option<TimeoutPolicy>) -> Self {
         Self {
             channel_id,
             ibc_transfer_timeout,
+            fallback_channel_ids: vec![],
+            counterparty_channel_ids: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_fallback_channels(mut self, fallback_channel_ids: Vec<String>) -> Self {
+        self.fallback_channel_ids = fallback_channel_ids;
+        self
+    }
+
+    pub fn with_counterparty_channels(
+        mut self,
+        counterparty_channel_ids: BTreeMap<String, String>,
+    ) -> Self {
+        self.counterparty_channel_ids = counterparty_channel_ids;
+        self
+    }
+
+    /// Returns the candidate channels in order of preference: the primary channel first, then the fallbacks
+    pub fn channel_routes(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.channel_id).chain(self.fallback_channel_ids.iter())
+    }
+}
+
+// Relative timeout used when no timeout policy is configured (10 minutes)
+pub const DEFAULT_IBC_TIMEOUT_SECONDS: u64 = 600;
+// Maximum relative timeout that can be configured (1 week)
+pub const MAX_RELATIVE_IBC_TIMEOUT_SECONDS: u64 = 604_800;
+// Maximum number of transfers waiting for their outcome, new transfers are rejected until the oldest outcomes are reported
+pub const MAX_IN_FLIGHT_TRANSFERS: usize = 50;
+// Maximum memo size used when none is configured, which is the limit of the IBC transfer module (32 KiB)
+pub const DEFAULT_MAX_MEMO_BYTES: u32 = 32_768;
+
+#[cw_serde]
+pub enum TimeoutPolicy {
+    // Timeout in seconds, relative to the block time at execution
+    Relative(u64),
+    // Timeout at a block height of the remote chain
+    BlockHeight {
+        revision_number: u64,
+        revision_height: u64,
+    },
+    // Timeout at a fixed timestamp
+    Absolute(Timestamp),
+}
+
+// Timeouts were a number of seconds before timeout policies were introduced,
+// and are still stored that way in the config of the libraries deployed before
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredTimeoutPolicy {
+    Policy(TimeoutPolicy),
+    Seconds(Uint64),
+}
+
+/// Reads a timeout policy, or a number of seconds as a relative timeout
+fn deserialize_timeout_policy<'de, D>(deserializer: D) -> Result<Option<TimeoutPolicy>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(
+        Option::<StoredTimeoutPolicy>::deserialize(deserializer)?.map(|timeout| match timeout {
+            StoredTimeoutPolicy::Policy(policy) => policy,
+            StoredTimeoutPolicy::Seconds(seconds) => TimeoutPolicy::Relative(seconds.u64()),
+        }),
+    )
+}
+
+impl TimeoutPolicy {
+    pub fn validate(&self) -> Result<(), LibraryError> {
+        match self {
+            TimeoutPolicy::Relative(0) => Err(LibraryError::ConfigurationError(
+                "Invalid ICA IBC transfer config: timeout cannot be zero.".to_string(),
+            )),
+            TimeoutPolicy::Relative(seconds) if *seconds > MAX_RELATIVE_IBC_TIMEOUT_SECONDS => {
+                Err(LibraryError::ConfigurationError(format!(
+                    "Invalid ICA IBC transfer config: timeout cannot exceed {MAX_RELATIVE_IBC_TIMEOUT_SECONDS} seconds."
+                )))
+            }
+            TimeoutPolicy::BlockHeight {
+                revision_height: 0, ..
+            } => Err(LibraryError::ConfigurationError(
+                "Invalid ICA IBC transfer config: timeout height cannot be zero.".to_string(),
+            )),
+            TimeoutPolicy::Absolute(timestamp) if timestamp.nanos() == 0 => {
+                Err(LibraryError::ConfigurationError(
+                    "Invalid ICA IBC transfer config: timeout timestamp cannot be zero."
+                        .to_string(),
+                ))
+            }
+            _ => Ok(()),
         }
     }
 }
@@ -77,13 +416,55 @@ impl LibraryConfig {
             denom,
             receiver,
             memo,
+            memo_template: None,
             remote_chain_info,
             denom_to_pfm_map,
             eureka_config,
+            min_transfer_amount: None,
+            recredit_failed_transfers: false,
+            expected_base_denom: None,
+            max_memo_bytes: None,
+            post_transfer_callback: None,
         }
     }
 
-    fn do_validate(&self, api: &dyn cosmwasm_std::Api) -> Result<Addr, LibraryError> {
+    pub fn with_min_transfer_amount(mut self, min_transfer_amount: Uint128) -> Self {
+        self.min_transfer_amount = Some(min_transfer_amount);
+        self
+    }
+
+    pub fn with_recredit_failed_transfers(mut self) -> Self {
+        self.recredit_failed_transfers = true;
+        self
+    }
+
+    pub fn with_expected_base_denom(mut self, expected_base_denom: DenomTrace) -> Self {
+        self.expected_base_denom = Some(expected_base_denom);
+        self
+    }
+
+    pub fn with_memo_template(mut self, memo_template: String) -> Self {
+        self.memo_template = Some(memo_template);
+        self
+    }
+
+    pub fn with_max_memo_bytes(mut self, max_memo_bytes: u32) -> Self {
+        self.max_memo_bytes = Some(max_memo_bytes);
+        self
+    }
+
+    pub fn with_post_transfer_callback(
+        mut self,
+        post_transfer_callback: PostTransferCallback,
+    ) -> Self {
+        self.post_transfer_callback = Some(post_transfer_callback);
+        self
+    }
+
+    fn do_validate(
+        &self,
+        api: &dyn cosmwasm_std::Api,
+    ) -> Result<(Addr, Option<CheckedPostTransferCallback>), LibraryError> {
         let input_addr = self.input_addr.to_addr(api)?;
         if self.amount.is_zero() {
             return Err(LibraryError::ConfigurationError(
@@ -97,27 +478,50 @@ impl LibraryConfig {
             ));
         }
 
-        if self.remote_chain_info.channel_id.is_empty() {
+        if self
+            .remote_chain_info
+            .fallback_channel_ids
+            .iter()
+            .any(|id| id.is_empty())
+        {
             return Err(LibraryError::ConfigurationError(
-                "Invalid ICA IBC transfer config: channel_id cannot be empty.".to_string(),
+                "Invalid ICA IBC transfer config: fallback channel_id cannot be empty.".to_string(),
             ));
         }
 
-        if self.receiver.is_empty() {
-            return Err(LibraryError::ConfigurationError(
-                "Invalid ICA IBC transfer config: receiver cannot be empty.".to_string(),
-            ));
-        }
+        validate_receiver(&self.receiver, self.eureka_config.as_ref())?;
 
-        if let Some(timeout) = self.remote_chain_info.ibc_transfer_timeout {
-            if timeout == 0 {
-                return Err(LibraryError::ConfigurationError(
-                    "Invalid ICA IBC transfer config: timeout cannot be zero.".to_string(),
-                ));
-            }
+        validate_denom_trace(&self.denom, self.expected_base_denom.as_ref())?;
+
+        if let Some(timeout) = &self.remote_chain_info.ibc_transfer_timeout {
+            timeout.validate()?;
         }
 
-        Ok(input_addr)
+        validate_routes(
+            &self.denom,
+            &self.remote_chain_info,
+            &self.denom_to_pfm_map,
+            self.eureka_config.as_ref(),
+        )?;
+
+        validate_min_transfer_amount(self.min_transfer_amount, self.amount)?;
+
+        validate_memo_template(
+            &self.memo,
+            self.memo_template.as_deref(),
+            &self.denom,
+            &self.denom_to_pfm_map,
+        )?;
+
+        validate_max_memo_bytes(self.max_memo_bytes)?;
+
+        let post_transfer_callback = self
+            .post_transfer_callback
+            .as_ref()
+            .map(|post_transfer_callback| post_transfer_callback.to_checked(api))
+            .transpose()?;
+
+        Ok((input_addr, post_transfer_callback))
     }
 }
 
@@ -129,7 +533,7 @@ impl LibraryConfigValidation<Config> for LibraryConfig {
     }
 
     fn validate(&self, deps: Deps) -> Result<Config, LibraryError> {
-        let input_addr = self.do_validate(deps.api)?;
+        let (input_addr, post_transfer_callback) = self.do_validate(deps.api)?;
 
         Ok(Config {
             input_addr,
@@ -137,9 +541,15 @@ impl LibraryConfigValidation<Config> for LibraryConfig {
             denom: self.denom.clone(),
             receiver: self.receiver.clone(),
             memo: self.memo.clone(),
+            memo_template: self.memo_template.clone(),
             remote_chain_info: self.remote_chain_info.clone(),
             denom_to_pfm_map: self.denom_to_pfm_map.clone(),
             eureka_config: self.eureka_config.clone(),
+            min_transfer_amount: self.min_transfer_amount,
+            recredit_failed_transfers: self.recredit_failed_transfers,
+            expected_base_denom: self.expected_base_denom.clone(),
+            max_memo_bytes: self.max_memo_bytes,
+            post_transfer_callback,
         })
     }
 }
@@ -175,28 +585,33 @@ impl LibraryConfigUpdate {
 
         // Next update the receiver (if needed)
         if let Some(receiver) = self.receiver {
-            if receiver.is_empty() {
-                return Err(LibraryError::ConfigurationError(
-                    "Invalid ICA IBC transfer config: receiver cannot be empty.".to_string(),
-                ));
-            }
             config.receiver = receiver;
         }
 
+        // Next update the memo (if needed)
+        if let Some(memo) = self.memo {
+            config.memo = memo;
+        }
+
+        if let OptionUpdate::Set(memo_template) = self.memo_template {
+            config.memo_template = memo_template;
+        }
+
         // Next update the remote_chain_info (if needed)
         if let Some(remote_chain_info) = self.remote_chain_info {
-            if remote_chain_info.channel_id.is_empty() {
+            if remote_chain_info
+                .fallback_channel_ids
+                .iter()
+                .any(|id| id.is_empty())
+            {
                 return Err(LibraryError::ConfigurationError(
-                    "Invalid ICA IBC transfer config: channel_id cannot be empty.".to_string(),
+                    "Invalid ICA IBC transfer config: fallback channel_id cannot be empty."
+                        .to_string(),
                 ));
             }
 
-            if let Some(timeout) = remote_chain_info.ibc_transfer_timeout {
-                if timeout == 0 {
-                    return Err(LibraryError::ConfigurationError(
-                        "Invalid ICA IBC transfer config: timeout cannot be zero.".to_string(),
-                    ));
-                }
+            if let Some(timeout) = &remote_chain_info.ibc_transfer_timeout {
+                timeout.validate()?;
             }
 
             config.remote_chain_info = remote_chain_info;
@@ -210,6 +625,54 @@ impl LibraryConfigUpdate {
             config.eureka_config = eureka_config;
         }
 
+        if let OptionUpdate::Set(min_transfer_amount) = self.min_transfer_amount {
+            config.min_transfer_amount = min_transfer_amount;
+        }
+
+        if let Some(recredit_failed_transfers) = self.recredit_failed_transfers {
+            config.recredit_failed_transfers = recredit_failed_transfers;
+        }
+
+        if let OptionUpdate::Set(expected_base_denom) = self.expected_base_denom {
+            config.expected_base_denom = expected_base_denom;
+        }
+
+        if let OptionUpdate::Set(max_memo_bytes) = self.max_memo_bytes {
+            validate_max_memo_bytes(max_memo_bytes)?;
+            config.max_memo_bytes = max_memo_bytes;
+        }
+
+        if let OptionUpdate::Set(post_transfer_callback) = self.post_transfer_callback {
+            config.post_transfer_callback = post_transfer_callback
+                .map(|post_transfer_callback| post_transfer_callback.to_checked(deps.api))
+                .transpose()?;
+        }
+
+        // The receiver is validated against the (possibly updated) Eureka config
+        validate_receiver(&config.receiver, config.eureka_config.as_ref())?;
+
+        // The (possibly updated) denom must resolve to the (possibly updated) expected trace
+        validate_denom_trace(&config.denom, config.expected_base_denom.as_ref())?;
+
+        // Routes are validated against the (possibly updated) denom and channels
+        validate_routes(
+            &config.denom,
+            &config.remote_chain_info,
+            &config.denom_to_pfm_map,
+            config.eureka_config.as_ref(),
+        )?;
+
+        // The minimum is validated against the (possibly updated) amount
+        validate_min_transfer_amount(config.min_transfer_amount, config.amount)?;
+
+        // The template is validated against the (possibly updated) memo and routes
+        validate_memo_template(
+            &config.memo,
+            config.memo_template.as_deref(),
+            &config.denom,
+            &config.denom_to_pfm_map,
+        )?;
+
         valence_library_base::save_config(deps.storage, &config)?;
         Ok(())
     }
@@ -222,9 +685,18 @@ pub struct Config {
     pub denom: String,
     pub receiver: String,
     pub memo: String,
+    pub memo_template: Option<String>,
     pub remote_chain_info: RemoteChainInfo,
     pub denom_to_pfm_map: BTreeMap<String, PacketForwardMiddlewareConfig>,
     pub eureka_config: Option<EurekaConfig>,
+    pub min_transfer_amount: Option<Uint128>,
+    #[serde(default)]
+    pub recredit_failed_transfers: bool,
+    pub expected_base_denom: Option<DenomTrace>,
+    #[serde(default)]
+    pub max_memo_bytes: Option<u32>,
+    #[serde(default)]
+    pub post_transfer_callback: Option<CheckedPostTransferCallback>,
 }
 
 impl Config {
@@ -245,9 +717,316 @@ impl Config {
             denom,
             receiver,
             memo,
+            memo_template: None,
             remote_chain_info,
             denom_to_pfm_map,
             eureka_config,
+            min_transfer_amount: None,
+            recredit_failed_transfers: false,
+            expected_base_denom: None,
+            max_memo_bytes: None,
+            post_transfer_callback: None,
+        }
+    }
+
+    pub fn with_min_transfer_amount(mut self, min_transfer_amount: Uint128) -> Self {
+        self.min_transfer_amount = Some(min_transfer_amount);
+        self
+    }
+
+    pub fn with_recredit_failed_transfers(mut self) -> Self {
+        self.recredit_failed_transfers = true;
+        self
+    }
+
+    pub fn with_expected_base_denom(mut self, expected_base_denom: DenomTrace) -> Self {
+        self.expected_base_denom = Some(expected_base_denom);
+        self
+    }
+
+    pub fn with_memo_template(mut self, memo_template: String) -> Self {
+        self.memo_template = Some(memo_template);
+        self
+    }
+
+    pub fn with_max_memo_bytes(mut self, max_memo_bytes: u32) -> Self {
+        self.max_memo_bytes = Some(max_memo_bytes);
+        self
+    }
+
+    pub fn with_post_transfer_callback(
+        mut self,
+        post_transfer_callback: CheckedPostTransferCallback,
+    ) -> Self {
+        self.post_transfer_callback = Some(post_transfer_callback);
+        self
+    }
+
+    /// Returns the memo of the transfer, rendering the memo template if there is one
+    pub fn transfer_memo(&self) -> String {
+        match &self.memo_template {
+            Some(memo_template) => {
+                render_memo_template(memo_template, self.amount, &self.receiver, &self.denom)
+            }
+            None => self.memo.clone(),
+        }
+    }
+
+    /// Returns the maximum size in bytes of the memo of the packets sent
+    pub fn max_memo_bytes(&self) -> u32 {
+        self.max_memo_bytes.unwrap_or(DEFAULT_MAX_MEMO_BYTES)
+    }
+}
+
+/// Replaces the {amount}, {receiver} and {denom} placeholders of a memo template
+pub fn render_memo_template(
+    memo_template: &str,
+    amount: Uint128,
+    receiver: &str,
+    denom: &str,
+) -> String {
+    memo_template
+        .replace("{amount}", &amount.to_string())
+        .replace("{receiver}", receiver)
+        .replace("{denom}", denom)
+}
+
+/// Checks that a memo template isn't combined with a plain memo, and that it renders to a JSON object
+/// when the denom is routed through PFM, since the rendered memo is then nested in the forward metadata
+fn validate_memo_template(
+    memo: &str,
+    memo_template: Option<&str>,
+    denom: &str,
+    denom_to_pfm_map: &BTreeMap<String, PacketForwardMiddlewareConfig>,
+) -> Result<(), LibraryError> {
+    let Some(memo_template) = memo_template else {
+        return Ok(());
+    };
+
+    if memo_template.is_empty() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid ICA IBC transfer config: memo_template cannot be empty.".to_string(),
+        ));
+    }
+
+    if !memo.is_empty() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid ICA IBC transfer config: memo and memo_template cannot both be set."
+                .to_string(),
+        ));
+    }
+
+    if denom_to_pfm_map.contains_key(denom) {
+        let rendered = render_memo_template(memo_template, Uint128::one(), "receiver", denom);
+        if !is_json_object(&rendered) {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid ICA IBC transfer config: memo_template must render to a JSON object for PFM transfers."
+                    .to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the maximum memo size, if set, is not zero
+fn validate_max_memo_bytes(max_memo_bytes: Option<u32>) -> Result<(), LibraryError> {
+    if max_memo_bytes == Some(0) {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid ICA IBC transfer config: max_memo_bytes cannot be zero.".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn is_json_object(memo: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(memo).is_ok_and(|value| value.is_object())
+}
+
+/// Checks that the receiver is a well-formed bech32 address on the remote chain.
+/// Eureka transfers are not checked, as their receiver is on the Eureka destination (e.g. an EVM address).
+fn validate_receiver(
+    receiver: &str,
+    eureka_config: Option<&EurekaConfig>,
+) -> Result<(), LibraryError> {
+    if receiver.is_empty() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid ICA IBC transfer config: receiver cannot be empty.".to_string(),
+        ));
+    }
+
+    if eureka_config.is_none() {
+        if is_evm_address(receiver) {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Invalid ICA IBC transfer config: receiver {receiver} is an EVM address, but IBC transfers need a bech32 receiver. Use the Eureka config or the CCTP transfer library to send funds to EVM chains."
+            )));
         }
+
+        receiver.parse::<RemoteAddress>().map_err(|_| {
+            LibraryError::ConfigurationError(format!(
+                "Invalid ICA IBC transfer config: receiver {receiver} is not a valid bech32 address."
+            ))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Whether the address is a 20 bytes hex address, with the `0x` prefix
+fn is_evm_address(address: &str) -> bool {
+    address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .is_some_and(|hex_addr| {
+            hex_addr.len() == 40 && hex_addr.chars().all(|c| c.is_ascii_hexdigit())
+        })
+}
+
+/// Checks that the denom is the one the expected trace resolves to
+fn validate_denom_trace(
+    denom: &str,
+    expected_base_denom: Option<&DenomTrace>,
+) -> Result<(), DenomTraceError> {
+    let Some(trace) = expected_base_denom else {
+        return Ok(());
+    };
+    trace.validate()?;
+
+    let expected_denom = trace.denom();
+    // The hash of IBC denoms is not case sensitive
+    if !denom.eq_ignore_ascii_case(&expected_denom) {
+        return Err(DenomTraceError::Mismatch {
+            denom: denom.to_string(),
+            base_denom: trace.base_denom.clone(),
+            path: trace.path.clone(),
+            expected_denom,
+        });
     }
+
+    Ok(())
+}
+
+fn validate_min_transfer_amount(
+    min_transfer_amount: Option<Uint128>,
+    amount: Uint128,
+) -> Result<(), LibraryError> {
+    if min_transfer_amount.is_some_and(|min| min > amount) {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid ICA IBC transfer config: min_transfer_amount cannot be larger than amount."
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validates every PFM entry and the Eureka config, and checks that the transferred denom can be routed,
+/// either through the direct channel or through its PFM entry. Eureka transfers always use the direct channel.
+fn validate_routes(
+    denom: &str,
+    remote_chain_info: &RemoteChainInfo,
+    denom_to_pfm_map: &BTreeMap<String, PacketForwardMiddlewareConfig>,
+    eureka_config: Option<&EurekaConfig>,
+) -> Result<(), RouteError> {
+    for (pfm_denom, pfm_config) in denom_to_pfm_map {
+        if pfm_denom.is_empty() {
+            return Err(RouteError::EmptyPfmDenom);
+        }
+
+        for (field, channel_id) in [
+            (
+                "local_to_hop_chain_channel_id",
+                &pfm_config.local_to_hop_chain_channel_id,
+            ),
+            (
+                "hop_to_destination_chain_channel_id",
+                &pfm_config.hop_to_destination_chain_channel_id,
+            ),
+        ] {
+            if !is_valid_channel_id(channel_id) {
+                return Err(RouteError::InvalidPfmChannel {
+                    denom: pfm_denom.clone(),
+                    field,
+                    channel_id: channel_id.clone(),
+                });
+            }
+        }
+
+        // If not set, a placeholder receiver is used on the hop chain
+        if pfm_config
+            .hop_chain_receiver_address
+            .as_ref()
+            .is_some_and(|receiver| receiver.is_empty())
+        {
+            return Err(RouteError::EmptyPfmReceiver(pfm_denom.clone()));
+        }
+
+        pfm_config
+            .validate()
+            .map_err(|e| RouteError::InvalidPfmForwarding {
+                denom: pfm_denom.clone(),
+                reason: e.to_string(),
+            })?;
+    }
+
+    if let Some((denom, other)) = find_overlapping_pfm_route(denom_to_pfm_map) {
+        return Err(RouteError::OverlappingPfmRoutes {
+            denom: denom.to_string(),
+            other: other.to_string(),
+        });
+    }
+
+    if let Some(eureka_config) = eureka_config {
+        eureka_config
+            .validate()
+            .map_err(|e| RouteError::InvalidEurekaConfig(e.to_string()))?;
+
+        // The denom can't be routed both through PFM and Eureka
+        if denom_to_pfm_map.contains_key(denom) {
+            return Err(RouteError::ConflictingRoutes(denom.to_string()));
+        }
+    }
+
+    if remote_chain_info.channel_id.is_empty()
+        && (eureka_config.is_some() || !denom_to_pfm_map.contains_key(denom))
+    {
+        return Err(RouteError::NoRoute(denom.to_string()));
+    }
+
+    // The channels of the direct route must be well-formed, an empty primary channel being left to PFM
+    if let Some(channel_id) = remote_chain_info
+        .channel_routes()
+        .find(|id| !id.is_empty() && !is_valid_channel_id(id))
+    {
+        return Err(RouteError::InvalidChannel(channel_id.clone()));
+    }
+
+    // The state of a channel can only be queried through the counterparty of one of the channels of the routes
+    for (channel_id, counterparty_channel_id) in &remote_chain_info.counterparty_channel_ids {
+        if !remote_chain_info
+            .channel_routes()
+            .any(|id| id == channel_id)
+        {
+            return Err(RouteError::InvalidCounterpartyChannel {
+                channel_id: channel_id.clone(),
+                reason: "it is not part of the configured routes".to_string(),
+            });
+        }
+        if !is_valid_channel_id(counterparty_channel_id) {
+            return Err(RouteError::InvalidCounterpartyChannel {
+                channel_id: channel_id.clone(),
+                reason: format!("'{counterparty_channel_id}' is not a valid channel id"),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// IBC channel identifiers have the `channel-{sequence}` format
+fn is_valid_channel_id(channel_id: &str) -> bool {
+    channel_id.strip_prefix("channel-").is_some_and(|sequence| {
+        !sequence.is_empty() && sequence.chars().all(|c| c.is_ascii_digit())
+    })
 }
@@ -0,0 +1,23 @@
+use cosmwasm_std::{Empty, Uint128};
+use cw_storage_plus::{Item, Map};
+
+use crate::msg::TransferRecord;
+
+// Channels that were flagged as closed and must be skipped when selecting the transfer route
+pub const CLOSED_CHANNELS: Map<String, Empty> = Map::new("closed_channels");
+// Last transfer sent, updated with its outcome once the input account calls back
+pub const LAST_TRANSFER: Item<TransferRecord> = Item::new("last_transfer");
+// ICA packet (channel and sequence) that carried the last transfer sent
+pub const LAST_TRANSFER_PACKET: Item<(String, u64)> = Item::new("last_transfer_packet");
+// Transfers waiting for their outcome, keyed by the ICA packet (channel and sequence) that carried them
+pub const IN_FLIGHT_TRANSFERS: Map<(String, u64), TransferRecord> =
+    Map::new("in_flight_transfers_by_packet");
+// Amount of the successful transfers, keyed by their ICA packet, whose funds must reach the arrival account
+// before their post-transfer callback is enqueued
+pub const PENDING_POST_TRANSFER_CALLBACKS: Map<(String, u64), Uint128> =
+    Map::new("pending_post_transfer_callbacks");
+// Balance of the arrival account already counted for the transfers whose post-transfer callback was enqueued,
+// so that it isn't counted again for the next transfers
+pub const COUNTED_ARRIVAL_BALANCE: Item<Uint128> = Item::new("counted_arrival_balance");
+// Amount of the failed transfers of each denom that still has to be transferred again
+pub const PENDING_RETRY_AMOUNTS: Map<String, Uint128> = Map::new("pending_retry_amounts");
@@ -1,19 +1,152 @@
 use std::collections::BTreeMap;
 
-use cosmwasm_std::{coin, Addr, Empty, Uint128};
+use cosmos_sdk_proto::prost::Message;
+use cosmwasm_std::{
+    coin, from_json,
+    testing::{mock_env, MockApi, MockStorage},
+    to_json_binary, to_json_string, Addr, Binary, ContractResult, Deps, DepsMut, Empty, Env, Event,
+    MessageInfo, Querier, QuerierResult, QuerierWrapper, QueryRequest, Response, StdResult,
+    SystemResult, Timestamp, Uint128,
+};
 use cw_multi_test::{error::AnyResult, App, AppResponse, ContractWrapper, Executor};
 use cw_ownable::Ownership;
+use cw_storage_plus::{Item, Map};
+use ibc_proto::ibc::{
+    apps::transfer::v1::MsgTransfer,
+    core::{channel::v1::QueryChannelRequest, client::v1::Height},
+};
+use valence_account_utils::ica::{
+    IcaCallbackMsg, IcaInformation, IcaState, IcaTxResult, IcaTxSubmission,
+};
+use valence_ibc_utils::types::{EurekaConfig, PacketForwardMiddlewareConfig, PacketMetadata};
 use valence_library_utils::{
+    error::LibraryError,
+    events::{TransferEvent, TRANSFER_EVENT_TYPE},
     msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation},
+    stats::TransferStats,
     testing::{LibraryTestSuite, LibraryTestSuiteBase},
 };
 
-use crate::msg::{
-    Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg, RemoteChainInfo,
+use crate::{
+    contract::{
+        callbacks::TRANSFER_RESULT_EVENT_TYPE,
+        execute::CONFIG_UPDATE_EVENT_TYPE,
+        functions::{create_msg_transfer, is_channel_open, packet_timeout, select_channel},
+    },
+    error::{DenomTraceError, RouteError},
+    msg::{
+        render_memo_template, Config, DenomTrace, FunctionMsgs, LibraryConfig, LibraryConfigUpdate,
+        PostTransferCallback, PostTransferCallbackMsg, QueryMsg, RemoteChainInfo, TimeoutPolicy,
+        TransferRecord, TransferStatus, TransfersResponse, DEFAULT_IBC_TIMEOUT_SECONDS,
+        MAX_IN_FLIGHT_TRANSFERS, MAX_RELATIVE_IBC_TIMEOUT_SECONDS,
+    },
+    state::CLOSED_CHANNELS,
 };
 
 const UUSDC: &str = "uusdc";
 const ONE_THOUSAND: u128 = 1_000_000_000;
+const RECEIVER: &str = "cosmos1sxawsa4hq5funhkvvz8w64yew75p47su9d45pq9wcftr88ne9c8skqjq4k";
+// USDC from Noble on Osmosis, and ATOM from the Hub on Osmosis
+const IBC_USDC: &str = "ibc/498A0751C798A0D9A389AA3691123DADA57DAA4FE165D5C75894505B876BA6E4";
+const IBC_ATOM: &str = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+const NEW_RECEIVER: &str = "cosmos1mj2vzjs5sfqkyl2wjnppp5njntulpl9cfd9r3h6wx6qpn7g62q5sykqf5t";
+const EVM_RECEIVER: &str = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045";
+
+// Remote balances recorded by the mock interchain account, and sequence of the last ICA packet it sent
+const MOCK_REMOTE_BALANCES: Map<String, Uint128> = Map::new("mock_remote_balances");
+const MOCK_ICA_SEQUENCE: Item<u64> = Item::new("mock_ica_sequence");
+
+// Minimal Valence interchain account mock that reports a created ICA, records remote balances and accepts any ICA message,
+// returning the ICA packet that carries it
+fn mock_ica_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn mock_ica_execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: valence_account_utils::ica::ExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        valence_account_utils::ica::ExecuteMsg::UpdateRemoteBalances { balances } => {
+            for balance in balances {
+                MOCK_REMOTE_BALANCES.save(deps.storage, balance.denom, &balance.amount)?;
+            }
+            Ok(Response::new())
+        }
+        valence_account_utils::ica::ExecuteMsg::ExecuteIcaMsgWithCallback { .. } => {
+            let sequence = MOCK_ICA_SEQUENCE
+                .may_load(deps.storage)?
+                .unwrap_or_default()
+                + 1;
+            MOCK_ICA_SEQUENCE.save(deps.storage, &sequence)?;
+            Ok(Response::new().set_data(to_json_binary(&IcaTxSubmission {
+                channel_id: "channel-0".to_string(),
+                sequence,
+            })?))
+        }
+        _ => Ok(Response::new()),
+    }
+}
+
+fn mock_ica_query(
+    deps: Deps,
+    _env: Env,
+    msg: valence_account_utils::ica::QueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        valence_account_utils::ica::QueryMsg::RemoteBalance { denom } => {
+            let amount = MOCK_REMOTE_BALANCES.load(deps.storage, denom.clone())?;
+            to_json_binary(&coin(amount.u128(), denom))
+        }
+        _ => to_json_binary(&IcaState::Created(IcaInformation {
+            address: "remote_ica".to_string(),
+            port_id: "icacontroller".to_string(),
+            controller_connection_id: "connection-0".to_string(),
+        })),
+    }
+}
+
+// Messages received by the mock authorization contract
+const MOCK_AUTHORIZATION_MSGS: Item<Vec<serde_json::Value>> = Item::new("mock_authorization_msgs");
+
+// Minimal authorization contract mock that accepts and records any message
+fn mock_authorization_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn mock_authorization_execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: serde_json::Value,
+) -> StdResult<Response> {
+    let mut msgs = MOCK_AUTHORIZATION_MSGS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    msgs.push(msg);
+    MOCK_AUTHORIZATION_MSGS.save(deps.storage, &msgs)?;
+    Ok(Response::new())
+}
+
+fn mock_authorization_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_json_binary(
+        &MOCK_AUTHORIZATION_MSGS
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+    )
+}
 
 struct IcaIbcTransferTestSuite {
     inner: LibraryTestSuiteBase,
@@ -40,7 +173,8 @@ impl IcaIbcTransferTestSuite {
             crate::contract::execute,
             crate::contract::instantiate,
             crate::contract::query,
-        );
+        )
+        .with_reply(crate::contract::reply);
 
         let ica_ibc_transfer_code_id = inner.app_mut().store_code(Box::new(ica_ibc_transfer_code));
 
@@ -97,6 +231,52 @@ impl IcaIbcTransferTestSuite {
         )
     }
 
+    fn mock_ica_init(&mut self) -> Addr {
+        let mock_ica_code =
+            ContractWrapper::new(mock_ica_execute, mock_ica_instantiate, mock_ica_query);
+        let mock_ica_code_id = self.app_mut().store_code(Box::new(mock_ica_code));
+
+        self.contract_init(mock_ica_code_id, "mock_ica", &Empty {}, &[])
+    }
+
+    fn mock_authorization_init(&mut self) -> Addr {
+        let mock_authorization_code = ContractWrapper::new(
+            mock_authorization_execute,
+            mock_authorization_instantiate,
+            mock_authorization_query,
+        );
+        let mock_authorization_code_id =
+            self.app_mut().store_code(Box::new(mock_authorization_code));
+
+        self.contract_init(
+            mock_authorization_code_id,
+            "mock_authorization",
+            &Empty {},
+            &[],
+        )
+    }
+
+    fn set_remote_balance(&mut self, ica_addr: Addr, amount: u128, denom: &str) {
+        let owner = self.owner().clone();
+        self.app_mut()
+            .execute_contract(
+                owner,
+                ica_addr,
+                &valence_account_utils::ica::ExecuteMsg::UpdateRemoteBalances {
+                    balances: vec![coin(amount, denom)],
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    fn execute_function(&mut self, addr: Addr, msg: FunctionMsgs) -> AnyResult<AppResponse> {
+        self.contract_execute(
+            addr,
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::ProcessFunction(msg),
+        )
+    }
+
     fn update_config(&mut self, addr: Addr, new_config: LibraryConfig) -> AnyResult<AppResponse> {
         let owner = self.owner().clone();
         let updated_config = LibraryConfigUpdate {
@@ -105,9 +285,21 @@ impl IcaIbcTransferTestSuite {
             denom: Some(new_config.denom),
             receiver: Some(new_config.receiver),
             memo: Some(new_config.memo),
+            memo_template: valence_library_utils::OptionUpdate::Set(new_config.memo_template),
             remote_chain_info: Some(new_config.remote_chain_info),
             denom_to_pfm_map: Some(new_config.denom_to_pfm_map),
             eureka_config: valence_library_utils::OptionUpdate::Set(new_config.eureka_config),
+            min_transfer_amount: valence_library_utils::OptionUpdate::Set(
+                new_config.min_transfer_amount,
+            ),
+            recredit_failed_transfers: Some(new_config.recredit_failed_transfers),
+            expected_base_denom: valence_library_utils::OptionUpdate::Set(
+                new_config.expected_base_denom,
+            ),
+            max_memo_bytes: valence_library_utils::OptionUpdate::Set(new_config.max_memo_bytes),
+            post_transfer_callback: valence_library_utils::OptionUpdate::Set(
+                new_config.post_transfer_callback,
+            ),
         };
         self.app_mut().execute_contract(
             owner,
@@ -153,8 +345,8 @@ fn instantiate_with_valid_config() {
     let cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     // Instantiate IBC transfer contract
@@ -176,9 +368,9 @@ fn instantiate_with_valid_config() {
             suite.input_addr.clone(),
             Uint128::new(ONE_THOUSAND),
             UUSDC.to_string(),
-            "receiver".to_string(),
+            RECEIVER.to_string(),
             "".to_string(),
-            RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+            RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
             BTreeMap::default(),
             None,
         )
@@ -192,8 +384,8 @@ fn pre_validate_config_works() {
     let cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     // Pre-validate config
@@ -208,8 +400,8 @@ fn instantiate_fails_for_zero_amount() {
     let cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::zero(),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     suite.ica_ibc_transfer_init(&cfg);
@@ -223,8 +415,8 @@ fn instantiate_fails_for_empty_denom() {
     let cfg = suite.ica_ibc_transfer_config(
         "".to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     suite.ica_ibc_transfer_init(&cfg);
@@ -238,8 +430,8 @@ fn instantiate_fails_for_empty_channel_id() {
     let cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     suite.ica_ibc_transfer_init(&cfg);
@@ -254,7 +446,7 @@ fn instantiate_fails_for_empty_receiver() {
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
         "".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     suite.ica_ibc_transfer_init(&cfg);
@@ -268,8 +460,8 @@ fn instantiate_fails_for_zero_timeout() {
     let cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(0)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(0))),
     );
 
     suite.ica_ibc_transfer_init(&cfg);
@@ -283,8 +475,8 @@ fn update_config_validates_amount() {
     let cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     let lib = suite.ica_ibc_transfer_init(&cfg);
@@ -292,8 +484,8 @@ fn update_config_validates_amount() {
     let new_cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::zero(),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     suite.update_config(lib, new_cfg).unwrap();
@@ -307,8 +499,8 @@ fn update_config_validates_denom() {
     let cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     let lib = suite.ica_ibc_transfer_init(&cfg);
@@ -316,8 +508,8 @@ fn update_config_validates_denom() {
     let new_cfg = suite.ica_ibc_transfer_config(
         "".to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     suite.update_config(lib, new_cfg).unwrap();
@@ -331,8 +523,8 @@ fn update_config_validates_receiver() {
     let cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     let lib = suite.ica_ibc_transfer_init(&cfg);
@@ -341,12 +533,71 @@ fn update_config_validates_receiver() {
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
         "".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     suite.update_config(lib, new_cfg).unwrap();
 }
 
+#[test]
+fn pre_validate_rejects_malformed_receiver() {
+    let suite = IcaIbcTransferTestSuite::default();
+
+    // Corrupt the checksum of the receiver
+    let corrupted = format!("{}q", &RECEIVER[..RECEIVER.len() - 1]);
+    let mut cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        corrupted.clone(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+    assert_eq!(
+        cfg.pre_validate(suite.api()).unwrap_err().to_string(),
+        format!("Configuration error: Invalid ICA IBC transfer config: receiver {corrupted} is not a valid bech32 address.")
+    );
+
+    // Receivers on any chain are accepted
+    for hrp in ["noble", "neutron", "mychain"] {
+        cfg.receiver = MockApi::default()
+            .with_prefix(hrp)
+            .addr_make("receiver")
+            .to_string();
+        cfg.pre_validate(suite.api()).unwrap();
+    }
+
+    // The receiver of Eureka transfers is on the Eureka destination
+    cfg.receiver = EVM_RECEIVER.to_string();
+    cfg.pre_validate(suite.api()).unwrap_err();
+    cfg.eureka_config = Some(eureka_config());
+    cfg.pre_validate(suite.api()).unwrap();
+}
+
+#[test]
+fn pre_validate_rejects_evm_receiver() {
+    let suite = IcaIbcTransferTestSuite::default();
+
+    let mut cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        EVM_RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+    assert_eq!(
+        cfg.pre_validate(suite.api()).unwrap_err().to_string(),
+        format!("Configuration error: Invalid ICA IBC transfer config: receiver {EVM_RECEIVER} is an EVM address, but IBC transfers need a bech32 receiver. Use the Eureka config or the CCTP transfer library to send funds to EVM chains.")
+    );
+
+    // Without the prefix, it is not a bech32 address either
+    cfg.receiver = EVM_RECEIVER[2..].to_string();
+    assert_eq!(
+        cfg.pre_validate(suite.api()).unwrap_err().to_string(),
+        format!(
+            "Configuration error: Invalid ICA IBC transfer config: receiver {} is not a valid bech32 address.",
+            &EVM_RECEIVER[2..]
+        )
+    );
+}
+
 #[test]
 #[should_panic(expected = "Invalid ICA IBC transfer config: channel_id cannot be empty.")]
 fn update_config_validates_channel_id() {
@@ -355,8 +606,8 @@ fn update_config_validates_channel_id() {
     let cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     let lib = suite.ica_ibc_transfer_init(&cfg);
@@ -364,13 +615,161 @@ fn update_config_validates_channel_id() {
     let new_cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+
+    suite.update_config(lib, new_cfg).unwrap();
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid ICA IBC transfer config: 'transfer/channel-2' is not a valid channel id."
+)]
+fn update_config_rejects_malformed_channel_id() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+
+    let cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+
+    let lib = suite.ica_ibc_transfer_init(&cfg);
+
+    let new_cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new(
+            "transfer/channel-2".to_string(),
+            Some(TimeoutPolicy::Relative(600)),
+        ),
     );
 
     suite.update_config(lib, new_cfg).unwrap();
 }
 
+// Update of the route of the transfer only, as done on a channel migration
+fn channel_migration_update() -> LibraryConfigUpdate {
+    LibraryConfigUpdate {
+        input_addr: None,
+        amount: None,
+        denom: None,
+        receiver: Some(NEW_RECEIVER.to_string()),
+        memo: Some("new memo".to_string()),
+        memo_template: valence_library_utils::OptionUpdate::None,
+        remote_chain_info: Some(RemoteChainInfo::new(
+            "channel-2".to_string(),
+            Some(TimeoutPolicy::Relative(600)),
+        )),
+        denom_to_pfm_map: None,
+        eureka_config: valence_library_utils::OptionUpdate::None,
+        min_transfer_amount: valence_library_utils::OptionUpdate::None,
+        recredit_failed_transfers: None,
+        expected_base_denom: valence_library_utils::OptionUpdate::None,
+        max_memo_bytes: valence_library_utils::OptionUpdate::None,
+        post_transfer_callback: valence_library_utils::OptionUpdate::None,
+    }
+}
+
+#[test]
+fn update_config_emits_config_update_event() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+
+    let cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+
+    let lib = suite.ica_ibc_transfer_init(&cfg);
+
+    let owner = suite.owner().clone();
+    let res = suite
+        .app_mut()
+        .execute_contract(
+            owner,
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::UpdateConfig {
+                new_config: channel_migration_update(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // The rest of the config is left untouched
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(lib_cfg.remote_chain_info.channel_id, "channel-2");
+    assert_eq!(lib_cfg.memo, "new memo");
+    assert_eq!(lib_cfg.receiver, NEW_RECEIVER);
+    assert_eq!(lib_cfg.amount, Uint128::new(ONE_THOUSAND));
+    assert_eq!(lib_cfg.denom, UUSDC);
+
+    let event = res
+        .events
+        .iter()
+        .find(|event| event.ty == format!("wasm-{CONFIG_UPDATE_EVENT_TYPE}"))
+        .unwrap();
+    let attribute = |key: &str| {
+        event
+            .attributes
+            .iter()
+            .find(|attr| attr.key == key)
+            .map(|attr| attr.value.clone())
+    };
+    assert_eq!(
+        attribute("old_remote_chain_info"),
+        Some(to_json_string(&cfg.remote_chain_info).unwrap())
+    );
+    assert_eq!(
+        attribute("new_remote_chain_info"),
+        Some(to_json_string(&lib_cfg.remote_chain_info).unwrap())
+    );
+    assert_eq!(attribute("old_memo"), Some("".to_string()));
+    assert_eq!(attribute("new_memo"), Some("new memo".to_string()));
+    assert_eq!(attribute("old_receiver"), Some(RECEIVER.to_string()));
+    assert_eq!(attribute("new_receiver"), Some(NEW_RECEIVER.to_string()));
+}
+
+#[test]
+fn update_config_fails_for_non_owner() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+
+    let cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+
+    let lib = suite.ica_ibc_transfer_init(&cfg);
+
+    let processor = suite.processor().clone();
+    let err = suite
+        .app_mut()
+        .execute_contract(
+            processor,
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::UpdateConfig {
+                new_config: channel_migration_update(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        LibraryError::OwnershipError(cw_ownable::OwnershipError::NotOwner).to_string()
+    );
+
+    // The config is left untouched
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(lib_cfg.remote_chain_info, cfg.remote_chain_info);
+    assert_eq!(lib_cfg.receiver, RECEIVER);
+}
+
 #[test]
 #[should_panic(expected = "Invalid ICA IBC transfer config: timeout cannot be zero.")]
 fn update_config_validates_timeout() {
@@ -379,8 +778,8 @@ fn update_config_validates_timeout() {
     let cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     let lib = suite.ica_ibc_transfer_init(&cfg);
@@ -388,8 +787,8 @@ fn update_config_validates_timeout() {
     let new_cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(0)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(0))),
     );
 
     suite.update_config(lib, new_cfg).unwrap();
@@ -402,8 +801,8 @@ fn update_config_works() {
     let cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-1".to_string(), Some(600)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
     );
 
     let lib = suite.ica_ibc_transfer_init(&cfg);
@@ -411,8 +810,8 @@ fn update_config_works() {
     let new_cfg = suite.ica_ibc_transfer_config(
         UUSDC.to_string(),
         Uint128::new(ONE_THOUSAND * 2),
-        "receiver".to_string(),
-        RemoteChainInfo::new("channel-2".to_string(), Some(1200)),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-2".to_string(), Some(TimeoutPolicy::Relative(1200))),
     );
 
     suite.update_config(lib.clone(), new_cfg.clone()).unwrap();
@@ -433,3 +832,1840 @@ fn update_config_works() {
         )
     );
 }
+
+#[test]
+#[should_panic(expected = "Invalid ICA IBC transfer config: fallback channel_id cannot be empty.")]
+fn instantiate_fails_for_empty_fallback_channel_id() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+
+    let cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600)))
+            .with_fallback_channels(vec!["".to_string()]),
+    );
+
+    suite.ica_ibc_transfer_init(&cfg);
+}
+
+#[test]
+fn transfer_fails_over_to_fallback_channel() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+
+    let cfg = LibraryConfig::new(
+        valence_library_utils::LibraryAccountType::Addr(ica_addr.to_string()),
+        Uint128::new(ONE_THOUSAND),
+        UUSDC.to_string(),
+        RECEIVER.to_string(),
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600)))
+            .with_fallback_channels(vec!["channel-2".to_string(), "channel-3".to_string()]),
+        BTreeMap::default(),
+        None,
+    );
+    let lib = suite.ica_ibc_transfer_init(&cfg);
+
+    let selected_channel = |res: AppResponse| {
+        res.events
+            .iter()
+            .flat_map(|e| e.attributes.iter())
+            .find(|a| a.key == "selected_channel_id")
+            .map(|a| a.value.clone())
+            .unwrap()
+    };
+
+    // The primary channel is used while it's open
+    let res = suite
+        .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+        .unwrap();
+    assert_eq!(selected_channel(res), "channel-1");
+
+    // Flag the primary channel as closed, the first fallback should be picked
+    suite
+        .execute_function(
+            lib.clone(),
+            FunctionMsgs::UpdateChannelStatus {
+                channel_id: "channel-1".to_string(),
+                open: false,
+            },
+        )
+        .unwrap();
+
+    let closed_channels: Vec<String> = suite.query_wasm(&lib, &QueryMsg::ClosedChannels {});
+    assert_eq!(closed_channels, vec!["channel-1".to_string()]);
+
+    let res = suite
+        .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+        .unwrap();
+    assert_eq!(selected_channel(res), "channel-2");
+
+    // Re-opening the primary channel restores it as the selected route
+    suite
+        .execute_function(
+            lib.clone(),
+            FunctionMsgs::UpdateChannelStatus {
+                channel_id: "channel-1".to_string(),
+                open: true,
+            },
+        )
+        .unwrap();
+
+    let res = suite
+        .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+        .unwrap();
+    assert_eq!(selected_channel(res), "channel-1");
+}
+
+#[test]
+fn transfer_fails_when_all_channels_are_closed() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+
+    let cfg = LibraryConfig::new(
+        valence_library_utils::LibraryAccountType::Addr(ica_addr.to_string()),
+        Uint128::new(ONE_THOUSAND),
+        UUSDC.to_string(),
+        RECEIVER.to_string(),
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600)))
+            .with_fallback_channels(vec!["channel-2".to_string()]),
+        BTreeMap::default(),
+        None,
+    );
+    let lib = suite.ica_ibc_transfer_init(&cfg);
+
+    for channel_id in ["channel-1", "channel-2"] {
+        suite
+            .execute_function(
+                lib.clone(),
+                FunctionMsgs::UpdateChannelStatus {
+                    channel_id: channel_id.to_string(),
+                    open: false,
+                },
+            )
+            .unwrap();
+    }
+
+    let err = suite
+        .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Execution error: No open channel available in the configured routes."
+    );
+
+    // Channels outside of the configured routes can't be flagged
+    let err = suite
+        .execute_function(
+            lib,
+            FunctionMsgs::UpdateChannelStatus {
+                channel_id: "channel-9".to_string(),
+                open: false,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Execution error: Channel channel-9 is not part of the configured routes."
+    );
+}
+
+// Querier answering the channel queries of the IBC module with the state of the known transfer channels
+struct MockChannelQuerier {
+    states: BTreeMap<String, &'static str>,
+}
+
+impl Querier for MockChannelQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        #[allow(deprecated)]
+        let QueryRequest::Stargate { path, data } =
+            from_json::<QueryRequest<Empty>>(bin_request).unwrap()
+        else {
+            panic!("Unexpected query");
+        };
+        assert_eq!(path, "/ibc.core.channel.v1.Query/Channel");
+
+        let request = QueryChannelRequest::decode(data.as_slice()).unwrap();
+        assert_eq!(request.port_id, "transfer");
+        SystemResult::Ok(match self.states.get(&request.channel_id) {
+            Some(state) => ContractResult::Ok(
+                to_json_binary(&serde_json::json!({
+                    "channel": { "state": state, "ordering": "ORDER_UNORDERED" },
+                    "proof": null,
+                }))
+                .unwrap(),
+            ),
+            None => ContractResult::Err(format!("channel {} not found", request.channel_id)),
+        })
+    }
+}
+
+#[test]
+fn transfer_route_is_selected_from_the_channel_state() {
+    let remote_chain_info =
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600)))
+            .with_fallback_channels(vec!["channel-2".to_string(), "channel-3".to_string()])
+            .with_counterparty_channels(BTreeMap::from([
+                ("channel-1".to_string(), "channel-10".to_string()),
+                ("channel-2".to_string(), "channel-20".to_string()),
+            ]));
+    let mut storage = MockStorage::new();
+    let api = MockApi::default();
+    let select = |storage: &MockStorage, states: &[(&str, &'static str)]| {
+        let querier = MockChannelQuerier {
+            states: states
+                .iter()
+                .map(|(channel_id, state)| (channel_id.to_string(), *state))
+                .collect(),
+        };
+        select_channel(
+            Deps {
+                storage,
+                api: &api,
+                querier: QuerierWrapper::new(&querier),
+            },
+            &remote_chain_info,
+        )
+    };
+
+    // The primary channel is used while its counterparty channel is open
+    let open = [("channel-10", "STATE_OPEN"), ("channel-20", "STATE_OPEN")];
+    assert_eq!(select(&storage, &open).unwrap(), "channel-1");
+
+    // Failing over to the first fallback whose counterparty channel is open
+    let primary_closed = [("channel-10", "STATE_CLOSED"), ("channel-20", "STATE_OPEN")];
+    assert_eq!(select(&storage, &primary_closed).unwrap(), "channel-2");
+
+    // And to the routes without a counterparty channel, whose state is not known
+    let both_closed = [
+        ("channel-10", "STATE_CLOSED"),
+        ("channel-20", "STATE_TRYOPEN"),
+    ];
+    assert_eq!(select(&storage, &both_closed).unwrap(), "channel-3");
+
+    // Channels flagged as closed are skipped whatever their state
+    CLOSED_CHANNELS
+        .save(&mut storage, "channel-2".to_string(), &Empty {})
+        .unwrap();
+    assert_eq!(select(&storage, &primary_closed).unwrap(), "channel-3");
+    CLOSED_CHANNELS
+        .save(&mut storage, "channel-3".to_string(), &Empty {})
+        .unwrap();
+    assert_eq!(
+        select(&storage, &primary_closed).unwrap_err().to_string(),
+        "Execution error: No open channel available in the configured routes."
+    );
+
+    // A counterparty channel that doesn't exist can't be queried
+    let querier = MockChannelQuerier {
+        states: BTreeMap::new(),
+    };
+    assert_eq!(
+        is_channel_open(&QuerierWrapper::new(&querier), "channel-10")
+            .unwrap_err()
+            .to_string(),
+        "Execution error: Failed to query the state of channel channel-10: Generic error: Querier contract error: channel channel-10 not found"
+    );
+}
+
+#[test]
+fn pre_validate_rejects_invalid_counterparty_channels() {
+    let suite = IcaIbcTransferTestSuite::default();
+    let remote_chain_info = |counterparty_channel_ids: Vec<(&str, &str)>| {
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600)))
+            .with_fallback_channels(vec!["channel-2".to_string()])
+            .with_counterparty_channels(
+                counterparty_channel_ids
+                    .into_iter()
+                    .map(|(channel_id, counterparty)| {
+                        (channel_id.to_string(), counterparty.to_string())
+                    })
+                    .collect(),
+            )
+    };
+    let cfg = |counterparty_channel_ids| {
+        suite.ica_ibc_transfer_config(
+            UUSDC.to_string(),
+            Uint128::new(ONE_THOUSAND),
+            RECEIVER.to_string(),
+            remote_chain_info(counterparty_channel_ids),
+        )
+    };
+
+    cfg(vec![("channel-2", "channel-20")])
+        .pre_validate(suite.api())
+        .unwrap();
+    assert_eq!(
+        cfg(vec![("channel-9", "channel-90")])
+            .pre_validate(suite.api())
+            .unwrap_err()
+            .to_string(),
+        format!(
+            "Configuration error: {}",
+            RouteError::InvalidCounterpartyChannel {
+                channel_id: "channel-9".to_string(),
+                reason: "it is not part of the configured routes".to_string(),
+            }
+        )
+    );
+    assert_eq!(
+        cfg(vec![("channel-1", "connection-0")])
+            .pre_validate(suite.api())
+            .unwrap_err()
+            .to_string(),
+        format!(
+            "Configuration error: {}",
+            RouteError::InvalidCounterpartyChannel {
+                channel_id: "channel-1".to_string(),
+                reason: "'connection-0' is not a valid channel id".to_string(),
+            }
+        )
+    );
+}
+
+fn pfm_config(hop_chain_receiver_address: Option<String>) -> PacketForwardMiddlewareConfig {
+    PacketForwardMiddlewareConfig::new("channel-5", "channel-7", hop_chain_receiver_address)
+}
+
+#[test]
+fn multi_hop_transfer_uses_pfm_route() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+
+    // No direct channel is needed when the denom has a PFM route
+    let cfg = LibraryConfig::new(
+        valence_library_utils::LibraryAccountType::Addr(ica_addr.to_string()),
+        Uint128::new(ONE_THOUSAND),
+        UUSDC.to_string(),
+        RECEIVER.to_string(),
+        "".to_string(),
+        RemoteChainInfo::new("".to_string(), Some(TimeoutPolicy::Relative(600))),
+        BTreeMap::from([(
+            UUSDC.to_string(),
+            pfm_config(Some("hop_receiver".to_string())),
+        )]),
+        None,
+    );
+    cfg.pre_validate(suite.api()).unwrap();
+    let lib = suite.ica_ibc_transfer_init(&cfg);
+
+    let res = suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap();
+    let selected_channel = res
+        .events
+        .iter()
+        .flat_map(|e| e.attributes.iter())
+        .find(|a| a.key == "selected_channel_id")
+        .map(|a| a.value.clone())
+        .unwrap();
+    assert_eq!(selected_channel, "channel-5");
+}
+
+#[test]
+fn pre_validate_rejects_pfm_route_without_hop_receiver() {
+    let suite = IcaIbcTransferTestSuite::default();
+
+    let mut cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+    cfg.denom_to_pfm_map = BTreeMap::from([
+        (
+            UUSDC.to_string(),
+            pfm_config(Some("hop_receiver".to_string())),
+        ),
+        ("untrn".to_string(), pfm_config(Some("".to_string()))),
+    ]);
+
+    let err = cfg.pre_validate(suite.api()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Configuration error: {}",
+            RouteError::EmptyPfmReceiver("untrn".to_string())
+        )
+    );
+
+    // Malformed channels are reported with the offending denom
+    let mut invalid_channel = pfm_config(None);
+    invalid_channel.hop_to_destination_chain_channel_id = "channel-".to_string();
+    cfg.denom_to_pfm_map = BTreeMap::from([("untrn".to_string(), invalid_channel)]);
+
+    let err = cfg.pre_validate(suite.api()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Configuration error: {}",
+            RouteError::InvalidPfmChannel {
+                denom: "untrn".to_string(),
+                field: "hop_to_destination_chain_channel_id",
+                channel_id: "channel-".to_string(),
+            }
+        )
+    );
+}
+
+#[test]
+fn pre_validate_rejects_overlapping_pfm_routes() {
+    let suite = IcaIbcTransferTestSuite::default();
+
+    let mut cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+    // The hash of IBC denoms is not case sensitive, so both keys route the same denom
+    cfg.denom_to_pfm_map = BTreeMap::from([
+        ("ibc/ABC123".to_string(), pfm_config(None)),
+        ("ibc/abc123".to_string(), pfm_config(None)),
+    ]);
+
+    let err = cfg.pre_validate(suite.api()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Configuration error: {}",
+            RouteError::OverlappingPfmRoutes {
+                denom: "ibc/abc123".to_string(),
+                other: "ibc/ABC123".to_string(),
+            }
+        )
+    );
+}
+
+#[test]
+#[should_panic(expected = "No PFM route is configured for denom uusdc either.")]
+fn instantiate_fails_without_direct_or_pfm_route() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+
+    // The PFM route is configured for a different denom than the one transferred
+    let mut cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+    cfg.denom_to_pfm_map = BTreeMap::from([("untrn".to_string(), pfm_config(None))]);
+
+    suite.ica_ibc_transfer_init(&cfg);
+}
+
+fn eureka_config() -> EurekaConfig {
+    EurekaConfig::builder()
+        .callback("cosmos1callback")
+        .action("cosmos1action")
+        .recover_address("cosmos1recover")
+        .source_channel("08-wasm-1369")
+        .timeout(43200)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn pre_validate_rejects_denom_routed_by_pfm_and_eureka() {
+    let suite = IcaIbcTransferTestSuite::default();
+
+    let mut cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+    cfg.eureka_config = Some(eureka_config());
+    cfg.pre_validate(suite.api()).unwrap();
+
+    // PFM entries for other denoms don't conflict with the Eureka route
+    cfg.denom_to_pfm_map = BTreeMap::from([("untrn".to_string(), pfm_config(None))]);
+    cfg.pre_validate(suite.api()).unwrap();
+
+    cfg.denom_to_pfm_map = BTreeMap::from([(UUSDC.to_string(), pfm_config(None))]);
+    let err = cfg.pre_validate(suite.api()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Configuration error: {}",
+            RouteError::ConflictingRoutes(UUSDC.to_string())
+        )
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid Eureka config: Generic error: recover_address cannot be empty")]
+fn instantiate_fails_for_incomplete_eureka_config() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+
+    let mut cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+    cfg.eureka_config = Some(EurekaConfig {
+        recover_address: "".to_string(),
+        ..eureka_config()
+    });
+
+    suite.ica_ibc_transfer_init(&cfg);
+}
+
+fn transfer_msg_with_timeout(
+    env: &Env,
+    timeout: Option<&TimeoutPolicy>,
+) -> Result<MsgTransfer, LibraryError> {
+    create_msg_transfer(
+        env,
+        "channel-1".to_string(),
+        RECEIVER.to_string(),
+        UUSDC.to_string(),
+        ONE_THOUSAND,
+        "".to_string(),
+        timeout,
+        &BTreeMap::default(),
+        "remote_ica".to_string(),
+    )
+}
+
+#[test]
+fn relative_timeout_policy_sets_packet_timestamp() {
+    let env = mock_env();
+
+    // The default relative timeout is used when no policy is configured
+    let msg = transfer_msg_with_timeout(&env, None).unwrap();
+    assert_eq!(msg.timeout_height, None);
+    assert_eq!(
+        msg.timeout_timestamp,
+        env.block
+            .time
+            .plus_seconds(DEFAULT_IBC_TIMEOUT_SECONDS)
+            .nanos()
+    );
+
+    let msg = transfer_msg_with_timeout(&env, Some(&TimeoutPolicy::Relative(3600))).unwrap();
+    assert_eq!(msg.timeout_height, None);
+    assert_eq!(
+        msg.timeout_timestamp,
+        env.block.time.plus_seconds(3600).nanos()
+    );
+}
+
+#[test]
+fn block_height_and_absolute_timeout_policies_set_packet_timeout() {
+    let env = mock_env();
+
+    let msg = transfer_msg_with_timeout(
+        &env,
+        Some(&TimeoutPolicy::BlockHeight {
+            revision_number: 1,
+            revision_height: 12_345,
+        }),
+    )
+    .unwrap();
+    assert_eq!(
+        msg.timeout_height,
+        Some(Height {
+            revision_number: 1,
+            revision_height: 12_345,
+        })
+    );
+    assert_eq!(msg.timeout_timestamp, 0);
+
+    let timeout = env.block.time.plus_hours(2);
+    let msg = transfer_msg_with_timeout(&env, Some(&TimeoutPolicy::Absolute(timeout))).unwrap();
+    assert_eq!(msg.timeout_height, None);
+    assert_eq!(msg.timeout_timestamp, timeout.nanos());
+
+    // Absolute timeouts that already passed are rejected at execution
+    let err = packet_timeout(&env, Some(&TimeoutPolicy::Absolute(env.block.time))).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Execution error: IBC transfer timeout {} is not in the future.",
+            env.block.time
+        )
+    );
+}
+
+#[test]
+fn pre_validate_rejects_out_of_range_timeout_policies() {
+    let suite = IcaIbcTransferTestSuite::default();
+
+    let timeout_err = |timeout: TimeoutPolicy| {
+        suite
+            .ica_ibc_transfer_config(
+                UUSDC.to_string(),
+                Uint128::new(ONE_THOUSAND),
+                RECEIVER.to_string(),
+                RemoteChainInfo::new("channel-1".to_string(), Some(timeout)),
+            )
+            .pre_validate(suite.api())
+            .err()
+            .map(|err| err.to_string())
+    };
+
+    assert_eq!(
+        timeout_err(TimeoutPolicy::Relative(MAX_RELATIVE_IBC_TIMEOUT_SECONDS + 1)),
+        Some(format!(
+            "Configuration error: Invalid ICA IBC transfer config: timeout cannot exceed {MAX_RELATIVE_IBC_TIMEOUT_SECONDS} seconds."
+        ))
+    );
+    assert_eq!(
+        timeout_err(TimeoutPolicy::BlockHeight {
+            revision_number: 1,
+            revision_height: 0,
+        }),
+        Some(
+            "Configuration error: Invalid ICA IBC transfer config: timeout height cannot be zero."
+                .to_string()
+        )
+    );
+    assert_eq!(
+        timeout_err(TimeoutPolicy::Absolute(Timestamp::from_nanos(0))),
+        Some(
+            "Configuration error: Invalid ICA IBC transfer config: timeout timestamp cannot be zero."
+                .to_string()
+        )
+    );
+    assert_eq!(
+        timeout_err(TimeoutPolicy::Relative(MAX_RELATIVE_IBC_TIMEOUT_SECONDS)),
+        None
+    );
+}
+
+#[test]
+fn timeouts_stored_as_seconds_are_read_as_relative_timeout_policies() {
+    // Shape of the remote chain info stored by the libraries deployed before timeout policies
+    let remote_chain_info: RemoteChainInfo =
+        from_json(r#"{"channel_id":"channel-1","ibc_transfer_timeout":"600"}"#).unwrap();
+    assert_eq!(
+        remote_chain_info,
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600)))
+    );
+
+    let remote_chain_info: RemoteChainInfo =
+        from_json(r#"{"channel_id":"channel-1","ibc_transfer_timeout":null}"#).unwrap();
+    assert_eq!(remote_chain_info.ibc_transfer_timeout, None);
+
+    // Timeout policies are read as they are written
+    let remote_chain_info = RemoteChainInfo::new(
+        "channel-1".to_string(),
+        Some(TimeoutPolicy::BlockHeight {
+            revision_number: 1,
+            revision_height: 100,
+        }),
+    );
+    assert_eq!(
+        from_json::<RemoteChainInfo>(to_json_binary(&remote_chain_info).unwrap()).unwrap(),
+        remote_chain_info
+    );
+}
+
+// Minimum transfer amount tests
+
+#[test]
+#[should_panic(
+    expected = "Invalid ICA IBC transfer config: min_transfer_amount cannot be larger than amount."
+)]
+fn instantiate_fails_for_min_transfer_amount_above_amount() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+
+    let cfg = suite
+        .ica_ibc_transfer_config(
+            UUSDC.to_string(),
+            Uint128::new(ONE_THOUSAND),
+            RECEIVER.to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), None),
+        )
+        .with_min_transfer_amount(Uint128::new(ONE_THOUSAND + 1));
+
+    // Instantiate ICA IBC transfer contract
+    suite.ica_ibc_transfer_init(&cfg);
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid ICA IBC transfer config: min_transfer_amount cannot be larger than amount."
+)]
+fn update_config_validates_min_transfer_amount() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+
+    let mut cfg = suite
+        .ica_ibc_transfer_config(
+            UUSDC.to_string(),
+            Uint128::new(ONE_THOUSAND),
+            RECEIVER.to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), None),
+        )
+        .with_min_transfer_amount(Uint128::new(ONE_THOUSAND));
+
+    // Instantiate ICA IBC transfer contract
+    let lib = suite.ica_ibc_transfer_init(&cfg);
+
+    // Lower the amount below the configured minimum
+    cfg.amount = Uint128::new(ONE_THOUSAND - 1);
+
+    // Execute update config action
+    suite.update_config(lib, cfg).unwrap();
+}
+
+#[test]
+fn transfer_skipped_below_min_transfer_amount() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    suite.set_remote_balance(ica_addr.clone(), ONE_THOUSAND - 1, UUSDC);
+
+    let cfg = LibraryConfig::new(
+        valence_library_utils::LibraryAccountType::Addr(ica_addr.to_string()),
+        Uint128::new(ONE_THOUSAND),
+        UUSDC.to_string(),
+        RECEIVER.to_string(),
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), None),
+        BTreeMap::default(),
+        None,
+    )
+    .with_min_transfer_amount(Uint128::new(ONE_THOUSAND));
+    let lib = suite.ica_ibc_transfer_init(&cfg);
+
+    let res = suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap();
+
+    let attributes: Vec<_> = res
+        .events
+        .iter()
+        .flat_map(|e| e.attributes.iter())
+        .collect();
+    assert!(attributes
+        .iter()
+        .any(|a| a.key == "method" && a.value == "ica_ibc_transfer_skipped"));
+    assert!(!attributes.iter().any(|a| a.key == "selected_channel_id"));
+    assert!(!attributes.iter().any(|a| a.key == "reason"));
+}
+
+#[test]
+fn transfer_skipped_without_known_remote_balance() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+
+    let lib = suite.ica_ibc_transfer_init(&min_transfer_amount_config(&ica_addr));
+
+    let res = suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap();
+
+    let attributes: Vec<_> = res
+        .events
+        .iter()
+        .flat_map(|e| e.attributes.iter())
+        .collect();
+    assert!(attributes
+        .iter()
+        .any(|a| a.key == "method" && a.value == "ica_ibc_transfer_skipped"));
+    assert!(attributes
+        .iter()
+        .any(|a| a.key == "balance" && a.value == "0"));
+    assert!(!attributes.iter().any(|a| a.key == "selected_channel_id"));
+}
+
+#[test]
+fn transfer_proceeds_at_min_transfer_amount() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    suite.set_remote_balance(ica_addr.clone(), ONE_THOUSAND, UUSDC);
+
+    let cfg = LibraryConfig::new(
+        valence_library_utils::LibraryAccountType::Addr(ica_addr.to_string()),
+        Uint128::new(ONE_THOUSAND),
+        UUSDC.to_string(),
+        RECEIVER.to_string(),
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), None),
+        BTreeMap::default(),
+        None,
+    )
+    .with_min_transfer_amount(Uint128::new(ONE_THOUSAND));
+    let lib = suite.ica_ibc_transfer_init(&cfg);
+
+    let res = suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap();
+
+    let attributes: Vec<_> = res
+        .events
+        .iter()
+        .flat_map(|e| e.attributes.iter())
+        .collect();
+    assert!(attributes
+        .iter()
+        .any(|a| a.key == "method" && a.value == "ica_ibc_transfer"));
+    assert!(attributes
+        .iter()
+        .any(|a| a.key == "selected_channel_id" && a.value == "channel-1"));
+}
+
+const WASM_HOOK_MEMO_TEMPLATE: &str = r#"{"wasm":{"contract":"{receiver}","msg":{"deposit":{"amount":"{amount}","denom":"{denom}"}}}}"#;
+
+#[test]
+fn memo_template_renders_transfer_values() {
+    let rendered = render_memo_template(
+        WASM_HOOK_MEMO_TEMPLATE,
+        Uint128::new(ONE_THOUSAND),
+        "receiver",
+        UUSDC,
+    );
+    assert_eq!(
+        rendered,
+        r#"{"wasm":{"contract":"receiver","msg":{"deposit":{"amount":"1000000000","denom":"uusdc"}}}}"#
+    );
+
+    let cfg = Config::new(
+        Addr::unchecked("input"),
+        Uint128::new(ONE_THOUSAND),
+        UUSDC.to_string(),
+        "receiver".to_string(),
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), None),
+        BTreeMap::default(),
+        None,
+    );
+    // The plain memo is used as is when there is no template
+    assert_eq!(cfg.transfer_memo(), "");
+    assert_eq!(
+        cfg.with_memo_template(WASM_HOOK_MEMO_TEMPLATE.to_string())
+            .transfer_memo(),
+        rendered
+    );
+}
+
+#[test]
+fn pfm_transfer_forwards_rendered_memo() {
+    let env = mock_env();
+    let memo = render_memo_template(
+        WASM_HOOK_MEMO_TEMPLATE,
+        Uint128::new(ONE_THOUSAND),
+        "receiver",
+        UUSDC,
+    );
+
+    let msg = create_msg_transfer(
+        &env,
+        "".to_string(),
+        "receiver".to_string(),
+        UUSDC.to_string(),
+        ONE_THOUSAND,
+        memo.clone(),
+        None,
+        &BTreeMap::from([(
+            UUSDC.to_string(),
+            pfm_config(Some("hop_receiver".to_string())),
+        )]),
+        "remote_ica".to_string(),
+    )
+    .unwrap();
+
+    // The rendered memo is carried by the packet forwarded by the hop chain
+    let metadata: PacketMetadata = serde_json::from_str(&msg.memo).unwrap();
+    let forward = metadata.forward.unwrap();
+    assert_eq!(forward.receiver, "receiver");
+    assert_eq!(forward.next, Some(memo));
+}
+
+#[test]
+fn pfm_transfer_forwards_configured_retries_and_timeout() {
+    let msg = create_msg_transfer(
+        &mock_env(),
+        "".to_string(),
+        "receiver".to_string(),
+        UUSDC.to_string(),
+        ONE_THOUSAND,
+        "".to_string(),
+        None,
+        &BTreeMap::from([(
+            UUSDC.to_string(),
+            pfm_config(None).with_retries(4).with_timeout(3_600),
+        )]),
+        "remote_ica".to_string(),
+    )
+    .unwrap();
+
+    let metadata: PacketMetadata = serde_json::from_str(&msg.memo).unwrap();
+    let forward = metadata.forward.unwrap();
+    assert_eq!(forward.channel, "channel-7");
+    assert_eq!(forward.retries, Some(4));
+    assert_eq!(forward.timeout, Some("3600s".to_string()));
+}
+
+#[test]
+fn pre_validate_rejects_pfm_route_out_of_bounds() {
+    let suite = IcaIbcTransferTestSuite::default();
+
+    let mut cfg = suite.ica_ibc_transfer_config(
+        UUSDC.to_string(),
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
+    );
+    cfg.denom_to_pfm_map = BTreeMap::from([(UUSDC.to_string(), pfm_config(None).with_timeout(30))]);
+
+    let err = cfg.pre_validate(suite.api()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Invalid PFM route for denom uusdc: Generic error: timeout must be between 60 and 604800 seconds."
+    );
+
+    cfg.denom_to_pfm_map = BTreeMap::from([(UUSDC.to_string(), pfm_config(None).with_retries(20))]);
+    assert!(cfg.pre_validate(suite.api()).is_err());
+}
+
+#[test]
+fn pre_validate_rejects_invalid_memo_templates() {
+    let suite = IcaIbcTransferTestSuite::default();
+
+    let mut cfg = suite
+        .ica_ibc_transfer_config(
+            UUSDC.to_string(),
+            Uint128::new(ONE_THOUSAND),
+            RECEIVER.to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(TimeoutPolicy::Relative(600))),
+        )
+        .with_memo_template("transfer of {amount}{denom}".to_string());
+
+    // Any template can be used for direct transfers
+    cfg.pre_validate(suite.api()).unwrap();
+
+    // Templates can't be combined with a plain memo
+    cfg.memo = "memo".to_string();
+    assert_eq!(
+        cfg.pre_validate(suite.api()).unwrap_err().to_string(),
+        "Configuration error: Invalid ICA IBC transfer config: memo and memo_template cannot both be set."
+    );
+
+    // PFM transfers require the template to render to a JSON object
+    cfg.memo = "".to_string();
+    cfg.denom_to_pfm_map = BTreeMap::from([(
+        UUSDC.to_string(),
+        pfm_config(Some("hop_receiver".to_string())),
+    )]);
+    assert_eq!(
+        cfg.pre_validate(suite.api()).unwrap_err().to_string(),
+        "Configuration error: Invalid ICA IBC transfer config: memo_template must render to a JSON object for PFM transfers."
+    );
+
+    cfg.memo_template = Some(WASM_HOOK_MEMO_TEMPLATE.to_string());
+    cfg.pre_validate(suite.api()).unwrap();
+}
+
+fn memo_config(ica_addr: &Addr, memo: &str) -> LibraryConfig {
+    LibraryConfig::new(
+        valence_library_utils::LibraryAccountType::Addr(ica_addr.to_string()),
+        Uint128::new(ONE_THOUSAND),
+        UUSDC.to_string(),
+        RECEIVER.to_string(),
+        memo.to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), None),
+        BTreeMap::default(),
+        None,
+    )
+}
+
+#[test]
+fn transfer_rejects_oversized_memo() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    let memo = render_memo_template(
+        WASM_HOOK_MEMO_TEMPLATE,
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER,
+        UUSDC,
+    );
+
+    let lib = suite.ica_ibc_transfer_init(&memo_config(&ica_addr, &memo).with_max_memo_bytes(64));
+
+    let err = suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Execution error: Packet memo of {} bytes exceeds the max memo size of 64 bytes.",
+            memo.len()
+        )
+    );
+}
+
+#[test]
+fn transfer_accepts_memo_within_max_memo_bytes() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    let memo = render_memo_template(
+        WASM_HOOK_MEMO_TEMPLATE,
+        Uint128::new(ONE_THOUSAND),
+        RECEIVER,
+        UUSDC,
+    );
+
+    // The default max memo size of the IBC transfer module applies
+    let lib = suite.ica_ibc_transfer_init(&memo_config(&ica_addr, &memo));
+    suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap();
+
+    let lib = suite.ica_ibc_transfer_init(
+        &memo_config(&ica_addr, &memo).with_max_memo_bytes(memo.len() as u32),
+    );
+    suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap();
+}
+
+#[test]
+fn transfer_rejects_malformed_json_memo() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+
+    let lib = suite.ica_ibc_transfer_init(&memo_config(&ica_addr, r#"{"wasm":{"contract":"#));
+
+    let err = suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Execution error: Packet memo is not valid JSON."
+    );
+}
+
+#[test]
+fn pre_validate_rejects_zero_max_memo_bytes() {
+    let suite = IcaIbcTransferTestSuite::default();
+
+    let cfg = memo_config(&suite.api().addr_make("input"), "").with_max_memo_bytes(0);
+    assert_eq!(
+        cfg.pre_validate(suite.api()).unwrap_err().to_string(),
+        "Configuration error: Invalid ICA IBC transfer config: max_memo_bytes cannot be zero."
+    );
+}
+
+// Transfer event tests
+
+fn transfer_event(events: &[Event]) -> Event {
+    let mut event = events
+        .iter()
+        .find(|event| event.ty == format!("wasm-{TRANSFER_EVENT_TYPE}"))
+        .unwrap()
+        .clone();
+    // Strip what the test app adds to the events emitted by contracts
+    event.ty = TRANSFER_EVENT_TYPE.to_string();
+    event
+        .attributes
+        .retain(|attr| attr.key != "_contract_address");
+    event
+}
+
+fn min_transfer_amount_config(ica_addr: &Addr) -> LibraryConfig {
+    LibraryConfig::new(
+        valence_library_utils::LibraryAccountType::Addr(ica_addr.to_string()),
+        Uint128::new(ONE_THOUSAND),
+        UUSDC.to_string(),
+        RECEIVER.to_string(),
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), None),
+        BTreeMap::default(),
+        None,
+    )
+    .with_min_transfer_amount(Uint128::new(ONE_THOUSAND))
+}
+
+#[test]
+fn transfer_emits_transfer_event() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    suite.set_remote_balance(ica_addr.clone(), ONE_THOUSAND + 1, UUSDC);
+
+    let lib = suite.ica_ibc_transfer_init(&min_transfer_amount_config(&ica_addr));
+
+    let res = suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap();
+
+    let event = transfer_event(&res.events);
+    assert_eq!(
+        event,
+        Event::from(
+            TransferEvent::new("ica_ibc_transfer", UUSDC)
+                .with_input_balance(Uint128::new(ONE_THOUSAND + 1))
+                .with_amount(Uint128::new(ONE_THOUSAND))
+                .with_channel_id("channel-1")
+        )
+    );
+    assert_eq!(
+        event
+            .attributes
+            .iter()
+            .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("method", "ica_ibc_transfer"),
+            ("status", "transferred"),
+            ("denom", UUSDC),
+            ("input_balance", "1000000001"),
+            ("amount", "1000000000"),
+            ("channel_id", "channel-1"),
+        ]
+    );
+}
+
+#[test]
+fn skipped_transfer_emits_transfer_event() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    suite.set_remote_balance(ica_addr.clone(), ONE_THOUSAND - 1, UUSDC);
+
+    let lib = suite.ica_ibc_transfer_init(&min_transfer_amount_config(&ica_addr));
+
+    let res = suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap();
+
+    let event = transfer_event(&res.events);
+    assert_eq!(
+        event
+            .attributes
+            .iter()
+            .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("method", "ica_ibc_transfer"),
+            ("status", "skipped"),
+            ("denom", UUSDC),
+            ("input_balance", "999999999"),
+            ("skip_reason", "balance below min_transfer_amount"),
+        ]
+    );
+}
+
+// ICA callback tests
+
+fn ica_callback_config(ica_addr: &Addr) -> LibraryConfig {
+    LibraryConfig::new(
+        valence_library_utils::LibraryAccountType::Addr(ica_addr.to_string()),
+        Uint128::new(ONE_THOUSAND),
+        UUSDC.to_string(),
+        RECEIVER.to_string(),
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), None),
+        BTreeMap::default(),
+        None,
+    )
+}
+
+fn ica_callback(
+    suite: &mut IcaIbcTransferTestSuite,
+    sender: Addr,
+    lib: Addr,
+    sequence: u64,
+    result: IcaTxResult,
+) -> AnyResult<AppResponse> {
+    suite.app_mut().execute_contract(
+        sender,
+        lib,
+        &IcaCallbackMsg::IcaTxCallback {
+            channel_id: "channel-0".to_string(),
+            sequence,
+            result,
+        },
+        &[],
+    )
+}
+
+fn query_transfers(suite: &IcaIbcTransferTestSuite, lib: &Addr) -> TransfersResponse {
+    suite.query_wasm(lib, &QueryMsg::Transfers {})
+}
+
+fn transfer_result_attributes(events: &[Event]) -> Vec<(String, String)> {
+    events
+        .iter()
+        .find(|event| event.ty == format!("wasm-{TRANSFER_RESULT_EVENT_TYPE}"))
+        .unwrap()
+        .attributes
+        .iter()
+        .filter(|attr| attr.key != "_contract_address")
+        .map(|attr| (attr.key.clone(), attr.value.clone()))
+        .collect()
+}
+
+fn transfer_record(status: TransferStatus) -> TransferRecord {
+    TransferRecord {
+        amount: Uint128::new(ONE_THOUSAND),
+        denom: UUSDC.to_string(),
+        channel_id: "channel-1".to_string(),
+        status,
+    }
+}
+
+#[test]
+fn timeout_callback_marks_last_transfer_timed_out() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    let lib = suite.ica_ibc_transfer_init(&ica_callback_config(&ica_addr));
+
+    suite
+        .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+        .unwrap();
+    assert_eq!(
+        query_transfers(&suite, &lib),
+        TransfersResponse {
+            last_transfer: Some(transfer_record(TransferStatus::Pending)),
+            in_flight: vec![transfer_record(TransferStatus::Pending)],
+            pending_retry_amounts: vec![],
+        }
+    );
+
+    // Only the input account can report the outcome of a transfer
+    let owner = suite.owner().clone();
+    ica_callback(&mut suite, owner, lib.clone(), 1, IcaTxResult::Timeout {}).unwrap_err();
+
+    let res = ica_callback(
+        &mut suite,
+        ica_addr.clone(),
+        lib.clone(),
+        1,
+        IcaTxResult::Timeout {},
+    )
+    .unwrap();
+    assert_eq!(
+        transfer_result_attributes(&res.events),
+        [
+            ("result", "timed_out"),
+            ("amount", "1000000000"),
+            ("denom", UUSDC),
+            ("channel_id", "channel-1"),
+            ("ica_channel_id", "channel-0"),
+            ("ica_sequence", "1"),
+            ("pending_retry_amount", "0"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+    );
+
+    // Failed transfers are not re-credited unless configured
+    assert_eq!(
+        query_transfers(&suite, &lib),
+        TransfersResponse {
+            last_transfer: Some(transfer_record(TransferStatus::TimedOut)),
+            in_flight: vec![],
+            pending_retry_amounts: vec![],
+        }
+    );
+
+    // Callbacks without a transfer in flight are rejected
+    ica_callback(&mut suite, ica_addr, lib, 2, IcaTxResult::Timeout {}).unwrap_err();
+}
+
+#[test]
+fn error_ack_callback_recredits_pending_retry_amount() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    let lib = suite
+        .ica_ibc_transfer_init(&ica_callback_config(&ica_addr).with_recredit_failed_transfers());
+
+    for _ in 0..2 {
+        suite
+            .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+            .unwrap();
+    }
+
+    // The outcome of the oldest transfer doesn't override the one of the last transfer sent
+    let res = ica_callback(
+        &mut suite,
+        ica_addr.clone(),
+        lib.clone(),
+        1,
+        IcaTxResult::Error {
+            details: "insufficient funds".to_string(),
+        },
+    )
+    .unwrap();
+    let attributes = transfer_result_attributes(&res.events);
+    assert!(attributes.contains(&("result".to_string(), "failed".to_string())));
+    assert!(attributes.contains(&("reason".to_string(), "insufficient funds".to_string())));
+    assert!(attributes.contains(&("pending_retry_amount".to_string(), "1000000000".to_string())));
+    assert_eq!(
+        query_transfers(&suite, &lib),
+        TransfersResponse {
+            last_transfer: Some(transfer_record(TransferStatus::Pending)),
+            in_flight: vec![transfer_record(TransferStatus::Pending)],
+            pending_retry_amounts: vec![coin(ONE_THOUSAND, UUSDC)],
+        }
+    );
+
+    // Callbacks are still handled while the library is paused
+    let owner = suite.owner().clone();
+    suite
+        .app_mut()
+        .execute_contract(
+            owner,
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::Pause {},
+            &[],
+        )
+        .unwrap();
+
+    // The successful transfer settles the re-credited amount
+    ica_callback(
+        &mut suite,
+        ica_addr,
+        lib.clone(),
+        2,
+        IcaTxResult::Success {
+            data: Binary::default(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        query_transfers(&suite, &lib),
+        TransfersResponse {
+            last_transfer: Some(transfer_record(TransferStatus::Succeeded)),
+            in_flight: vec![],
+            pending_retry_amounts: vec![],
+        }
+    );
+}
+
+#[test]
+fn callbacks_are_matched_to_the_ica_packet_of_their_transfer() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    let lib = suite
+        .ica_ibc_transfer_init(&ica_callback_config(&ica_addr).with_recredit_failed_transfers());
+
+    for _ in 0..2 {
+        suite
+            .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+            .unwrap();
+    }
+
+    // The last transfer is acknowledged first
+    ica_callback(
+        &mut suite,
+        ica_addr.clone(),
+        lib.clone(),
+        2,
+        IcaTxResult::Success {
+            data: Binary::default(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        query_transfers(&suite, &lib),
+        TransfersResponse {
+            last_transfer: Some(transfer_record(TransferStatus::Succeeded)),
+            in_flight: vec![transfer_record(TransferStatus::Pending)],
+            pending_retry_amounts: vec![],
+        }
+    );
+
+    // Outcomes are only reported once, and only for packets that carried a transfer
+    ica_callback(
+        &mut suite,
+        ica_addr.clone(),
+        lib.clone(),
+        2,
+        IcaTxResult::Timeout {},
+    )
+    .unwrap_err();
+    ica_callback(
+        &mut suite,
+        ica_addr.clone(),
+        lib.clone(),
+        3,
+        IcaTxResult::Timeout {},
+    )
+    .unwrap_err();
+
+    // The outcome of the older transfer doesn't override the one of the last transfer
+    ica_callback(
+        &mut suite,
+        ica_addr,
+        lib.clone(),
+        1,
+        IcaTxResult::Timeout {},
+    )
+    .unwrap();
+    assert_eq!(
+        query_transfers(&suite, &lib),
+        TransfersResponse {
+            last_transfer: Some(transfer_record(TransferStatus::Succeeded)),
+            in_flight: vec![],
+            pending_retry_amounts: vec![coin(ONE_THOUSAND, UUSDC)],
+        }
+    );
+}
+
+#[test]
+fn only_successful_transfers_are_counted_in_stats() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    let lib = suite
+        .ica_ibc_transfer_init(&ica_callback_config(&ica_addr).with_recredit_failed_transfers());
+
+    // A transfer that fails and its retry that succeeds
+    for _ in 0..2 {
+        suite
+            .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+            .unwrap();
+    }
+    let stats: TransferStats = suite.query_wasm(&lib, &QueryMsg::Stats {});
+    assert_eq!(stats, TransferStats::default());
+
+    ica_callback(
+        &mut suite,
+        ica_addr.clone(),
+        lib.clone(),
+        1,
+        IcaTxResult::Timeout {},
+    )
+    .unwrap();
+    let stats: TransferStats = suite.query_wasm(&lib, &QueryMsg::Stats {});
+    assert_eq!(stats, TransferStats::default());
+
+    ica_callback(
+        &mut suite,
+        ica_addr,
+        lib.clone(),
+        2,
+        IcaTxResult::Success {
+            data: Binary::default(),
+        },
+    )
+    .unwrap();
+    let stats: TransferStats = suite.query_wasm(&lib, &QueryMsg::Stats {});
+    assert_eq!(
+        stats,
+        TransferStats {
+            total_amounts: BTreeMap::from([(UUSDC.to_string(), Uint128::new(ONE_THOUSAND))]),
+            executions: 1,
+            last_execution_height: Some(suite.app().block_info().height),
+        }
+    );
+}
+
+#[test]
+fn transfers_are_rejected_while_too_many_are_in_flight() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    let lib = suite.ica_ibc_transfer_init(&ica_callback_config(&ica_addr));
+
+    for _ in 0..MAX_IN_FLIGHT_TRANSFERS {
+        suite
+            .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+            .unwrap();
+    }
+
+    let err = suite
+        .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        LibraryError::ExecutionError(format!(
+            "Too many transfers waiting for their outcome (max {MAX_IN_FLIGHT_TRANSFERS})."
+        ))
+        .to_string()
+    );
+
+    // Reporting an outcome frees a slot
+    ica_callback(
+        &mut suite,
+        ica_addr,
+        lib.clone(),
+        1,
+        IcaTxResult::Timeout {},
+    )
+    .unwrap();
+    suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap();
+}
+
+fn post_transfer_callback_attribute(events: &[Event]) -> Option<String> {
+    transfer_result_attributes(events)
+        .into_iter()
+        .find(|(key, _)| key == "post_transfer_callback")
+        .map(|(_, value)| value)
+}
+
+fn post_transfer_callback(authorization: &Addr, arrival_account: &Addr) -> PostTransferCallback {
+    PostTransferCallback::new(
+        authorization.to_string(),
+        "provide_liquidity".to_string(),
+        &MockApi::default().addr_make("lper"),
+        Binary::from(br#"{"provide_double_sided_liquidity":{}}"#),
+        arrival_account,
+        IBC_USDC.to_string(),
+    )
+}
+
+fn authorization_msgs(
+    suite: &IcaIbcTransferTestSuite,
+    authorization: &Addr,
+) -> Vec<serde_json::Value> {
+    suite.query_wasm(authorization, &Empty {})
+}
+
+// Message enqueuing the function of the follow-up library through the authorization contract
+fn expected_enqueue_msg() -> serde_json::Value {
+    serde_json::to_value(
+        valence_authorization_utils::msg::ExecuteMsg::PermissionlessAction(
+            valence_authorization_utils::msg::PermissionlessMsg::SendMsgs {
+                label: "provide_liquidity".to_string(),
+                messages: vec![
+                    valence_authorization_utils::msg::ProcessorMessage::CosmwasmExecuteMsg {
+                        msg: to_json_binary(&serde_json::json!({
+                            "process_function": {"provide_double_sided_liquidity": {}}
+                        }))
+                        .unwrap(),
+                    },
+                ],
+                ttl: None,
+            },
+        ),
+    )
+    .unwrap()
+}
+
+fn enqueue_arrived_transfers(
+    suite: &mut IcaIbcTransferTestSuite,
+    lib: &Addr,
+) -> AnyResult<AppResponse> {
+    let sender = suite.api().addr_make("anyone");
+    suite.app_mut().execute_contract(
+        sender,
+        lib.clone(),
+        &PostTransferCallbackMsg::EnqueueArrivedTransfers {},
+        &[],
+    )
+}
+
+#[test]
+fn post_transfer_callback_waits_for_the_funds_to_arrive() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    let authorization = suite.mock_authorization_init();
+    let arrival_account = suite.api().addr_make("deposit_account");
+    let lib = suite.ica_ibc_transfer_init(
+        &ica_callback_config(&ica_addr)
+            .with_post_transfer_callback(post_transfer_callback(&authorization, &arrival_account)),
+    );
+
+    for _ in 0..2 {
+        suite
+            .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+            .unwrap();
+    }
+
+    // Failed transfers don't enqueue the follow-up
+    let res = ica_callback(
+        &mut suite,
+        ica_addr.clone(),
+        lib.clone(),
+        1,
+        IcaTxResult::Timeout {},
+    )
+    .unwrap();
+    assert_eq!(post_transfer_callback_attribute(&res.events), None);
+
+    // The ack of the ICA tx doesn't mean that the funds reached the arrival account
+    let res = ica_callback(
+        &mut suite,
+        ica_addr,
+        lib.clone(),
+        2,
+        IcaTxResult::Success {
+            data: Binary::default(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        post_transfer_callback_attribute(&res.events).as_deref(),
+        Some("waiting_for_arrival")
+    );
+    assert!(authorization_msgs(&suite, &authorization).is_empty());
+    let err = enqueue_arrived_transfers(&mut suite, &lib).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Execution error: No transfer waiting for its funds has arrived."
+    );
+
+    // Once the funds arrived, anyone can enqueue the follow-up through the authorization contract
+    suite.init_balance(&arrival_account, vec![coin(ONE_THOUSAND, IBC_USDC)]);
+    enqueue_arrived_transfers(&mut suite, &lib).unwrap();
+    assert_eq!(
+        authorization_msgs(&suite, &authorization),
+        vec![expected_enqueue_msg()]
+    );
+
+    // The follow-up of a transfer is only enqueued once
+    enqueue_arrived_transfers(&mut suite, &lib).unwrap_err();
+}
+
+#[test]
+fn post_transfer_callback_is_enqueued_on_ack_once_the_funds_arrived() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+    let authorization = suite.mock_authorization_init();
+    let arrival_account = suite.api().addr_make("deposit_account");
+    let lib = suite.ica_ibc_transfer_init(
+        &ica_callback_config(&ica_addr)
+            .with_post_transfer_callback(post_transfer_callback(&authorization, &arrival_account)),
+    );
+
+    for _ in 0..2 {
+        suite
+            .execute_function(lib.clone(), FunctionMsgs::Transfer {})
+            .unwrap();
+    }
+
+    // The funds of the first transfer were relayed before the ack of the ICA tx
+    suite.init_balance(&arrival_account, vec![coin(ONE_THOUSAND, IBC_USDC)]);
+    let res = ica_callback(
+        &mut suite,
+        ica_addr.clone(),
+        lib.clone(),
+        1,
+        IcaTxResult::Success {
+            data: Binary::default(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        post_transfer_callback_attribute(&res.events).as_deref(),
+        Some("enqueued")
+    );
+    assert_eq!(
+        authorization_msgs(&suite, &authorization),
+        vec![expected_enqueue_msg()]
+    );
+
+    // The funds already counted for the first transfer aren't counted for the second one
+    let res = ica_callback(
+        &mut suite,
+        ica_addr,
+        lib.clone(),
+        2,
+        IcaTxResult::Success {
+            data: Binary::default(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        post_transfer_callback_attribute(&res.events).as_deref(),
+        Some("waiting_for_arrival")
+    );
+    assert_eq!(
+        query_transfers(&suite, &lib).last_transfer,
+        Some(transfer_record(TransferStatus::Succeeded))
+    );
+}
+
+#[test]
+fn post_transfer_callback_is_validated() {
+    let suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.api().addr_make("input_account");
+    let authorization = suite.api().addr_make("authorization");
+    let arrival_account = suite.api().addr_make("deposit_account");
+
+    // The function of the follow-up must be a JSON object
+    let mut callback = post_transfer_callback(&authorization, &arrival_account);
+    callback.function = Binary::from(b"sweep");
+    let err = ica_callback_config(&ica_addr)
+        .with_post_transfer_callback(callback)
+        .pre_validate(suite.api())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Invalid ICA IBC transfer config: function of the post_transfer_callback is not a JSON object."
+    );
+
+    let mut callback = post_transfer_callback(&authorization, &arrival_account);
+    callback.authorization_label = String::new();
+    let err = ica_callback_config(&ica_addr)
+        .with_post_transfer_callback(callback)
+        .pre_validate(suite.api())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Invalid ICA IBC transfer config: authorization_label of the post_transfer_callback cannot be empty."
+    );
+
+    let mut callback = post_transfer_callback(&authorization, &arrival_account);
+    callback.arrival_denom = String::new();
+    let err = ica_callback_config(&ica_addr)
+        .with_post_transfer_callback(callback)
+        .pre_validate(suite.api())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Invalid ICA IBC transfer config: arrival_denom of the post_transfer_callback cannot be empty."
+    );
+}
+
+fn usdc_trace() -> DenomTrace {
+    DenomTrace {
+        path: "transfer/channel-750".to_string(),
+        base_denom: UUSDC.to_string(),
+    }
+}
+
+fn denom_trace_config(ica_addr: &Addr, denom: &str, trace: DenomTrace) -> LibraryConfig {
+    LibraryConfig::new(
+        valence_library_utils::LibraryAccountType::Addr(ica_addr.to_string()),
+        Uint128::new(ONE_THOUSAND),
+        denom.to_string(),
+        RECEIVER.to_string(),
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), None),
+        BTreeMap::default(),
+        None,
+    )
+    .with_expected_base_denom(trace)
+}
+
+#[test]
+fn denom_trace_resolves_to_ibc_denom() {
+    assert_eq!(usdc_trace().denom(), IBC_USDC);
+    assert_eq!(
+        DenomTrace {
+            path: "transfer/channel-0".to_string(),
+            base_denom: "uatom".to_string(),
+        }
+        .denom(),
+        IBC_ATOM
+    );
+    // Native denoms have no path
+    assert_eq!(
+        DenomTrace {
+            path: "".to_string(),
+            base_denom: UUSDC.to_string(),
+        }
+        .denom(),
+        UUSDC
+    );
+}
+
+#[test]
+fn transfer_with_matching_denom_trace() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+
+    let cfg = denom_trace_config(&ica_addr, IBC_USDC, usdc_trace());
+    let lib = suite.ica_ibc_transfer_init(&cfg);
+
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(lib_cfg.expected_base_denom, Some(usdc_trace()));
+
+    let res = suite
+        .execute_function(lib, FunctionMsgs::Transfer {})
+        .unwrap();
+    assert!(res
+        .events
+        .iter()
+        .flat_map(|e| e.attributes.iter())
+        .any(|a| a.key == "method" && a.value == "ica_ibc_transfer"));
+
+    // The hash of the denom is not case sensitive
+    denom_trace_config(&ica_addr, &IBC_USDC.to_lowercase(), usdc_trace())
+        .pre_validate(suite.api())
+        .unwrap();
+}
+
+#[test]
+#[should_panic(
+    expected = "Denom ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2 does not resolve to base denom uusdc through path 'transfer/channel-750'"
+)]
+fn instantiate_fails_for_mismatched_denom_trace() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+
+    let cfg = denom_trace_config(&ica_addr, IBC_ATOM, usdc_trace());
+    suite.ica_ibc_transfer_init(&cfg);
+}
+
+#[test]
+fn pre_validate_rejects_invalid_denom_traces() {
+    let suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.api().addr_make("ica");
+
+    // Same base denom received through another channel
+    let other_path = DenomTrace {
+        path: "transfer/channel-1".to_string(),
+        ..usdc_trace()
+    };
+    let err = denom_trace_config(&ica_addr, IBC_USDC, other_path.clone())
+        .pre_validate(suite.api())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        LibraryError::from(DenomTraceError::Mismatch {
+            denom: IBC_USDC.to_string(),
+            base_denom: UUSDC.to_string(),
+            path: "transfer/channel-1".to_string(),
+            expected_denom: other_path.denom(),
+        })
+        .to_string()
+    );
+
+    // A native denom doesn't resolve to an IBC trace
+    let err = denom_trace_config(&ica_addr, UUSDC, usdc_trace())
+        .pre_validate(suite.api())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        LibraryError::ConfigurationError(msg) if msg.starts_with("Denom uusdc does not resolve")
+    ));
+
+    for (path, base_denom, expected_err) in [
+        ("transfer/channel-750", "", DenomTraceError::EmptyBaseDenom),
+        (
+            "transfer",
+            UUSDC,
+            DenomTraceError::InvalidPath("transfer".to_string()),
+        ),
+        (
+            "transfer/750",
+            UUSDC,
+            DenomTraceError::InvalidPath("transfer/750".to_string()),
+        ),
+    ] {
+        let trace = DenomTrace {
+            path: path.to_string(),
+            base_denom: base_denom.to_string(),
+        };
+        let err = denom_trace_config(&ica_addr, IBC_USDC, trace)
+            .pre_validate(suite.api())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            LibraryError::from(expected_err).to_string()
+        );
+    }
+}
+
+#[test]
+#[should_panic(
+    expected = "does not resolve to base denom uusdc through path 'transfer/channel-750'"
+)]
+fn update_config_validates_denom_against_trace() {
+    let mut suite = IcaIbcTransferTestSuite::default();
+    let ica_addr = suite.mock_ica_init();
+
+    let cfg = denom_trace_config(&ica_addr, IBC_USDC, usdc_trace());
+    let lib = suite.ica_ibc_transfer_init(&cfg);
+
+    // Switching to a look-alike denom without updating the trace fails
+    let new_cfg = denom_trace_config(&ica_addr, IBC_ATOM, usdc_trace());
+    suite.update_config(lib, new_cfg).unwrap();
+}
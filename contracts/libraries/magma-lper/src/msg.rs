@@ -52,6 +52,7 @@ impl LibraryConfig {
         let input_addr = self.input_addr.to_addr(api)?;
         let output_addr = self.output_addr.to_addr(api)?;
         api.addr_validate(&self.lp_config.vault_addr)?;
+        self.lp_config.asset_data.ensure_two_assets()?;
 
         Ok((input_addr, output_addr))
     }
@@ -104,6 +105,7 @@ impl LibraryConfigUpdate {
         }
 
         if let Some(lp_config) = self.lp_config {
+            lp_config.asset_data.ensure_two_assets()?;
             config.lp_config = lp_config;
             deps.api
                 .addr_validate(config.lp_config.vault_addr.as_ref())?;
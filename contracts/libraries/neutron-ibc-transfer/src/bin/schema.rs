@@ -1,6 +1,6 @@
 use cosmwasm_schema::write_api;
 
-use valence_library_utils::msg::{ExecuteMsg, InstantiateMsg};
+use valence_library_utils::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg};
 use valence_neutron_ibc_transfer_library::msg::{
     FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg,
 };
@@ -9,6 +9,7 @@ fn main() {
     write_api! {
         instantiate: InstantiateMsg<LibraryConfig>,
         execute: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
+        migrate: MigrateMsg<LibraryConfigUpdate>,
         query: QueryMsg,
     }
 }
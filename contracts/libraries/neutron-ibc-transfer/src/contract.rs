@@ -4,7 +4,7 @@ use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Resp
 use neutron_sdk::bindings::query::NeutronQuery;
 use valence_library_utils::{
     error::LibraryError,
-    msg::{ExecuteMsg, InstantiateMsg},
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg},
 };
 
 use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
@@ -40,49 +40,199 @@ pub fn execute(
     )
 }
 
-mod functions {
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
+    )
+}
+
+pub(crate) mod functions {
     use std::collections::BTreeMap;
 
-    use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdError};
+    use cosmwasm_std::{CosmosMsg, DepsMut, Env, MessageInfo, Response, StdError, Uint128};
     use neutron_sdk::bindings::query::NeutronQuery;
-    use valence_library_utils::{error::LibraryError, execute_on_behalf_of};
+    use valence_library_utils::{
+        denoms::CheckedDenom, error::LibraryError, events::TransferEvent, execute_on_behalf_of,
+        stats::record_transfers,
+    };
 
     use crate::msg::{Config, FunctionMsgs, IbcTransferAmount};
 
     pub fn process_function(
-        deps: DepsMut<NeutronQuery>,
+        mut deps: DepsMut<NeutronQuery>,
         env: Env,
         _info: MessageInfo,
         msg: FunctionMsgs,
         cfg: Config,
     ) -> Result<Response, LibraryError> {
-        let balance = cfg.denom().query_balance(&deps.querier, cfg.input_addr())?;
-
-        let amount = match cfg.amount() {
-            IbcTransferAmount::FullAmount => balance,
-            IbcTransferAmount::FixedAmount(amount) => {
-                if balance < *amount {
-                    return Err(LibraryError::ExecutionError(format!(
-                        "Insufficient balance for denom '{}' in config (required: {}, available: {}).",
-                        cfg.denom(), amount, balance,
-                    )));
+        let method = match msg {
+            FunctionMsgs::IbcTransfer {} => "ibc-transfer",
+            FunctionMsgs::EurekaTransfer { .. } => "ibc-eureka-transfer",
+        };
+
+        // Skip the transfers while the precondition is not met
+        if let Some(precondition) = cfg.precondition() {
+            if !precondition.is_met(&deps.querier)? {
+                return Ok(Response::new()
+                    .add_attribute("method", "ibc-transfer-skipped")
+                    .add_attribute("reason", "precondition not met"));
+            }
+        }
+
+        let balances: Vec<(CheckedDenom, Uint128)> = match cfg.denom_selection() {
+            Some(denom_selection) => {
+                if denom_selection.is_multi_denom()
+                    && matches!(msg, FunctionMsgs::EurekaTransfer { .. })
+                {
+                    return Err(LibraryError::ExecutionError(
+                        "Eureka transfers only support a single denom.".to_string(),
+                    ));
                 }
-                *amount
+                denom_selection
+                    .query_balances(&deps.querier, cfg.input_addr())?
+                    .into_iter()
+                    .map(|(denom, balance)| (CheckedDenom::Native(denom), balance))
+                    .collect()
             }
+            None => vec![(
+                cfg.denom().clone(),
+                cfg.denom().query_balance(&deps.querier, cfg.input_addr())?,
+            )],
         };
+
+        let output_addr = cfg.resolve_output_addr(&deps.querier)?;
+
+        let mut response = Response::new();
+        let mut ibc_send_msgs = vec![];
+        let mut transfers = vec![];
+        let mut skipped_balances = vec![];
+
+        // One transfer per denom, each resolving its amount against its own balance
+        for (denom, balance) in balances {
+            let transfer_event =
+                TransferEvent::new(method, denom.to_string()).with_input_balance(balance);
+
+            // Skip the transfer while the balance is below the configured minimum
+            if let Some(min_transfer_amount) = cfg.min_transfer_amount() {
+                if balance < *min_transfer_amount {
+                    response = response
+                        .add_event(transfer_event.skipped("balance below min_transfer_amount"));
+                    skipped_balances.push(balance);
+                    continue;
+                }
+            }
+
+            // The gas reserve is left in the input account so it can keep paying fees
+            let available = match cfg.gas_reserve() {
+                Some(gas_reserve) => gas_reserve.available_balance(&denom, balance),
+                None => balance,
+            };
+
+            let amount = match cfg.amount() {
+                IbcTransferAmount::FullAmount => available,
+                IbcTransferAmount::FixedAmount(amount) => {
+                    if available < *amount {
+                        return Err(LibraryError::ExecutionError(format!(
+                            "Insufficient balance for denom '{}' in config (required: {}, available: {}).",
+                            denom, amount, available,
+                        )));
+                    }
+                    *amount
+                }
+            };
+
+            // Nothing is left to transfer once the gas reserve is put aside
+            if amount.is_zero() && available < balance {
+                response = response.add_event(transfer_event.skipped("balance within gas_reserve"));
+                continue;
+            }
+
+            let (ibc_send_msg, channel_id) = ibc_transfer_msg(
+                deps.branch(),
+                &env,
+                &msg,
+                &cfg,
+                &output_addr,
+                &denom,
+                amount,
+            )?;
+
+            ibc_send_msgs.push(ibc_send_msg);
+            transfers.push((denom.to_string(), amount));
+            response = response.add_event(
+                transfer_event
+                    .with_amount(amount)
+                    .with_channel_id(channel_id),
+            );
+        }
+
+        // Nothing to send when every selected balance is empty or below the configured minimum
+        if ibc_send_msgs.is_empty() {
+            response = response.add_attribute("method", "ibc-transfer-skipped");
+            if skipped_balances.is_empty() {
+                return Ok(response.add_attribute("reason", "no balance to transfer"));
+            }
+
+            // The skipped transfer events already carry the reason
+            if let [balance] = skipped_balances.as_slice() {
+                response = response.add_attribute("balance", balance.to_string());
+            }
+            if let Some(min_transfer_amount) = cfg.min_transfer_amount() {
+                response =
+                    response.add_attribute("min_transfer_amount", min_transfer_amount.to_string());
+            }
+            return Ok(response);
+        }
+
+        record_transfers(deps.storage, env.block.height, &transfers)?;
+
+        let input_account_msgs = execute_on_behalf_of(ibc_send_msgs, cfg.input_addr())?;
+
+        Ok(response
+            .add_attribute("method", method)
+            .add_message(input_account_msgs))
+    }
+
+    // Returns the transfer message of `amount` of `denom` along with the channel it leaves through
+    fn ibc_transfer_msg(
+        deps: DepsMut<NeutronQuery>,
+        env: &Env,
+        msg: &FunctionMsgs,
+        cfg: &Config,
+        output_addr: &str,
+        denom: &CheckedDenom,
+        amount: Uint128,
+    ) -> Result<(CosmosMsg, String), LibraryError> {
         match msg {
             FunctionMsgs::IbcTransfer {} => {
+                // The transfer leaves through the hop chain channel if the denom is routed by PFM
+                let channel_id = cfg
+                    .denom_to_pfm_map()
+                    .get(&denom.to_string())
+                    .map(|pfm_config| pfm_config.local_to_hop_chain_channel_id.clone())
+                    .unwrap_or_else(|| cfg.remote_chain_info().channel_id.clone());
+
                 // IBC Transfer funds from input account to output account on the remote chain
                 let ibc_send_msg = valence_ibc_utils::neutron::ibc_send_message(
                     deps,
-                    env,
+                    env.clone(),
                     cfg.remote_chain_info().channel_id.clone(),
                     cfg.input_addr(),
-                    cfg.output_addr().to_string(),
-                    cfg.denom(),
+                    output_addr.to_string(),
+                    denom,
                     amount.u128(),
                     cfg.memo().clone(),
-                    cfg.remote_chain_info().ibc_transfer_timeout.map(Into::into),
+                    Some(cfg.ibc_transfer_timeout()),
                     cfg.denom_to_pfm_map().clone(),
                 )
                 .map_err(|err| {
@@ -93,12 +243,7 @@ mod functions {
                     }
                 })?;
 
-                let input_account_msgs =
-                    execute_on_behalf_of(vec![ibc_send_msg], cfg.input_addr())?;
-
-                Ok(Response::new()
-                    .add_attribute("method", "ibc-transfer")
-                    .add_message(input_account_msgs))
+                Ok((ibc_send_msg, channel_id))
             }
             FunctionMsgs::EurekaTransfer { eureka_fee } => {
                 let eureka_config = match cfg.eureka_config() {
@@ -111,31 +256,26 @@ mod functions {
                 };
 
                 let eureka_memo = valence_ibc_utils::generic::build_eureka_memo(
-                    &env,
-                    cfg.output_addr().clone(),
-                    eureka_fee,
+                    env,
+                    output_addr.to_string(),
+                    eureka_fee.clone(),
                     eureka_config.clone(),
                 )?;
 
                 let ibc_send_msg = valence_ibc_utils::neutron::ibc_send_message(
                     deps,
-                    env,
+                    env.clone(),
                     cfg.remote_chain_info().channel_id.clone(),
                     cfg.input_addr(),
                     eureka_config.callback_contract.clone(),
-                    cfg.denom(),
+                    denom,
                     amount.u128(),
                     eureka_memo,
-                    cfg.remote_chain_info().ibc_transfer_timeout.map(Into::into),
+                    Some(cfg.ibc_transfer_timeout()),
                     BTreeMap::default(),
                 )?;
 
-                let input_account_msgs =
-                    execute_on_behalf_of(vec![ibc_send_msg], cfg.input_addr())?;
-
-                Ok(Response::new()
-                    .add_attribute("method", "ibc-eureka-transfer")
-                    .add_message(input_account_msgs))
+                Ok((ibc_send_msg, cfg.remote_chain_info().channel_id.clone()))
             }
         }
     }
@@ -159,7 +299,7 @@ mod execute {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps<NeutronQuery>, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps<NeutronQuery>, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Ownership {} => {
             to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
@@ -167,6 +307,21 @@ pub fn query(deps: Deps<NeutronQuery>, _env: Env, msg: QueryMsg) -> StdResult<Bi
         QueryMsg::GetProcessor {} => {
             to_json_binary(&valence_library_base::get_processor(deps.storage)?)
         }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        QueryMsg::IsApproved {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&valence_library_utils::is_library_approved(
+                &deps.querier,
+                config.input_addr(),
+                &env.contract.address,
+            )?)
+        }
         QueryMsg::GetLibraryConfig {} => {
             let config: Config = valence_library_base::load_config(deps.storage)?;
             to_json_binary(&config)
@@ -176,5 +331,8 @@ pub fn query(deps: Deps<NeutronQuery>, _env: Env, msg: QueryMsg) -> StdResult<Bi
                 valence_library_utils::raw_config::query_raw_library_config(deps.storage)?;
             to_json_binary(&raw_config)
         }
+        QueryMsg::Stats {} => to_json_binary(&valence_library_utils::stats::query_transfer_stats(
+            deps.storage,
+        )?),
     }
 }
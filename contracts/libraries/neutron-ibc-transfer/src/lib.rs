@@ -2,8 +2,8 @@ pub mod contract;
 
 pub mod msg {
     pub use valence_generic_ibc_transfer_library::msg::{
-        Config, FunctionMsgs, IbcTransferAmount, LibraryConfig, LibraryConfigUpdate, QueryMsg,
-        RemoteChainInfo,
+        Config, DenomSelection, FunctionMsgs, IbcTransferAmount, LibraryConfig,
+        LibraryConfigUpdate, QueryMsg, RemoteChainInfo,
     };
 }
 
@@ -1,9 +1,14 @@
 use crate::msg::{
-    Config, FunctionMsgs, IbcTransferAmount, LibraryConfig, QueryMsg, RemoteChainInfo,
+    Config, DenomSelection, FunctionMsgs, IbcTransferAmount, LibraryConfig, QueryMsg,
+    RemoteChainInfo,
 };
+use std::{collections::BTreeMap, marker::PhantomData};
+
 use cosmwasm_std::{
-    coin, to_json_binary, Addr, Api, BlockInfo, CustomMsg, CustomQuery, Empty, Storage, Uint128,
-    Uint64,
+    coin, from_json,
+    testing::{message_info, mock_env, MockApi, MockQuerier, MockStorage},
+    to_json_binary, Addr, Api, BlockInfo, Coin, ContractResult, CustomMsg, CustomQuery, Empty,
+    Event, OwnedDeps, Response, Storage, SubMsg, SystemResult, Uint128, Uint64, WasmQuery,
 };
 use cw_multi_test::{
     error::AnyResult, no_init, AppBuilder, AppResponse, ContractWrapper, CosmosRouter, Executor,
@@ -15,17 +20,23 @@ use neutron_sdk::{
     bindings::query::NeutronQuery, query::min_ibc_fee::MinIbcFeeResponse, sudo::msg::SudoMsg,
 };
 use serde::de::DeserializeOwned;
+use valence_account_utils::ica::{IcaInformation, IcaState, QueryMsg as IcaQueryMsg};
 use valence_generic_ibc_transfer_library::msg::LibraryConfigUpdate;
-use valence_ibc_utils::types::EurekaFee;
+use valence_ibc_utils::types::{EurekaFee, TransferDefaults};
 use valence_library_utils::{
     denoms::CheckedDenom,
-    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation},
+    events::{TransferEvent, TRANSFER_EVENT_TYPE},
+    execute_on_behalf_of,
+    gas_reserve::GasReserve,
+    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation, MigrateMsg},
+    stats::TransferStats,
     testing::{CustomLibraryTestSuiteBase, LibraryTestSuite, TestApp},
     LibraryAccountType,
 };
 
 const NTRN: &str = "untrn";
 const ATOM: &str = "uatom";
+const OSMO: &str = "uosmo";
 const ONE_HUNDRED: u128 = 100_000_000_u128;
 const ONE_MILLION: u128 = 1_000_000_000_000_u128;
 
@@ -65,7 +76,8 @@ impl IbcTransferTestSuite {
             crate::contract::execute,
             crate::contract::instantiate,
             crate::contract::query,
-        );
+        )
+        .with_migrate(crate::contract::migrate);
 
         let ibc_transfer_code_id = inner.app_mut().store_code(Box::new(ibc_transfer_code));
 
@@ -152,6 +164,15 @@ impl IbcTransferTestSuite {
             remote_chain_info: Some(new_config.remote_chain_info),
             denom_to_pfm_map: Some(new_config.denom_to_pfm_map),
             eureka_config: valence_library_utils::OptionUpdate::Set(new_config.eureka_config),
+            min_transfer_amount: valence_library_utils::OptionUpdate::Set(
+                new_config.min_transfer_amount,
+            ),
+            denom_selection: valence_library_utils::OptionUpdate::Set(new_config.denom_selection),
+            gas_reserve: valence_library_utils::OptionUpdate::Set(new_config.gas_reserve),
+            precondition: valence_library_utils::OptionUpdate::Set(new_config.precondition),
+            transfer_defaults: valence_library_utils::OptionUpdate::Set(
+                new_config.transfer_defaults,
+            ),
         };
         self.app_mut().execute_contract(
             owner,
@@ -162,6 +183,26 @@ impl IbcTransferTestSuite {
             &[],
         )
     }
+
+    fn migrate(
+        &mut self,
+        addr: Addr,
+        stored_version: &str,
+        config_update: Option<LibraryConfigUpdate>,
+    ) -> AnyResult<AppResponse> {
+        // Simulate a library deployed with another version of the code
+        cw2::set_contract_version(
+            self.app_mut().contract_storage_mut(&addr).as_mut(),
+            env!("CARGO_PKG_NAME"),
+            stored_version,
+        )
+        .unwrap();
+
+        let owner = self.owner().clone();
+        let code_id = self.ibc_transfer_code_id;
+        self.app_mut()
+            .migrate_contract(owner, addr, &MigrateMsg { config_update }, code_id)
+    }
 }
 
 impl LibraryTestSuite<Empty, NeutronQuery, NeutronKeeper> for IbcTransferTestSuite {
@@ -506,3 +547,930 @@ fn eureka_ibc_transfer_fails_no_eureka_config() {
     // Execute IBC transfer
     suite.execute_eureka_ibc_transfer(lib).unwrap();
 }
+
+// Minimum transfer amount tests
+
+#[test]
+#[should_panic(
+    expected = "Invalid IBC transfer config: min_transfer_amount cannot be larger than the fixed amount."
+)]
+fn instantiate_fails_for_min_transfer_amount_above_fixed_amount() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let mut cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FixedAmount(ONE_MILLION.into()),
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+    cfg.min_transfer_amount = Some((ONE_MILLION + 1).into());
+
+    // Instantiate IBC transfer contract
+    suite.ibc_transfer_init(&cfg);
+}
+
+#[test]
+fn ibc_transfer_skipped_below_min_transfer_amount() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    suite.init_balance(
+        &suite.input_addr().clone(),
+        vec![coin(ONE_HUNDRED, ATOM.to_string())],
+    );
+
+    let mut cfg = suite.ibc_transfer_config(
+        ATOM.to_string(),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+    cfg.min_transfer_amount = Some((ONE_HUNDRED + 1).into());
+
+    // Instantiate contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Execute IBC transfer: it should be a no-op
+    let res = suite.execute_ibc_transfer(lib).unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "method" && a.value == "ibc-transfer-skipped")));
+    // The reason is only reported once, by the skipped transfer event
+    assert!(!res
+        .events
+        .iter()
+        .any(|e| e.attributes.iter().any(|a| a.key == "reason")));
+
+    // Verify the input account balance is untouched
+    suite.assert_balance(suite.input_addr(), ONE_HUNDRED, ATOM);
+}
+
+#[test]
+#[should_panic(
+    expected = "Execution error: Insufficient balance to cover for IBC fees 'untrn' in sender account (required: 20000, available: 0)."
+)]
+fn ibc_transfer_proceeds_at_min_transfer_amount() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    suite.init_balance(
+        &suite.input_addr().clone(),
+        vec![coin(ONE_HUNDRED, ATOM.to_string())],
+    );
+
+    let mut cfg = suite.ibc_transfer_config(
+        ATOM.to_string(),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+    cfg.min_transfer_amount = Some(ONE_HUNDRED.into());
+
+    // Instantiate contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Execute IBC transfer: the guard lets it through, so it fails on the missing fee balance
+    suite.execute_ibc_transfer(lib).unwrap();
+}
+
+// Migration tests
+
+#[test]
+fn migrate_bumps_version_and_preserves_config() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FixedAmount(ONE_MILLION.into()),
+        "memo".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+
+    // Instantiate IBC transfer contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Migrate from a previous version without patching the config
+    suite.migrate(lib.clone(), "0.1.0", None).unwrap();
+
+    // Verify the stored version is bumped
+    let version = cw2::get_contract_version(suite.app().contract_storage(&lib).as_ref()).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // Verify owner and processor are preserved
+    let owner_res: Ownership<Addr> = suite.query_wasm(&lib, &QueryMsg::Ownership {});
+    assert_eq!(owner_res.owner, Some(suite.owner().clone()));
+    let processor_addr: Addr = suite.query_wasm(&lib, &QueryMsg::GetProcessor {});
+    assert_eq!(processor_addr, suite.processor().clone());
+
+    // Verify library config is preserved
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        lib_cfg,
+        Config::new(
+            suite.input_addr().clone(),
+            suite.output_addr().clone(),
+            CheckedDenom::Native(NTRN.into()),
+            IbcTransferAmount::FixedAmount(ONE_MILLION.into()),
+            "memo".to_string(),
+            cfg.remote_chain_info.clone()
+        )
+    );
+}
+
+#[test]
+fn migrate_patches_config_fields() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FixedAmount(ONE_MILLION.into()),
+        "memo".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+
+    // Instantiate IBC transfer contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Migrate and rotate the channel, leaving the other fields untouched
+    suite
+        .migrate(
+            lib.clone(),
+            "0.1.0",
+            Some(LibraryConfigUpdate {
+                input_addr: None,
+                output_addr: None,
+                amount: None,
+                denom: None,
+                memo: None,
+                remote_chain_info: Some(RemoteChainInfo::new(
+                    "channel-2".to_string(),
+                    Some(600u64.into()),
+                )),
+                denom_to_pfm_map: None,
+                eureka_config: valence_library_utils::OptionUpdate::None,
+                min_transfer_amount: valence_library_utils::OptionUpdate::None,
+                denom_selection: valence_library_utils::OptionUpdate::None,
+                gas_reserve: valence_library_utils::OptionUpdate::None,
+                precondition: valence_library_utils::OptionUpdate::None,
+                transfer_defaults: valence_library_utils::OptionUpdate::None,
+            }),
+        )
+        .unwrap();
+
+    // Verify library config
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        lib_cfg,
+        Config::new(
+            suite.input_addr().clone(),
+            suite.output_addr().clone(),
+            CheckedDenom::Native(NTRN.into()),
+            IbcTransferAmount::FixedAmount(ONE_MILLION.into()),
+            "memo".to_string(),
+            RemoteChainInfo::new("channel-2".to_string(), Some(600u64.into())),
+        )
+    );
+
+    // Verify the raw config is patched as well
+    let raw_cfg: LibraryConfig = suite.query_wasm(&lib, &QueryMsg::GetRawLibraryConfig {});
+    assert_eq!(raw_cfg.remote_chain_info.channel_id, "channel-2");
+}
+
+#[test]
+#[should_panic(expected = "Cannot migrate from newer version")]
+fn migrate_rejects_downgrade() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite.ibc_transfer_config(
+        NTRN.to_string(),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+
+    // Instantiate IBC transfer contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // The stored version is newer than the code
+    suite.migrate(lib, "99.0.0", None).unwrap();
+}
+
+// Transfer event tests
+
+fn transfer_event(events: &[Event]) -> Event {
+    let mut event = events
+        .iter()
+        .find(|event| event.ty == format!("wasm-{TRANSFER_EVENT_TYPE}"))
+        .unwrap()
+        .clone();
+    // Strip what the test app adds to the events emitted by contracts
+    event.ty = TRANSFER_EVENT_TYPE.to_string();
+    event
+        .attributes
+        .retain(|attr| attr.key != "_contract_address");
+    event
+}
+
+fn mock_neutron_deps(
+    input_addr: &Addr,
+    balances: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, MockQuerier<NeutronQuery>, NeutronQuery> {
+    let querier = MockQuerier::<NeutronQuery>::new(&[(input_addr.as_str(), balances)])
+        .with_custom_handler(|query| match query {
+            NeutronQuery::MinIbcFee {} => SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&MinIbcFeeResponse {
+                    min_fee: neutron_sdk::bindings::msg::IbcFee {
+                        recv_fee: vec![],
+                        ack_fee: vec![coin(10_000, NTRN)],
+                        timeout_fee: vec![coin(10_000, NTRN)],
+                    },
+                })
+                .unwrap(),
+            )),
+            _ => unimplemented!(),
+        });
+
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier,
+        custom_query_type: PhantomData,
+    }
+}
+
+#[test]
+fn ibc_transfer_emits_transfer_event() {
+    let api = MockApi::default();
+    let input_addr = api.addr_make("input_account");
+    let mut deps = mock_neutron_deps(
+        &input_addr,
+        &[coin(ONE_HUNDRED, ATOM), coin(ONE_HUNDRED, NTRN)],
+    );
+
+    let cfg = Config::new(
+        input_addr.clone(),
+        api.addr_make("output_account").to_string(),
+        CheckedDenom::Native(ATOM.into()),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+
+    let res = crate::contract::functions::process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&api.addr_make("processor"), &[]),
+        FunctionMsgs::IbcTransfer {},
+        cfg,
+    )
+    .unwrap();
+
+    assert_eq!(
+        res.events,
+        vec![Event::from(
+            TransferEvent::new("ibc-transfer", ATOM)
+                .with_input_balance(ONE_HUNDRED.into())
+                .with_amount(ONE_HUNDRED.into())
+                .with_channel_id("channel-1")
+        )]
+    );
+}
+
+#[test]
+fn skipped_ibc_transfer_emits_transfer_event() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    suite.init_balance(
+        &suite.input_addr().clone(),
+        vec![coin(ONE_HUNDRED, ATOM.to_string())],
+    );
+
+    let mut cfg = suite.ibc_transfer_config(
+        ATOM.to_string(),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+    cfg.min_transfer_amount = Some((ONE_HUNDRED + 1).into());
+
+    // Instantiate contract
+    let lib = suite.ibc_transfer_init(&cfg);
+
+    // Execute IBC transfer: it should be a no-op
+    let res = suite.execute_ibc_transfer(lib).unwrap();
+    assert_eq!(
+        transfer_event(&res.events),
+        Event::from(
+            TransferEvent::new("ibc-transfer", ATOM)
+                .with_input_balance(ONE_HUNDRED.into())
+                .skipped("balance below min_transfer_amount")
+        )
+    );
+}
+
+#[test]
+fn ibc_transfer_resolves_ica_remote_output() {
+    let api = MockApi::default();
+    let input_addr = api.addr_make("input_account");
+    let ica_library = api.addr_make("ica_library");
+    let mut deps = mock_neutron_deps(
+        &input_addr,
+        &[coin(ONE_HUNDRED, ATOM), coin(ONE_HUNDRED, NTRN)],
+    );
+    let queried_ica = ica_library.clone();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, msg }
+            if contract_addr.as_str() == queried_ica.as_str() =>
+        {
+            match from_json(msg).unwrap() {
+                IcaQueryMsg::IcaState {} => SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&IcaState::Created(IcaInformation {
+                        address: "noble1ica".to_string(),
+                        port_id: "icacontroller-ica_library".to_string(),
+                        controller_connection_id: "connection-0".to_string(),
+                    }))
+                    .unwrap(),
+                )),
+                _ => unimplemented!(),
+            }
+        }
+        _ => unimplemented!(),
+    });
+
+    let cfg = Config::new(
+        input_addr.clone(),
+        api.addr_make("output_account").to_string(),
+        CheckedDenom::Native(ATOM.into()),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    )
+    .with_output_ica_library(ica_library);
+
+    let res = crate::contract::functions::process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&api.addr_make("processor"), &[]),
+        FunctionMsgs::IbcTransfer {},
+        cfg,
+    )
+    .unwrap();
+
+    // The transfer is sent to the remote address of the ICA at execution time
+    let expected_transfer = valence_ibc_utils::neutron::ibc_send_message(
+        deps.as_mut(),
+        mock_env(),
+        "channel-1".to_string(),
+        &input_addr,
+        "noble1ica".to_string(),
+        &CheckedDenom::Native(ATOM.into()),
+        ONE_HUNDRED,
+        "".to_string(),
+        Some(600),
+        BTreeMap::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(
+            execute_on_behalf_of(vec![expected_transfer], &input_addr).unwrap()
+        )]
+    );
+}
+
+// Checks that the transfer of `remote_chain_info` is sent with a timeout of `expected_timeout` seconds
+fn assert_transfer_timeout(
+    remote_chain_info: RemoteChainInfo,
+    transfer_defaults: Option<TransferDefaults>,
+    expected_timeout: u64,
+) {
+    let api = MockApi::default();
+    let input_addr = api.addr_make("input_account");
+    let output_addr = api.addr_make("output_account").to_string();
+    let mut deps = mock_neutron_deps(
+        &input_addr,
+        &[coin(ONE_HUNDRED, ATOM), coin(ONE_HUNDRED, NTRN)],
+    );
+
+    let mut cfg = Config::new(
+        input_addr.clone(),
+        output_addr.clone(),
+        CheckedDenom::Native(ATOM.into()),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        remote_chain_info,
+    );
+    if let Some(transfer_defaults) = transfer_defaults {
+        cfg = cfg.with_transfer_defaults(transfer_defaults);
+    }
+
+    let res = crate::contract::functions::process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&api.addr_make("processor"), &[]),
+        FunctionMsgs::IbcTransfer {},
+        cfg,
+    )
+    .unwrap();
+
+    let expected_transfer = valence_ibc_utils::neutron::ibc_send_message(
+        deps.as_mut(),
+        mock_env(),
+        "channel-1".to_string(),
+        &input_addr,
+        output_addr,
+        &CheckedDenom::Native(ATOM.into()),
+        ONE_HUNDRED,
+        "".to_string(),
+        Some(expected_timeout),
+        BTreeMap::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(
+            execute_on_behalf_of(vec![expected_transfer], &input_addr).unwrap()
+        )]
+    );
+}
+
+#[test]
+fn ibc_transfer_without_timeout_uses_transfer_defaults() {
+    // The crate default applies without transfer defaults
+    assert_transfer_timeout(
+        RemoteChainInfo::new("channel-1".to_string(), None),
+        None,
+        valence_ibc_utils::types::DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS,
+    );
+    assert_transfer_timeout(
+        RemoteChainInfo::new("channel-1".to_string(), None),
+        Some(TransferDefaults::new(1_800)),
+        1_800,
+    );
+}
+
+#[test]
+fn ibc_transfer_timeout_overrides_transfer_defaults() {
+    assert_transfer_timeout(
+        RemoteChainInfo::new("channel-1".to_string(), Some(300u64.into())),
+        Some(TransferDefaults::new(1_800)),
+        300,
+    );
+}
+
+#[test]
+fn ibc_transfer_fails_if_output_ica_not_created() {
+    let api = MockApi::default();
+    let input_addr = api.addr_make("input_account");
+    let mut deps = mock_neutron_deps(
+        &input_addr,
+        &[coin(ONE_HUNDRED, ATOM), coin(ONE_HUNDRED, NTRN)],
+    );
+    deps.querier.update_wasm(|_| {
+        SystemResult::Ok(ContractResult::Ok(
+            to_json_binary(&IcaState::NotCreated).unwrap(),
+        ))
+    });
+
+    let cfg = Config::new(
+        input_addr.clone(),
+        api.addr_make("output_account").to_string(),
+        CheckedDenom::Native(ATOM.into()),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    )
+    .with_output_ica_library(api.addr_make("ica_library"));
+
+    let err = crate::contract::functions::process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&api.addr_make("processor"), &[]),
+        FunctionMsgs::IbcTransfer {},
+        cfg,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Execution error: Failed to resolve the remote address of ICA {}: Generic error: ICA not created",
+            api.addr_make("ica_library")
+        )
+    );
+}
+
+// Denom selection tests
+
+fn multi_denom_config(input_addr: &Addr, denom_selection: DenomSelection) -> Config {
+    Config::new(
+        input_addr.clone(),
+        MockApi::default().addr_make("output_account").to_string(),
+        CheckedDenom::Native(NTRN.into()),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    )
+    .with_denom_selection(denom_selection)
+}
+
+fn process_multi_denom_transfer(balances: &[Coin], denom_selection: DenomSelection) -> Response {
+    let api = MockApi::default();
+    let input_addr = api.addr_make("input_account");
+    let mut deps = mock_neutron_deps(&input_addr, balances);
+
+    crate::contract::functions::process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&api.addr_make("processor"), &[]),
+        FunctionMsgs::IbcTransfer {},
+        multi_denom_config(&input_addr, denom_selection),
+    )
+    .unwrap()
+}
+
+fn transferred_event(denom: &str, amount: u128) -> Event {
+    Event::from(
+        TransferEvent::new("ibc-transfer", denom)
+            .with_input_balance(amount.into())
+            .with_amount(amount.into())
+            .with_channel_id("channel-1"),
+    )
+}
+
+#[test]
+fn single_denom_selection_overrides_denom() {
+    let res = process_multi_denom_transfer(
+        &[coin(ONE_HUNDRED, ATOM), coin(ONE_HUNDRED, NTRN)],
+        DenomSelection::Single(ATOM.to_string()),
+    );
+
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.events, vec![transferred_event(ATOM, ONE_HUNDRED)]);
+}
+
+#[test]
+fn denom_selection_list_transfers_each_denom() {
+    let res = process_multi_denom_transfer(
+        &[
+            coin(ONE_HUNDRED, ATOM),
+            coin(2 * ONE_HUNDRED, OSMO),
+            coin(ONE_HUNDRED, NTRN),
+        ],
+        DenomSelection::List(vec![
+            ATOM.to_string(),
+            OSMO.to_string(),
+            "uusdc".to_string(),
+        ]),
+    );
+
+    // Both transfers are executed by the input account in a single message,
+    // the denom without balance is left out
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.events,
+        vec![
+            transferred_event(ATOM, ONE_HUNDRED),
+            transferred_event(OSMO, 2 * ONE_HUNDRED),
+        ]
+    );
+}
+
+#[test]
+fn denom_selection_list_transfers_in_denom_order() {
+    let res = process_multi_denom_transfer(
+        &[
+            coin(ONE_HUNDRED, ATOM),
+            coin(2 * ONE_HUNDRED, OSMO),
+            coin(ONE_HUNDRED, NTRN),
+        ],
+        DenomSelection::List(vec![OSMO.to_string(), ATOM.to_string()]),
+    );
+
+    // The transfers follow the order of the denoms, not the order of the list
+    assert_eq!(
+        res.events,
+        vec![
+            transferred_event(ATOM, ONE_HUNDRED),
+            transferred_event(OSMO, 2 * ONE_HUNDRED),
+        ]
+    );
+}
+
+#[test]
+fn denom_selection_all_excludes_fee_reserve_denom() {
+    let res = process_multi_denom_transfer(
+        &[
+            coin(ONE_HUNDRED, NTRN),
+            coin(ONE_HUNDRED, ATOM),
+            coin(2 * ONE_HUNDRED, OSMO),
+        ],
+        DenomSelection::All {
+            fee_reserve_denom: Some(NTRN.to_string()),
+        },
+    );
+
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.events,
+        vec![
+            transferred_event(ATOM, ONE_HUNDRED),
+            transferred_event(OSMO, 2 * ONE_HUNDRED),
+        ]
+    );
+}
+
+#[test]
+fn denom_selection_without_balances_is_skipped() {
+    let res = process_multi_denom_transfer(
+        &[coin(ONE_HUNDRED, NTRN)],
+        DenomSelection::All {
+            fee_reserve_denom: Some(NTRN.to_string()),
+        },
+    );
+
+    assert!(res.messages.is_empty());
+    assert!(res
+        .attributes
+        .iter()
+        .any(|a| a.key == "method" && a.value == "ibc-transfer-skipped"));
+}
+
+#[test]
+#[should_panic(expected = "Eureka transfers only support a single denom.")]
+fn eureka_transfer_rejects_multi_denom_selection() {
+    let api = MockApi::default();
+    let input_addr = api.addr_make("input_account");
+    let mut deps = mock_neutron_deps(&input_addr, &[coin(ONE_HUNDRED, ATOM)]);
+
+    crate::contract::functions::process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&api.addr_make("processor"), &[]),
+        FunctionMsgs::EurekaTransfer {
+            eureka_fee: EurekaFee {
+                coin: coin(1, ATOM),
+                receiver: "relayer".to_string(),
+                timeout_timestamp: 0,
+            },
+        },
+        multi_denom_config(
+            &input_addr,
+            DenomSelection::List(vec![ATOM.to_string(), OSMO.to_string()]),
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid IBC transfer config: denom_selection contains duplicate denom 'uatom'."
+)]
+fn instantiate_fails_for_duplicate_selected_denoms() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FullAmount,
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+        )
+        .with_denom_selection(DenomSelection::List(vec![
+            ATOM.to_string(),
+            ATOM.to_string(),
+        ]));
+
+    // Instantiate IBC transfer contract
+    suite.ibc_transfer_init(&cfg);
+}
+
+#[test]
+#[should_panic(expected = "Invalid IBC transfer config: denom_selection list cannot be empty.")]
+fn instantiate_fails_for_empty_denom_selection() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FullAmount,
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+        )
+        .with_denom_selection(DenomSelection::List(vec![]));
+
+    // Instantiate IBC transfer contract
+    suite.ibc_transfer_init(&cfg);
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid IBC transfer config: a fixed amount cannot be used with a multi-denom denom_selection."
+)]
+fn instantiate_fails_for_fixed_amount_with_multi_denom_selection() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FixedAmount(ONE_HUNDRED.into()),
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+        )
+        .with_denom_selection(DenomSelection::List(vec![
+            ATOM.to_string(),
+            OSMO.to_string(),
+        ]));
+
+    // Instantiate IBC transfer contract
+    suite.ibc_transfer_init(&cfg);
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid IBC transfer config: min_transfer_amount cannot be used with a multi-denom denom_selection."
+)]
+fn instantiate_fails_for_min_transfer_amount_with_multi_denom_selection() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let mut cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FullAmount,
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+        )
+        .with_denom_selection(DenomSelection::All {
+            fee_reserve_denom: Some(NTRN.to_string()),
+        });
+    cfg.min_transfer_amount = Some(ONE_HUNDRED.into());
+
+    // Instantiate IBC transfer contract
+    suite.ibc_transfer_init(&cfg);
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid IBC transfer config: gas_reserve cannot be used with denom_selection."
+)]
+fn instantiate_fails_for_gas_reserve_with_denom_selection() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FullAmount,
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+        )
+        .with_denom_selection(DenomSelection::Single(NTRN.to_string()))
+        .with_gas_reserve(GasReserve::new(NTRN, ONE_HUNDRED));
+
+    // Instantiate IBC transfer contract
+    suite.ibc_transfer_init(&cfg);
+}
+
+// Gas reserve tests
+
+const GAS_RESERVE: u128 = 1_000_000_u128;
+
+fn process_gas_reserve_transfer(balances: &[Coin], denom: &str) -> Response {
+    let api = MockApi::default();
+    let input_addr = api.addr_make("input_account");
+    let mut deps = mock_neutron_deps(&input_addr, balances);
+
+    let cfg = Config::new(
+        input_addr.clone(),
+        api.addr_make("output_account").to_string(),
+        CheckedDenom::Native(denom.into()),
+        IbcTransferAmount::FullAmount,
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    )
+    .with_gas_reserve(GasReserve::new(NTRN, GAS_RESERVE));
+
+    crate::contract::functions::process_function(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&api.addr_make("processor"), &[]),
+        FunctionMsgs::IbcTransfer {},
+        cfg,
+    )
+    .unwrap()
+}
+
+#[test]
+fn gas_reserve_is_kept_for_gas_denom() {
+    let res = process_gas_reserve_transfer(&[coin(ONE_HUNDRED, NTRN)], NTRN);
+
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.events,
+        vec![Event::from(
+            TransferEvent::new("ibc-transfer", NTRN)
+                .with_input_balance(ONE_HUNDRED.into())
+                .with_amount((ONE_HUNDRED - GAS_RESERVE).into())
+                .with_channel_id("channel-1")
+        )]
+    );
+}
+
+#[test]
+fn gas_reserve_is_ignored_for_non_gas_denom() {
+    let res =
+        process_gas_reserve_transfer(&[coin(ONE_HUNDRED, ATOM), coin(ONE_HUNDRED, NTRN)], ATOM);
+
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.events, vec![transferred_event(ATOM, ONE_HUNDRED)]);
+}
+
+#[test]
+fn ibc_transfer_skipped_when_balance_within_gas_reserve() {
+    let res = process_gas_reserve_transfer(&[coin(GAS_RESERVE, NTRN)], NTRN);
+
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.events,
+        vec![Event::from(
+            TransferEvent::new("ibc-transfer", NTRN)
+                .with_input_balance(GAS_RESERVE.into())
+                .skipped("balance within gas_reserve")
+        )]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid gas reserve: amount cannot be zero.")]
+fn instantiate_fails_for_zero_gas_reserve() {
+    let mut suite = IbcTransferTestSuite::default();
+
+    let cfg = suite
+        .ibc_transfer_config(
+            NTRN.to_string(),
+            IbcTransferAmount::FullAmount,
+            "".to_string(),
+            RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+        )
+        .with_gas_reserve(GasReserve::new(NTRN, Uint128::zero()));
+
+    // Instantiate IBC transfer contract
+    suite.ibc_transfer_init(&cfg);
+}
+
+#[test]
+fn ibc_transfers_accumulate_in_stats() {
+    let api = MockApi::default();
+    let input_addr = api.addr_make("input_account");
+    let mut deps = mock_neutron_deps(
+        &input_addr,
+        &[
+            coin(ONE_HUNDRED, ATOM),
+            coin(2 * ONE_HUNDRED, OSMO),
+            coin(ONE_HUNDRED, NTRN),
+        ],
+    );
+    let mut env = mock_env();
+
+    let atom_transfer = Config::new(
+        input_addr.clone(),
+        api.addr_make("output_account").to_string(),
+        CheckedDenom::Native(ATOM.into()),
+        IbcTransferAmount::FixedAmount(40u128.into()),
+        "".to_string(),
+        RemoteChainInfo::new("channel-1".to_string(), Some(600u64.into())),
+    );
+
+    for (height, cfg) in [
+        (100, atom_transfer.clone()),
+        (
+            105,
+            multi_denom_config(
+                &input_addr,
+                DenomSelection::List(vec![ATOM.to_string(), OSMO.to_string()]),
+            ),
+        ),
+        (110, atom_transfer),
+    ] {
+        env.block.height = height;
+        crate::contract::functions::process_function(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&api.addr_make("processor"), &[]),
+            FunctionMsgs::IbcTransfer {},
+            cfg,
+        )
+        .unwrap();
+    }
+
+    let stats: TransferStats =
+        from_json(crate::contract::query(deps.as_ref(), env, QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(
+        stats,
+        TransferStats {
+            total_amounts: BTreeMap::from([
+                (ATOM.to_string(), Uint128::new(40 + ONE_HUNDRED + 40)),
+                (OSMO.to_string(), Uint128::new(2 * ONE_HUNDRED)),
+            ]),
+            executions: 3,
+            last_execution_height: Some(110),
+        }
+    );
+}
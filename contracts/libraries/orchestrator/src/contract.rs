@@ -0,0 +1,255 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use valence_library_utils::{
+    error::LibraryError,
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg},
+};
+
+use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
+
+// version info for migration info
+const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg<LibraryConfig>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::instantiate(deps, CONTRACT_NAME, CONTRACT_VERSION, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::execute(
+        deps,
+        env,
+        info,
+        msg,
+        functions::process_function,
+        execute::update_config,
+    )
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
+    )
+}
+
+mod functions {
+    use cosmwasm_std::{
+        from_json, to_json_binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, WasmMsg,
+    };
+    use valence_authorization_utils::{
+        authorization::{
+            AtomicSubroutine, Authorization, AuthorizationMode, AuthorizationState, Subroutine,
+        },
+        msg::{PermissionlessMsg, ProcessorMessage},
+    };
+    use valence_library_utils::error::LibraryError;
+
+    use crate::msg::{CheckedStep, Config, FunctionMsgs, QueryMsg};
+
+    pub fn process_function(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: FunctionMsgs,
+        cfg: Config,
+    ) -> Result<Response, LibraryError> {
+        match msg {
+            FunctionMsgs::Run {} => run(deps.as_ref(), &cfg),
+        }
+    }
+
+    fn run(deps: Deps, cfg: &Config) -> Result<Response, LibraryError> {
+        let authorization = query_authorization(deps, cfg)?;
+        check_authorization(deps, cfg, &authorization)?;
+
+        // The processor executes the functions of an atomic subroutine in order, and reverts all of them if any fails
+        let messages = cfg
+            .steps
+            .iter()
+            .map(process_function_msg)
+            .collect::<Result<Vec<_>, _>>()?;
+        let send_msgs = valence_authorization_utils::msg::ExecuteMsg::PermissionlessAction(
+            PermissionlessMsg::SendMsgs {
+                label: cfg.authorization_label.clone(),
+                messages,
+                ttl: None,
+            },
+        );
+
+        Ok(Response::new()
+            .add_message(WasmMsg::Execute {
+                contract_addr: cfg.authorization_contract.to_string(),
+                msg: to_json_binary(&send_msgs)?,
+                funds: vec![],
+            })
+            .add_attribute("method", "run")
+            .add_attribute("authorization_label", cfg.authorization_label.clone())
+            .add_attribute("steps", cfg.steps.len().to_string()))
+    }
+
+    /// Authorization of the steps, looked up page by page in the authorization contract
+    fn query_authorization(deps: Deps, cfg: &Config) -> Result<Authorization, LibraryError> {
+        let mut start_after = None;
+        loop {
+            let authorizations: Vec<Authorization> = deps.querier.query_wasm_smart(
+                &cfg.authorization_contract,
+                &valence_authorization_utils::msg::QueryMsg::Authorizations {
+                    start_after: start_after.clone(),
+                    limit: None,
+                },
+            )?;
+
+            if let Some(authorization) = authorizations
+                .iter()
+                .find(|authorization| authorization.label == cfg.authorization_label)
+            {
+                return Ok(authorization.clone());
+            }
+
+            match authorizations.last() {
+                Some(last) => start_after = Some(last.label.clone()),
+                None => {
+                    return Err(LibraryError::ExecutionError(format!(
+                        "Authorization {} does not exist in the authorization contract {}",
+                        cfg.authorization_label, cfg.authorization_contract
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Checks that the authorization is enabled, permissionless and atomic, that its functions are the steps, in order,
+    /// and that the library of every step is approved on its accounts
+    fn check_authorization(
+        deps: Deps,
+        cfg: &Config,
+        authorization: &Authorization,
+    ) -> Result<(), LibraryError> {
+        let label = &authorization.label;
+        if authorization.state != AuthorizationState::Enabled {
+            return Err(LibraryError::ExecutionError(format!(
+                "Authorization {label} is disabled"
+            )));
+        }
+        if authorization.mode != AuthorizationMode::Permissionless {
+            return Err(LibraryError::ExecutionError(format!(
+                "Authorization {label} is not permissionless"
+            )));
+        }
+        let Subroutine::Atomic(AtomicSubroutine { functions, .. }) = &authorization.subroutine
+        else {
+            return Err(LibraryError::ExecutionError(format!(
+                "Authorization {label} is not atomic, so its steps would not be reverted together"
+            )));
+        };
+        if functions.len() != cfg.steps.len() {
+            return Err(LibraryError::ExecutionError(format!(
+                "Authorization {label} has {} functions but {} steps are configured",
+                functions.len(),
+                cfg.steps.len()
+            )));
+        }
+
+        for (index, (function, step)) in functions.iter().zip(&cfg.steps).enumerate() {
+            if function.contract_address.to_addr(deps.api)? != step.library {
+                return Err(LibraryError::ExecutionError(format!(
+                    "Library {} of step {index} is not the contract of function {index} of authorization {label}",
+                    step.library
+                )));
+            }
+
+            let approved: bool = deps
+                .querier
+                .query_wasm_smart(&step.library, &QueryMsg::IsApproved {})?;
+            if !approved {
+                return Err(LibraryError::ExecutionError(format!(
+                    "Library {} of step {index} is not approved on the accounts it executes messages from",
+                    step.library
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// ProcessFunction message of the library, wrapping the JSON of its function
+    fn process_function_msg(step: &CheckedStep) -> Result<ProcessorMessage, LibraryError> {
+        let process_function =
+            valence_library_utils::msg::ExecuteMsg::<serde_json::Value, Empty>::ProcessFunction(
+                from_json(&step.function)?,
+            );
+
+        Ok(ProcessorMessage::CosmwasmExecuteMsg {
+            msg: to_json_binary(&process_function)?,
+        })
+    }
+}
+
+mod execute {
+    use cosmwasm_std::{DepsMut, Env, MessageInfo};
+    use valence_library_utils::error::LibraryError;
+
+    use crate::msg::LibraryConfigUpdate;
+
+    pub fn update_config(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        new_config: LibraryConfigUpdate,
+    ) -> Result<(), LibraryError> {
+        new_config.update_config(deps)
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Ownership {} => {
+            to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
+        }
+        QueryMsg::GetProcessor {} => {
+            to_json_binary(&valence_library_base::get_processor(deps.storage)?)
+        }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        // the library doesn't execute messages from any account
+        QueryMsg::IsApproved {} => to_json_binary(&true),
+        QueryMsg::GetLibraryConfig {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&config)
+        }
+        QueryMsg::GetRawLibraryConfig {} => {
+            let raw_config: LibraryConfig =
+                valence_library_utils::raw_config::query_raw_library_config(deps.storage)?;
+            to_json_binary(&raw_config)
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod contract;
+pub mod msg;
+
+#[cfg(test)]
+mod tests;
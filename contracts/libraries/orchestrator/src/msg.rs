@@ -0,0 +1,179 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Api, Binary, Deps, DepsMut};
+use cw_ownable::cw_ownable_query;
+use valence_library_utils::{
+    error::LibraryError, msg::LibraryConfigValidation, LibraryAccountType,
+};
+use valence_macros::{valence_library_query, ValenceLibraryInterface};
+
+#[cw_serde]
+pub enum FunctionMsgs {
+    // Execute the function of every step, in order. If any of them fails, all of them are reverted
+    Run {},
+}
+
+#[valence_library_query]
+#[cw_ownable_query]
+#[cw_serde]
+#[derive(QueryResponses)]
+/// Enum representing the different query messages that can be sent.
+pub enum QueryMsg {}
+
+/// Function of a library executed as one step of the sequence
+#[cw_serde]
+pub struct Step {
+    // Library executing the function, which must be the contract of the function of the authorization at the same index
+    pub library: LibraryAccountType,
+    // JSON object of the function message of the library, e.g. {"sweep":{}}
+    pub function: Binary,
+}
+
+impl Step {
+    pub fn new(library: impl Into<LibraryAccountType>, function: Binary) -> Self {
+        Step {
+            library: library.into(),
+            function,
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(ValenceLibraryInterface)]
+pub struct LibraryConfig {
+    // Authorization contract of the program
+    pub authorization_contract: String,
+    // Label of the permissionless atomic authorization whose functions are the steps
+    pub authorization_label: String,
+    // Steps of the sequence, in execution order
+    pub steps: Vec<Step>,
+}
+
+impl LibraryConfig {
+    pub fn new(
+        authorization_contract: impl Into<String>,
+        authorization_label: impl Into<String>,
+        steps: Vec<Step>,
+    ) -> Self {
+        LibraryConfig {
+            authorization_contract: authorization_contract.into(),
+            authorization_label: authorization_label.into(),
+            steps,
+        }
+    }
+
+    fn do_validate(&self, api: &dyn Api) -> Result<(Addr, Vec<CheckedStep>), LibraryError> {
+        let authorization_contract = api.addr_validate(&self.authorization_contract)?;
+        validate_label(&self.authorization_label)?;
+        let steps = validate_steps(api, &self.steps)?;
+
+        Ok((authorization_contract, steps))
+    }
+}
+
+impl LibraryConfigValidation<Config> for LibraryConfig {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pre_validate(&self, api: &dyn Api) -> Result<(), LibraryError> {
+        self.do_validate(api)?;
+        Ok(())
+    }
+
+    fn validate(&self, deps: Deps) -> Result<Config, LibraryError> {
+        let (authorization_contract, steps) = self.do_validate(deps.api)?;
+
+        Ok(Config {
+            authorization_contract,
+            authorization_label: self.authorization_label.clone(),
+            steps,
+        })
+    }
+}
+
+impl LibraryConfigUpdate {
+    pub fn update_config(self, deps: DepsMut) -> Result<(), LibraryError> {
+        let mut config: Config = valence_library_base::load_config(deps.storage)?;
+
+        if let Some(authorization_contract) = self.authorization_contract {
+            config.authorization_contract = deps.api.addr_validate(&authorization_contract)?;
+        }
+
+        if let Some(authorization_label) = self.authorization_label {
+            validate_label(&authorization_label)?;
+            config.authorization_label = authorization_label;
+        }
+
+        if let Some(steps) = self.steps {
+            config.steps = validate_steps(deps.api, &steps)?;
+        }
+
+        valence_library_base::save_config(deps.storage, &config)?;
+        Ok(())
+    }
+}
+
+#[cw_serde]
+pub struct CheckedStep {
+    pub library: Addr,
+    pub function: Binary,
+}
+
+#[cw_serde]
+pub struct Config {
+    pub authorization_contract: Addr,
+    pub authorization_label: String,
+    pub steps: Vec<CheckedStep>,
+}
+
+impl Config {
+    pub fn new(
+        authorization_contract: Addr,
+        authorization_label: String,
+        steps: Vec<CheckedStep>,
+    ) -> Self {
+        Config {
+            authorization_contract,
+            authorization_label,
+            steps,
+        }
+    }
+}
+
+fn validate_label(authorization_label: &str) -> Result<(), LibraryError> {
+    if authorization_label.is_empty() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid orchestrator config: authorization_label cannot be empty.".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that there is at least one step, and that the function of every step is a JSON object
+fn validate_steps(api: &dyn Api, steps: &[Step]) -> Result<Vec<CheckedStep>, LibraryError> {
+    if steps.is_empty() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid orchestrator config: at least one step must be configured.".to_string(),
+        ));
+    }
+
+    steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| {
+            let library = step.library.to_addr(api)?;
+            if !is_json_object(&step.function) {
+                return Err(LibraryError::ConfigurationError(format!(
+                    "Invalid orchestrator config: function of step {index} is not a JSON object."
+                )));
+            }
+
+            Ok(CheckedStep {
+                library,
+                function: step.function.clone(),
+            })
+        })
+        .collect()
+}
+
+fn is_json_object(function: &Binary) -> bool {
+    serde_json::from_slice::<serde_json::Value>(function).is_ok_and(|value| value.is_object())
+}
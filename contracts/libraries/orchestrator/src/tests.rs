@@ -0,0 +1,556 @@
+use cosmwasm_std::{
+    coin, to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError,
+    StdResult, WasmMsg,
+};
+use cw_multi_test::{error::AnyResult, App, AppResponse, ContractWrapper, Executor};
+use cw_storage_plus::Item;
+use cw_utils::Expiration;
+use valence_authorization_utils::{
+    authorization::{
+        Authorization, AuthorizationMode, AuthorizationState, NonAtomicSubroutine, Priority,
+        Subroutine,
+    },
+    authorization_message::{Message, MessageDetails, MessageType},
+    builders::{AtomicFunctionBuilder, AtomicSubroutineBuilder, NonAtomicFunctionBuilder},
+    msg::PermissionlessMsg,
+};
+use valence_library_utils::{
+    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation},
+    testing::{LibraryTestSuite, LibraryTestSuiteBase},
+    LibraryAccountType,
+};
+
+use crate::msg::{CheckedStep, Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, Step};
+
+const NTRN: &str = "untrn";
+const AIRDROP: &str = "factory/airdrop/token";
+const LABEL: &str = "rebalance";
+
+const MOCK_AUTHORIZATIONS: Item<Vec<Authorization>> = Item::new("mock_authorizations");
+
+// Minimal authorization contract mock, which also plays the processor: the messages of a SendMsgs are executed right away,
+// in order, and all of them are reverted if any fails, like the processor does for an atomic subroutine
+fn mock_authorization_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: Vec<Authorization>,
+) -> StdResult<Response> {
+    MOCK_AUTHORIZATIONS.save(deps.storage, &msg)?;
+    Ok(Response::new())
+}
+
+fn mock_authorization_execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: valence_authorization_utils::msg::ExecuteMsg,
+) -> StdResult<Response> {
+    let valence_authorization_utils::msg::ExecuteMsg::PermissionlessAction(
+        PermissionlessMsg::SendMsgs {
+            label, messages, ..
+        },
+    ) = msg
+    else {
+        return Err(StdError::generic_err("Unsupported message"));
+    };
+    let authorization = MOCK_AUTHORIZATIONS
+        .load(deps.storage)?
+        .into_iter()
+        .find(|authorization| authorization.label == label)
+        .ok_or_else(|| StdError::generic_err("Unknown authorization"))?;
+
+    let msgs = messages
+        .iter()
+        .enumerate()
+        .map(|(index, message)| WasmMsg::Execute {
+            contract_addr: authorization
+                .subroutine
+                .get_contract_address_by_function_index(index),
+            msg: message.get_msg().clone(),
+            funds: vec![],
+        });
+    Ok(Response::new().add_messages(msgs))
+}
+
+// Returns a single authorization per page, so that the orchestrator has to paginate
+fn mock_authorization_query(
+    deps: Deps,
+    _env: Env,
+    msg: valence_authorization_utils::msg::QueryMsg,
+) -> StdResult<Binary> {
+    let valence_authorization_utils::msg::QueryMsg::Authorizations { start_after, .. } = msg else {
+        return Err(StdError::generic_err("Unsupported query"));
+    };
+    let authorizations: Vec<Authorization> = MOCK_AUTHORIZATIONS
+        .load(deps.storage)?
+        .into_iter()
+        .filter(|authorization| match &start_after {
+            Some(start_after) => authorization.label > *start_after,
+            None => true,
+        })
+        .take(1)
+        .collect();
+    to_json_binary(&authorizations)
+}
+
+struct OrchestratorTestSuite {
+    inner: LibraryTestSuiteBase,
+    orchestrator_code_id: u64,
+    sweeper_code_id: u64,
+    mock_authorization_code_id: u64,
+    // Address the mock authorization is instantiated at, which is the processor of the sweepers
+    authorization_addr: Addr,
+    input_addr: Addr,
+    mid_addr: Addr,
+    output_addr: Addr,
+}
+
+impl Default for OrchestratorTestSuite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrchestratorTestSuite {
+    pub fn new() -> Self {
+        let mut inner = LibraryTestSuiteBase::new();
+
+        let orchestrator_code = ContractWrapper::new(
+            crate::contract::execute,
+            crate::contract::instantiate,
+            crate::contract::query,
+        );
+        let orchestrator_code_id = inner.app_mut().store_code(Box::new(orchestrator_code));
+
+        let sweeper_code = ContractWrapper::new(
+            valence_sweeper_library::contract::execute,
+            valence_sweeper_library::contract::instantiate,
+            valence_sweeper_library::contract::query,
+        );
+        let sweeper_code_id = inner.app_mut().store_code(Box::new(sweeper_code));
+
+        let mock_authorization_code = ContractWrapper::new(
+            mock_authorization_execute,
+            mock_authorization_instantiate,
+            mock_authorization_query,
+        );
+        let mock_authorization_code_id = inner
+            .app_mut()
+            .store_code(Box::new(mock_authorization_code));
+
+        let authorization_addr =
+            inner.get_contract_addr(mock_authorization_code_id, "mock_authorization");
+        let input_addr = inner.account_init("input_account", vec![]);
+        let mid_addr = inner.account_init("mid_account", vec![]);
+        let output_addr = inner.account_init("output_account", vec![]);
+
+        Self {
+            inner,
+            orchestrator_code_id,
+            sweeper_code_id,
+            mock_authorization_code_id,
+            authorization_addr,
+            input_addr,
+            mid_addr,
+            output_addr,
+        }
+    }
+
+    // Instantiates a sweeper of the non NTRN denoms from `input_addr` to `output_addr`, processed by the mock authorization
+    fn sweeper_init(&mut self, input_addr: &Addr, output_addr: &Addr) -> Addr {
+        let init_msg = InstantiateMsg {
+            owner: self.owner().to_string(),
+            processor: self.authorization_addr.to_string(),
+            config: valence_sweeper_library::msg::LibraryConfig::new(
+                input_addr,
+                output_addr,
+                vec![NTRN.to_string()],
+            ),
+        };
+        self.contract_init(self.sweeper_code_id, "sweeper", &init_msg, &[])
+    }
+
+    fn mock_authorization_init(&mut self, authorizations: Vec<Authorization>) {
+        let addr = self.contract_init2(
+            self.mock_authorization_code_id,
+            "mock_authorization",
+            &authorizations,
+            &[],
+        );
+        assert_eq!(addr, self.authorization_addr);
+    }
+
+    fn orchestrator_init(&mut self, steps: Vec<Step>) -> Addr {
+        let init_msg = InstantiateMsg {
+            owner: self.owner().to_string(),
+            processor: self.processor().to_string(),
+            config: LibraryConfig::new(self.authorization_addr.to_string(), LABEL, steps),
+        };
+        self.contract_init(self.orchestrator_code_id, "orchestrator", &init_msg, &[])
+    }
+
+    fn execute_run(&mut self, addr: Addr) -> AnyResult<AppResponse> {
+        self.contract_execute(
+            addr,
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::ProcessFunction(FunctionMsgs::Run {}),
+        )
+    }
+}
+
+impl LibraryTestSuite<Empty, Empty> for OrchestratorTestSuite {
+    fn app(&self) -> &App {
+        self.inner.app()
+    }
+
+    fn app_mut(&mut self) -> &mut App {
+        self.inner.app_mut()
+    }
+
+    fn owner(&self) -> &Addr {
+        self.inner.owner()
+    }
+
+    fn processor(&self) -> &Addr {
+        self.inner.processor()
+    }
+
+    fn account_code_id(&self) -> u64 {
+        self.inner.account_code_id()
+    }
+
+    fn cw20_code_id(&self) -> u64 {
+        self.inner.cw20_code_id()
+    }
+}
+
+fn sweep_function() -> Binary {
+    to_json_binary(&valence_sweeper_library::msg::FunctionMsgs::Sweep {}).unwrap()
+}
+
+fn process_function_details() -> MessageDetails {
+    MessageDetails {
+        message_type: MessageType::CosmwasmExecuteMsg,
+        message: Message {
+            name: "process_function".to_string(),
+            params_restrictions: None,
+        },
+    }
+}
+
+fn authorization(label: &str, subroutine: Subroutine) -> Authorization {
+    Authorization {
+        label: label.to_string(),
+        mode: AuthorizationMode::Permissionless,
+        not_before: Expiration::Never {},
+        expiration: Expiration::Never {},
+        max_concurrent_executions: 1,
+        subroutine,
+        priority: Priority::Medium,
+        state: AuthorizationState::Enabled,
+    }
+}
+
+// Permissionless atomic authorization executing the function of every library, in order
+fn atomic_authorization(label: &str, libraries: &[&Addr]) -> Authorization {
+    let subroutine = libraries
+        .iter()
+        .fold(AtomicSubroutineBuilder::new(), |builder, library| {
+            builder.with_function(
+                AtomicFunctionBuilder::new()
+                    .with_message_details(process_function_details())
+                    .with_contract_address(LibraryAccountType::Addr(library.to_string()))
+                    .build(),
+            )
+        })
+        .build();
+
+    authorization(label, subroutine)
+}
+
+#[test]
+fn instantiate_with_valid_config() {
+    let mut suite = OrchestratorTestSuite::default();
+    let (input_addr, mid_addr) = (suite.input_addr.clone(), suite.mid_addr.clone());
+    let sweeper = suite.sweeper_init(&input_addr, &mid_addr);
+
+    let lib = suite.orchestrator_init(vec![Step::new(&sweeper, sweep_function())]);
+
+    let config: Config = suite.query_wasm(&lib, &crate::msg::QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        config,
+        Config::new(
+            suite.authorization_addr.clone(),
+            LABEL.to_string(),
+            vec![CheckedStep {
+                library: sweeper,
+                function: sweep_function(),
+            }]
+        )
+    );
+}
+
+#[test]
+fn pre_validate_config_rejects_invalid_steps() {
+    let suite = OrchestratorTestSuite::default();
+    let library = suite.api().addr_make("library");
+    let authorization_contract = suite.authorization_addr.to_string();
+
+    let err = LibraryConfig::new(&authorization_contract, LABEL, vec![])
+        .pre_validate(suite.api())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Invalid orchestrator config: at least one step must be configured."
+    );
+
+    let err = LibraryConfig::new(
+        &authorization_contract,
+        "",
+        vec![Step::new(&library, sweep_function())],
+    )
+    .pre_validate(suite.api())
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Invalid orchestrator config: authorization_label cannot be empty."
+    );
+
+    for function in [b"not json".to_vec(), b"\"sweep\"".to_vec()] {
+        let err = LibraryConfig::new(
+            &authorization_contract,
+            LABEL,
+            vec![
+                Step::new(&library, sweep_function()),
+                Step::new(&library, Binary::from(function)),
+            ],
+        )
+        .pre_validate(suite.api())
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: Invalid orchestrator config: function of step 1 is not a JSON object."
+        );
+    }
+}
+
+#[test]
+fn run_executes_steps_in_order() {
+    let mut suite = OrchestratorTestSuite::default();
+    let (input_addr, mid_addr, output_addr) = (
+        suite.input_addr.clone(),
+        suite.mid_addr.clone(),
+        suite.output_addr.clone(),
+    );
+
+    // The second sweep can only move the funds once the first one delivered them
+    let first = suite.sweeper_init(&input_addr, &mid_addr);
+    let second = suite.sweeper_init(&mid_addr, &output_addr);
+    suite
+        .account_approve_library(input_addr.clone(), first.to_string())
+        .unwrap();
+    suite
+        .account_approve_library(mid_addr.clone(), second.to_string())
+        .unwrap();
+    suite.init_balance(&input_addr, vec![coin(1_000, NTRN), coin(500, AIRDROP)]);
+
+    // The authorization of the steps is on the second page of the authorizations
+    suite.mock_authorization_init(vec![
+        atomic_authorization("enter_position", &[&first]),
+        atomic_authorization(LABEL, &[&first, &second]),
+    ]);
+    let lib = suite.orchestrator_init(vec![
+        Step::new(&first, sweep_function()),
+        Step::new(&second, sweep_function()),
+    ]);
+
+    suite.execute_run(lib).unwrap();
+
+    suite.assert_balance(&input_addr, 1_000, NTRN);
+    suite.assert_balance(&input_addr, 0, AIRDROP);
+    suite.assert_balance(&mid_addr, 0, AIRDROP);
+    suite.assert_balance(&output_addr, 500, AIRDROP);
+}
+
+#[test]
+fn run_rolls_back_on_mid_sequence_failure() {
+    let mut suite = OrchestratorTestSuite::default();
+    let (input_addr, mid_addr, output_addr) = (
+        suite.input_addr.clone(),
+        suite.mid_addr.clone(),
+        suite.output_addr.clone(),
+    );
+
+    // The function of the second step is not a function of the sweeper, so it fails
+    let first = suite.sweeper_init(&input_addr, &mid_addr);
+    let second = suite.sweeper_init(&mid_addr, &output_addr);
+    suite
+        .account_approve_library(input_addr.clone(), first.to_string())
+        .unwrap();
+    suite
+        .account_approve_library(mid_addr.clone(), second.to_string())
+        .unwrap();
+    suite.init_balance(&input_addr, vec![coin(500, AIRDROP)]);
+
+    suite.mock_authorization_init(vec![atomic_authorization(LABEL, &[&first, &second])]);
+    let lib = suite.orchestrator_init(vec![
+        Step::new(&first, sweep_function()),
+        Step::new(&second, Binary::from(br#"{"unknown":{}}"#.as_slice())),
+    ]);
+
+    suite.execute_run(lib).unwrap_err();
+
+    // The first sweep is reverted along with the second one
+    suite.assert_balance(&input_addr, 500, AIRDROP);
+    suite.assert_balance(&mid_addr, 0, AIRDROP);
+    suite.assert_balance(&output_addr, 0, AIRDROP);
+}
+
+#[test]
+fn run_rejects_missing_authorization() {
+    let mut suite = OrchestratorTestSuite::default();
+    let (input_addr, mid_addr) = (suite.input_addr.clone(), suite.mid_addr.clone());
+    let sweeper = suite.sweeper_init(&input_addr, &mid_addr);
+
+    suite.mock_authorization_init(vec![atomic_authorization("enter_position", &[&sweeper])]);
+    let lib = suite.orchestrator_init(vec![Step::new(&sweeper, sweep_function())]);
+
+    let err = suite.execute_run(lib).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Execution error: Authorization {LABEL} does not exist in the authorization contract {}",
+            suite.authorization_addr
+        )
+    );
+}
+
+#[test]
+fn run_rejects_authorization_not_matching_steps() {
+    let mut suite = OrchestratorTestSuite::default();
+    let (input_addr, mid_addr, output_addr) = (
+        suite.input_addr.clone(),
+        suite.mid_addr.clone(),
+        suite.output_addr.clone(),
+    );
+    let first = suite.sweeper_init(&input_addr, &mid_addr);
+    let second = suite.sweeper_init(&mid_addr, &output_addr);
+
+    // The authorization executes the libraries in the opposite order of the steps
+    suite.mock_authorization_init(vec![atomic_authorization(LABEL, &[&second, &first])]);
+    let lib = suite.orchestrator_init(vec![
+        Step::new(&first, sweep_function()),
+        Step::new(&second, sweep_function()),
+    ]);
+
+    let err = suite.execute_run(lib.clone()).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Execution error: Library {first} of step 0 is not the contract of function 0 of authorization {LABEL}"
+        )
+    );
+
+    let owner = suite.owner().clone();
+    suite
+        .app_mut()
+        .execute_contract(
+            owner,
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::UpdateConfig {
+                new_config: LibraryConfigUpdate {
+                    steps: Some(vec![Step::new(&second, sweep_function())]),
+                    ..Default::default()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    let err = suite.execute_run(lib).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Execution error: Authorization {LABEL} has 2 functions but 1 steps are configured"
+        )
+    );
+}
+
+#[test]
+fn run_rejects_library_not_approved() {
+    let mut suite = OrchestratorTestSuite::default();
+    let (input_addr, mid_addr) = (suite.input_addr.clone(), suite.mid_addr.clone());
+    let sweeper = suite.sweeper_init(&input_addr, &mid_addr);
+    suite.init_balance(&input_addr, vec![coin(500, AIRDROP)]);
+
+    suite.mock_authorization_init(vec![atomic_authorization(LABEL, &[&sweeper])]);
+    let lib = suite.orchestrator_init(vec![Step::new(&sweeper, sweep_function())]);
+
+    let err = suite.execute_run(lib).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Execution error: Library {sweeper} of step 0 is not approved on the accounts it executes messages from"
+        )
+    );
+    suite.assert_balance(&input_addr, 500, AIRDROP);
+}
+
+#[test]
+fn run_rejects_non_atomic_authorization() {
+    let mut suite = OrchestratorTestSuite::default();
+    let (input_addr, mid_addr) = (suite.input_addr.clone(), suite.mid_addr.clone());
+    let sweeper = suite.sweeper_init(&input_addr, &mid_addr);
+    suite
+        .account_approve_library(input_addr.clone(), sweeper.to_string())
+        .unwrap();
+    suite.init_balance(&input_addr, vec![coin(500, AIRDROP)]);
+
+    let subroutine = Subroutine::NonAtomic(NonAtomicSubroutine {
+        functions: vec![NonAtomicFunctionBuilder::new()
+            .with_message_details(process_function_details())
+            .with_contract_address(LibraryAccountType::Addr(sweeper.to_string()))
+            .build()],
+        expiration_time: None,
+    });
+    suite.mock_authorization_init(vec![authorization(LABEL, subroutine)]);
+    let lib = suite.orchestrator_init(vec![Step::new(&sweeper, sweep_function())]);
+
+    let err = suite.execute_run(lib).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Execution error: Authorization {LABEL} is not atomic, so its steps would not be reverted together"
+        )
+    );
+    suite.assert_balance(&input_addr, 500, AIRDROP);
+}
+
+#[test]
+fn update_config_validates_steps() {
+    let mut suite = OrchestratorTestSuite::default();
+    let (input_addr, mid_addr) = (suite.input_addr.clone(), suite.mid_addr.clone());
+    let sweeper = suite.sweeper_init(&input_addr, &mid_addr);
+
+    let lib = suite.orchestrator_init(vec![Step::new(&sweeper, sweep_function())]);
+
+    let owner = suite.owner().clone();
+    let err = suite
+        .app_mut()
+        .execute_contract(
+            owner,
+            lib,
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::UpdateConfig {
+                new_config: LibraryConfigUpdate {
+                    steps: Some(vec![]),
+                    ..Default::default()
+                },
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Configuration error: Invalid orchestrator config: at least one step must be configured."
+    );
+}
@@ -33,6 +33,16 @@ pub enum FunctionMsgs {
 pub enum QueryMsg {
     #[returns(Addr)]
     GetProcessor {},
+    #[returns(bool)]
+    IsPaused {},
+    #[returns(valence_library_utils::msg::LastErrorResponse)]
+    GetLastError {},
+    #[returns(bool)]
+    IsApproved {},
+    #[returns(Addr)]
+    ResolveAccount {
+        account: valence_library_utils::LibraryAccountType,
+    },
     #[returns(Config)]
     GetLibraryConfig {},
 }
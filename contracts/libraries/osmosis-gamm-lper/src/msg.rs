@@ -63,6 +63,7 @@ impl LibraryConfig {
     ) -> Result<(Addr, Addr, Uint64), LibraryError> {
         let input_addr = self.input_addr.to_addr(api)?;
         let output_addr = self.output_addr.to_addr(api)?;
+        self.lp_config.asset_data.ensure_two_assets()?;
 
         Ok((input_addr, output_addr, self.lp_config.pool_id.into()))
     }
@@ -129,6 +130,7 @@ impl LibraryConfigUpdate {
         }
 
         if let Some(cfg) = self.lp_config {
+            cfg.asset_data.ensure_two_assets()?;
             config.lp_config = cfg;
         }
 
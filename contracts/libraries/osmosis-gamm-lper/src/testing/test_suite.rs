@@ -65,6 +65,7 @@ impl LPerTestSuite {
                     asset_data: AssetData {
                         asset1: inner.pool_cfg.pool_asset1.to_string(),
                         asset2: inner.pool_cfg.pool_asset2.to_string(),
+                        additional_assets: vec![],
                     },
                 }),
             ),
@@ -19,6 +19,7 @@ fn test_provide_liquidity_fails_validation() {
             asset_data: AssetData {
                 asset1: OSMO_DENOM.to_string(),
                 asset2: "random_denom".to_string(),
+                additional_assets: vec![],
             },
         }),
     );
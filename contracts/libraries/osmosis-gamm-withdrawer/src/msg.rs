@@ -58,6 +58,7 @@ impl LibraryConfig {
     ) -> Result<(Addr, Addr, Uint64), LibraryError> {
         let input_addr = self.input_addr.to_addr(api)?;
         let output_addr = self.output_addr.to_addr(api)?;
+        self.lw_config.asset_data.ensure_two_assets()?;
 
         Ok((input_addr, output_addr, self.lw_config.pool_id.into()))
     }
@@ -105,6 +106,7 @@ impl LibraryConfigUpdate {
         }
 
         if let Some(cfg) = self.lw_config {
+            cfg.asset_data.ensure_two_assets()?;
             config.lw_config = cfg;
         }
 
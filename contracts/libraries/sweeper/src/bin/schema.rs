@@ -0,0 +1,13 @@
+use cosmwasm_schema::write_api;
+
+use valence_library_utils::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg};
+use valence_sweeper_library::msg::{FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
+
+fn main() {
+    write_api! {
+        instantiate: InstantiateMsg<LibraryConfig>,
+        execute: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
+        migrate: MigrateMsg<LibraryConfigUpdate>,
+        query: QueryMsg,
+    }
+}
@@ -0,0 +1,180 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult,
+};
+use valence_library_utils::{
+    balance_snapshot::{reply_balance_snapshots, BALANCE_SNAPSHOT_REPLY_ID},
+    error::LibraryError,
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg},
+};
+
+use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
+
+// version info for migration info
+const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg<LibraryConfig>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::instantiate(deps, CONTRACT_NAME, CONTRACT_VERSION, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg<FunctionMsgs, LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::execute(
+        deps,
+        env,
+        info,
+        msg,
+        functions::process_function,
+        execute::update_config,
+    )
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg<LibraryConfigUpdate>,
+) -> Result<Response, LibraryError> {
+    valence_library_base::migrate(
+        deps,
+        env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        msg,
+        execute::update_config,
+    )
+}
+
+mod functions {
+    use cosmwasm_std::{BankMsg, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response};
+    use valence_library_utils::{
+        balance_snapshot::with_balance_snapshots, error::LibraryError, execute_on_behalf_of,
+    };
+
+    use crate::msg::{Config, FunctionMsgs};
+
+    pub fn process_function(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: FunctionMsgs,
+        cfg: Config,
+    ) -> Result<Response, LibraryError> {
+        match msg {
+            FunctionMsgs::Sweep {} => sweep(deps, &cfg),
+        }
+    }
+
+    fn sweep(deps: DepsMut, cfg: &Config) -> Result<Response, LibraryError> {
+        // Only native denoms can be discovered, cw20 balances are not known to the input account
+        #[allow(deprecated)]
+        let balances = deps.querier.query_all_balances(&cfg.input_addr)?;
+
+        let unexpected: Vec<Coin> = balances
+            .into_iter()
+            .filter(|coin| !coin.amount.is_zero() && !cfg.expected_denoms.contains(&coin.denom))
+            .collect();
+
+        let swept = unexpected
+            .iter()
+            .map(|coin| coin.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let mut response = Response::new()
+            .add_attribute("method", "sweep")
+            .add_attribute("swept", swept);
+
+        // Nothing is sent when the input account only holds expected denoms
+        if !unexpected.is_empty() {
+            let bank_msg = CosmosMsg::Bank(BankMsg::Send {
+                to_address: cfg.output_addr.to_string(),
+                amount: unexpected,
+            });
+            let input_account_msg = execute_on_behalf_of(vec![bank_msg], &cfg.input_addr)?;
+            response = response.add_message(input_account_msg);
+        }
+
+        if !cfg.snapshot_balances {
+            return Ok(response);
+        }
+        with_balance_snapshots(
+            &deps.querier,
+            response,
+            &[&cfg.input_addr, &cfg.output_addr],
+        )
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, LibraryError> {
+    match msg.id {
+        BALANCE_SNAPSHOT_REPLY_ID => reply_balance_snapshots(&deps.querier, msg),
+        _ => Err(LibraryError::Std(StdError::generic_err("unknown reply id"))),
+    }
+}
+
+mod execute {
+    use cosmwasm_std::{DepsMut, Env, MessageInfo};
+    use valence_library_utils::error::LibraryError;
+
+    use crate::msg::LibraryConfigUpdate;
+
+    pub fn update_config(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        new_config: LibraryConfigUpdate,
+    ) -> Result<(), LibraryError> {
+        new_config.update_config(deps)
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Ownership {} => {
+            to_json_binary(&valence_library_base::get_ownership(deps.storage)?)
+        }
+        QueryMsg::GetProcessor {} => {
+            to_json_binary(&valence_library_base::get_processor(deps.storage)?)
+        }
+        QueryMsg::IsPaused {} => to_json_binary(&valence_library_base::is_paused(deps.storage)?),
+        QueryMsg::GetLastError {} => {
+            to_json_binary(&valence_library_base::get_last_error(deps.storage)?)
+        }
+        QueryMsg::ResolveAccount { account } => {
+            to_json_binary(&valence_library_base::resolve_account(deps, &account)?)
+        }
+        QueryMsg::IsApproved {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&valence_library_utils::is_library_approved(
+                &deps.querier,
+                &config.input_addr,
+                &env.contract.address,
+            )?)
+        }
+        QueryMsg::GetLibraryConfig {} => {
+            let config: Config = valence_library_base::load_config(deps.storage)?;
+            to_json_binary(&config)
+        }
+        QueryMsg::GetRawLibraryConfig {} => {
+            let raw_config: LibraryConfig =
+                valence_library_utils::raw_config::query_raw_library_config(deps.storage)?;
+            to_json_binary(&raw_config)
+        }
+    }
+}
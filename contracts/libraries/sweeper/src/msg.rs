@@ -0,0 +1,167 @@
+use std::collections::BTreeSet;
+
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Api, Deps, DepsMut};
+use cw_ownable::cw_ownable_query;
+use valence_library_utils::{
+    error::LibraryError, msg::LibraryConfigValidation, LibraryAccountType,
+};
+use valence_macros::{valence_library_query, ValenceLibraryInterface};
+
+#[cw_serde]
+pub enum FunctionMsgs {
+    // Send every nonzero balance of the input account whose denom is not expected to the output account
+    Sweep {},
+}
+
+#[valence_library_query]
+#[cw_ownable_query]
+#[cw_serde]
+#[derive(QueryResponses)]
+/// Enum representing the different query messages that can be sent.
+pub enum QueryMsg {}
+
+#[cw_serde]
+#[derive(ValenceLibraryInterface)]
+pub struct LibraryConfig {
+    // Account from which the unexpected denoms are swept
+    pub input_addr: LibraryAccountType,
+    // Account to which the unexpected denoms are sent
+    pub output_addr: LibraryAccountType,
+    // Denoms that belong on the input account, and are never swept
+    pub expected_denoms: Vec<String>,
+    // Whether the balances of the input and output accounts are emitted before and after every sweep, off by default
+    #[serde(default)]
+    pub snapshot_balances: bool,
+}
+
+impl LibraryConfig {
+    pub fn new(
+        input_addr: impl Into<LibraryAccountType>,
+        output_addr: impl Into<LibraryAccountType>,
+        expected_denoms: Vec<String>,
+    ) -> Self {
+        LibraryConfig {
+            input_addr: input_addr.into(),
+            output_addr: output_addr.into(),
+            expected_denoms,
+            snapshot_balances: false,
+        }
+    }
+
+    pub fn with_snapshot_balances(mut self, snapshot_balances: bool) -> Self {
+        self.snapshot_balances = snapshot_balances;
+        self
+    }
+
+    fn do_validate(&self, api: &dyn Api) -> Result<(Addr, Addr, BTreeSet<String>), LibraryError> {
+        let input_addr = self.input_addr.to_addr(api)?;
+        let output_addr = self.output_addr.to_addr(api)?;
+        ensure_distinct_accounts(&input_addr, &output_addr)?;
+        let expected_denoms = validate_expected_denoms(&self.expected_denoms)?;
+
+        Ok((input_addr, output_addr, expected_denoms))
+    }
+}
+
+impl LibraryConfigValidation<Config> for LibraryConfig {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pre_validate(&self, api: &dyn Api) -> Result<(), LibraryError> {
+        self.do_validate(api)?;
+        Ok(())
+    }
+
+    fn validate(&self, deps: Deps) -> Result<Config, LibraryError> {
+        let (input_addr, output_addr, expected_denoms) = self.do_validate(deps.api)?;
+
+        Ok(Config {
+            input_addr,
+            output_addr,
+            expected_denoms,
+            snapshot_balances: self.snapshot_balances,
+        })
+    }
+}
+
+impl LibraryConfigUpdate {
+    pub fn update_config(self, deps: DepsMut) -> Result<(), LibraryError> {
+        let mut config: Config = valence_library_base::load_config(deps.storage)?;
+
+        if let Some(input_addr) = self.input_addr {
+            config.input_addr = input_addr.to_addr(deps.api)?;
+        }
+
+        if let Some(output_addr) = self.output_addr {
+            config.output_addr = output_addr.to_addr(deps.api)?;
+        }
+
+        if let Some(expected_denoms) = self.expected_denoms {
+            config.expected_denoms = validate_expected_denoms(&expected_denoms)?;
+        }
+
+        if let Some(snapshot_balances) = self.snapshot_balances {
+            config.snapshot_balances = snapshot_balances;
+        }
+
+        // The accounts are checked against each other once both are (possibly) updated
+        ensure_distinct_accounts(&config.input_addr, &config.output_addr)?;
+
+        valence_library_base::save_config(deps.storage, &config)?;
+        Ok(())
+    }
+}
+
+#[cw_serde]
+pub struct Config {
+    pub input_addr: Addr,
+    pub output_addr: Addr,
+    pub expected_denoms: BTreeSet<String>,
+    #[serde(default)]
+    pub snapshot_balances: bool,
+}
+
+impl Config {
+    pub fn new(input_addr: Addr, output_addr: Addr, expected_denoms: BTreeSet<String>) -> Self {
+        Config {
+            input_addr,
+            output_addr,
+            expected_denoms,
+            snapshot_balances: false,
+        }
+    }
+}
+
+fn ensure_distinct_accounts(input_addr: &Addr, output_addr: &Addr) -> Result<(), LibraryError> {
+    if input_addr == output_addr {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid sweeper config: input and output accounts must be different.".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that at least one denom is expected, and that the expected denoms are neither empty nor duplicated
+fn validate_expected_denoms(expected_denoms: &[String]) -> Result<BTreeSet<String>, LibraryError> {
+    if expected_denoms.is_empty() {
+        return Err(LibraryError::ConfigurationError(
+            "Invalid sweeper config: at least one expected denom must be configured.".to_string(),
+        ));
+    }
+
+    let mut denoms = BTreeSet::new();
+    for denom in expected_denoms {
+        if denom.is_empty() {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid sweeper config: expected denoms cannot be empty.".to_string(),
+            ));
+        }
+        if !denoms.insert(denom.clone()) {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Invalid sweeper config: denom {denom} is expected more than once."
+            )));
+        }
+    }
+
+    Ok(denoms)
+}
@@ -0,0 +1,375 @@
+use std::collections::BTreeSet;
+
+use cosmwasm_std::{coin, Addr, Coin, Empty};
+use cw_multi_test::{error::AnyResult, App, AppResponse, ContractWrapper, Executor};
+use cw_ownable::Ownership;
+use valence_library_utils::{
+    events::{BALANCES_KEY, BALANCE_SNAPSHOT_EVENT_TYPE, STAGE_KEY},
+    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation},
+    testing::{LibraryTestSuite, LibraryTestSuiteBase},
+};
+
+use crate::msg::{Config, FunctionMsgs, LibraryConfig, LibraryConfigUpdate, QueryMsg};
+
+const NTRN: &str = "untrn";
+const USDC: &str = "uusdc";
+const AIRDROP: &str = "factory/airdrop/token";
+const WRONG_DEPOSIT: &str = "ibc/WRONGDEPOSIT";
+
+struct SweeperTestSuite {
+    inner: LibraryTestSuiteBase,
+    sweeper_code_id: u64,
+    input_addr: Addr,
+    output_addr: Addr,
+}
+
+impl Default for SweeperTestSuite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SweeperTestSuite {
+    pub fn new() -> Self {
+        let mut inner = LibraryTestSuiteBase::new();
+
+        let input_addr = inner.get_contract_addr(inner.account_code_id(), "input_account");
+        let output_addr = inner.get_contract_addr(inner.account_code_id(), "output_account");
+
+        let sweeper_code = ContractWrapper::new(
+            crate::contract::execute,
+            crate::contract::instantiate,
+            crate::contract::query,
+        )
+        .with_reply(crate::contract::reply);
+        let sweeper_code_id = inner.app_mut().store_code(Box::new(sweeper_code));
+
+        Self {
+            inner,
+            sweeper_code_id,
+            input_addr,
+            output_addr,
+        }
+    }
+
+    // Instantiates the library, along with the input account holding `balances` and the output account
+    pub fn sweeper_init(&mut self, cfg: &LibraryConfig, balances: Vec<Coin>) -> Addr {
+        let init_msg = InstantiateMsg {
+            owner: self.owner().to_string(),
+            processor: self.processor().to_string(),
+            config: cfg.clone(),
+        };
+        let addr = self.contract_init(self.sweeper_code_id, "sweeper", &init_msg, &[]);
+
+        let input_addr = self.account_init("input_account", vec![addr.to_string()]);
+        assert_eq!(input_addr, self.input_addr);
+        let output_addr = self.account_init("output_account", vec![]);
+        assert_eq!(output_addr, self.output_addr);
+
+        if !balances.is_empty() {
+            self.init_balance(&input_addr, balances);
+        }
+
+        addr
+    }
+
+    fn sweeper_config(&self, expected_denoms: &[&str]) -> LibraryConfig {
+        LibraryConfig::new(
+            &self.input_addr,
+            &self.output_addr,
+            expected_denoms
+                .iter()
+                .map(|denom| denom.to_string())
+                .collect(),
+        )
+    }
+
+    fn execute_sweep(&mut self, addr: Addr) -> AnyResult<AppResponse> {
+        self.contract_execute(
+            addr,
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::ProcessFunction(
+                FunctionMsgs::Sweep {},
+            ),
+        )
+    }
+
+    fn update_config(
+        &mut self,
+        addr: Addr,
+        new_config: LibraryConfigUpdate,
+    ) -> AnyResult<AppResponse> {
+        let owner = self.owner().clone();
+        self.app_mut().execute_contract(
+            owner,
+            addr,
+            &ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::UpdateConfig { new_config },
+            &[],
+        )
+    }
+}
+
+impl LibraryTestSuite<Empty, Empty> for SweeperTestSuite {
+    fn app(&self) -> &App {
+        self.inner.app()
+    }
+
+    fn app_mut(&mut self) -> &mut App {
+        self.inner.app_mut()
+    }
+
+    fn owner(&self) -> &Addr {
+        self.inner.owner()
+    }
+
+    fn processor(&self) -> &Addr {
+        self.inner.processor()
+    }
+
+    fn account_code_id(&self) -> u64 {
+        self.inner.account_code_id()
+    }
+
+    fn cw20_code_id(&self) -> u64 {
+        self.inner.cw20_code_id()
+    }
+}
+
+#[test]
+fn instantiate_with_valid_config() {
+    let mut suite = SweeperTestSuite::default();
+    let cfg = suite.sweeper_config(&[USDC, NTRN]);
+    let lib = suite.sweeper_init(&cfg, vec![]);
+
+    let owner_res: Ownership<Addr> = suite.query_wasm(&lib, &QueryMsg::Ownership {});
+    assert_eq!(owner_res.owner, Some(suite.owner().clone()));
+
+    let processor_addr: Addr = suite.query_wasm(&lib, &QueryMsg::GetProcessor {});
+    assert_eq!(processor_addr, suite.processor().clone());
+
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        lib_cfg,
+        Config::new(
+            suite.input_addr.clone(),
+            suite.output_addr.clone(),
+            BTreeSet::from([NTRN.to_string(), USDC.to_string()]),
+        )
+    );
+}
+
+#[test]
+fn sweep_only_moves_unexpected_denoms() {
+    let mut suite = SweeperTestSuite::default();
+    let cfg = suite.sweeper_config(&[USDC, NTRN]);
+    let lib = suite.sweeper_init(
+        &cfg,
+        vec![
+            coin(1_000, USDC),
+            coin(2_000, NTRN),
+            coin(300, AIRDROP),
+            coin(40, WRONG_DEPOSIT),
+        ],
+    );
+
+    let res = suite.execute_sweep(lib).unwrap();
+    assert!(res.events.iter().any(|event| event.attributes.iter().any(
+        |attr| attr.key == "swept" && attr.value == format!("300{AIRDROP},40{WRONG_DEPOSIT}")
+    )));
+
+    // The expected denoms stay on the input account
+    let input_addr = suite.input_addr.clone();
+    let output_addr = suite.output_addr.clone();
+    suite.assert_balance(&input_addr, 1_000, USDC);
+    suite.assert_balance(&input_addr, 2_000, NTRN);
+    suite.assert_balance(&input_addr, 0, AIRDROP);
+    suite.assert_balance(&input_addr, 0, WRONG_DEPOSIT);
+
+    // Every unexpected denom is swept in the same message
+    assert_eq!(
+        suite.query_all_balances(&output_addr),
+        vec![coin(300, AIRDROP), coin(40, WRONG_DEPOSIT)]
+    );
+}
+
+#[test]
+fn sweep_without_unexpected_denoms_sends_nothing() {
+    let mut suite = SweeperTestSuite::default();
+    let cfg = suite.sweeper_config(&[USDC, NTRN]);
+    let lib = suite.sweeper_init(&cfg, vec![coin(1_000, USDC), coin(2_000, NTRN)]);
+
+    let res = suite.execute_sweep(lib.clone()).unwrap();
+    // Only the library was executed, not the input account
+    assert_eq!(
+        res.events
+            .iter()
+            .filter(|event| event.ty == "execute")
+            .count(),
+        1
+    );
+
+    let output_addr = suite.output_addr.clone();
+    assert!(suite.query_all_balances(&output_addr).is_empty());
+
+    // Stray denoms received later are swept on the next execution
+    let input_addr = suite.input_addr.clone();
+    suite.init_balance(
+        &input_addr,
+        vec![coin(1_000, USDC), coin(2_000, NTRN), coin(5, AIRDROP)],
+    );
+    suite.execute_sweep(lib).unwrap();
+    assert_eq!(
+        suite.query_all_balances(&output_addr),
+        vec![coin(5, AIRDROP)]
+    );
+}
+
+#[test]
+fn pre_validate_rejects_invalid_expected_denoms() {
+    let suite = SweeperTestSuite::default();
+
+    for (expected_denoms, error) in [
+        (
+            vec![],
+            "Invalid sweeper config: at least one expected denom must be configured.",
+        ),
+        (
+            vec![USDC, ""],
+            "Invalid sweeper config: expected denoms cannot be empty.",
+        ),
+        (
+            vec![USDC, NTRN, USDC],
+            "Invalid sweeper config: denom uusdc is expected more than once.",
+        ),
+    ] {
+        let err = suite
+            .sweeper_config(&expected_denoms)
+            .pre_validate(suite.api())
+            .unwrap_err();
+        assert_eq!(err.to_string(), format!("Configuration error: {error}"));
+    }
+}
+
+#[test]
+#[should_panic(expected = "Invalid sweeper config: input and output accounts must be different.")]
+fn instantiate_fails_when_output_is_input() {
+    let mut suite = SweeperTestSuite::default();
+    let mut cfg = suite.sweeper_config(&[USDC]);
+    cfg.output_addr = cfg.input_addr.clone();
+
+    suite.sweeper_init(&cfg, vec![]);
+}
+
+#[test]
+fn update_config_validates_expected_denoms_and_output() {
+    let mut suite = SweeperTestSuite::default();
+    let cfg = suite.sweeper_config(&[USDC]);
+    let lib = suite.sweeper_init(&cfg, vec![]);
+
+    let err = suite
+        .update_config(
+            lib.clone(),
+            LibraryConfigUpdate {
+                expected_denoms: Some(vec![USDC.to_string(), USDC.to_string()]),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Configuration error: Invalid sweeper config: denom uusdc is expected more than once."
+    );
+
+    let err = suite
+        .update_config(
+            lib.clone(),
+            LibraryConfigUpdate {
+                output_addr: Some(cfg.input_addr.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Configuration error: Invalid sweeper config: input and output accounts must be different."
+    );
+
+    suite
+        .update_config(
+            lib.clone(),
+            LibraryConfigUpdate {
+                expected_denoms: Some(vec![USDC.to_string(), NTRN.to_string()]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let lib_cfg: Config = suite.query_wasm(&lib, &QueryMsg::GetLibraryConfig {});
+    assert_eq!(
+        lib_cfg.expected_denoms,
+        BTreeSet::from([NTRN.to_string(), USDC.to_string()])
+    );
+}
+
+#[test]
+fn sweep_snapshots_balances_only_when_enabled() {
+    let mut suite = SweeperTestSuite::default();
+    let cfg = suite.sweeper_config(&[USDC]);
+    let lib = suite.sweeper_init(&cfg, vec![coin(1_000, USDC), coin(300, AIRDROP)]);
+
+    // (stage, balances) of the snapshots of the input and output accounts, in order
+    let snapshots = |res: &AppResponse| -> Vec<(String, String)> {
+        res.events
+            .iter()
+            .filter(|event| event.ty == format!("wasm-{BALANCE_SNAPSHOT_EVENT_TYPE}"))
+            .map(|event| {
+                let attr = |key: &str| {
+                    event
+                        .attributes
+                        .iter()
+                        .find(|attr| attr.key == key)
+                        .map(|attr| attr.value.clone())
+                        .unwrap()
+                };
+                (attr(STAGE_KEY), attr(BALANCES_KEY))
+            })
+            .collect()
+    };
+
+    // Balances are not snapshotted by default
+    let res = suite.execute_sweep(lib.clone()).unwrap();
+    assert!(snapshots(&res).is_empty());
+
+    suite
+        .update_config(
+            lib.clone(),
+            LibraryConfigUpdate {
+                snapshot_balances: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let input_addr = suite.input_addr.clone();
+    suite.init_balance(&input_addr, vec![coin(1_000, USDC), coin(20, AIRDROP)]);
+
+    let res = suite.execute_sweep(lib.clone()).unwrap();
+    assert_eq!(
+        snapshots(&res),
+        vec![
+            ("before".to_string(), format!("20{AIRDROP},1000{USDC}")),
+            ("before".to_string(), format!("300{AIRDROP}")),
+            ("after".to_string(), format!("1000{USDC}")),
+            ("after".to_string(), format!("320{AIRDROP}")),
+        ]
+    );
+
+    // Without anything to sweep, the balances after are the balances before
+    let res = suite.execute_sweep(lib).unwrap();
+    assert_eq!(
+        snapshots(&res),
+        vec![
+            ("before".to_string(), format!("1000{USDC}")),
+            ("before".to_string(), format!("320{AIRDROP}")),
+            ("after".to_string(), format!("1000{USDC}")),
+            ("after".to_string(), format!("320{AIRDROP}")),
+        ]
+    );
+}
@@ -4,9 +4,9 @@ use cw_multi_test::{error::AnyResult, App, AppResponse, ContractWrapper, Executo
 use cw_ownable::Ownership;
 use getset::{Getters, Setters};
 use valence_library_utils::{
-    msg::{ExecuteMsg, InstantiateMsg},
+    msg::{ExecuteMsg, InstantiateMsg, LastErrorResponse, MigrateMsg, OwnerThreshold},
     testing::{LibraryTestSuite, LibraryTestSuiteBase},
-    OptionUpdate,
+    LibraryAccountType, OptionUpdate,
 };
 
 #[derive(Getters, Setters)]
@@ -33,7 +33,8 @@ impl TemplateTestSuite {
             crate::contract::execute,
             crate::contract::instantiate,
             crate::contract::query,
-        );
+        )
+        .with_migrate(crate::contract::migrate);
 
         let template_code_id = inner.app_mut().store_code(Box::new(template_code));
 
@@ -67,6 +68,57 @@ impl TemplateTestSuite {
         )
     }
 
+    fn execute_noop_with_nonce(&mut self, addr: Addr, nonce: u64) -> AnyResult<AppResponse> {
+        self.contract_execute(
+            addr,
+            &ExecuteMsg::<_, LibraryConfig>::ProcessFunctionWithNonce {
+                function: FunctionMsgs::NoOp {},
+                nonce,
+            },
+        )
+    }
+
+    fn owner_execute(
+        &mut self,
+        addr: Addr,
+        msg: &ExecuteMsg<FunctionMsgs, LibraryConfig>,
+    ) -> AnyResult<AppResponse> {
+        let owner = self.owner().clone();
+        self.app_mut().execute_contract(owner, addr, msg, &[])
+    }
+
+    fn migrate(&mut self, addr: Addr) -> AnyResult<AppResponse> {
+        let owner = self.owner().clone();
+        let code_id = self.template_code_id;
+        self.app_mut().migrate_contract(
+            owner,
+            addr,
+            &MigrateMsg::<LibraryConfig> {
+                config_update: None,
+            },
+            code_id,
+        )
+    }
+
+    // Requires `threshold` approvals of the returned owners for the privileged actions
+    fn set_owner_threshold(&mut self, addr: Addr, threshold: u64) -> Vec<Addr> {
+        let owners: Vec<Addr> = ["owner_1", "owner_2", "owner_3"]
+            .iter()
+            .map(|owner| self.api().addr_make(owner))
+            .collect();
+        self.owner_execute(
+            addr,
+            &ExecuteMsg::UpdateOwnerThreshold {
+                owner_threshold: Some(OwnerThreshold {
+                    owners: owners.iter().map(|owner| owner.to_string()).collect(),
+                    threshold,
+                }),
+            },
+        )
+        .unwrap();
+        owners
+    }
+
     fn update_config(&mut self, addr: Addr, new_config: LibraryConfig) -> AnyResult<AppResponse> {
         let owner = self.owner().clone();
         self.app_mut().execute_contract(
@@ -188,3 +240,408 @@ fn execute_function() {
     // Execute function
     suite.execute_noop(lib).unwrap();
 }
+
+#[test]
+fn pause_blocks_function_execution() {
+    let mut suite = TemplateTestSuite::default();
+
+    let cfg = suite.template_config(suite.owner().to_string());
+    let lib = suite.template_init(&cfg);
+
+    let paused: bool = suite.query_wasm(&lib, &QueryMsg::IsPaused {});
+    assert!(!paused);
+
+    // Only the owner can pause the library
+    suite
+        .contract_execute(
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfig>::Pause {},
+        )
+        .unwrap_err();
+
+    suite
+        .owner_execute(lib.clone(), &ExecuteMsg::Pause {})
+        .unwrap();
+    let paused: bool = suite.query_wasm(&lib, &QueryMsg::IsPaused {});
+    assert!(paused);
+
+    // Functions can't be executed while paused
+    let err = suite.execute_noop(lib.clone()).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Library is paused");
+
+    suite
+        .owner_execute(lib.clone(), &ExecuteMsg::Unpause {})
+        .unwrap();
+    let paused: bool = suite.query_wasm(&lib, &QueryMsg::IsPaused {});
+    assert!(!paused);
+
+    // Execution resumes once unpaused
+    suite.execute_noop(lib).unwrap();
+}
+
+#[test]
+fn processor_records_the_last_error() {
+    let mut suite = TemplateTestSuite::default();
+
+    let cfg = suite.template_config(suite.owner().to_string());
+    let lib = suite.template_init(&cfg);
+
+    let last_error: LastErrorResponse = suite.query_wasm(&lib, &QueryMsg::GetLastError {});
+    assert_eq!(last_error, LastErrorResponse::default());
+
+    // The function fails while the library is paused
+    suite
+        .owner_execute(lib.clone(), &ExecuteMsg::Pause {})
+        .unwrap();
+    suite.next_block();
+    let error = suite
+        .execute_noop(lib.clone())
+        .unwrap_err()
+        .root_cause()
+        .to_string();
+
+    // Only the processor can record the error of the failed execution
+    let owner = suite.owner().clone();
+    let err = suite
+        .app_mut()
+        .execute_contract(
+            owner,
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfig>::RecordError {
+                error: error.clone(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Unauthorized: This address is not allowed to execute this action"
+    );
+
+    suite
+        .contract_execute(
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfig>::RecordError { error },
+        )
+        .unwrap();
+
+    let last_error: LastErrorResponse = suite.query_wasm(&lib, &QueryMsg::GetLastError {});
+    assert_eq!(
+        last_error,
+        LastErrorResponse {
+            last_error: Some("Library is paused".to_string()),
+            last_error_height: Some(suite.app().block_info().height),
+        }
+    );
+}
+
+#[test]
+fn resolve_account_returns_the_referenced_address() {
+    let mut suite = TemplateTestSuite::default();
+
+    let cfg = suite.template_config(suite.owner().to_string());
+    let lib = suite.template_init(&cfg);
+
+    let owner = suite.owner().clone();
+    let resolved: Addr = suite.query_wasm(
+        &lib,
+        &QueryMsg::ResolveAccount {
+            account: LibraryAccountType::from(&owner),
+        },
+    );
+    assert_eq!(resolved, owner);
+
+    // Roles are resolved by the program manager, the library can't resolve them
+    let err = suite
+        .app()
+        .wrap()
+        .query_wasm_smart::<Addr>(
+            &lib,
+            &QueryMsg::ResolveAccount {
+                account: LibraryAccountType::Role("deposit".to_string()),
+            },
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("LibraryAccountType must be an address, role deposit was not resolved"));
+}
+
+#[test]
+fn same_nonce_is_rejected() {
+    let mut suite = TemplateTestSuite::default();
+
+    let cfg = suite.template_config(suite.owner().to_string());
+    let lib = suite.template_init(&cfg);
+
+    let res = suite.execute_noop_with_nonce(lib.clone(), 1).unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "nonce" && a.value == "1")));
+
+    // Replaying the same nonce is rejected, however late it is replayed
+    suite.app_mut().update_block(|block| {
+        block.time = block.time.plus_seconds(365 * 86_400);
+    });
+    let err = suite.execute_noop_with_nonce(lib.clone(), 1).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Execution error: Execution nonce 1 must be greater than the last executed nonce 1"
+    );
+
+    // Executions without nonce are not affected
+    suite.execute_noop(lib).unwrap();
+}
+
+#[test]
+fn distinct_nonces_are_all_executed() {
+    let mut suite = TemplateTestSuite::default();
+
+    let cfg = suite.template_config(suite.owner().to_string());
+    let lib = suite.template_init(&cfg);
+
+    suite.execute_noop_with_nonce(lib.clone(), 1).unwrap();
+    suite.execute_noop_with_nonce(lib.clone(), 3).unwrap();
+
+    // Nonces only go up, an older nonce is rejected once a later one was executed
+    let err = suite.execute_noop_with_nonce(lib, 2).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Execution error: Execution nonce 2 must be greater than the last executed nonce 3"
+    );
+}
+
+#[test]
+fn owner_threshold_requires_approvals_of_the_owners() {
+    let mut suite = TemplateTestSuite::default();
+
+    let cfg = suite.template_config(suite.owner().to_string());
+    let lib = suite.template_init(&cfg);
+    let owners = suite.set_owner_threshold(lib.clone(), 2);
+
+    // The single owner can no longer pause the library
+    let err = suite
+        .owner_execute(lib.clone(), &ExecuteMsg::Pause {})
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Unauthorized: This address is not allowed to execute this action"
+    );
+
+    // A single approval is recorded without pausing the library
+    let res = suite
+        .app_mut()
+        .execute_contract(
+            owners[0].clone(),
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfig>::Pause {},
+            &[],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| e
+        .attributes
+        .iter()
+        .any(|a| a.key == "approvals" && a.value == "1/2")));
+    let paused: bool = suite.query_wasm(&lib, &QueryMsg::IsPaused {});
+    assert!(!paused);
+
+    // Owners can't approve the same action twice
+    let err = suite
+        .app_mut()
+        .execute_contract(
+            owners[0].clone(),
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfig>::Pause {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Execution error: Action was already approved by {}",
+            owners[0]
+        )
+    );
+
+    // The action is executed with the approval of a second owner
+    suite
+        .app_mut()
+        .execute_contract(
+            owners[2].clone(),
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfig>::Pause {},
+            &[],
+        )
+        .unwrap();
+    let paused: bool = suite.query_wasm(&lib, &QueryMsg::IsPaused {});
+    assert!(paused);
+
+    // Approvals are consumed by the execution, unpausing needs its own approvals
+    suite
+        .app_mut()
+        .execute_contract(
+            owners[1].clone(),
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfig>::Unpause {},
+            &[],
+        )
+        .unwrap();
+    let paused: bool = suite.query_wasm(&lib, &QueryMsg::IsPaused {});
+    assert!(paused);
+}
+
+#[test]
+fn pending_approvals_expire_and_can_be_cancelled() {
+    let mut suite = TemplateTestSuite::default();
+
+    let cfg = suite.template_config(suite.owner().to_string());
+    let lib = suite.template_init(&cfg);
+    let owners = suite.set_owner_threshold(lib.clone(), 2);
+
+    let approve_pause = |suite: &mut TemplateTestSuite, owner: &Addr| {
+        let res = suite
+            .app_mut()
+            .execute_contract(
+                owner.clone(),
+                lib.clone(),
+                &ExecuteMsg::<FunctionMsgs, LibraryConfig>::Pause {},
+                &[],
+            )
+            .unwrap();
+        res.events
+            .iter()
+            .flat_map(|e| e.attributes.iter())
+            .find(|a| a.key == "approval_nonce")
+            .map(|a| a.value.parse::<u64>().unwrap())
+    };
+
+    // Approvals that didn't reach the threshold in time are dropped
+    assert_eq!(approve_pause(&mut suite, &owners[0]), Some(0));
+    suite.app_mut().update_block(|block| {
+        block.time = block
+            .time
+            .plus_seconds(valence_library_base::APPROVAL_EXPIRY_SECONDS)
+    });
+    assert_eq!(approve_pause(&mut suite, &owners[1]), Some(1));
+    let paused: bool = suite.query_wasm(&lib, &QueryMsg::IsPaused {});
+    assert!(!paused);
+
+    // Only the owners of the threshold can cancel a pending approval
+    let cancel = ExecuteMsg::<FunctionMsgs, LibraryConfig>::CancelApproval { nonce: 1 };
+    let err = suite.owner_execute(lib.clone(), &cancel).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Unauthorized: This address is not allowed to execute this action"
+    );
+    suite
+        .app_mut()
+        .execute_contract(owners[2].clone(), lib.clone(), &cancel, &[])
+        .unwrap();
+    let err = suite
+        .app_mut()
+        .execute_contract(owners[2].clone(), lib.clone(), &cancel, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Execution error: No pending approval with nonce 1"
+    );
+
+    // The cancelled approval doesn't count, the action starts a new approval
+    assert_eq!(approve_pause(&mut suite, &owners[0]), Some(2));
+    let paused: bool = suite.query_wasm(&lib, &QueryMsg::IsPaused {});
+    assert!(!paused);
+    assert_eq!(approve_pause(&mut suite, &owners[1]), None);
+    let paused: bool = suite.query_wasm(&lib, &QueryMsg::IsPaused {});
+    assert!(paused);
+}
+
+#[test]
+fn migration_with_owner_threshold_must_be_authorized() {
+    let mut suite = TemplateTestSuite::default();
+
+    let cfg = suite.template_config(suite.owner().to_string());
+    let lib = suite.template_init(&cfg);
+    let owners = suite.set_owner_threshold(lib.clone(), 2);
+
+    let err = suite.migrate(lib.clone()).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Execution error: Migration was not authorized by the owners"
+    );
+
+    for owner in &owners[..2] {
+        suite
+            .app_mut()
+            .execute_contract(
+                owner.clone(),
+                lib.clone(),
+                &ExecuteMsg::<FunctionMsgs, LibraryConfig>::AuthorizeMigration {},
+                &[],
+            )
+            .unwrap();
+    }
+    suite.migrate(lib.clone()).unwrap();
+
+    // The authorization only covers a single migration
+    suite.migrate(lib).unwrap_err();
+}
+
+#[test]
+fn update_owner_threshold_validates_owners() {
+    let mut suite = TemplateTestSuite::default();
+
+    let cfg = suite.template_config(suite.owner().to_string());
+    let lib = suite.template_init(&cfg);
+    let owner = suite.owner().to_string();
+
+    for (owners, threshold, error) in [
+        (
+            vec![owner.clone(), owner.clone()],
+            1,
+            format!("Invalid owner threshold: {owner} is an owner more than once."),
+        ),
+        (
+            vec![owner.clone()],
+            0,
+            "Invalid owner threshold: threshold must be between 1 and the 1 owners.".to_string(),
+        ),
+        (
+            vec![owner.clone()],
+            2,
+            "Invalid owner threshold: threshold must be between 1 and the 1 owners.".to_string(),
+        ),
+    ] {
+        let err = suite
+            .owner_execute(
+                lib.clone(),
+                &ExecuteMsg::UpdateOwnerThreshold {
+                    owner_threshold: Some(OwnerThreshold { owners, threshold }),
+                },
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            format!("Configuration error: {error}")
+        );
+    }
+
+    // Removing the owner threshold goes back to the single owner
+    let owners = suite.set_owner_threshold(lib.clone(), 1);
+    suite
+        .app_mut()
+        .execute_contract(
+            owners[0].clone(),
+            lib.clone(),
+            &ExecuteMsg::<FunctionMsgs, LibraryConfig>::UpdateOwnerThreshold {
+                owner_threshold: None,
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .owner_execute(lib.clone(), &ExecuteMsg::Pause {})
+        .unwrap();
+    let paused: bool = suite.query_wasm(&lib, &QueryMsg::IsPaused {});
+    assert!(paused);
+}
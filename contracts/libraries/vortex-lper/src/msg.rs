@@ -63,6 +63,7 @@ impl LibraryConfig {
         let input_addr = self.input_addr.to_addr(api)?;
         let output_addr = self.output_addr.to_addr(api)?;
         let output_addr_2 = self.output_addr_2.to_addr(api)?;
+        self.lp_config.asset_data.ensure_two_assets()?;
 
         Ok((input_addr, output_addr, output_addr_2))
     }
@@ -137,6 +138,7 @@ impl LibraryConfigUpdate {
         }
 
         if let Some(lp_config) = self.lp_config {
+            lp_config.asset_data.ensure_two_assets()?;
             config.lp_config = lp_config;
         }
 
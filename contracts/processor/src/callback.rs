@@ -1,25 +1,59 @@
-use cosmwasm_std::{to_json_binary, Addr, BlockInfo, CosmosMsg, Storage, Uint64, WasmMsg};
+use cosmwasm_std::{
+    to_json_binary, Addr, BlockInfo, CosmosMsg, Empty, Order, StdResult, Storage, SubMsg, Uint64,
+    WasmMsg,
+};
 use valence_authorization_utils::{
     authorization::Subroutine,
     callback::ExecutionResult,
     function::RetryTimes,
-    msg::{ExecuteMsg, InternalAuthorizationMsg},
+    msg::{ExecuteMsg, InternalAuthorizationMsg, ProcessorMessage},
 };
 use valence_gmp_utils::polytone::{CallbackRequest, PolytoneExecuteMsg};
+use valence_library_utils::msg::ExecuteMsg as LibraryExecuteMsg;
 use valence_processor_utils::{
     callback::{PendingPolytoneCallbackInfo, PolytoneCallbackMsg, PolytoneCallbackState},
-    processor::{Config, MessageBatch, ProcessorDomain},
+    processor::{Config, DeadLetter, MessageBatch, ProcessorDomain},
 };
 
 use crate::{
     error::ContractError,
     queue::{get_queue_map, put_back_into_queue},
     state::{
-        CONFIG, EXECUTION_ID_TO_BATCH, NON_ATOMIC_BATCH_CURRENT_FUNCTION_INDEX,
-        PENDING_POLYTONE_CALLBACKS,
+        CONFIG, DEAD_LETTER_QUEUE, EXECUTION_ID_TO_BATCH, MAX_DEAD_LETTERS,
+        NON_ATOMIC_BATCH_CURRENT_FUNCTION_INDEX, PENDING_POLYTONE_CALLBACKS,
     },
 };
 
+// Reply ID of the messages recording the error of a failed function on its library. Execution IDs are
+// assigned incrementally by the authorization contract, so they never reach it
+pub const RECORD_ERROR_REPLY_ID: u64 = u64::MAX;
+
+/// Creates the message recording `error` on the library of the failed function of a non-atomic batch,
+/// so that the library can tell why it stalled. Only replies on error, which is ignored, because
+/// the contract of the function is not necessarily a library
+pub fn create_record_error_message(
+    batch: &MessageBatch,
+    index: usize,
+    error: &str,
+) -> Result<Option<SubMsg>, ContractError> {
+    let Some(ProcessorMessage::CosmwasmExecuteMsg { .. }) = batch.msgs.get(index) else {
+        return Ok(None);
+    };
+
+    Ok(Some(SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: batch
+                .subroutine
+                .get_contract_address_by_function_index(index),
+            msg: to_json_binary(&LibraryExecuteMsg::<Empty, Empty>::RecordError {
+                error: error.to_string(),
+            })?,
+            funds: vec![],
+        },
+        RECORD_ERROR_REPLY_ID,
+    )))
+}
+
 pub fn create_callback_message(
     storage: &mut dyn Storage,
     config: &Config,
@@ -126,6 +160,25 @@ pub fn handle_successful_atomic_callback(
     Ok(())
 }
 
+/// Stores a batch in the dead letter queue, dropping the oldest ones if the queue is full so that it stays bounded
+fn save_dead_letter(
+    storage: &mut dyn Storage,
+    execution_id: u64,
+    dead_letter: &DeadLetter,
+) -> Result<(), ContractError> {
+    let stored = DEAD_LETTER_QUEUE
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<u64>>>()?;
+    // Execution IDs are increasing, so the lowest keys are the oldest dead letters
+    let excess = (stored.len() + 1).saturating_sub(MAX_DEAD_LETTERS);
+    for id in stored.into_iter().take(excess) {
+        DEAD_LETTER_QUEUE.remove(storage, id);
+    }
+    DEAD_LETTER_QUEUE.save(storage, execution_id, dead_letter)?;
+
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn handle_unsuccessful_callback(
     storage: &mut dyn Storage,
@@ -158,7 +211,18 @@ pub fn handle_unsuccessful_callback(
             match &retry_logic.times {
                 RetryTimes::Amount(max_retries) => {
                     if retry_amounts >= *max_retries {
-                        // We've retried the function the maximum amount of times, we'll provide the error callback to the authorization module
+                        // We've retried the function the maximum amount of times, we move the batch to the dead letter queue
+                        save_dead_letter(
+                            storage,
+                            execution_id,
+                            &DeadLetter {
+                                batch: batch.clone(),
+                                function_index: index,
+                                error: error.clone(),
+                            },
+                        )?;
+
+                        // and provide the error callback to the authorization module
                         let execution_result = if index == 0 {
                             ExecutionResult::Rejected(error)
                         } else {
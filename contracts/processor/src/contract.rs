@@ -2,8 +2,8 @@
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_json, to_json_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, Reply,
-    Response, StdResult, SubMsg, SubMsgResult, Uint64, WasmMsg,
+    from_json, to_json_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order,
+    Reply, Response, StdResult, Storage, SubMsg, SubMsgResult, Uint64, WasmMsg,
 };
 
 use cw_storage_plus::Bound;
@@ -18,20 +18,26 @@ use valence_processor_utils::{
     callback::{
         PendingCallback, PendingPolytoneCallbackInfo, PolytoneCallbackMsg, PolytoneCallbackState,
     },
-    msg::{ExecuteMsg, InstantiateMsg, InternalProcessorMsg, PermissionlessMsg, QueryMsg},
-    processor::{Config, MessageBatch, Polytone, ProcessorDomain, State},
+    msg::{
+        ExecuteMsg, InstantiateMsg, InternalProcessorMsg, PermissionlessMsg, QueryMsg,
+        QueuesResponse,
+    },
+    processor::{
+        Config, DeadLetter, MessageBatch, Polytone, ProcessorDomain, QueueGasEstimate, State,
+        DEFAULT_BLOCK_GAS_LIMIT,
+    },
 };
 
 use crate::{
     callback::{
-        create_callback_message, handle_successful_atomic_callback,
-        handle_successful_non_atomic_callback, handle_unsuccessful_callback,
+        create_callback_message, create_record_error_message, handle_successful_atomic_callback,
+        handle_successful_non_atomic_callback, handle_unsuccessful_callback, RECORD_ERROR_REPLY_ID,
     },
     error::{CallbackErrorReason, ContractError, UnauthorizedReason},
     queue::get_queue_map,
     state::{
-        CONFIG, EXECUTION_ID_TO_BATCH, NON_ATOMIC_BATCH_CURRENT_FUNCTION_INDEX, PENDING_CALLBACK,
-        PENDING_POLYTONE_CALLBACKS,
+        CONFIG, DEAD_LETTER_QUEUE, EXECUTION_ID_TO_BATCH, NON_ATOMIC_BATCH_CURRENT_FUNCTION_INDEX,
+        PENDING_CALLBACK, PENDING_POLYTONE_CALLBACKS,
     },
 };
 
@@ -142,6 +148,10 @@ pub fn execute(
                     priority,
                     expiration_time,
                 ),
+                AuthorizationMsg::RemoveMsg { id } => remove_message(deps, env, id),
+                AuthorizationMsg::Flush {} => flush_queues(deps, env),
+                AuthorizationMsg::PurgeDeadLetters { ids } => purge_dead_letters(deps, ids),
+                AuthorizationMsg::RequeueDeadLetter { id } => requeue_dead_letter(deps, id),
                 AuthorizationMsg::Pause {} => pause_processor(deps),
                 AuthorizationMsg::Resume {} => resume_processor(deps),
             }
@@ -196,6 +206,8 @@ fn enqueue_messages(
     priority: Priority,
     expiration_time: Option<u64>,
 ) -> Result<Response, ContractError> {
+    validate_scopes(&msgs, &subroutine)?;
+
     // If it's already expired we won't even add it to the queue and send the callback
     if let Some(expiration_time) = expiration_time {
         if expiration_time < env.block.time.seconds() {
@@ -242,17 +254,8 @@ fn evict_messages(
     match batch {
         Some(batch) => {
             let config = CONFIG.load(deps.storage)?;
-            // Do the clean up and send the callback
-            EXECUTION_ID_TO_BATCH.remove(deps.storage, batch.id);
-            NON_ATOMIC_BATCH_CURRENT_FUNCTION_INDEX.remove(deps.storage, batch.id);
-            PENDING_CALLBACK.remove(deps.storage, batch.id);
-            let callback_msg = create_callback_message(
-                deps.storage,
-                &config,
-                batch.id,
-                ExecutionResult::RemovedByOwner,
-                &env.contract.address,
-            )?;
+            let callback_msg =
+                clean_up_removed_batch(deps.storage, &config, batch.id, &env.contract.address)?;
             Ok(Response::new()
                 .add_message(callback_msg)
                 .add_attribute("method", "evict_messages")
@@ -265,6 +268,166 @@ fn evict_messages(
     }
 }
 
+/// Removes the batch with the given execution id from its queue, as long as it didn't start executing
+fn remove_message(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
+    if is_in_flight(deps.storage, id)? {
+        return Err(ContractError::BatchInFlight(id));
+    }
+
+    let batch = EXECUTION_ID_TO_BATCH
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::BatchNotEnqueued(id))?;
+    let mut queue = get_queue_map(&batch.priority);
+    // The batch might not be in the queue anymore if it is being processed
+    let queue_position = queue
+        .query(deps.storage, None, None, Order::Ascending)?
+        .iter()
+        .position(|enqueued| enqueued.id == id)
+        .ok_or(ContractError::BatchNotEnqueued(id))?;
+    queue.remove_at(deps.storage, queue_position as u64)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let callback_msg = clean_up_removed_batch(deps.storage, &config, id, &env.contract.address)?;
+
+    Ok(Response::new()
+        .add_message(callback_msg)
+        .add_attribute("method", "remove_message")
+        .add_attribute("removed_ids", id.to_string()))
+}
+
+/// Empties all the queues, except for the non-atomic batches that are partially executed which keep their place
+fn flush_queues(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut callback_msgs = vec![];
+    let mut removed_ids = vec![];
+    let mut kept_ids = vec![];
+
+    for priority in [Priority::High, Priority::Medium, Priority::Low] {
+        let queue = get_queue_map(&priority);
+        // Every batch is popped once and the ones in flight are pushed back, so that their order is preserved
+        for _ in 0..queue.len(deps.storage)? {
+            let Some(batch) = queue.pop_front(deps.storage)? else {
+                break;
+            };
+
+            if is_in_flight(deps.storage, batch.id)? {
+                queue.push_back(deps.storage, &batch)?;
+                kept_ids.push(batch.id.to_string());
+            } else {
+                callback_msgs.push(clean_up_removed_batch(
+                    deps.storage,
+                    &config,
+                    batch.id,
+                    &env.contract.address,
+                )?);
+                removed_ids.push(batch.id.to_string());
+            }
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(callback_msgs)
+        .add_attribute("method", "flush")
+        .add_attribute("removed_ids", removed_ids.join(","))
+        .add_attribute("kept_in_flight_ids", kept_ids.join(",")))
+}
+
+/// Drops the given batches from the dead letter queue, their callbacks were already sent when they got there
+fn purge_dead_letters(deps: DepsMut, ids: Vec<u64>) -> Result<Response, ContractError> {
+    for id in &ids {
+        if !DEAD_LETTER_QUEUE.has(deps.storage, *id) {
+            return Err(ContractError::DeadLetterNotFound(*id));
+        }
+        DEAD_LETTER_QUEUE.remove(deps.storage, *id);
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "purge_dead_letters")
+        .add_attribute(
+            "purged_ids",
+            ids.iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ))
+}
+
+/// Puts a batch of the dead letter queue back at the end of its queue, resuming from the function that failed
+fn requeue_dead_letter(deps: DepsMut, id: u64) -> Result<Response, ContractError> {
+    let DeadLetter {
+        mut batch,
+        function_index,
+        ..
+    } = DEAD_LETTER_QUEUE
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::DeadLetterNotFound(id))?;
+    DEAD_LETTER_QUEUE.remove(deps.storage, id);
+
+    // The batch gets a fresh set of retries
+    batch.retry = None;
+    if function_index > 0 {
+        NON_ATOMIC_BATCH_CURRENT_FUNCTION_INDEX.save(deps.storage, id, &function_index)?;
+    }
+    EXECUTION_ID_TO_BATCH.save(deps.storage, id, &batch)?;
+    get_queue_map(&batch.priority).push_back(deps.storage, &batch)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "requeue_dead_letter")
+        .add_attribute("execution_id", id.to_string())
+        .add_attribute("function_index", function_index.to_string()))
+}
+
+// Rejects the messages calling a variant that is not in the scope of their function, for the functions that have a scope
+fn validate_scopes(
+    msgs: &[ProcessorMessage],
+    subroutine: &Subroutine,
+) -> Result<(), ContractError> {
+    for (index, msg) in msgs.iter().enumerate() {
+        let Some(scope) = subroutine.get_scope_by_function_index(index) else {
+            continue;
+        };
+
+        let variant = msg.called_variant();
+        if !variant
+            .as_ref()
+            .is_some_and(|variant| scope.contains(variant))
+        {
+            return Err(ContractError::MessageOutOfScope {
+                index,
+                variant: variant.unwrap_or_default(),
+            });
+        }
+    }
+    Ok(())
+}
+
+// A non-atomic batch is in flight once one of its functions was executed, or while it waits for the callback of a library
+fn is_in_flight(storage: &dyn Storage, id: u64) -> StdResult<bool> {
+    let current_function_index = NON_ATOMIC_BATCH_CURRENT_FUNCTION_INDEX
+        .may_load(storage, id)?
+        .unwrap_or_default();
+    Ok(current_function_index > 0 || PENDING_CALLBACK.has(storage, id))
+}
+
+// Cleans up the state of a batch taken out of its queue by the owner and creates the callback for the authorization module
+fn clean_up_removed_batch(
+    storage: &mut dyn Storage,
+    config: &Config,
+    id: u64,
+    processor_address: &Addr,
+) -> Result<CosmosMsg, ContractError> {
+    EXECUTION_ID_TO_BATCH.remove(storage, id);
+    NON_ATOMIC_BATCH_CURRENT_FUNCTION_INDEX.remove(storage, id);
+    PENDING_CALLBACK.remove(storage, id);
+    create_callback_message(
+        storage,
+        config,
+        id,
+        ExecutionResult::RemovedByOwner,
+        processor_address,
+    )
+}
+
 /// Insert a set of messages in a specific position of the queue
 #[allow(clippy::too_many_arguments)]
 fn insert_messages(
@@ -277,6 +440,8 @@ fn insert_messages(
     priority: Priority,
     expiration_time: Option<u64>,
 ) -> Result<Response, ContractError> {
+    validate_scopes(&msgs, &subroutine)?;
+
     // If it's already expired we won't even add it to the queue and send the callback
     if let Some(expiration_time) = expiration_time {
         if expiration_time < env.block.time.seconds() {
@@ -320,11 +485,14 @@ fn process_tick(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
         return Err(ContractError::ProcessorPaused {});
     }
 
-    // If there is something in the high priority queue we'll process it first
+    // We'll process the highest priority queue that has something in it, batches within the same queue are processed FIFO
     let mut queue = get_queue_map(&Priority::High);
     if queue.len(deps.storage)? == 0 {
         queue = get_queue_map(&Priority::Medium);
     }
+    if queue.len(deps.storage)? == 0 {
+        queue = get_queue_map(&Priority::Low);
+    }
 
     let message_batch = queue.pop_front(deps.storage)?;
 
@@ -580,10 +748,10 @@ fn execute_atomic(
         ));
     }
 
-    let messages = Into::<StdResult<Vec<CosmosMsg>>>::into(batch)?;
+    let messages = batch.create_atomic_messages()?;
 
     Ok(Response::new()
-        .add_messages(messages)
+        .add_submessages(messages)
         .add_attribute("method", "execute_atomic"))
 }
 
@@ -706,11 +874,19 @@ fn process_polytone_callback(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    // Recording the error of a failed function is best effort, if the library could not record it there's nothing else to do
+    if msg.id == RECORD_ERROR_REPLY_ID {
+        return Ok(Response::new()
+            .add_attribute("method", "reply")
+            .add_attribute("action", "error_not_recorded"));
+    }
+
     // The reply logic will be different depending on the execution type of the batch
     // First we check if the reply comes from an atomic or non-atomic batch
     let config = CONFIG.load(deps.storage)?;
     let mut batch = EXECUTION_ID_TO_BATCH.load(deps.storage, msg.id)?;
     let mut messages = vec![];
+    let mut submessages = vec![];
 
     match NON_ATOMIC_BATCH_CURRENT_FUNCTION_INDEX.may_load(deps.storage, msg.id)? {
         Some(index) => {
@@ -731,6 +907,8 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
                     }
                 }
                 SubMsgResult::Err(error) => {
+                    // The library of the failed function records the error, whether the function is retried or not
+                    submessages.extend(create_record_error_message(&batch, index, &error)?);
                     handle_unsuccessful_callback(
                         deps.storage,
                         msg.id,
@@ -776,6 +954,7 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
 
     Ok(Response::new()
         .add_messages(messages)
+        .add_submessages(submessages)
         .add_attribute("method", "reply"))
 }
 
@@ -787,12 +966,21 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_json_binary(&get_queue(deps, from, to, &priority)?)
         }
         QueryMsg::IsQueueEmpty {} => to_json_binary(&is_queue_empty(deps)?),
+        QueryMsg::GetQueues { start_after, limit } => {
+            to_json_binary(&get_queues(deps, start_after, limit)?)
+        }
         QueryMsg::PendingPolytoneCallbacks { start_after, limit } => {
             to_json_binary(&get_pending_polytone_callbacks(deps, start_after, limit))
         }
         QueryMsg::PendingPolytoneCallback { execution_id } => {
             to_json_binary(&get_pending_polytone_callback(deps, execution_id)?)
         }
+        QueryMsg::DeadLetterQueue { start_after, limit } => {
+            to_json_binary(&get_dead_letter_queue(deps, start_after, limit))
+        }
+        QueryMsg::EstimateQueueGas { block_gas_limit } => {
+            to_json_binary(&estimate_queue_gas(deps, block_gas_limit)?)
+        }
     }
 }
 
@@ -813,8 +1001,53 @@ fn get_queue(
 fn is_queue_empty(deps: Deps) -> StdResult<bool> {
     let queue_high = get_queue_map(&Priority::High);
     let queue_med = get_queue_map(&Priority::Medium);
+    let queue_low = get_queue_map(&Priority::Low);
 
-    Ok(queue_high.is_empty(deps.storage)? && queue_med.is_empty(deps.storage)?)
+    Ok(queue_high.is_empty(deps.storage)?
+        && queue_med.is_empty(deps.storage)?
+        && queue_low.is_empty(deps.storage)?)
+}
+
+fn get_queues(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<QueuesResponse> {
+    // Positions are counted across the queues, in the order the batches will be processed
+    let mut skip = start_after.map_or(0, |position| position.saturating_add(1));
+    let mut remaining = u64::from(limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT));
+
+    let mut get_page = |priority: Priority| -> StdResult<Vec<MessageBatch>> {
+        let len = get_queue_map(&priority).len(deps.storage)?;
+        let from = skip.min(len);
+        let to = from.saturating_add(remaining).min(len);
+        skip -= from;
+        remaining -= to - from;
+
+        if from == to {
+            return Ok(vec![]);
+        }
+        get_queue(deps, Some(from), Some(to), &priority)
+    };
+
+    Ok(QueuesResponse {
+        high: get_page(Priority::High)?,
+        medium: get_page(Priority::Medium)?,
+        low: get_page(Priority::Low)?,
+    })
+}
+
+fn estimate_queue_gas(deps: Deps, block_gas_limit: Option<u64>) -> StdResult<QueueGasEstimate> {
+    // Batches are estimated in the order they will be processed
+    let high = get_queue(deps, None, None, &Priority::High)?;
+    let medium = get_queue(deps, None, None, &Priority::Medium)?;
+    let low = get_queue(deps, None, None, &Priority::Low)?;
+    let batches = high.iter().chain(medium.iter()).chain(low.iter());
+
+    Ok(QueueGasEstimate::new(
+        batches,
+        block_gas_limit.unwrap_or(DEFAULT_BLOCK_GAS_LIMIT),
+    ))
 }
 
 fn get_pending_polytone_callbacks(
@@ -839,3 +1072,19 @@ fn get_pending_polytone_callback(
 ) -> StdResult<PendingPolytoneCallbackInfo> {
     PENDING_POLYTONE_CALLBACKS.load(deps.storage, execution_id)
 }
+
+fn get_dead_letter_queue(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Vec<DeadLetter> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+    let start = start_after.map(Bound::exclusive);
+
+    DEAD_LETTER_QUEUE
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .filter_map(Result::ok)
+        .map(|(_, dead_letter)| dead_letter)
+        .collect()
+}
@@ -18,6 +18,18 @@ pub enum ContractError {
 
     #[error(transparent)]
     CallbackError(#[from] CallbackErrorReason),
+
+    #[error("There is no batch with execution id {0} waiting in the queues")]
+    BatchNotEnqueued(u64),
+
+    #[error("Batch with execution id {0} is partially executed and can't be removed")]
+    BatchInFlight(u64),
+
+    #[error("There is no batch with execution id {0} in the dead letter queue")]
+    DeadLetterNotFound(u64),
+
+    #[error("Message {index} calls `{variant}`, which is out of the scope of its function")]
+    MessageOutOfScope { index: usize, variant: String },
 }
 
 #[derive(Error, Debug, PartialEq)]
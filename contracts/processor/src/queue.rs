@@ -7,13 +7,14 @@ use valence_processor_utils::{
 
 use crate::{
     error::ContractError,
-    state::{EXECUTION_ID_TO_BATCH, HIGH_PRIORITY_QUEUE, MED_PRIORITY_QUEUE},
+    state::{EXECUTION_ID_TO_BATCH, HIGH_PRIORITY_QUEUE, LOW_PRIORITY_QUEUE, MED_PRIORITY_QUEUE},
 };
 
 pub fn get_queue_map(priority: &Priority) -> QueueMap<MessageBatch> {
     match priority {
         Priority::High => HIGH_PRIORITY_QUEUE,
         Priority::Medium => MED_PRIORITY_QUEUE,
+        Priority::Low => LOW_PRIORITY_QUEUE,
     }
 }
 
@@ -24,10 +25,10 @@ pub fn put_back_into_queue(
     retry_logic: &RetryLogic,
     block: &BlockInfo,
 ) -> Result<(), ContractError> {
-    // Increment the retry for the batch
+    // Increment the retry for the batch, the cooldown grows with the retries already done if backoff is exponential
     batch.retry = Some(CurrentRetry {
         retry_amounts: retry_amounts.checked_add(1).expect("Overflow"),
-        retry_cooldown: retry_logic.interval.after(block),
+        retry_cooldown: retry_logic.cooldown(retry_amounts).after(block),
     });
     // Re-add to queue and save the batch with new retries
     let queue = get_queue_map(&batch.priority);
@@ -1,7 +1,7 @@
 use cw_storage_plus::{Item, Map};
 use valence_processor_utils::{
     callback::{PendingCallback, PendingPolytoneCallbackInfo},
-    processor::{Config, MessageBatch},
+    processor::{Config, DeadLetter, MessageBatch},
     queue::QueueMap,
 };
 
@@ -16,6 +16,11 @@ pub const HIGH_PRIORITY_QUEUE: QueueMap<MessageBatch> = QueueMap::new(
     "high_priority_queue_start_index",
     "high_priority_queue_end_index",
 );
+pub const LOW_PRIORITY_QUEUE: QueueMap<MessageBatch> = QueueMap::new(
+    "low_priority_queue",
+    "low_priority_queue_start_index",
+    "low_priority_queue_end_index",
+);
 // We'll use this map to store the execution ID and the batch itself. This way we can retrieve the batch by ID to process retries
 pub const EXECUTION_ID_TO_BATCH: Map<u64, MessageBatch> = Map::new("id_to_batch");
 
@@ -33,3 +38,9 @@ pub const PENDING_CALLBACK: Map<u64, PendingCallback> = Map::new("pending_callba
 // The key will be the execution ID the callback was for and we will store the result and the status to re-send if the state is TimedOut
 pub const PENDING_POLYTONE_CALLBACKS: Map<u64, PendingPolytoneCallbackInfo> =
     Map::new("pending_polytone_callbacks");
+
+// Batches that exhausted their retry logic are moved here, keyed by execution ID, so that they can be queried by the operator
+// and purged or requeued by the owner through the authorization contract
+pub const DEAD_LETTER_QUEUE: Map<u64, DeadLetter> = Map::new("dead_letter_queue");
+// Maximum amount of batches kept in the dead letter queue, the oldest ones are dropped when a new one comes in
+pub const MAX_DEAD_LETTERS: usize = 100;
@@ -8,7 +8,7 @@ use valence_processor_utils::msg::{ExecuteMsg as ProcessorExecuteMsg, InternalPr
 
 use crate::{
     error::ContractError,
-    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg},
+    msg::{ExecuteMsg, FunctionMsgs, InstantiateMsg, MigrateMsg, QueryMsg},
     state::{CONDITION, EXECUTION_ID},
 };
 
@@ -66,6 +66,10 @@ pub fn execute(
             CONDITION.save(deps.storage, &condition)?;
             Ok(Response::new())
         }
+        ExecuteMsg::ProcessFunction(function_msg) => match function_msg {
+            FunctionMsgs::Succeed {} => Ok(Response::new()),
+            FunctionMsgs::Error { error } => Err(ContractError::Std(StdError::generic_err(error))),
+        },
     }
 }
 
@@ -11,6 +11,14 @@ pub enum ExecuteMsg {
     WillSucceedIfTrue {},
     SetCondition { condition: bool },
     SendCallback { to: String, callback: Binary },
+    ProcessFunction(FunctionMsgs),
+}
+
+// Nested variants, called like the functions of a library
+#[cw_serde]
+pub enum FunctionMsgs {
+    Succeed {},
+    Error { error: String },
 }
 
 #[cw_serde]
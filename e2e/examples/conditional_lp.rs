@@ -447,11 +447,11 @@ fn osmosis_setup(
         osmosis_base_acc_code_id,
         OSMOSIS_CHAIN_ADMIN_ADDR.to_string(),
         vec![processor_on_osmosis.to_string()],
-        2,
+        &["input", "output"],
         Some(Coin::new(1000000u128, OSMOSIS_CHAIN_DENOM)),
     );
-    let osmo_input_acc_addr = osmo_base_accounts.first().unwrap();
-    let osmo_output_acc_addr = osmo_base_accounts.get(1).unwrap();
+    let osmo_input_acc_addr = &osmo_base_accounts["input"].to_string();
+    let osmo_output_acc_addr = &osmo_base_accounts["output"].to_string();
     info!("osmo_input_acc_addr: {osmo_input_acc_addr}");
     info!("osmo_output_acc_addr: {osmo_output_acc_addr}");
     std::thread::sleep(std::time::Duration::from_secs(3));
@@ -469,6 +469,7 @@ fn osmosis_setup(
                 asset_data: valence_library_utils::liquidity_utils::AssetData {
                     asset1: ntrn_on_osmo_denom.to_string(),
                     asset2: OSMOSIS_CHAIN_DENOM.to_string(),
+                    additional_assets: vec![],
                 },
             },
         ),
@@ -654,10 +655,10 @@ fn neutron_setup(
         neutron_base_acc_code_id,
         NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         vec![neutron_processor_address.to_string()],
-        1,
+        &["input"],
         Some(Coin::new(1000000u128, NEUTRON_CHAIN_DENOM)),
     );
-    let neutron_input_acc_addr = neutron_base_accounts.first().unwrap();
+    let neutron_input_acc_addr = &neutron_base_accounts["input"].to_string();
 
     // Get the code id
     let code_id_ibc_transfer_lib = test_ctx
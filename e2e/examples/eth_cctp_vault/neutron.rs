@@ -10,11 +10,15 @@ pub mod ica {
     use log::info;
     use valence_account_utils::ica::{IcaState, RemoteDomainInfo};
     use valence_e2e::utils::{
-        ibc::poll_for_ica_state, manager::INTERCHAIN_ACCOUNT_NAME, GAS_FLAGS, NOBLE_CHAIN_NAME,
+        ibc::wait_for_ica_channel_open, manager::INTERCHAIN_ACCOUNT_NAME, relayer::restart_relayer,
+        GAS_FLAGS,
     };
 
+    /// Instantiates an ICA contract registering its ICA over `connection_id`, which must be given
+    /// explicitly as the chain can have several connections to the remote chain
     pub fn instantiate_interchain_account_contract(
         test_ctx: &TestContext,
+        connection_id: &str,
     ) -> Result<String, Box<dyn Error>> {
         let ica_account_code = *test_ctx
             .get_chain(NEUTRON_CHAIN_NAME)
@@ -28,11 +32,7 @@ pub mod ica {
             admin: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
             approved_libraries: vec![],
             remote_domain_information: RemoteDomainInfo {
-                connection_id: test_ctx
-                    .get_connections()
-                    .src(NEUTRON_CHAIN_NAME)
-                    .dest(NOBLE_CHAIN_NAME)
-                    .get(),
+                connection_id: connection_id.to_string(),
                 ica_timeout_seconds: Uint64::new(timeout_seconds),
             },
         };
@@ -56,50 +56,205 @@ pub mod ica {
         Ok(valence_ica.address)
     }
 
-    pub fn register_interchain_account(
+    pub fn query_remote_domain_info(
         test_ctx: &mut TestContext,
         interchain_account_addr: &str,
-    ) -> Result<String, Box<dyn Error>> {
-        info!("Registering the ICA...");
-        contract_execute(
-            test_ctx
-                .get_request_builder()
-                .get_request_builder(NEUTRON_CHAIN_NAME),
-            interchain_account_addr,
-            DEFAULT_KEY,
-            &serde_json::to_string(&valence_account_utils::ica::ExecuteMsg::RegisterIca {})
-                .unwrap(),
-            &format!("{GAS_FLAGS} --amount=100000000{NEUTRON_CHAIN_DENOM}"),
-        )
-        .unwrap();
-        std::thread::sleep(Duration::from_secs(3));
-
-        // We want to check that it's in state created
-        poll_for_ica_state(test_ctx, interchain_account_addr, |state| {
-            matches!(state, IcaState::Created(_))
-        });
-
-        // Get the remote address
+    ) -> Result<RemoteDomainInfo, Box<dyn Error>> {
+        let remote_domain_info: RemoteDomainInfo = serde_json::from_value(
+            contract_query(
+                test_ctx
+                    .get_request_builder()
+                    .get_request_builder(NEUTRON_CHAIN_NAME),
+                interchain_account_addr,
+                &serde_json::to_string(&valence_account_utils::ica::QueryMsg::RemoteDomainInfo {})?,
+            )["data"]
+                .clone(),
+        )?;
+
+        Ok(remote_domain_info)
+    }
+
+    pub fn query_ica_state(
+        test_ctx: &mut TestContext,
+        interchain_account_addr: &str,
+    ) -> Result<IcaState, Box<dyn Error>> {
         let ica_state: IcaState = serde_json::from_value(
             contract_query(
                 test_ctx
                     .get_request_builder()
                     .get_request_builder(NEUTRON_CHAIN_NAME),
                 interchain_account_addr,
-                &serde_json::to_string(&valence_account_utils::ica::QueryMsg::IcaState {}).unwrap(),
+                &serde_json::to_string(&valence_account_utils::ica::QueryMsg::IcaState {})?,
             )["data"]
                 .clone(),
-        )
-        .unwrap();
+        )?;
+
+        Ok(ica_state)
+    }
+
+    /// What registering an ICA in `ica_state` over `connection_id` requires
+    #[derive(Debug, PartialEq)]
+    pub enum RegistrationStep {
+        // already registered on that connection, with this remote address
+        Registered(String),
+        // a previous registration is still waiting for the channel to open
+        AwaitChannel,
+        Register,
+    }
 
-        let remote_address = match ica_state {
-            IcaState::Created(ica_info) => ica_info.address,
-            _ => {
-                unreachable!("Expected IcaState::Created variant");
+    pub fn registration_step(
+        ica_state: &IcaState,
+        connection_id: &str,
+        force: bool,
+    ) -> Result<RegistrationStep, Box<dyn Error>> {
+        match ica_state {
+            IcaState::Created(ica_info) if !force => {
+                if ica_info.controller_connection_id != connection_id {
+                    return Err(format!(
+                        "ICA {} is registered on connection {}, expected {connection_id}",
+                        ica_info.address, ica_info.controller_connection_id
+                    )
+                    .into());
+                }
+                Ok(RegistrationStep::Registered(ica_info.address.clone()))
             }
-        };
+            IcaState::InProgress if !force => Ok(RegistrationStep::AwaitChannel),
+            _ => Ok(RegistrationStep::Register),
+        }
+    }
+
+    /// Registers the ICA over `connection_id` and returns its remote address.
+    /// If the ICA is already registered on that connection (e.g. the setup is re-run after
+    /// a partial failure), the existing remote address is returned instead of registering it again.
+    /// `force` always issues the registration, which the contract only accepts if the ICA is not
+    /// created or its channel is closed.
+    /// The ICA state is polled every `polling_interval` until its channel is open, returning a
+    /// `RegistrationTimeout` error if it is still not open after `timeout`.
+    pub fn register_interchain_account(
+        test_ctx: &mut TestContext,
+        interchain_account_addr: &str,
+        connection_id: &str,
+        force: bool,
+        timeout: Duration,
+        polling_interval: Duration,
+    ) -> Result<String, Box<dyn Error>> {
+        // The contract registers over the connection it was instantiated with
+        let remote_domain_info = query_remote_domain_info(test_ctx, interchain_account_addr)?;
+        if remote_domain_info.connection_id != connection_id {
+            return Err(format!(
+                "ICA contract {interchain_account_addr} registers over connection {}, expected {connection_id}",
+                remote_domain_info.connection_id
+            )
+            .into());
+        }
+
+        let ica_state = query_ica_state(test_ctx, interchain_account_addr)?;
+        match registration_step(&ica_state, connection_id, force)? {
+            RegistrationStep::Registered(remote_address) => {
+                info!("ICA already registered. Remote address: {remote_address}");
+                return Ok(remote_address);
+            }
+            RegistrationStep::AwaitChannel => {
+                info!("ICA registration already in progress...");
+            }
+            RegistrationStep::Register => {
+                info!("Registering the ICA...");
+                contract_execute(
+                    test_ctx
+                        .get_request_builder()
+                        .get_request_builder(NEUTRON_CHAIN_NAME),
+                    interchain_account_addr,
+                    DEFAULT_KEY,
+                    &serde_json::to_string(
+                        &valence_account_utils::ica::ExecuteMsg::RegisterIca {},
+                    )?,
+                    &format!("{GAS_FLAGS} --amount=100000000{NEUTRON_CHAIN_DENOM}"),
+                )?;
+            }
+        }
+
+        let mut polls = 0;
+        let ica_info = wait_for_ica_channel_open(
+            interchain_account_addr,
+            || {
+                polls += 1;
+                // The relayer can get stuck on the channel handshake, restarting it unblocks it
+                if polls % 5 == 0 {
+                    restart_relayer(test_ctx);
+                }
+                Ok(query_ica_state(test_ctx, interchain_account_addr)?)
+            },
+            timeout,
+            polling_interval,
+        )?;
+        let remote_address = ica_info.address;
         info!("Remote address created: {}", remote_address);
 
         Ok(remote_address)
     }
+
+    #[cfg(test)]
+    mod tests {
+        use valence_account_utils::ica::IcaInformation;
+
+        use super::*;
+
+        const CONNECTION: &str = "connection-0";
+
+        fn created_on(connection_id: &str) -> IcaState {
+            IcaState::Created(IcaInformation {
+                address: "noble1remote".to_string(),
+                port_id: "icacontroller-neutron1ica".to_string(),
+                controller_connection_id: connection_id.to_string(),
+            })
+        }
+
+        #[test]
+        fn first_call_registers() {
+            assert_eq!(
+                registration_step(&IcaState::NotCreated, CONNECTION, false).unwrap(),
+                RegistrationStep::Register
+            );
+            // A closed channel is registered again
+            assert_eq!(
+                registration_step(&IcaState::Closed, CONNECTION, false).unwrap(),
+                RegistrationStep::Register
+            );
+        }
+
+        #[test]
+        fn second_call_returns_the_existing_address() {
+            assert_eq!(
+                registration_step(&created_on(CONNECTION), CONNECTION, false).unwrap(),
+                RegistrationStep::Registered("noble1remote".to_string())
+            );
+            // A registration in progress is waited on instead of issued twice
+            assert_eq!(
+                registration_step(&IcaState::InProgress, CONNECTION, false).unwrap(),
+                RegistrationStep::AwaitChannel
+            );
+        }
+
+        #[test]
+        fn force_registers_again() {
+            assert_eq!(
+                registration_step(&created_on(CONNECTION), CONNECTION, true).unwrap(),
+                RegistrationStep::Register
+            );
+            assert_eq!(
+                registration_step(&IcaState::InProgress, CONNECTION, true).unwrap(),
+                RegistrationStep::Register
+            );
+        }
+
+        #[test]
+        fn ica_registered_on_another_connection_is_rejected() {
+            let err =
+                registration_step(&created_on("connection-1"), CONNECTION, false).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "ICA noble1remote is registered on connection connection-1, expected connection-0"
+            );
+        }
+    }
 }
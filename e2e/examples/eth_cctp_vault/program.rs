@@ -1,9 +1,8 @@
 use std::collections::BTreeMap;
 use std::error::Error;
-use std::path::Path;
 use std::time::Duration;
 
-use cosmwasm_std::{Binary, Uint128};
+use cosmwasm_std::{Decimal, Uint128};
 use localic_std::modules::cosmwasm::contract_instantiate;
 use localic_utils::utils::test_context::TestContext;
 use localic_utils::{
@@ -11,16 +10,37 @@ use localic_utils::{
 };
 use log::info;
 
-use valence_e2e::utils::astroport::{setup_astroport_lper_lib, setup_astroport_lwer_lib};
-use valence_e2e::utils::base_account::{approve_library, create_base_accounts};
+use valence_e2e::utils::astroport::{
+    astroport_lper_instantiate_msg, astroport_lwer_instantiate_msg, setup_astroport_lper_lib,
+    setup_astroport_lwer_lib,
+};
+use valence_e2e::utils::base_account::{
+    approve_library, create_base_accounts, wait_for_library_approval,
+};
+use valence_e2e::utils::batch::{
+    batch_instantiate_and_approve, LibraryInstantiation, LibrarySetupMode,
+};
+use valence_e2e::utils::error::{get_code_id, get_transfer_channel, ValenceSetupError};
+use valence_e2e::utils::health::{program_health, ProgramComponents, ProgramHealthReport};
+use valence_e2e::utils::setup_context::{LibraryDeployment, SetupContext};
+use valence_e2e::utils::setup_report::{SetupFailure, SetupReport};
+use valence_e2e::utils::teardown::{teardown, TeardownComponents, TeardownReport};
 
+use valence_astroport_utils::PoolKind;
 use valence_e2e::utils::manager::{
     ASTROPORT_LPER_NAME, ASTROPORT_WITHDRAWER_NAME, BASE_ACCOUNT_NAME, FORWARDER_NAME,
     ICA_CCTP_TRANSFER_NAME, ICA_IBC_TRANSFER_NAME, INTERCHAIN_ACCOUNT_NAME,
     NEUTRON_IBC_TRANSFER_NAME,
 };
-use valence_e2e::utils::vault::{setup_liquidation_fwd_lib, setup_neutron_ibc_transfer_lib};
-use valence_e2e::utils::{LOCAL_CODE_ID_CACHE_PATH_NEUTRON, NOBLE_CHAIN_NAME, UUSDC_DENOM};
+use valence_e2e::utils::vault::{
+    liquidation_fwd_instantiate_msg, neutron_ibc_transfer_instantiate_msg,
+    setup_liquidation_fwd_lib, setup_neutron_ibc_transfer_lib,
+};
+use valence_e2e::utils::{upload_contracts, NOBLE_CHAIN_NAME, UUSDC_DENOM};
+use valence_ica_cctp_transfer::domain::DestinationDomain;
+use valence_ica_cctp_transfer::msg::TransferAmount;
+use valence_ica_cctp_transfer::token::CctpToken;
+use valence_ica_cctp_transfer::utils::evm_address_to_mint_recipient;
 use valence_ica_ibc_transfer::msg::RemoteChainInfo;
 use valence_library_utils::liquidity_utils::AssetData;
 use valence_library_utils::LibraryAccountType;
@@ -29,8 +49,38 @@ use crate::neutron::ica::{instantiate_interchain_account_contract, register_inte
 use crate::strategist::strategy_config;
 use crate::VAULT_NEUTRON_CACHE_PATH;
 
+/// Stablecoin hub the vault moves its funds through: the chain on which the ICAs are
+/// registered, and the denom transferred from it over IBC and burnt from it with CCTP
+#[derive(Clone, Debug, PartialEq)]
+pub struct VaultChainConfig {
+    pub hub_chain_name: String,
+    pub hub_denom: String,
+}
+
+impl Default for VaultChainConfig {
+    fn default() -> Self {
+        Self {
+            hub_chain_name: NOBLE_CHAIN_NAME.to_string(),
+            hub_denom: UUSDC_DENOM.to_string(),
+        }
+    }
+}
+
+impl VaultChainConfig {
+    /// CCTP token burnt as the hub denom
+    pub fn cctp_token(&self) -> Result<CctpToken, Box<dyn Error>> {
+        CctpToken::from_denom(&self.hub_denom)
+            .ok_or_else(|| format!("{} can't be burnt with CCTP", self.hub_denom).into())
+    }
+}
+
+// Same bound as the previous 60 polls of 10 seconds, after which the relayer is assumed stuck
+const ICA_REGISTRATION_TIMEOUT: Duration = Duration::from_secs(600);
+const ICA_REGISTRATION_POLLING_INTERVAL: Duration = Duration::from_secs(10);
+
 pub fn setup_neutron_accounts(
     test_ctx: &mut TestContext,
+    chain_config: &VaultChainConfig,
 ) -> Result<strategy_config::neutron::NeutronAccounts, Box<dyn Error>> {
     let base_account_code_id = test_ctx
         .get_contract()
@@ -46,17 +96,37 @@ pub fn setup_neutron_accounts(
         base_account_code_id,
         NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         vec![],
-        4,
+        &["deposit", "position", "withdraw", "liquidation"],
         None,
     );
 
-    let noble_inbound_interchain_account_addr = instantiate_interchain_account_contract(test_ctx)?;
-    let noble_outbound_interchain_account_addr = instantiate_interchain_account_contract(test_ctx)?;
+    // Both ICAs are registered over the same connection to Noble
+    let noble_connection_id = test_ctx
+        .get_connections()
+        .src(NEUTRON_CHAIN_NAME)
+        .dest(&chain_config.hub_chain_name)
+        .get();
+    let noble_inbound_interchain_account_addr =
+        instantiate_interchain_account_contract(test_ctx, &noble_connection_id)?;
+    let noble_outbound_interchain_account_addr =
+        instantiate_interchain_account_contract(test_ctx, &noble_connection_id)?;
 
-    let inbound_noble_ica_addr =
-        register_interchain_account(test_ctx, &noble_inbound_interchain_account_addr)?;
-    let outbound_noble_ica_addr =
-        register_interchain_account(test_ctx, &noble_outbound_interchain_account_addr)?;
+    let inbound_noble_ica_addr = register_interchain_account(
+        test_ctx,
+        &noble_inbound_interchain_account_addr,
+        &noble_connection_id,
+        false,
+        ICA_REGISTRATION_TIMEOUT,
+        ICA_REGISTRATION_POLLING_INTERVAL,
+    )?;
+    let outbound_noble_ica_addr = register_interchain_account(
+        test_ctx,
+        &noble_outbound_interchain_account_addr,
+        &noble_connection_id,
+        false,
+        ICA_REGISTRATION_TIMEOUT,
+        ICA_REGISTRATION_POLLING_INTERVAL,
+    )?;
 
     let neutron_accounts = strategy_config::neutron::NeutronAccounts {
         noble_inbound_ica: strategy_config::neutron::IcaAccount {
@@ -67,51 +137,220 @@ pub fn setup_neutron_accounts(
             library_account: noble_outbound_interchain_account_addr,
             remote_addr: outbound_noble_ica_addr,
         },
-        deposit: neutron_base_accounts[0].to_string(),
-        position: neutron_base_accounts[1].to_string(),
-        withdraw: neutron_base_accounts[2].to_string(),
-        liquidation: neutron_base_accounts[3].to_string(),
+        deposit: neutron_base_accounts["deposit"].to_string(),
+        position: neutron_base_accounts["position"].to_string(),
+        withdraw: neutron_base_accounts["withdraw"].to_string(),
+        liquidation: neutron_base_accounts["liquidation"].to_string(),
     };
 
     Ok(neutron_accounts)
 }
 
 pub fn upload_neutron_contracts(test_ctx: &mut TestContext) -> Result<(), Box<dyn Error>> {
-    // copy over relevant contracts from artifacts/ to local path
-    let local_contracts_path = Path::new(VAULT_NEUTRON_CACHE_PATH);
-    if !local_contracts_path.exists() {
-        std::fs::create_dir(local_contracts_path)?;
-    }
-
-    for contract in [
-        INTERCHAIN_ACCOUNT_NAME,
-        ASTROPORT_LPER_NAME,
-        ASTROPORT_WITHDRAWER_NAME,
-        NEUTRON_IBC_TRANSFER_NAME,
-        FORWARDER_NAME,
-        ICA_CCTP_TRANSFER_NAME,
-        ICA_IBC_TRANSFER_NAME,
-        BASE_ACCOUNT_NAME,
-    ] {
-        let contract_name = format!("{contract}.wasm");
-        let contract_path = Path::new(&contract_name);
-        let src = Path::new("artifacts/").join(contract_path);
-        let dest = local_contracts_path.join(contract_path);
-        std::fs::copy(src, dest)?;
-    }
-
-    let mut uploader = test_ctx.build_tx_upload_contracts();
-    uploader
-        .with_chain_name(NEUTRON_CHAIN_NAME)
-        .send_with_local_cache(
-            "e2e/examples/eth_cctp_vault/neutron_contracts/",
-            LOCAL_CODE_ID_CACHE_PATH_NEUTRON,
-        )?;
+    upload_contracts(
+        test_ctx,
+        NEUTRON_CHAIN_NAME,
+        &[
+            INTERCHAIN_ACCOUNT_NAME,
+            ASTROPORT_LPER_NAME,
+            ASTROPORT_WITHDRAWER_NAME,
+            NEUTRON_IBC_TRANSFER_NAME,
+            FORWARDER_NAME,
+            ICA_CCTP_TRANSFER_NAME,
+            ICA_IBC_TRANSFER_NAME,
+            BASE_ACCOUNT_NAME,
+        ],
+        VAULT_NEUTRON_CACHE_PATH,
+    )?;
 
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Parameters of the neutron libraries of the program
+#[derive(Clone, Debug, PartialEq)]
+pub struct NeutronLibrariesConfig {
+    // astroport pool the position is entered into, and its LP token denom
+    pub pool: String,
+    pub lp_token_denom: String,
+    pub authorizations: String,
+    pub processor: String,
+    // amount moved from the inbound ICA into the deposit account by a single transfer
+    pub amount: u128,
+    pub usdc_on_neutron: String,
+    pub eth_withdraw_acc: String,
+    // share of the input account balance burnt by a single CCTP transfer
+    pub cctp_split_ratio: Decimal,
+    // (interchain) account the CCTP transfer burns from, the outbound ICA if not set
+    pub cctp_input_account: Option<String>,
+    pub mode: LibrarySetupMode,
+    pub chain_config: VaultChainConfig,
+    // label prefix and migrate admin of the libraries
+    pub deployment: LibraryDeployment,
+}
+
+impl NeutronLibrariesConfig {
+    /// Account the CCTP transfer burns from, `outbound_ica` unless another one was configured
+    pub fn cctp_input_account<'a>(&'a self, outbound_ica: &'a str) -> &'a str {
+        self.cctp_input_account.as_deref().unwrap_or(outbound_ica)
+    }
+}
+
+/// Accumulates the accounts and libraries of the neutron program through named methods,
+/// and sets all of them up in a single `build`
+#[derive(Clone, Debug, Default)]
+pub struct ProgramBuilder {
+    // accounts set up beforehand, created by `build` if not set
+    accounts: Option<strategy_config::neutron::NeutronAccounts>,
+    pool: Option<(String, String)>,
+    authorizations: Option<String>,
+    processor: Option<String>,
+    amount: Option<u128>,
+    usdc_on_neutron: Option<String>,
+    eth_withdraw_acc: Option<String>,
+    cctp_split_ratio: Option<Decimal>,
+    cctp_input_account: Option<String>,
+    mode: LibrarySetupMode,
+    chain_config: VaultChainConfig,
+    deployment: LibraryDeployment,
+    // report of a failed attempt of the setup, whose completed steps are not run again
+    resume_from: Option<SetupReport>,
+}
+
+impl ProgramBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // not used by the vault example, which lets `build` create the accounts
+    #[allow(dead_code)]
+    pub fn with_accounts(mut self, accounts: strategy_config::neutron::NeutronAccounts) -> Self {
+        self.accounts = Some(accounts);
+        self
+    }
+
+    pub fn with_pool(mut self, pool: &str, lp_token_denom: &str) -> Self {
+        self.pool = Some((pool.to_string(), lp_token_denom.to_string()));
+        self
+    }
+
+    pub fn with_authorizations(mut self, authorizations: &str) -> Self {
+        self.authorizations = Some(authorizations.to_string());
+        self
+    }
+
+    pub fn with_processor(mut self, processor: &str) -> Self {
+        self.processor = Some(processor.to_string());
+        self
+    }
+
+    pub fn with_transfer_amount(mut self, amount: u128) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn with_usdc_on_neutron(mut self, usdc_on_neutron: &str) -> Self {
+        self.usdc_on_neutron = Some(usdc_on_neutron.to_string());
+        self
+    }
+
+    pub fn with_eth_withdraw_account(mut self, eth_withdraw_acc: &str) -> Self {
+        self.eth_withdraw_acc = Some(eth_withdraw_acc.to_string());
+        self
+    }
+
+    pub fn with_cctp_split_ratio(mut self, cctp_split_ratio: Decimal) -> Self {
+        self.cctp_split_ratio = Some(cctp_split_ratio);
+        self
+    }
+
+    // not used by the vault example, which burns from the outbound ICA
+    #[allow(dead_code)]
+    pub fn with_cctp_input_account(mut self, cctp_input_account: &str) -> Self {
+        self.cctp_input_account = Some(cctp_input_account.to_string());
+        self
+    }
+
+    pub fn with_setup_mode(mut self, mode: LibrarySetupMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_chain_config(mut self, chain_config: VaultChainConfig) -> Self {
+        self.chain_config = chain_config;
+        self
+    }
+
+    pub fn with_deployment(mut self, deployment: LibraryDeployment) -> Self {
+        self.deployment = deployment;
+        self
+    }
+
+    // not used by the vault example, whose setup is not retried
+    #[allow(dead_code)]
+    pub fn with_resume_from(mut self, report: SetupReport) -> Self {
+        self.resume_from = Some(report);
+        self
+    }
+
+    /// Config of the libraries, erroring on the first missing parameter
+    pub fn libraries_config(&self) -> Result<NeutronLibrariesConfig, Box<dyn Error>> {
+        fn required<T: Clone>(value: &Option<T>, name: &str) -> Result<T, Box<dyn Error>> {
+            value
+                .clone()
+                .ok_or_else(|| format!("ProgramBuilder: missing {name}").into())
+        }
+
+        let (pool, lp_token_denom) = required(&self.pool, "pool")?;
+
+        Ok(NeutronLibrariesConfig {
+            pool,
+            lp_token_denom,
+            authorizations: required(&self.authorizations, "authorizations")?,
+            processor: required(&self.processor, "processor")?,
+            amount: required(&self.amount, "transfer amount")?,
+            usdc_on_neutron: required(&self.usdc_on_neutron, "usdc on neutron denom")?,
+            eth_withdraw_acc: required(&self.eth_withdraw_acc, "eth withdraw account")?,
+            cctp_split_ratio: required(&self.cctp_split_ratio, "cctp split ratio")?,
+            cctp_input_account: self.cctp_input_account.clone(),
+            mode: self.mode,
+            chain_config: self.chain_config.clone(),
+            deployment: self.deployment.clone(),
+        })
+    }
+
+    /// Sets up the accounts (unless they were provided) and then the libraries of the program,
+    /// along with the report of the library setup steps. The config is checked before anything is
+    /// set up. If a library step fails, the error is a `SetupFailure` whose report can be resumed from
+    pub fn build(
+        self,
+        test_ctx: &mut TestContext,
+    ) -> Result<
+        (
+            strategy_config::neutron::NeutronAccounts,
+            strategy_config::neutron::NeutronLibraries,
+            SetupReport,
+        ),
+        Box<dyn Error>,
+    > {
+        let config = self.libraries_config()?;
+
+        let accounts = match self.accounts {
+            Some(accounts) => accounts,
+            None => setup_neutron_accounts(test_ctx, &config.chain_config)?,
+        };
+        let report = match self.resume_from {
+            Some(previous) => SetupReport::resuming(previous),
+            None => SetupReport::new(),
+        };
+        let (libraries, report) =
+            setup_neutron_libraries_from_config(test_ctx, &accounts, &config, report)?;
+
+        Ok((accounts, libraries, report))
+    }
+}
+
+// positional equivalent of `ProgramBuilder`, which the vault example uses instead
+#[allow(dead_code, clippy::too_many_arguments)]
 pub fn setup_neutron_libraries(
     test_ctx: &mut TestContext,
     neutron_program_accounts: &strategy_config::neutron::NeutronAccounts,
@@ -122,94 +361,216 @@ pub fn setup_neutron_libraries(
     usdc_on_neutron: &str,
     eth_withdraw_acc: String,
     lp_token_denom: &str,
+    cctp_split_ratio: Decimal,
+    mode: LibrarySetupMode,
+    chain_config: &VaultChainConfig,
+) -> Result<(strategy_config::neutron::NeutronLibraries, SetupReport), Box<dyn Error>> {
+    let config = ProgramBuilder::new()
+        .with_pool(pool, lp_token_denom)
+        .with_authorizations(authorizations)
+        .with_processor(processor)
+        .with_transfer_amount(amount)
+        .with_usdc_on_neutron(usdc_on_neutron)
+        .with_eth_withdraw_account(&eth_withdraw_acc)
+        .with_cctp_split_ratio(cctp_split_ratio)
+        .with_setup_mode(mode)
+        .with_chain_config(chain_config.clone())
+        .libraries_config()?;
+
+    Ok(setup_neutron_libraries_from_config(
+        test_ctx,
+        neutron_program_accounts,
+        &config,
+        SetupReport::new(),
+    )?)
+}
+
+// runs the setup steps of the libraries, recording them in `report`. on failure, the report
+// lists the steps completed before it, so that a retry can resume from it
+fn setup_neutron_libraries_from_config(
+    test_ctx: &mut TestContext,
+    neutron_program_accounts: &strategy_config::neutron::NeutronAccounts,
+    config: &NeutronLibrariesConfig,
+    mut report: SetupReport,
+) -> Result<(strategy_config::neutron::NeutronLibraries, SetupReport), SetupFailure> {
+    match run_neutron_libraries_setup(test_ctx, neutron_program_accounts, config, &mut report) {
+        Ok(libraries) => {
+            info!(
+                "Neutron libraries set up in {:?}: {}",
+                report.total_duration(),
+                serde_json::to_string(&report).unwrap_or_default()
+            );
+            Ok((libraries, report))
+        }
+        Err(error) => Err(SetupFailure { report, error }),
+    }
+}
+
+fn run_neutron_libraries_setup(
+    test_ctx: &mut TestContext,
+    neutron_program_accounts: &strategy_config::neutron::NeutronAccounts,
+    config: &NeutronLibrariesConfig,
+    report: &mut SetupReport,
 ) -> Result<strategy_config::neutron::NeutronLibraries, Box<dyn Error>> {
     let astro_cl_pool_asset_data = AssetData {
         asset1: NEUTRON_CHAIN_DENOM.to_string(),
-        asset2: usdc_on_neutron.to_string(),
+        asset2: config.usdc_on_neutron.to_string(),
+        additional_assets: vec![],
+    };
+
+    let libraries = match config.mode {
+        LibrarySetupMode::Serial => setup_neutron_libraries_serial(
+            test_ctx,
+            neutron_program_accounts,
+            config,
+            astro_cl_pool_asset_data,
+            report,
+        )?,
+        LibrarySetupMode::Batched => setup_neutron_libraries_batched(
+            test_ctx,
+            neutron_program_accounts,
+            config,
+            astro_cl_pool_asset_data,
+            report,
+        )?,
     };
 
+    report.step("approve_strategist", || {
+        info!("approving strategist on liquidation account...");
+        approve_library(
+            test_ctx,
+            NEUTRON_CHAIN_NAME,
+            DEFAULT_KEY,
+            &neutron_program_accounts.liquidation.to_string(),
+            NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
+            None,
+        );
+        Ok::<_, Box<dyn Error>>(())
+    })?;
+
+    Ok(libraries)
+}
+
+// instantiates and approves the libraries one by one
+fn setup_neutron_libraries_serial(
+    test_ctx: &mut TestContext,
+    neutron_program_accounts: &strategy_config::neutron::NeutronAccounts,
+    config: &NeutronLibrariesConfig,
+    astro_cl_pool_asset_data: AssetData,
+    report: &mut SetupReport,
+) -> Result<strategy_config::neutron::NeutronLibraries, Box<dyn Error>> {
+    let NeutronLibrariesConfig {
+        pool,
+        lp_token_denom,
+        authorizations,
+        processor,
+        amount,
+        usdc_on_neutron,
+        eth_withdraw_acc,
+        cctp_split_ratio,
+        chain_config,
+        deployment,
+        ..
+    } = config;
+    let (amount, cctp_split_ratio) = (*amount, *cctp_split_ratio);
+
     // library to enter into the position from the deposit account
     // and route the issued shares into the into the position account
-    let astro_lper_lib = setup_astroport_lper_lib(
-        test_ctx,
-        neutron_program_accounts.deposit.to_string(),
-        neutron_program_accounts.position.to_string(),
-        astro_cl_pool_asset_data.clone(),
-        pool.to_string(),
-        processor.to_string(),
-        authorizations.to_string(),
-    )?;
+    let astro_lper_lib = report.step("astro_lper", || {
+        setup_astroport_lper_lib(
+            test_ctx,
+            neutron_program_accounts.deposit.to_string(),
+            neutron_program_accounts.position.to_string(),
+            astro_cl_pool_asset_data.clone(),
+            pool.to_string(),
+            PoolKind::ConcentratedLiquidity,
+            // leftovers of a provision already stay in the deposit account (the input account)
+            None,
+            processor.to_string(),
+            authorizations.to_string(),
+            deployment,
+        )
+    })?;
 
     // library to forward the required amount of shares, from the position account
     // to the liquidation account, needed to fulfill the withdraw obligations
-    let forwarder_lib = setup_liquidation_fwd_lib(
-        test_ctx,
-        neutron_program_accounts.position.to_string(),
-        neutron_program_accounts.liquidation.to_string(),
-        lp_token_denom,
-    )?;
+    let forwarder_lib = report.step("liquidation_forwarder", || {
+        setup_liquidation_fwd_lib(
+            test_ctx,
+            neutron_program_accounts.position.to_string(),
+            neutron_program_accounts.liquidation.to_string(),
+            lp_token_denom,
+            None,
+            deployment,
+        )
+    })?;
 
     // library to withdraw the position held by the position account
     // and route the underlying funds into the withdraw account
-    let astro_lwer_lib = setup_astroport_lwer_lib(
-        test_ctx,
-        neutron_program_accounts.liquidation.to_string(),
-        neutron_program_accounts.withdraw.to_string(),
-        astro_cl_pool_asset_data.clone(),
-        pool.to_string(),
-        processor.to_string(),
-    )?;
+    let astro_lwer_lib = report.step("astro_lwer", || {
+        setup_astroport_lwer_lib(
+            test_ctx,
+            neutron_program_accounts.liquidation.to_string(),
+            neutron_program_accounts.withdraw.to_string(),
+            astro_cl_pool_asset_data.clone(),
+            pool.to_string(),
+            PoolKind::ConcentratedLiquidity,
+            processor.to_string(),
+            deployment,
+        )
+    })?;
 
     // library to move USDC from a program-owned ICA on noble
     // into the deposit account on neutron
-    let ica_ibc_transfer_lib = setup_ica_ibc_transfer_lib(
-        test_ctx,
-        &neutron_program_accounts.noble_inbound_ica.library_account,
-        &neutron_program_accounts.deposit,
-        amount,
-    )?;
+    let ica_ibc_transfer_lib = report.step("valence_ica_ibc_transfer", || {
+        setup_ica_ibc_transfer_lib(
+            test_ctx,
+            chain_config,
+            &neutron_program_accounts.noble_inbound_ica.library_account,
+            &neutron_program_accounts.deposit,
+            amount,
+            deployment,
+        )
+    })?;
 
-    // library to move USDC from a program-owned ICA on noble
-    // into the withdraw account on ethereum
-    let cctp_forwarder_lib_addr = setup_cctp_forwarder_lib(
-        test_ctx,
-        neutron_program_accounts
-            .noble_outbound_ica
-            .library_account
-            .to_string(),
-        eth_withdraw_acc,
-        processor.to_string(),
-        authorizations.to_string(),
-        amount,
-    )?;
+    // library to move USDC from a program-owned ICA on noble (the outbound ICA
+    // unless configured otherwise) into the withdraw account on ethereum.
+    // only the `cctp_split_ratio` share of the ICA balance at execution time is burned
+    let cctp_input_account =
+        config.cctp_input_account(&neutron_program_accounts.noble_outbound_ica.library_account);
+    let cctp_forwarder_lib_addr = report.step("cctp_transfer", || {
+        setup_cctp_forwarder_lib(
+            test_ctx,
+            cctp_input_account.to_string(),
+            eth_withdraw_acc.to_string(),
+            processor.to_string(),
+            authorizations.to_string(),
+            cctp_split_ratio,
+            chain_config.cctp_token()?,
+            deployment,
+        )
+    })?;
 
     // library to move USDC from the withdraw account on neutron
     // into a program-owned ICA on noble
-    let neutron_ibc_transfer_lib = setup_neutron_ibc_transfer_lib(
-        test_ctx,
-        neutron_program_accounts.withdraw.to_string(),
-        neutron_program_accounts
-            .noble_outbound_ica
-            .remote_addr
-            .to_string(),
-        usdc_on_neutron,
-        authorizations.to_string(),
-        processor.to_string(),
-        NOBLE_CHAIN_NAME,
-        None,
-    )?;
-
-    info!("approving strategist on liquidation account...");
-    approve_library(
-        test_ctx,
-        NEUTRON_CHAIN_NAME,
-        DEFAULT_KEY,
-        &neutron_program_accounts.liquidation.to_string(),
-        NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
-        None,
-    );
+    let neutron_ibc_transfer_lib = report.step("neutron_ibc_transfer", || {
+        setup_neutron_ibc_transfer_lib(
+            test_ctx,
+            neutron_program_accounts.withdraw.to_string(),
+            neutron_program_accounts
+                .noble_outbound_ica
+                .remote_addr
+                .to_string(),
+            usdc_on_neutron,
+            authorizations.to_string(),
+            processor.to_string(),
+            &chain_config.hub_chain_name,
+            None,
+            deployment,
+        )
+    })?;
 
-    let libraries = strategy_config::neutron::NeutronLibraries {
+    Ok(strategy_config::neutron::NeutronLibraries {
         astroport_lper: astro_lper_lib,
         astroport_lwer: astro_lwer_lib,
         noble_inbound_transfer: ica_ibc_transfer_lib,
@@ -218,18 +579,312 @@ pub fn setup_neutron_libraries(
         liquidation_forwarder: forwarder_lib,
         authorizations: authorizations.to_string(),
         processor: processor.to_string(),
+    })
+}
+
+// instantiates all the libraries in a single tx, then approves them in a single tx.
+// the libraries are configured exactly as in the serial setup
+fn setup_neutron_libraries_batched(
+    test_ctx: &mut TestContext,
+    neutron_program_accounts: &strategy_config::neutron::NeutronAccounts,
+    config: &NeutronLibrariesConfig,
+    astro_cl_pool_asset_data: AssetData,
+    report: &mut SetupReport,
+) -> Result<strategy_config::neutron::NeutronLibraries, Box<dyn Error>> {
+    let NeutronLibrariesConfig {
+        pool,
+        lp_token_denom,
+        authorizations,
+        processor,
+        amount,
+        usdc_on_neutron,
+        eth_withdraw_acc,
+        cctp_split_ratio,
+        chain_config,
+        deployment,
+        ..
+    } = config;
+    let (amount, cctp_split_ratio) = (*amount, *cctp_split_ratio);
+    let accounts = neutron_program_accounts;
+    let cctp_input_account =
+        config.cctp_input_account(&accounts.noble_outbound_ica.library_account);
+
+    let libraries = [
+        LibraryInstantiation::new(
+            &deployment.label("astro_lper"),
+            get_code_id(test_ctx, NEUTRON_CHAIN_NAME, ASTROPORT_LPER_NAME)?,
+            &astroport_lper_instantiate_msg(
+                &accounts.deposit,
+                &accounts.position,
+                astro_cl_pool_asset_data.clone(),
+                pool.to_string(),
+                PoolKind::ConcentratedLiquidity,
+                None,
+            ),
+            &accounts.deposit,
+        )?,
+        LibraryInstantiation::new(
+            &deployment.label("liquidation_forwarder"),
+            get_code_id(test_ctx, NEUTRON_CHAIN_NAME, FORWARDER_NAME)?,
+            &liquidation_fwd_instantiate_msg(
+                &accounts.position,
+                &accounts.liquidation,
+                lp_token_denom,
+                None,
+            ),
+            &accounts.position,
+        )?,
+        LibraryInstantiation::new(
+            &deployment.label("astro_lwer"),
+            get_code_id(test_ctx, NEUTRON_CHAIN_NAME, ASTROPORT_WITHDRAWER_NAME)?,
+            &astroport_lwer_instantiate_msg(
+                &accounts.liquidation,
+                &accounts.withdraw,
+                astro_cl_pool_asset_data,
+                pool.to_string(),
+                PoolKind::ConcentratedLiquidity,
+            ),
+            &accounts.liquidation,
+        )?,
+        LibraryInstantiation::new(
+            &deployment.label("valence_ica_ibc_transfer"),
+            get_code_id(test_ctx, NEUTRON_CHAIN_NAME, ICA_IBC_TRANSFER_NAME)?,
+            &ica_ibc_transfer_instantiate_msg(
+                chain_config,
+                get_transfer_channel(test_ctx, &chain_config.hub_chain_name, NEUTRON_CHAIN_NAME)?,
+                &accounts.noble_inbound_ica.library_account,
+                &accounts.deposit,
+                amount,
+            ),
+            &accounts.noble_inbound_ica.library_account,
+        )?,
+        LibraryInstantiation::new(
+            &deployment.label("cctp_transfer"),
+            get_code_id(test_ctx, NEUTRON_CHAIN_NAME, ICA_CCTP_TRANSFER_NAME)?,
+            &cctp_forwarder_instantiate_msg(
+                cctp_input_account,
+                eth_withdraw_acc,
+                cctp_split_ratio,
+                chain_config.cctp_token()?,
+            )?,
+            cctp_input_account,
+        )?,
+        LibraryInstantiation::new(
+            &deployment.label("neutron_ibc_transfer"),
+            get_code_id(test_ctx, NEUTRON_CHAIN_NAME, NEUTRON_IBC_TRANSFER_NAME)?,
+            &neutron_ibc_transfer_instantiate_msg(
+                test_ctx,
+                &accounts.withdraw,
+                &accounts.noble_outbound_ica.remote_addr,
+                usdc_on_neutron,
+                &chain_config.hub_chain_name,
+                None,
+            )?,
+            &accounts.withdraw,
+        )?,
+    ];
+
+    let libraries = libraries.map(|library| library.with_admin(deployment.admin.clone()));
+
+    // addresses are returned in the order of `libraries`. a single step, as a failed batch is reverted
+    let addresses: Vec<String> = report.step("batch_instantiate_and_approve", || {
+        batch_instantiate_and_approve(test_ctx, &libraries)
+    })?;
+
+    Ok(strategy_config::neutron::NeutronLibraries {
+        astroport_lper: addresses[0].clone(),
+        liquidation_forwarder: addresses[1].clone(),
+        astroport_lwer: addresses[2].clone(),
+        noble_inbound_transfer: addresses[3].clone(),
+        noble_cctp_transfer: addresses[4].clone(),
+        neutron_ibc_transfer: addresses[5].clone(),
+        authorizations: authorizations.to_string(),
+        processor: processor.to_string(),
+    })
+}
+
+/// Reports whether every neutron account is instantiated, every library approved
+/// on its input account, and both noble ICAs registered
+pub fn neutron_program_health(
+    test_ctx: &mut TestContext,
+    accounts: &strategy_config::neutron::NeutronAccounts,
+    libraries: &strategy_config::neutron::NeutronLibraries,
+) -> ProgramHealthReport {
+    let components = ProgramComponents::default()
+        .with_account("deposit", &accounts.deposit)
+        .with_account("position", &accounts.position)
+        .with_account("withdraw", &accounts.withdraw)
+        .with_account("liquidation", &accounts.liquidation)
+        .with_interchain_account(
+            "noble_inbound_ica",
+            &accounts.noble_inbound_ica.library_account,
+        )
+        .with_interchain_account(
+            "noble_outbound_ica",
+            &accounts.noble_outbound_ica.library_account,
+        )
+        .with_library(
+            "astroport_lper",
+            &libraries.astroport_lper,
+            &accounts.deposit,
+        )
+        .with_library(
+            "liquidation_forwarder",
+            &libraries.liquidation_forwarder,
+            &accounts.position,
+        )
+        .with_library(
+            "astroport_lwer",
+            &libraries.astroport_lwer,
+            &accounts.liquidation,
+        )
+        .with_library(
+            "neutron_ibc_transfer",
+            &libraries.neutron_ibc_transfer,
+            &accounts.withdraw,
+        )
+        .with_library(
+            "noble_inbound_transfer",
+            &libraries.noble_inbound_transfer,
+            &accounts.noble_inbound_ica.library_account,
+        )
+        .with_library(
+            "noble_cctp_transfer",
+            &libraries.noble_cctp_transfer,
+            &accounts.noble_outbound_ica.library_account,
+        );
+
+    program_health(test_ctx, NEUTRON_CHAIN_NAME, &components)
+}
+
+/// Drains every neutron account of the program into `admin`, returning the balances of
+/// both noble ICAs over IBC. If `remove_approvals` is set, the libraries are then removed
+/// from their input accounts, so that the drained program can't move funds anymore
+pub fn teardown_program(
+    test_ctx: &mut TestContext,
+    accounts: &strategy_config::neutron::NeutronAccounts,
+    libraries: &strategy_config::neutron::NeutronLibraries,
+    admin: &str,
+    remove_approvals: bool,
+) -> Result<TeardownReport, Box<dyn Error>> {
+    let noble_to_neutron_channel =
+        get_transfer_channel(test_ctx, NOBLE_CHAIN_NAME, NEUTRON_CHAIN_NAME)?;
+
+    let components = TeardownComponents::default()
+        .with_account("deposit", &accounts.deposit)
+        .with_account("position", &accounts.position)
+        .with_account("withdraw", &accounts.withdraw)
+        .with_account("liquidation", &accounts.liquidation)
+        .with_interchain_account(
+            "noble_inbound_ica",
+            &accounts.noble_inbound_ica.library_account,
+            &accounts.noble_inbound_ica.remote_addr,
+            &noble_to_neutron_channel,
+        )
+        .with_interchain_account(
+            "noble_outbound_ica",
+            &accounts.noble_outbound_ica.library_account,
+            &accounts.noble_outbound_ica.remote_addr,
+            &noble_to_neutron_channel,
+        )
+        .with_library(
+            "astroport_lper",
+            &libraries.astroport_lper,
+            &accounts.deposit,
+        )
+        .with_library(
+            "liquidation_forwarder",
+            &libraries.liquidation_forwarder,
+            &accounts.position,
+        )
+        .with_library(
+            "astroport_lwer",
+            &libraries.astroport_lwer,
+            &accounts.liquidation,
+        )
+        .with_library(
+            "neutron_ibc_transfer",
+            &libraries.neutron_ibc_transfer,
+            &accounts.withdraw,
+        )
+        .with_library(
+            "noble_inbound_transfer",
+            &libraries.noble_inbound_transfer,
+            &accounts.noble_inbound_ica.library_account,
+        )
+        .with_library(
+            "noble_cctp_transfer",
+            &libraries.noble_cctp_transfer,
+            &accounts.noble_outbound_ica.library_account,
+        );
+
+    let report = teardown(
+        test_ctx,
+        NEUTRON_CHAIN_NAME,
+        NOBLE_CHAIN_NAME,
+        DEFAULT_KEY,
+        &components,
+        admin,
+        remove_approvals,
+    )?;
+    info!("Program teardown: {}", serde_json::to_string(&report)?);
+
+    Ok(report)
+}
+
+/// Instantiate message of the cctp transfer library, burning the `split_ratio`
+/// share of the input account `token` balance towards `output_addr` on ethereum
+pub fn cctp_forwarder_instantiate_msg(
+    input_account: &str,
+    output_addr: &str,
+    split_ratio: Decimal,
+    token: CctpToken,
+) -> Result<
+    valence_library_utils::msg::InstantiateMsg<valence_ica_cctp_transfer::msg::LibraryConfig>,
+    Box<dyn Error>,
+> {
+    let mint_recipient = evm_address_to_mint_recipient(output_addr)?;
+
+    let cctp_transfer_config = valence_ica_cctp_transfer::msg::LibraryConfig {
+        input_addr: LibraryAccountType::Addr(input_account.to_string()),
+        amount: TransferAmount::Percentage(split_ratio),
+        denom: token.denom().to_string(),
+        destination_domain: DestinationDomain::Ethereum,
+        allow_unknown_domain: false,
+        mint_recipient,
+        max_fee: None,
+        min_finality_threshold: None,
+        destination_caller: None,
+        max_amount: None,
+        max_amount_behavior: Default::default(),
+        rounding: Default::default(),
+        attestation_sla: None,
+        decimals: None,
+        burn_cooldown: None,
+        burn_legs: vec![],
     };
 
-    Ok(libraries)
+    Ok(
+        valence_library_utils::msg::InstantiateMsg::<valence_ica_cctp_transfer::msg::LibraryConfig> {
+            // TODO: uncomment to not bypass authorizations/processor logic
+            // owner: authorizations.to_string(),
+            // processor: processor.to_string(),
+            owner: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
+            processor: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
+            config: cctp_transfer_config,
+        },
+    )
 }
 
 pub fn setup_cctp_forwarder_lib(
     test_ctx: &mut TestContext,
     input_account: String,
-    mut output_addr: String,
+    output_addr: String,
     _processor: String,
     _authorizations: String,
-    amount: u128,
+    split_ratio: Decimal,
+    token: CctpToken,
+    deployment: &LibraryDeployment,
 ) -> Result<String, Box<dyn Error>> {
     let ica_cctp_transfer_code_id = test_ctx
         .get_contract()
@@ -238,30 +893,8 @@ pub fn setup_cctp_forwarder_lib(
         .code_id
         .unwrap();
 
-    let trimmed_addr = output_addr.split_off(2);
-    let mut mint_recipient = vec![0u8; 32];
-
-    let addr_bytes = hex::decode(trimmed_addr).unwrap();
-    mint_recipient[(32 - addr_bytes.len())..].copy_from_slice(&addr_bytes);
-
-    let cctp_transfer_config = valence_ica_cctp_transfer::msg::LibraryConfig {
-        input_addr: LibraryAccountType::Addr(input_account.to_string()),
-        amount: (amount / 2).into(),
-        denom: UUSDC_DENOM.to_string(),
-        destination_domain_id: 0,
-        mint_recipient: Binary::from(mint_recipient),
-    };
-
-    let ica_cctp_transfer_instantiate_msg = valence_library_utils::msg::InstantiateMsg::<
-        valence_ica_cctp_transfer::msg::LibraryConfig,
-    > {
-        // TODO: uncomment to not bypass authorizations/processor logic
-        // owner: authorizations.to_string(),
-        // processor: processor.to_string(),
-        owner: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
-        processor: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
-        config: cctp_transfer_config,
-    };
+    let ica_cctp_transfer_instantiate_msg =
+        cctp_forwarder_instantiate_msg(&input_account, &output_addr, split_ratio, token)?;
 
     let cctp_transfer_lib = contract_instantiate(
         test_ctx
@@ -270,8 +903,8 @@ pub fn setup_cctp_forwarder_lib(
         DEFAULT_KEY,
         ica_cctp_transfer_code_id,
         &serde_json::to_string(&ica_cctp_transfer_instantiate_msg)?,
-        "cctp_transfer",
-        None,
+        &deployment.label("cctp_transfer"),
+        deployment.admin.clone(),
         "",
     )?;
     info!("cctp transfer lib: {}", cctp_transfer_lib.address);
@@ -285,76 +918,480 @@ pub fn setup_cctp_forwarder_lib(
         cctp_transfer_lib.address.to_string(),
         None,
     );
+    // the input account is not necessarily the outbound ICA, make sure it approved the library
+    wait_for_library_approval(
+        test_ctx,
+        NEUTRON_CHAIN_NAME,
+        &input_account,
+        &cctp_transfer_lib.address,
+    )?;
 
     Ok(cctp_transfer_lib.address)
 }
 
-pub fn setup_ica_ibc_transfer_lib(
-    test_ctx: &mut TestContext,
+/// Instantiate message of the ica ibc transfer library, moving `amount_to_transfer`
+/// of the hub denom from the ICA on the hub chain to `neutron_deposit_acc`,
+/// through the `hub_to_neutron_channel_id` transfer channel
+pub fn ica_ibc_transfer_instantiate_msg(
+    chain_config: &VaultChainConfig,
+    hub_to_neutron_channel_id: String,
     interchain_account_addr: &str,
     neutron_deposit_acc: &str,
     amount_to_transfer: u128,
-) -> Result<String, Box<dyn Error>> {
-    let ica_ibc_transfer_lib_code = *test_ctx
-        .get_chain(NEUTRON_CHAIN_NAME)
-        .contract_codes
-        .get(ICA_IBC_TRANSFER_NAME)
-        .unwrap();
-
-    info!("ica ibc transfer lib code: {ica_ibc_transfer_lib_code}");
-
-    info!("Instantiating the ICA IBC transfer contract...");
-    let ica_ibc_transfer_instantiate_msg = valence_library_utils::msg::InstantiateMsg::<
-        valence_ica_ibc_transfer::msg::LibraryConfig,
-    > {
+) -> valence_library_utils::msg::InstantiateMsg<valence_ica_ibc_transfer::msg::LibraryConfig> {
+    valence_library_utils::msg::InstantiateMsg::<valence_ica_ibc_transfer::msg::LibraryConfig> {
         owner: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         processor: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         config: valence_ica_ibc_transfer::msg::LibraryConfig {
             input_addr: LibraryAccountType::Addr(interchain_account_addr.to_string()),
             amount: Uint128::new(amount_to_transfer),
-            denom: UUSDC_DENOM.to_string(),
+            denom: chain_config.hub_denom.clone(),
             receiver: neutron_deposit_acc.to_string(),
             memo: "".to_string(),
+            memo_template: None,
             remote_chain_info: RemoteChainInfo {
-                channel_id: test_ctx
-                    .get_transfer_channels()
-                    .src(NOBLE_CHAIN_NAME)
-                    .dest(NEUTRON_CHAIN_NAME)
-                    .get(),
+                channel_id: hub_to_neutron_channel_id,
                 ibc_transfer_timeout: None,
+                fallback_channel_ids: vec![],
+                counterparty_channel_ids: BTreeMap::default(),
             },
             denom_to_pfm_map: BTreeMap::default(),
             eureka_config: None,
+            min_transfer_amount: None,
+            recredit_failed_transfers: false,
+            expected_base_denom: None,
+            max_memo_bytes: None,
+            post_transfer_callback: None,
         },
-    };
+    }
+}
 
-    let ica_ibc_transfer = contract_instantiate(
-        test_ctx
-            .get_request_builder()
-            .get_request_builder(NEUTRON_CHAIN_NAME),
+pub fn setup_ica_ibc_transfer_lib(
+    test_ctx: &mut impl SetupContext,
+    chain_config: &VaultChainConfig,
+    interchain_account_addr: &str,
+    neutron_deposit_acc: &str,
+    amount_to_transfer: u128,
+    deployment: &LibraryDeployment,
+) -> Result<String, ValenceSetupError> {
+    let ica_ibc_transfer_lib_code = test_ctx.code_id(NEUTRON_CHAIN_NAME, ICA_IBC_TRANSFER_NAME)?;
+
+    info!("ica ibc transfer lib code: {ica_ibc_transfer_lib_code}");
+
+    info!("Instantiating the ICA IBC transfer contract...");
+    let ica_ibc_transfer_instantiate_msg = ica_ibc_transfer_instantiate_msg(
+        chain_config,
+        test_ctx.transfer_channel(&chain_config.hub_chain_name, NEUTRON_CHAIN_NAME)?,
+        interchain_account_addr,
+        neutron_deposit_acc,
+        amount_to_transfer,
+    );
+
+    let ica_ibc_transfer = test_ctx.instantiate_contract(
+        NEUTRON_CHAIN_NAME,
         DEFAULT_KEY,
         ica_ibc_transfer_lib_code,
         &serde_json::to_string(&ica_ibc_transfer_instantiate_msg)?,
-        "valence_ica_ibc_transfer",
-        None,
-        "",
+        &deployment.label("valence_ica_ibc_transfer"),
+        deployment.admin.as_deref(),
     )?;
-    info!(
-        "ICA IBC transfer contract instantiated. Address: {}",
-        ica_ibc_transfer.address
-    );
+    info!("ICA IBC transfer contract instantiated. Address: {ica_ibc_transfer}");
 
     info!("Approving the ICA IBC transfer library...");
-    approve_library(
-        test_ctx,
+    test_ctx.approve_library(
         NEUTRON_CHAIN_NAME,
         DEFAULT_KEY,
         interchain_account_addr,
-        ica_ibc_transfer.address.to_string(),
-        None,
-    );
+        &ica_ibc_transfer,
+    )?;
+
+    Ok(ica_ibc_transfer)
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_e2e::utils::mocks::test_context::{MockAction, MockTestContext};
+    use valence_e2e::utils::setup_report::StepStatus;
+
+    use super::*;
+
+    const ICA: &str = "neutron1hubica";
+    const DEPOSIT: &str = "neutron1deposit";
+    const ETH_WITHDRAW: &str = "0x0000000000000000000000000000000000000001";
+
+    fn eurc_hub() -> VaultChainConfig {
+        VaultChainConfig {
+            hub_chain_name: "stablehub".to_string(),
+            hub_denom: "ueurc".to_string(),
+        }
+    }
+
+    #[test]
+    fn default_hub_is_noble_usdc() {
+        let chain_config = VaultChainConfig::default();
+        assert_eq!(chain_config.hub_chain_name, NOBLE_CHAIN_NAME);
+        assert_eq!(chain_config.hub_denom, UUSDC_DENOM);
+        assert_eq!(chain_config.cctp_token().unwrap(), CctpToken::Usdc);
+    }
+
+    #[test]
+    fn hub_config_propagates_to_library_configs() {
+        let chain_config = eurc_hub();
+
+        let ica_ibc_transfer = ica_ibc_transfer_instantiate_msg(
+            &chain_config,
+            "channel-7".to_string(),
+            ICA,
+            DEPOSIT,
+            1_000,
+        );
+        assert_eq!(ica_ibc_transfer.config.denom, "ueurc");
+        assert_eq!(
+            ica_ibc_transfer.config.remote_chain_info.channel_id,
+            "channel-7"
+        );
+        assert_eq!(ica_ibc_transfer.config.receiver, DEPOSIT);
+
+        let cctp_transfer = cctp_forwarder_instantiate_msg(
+            ICA,
+            ETH_WITHDRAW,
+            Decimal::percent(50),
+            chain_config.cctp_token().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(cctp_transfer.config.denom, "ueurc");
+    }
+
+    #[test]
+    fn program_builder_accumulates_the_libraries_config() {
+        let builder = ProgramBuilder::new()
+            .with_pool("neutron1pool", "factory/neutron1pool/astroport/share")
+            .with_authorizations("neutron1authorizations")
+            .with_processor("neutron1processor")
+            .with_transfer_amount(1_000)
+            .with_usdc_on_neutron("ibc/usdc")
+            .with_eth_withdraw_account(ETH_WITHDRAW)
+            .with_cctp_split_ratio(Decimal::percent(50))
+            .with_chain_config(eurc_hub());
+
+        assert_eq!(
+            builder.libraries_config().unwrap(),
+            NeutronLibrariesConfig {
+                pool: "neutron1pool".to_string(),
+                lp_token_denom: "factory/neutron1pool/astroport/share".to_string(),
+                authorizations: "neutron1authorizations".to_string(),
+                processor: "neutron1processor".to_string(),
+                amount: 1_000,
+                usdc_on_neutron: "ibc/usdc".to_string(),
+                eth_withdraw_acc: ETH_WITHDRAW.to_string(),
+                cctp_split_ratio: Decimal::percent(50),
+                // the CCTP transfer burns from the outbound ICA unless configured otherwise
+                cctp_input_account: None,
+                // libraries are set up one by one unless configured otherwise
+                mode: LibrarySetupMode::Serial,
+                chain_config: eurc_hub(),
+                // libraries are labelled with their name and can't be migrated unless configured otherwise
+                deployment: LibraryDeployment::default(),
+            }
+        );
+
+        let config = builder
+            .with_setup_mode(LibrarySetupMode::Batched)
+            .libraries_config()
+            .unwrap();
+        assert_eq!(config.mode, LibrarySetupMode::Batched);
+        assert_eq!(config.cctp_input_account(ICA), ICA);
+    }
+
+    #[test]
+    fn cctp_transfer_burns_from_the_configured_input_account() {
+        let config = ProgramBuilder::new()
+            .with_pool("neutron1pool", "factory/neutron1pool/astroport/share")
+            .with_authorizations("neutron1authorizations")
+            .with_processor("neutron1processor")
+            .with_transfer_amount(1_000)
+            .with_usdc_on_neutron("ibc/usdc")
+            .with_eth_withdraw_account(ETH_WITHDRAW)
+            .with_cctp_split_ratio(Decimal::percent(50))
+            .with_cctp_input_account(DEPOSIT)
+            .libraries_config()
+            .unwrap();
+        let input_account = config.cctp_input_account(ICA);
+        assert_eq!(input_account, DEPOSIT);
+
+        let cctp_transfer = cctp_forwarder_instantiate_msg(
+            input_account,
+            ETH_WITHDRAW,
+            Decimal::percent(50),
+            CctpToken::Usdc,
+        )
+        .unwrap();
+        assert_eq!(
+            cctp_transfer.config.input_addr,
+            LibraryAccountType::Addr(DEPOSIT.to_string())
+        );
+    }
+
+    #[test]
+    fn program_builder_requires_every_library_parameter() {
+        let err = ProgramBuilder::new()
+            .with_pool("neutron1pool", "factory/neutron1pool/astroport/share")
+            .with_authorizations("neutron1authorizations")
+            .libraries_config()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "ProgramBuilder: missing processor");
+    }
+
+    fn mock_neutron_ctx() -> MockTestContext {
+        MockTestContext::new()
+            .with_code_id(NEUTRON_CHAIN_NAME, ICA_IBC_TRANSFER_NAME, 42)
+            .with_transfer_channel("stablehub", NEUTRON_CHAIN_NAME, "channel-7")
+    }
+
+    #[test]
+    fn ica_ibc_transfer_lib_is_instantiated_and_approved_on_the_ica() {
+        let mut test_ctx = mock_neutron_ctx();
+
+        let lib = setup_ica_ibc_transfer_lib(
+            &mut test_ctx,
+            &eurc_hub(),
+            ICA,
+            DEPOSIT,
+            1_000,
+            &LibraryDeployment::default(),
+        )
+        .unwrap();
+
+        let expected_msg = serde_json::to_value(ica_ibc_transfer_instantiate_msg(
+            &eurc_hub(),
+            "channel-7".to_string(),
+            ICA,
+            DEPOSIT,
+            1_000,
+        ))
+        .unwrap();
+        assert_eq!(
+            test_ctx.actions,
+            vec![
+                MockAction::Instantiate {
+                    chain_name: NEUTRON_CHAIN_NAME.to_string(),
+                    key: DEFAULT_KEY.to_string(),
+                    code_id: 42,
+                    label: "valence_ica_ibc_transfer".to_string(),
+                    admin: None,
+                    msg: expected_msg,
+                    address: lib.clone(),
+                },
+                MockAction::ApproveLibrary {
+                    chain_name: NEUTRON_CHAIN_NAME.to_string(),
+                    key: DEFAULT_KEY.to_string(),
+                    account: ICA.to_string(),
+                    library: lib,
+                },
+            ]
+        );
+
+        // The ICA transfers the hub denom it holds to the deposit account, through the hub channel
+        let config = &test_ctx
+            .instantiate_msg("valence_ica_ibc_transfer")
+            .unwrap()["config"];
+        assert_eq!(
+            config["input_addr"],
+            serde_json::json!({ "|library_account_addr|": ICA })
+        );
+        assert_eq!(config["denom"], "ueurc");
+        assert_eq!(config["amount"], "1000");
+        assert_eq!(config["receiver"], DEPOSIT);
+        assert_eq!(config["remote_chain_info"]["channel_id"], "channel-7");
+    }
+
+    #[test]
+    fn libraries_are_instantiated_with_the_deployment_label_and_admin() {
+        let mut test_ctx = mock_neutron_ctx();
+        let deployment = LibraryDeployment::new()
+            .with_label_prefix("eth-cctp-vault-1")
+            .with_admin("neutron1migrator");
+
+        let lib = setup_ica_ibc_transfer_lib(
+            &mut test_ctx,
+            &eurc_hub(),
+            ICA,
+            DEPOSIT,
+            1_000,
+            &deployment,
+        )
+        .unwrap();
 
-    std::thread::sleep(Duration::from_secs(2));
+        let MockAction::Instantiate {
+            label,
+            admin,
+            address,
+            ..
+        } = &test_ctx.actions[0]
+        else {
+            panic!("the library is instantiated first");
+        };
+        assert_eq!(label, "eth-cctp-vault-1-valence_ica_ibc_transfer");
+        assert_eq!(admin.as_deref(), Some("neutron1migrator"));
+        assert_eq!(address, &lib);
 
-    Ok(ica_ibc_transfer.address)
+        // The batched setup instantiates the libraries with the same label and admin
+        let library = LibraryInstantiation::new(
+            &deployment.label("cctp_transfer"),
+            7,
+            &serde_json::json!({}),
+            ICA,
+        )
+        .unwrap()
+        .with_admin(deployment.admin.clone());
+        assert_eq!(library.label, "eth-cctp-vault-1-cctp_transfer");
+        assert_eq!(library.admin.as_deref(), Some("neutron1migrator"));
+
+        let config = ProgramBuilder::new()
+            .with_pool("neutron1pool", "factory/neutron1pool/astroport/share")
+            .with_authorizations("neutron1authorizations")
+            .with_processor("neutron1processor")
+            .with_transfer_amount(1_000)
+            .with_usdc_on_neutron("ibc/usdc")
+            .with_eth_withdraw_account(ETH_WITHDRAW)
+            .with_cctp_split_ratio(Decimal::percent(50))
+            .with_deployment(deployment.clone())
+            .libraries_config()
+            .unwrap();
+        assert_eq!(config.deployment, deployment);
+    }
+
+    #[test]
+    fn ica_ibc_transfer_lib_setup_needs_the_hub_channel() {
+        let mut test_ctx =
+            MockTestContext::new().with_code_id(NEUTRON_CHAIN_NAME, ICA_IBC_TRANSFER_NAME, 42);
+
+        let err = setup_ica_ibc_transfer_lib(
+            &mut test_ctx,
+            &eurc_hub(),
+            ICA,
+            DEPOSIT,
+            1_000,
+            &LibraryDeployment::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            &err,
+            ValenceSetupError::ChannelNotFound { src, dest }
+                if src == "stablehub" && dest == NEUTRON_CHAIN_NAME
+        ));
+        // The relayer may not have created the channel yet, so the setup can be retried
+        assert!(err.is_transient());
+        assert_eq!(
+            err.to_string(),
+            format!("No transfer channel found from stablehub to {NEUTRON_CHAIN_NAME}")
+        );
+        // Nothing is sent once a step fails
+        assert!(test_ctx.actions.is_empty());
+    }
+
+    #[test]
+    fn ica_ibc_transfer_lib_setup_needs_the_uploaded_code() {
+        let mut test_ctx = MockTestContext::new().with_transfer_channel(
+            "stablehub",
+            NEUTRON_CHAIN_NAME,
+            "channel-7",
+        );
+
+        let err = setup_ica_ibc_transfer_lib(
+            &mut test_ctx,
+            &eurc_hub(),
+            ICA,
+            DEPOSIT,
+            1_000,
+            &LibraryDeployment::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            &err,
+            ValenceSetupError::CodeIdNotFound { chain, contract }
+                if chain == NEUTRON_CHAIN_NAME && contract == ICA_IBC_TRANSFER_NAME
+        ));
+        // Retrying doesn't help until the contract is uploaded
+        assert!(!err.is_transient());
+        assert!(test_ctx.actions.is_empty());
+    }
+
+    #[test]
+    fn setup_report_records_the_library_steps() {
+        let mut test_ctx = mock_neutron_ctx();
+        let mut report = SetupReport::new();
+
+        let lib = report
+            .step("valence_ica_ibc_transfer", || {
+                setup_ica_ibc_transfer_lib(
+                    &mut test_ctx,
+                    &eurc_hub(),
+                    ICA,
+                    DEPOSIT,
+                    1_000,
+                    &LibraryDeployment::default(),
+                )
+            })
+            .unwrap();
+
+        // The next library can't be set up without the channel of its hub
+        let mut test_ctx =
+            MockTestContext::new().with_code_id(NEUTRON_CHAIN_NAME, ICA_IBC_TRANSFER_NAME, 42);
+        let error = report
+            .step("second_ica_ibc_transfer", || {
+                setup_ica_ibc_transfer_lib(
+                    &mut test_ctx,
+                    &eurc_hub(),
+                    ICA,
+                    DEPOSIT,
+                    1_000,
+                    &LibraryDeployment::default(),
+                )
+            })
+            .unwrap_err();
+
+        let steps: Vec<_> = report
+            .steps
+            .iter()
+            .map(|step| (step.name.as_str(), step.status, step.output.clone()))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (
+                    "valence_ica_ibc_transfer",
+                    StepStatus::Completed,
+                    Some(serde_json::json!(lib))
+                ),
+                ("second_ica_ibc_transfer", StepStatus::Failed, None),
+            ]
+        );
+
+        // The failure reports the completed steps, which a retry resumes after
+        let failure = SetupFailure { report, error };
+        assert_eq!(
+            failure.to_string(),
+            format!(
+                "Setup failed after completing [valence_ica_ibc_transfer]: No transfer channel found from stablehub to {NEUTRON_CHAIN_NAME}"
+            )
+        );
+        let mut retry = SetupReport::resuming(failure.report);
+        let resumed: String = retry
+            .step("valence_ica_ibc_transfer", || {
+                Err::<String, _>("the completed step is not run again")
+            })
+            .unwrap();
+        assert_eq!(resumed, lib);
+        assert_eq!(retry.steps[0].status, StepStatus::Resumed);
+    }
+
+    #[test]
+    fn hub_denom_must_be_burnable_with_cctp() {
+        let chain_config = VaultChainConfig {
+            hub_denom: "uusdt".to_string(),
+            ..eurc_hub()
+        };
+        assert!(chain_config.cctp_token().is_err());
+    }
 }
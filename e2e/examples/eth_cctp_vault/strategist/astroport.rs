@@ -1,16 +1,34 @@
 use std::error::Error;
 
 use async_trait::async_trait;
-use cosmwasm_std::{coin, to_json_binary, CosmosMsg, Uint128, WasmMsg};
-use localic_utils::NEUTRON_CHAIN_DENOM;
-use log::{info, warn};
+use cosmwasm_std::{coin, to_json_binary, Coin, CosmosMsg, Uint128, WasmMsg};
+use localic_utils::{NEUTRON_CHAIN_DENOM, NEUTRON_CHAIN_NAME};
+use log::info;
+use serde::Serialize;
 use valence_astroport_utils::astroport_native_lp_token::{
     Asset, AssetInfo, ExecuteMsg as AstroportExecuteMsg, PoolQueryMsg, ReverseSimulationResponse,
     SimulationResponse,
 };
 use valence_domain_clients::cosmos::{base_client::BaseClient, wasm_client::WasmClient};
 
-use super::strategy::Strategy;
+use super::{
+    confirmations::ConfirmationStatus, plan::PlannedStep, step_log::StepLog, strategy::Strategy,
+};
+
+// step providing the deposited usdc to the pool through the LPer library
+pub(crate) fn provide_liquidity_step(
+    astroport_lper: &str,
+    provide_liquidity_msg: &impl Serialize,
+    usdc: Coin,
+) -> Result<PlannedStep, serde_json::Error> {
+    Ok(PlannedStep::new(
+        NEUTRON_CHAIN_NAME,
+        astroport_lper,
+        "provide single sided liquidity",
+        provide_liquidity_msg,
+    )?
+    .with_amount(usdc))
+}
 
 #[async_trait]
 pub trait AstroportOps {
@@ -147,19 +165,40 @@ impl AstroportOps for Strategy {
             )
             .await?;
 
+        let step_log = StepLog::new("exit_position", &self.cfg.neutron.accounts.liquidation)
+            .with_amount(liquidation_account_shares_bal);
         if liquidation_account_shares_bal == 0 {
-            warn!("Liquidation account must have LP shares in order to exit LP; returning");
+            step_log
+                .skipped("Liquidation account must have LP shares in order to exit LP; returning");
             return Ok(());
         } else {
-            info!("exiting LP position for {liquidation_account_shares_bal}shares...");
+            step_log.started(&format!(
+                "exiting LP position for {liquidation_account_shares_bal}shares..."
+            ));
         }
 
         let withdraw_liquidity_msg =
             &valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
                 valence_astroport_withdrawer::msg::FunctionMsgs::WithdrawLiquidity {
                     expected_pool_ratio_range: None,
+                    value_provided: None,
                 },
             );
+
+        let step = PlannedStep::new(
+            NEUTRON_CHAIN_NAME,
+            &self.cfg.neutron.libraries.astroport_lwer,
+            "withdraw liquidity",
+            withdraw_liquidity_msg,
+        )?
+        .with_amount(coin(
+            liquidation_account_shares_bal,
+            &self.cfg.neutron.denoms.lp_token,
+        ));
+        if self.plan_step(step) {
+            return Ok(());
+        }
+
         let rx = self
             .neutron_client
             .execute_wasm(
@@ -171,7 +210,7 @@ impl AstroportOps for Strategy {
             .await?;
         self.neutron_client.poll_for_tx(&rx.hash).await?;
 
-        info!("success exiting position");
+        step_log.completed("success exiting position");
 
         Ok(())
     }
@@ -263,12 +302,32 @@ impl AstroportOps for Strategy {
             )
             .await?;
 
+        let step_log = StepLog::new("enter_position", &self.cfg.neutron.accounts.deposit)
+            .with_amount(deposit_account_usdc_bal);
         if deposit_account_usdc_bal == 0 {
-            warn!("Deposit account must have USDC in order to LP; returning");
+            step_log.skipped("Deposit account must have USDC in order to LP; returning");
+            return Ok(());
+        }
+
+        // the balance may come from an inbound transfer that is not final yet,
+        // so it is only provided once it was held for the min confirmations
+        let height = u64::try_from(self.neutron_client.latest_block_header().await?.height)?;
+        let confirmation_status = self.deposit_confirmations.lock().unwrap().check(
+            &self.cfg.confirmations,
+            deposit_account_usdc_bal,
+            height,
+        );
+        if let ConfirmationStatus::Pending {
+            observed_at,
+            remaining,
+        } = confirmation_status
+        {
+            step_log.skipped(&format!(
+                "Deposit account balance observed at height {observed_at} needs {remaining} more confirmations; returning"
+            ));
             return Ok(());
-        } else {
-            info!("entering LP position...");
         }
+        step_log.started("entering LP position...");
 
         let provide_liquidity_msg =
             &valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
@@ -278,6 +337,16 @@ impl AstroportOps for Strategy {
                     expected_pool_ratio_range: None,
                 },
             );
+
+        let step = provide_liquidity_step(
+            &self.cfg.neutron.libraries.astroport_lper,
+            provide_liquidity_msg,
+            coin(deposit_account_usdc_bal, &self.cfg.neutron.denoms.usdc),
+        )?;
+        if self.plan_step(step) {
+            return Ok(());
+        }
+
         let rx = self
             .neutron_client
             .execute_wasm(
@@ -288,8 +357,9 @@ impl AstroportOps for Strategy {
             )
             .await?;
         self.neutron_client.poll_for_tx(&rx.hash).await?;
+        self.deposit_confirmations.lock().unwrap().reset();
 
-        info!("success entering position");
+        step_log.completed("success entering position");
 
         Ok(())
     }
@@ -303,14 +373,19 @@ impl AstroportOps for Strategy {
 
         // prior to swapping we ensure that the withdraw account has sufficient untrn to
         // cover the subsequent ibc transfer fee for routing the usdc to noble
+        let step_log = StepLog::new("swap_ntrn_into_usdc", &self.cfg.neutron.accounts.withdraw);
         let applicable_balance = if withdraw_account_ntrn_bal <= self.cfg.neutron.min_ibc_fee.u128()
         {
-            warn!("Withdraw account must have NTRN in order to swap into USDC; returning");
+            step_log
+                .skipped("Withdraw account must have NTRN in order to swap into USDC; returning");
             return Ok(());
         } else {
-            info!("swapping {withdraw_account_ntrn_bal}NTRN into USDC...");
             withdraw_account_ntrn_bal - self.cfg.neutron.min_ibc_fee.u128()
         };
+        let step_log = step_log.with_amount(applicable_balance);
+        step_log.started(&format!(
+            "swapping {withdraw_account_ntrn_bal}NTRN into USDC..."
+        ));
 
         let astroport_swap_msg = AstroportExecuteMsg::Swap {
             offer_asset: Asset {
@@ -335,6 +410,17 @@ impl AstroportOps for Strategy {
             msgs: vec![swap_msg],
         };
 
+        let step = PlannedStep::new(
+            NEUTRON_CHAIN_NAME,
+            &self.cfg.neutron.accounts.withdraw,
+            "swap ntrn into usdc",
+            &base_account_execute_msgs,
+        )?
+        .with_amount(coin(applicable_balance, NEUTRON_CHAIN_DENOM));
+        if self.plan_step(step) {
+            return Ok(());
+        }
+
         let rx = self
             .neutron_client
             .execute_wasm(
@@ -347,7 +433,7 @@ impl AstroportOps for Strategy {
 
         self.neutron_client.poll_for_tx(&rx.hash).await?;
 
-        info!("success swapping ntrn into usdc");
+        step_log.completed("success swapping ntrn into usdc");
 
         Ok(())
     }
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+// confirmation depth that the inbound transfers must reach before the
+// strategist acts on the balance they credited, read from the strategy config
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfirmationsConfig {
+    // blocks that the deposit account balance must have been held for before
+    // it is provided into the LP. defaults to 0, acting on the balance right away
+    #[serde(default)]
+    pub min_confirmations: u64,
+}
+
+// outcome of checking the confirmation depth of a balance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmationStatus {
+    // the balance was held for at least the min confirmations
+    Confirmed,
+    // the balance was first observed at `observed_at` and needs `remaining` more blocks
+    Pending { observed_at: u64, remaining: u64 },
+}
+
+// tracks the height at which the current balance of an inbound account was
+// first observed. a balance that changes (another transfer landing, or a
+// transient one going away) is only acted on once it was held unchanged for
+// the min confirmations, so that the strategist never acts on a balance from
+// a transfer that is not final yet
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InboundConfirmations {
+    // balance currently held along with the height at which it was first observed
+    observed: Option<(u128, u64)>,
+}
+
+impl InboundConfirmations {
+    // records `balance` as held at `height`, and checks whether it reached the
+    // min confirmations
+    pub fn check(
+        &mut self,
+        cfg: &ConfirmationsConfig,
+        balance: u128,
+        height: u64,
+    ) -> ConfirmationStatus {
+        let observed_at = match self.observed {
+            Some((observed_balance, observed_at)) if observed_balance == balance => observed_at,
+            _ => {
+                self.observed = Some((balance, height));
+                height
+            }
+        };
+
+        let confirmations = height.saturating_sub(observed_at);
+        if confirmations >= cfg.min_confirmations {
+            ConfirmationStatus::Confirmed
+        } else {
+            ConfirmationStatus::Pending {
+                observed_at,
+                remaining: cfg.min_confirmations - confirmations,
+            }
+        }
+    }
+
+    // forgets the observed balance once it was acted on
+    pub fn reset(&mut self) {
+        self.observed = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPOSIT: u128 = 1_000_000;
+
+    fn confirmations(min_confirmations: u64) -> ConfirmationsConfig {
+        ConfirmationsConfig { min_confirmations }
+    }
+
+    #[test]
+    fn action_fires_once_the_confirmation_depth_is_reached() {
+        let cfg = confirmations(3);
+        let mut inbound = InboundConfirmations::default();
+
+        // the deposit lands at height 100 and is checked at every block
+        let fired: Vec<u64> = (100..=105)
+            .filter(|height| inbound.check(&cfg, DEPOSIT, *height) == ConfirmationStatus::Confirmed)
+            .collect();
+
+        assert_eq!(fired, vec![103, 104, 105]);
+        assert_eq!(
+            InboundConfirmations::default().check(&cfg, DEPOSIT, 100),
+            ConfirmationStatus::Pending {
+                observed_at: 100,
+                remaining: 3
+            }
+        );
+    }
+
+    #[test]
+    fn changed_balance_must_be_confirmed_again() {
+        let cfg = confirmations(3);
+        let mut inbound = InboundConfirmations::default();
+
+        assert!(matches!(
+            inbound.check(&cfg, DEPOSIT, 100),
+            ConfirmationStatus::Pending { .. }
+        ));
+        // another transfer lands before the first one is confirmed
+        assert_eq!(
+            inbound.check(&cfg, DEPOSIT * 2, 102),
+            ConfirmationStatus::Pending {
+                observed_at: 102,
+                remaining: 3
+            }
+        );
+        assert!(matches!(
+            inbound.check(&cfg, DEPOSIT * 2, 104),
+            ConfirmationStatus::Pending { remaining: 1, .. }
+        ));
+        // a transient balance going away restarts the count as well
+        assert!(matches!(
+            inbound.check(&cfg, DEPOSIT, 105),
+            ConfirmationStatus::Pending { remaining: 3, .. }
+        ));
+        assert_eq!(
+            inbound.check(&cfg, DEPOSIT, 108),
+            ConfirmationStatus::Confirmed
+        );
+    }
+
+    #[test]
+    fn no_min_confirmations_acts_right_away() {
+        let mut inbound = InboundConfirmations::default();
+
+        assert_eq!(
+            inbound.check(&ConfirmationsConfig::default(), DEPOSIT, 100),
+            ConfirmationStatus::Confirmed
+        );
+    }
+}
@@ -0,0 +1,480 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use cosmwasm_std::{Decimal, Uint128};
+use localic_utils::NEUTRON_CHAIN_DENOM;
+use log::info;
+use serde::{Deserialize, Serialize};
+use valence_domain_clients::cosmos::base_client::BaseClient;
+use valence_e2e::utils::UUSDC_DENOM;
+
+use super::{
+    astroport::AstroportOps, routing::EthereumVaultRouting, step_log::StepLog, strategy::Strategy,
+};
+
+// steps of the liquidation flow, in execution order. each of them is only
+// executed if the cycle is still on track once the previous one completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiquidationStep {
+    ForwardShares,
+    ExitPosition,
+    SwapNtrnIntoUsdc,
+    RouteNeutronToNoble,
+    RouteNobleToEth,
+}
+
+impl LiquidationStep {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LiquidationStep::ForwardShares => "forward_shares_for_liquidation",
+            LiquidationStep::ExitPosition => "exit_position",
+            LiquidationStep::SwapNtrnIntoUsdc => "swap_ntrn_into_usdc",
+            LiquidationStep::RouteNeutronToNoble => "route_neutron_to_noble",
+            LiquidationStep::RouteNobleToEth => "route_noble_to_eth",
+        }
+    }
+}
+
+// reason for which the remaining steps of a liquidation cycle are not executed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiquidationAbort {
+    DeadlineExceeded {
+        step: LiquidationStep,
+        now: u64,
+        deadline: u64,
+    },
+    BelowTarget {
+        step: LiquidationStep,
+        realizable: Uint128,
+        min_output: Uint128,
+    },
+}
+
+impl Display for LiquidationAbort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiquidationAbort::DeadlineExceeded {
+                step,
+                now,
+                deadline,
+            } => write!(
+                f,
+                "liquidation deadline {deadline} exceeded at {now}, aborting before {}",
+                step.as_str()
+            ),
+            LiquidationAbort::BelowTarget {
+                step,
+                realizable,
+                min_output,
+            } => write!(
+                f,
+                "realizable output {realizable}uusdc is below the min output {min_output}uusdc, aborting before {}",
+                step.as_str()
+            ),
+        }
+    }
+}
+
+impl Error for LiquidationAbort {}
+
+// limits applied to every liquidation cycle, read from the strategy config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiquidationGuardConfig {
+    // time available to the cycle to deliver the usdc to ethereum, from its start
+    pub max_duration_secs: u64,
+    // shortfall of the realizable output tolerated against the target, in basis points
+    pub max_shortfall_bps: u64,
+}
+
+impl Default for LiquidationGuardConfig {
+    fn default() -> Self {
+        LiquidationGuardConfig {
+            max_duration_secs: 1_800,
+            max_shortfall_bps: 100,
+        }
+    }
+}
+
+// end-to-end guard of a liquidation cycle (forward shares -> withdraw LP ->
+// IBC to noble -> CCTP to ethereum). before each step, the usdc that can still
+// be delivered to ethereum is compared against the min output of the cycle, so
+// that a stale or losing liquidation stops early instead of delivering less
+// than the obligations it was started for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiquidationCycle {
+    // usdc amount the cycle is expected to deliver to the ethereum withdraw account
+    pub target_usdc: Uint128,
+    // lowest realizable usdc amount for which the cycle keeps going
+    pub min_output: Uint128,
+    // unix timestamp (in seconds) after which no step is executed anymore
+    pub deadline: u64,
+}
+
+impl LiquidationCycle {
+    pub fn new(cfg: &LiquidationGuardConfig, target_usdc: Uint128, started_at: u64) -> Self {
+        let shortfall = target_usdc.mul_floor(Decimal::bps(cfg.max_shortfall_bps));
+        LiquidationCycle {
+            target_usdc,
+            min_output: target_usdc - shortfall,
+            deadline: started_at.saturating_add(cfg.max_duration_secs),
+        }
+    }
+
+    // checks that `step` can be executed at `now`, given the usdc amount that
+    // is realizable from the funds currently held along the flow
+    pub fn check(
+        &self,
+        step: LiquidationStep,
+        realizable: Uint128,
+        now: u64,
+    ) -> Result<(), LiquidationAbort> {
+        if now > self.deadline {
+            return Err(LiquidationAbort::DeadlineExceeded {
+                step,
+                now,
+                deadline: self.deadline,
+            });
+        }
+
+        if realizable < self.min_output {
+            return Err(LiquidationAbort::BelowTarget {
+                step,
+                realizable,
+                min_output: self.min_output,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[async_trait]
+pub trait GuardedLiquidation {
+    // runs the liquidation flow for `shares`, checking the cycle before every step.
+    // returns the amount of USDC transferred out to ethereum, or the reason for
+    // which the remaining steps were not executed.
+    async fn liquidate(
+        &self,
+        cycle: &LiquidationCycle,
+        shares: Uint128,
+    ) -> Result<Uint128, LiquidationAbort>;
+
+    // usdc that can be delivered to ethereum from the funds that `step` is about to move
+    async fn realizable_usdc(&self, step: LiquidationStep, shares: Uint128) -> Uint128;
+
+    async fn check_step(
+        &self,
+        cycle: &LiquidationCycle,
+        step: LiquidationStep,
+        shares: Uint128,
+    ) -> Result<(), LiquidationAbort>;
+}
+
+#[async_trait]
+impl GuardedLiquidation for Strategy {
+    async fn liquidate(
+        &self,
+        cycle: &LiquidationCycle,
+        shares: Uint128,
+    ) -> Result<Uint128, LiquidationAbort> {
+        // 1. forward the shares to be liquidated from the position account to the withdraw account
+        self.check_step(cycle, LiquidationStep::ForwardShares, shares)
+            .await?;
+        self.forward_shares_for_liquidation(shares).await;
+
+        // 2. liquidate the forwarded shares to get USDC+NTRN
+        self.check_step(cycle, LiquidationStep::ExitPosition, shares)
+            .await?;
+        if let Err(e) = self.exit_position().await {
+            StepLog::new("exit_position", &self.cfg.neutron.accounts.liquidation).failed(&e)
+        }
+
+        // 3. swap NTRN into USDC to obtain the full obligation amount
+        self.check_step(cycle, LiquidationStep::SwapNtrnIntoUsdc, shares)
+            .await?;
+        if let Err(e) = self.swap_ntrn_into_usdc().await {
+            StepLog::new("swap_ntrn_into_usdc", &self.cfg.neutron.accounts.withdraw).failed(&e)
+        }
+
+        // the shares covering the pending obligations are now liquidated
+        self.obligations.lock().unwrap().mark_liquidating();
+
+        // 4. ibc transfer neutron withdraw acc -> noble outbound ica
+        self.check_step(cycle, LiquidationStep::RouteNeutronToNoble, shares)
+            .await?;
+        self.route_neutron_to_noble().await;
+
+        // 5. cctp transfer noble outbound ica -> eth withdraw acc
+        self.check_step(cycle, LiquidationStep::RouteNobleToEth, shares)
+            .await?;
+        Ok(self.route_noble_to_eth().await)
+    }
+
+    async fn realizable_usdc(&self, step: LiquidationStep, shares: Uint128) -> Uint128 {
+        let usdc = &self.cfg.neutron.denoms.usdc;
+        let withdraw_acc = &self.cfg.neutron.accounts.withdraw;
+
+        match step {
+            LiquidationStep::ForwardShares => self.shares_usdc_value(shares.u128()).await,
+            LiquidationStep::ExitPosition => {
+                let forwarded_shares = self
+                    .neutron_client
+                    .query_balance(
+                        &self.cfg.neutron.accounts.liquidation,
+                        &self.cfg.neutron.denoms.lp_token,
+                    )
+                    .await
+                    .unwrap();
+                self.shares_usdc_value(forwarded_shares).await
+            }
+            LiquidationStep::SwapNtrnIntoUsdc => {
+                let usdc_bal = self
+                    .neutron_client
+                    .query_balance(withdraw_acc, usdc)
+                    .await
+                    .unwrap();
+                let ntrn_bal = self
+                    .neutron_client
+                    .query_balance(withdraw_acc, NEUTRON_CHAIN_DENOM)
+                    .await
+                    .unwrap();
+                // the ibc fee of the transfer to noble is kept in untrn
+                let swappable = ntrn_bal.saturating_sub(self.cfg.neutron.min_ibc_fee.u128());
+                let swapped = if swappable == 0 {
+                    Uint128::zero()
+                } else {
+                    self.simulate_swap(
+                        &self.cfg.neutron.target_pool,
+                        NEUTRON_CHAIN_DENOM,
+                        swappable.into(),
+                        usdc,
+                    )
+                    .await
+                    .unwrap()
+                };
+                Uint128::new(usdc_bal) + swapped
+            }
+            LiquidationStep::RouteNeutronToNoble => Uint128::new(
+                self.neutron_client
+                    .query_balance(withdraw_acc, usdc)
+                    .await
+                    .unwrap(),
+            ),
+            LiquidationStep::RouteNobleToEth => Uint128::new(
+                self.noble_client
+                    .query_balance(
+                        &self.cfg.neutron.accounts.noble_outbound_ica.remote_addr,
+                        UUSDC_DENOM,
+                    )
+                    .await
+                    .unwrap(),
+            ),
+        }
+    }
+
+    async fn check_step(
+        &self,
+        cycle: &LiquidationCycle,
+        step: LiquidationStep,
+        shares: Uint128,
+    ) -> Result<(), LiquidationAbort> {
+        // planned steps are not executed, so the balances past the first step
+        // don't reflect the flow of a dry-run cycle
+        if self.is_dry_run() && step != LiquidationStep::ForwardShares {
+            return Ok(());
+        }
+
+        let realizable = self.realizable_usdc(step, shares).await;
+        info!(
+            "[CYCLE] realizable output before {}: {realizable}uusdc (min output: {}uusdc)",
+            step.as_str(),
+            cycle.min_output
+        );
+        cycle.check(step, realizable, now_secs())
+    }
+}
+
+impl Strategy {
+    // usdc obtained by withdrawing `shares` from the pool and swapping the untrn into usdc
+    async fn shares_usdc_value(&self, shares: u128) -> Uint128 {
+        if shares == 0 {
+            return Uint128::zero();
+        }
+
+        let pool = &self.cfg.neutron.target_pool;
+        let usdc = &self.cfg.neutron.denoms.usdc;
+        let (usdc_amount, ntrn_amount) = self
+            .simulate_liquidation(pool, shares, usdc, NEUTRON_CHAIN_DENOM)
+            .await
+            .unwrap();
+        let swapped = self
+            .simulate_swap(pool, NEUTRON_CHAIN_DENOM, ntrn_amount, usdc)
+            .await
+            .unwrap();
+        usdc_amount + swapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STARTED_AT: u64 = 1_700_000_000;
+
+    // constant product pool of usdc and ntrn, as seen by the strategist simulations
+    struct Pool {
+        usdc: u128,
+        ntrn: u128,
+        shares: u128,
+    }
+
+    impl Pool {
+        // usdc obtained by withdrawing `shares` and swapping the ntrn share into usdc
+        fn realizable_usdc(&self, shares: u128) -> Uint128 {
+            let usdc = self.usdc * shares / self.shares;
+            let ntrn = self.ntrn * shares / self.shares;
+            // the withdrawn ntrn is swapped back into the pool left after the withdrawal
+            let swapped = (self.usdc - usdc) * ntrn / self.ntrn;
+            Uint128::new(usdc + swapped)
+        }
+
+        // trade selling `ntrn` into the pool, moving its price against the position
+        fn sell_ntrn(&mut self, ntrn: u128) {
+            let usdc_out = self.usdc * ntrn / (self.ntrn + ntrn);
+            self.usdc -= usdc_out;
+            self.ntrn += ntrn;
+        }
+    }
+
+    fn pool() -> Pool {
+        Pool {
+            usdc: 1_000_000_000,
+            ntrn: 4_000_000_000,
+            shares: 1_000_000,
+        }
+    }
+
+    // runs the steps of the flow in order, returning the executed ones along with the
+    // abort reason if the cycle stopped early
+    fn run(
+        cycle: &LiquidationCycle,
+        realizable_before: impl Fn(LiquidationStep) -> (Uint128, u64),
+    ) -> (Vec<LiquidationStep>, Option<LiquidationAbort>) {
+        let mut executed = vec![];
+        for step in [
+            LiquidationStep::ForwardShares,
+            LiquidationStep::ExitPosition,
+            LiquidationStep::SwapNtrnIntoUsdc,
+            LiquidationStep::RouteNeutronToNoble,
+            LiquidationStep::RouteNobleToEth,
+        ] {
+            let (realizable, now) = realizable_before(step);
+            if let Err(abort) = cycle.check(step, realizable, now) {
+                return (executed, Some(abort));
+            }
+            executed.push(step);
+        }
+        (executed, None)
+    }
+
+    #[test]
+    fn min_output_and_deadline_follow_the_config() {
+        let cycle = LiquidationCycle::new(
+            &LiquidationGuardConfig::default(),
+            Uint128::new(1_000_000),
+            STARTED_AT,
+        );
+
+        assert_eq!(cycle.min_output, Uint128::new(990_000));
+        assert_eq!(cycle.deadline, STARTED_AT + 1_800);
+        assert!(cycle
+            .check(
+                LiquidationStep::ForwardShares,
+                Uint128::new(990_000),
+                cycle.deadline
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn cycle_on_track_executes_every_step() {
+        let pool = pool();
+        let shares = 1_000;
+        let cycle = LiquidationCycle::new(
+            &LiquidationGuardConfig::default(),
+            pool.realizable_usdc(shares),
+            STARTED_AT,
+        );
+
+        let (executed, abort) = run(&cycle, |_| (pool.realizable_usdc(shares), STARTED_AT));
+
+        assert_eq!(abort, None);
+        assert_eq!(executed.len(), 5);
+    }
+
+    #[test]
+    fn pool_move_below_target_aborts_early() {
+        let mut pool = pool();
+        let shares = 1_000;
+        let cycle = LiquidationCycle::new(
+            &LiquidationGuardConfig::default(),
+            pool.realizable_usdc(shares),
+            STARTED_AT,
+        );
+
+        // the pool moves once the shares are forwarded, before they are withdrawn
+        let target = cycle.target_usdc;
+        pool.sell_ntrn(400_000_000);
+        let moved = pool.realizable_usdc(shares);
+        let (executed, abort) = run(&cycle, |step| match step {
+            LiquidationStep::ForwardShares => (target, STARTED_AT),
+            _ => (moved, STARTED_AT + 60),
+        });
+
+        assert_eq!(executed, vec![LiquidationStep::ForwardShares]);
+        assert_eq!(
+            abort,
+            Some(LiquidationAbort::BelowTarget {
+                step: LiquidationStep::ExitPosition,
+                realizable: moved,
+                min_output: cycle.min_output,
+            })
+        );
+        assert!(moved < cycle.min_output);
+    }
+
+    #[test]
+    fn stale_cycle_aborts_past_the_deadline() {
+        let cycle = LiquidationCycle::new(
+            &LiquidationGuardConfig::default(),
+            Uint128::new(1_000_000),
+            STARTED_AT,
+        );
+
+        let (executed, abort) = run(&cycle, |step| match step {
+            LiquidationStep::RouteNobleToEth => (Uint128::new(1_000_000), cycle.deadline + 1),
+            _ => (Uint128::new(1_000_000), STARTED_AT),
+        });
+
+        assert_eq!(executed.len(), 4);
+        assert_eq!(
+            abort,
+            Some(LiquidationAbort::DeadlineExceeded {
+                step: LiquidationStep::RouteNobleToEth,
+                now: cycle.deadline + 1,
+                deadline: cycle.deadline,
+            })
+        );
+    }
+}
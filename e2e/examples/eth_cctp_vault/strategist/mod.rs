@@ -1,4 +1,8 @@
 pub(crate) mod astroport;
+pub mod confirmations;
+pub mod liquidation;
+pub mod plan;
 pub(crate) mod routing;
+pub mod step_log;
 pub mod strategy;
 pub mod strategy_config;
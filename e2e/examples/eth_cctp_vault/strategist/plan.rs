@@ -0,0 +1,173 @@
+use cosmwasm_std::Coin;
+use serde::Serialize;
+
+use super::strategy_config::StrategyMode;
+
+// a single message that the strategy would have submitted
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedStep {
+    // domain on which the message would be submitted
+    pub domain: String,
+    // library, account, or contract targeted by the message
+    pub target: String,
+    pub description: String,
+    // amount that the step is expected to move, if known ahead of execution
+    pub amount: Option<Coin>,
+    pub msg: serde_json::Value,
+    pub funds: Vec<Coin>,
+}
+
+impl PlannedStep {
+    pub fn new(
+        domain: &str,
+        target: &str,
+        description: &str,
+        msg: &impl Serialize,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(PlannedStep {
+            domain: domain.to_string(),
+            target: target.to_string(),
+            description: description.to_string(),
+            amount: None,
+            msg: serde_json::to_value(msg)?,
+            funds: vec![],
+        })
+    }
+
+    pub fn with_amount(mut self, amount: Coin) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn with_funds(mut self, funds: Vec<Coin>) -> Self {
+        self.funds = funds;
+        self
+    }
+}
+
+// ordered list of the messages that a dry-run cycle would have submitted.
+// note that steps are planned against the state at the start of the cycle,
+// so steps that depend on the outcome of previous ones (e.g. entering the
+// position after routing the funds to neutron) reflect the pre-cycle balances.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StrategyPlan {
+    pub steps: Vec<PlannedStep>,
+}
+
+impl StrategyPlan {
+    pub fn push(&mut self, step: PlannedStep) {
+        self.steps.push(step);
+    }
+
+    // in dry-run mode, records the step and returns true to signal that it
+    // must not be broadcasted. in live mode the plan is left untouched.
+    pub fn record(&mut self, mode: &StrategyMode, step: PlannedStep) -> bool {
+        if *mode != StrategyMode::DryRun {
+            return false;
+        }
+        self.push(step);
+        true
+    }
+
+    // returns the steps targeting a specific library or account
+    pub fn steps_for(&self, target: &str) -> Vec<&PlannedStep> {
+        self.steps.iter().filter(|s| s.target == target).collect()
+    }
+}
+
+// plans of the dry-run cycles. the plan of the last completed cycle is kept
+// for inspection while the next cycle builds its own.
+#[derive(Debug, Clone, Default)]
+pub struct DryRunPlans {
+    current: StrategyPlan,
+    last: Option<StrategyPlan>,
+}
+
+impl DryRunPlans {
+    pub fn current_mut(&mut self) -> &mut StrategyPlan {
+        &mut self.current
+    }
+
+    // each cycle plans against the fresh on-chain state, so it starts from an
+    // empty plan. the last completed plan is left in place.
+    pub fn start_cycle(&mut self) {
+        self.current = StrategyPlan::default();
+    }
+
+    // keeps the plan of the cycle that just completed as the last plan
+    pub fn complete_cycle(&mut self) -> &StrategyPlan {
+        self.last.insert(self.current.clone())
+    }
+
+    // plan of the cycle in progress
+    pub fn current(&self) -> &StrategyPlan {
+        &self.current
+    }
+
+    // plan of the last completed cycle, if any
+    pub fn last(&self) -> Option<&StrategyPlan> {
+        self.last.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::coin;
+    use valence_astroport_lper::msg::FunctionMsgs as LperFunctionMsgs;
+    use valence_library_utils::msg::ExecuteMsg as LibraryExecuteMsg;
+
+    use crate::strategist::astroport::provide_liquidity_step;
+
+    use super::*;
+
+    const LPER: &str = "neutron1lper";
+    const USDC: &str = "ibc/usdc";
+
+    fn provide_msg() -> LibraryExecuteMsg<LperFunctionMsgs, ()> {
+        LibraryExecuteMsg::ProcessFunction(LperFunctionMsgs::ProvideSingleSidedLiquidity {
+            asset: USDC.to_string(),
+            limit: None,
+            expected_pool_ratio_range: None,
+        })
+    }
+
+    fn provide_step() -> PlannedStep {
+        provide_liquidity_step(LPER, &provide_msg(), coin(1_000_000, USDC)).unwrap()
+    }
+
+    #[test]
+    fn dry_run_plans_the_lper_provide_message() {
+        let mut plan = StrategyPlan::default();
+        assert!(plan.record(&StrategyMode::DryRun, provide_step()));
+
+        let steps = plan.steps_for(LPER);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].msg, serde_json::to_value(provide_msg()).unwrap());
+        assert_eq!(steps[0].amount, Some(coin(1_000_000, USDC)));
+        assert!(steps[0].funds.is_empty());
+    }
+
+    #[test]
+    fn live_mode_plans_nothing() {
+        let mut plan = StrategyPlan::default();
+        assert!(!plan.record(&StrategyMode::Live, provide_step()));
+        assert!(plan.steps.is_empty());
+    }
+
+    #[test]
+    fn last_plan_outlives_the_next_cycle_start() {
+        let mut plans = DryRunPlans::default();
+        assert!(plans.last().is_none());
+
+        plans.start_cycle();
+        plans
+            .current_mut()
+            .record(&StrategyMode::DryRun, provide_step());
+        assert_eq!(plans.complete_cycle().steps_for(LPER).len(), 1);
+
+        // the next cycle starts on an empty plan, the completed one can still be inspected
+        plans.start_cycle();
+        assert!(plans.current().steps.is_empty());
+        assert_eq!(plans.last().unwrap().steps_for(LPER).len(), 1);
+    }
+}
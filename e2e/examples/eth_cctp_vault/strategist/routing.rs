@@ -2,27 +2,28 @@ use std::str::FromStr;
 
 use alloy::primitives::{Address, U256};
 use async_trait::async_trait;
-use cosmwasm_std::Uint128;
-use localic_utils::NEUTRON_CHAIN_DENOM;
-use log::{error, info, warn};
+use cosmwasm_std::{coin, BankMsg, Uint128};
+use localic_utils::{NEUTRON_CHAIN_DENOM, NEUTRON_CHAIN_NAME};
+use log::info;
 use valence_domain_clients::{
     cosmos::{base_client::BaseClient, wasm_client::WasmClient},
     evm::{base_client::EvmBaseClient, request_provider_client::RequestProviderClient},
 };
 use valence_e2e::utils::{
     solidity_contracts::{CCTPTransfer, MockERC20},
-    UUSDC_DENOM,
+    ETHEREUM_CHAIN_NAME, UUSDC_DENOM,
 };
 use valence_forwarder_library::msg::UncheckedForwardingConfig;
 use valence_library_utils::denoms::UncheckedDenom;
 
-use super::strategy::Strategy;
+use super::{plan::PlannedStep, step_log::StepLog, strategy::Strategy};
 
 #[async_trait]
 pub trait EthereumVaultRouting {
     async fn ensure_neutron_account_fees_coverage(&self, acc: String);
 
-    async fn route_noble_to_eth(&self);
+    // returns the amount of USDC transferred out to ethereum
+    async fn route_noble_to_eth(&self) -> Uint128;
 
     async fn route_eth_to_noble(&self);
 
@@ -45,6 +46,21 @@ impl EthereumVaultRouting for Strategy {
         if account_ntrn_balance < self.cfg.neutron.min_ibc_fee.u128() {
             let delta = self.cfg.neutron.min_ibc_fee.u128() - account_ntrn_balance;
 
+            let step = PlannedStep::new(
+                NEUTRON_CHAIN_NAME,
+                &acc,
+                "fund account for ibc tx fees",
+                &BankMsg::Send {
+                    to_address: acc.to_string(),
+                    amount: vec![coin(delta, NEUTRON_CHAIN_DENOM)],
+                },
+            )
+            .unwrap()
+            .with_amount(coin(delta, NEUTRON_CHAIN_DENOM));
+            if self.plan_step(step) {
+                return;
+            }
+
             info!("Funding neutron account with {delta}untrn for ibc tx fees...");
             let transfer_rx = self
                 .neutron_client
@@ -62,8 +78,13 @@ impl EthereumVaultRouting for Strategy {
     /// pending withdraw obligations and forwards those shares from the position
     /// account to the withdrawal account.
     async fn forward_shares_for_liquidation(&self, amount: Uint128) {
+        let step_log = StepLog::new(
+            "forward_shares_for_liquidation",
+            &self.cfg.neutron.accounts.position,
+        )
+        .with_amount(amount.u128());
         if amount.is_zero() {
-            warn!("zero-shares liquidation request; returning");
+            step_log.skipped("zero-shares liquidation request; returning");
             return;
         } else {
             let pre_fwd_position = self
@@ -76,11 +97,15 @@ impl EthereumVaultRouting for Strategy {
                 .unwrap();
 
             if pre_fwd_position < amount.u128() {
-                error!("position account shares balance is insufficient: {pre_fwd_position} < {amount}. returning.");
+                step_log.failed(&format!(
+                    "position account shares balance is insufficient: {pre_fwd_position} < {amount}"
+                ));
                 return;
             }
 
-            info!("forwarding {amount}shares from position to withdraw account for liquidation");
+            step_log.started(&format!(
+                "forwarding {amount}shares from position to withdraw account for liquidation"
+            ));
         }
 
         let updated_share_fwd_cfg = UncheckedForwardingConfig {
@@ -93,12 +118,44 @@ impl EthereumVaultRouting for Strategy {
         >::UpdateConfig {
             new_config: valence_forwarder_library::msg::LibraryConfigUpdate {
                 input_addr: None,
+                additional_input_addrs: valence_library_utils::OptionUpdate::None,
                 output_addr: None,
                 forwarding_configs: Some(vec![updated_share_fwd_cfg]),
                 forwarding_constraints: None,
+                split_outputs: valence_library_utils::OptionUpdate::None,
+                rounding: None,
+                lp_redemption: valence_library_utils::OptionUpdate::None,
+                gas_reserve: valence_library_utils::OptionUpdate::None,
+                snapshot_balances: None,
+                precondition: valence_library_utils::OptionUpdate::None,
+                fee_skim: valence_library_utils::OptionUpdate::None,
             },
         };
 
+        let fwd_msg = &valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
+            valence_forwarder_library::msg::FunctionMsgs::Forward {},
+        );
+
+        let update_cfg_step = PlannedStep::new(
+            NEUTRON_CHAIN_NAME,
+            &self.cfg.neutron.libraries.liquidation_forwarder,
+            "update liquidation forwarder config",
+            update_cfg_msg,
+        )
+        .unwrap();
+        let fwd_step = PlannedStep::new(
+            NEUTRON_CHAIN_NAME,
+            &self.cfg.neutron.libraries.liquidation_forwarder,
+            "forward shares for liquidation",
+            fwd_msg,
+        )
+        .unwrap()
+        .with_amount(coin(amount.u128(), &self.cfg.neutron.denoms.lp_token));
+        if self.plan_step(update_cfg_step) {
+            self.plan_step(fwd_step);
+            return;
+        }
+
         info!("updating liquidation forwarder config to route {amount}shares");
         let update_rx = self
             .neutron_client
@@ -116,10 +173,6 @@ impl EthereumVaultRouting for Strategy {
             .await
             .unwrap();
 
-        let fwd_msg = &valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
-            valence_forwarder_library::msg::FunctionMsgs::Forward {},
-        );
-
         let rx = self
             .neutron_client
             .execute_wasm(
@@ -131,7 +184,7 @@ impl EthereumVaultRouting for Strategy {
             .await
             .unwrap();
         self.neutron_client.poll_for_tx(&rx.hash).await.unwrap();
-        info!("shares forwarding complete");
+        step_log.completed("shares forwarding complete");
     }
 
     /// IBC-transfers funds from Neutron withdraw account to noble outbound ica
@@ -145,11 +198,35 @@ impl EthereumVaultRouting for Strategy {
             .await
             .unwrap();
 
+        let step_log = StepLog::new(
+            "route_neutron_to_noble",
+            &self.cfg.neutron.accounts.withdraw,
+        )
+        .with_amount(withdraw_account_usdc_bal);
         if withdraw_account_usdc_bal == 0 {
-            warn!("Neutron withdraw account holds no USDC; skipping routing from neutron to noble");
+            step_log.skipped(
+                "Neutron withdraw account holds no USDC; skipping routing from neutron to noble",
+            );
             return;
         } else {
-            info!("Routing USDC from Neutron to Noble");
+            step_log.started("Routing USDC from Neutron to Noble");
+        }
+
+        let step = PlannedStep::new(
+            NEUTRON_CHAIN_NAME,
+            &self.cfg.neutron.libraries.neutron_ibc_transfer,
+            "ibc transfer neutron withdraw account -> noble outbound ica",
+            &valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
+                valence_neutron_ibc_transfer_library::msg::FunctionMsgs::IbcTransfer {},
+            ),
+        )
+        .unwrap()
+        .with_amount(coin(
+            withdraw_account_usdc_bal,
+            &self.cfg.neutron.denoms.usdc,
+        ));
+        if self.plan_step(step) {
+            return;
         }
 
         self.ensure_neutron_account_fees_coverage(self.cfg.neutron.accounts.withdraw.to_string())
@@ -192,6 +269,7 @@ impl EthereumVaultRouting for Strategy {
             )
             .await
             .unwrap();
+        step_log.completed("USDC routed from Neutron to Noble");
     }
 
     /// CCTP-transfers funds from Ethereum deposit account to Noble inbound ica
@@ -219,11 +297,30 @@ impl EthereumVaultRouting for Strategy {
             Uint128::from_str(&eth_deposit_acc_usdc_bal.to_string()).unwrap();
         info!("[routing] eth_deposit_acc_usdc_u128: {eth_deposit_acc_usdc_u128}");
 
+        let step_log = StepLog::new("route_eth_to_noble", &self.cfg.ethereum.accounts.deposit)
+            .with_amount(eth_deposit_acc_usdc_u128.u128());
         if eth_deposit_acc_usdc_u128 < Uint128::new(10_000) {
-            info!("Ethereum deposit account balance < 10_000, returning...");
+            step_log.skipped("Ethereum deposit account balance < 10_000, returning...");
             return;
         } else {
-            info!("Ethereum deposit account USDC balance: {eth_deposit_acc_usdc_u128}");
+            step_log.started(&format!(
+                "Ethereum deposit account USDC balance: {eth_deposit_acc_usdc_u128}"
+            ));
+        }
+
+        let step = PlannedStep::new(
+            ETHEREUM_CHAIN_NAME,
+            &self.cfg.ethereum.libraries.cctp_forwarder,
+            "cctp transfer ethereum deposit account -> noble inbound ica",
+            &serde_json::json!({ "transfer": {} }),
+        )
+        .unwrap()
+        .with_amount(coin(
+            eth_deposit_acc_usdc_u128.u128(),
+            &self.cfg.ethereum.denoms.usdc_erc20,
+        ));
+        if self.plan_step(step) {
+            return;
         }
 
         let pre_cctp_inbound_ica_usdc_bal = self
@@ -251,10 +348,11 @@ impl EthereumVaultRouting for Strategy {
             )
             .await
             .unwrap();
+        step_log.completed("USDC routed from Ethereum to Noble");
     }
 
     /// CCTP-transfers funds from Noble outbound ica to Ethereum withdraw account
-    async fn route_noble_to_eth(&self) {
+    async fn route_noble_to_eth(&self) -> Uint128 {
         let pre_cctp_noble_outbound_ica_usdc_bal = self
             .noble_client
             .query_balance(
@@ -264,11 +362,16 @@ impl EthereumVaultRouting for Strategy {
             .await
             .unwrap();
 
+        let step_log = StepLog::new(
+            "route_noble_to_eth",
+            &self.cfg.neutron.accounts.noble_outbound_ica.remote_addr,
+        )
+        .with_amount(pre_cctp_noble_outbound_ica_usdc_bal);
         if pre_cctp_noble_outbound_ica_usdc_bal == 0 {
-            warn!("Noble outbound ICA account must have USDC in order to CCTP forward to Ethereum; returning");
-            return;
+            step_log.skipped("Noble outbound ICA account must have USDC in order to CCTP forward to Ethereum; returning");
+            return Uint128::zero();
         } else {
-            info!("CCTP forwarding USDC from Noble to Ethereum...");
+            step_log.started("CCTP forwarding USDC from Noble to Ethereum...");
         }
 
         let eth_rp = self.eth_client.get_request_provider().await.unwrap();
@@ -294,13 +397,49 @@ impl EthereumVaultRouting for Strategy {
         >::UpdateConfig {
             new_config: valence_ica_cctp_transfer::msg::LibraryConfigUpdate {
                 input_addr: None,
-                amount: Some(pre_cctp_noble_outbound_ica_usdc_bal.into()),
+                amount: Some(valence_ica_cctp_transfer::msg::TransferAmount::Fixed(
+                    pre_cctp_noble_outbound_ica_usdc_bal.into(),
+                )),
                 denom: None,
-                destination_domain_id: None,
+                destination_domain: None,
+                allow_unknown_domain: None,
                 mint_recipient: None,
+                max_fee: valence_library_utils::OptionUpdate::None,
+                min_finality_threshold: valence_library_utils::OptionUpdate::None,
+                destination_caller: valence_library_utils::OptionUpdate::None,
+                max_amount: valence_library_utils::OptionUpdate::None,
+                max_amount_behavior: None,
+                rounding: None,
+                attestation_sla: valence_library_utils::OptionUpdate::None,
+                decimals: valence_library_utils::OptionUpdate::None,
+                burn_cooldown: valence_library_utils::OptionUpdate::None,
+                burn_legs: None,
             },
         };
 
+        let update_cfg_step = PlannedStep::new(
+            NEUTRON_CHAIN_NAME,
+            &self.cfg.neutron.libraries.noble_cctp_transfer,
+            "update noble outbound cctp transfer config",
+            update_cfg_msg,
+        )
+        .unwrap();
+        let transfer_step = PlannedStep::new(
+            NEUTRON_CHAIN_NAME,
+            &self.cfg.neutron.libraries.noble_cctp_transfer,
+            "cctp transfer noble outbound ica -> ethereum withdraw account",
+            &valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
+                valence_ica_cctp_transfer::msg::FunctionMsgs::Transfer {},
+            ),
+        )
+        .unwrap()
+        .with_amount(coin(pre_cctp_noble_outbound_ica_usdc_bal, UUSDC_DENOM));
+        if self.plan_step(update_cfg_step) {
+            self.plan_step(transfer_step);
+            // nothing leaves in dry-run mode
+            return Uint128::zero();
+        }
+
         let update_rx = self
             .neutron_client
             .execute_wasm(
@@ -353,6 +492,9 @@ impl EthereumVaultRouting for Strategy {
             )
             .await
             .unwrap();
+        step_log.completed("USDC routed from Noble to Ethereum");
+
+        Uint128::new(pre_cctp_noble_outbound_ica_usdc_bal)
     }
 
     /// IBC-transfers funds from noble inbound ica into neutron deposit account
@@ -366,11 +508,18 @@ impl EthereumVaultRouting for Strategy {
             .await
             .unwrap();
 
+        let step_log = StepLog::new(
+            "route_noble_to_neutron",
+            &self.cfg.neutron.accounts.noble_inbound_ica.remote_addr,
+        )
+        .with_amount(noble_inbound_ica_balance);
         if noble_inbound_ica_balance == 0 {
-            warn!("Noble inbound ICA account must have enough USDC to route funds to Neutron deposit acc; returning");
+            step_log.skipped("Noble inbound ICA account must have enough USDC to route funds to Neutron deposit acc; returning");
             return;
         } else {
-            info!("noble inbound ica USDC balance: {noble_inbound_ica_balance}");
+            step_log.started(&format!(
+                "noble inbound ica USDC balance: {noble_inbound_ica_balance}"
+            ));
         }
 
         info!("updating noble inbound ica transfer cfg");
@@ -385,12 +534,40 @@ impl EthereumVaultRouting for Strategy {
                 denom: None,
                 receiver: None,
                 memo: None,
+                memo_template: valence_library_utils::OptionUpdate::None,
                 remote_chain_info: None,
                 denom_to_pfm_map: None,
                 eureka_config: valence_library_utils::OptionUpdate::Set(None),
+                min_transfer_amount: valence_library_utils::OptionUpdate::None,
+                recredit_failed_transfers: None,
+                expected_base_denom: valence_library_utils::OptionUpdate::None,
+                max_memo_bytes: valence_library_utils::OptionUpdate::None,
+                post_transfer_callback: valence_library_utils::OptionUpdate::None,
             },
         };
 
+        let update_cfg_step = PlannedStep::new(
+            NEUTRON_CHAIN_NAME,
+            &self.cfg.neutron.libraries.noble_inbound_transfer,
+            "update noble inbound ica transfer config",
+            update_cfg_msg,
+        )
+        .unwrap();
+        let transfer_step = PlannedStep::new(
+            NEUTRON_CHAIN_NAME,
+            &self.cfg.neutron.libraries.noble_inbound_transfer,
+            "ibc transfer noble inbound ica -> neutron deposit account",
+            &valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
+                valence_ica_ibc_transfer::msg::FunctionMsgs::Transfer {},
+            ),
+        )
+        .unwrap()
+        .with_amount(coin(noble_inbound_ica_balance, UUSDC_DENOM));
+        if self.plan_step(update_cfg_step) {
+            self.plan_step(transfer_step);
+            return;
+        }
+
         let update_rx = self
             .neutron_client
             .execute_wasm(
@@ -453,5 +630,6 @@ impl EthereumVaultRouting for Strategy {
             )
             .await
             .unwrap();
+        step_log.completed("USDC routed from Noble to Neutron");
     }
 }
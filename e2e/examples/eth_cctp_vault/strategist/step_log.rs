@@ -0,0 +1,214 @@
+use std::fmt::Display;
+
+use tracing::{error, info, warn};
+
+// outcome of a strategist step, recorded in the `outcome` field of its log records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    Started,
+    Completed,
+    Skipped,
+    Failed,
+}
+
+impl StepOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StepOutcome::Started => "started",
+            StepOutcome::Completed => "completed",
+            StepOutcome::Skipped => "skipped",
+            StepOutcome::Failed => "failed",
+        }
+    }
+}
+
+// structured log records of a strategist step. on top of the human readable
+// message, every record carries the `step`, `account` and `outcome` fields
+// (and `amount`, if known) so that the records of a run can be filtered and
+// aggregated by step or outcome. skipped steps are logged as warnings and
+// failed ones as errors. without a tracing subscriber, the records are
+// forwarded to `log` (with the fields appended to the message), so they
+// still show up in the env_logger output of the strategist.
+#[derive(Debug, Clone)]
+pub struct StepLog {
+    pub step: &'static str,
+    // account that the step moves funds from
+    pub account: String,
+    pub amount: Option<u128>,
+}
+
+impl StepLog {
+    pub fn new(step: &'static str, account: &str) -> Self {
+        StepLog {
+            step,
+            account: account.to_string(),
+            amount: None,
+        }
+    }
+
+    pub fn with_amount(mut self, amount: u128) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn started(&self, message: &str) {
+        self.record(StepOutcome::Started, message);
+    }
+
+    pub fn completed(&self, message: &str) {
+        self.record(StepOutcome::Completed, message);
+    }
+
+    pub fn skipped(&self, message: &str) {
+        self.record(StepOutcome::Skipped, message);
+    }
+
+    pub fn failed(&self, err: &dyn Display) {
+        self.record(StepOutcome::Failed, &format!("{} failed: {err}", self.step));
+    }
+
+    fn record(&self, outcome: StepOutcome, message: &str) {
+        let step = self.step;
+        let account = self.account.as_str();
+        let amount = self.amount;
+
+        match outcome {
+            StepOutcome::Skipped => warn!(
+                step,
+                account,
+                amount,
+                outcome = outcome.as_str(),
+                "{message}"
+            ),
+            StepOutcome::Failed => error!(
+                step,
+                account,
+                amount,
+                outcome = outcome.as_str(),
+                "{message}"
+            ),
+            StepOutcome::Started | StepOutcome::Completed => info!(
+                step,
+                account,
+                amount,
+                outcome = outcome.as_str(),
+                "{message}"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Level, Metadata, Subscriber,
+    };
+
+    use super::*;
+
+    // log record captured by the test subscriber
+    #[derive(Debug)]
+    struct CapturedEvent {
+        level: Level,
+        fields: BTreeMap<String, String>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut BTreeMap<String, String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    // subscriber recording the fields of every event
+    #[derive(Clone, Default)]
+    struct CaptureSubscriber(Arc<Mutex<Vec<CapturedEvent>>>);
+
+    impl Subscriber for CaptureSubscriber {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = BTreeMap::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            self.0.lock().unwrap().push(CapturedEvent {
+                level: *event.metadata().level(),
+                fields,
+            });
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    fn capture(f: impl FnOnce()) -> Vec<CapturedEvent> {
+        let subscriber = CaptureSubscriber::default();
+        tracing::subscriber::with_default(subscriber.clone(), f);
+        let mut events = subscriber.0.lock().unwrap();
+        std::mem::take(&mut *events)
+    }
+
+    #[test]
+    fn deposit_step_records_structured_fields() {
+        let events = capture(|| {
+            let step = StepLog::new("enter_position", "neutron1deposit").with_amount(1_000_000);
+            step.started("entering LP position...");
+            step.completed("success entering position");
+        });
+
+        assert_eq!(events.len(), 2);
+        for (event, outcome) in events.iter().zip(["started", "completed"]) {
+            assert_eq!(event.level, Level::INFO);
+            assert_eq!(event.fields["step"], "enter_position");
+            assert_eq!(event.fields["account"], "neutron1deposit");
+            assert_eq!(event.fields["amount"], "1000000");
+            assert_eq!(event.fields["outcome"], outcome);
+        }
+        assert_eq!(events[0].fields["message"], "entering LP position...");
+    }
+
+    #[test]
+    fn skipped_and_failed_steps_are_logged_with_their_level() {
+        let events = capture(|| {
+            let step = StepLog::new("enter_position", "neutron1deposit");
+            step.skipped("Deposit account must have USDC in order to LP; returning");
+            step.failed(&"out of gas");
+        });
+
+        assert_eq!(events[0].level, Level::WARN);
+        assert_eq!(events[0].fields["outcome"], "skipped");
+        // the amount is not recorded when unknown
+        assert!(!events[0].fields.contains_key("amount"));
+
+        assert_eq!(events[1].level, Level::ERROR);
+        assert_eq!(events[1].fields["outcome"], "failed");
+        assert_eq!(
+            events[1].fields["message"],
+            "enter_position failed: out of gas"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::{cmp::max, error::Error, path::Path, str::FromStr};
+use std::{cmp::max, error::Error, path::Path, str::FromStr, sync::Mutex};
 
 use alloy::{
     primitives::{Address, U256},
@@ -21,12 +21,19 @@ use valence_e2e::utils::{
     solidity_contracts::{MockERC20, ValenceVault},
     vault::time::{get_current_second, wait_until_next_minute},
     worker::{ValenceWorker, ValenceWorkerTomlSerde},
-    NOBLE_CHAIN_DENOM,
+    ETHEREUM_CHAIN_NAME, NOBLE_CHAIN_DENOM,
 };
 
-use crate::strategist::{astroport::AstroportOps, routing::EthereumVaultRouting};
+use crate::strategist::{
+    astroport::AstroportOps,
+    confirmations::InboundConfirmations,
+    liquidation::{now_secs, GuardedLiquidation, LiquidationCycle},
+    plan::{DryRunPlans, PlannedStep, StrategyPlan},
+    routing::EthereumVaultRouting,
+    step_log::StepLog,
+};
 
-use super::strategy_config::StrategyConfig;
+use super::strategy_config::{obligations::WithdrawObligationLedger, StrategyConfig, StrategyMode};
 
 // main strategy struct that wraps around the StrategyConfig
 // and stores the initialized clients
@@ -36,6 +43,15 @@ pub struct Strategy {
     pub(crate) eth_client: EthereumClient,
     pub(crate) noble_client: NobleClient,
     pub(crate) neutron_client: NeutronClient,
+
+    // messages that would have been submitted while running in dry-run mode
+    pub(crate) plans: Mutex<DryRunPlans>,
+
+    // withdraw obligations that are yet to be routed out to ethereum
+    pub(crate) obligations: Mutex<WithdrawObligationLedger>,
+
+    // height at which the current deposit account balance was first observed
+    pub(crate) deposit_confirmations: Mutex<InboundConfirmations>,
 }
 
 impl Strategy {
@@ -67,6 +83,9 @@ impl Strategy {
             eth_client,
             noble_client,
             neutron_client,
+            plans: Mutex::new(DryRunPlans::default()),
+            obligations: Mutex::new(WithdrawObligationLedger::default()),
+            deposit_confirmations: Mutex::new(InboundConfirmations::default()),
         })
     }
 
@@ -76,6 +95,50 @@ impl Strategy {
         let strategy_cfg = StrategyConfig::from_file(path)?;
         Self::new(strategy_cfg).await
     }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.cfg.mode == StrategyMode::DryRun
+    }
+
+    // in dry-run mode, records the step into the plan and returns true to signal
+    // that the step must not be broadcasted. in live mode this is a no-op.
+    pub(crate) fn plan_step(&self, step: PlannedStep) -> bool {
+        if self.is_dry_run() {
+            info!(
+                "[DRY RUN] planned {} on {}: {}",
+                step.description, step.domain, step.target
+            );
+        }
+        self.plans
+            .lock()
+            .unwrap()
+            .current_mut()
+            .record(&self.cfg.mode, step)
+    }
+
+    // applies the USDC transferred out to ethereum to the withdraw obligations
+    fn settle_obligations(&self, transferred_out: Uint128) {
+        let mut obligations = self.obligations.lock().unwrap();
+        let surplus = obligations.fulfill(transferred_out);
+        if !surplus.is_zero() {
+            warn!("[CYCLE] {surplus}uusdc transferred out in excess of the withdraw obligations");
+        }
+        info!(
+            "[CYCLE] withdraw obligations remaining: {}uusdc across {} obligations",
+            obligations.remaining_total(),
+            obligations.outstanding().len()
+        );
+    }
+
+    // returns the steps planned so far in the current cycle
+    pub fn load_plan(&self) -> StrategyPlan {
+        self.plans.lock().unwrap().current().clone()
+    }
+
+    // returns the plan of the last completed dry-run cycle, if any
+    pub fn load_last_plan(&self) -> Option<StrategyPlan> {
+        self.plans.lock().unwrap().last().cloned()
+    }
 }
 
 // implement the ValenceWorker trait for the Strategy struct.
@@ -90,8 +153,13 @@ impl ValenceWorker for Strategy {
     async fn cycle(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
         let worker_name = self.get_name();
         info!("{worker_name}: Starting cycle...");
+        // each dry-run cycle plans against the fresh on-chain state
+        if self.is_dry_run() {
+            self.plans.lock().unwrap().start_cycle();
+        }
         info!("{worker_name}: Waiting until next minute...");
         wait_until_next_minute().await;
+        let cycle_started_at = now_secs();
         let eth_block = self.eth_client.latest_block_height().await?;
         info!(
             "{worker_name}: worker loop started at second {} at evm block: {eth_block}",
@@ -181,6 +249,17 @@ impl ValenceWorker for Strategy {
         info!("[CYCLE] netting amount Uint128: {netting_amount_u128}");
         info!("[CYCLE] effective pending obligations: {effective_pending_obligations}");
 
+        // 3. register the obligations of this epoch into the ledger
+        let effective_pending_obligations_u128 =
+            Uint128::from_str(&effective_pending_obligations.to_string())?;
+        if !effective_pending_obligations_u128.is_zero() {
+            let obligation_id = self.obligations.lock().unwrap().enqueue(
+                effective_pending_obligations_u128,
+                &self.cfg.ethereum.accounts.withdraw,
+            );
+            info!("[CYCLE] enqueued withdraw obligation #{obligation_id}");
+        }
+
         // =================== calculate withdraw amt =========================
         // 1. half the pending obligations to estimate the amount of neutron needed
         // to obtain it
@@ -294,21 +373,35 @@ impl ValenceWorker for Strategy {
                 netting_amount: {netting_amount}"
         );
 
-        let update_result = self
-            .eth_client
-            .execute_tx(
-                valence_vault
-                    .update(
-                        r,
-                        total_fee,
-                        U256::from_be_bytes(netting_amount.to_be_bytes()),
-                    )
-                    .into_transaction_request(),
-            )
-            .await?;
-        eth_rp
-            .get_transaction_receipt(update_result.transaction_hash)
-            .await?;
+        let vault_update_step = PlannedStep::new(
+            ETHEREUM_CHAIN_NAME,
+            &self.cfg.ethereum.libraries.valence_vault,
+            "update vault",
+            &serde_json::json!({
+                "update": {
+                    "rate": r.to_string(),
+                    "withdraw_fee_bps": total_fee,
+                    "netting_amount": netting_amount.to_string(),
+                }
+            }),
+        )?;
+        if !self.plan_step(vault_update_step) {
+            let update_result = self
+                .eth_client
+                .execute_tx(
+                    valence_vault
+                        .update(
+                            r,
+                            total_fee,
+                            U256::from_be_bytes(netting_amount.to_be_bytes()),
+                        )
+                        .into_transaction_request(),
+                )
+                .await?;
+            eth_rp
+                .get_transaction_receipt(update_result.transaction_hash)
+                .await?;
+        }
 
         // ====================================================================
 
@@ -325,35 +418,35 @@ impl ValenceWorker for Strategy {
         // provide them into the LP
         match self.enter_position().await {
             Ok(_) => (),
-            Err(e) => warn!("error entering position: {:?}", e),
+            Err(e) => StepLog::new("enter_position", &self.cfg.neutron.accounts.deposit).failed(&e),
         };
         // ====================================================================
 
-        // ======================= exit the position ==========================
-        // 1. forward the shares to be liquidated from the position account to the withdraw account
-        self.forward_shares_for_liquidation(shares_to_liquidate)
-            .await;
-
-        // 2. liquidate the forwarded shares to get USDC+NTRN
-        match self.exit_position().await {
-            Ok(_) => (),
-            Err(e) => warn!("error exiting position: {:?}", e),
-        };
-
-        // 3. swap NTRN into USDC to obtain the full obligation amount
-        match self.swap_ntrn_into_usdc().await {
-            Ok(_) => (),
-            Err(e) => warn!("error swapping ntrn into usdc: {:?}", e),
-        };
-
-        // ====================================================================
-
-        // ================== route the funds ntrn->eth =======================
-        //   1. ibc transfer neutron withdraw acc -> noble outbound ica
-        self.route_neutron_to_noble().await;
+        // ================ exit the position and route to eth ================
+        // the cycle aims at delivering the pending obligations to ethereum.
+        // every step is checked against its min output and deadline, and the
+        // remaining steps are not executed once the cycle drifts off track.
+        let liquidation_cycle = LiquidationCycle::new(
+            &self.cfg.liquidation,
+            effective_pending_obligations_u128,
+            cycle_started_at,
+        );
+        info!(
+            "[CYCLE] liquidation target: {}uusdc, min output: {}uusdc, deadline: {}",
+            liquidation_cycle.target_usdc, liquidation_cycle.min_output, liquidation_cycle.deadline
+        );
 
-        //   2. cctp transfer noble outbound ica -> eth withdraw acc
-        self.route_noble_to_eth().await;
+        match self
+            .liquidate(&liquidation_cycle, shares_to_liquidate)
+            .await
+        {
+            Ok(transferred_out) => self.settle_obligations(transferred_out),
+            Err(abort) => {
+                StepLog::new("liquidation_cycle", &self.cfg.neutron.accounts.position).skipped(
+                    &format!("{abort}; funds are left in place for the next cycle"),
+                );
+            }
+        }
         // ====================================================================
 
         info!(
@@ -361,6 +454,14 @@ impl ValenceWorker for Strategy {
             get_current_second()
         );
 
+        if self.is_dry_run() {
+            let mut plans = self.plans.lock().unwrap();
+            info!(
+                "[DRY RUN] cycle plan: {}",
+                serde_json::to_string_pretty(plans.complete_cycle())?
+            );
+        }
+
         Ok(())
     }
 }
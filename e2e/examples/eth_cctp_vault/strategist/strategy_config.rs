@@ -1,17 +1,41 @@
 use serde::{Deserialize, Serialize};
 use valence_e2e::utils::worker::ValenceWorkerTomlSerde;
 
+use super::{confirmations::ConfirmationsConfig, liquidation::LiquidationGuardConfig};
+
 // here we define the inputs for the strategy.
 // this configuration type should have sufficient information
 // to create the strategy, initialize the (g)rpc clients, and
 // begin with the execution.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyConfig {
+    // defaults to live execution if not specified
+    #[serde(default)]
+    pub mode: StrategyMode,
+    // min output and deadline of the liquidation cycles, with defaults if not specified
+    #[serde(default)]
+    pub liquidation: LiquidationGuardConfig,
+    // confirmations of the inbound transfers before their balance is acted on,
+    // acting on it right away if not specified
+    #[serde(default)]
+    pub confirmations: ConfirmationsConfig,
     pub noble: noble::NobleStrategyConfig,
     pub neutron: neutron::NeutronStrategyConfig,
     pub ethereum: ethereum::EthereumStrategyConfig,
 }
 
+// determines whether the strategy broadcasts its messages or only
+// plans them for inspection
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyMode {
+    #[default]
+    Live,
+    // every step records the message it would submit into the strategy
+    // plan instead of broadcasting it
+    DryRun,
+}
+
 // default impl serde trait to enable toml config file parsing
 impl ValenceWorkerTomlSerde for StrategyConfig {}
 
@@ -114,3 +138,241 @@ pub mod ethereum {
         pub lite_processor: String,
     }
 }
+
+// withdraw obligations of the vault that the strategist must route out to ethereum
+pub mod obligations {
+    use cosmwasm_std::Uint128;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ObligationStatus {
+        // waiting for the position to be liquidated
+        Pending,
+        // shares forwarded for liquidation and withdrawn from the pool,
+        // funds not yet transferred out to ethereum
+        Liquidating,
+        // part of the amount already reached ethereum
+        PartiallyFulfilled { fulfilled: Uint128 },
+        Fulfilled,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct WithdrawObligation {
+        pub id: u64,
+        // usdc amount owed to the recipient
+        pub amount: Uint128,
+        pub recipient: String,
+        pub status: ObligationStatus,
+    }
+
+    impl WithdrawObligation {
+        // amount that still needs to leave to ethereum
+        pub fn remaining(&self) -> Uint128 {
+            match &self.status {
+                ObligationStatus::Pending | ObligationStatus::Liquidating => self.amount,
+                ObligationStatus::PartiallyFulfilled { fulfilled } => {
+                    self.amount.saturating_sub(*fulfilled)
+                }
+                ObligationStatus::Fulfilled => Uint128::zero(),
+            }
+        }
+
+        fn fulfilled(&self) -> Uint128 {
+            self.amount - self.remaining()
+        }
+    }
+
+    // ledger of the withdraw obligations, updated by the strategist as the funds
+    // move along the liquidation flow. obligations are fulfilled in the order
+    // they were enqueued.
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct WithdrawObligationLedger {
+        pub obligations: Vec<WithdrawObligation>,
+        next_id: u64,
+    }
+
+    impl WithdrawObligationLedger {
+        // registers a new obligation and returns its id
+        pub fn enqueue(&mut self, amount: Uint128, recipient: &str) -> u64 {
+            let id = self.next_id;
+            self.next_id += 1;
+            self.obligations.push(WithdrawObligation {
+                id,
+                amount,
+                recipient: recipient.to_string(),
+                status: ObligationStatus::Pending,
+            });
+            id
+        }
+
+        // marks the pending obligations as being liquidated, once the shares
+        // covering them are forwarded and withdrawn from the pool
+        pub fn mark_liquidating(&mut self) {
+            for obligation in self.obligations.iter_mut() {
+                if obligation.status == ObligationStatus::Pending {
+                    obligation.status = ObligationStatus::Liquidating;
+                }
+            }
+        }
+
+        // applies `amount` transferred out to ethereum to the outstanding obligations,
+        // oldest first. returns the part of `amount` exceeding the outstanding total.
+        pub fn fulfill(&mut self, amount: Uint128) -> Uint128 {
+            let mut available = amount;
+            for obligation in self.obligations.iter_mut() {
+                if available.is_zero() {
+                    break;
+                }
+
+                let remaining = obligation.remaining();
+                if remaining.is_zero() {
+                    continue;
+                }
+
+                let applied = remaining.min(available);
+                available -= applied;
+                obligation.status = if applied == remaining {
+                    ObligationStatus::Fulfilled
+                } else {
+                    ObligationStatus::PartiallyFulfilled {
+                        fulfilled: obligation.fulfilled() + applied,
+                    }
+                };
+            }
+            available
+        }
+
+        // total amount that still needs to leave to ethereum
+        pub fn remaining_total(&self) -> Uint128 {
+            self.obligations
+                .iter()
+                .map(WithdrawObligation::remaining)
+                .sum()
+        }
+
+        // obligations that are not fulfilled yet
+        pub fn outstanding(&self) -> Vec<&WithdrawObligation> {
+            self.obligations
+                .iter()
+                .filter(|o| o.status != ObligationStatus::Fulfilled)
+                .collect()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn status(ledger: &WithdrawObligationLedger, id: u64) -> &ObligationStatus {
+            &ledger
+                .obligations
+                .iter()
+                .find(|o| o.id == id)
+                .unwrap()
+                .status
+        }
+
+        const RECIPIENT: &str = "0x0000000000000000000000000000000000000001";
+
+        #[test]
+        fn enqueued_obligations_are_pending() {
+            let mut ledger = WithdrawObligationLedger::default();
+            let first = ledger.enqueue(Uint128::new(100), RECIPIENT);
+            let second = ledger.enqueue(Uint128::new(50), RECIPIENT);
+
+            assert_ne!(first, second);
+            assert_eq!(*status(&ledger, first), ObligationStatus::Pending);
+            assert_eq!(ledger.remaining_total(), Uint128::new(150));
+            assert_eq!(ledger.outstanding().len(), 2);
+
+            ledger.mark_liquidating();
+            assert_eq!(*status(&ledger, second), ObligationStatus::Liquidating);
+            assert_eq!(ledger.remaining_total(), Uint128::new(150));
+        }
+
+        #[test]
+        fn partial_fulfillment_is_applied_oldest_first() {
+            let mut ledger = WithdrawObligationLedger::default();
+            let first = ledger.enqueue(Uint128::new(100), RECIPIENT);
+            let second = ledger.enqueue(Uint128::new(50), RECIPIENT);
+
+            assert_eq!(ledger.fulfill(Uint128::new(120)), Uint128::zero());
+            assert_eq!(*status(&ledger, first), ObligationStatus::Fulfilled);
+            assert_eq!(
+                *status(&ledger, second),
+                ObligationStatus::PartiallyFulfilled {
+                    fulfilled: Uint128::new(20)
+                }
+            );
+            assert_eq!(ledger.remaining_total(), Uint128::new(30));
+            assert_eq!(ledger.outstanding().len(), 1);
+
+            // fulfilling a partially fulfilled obligation accumulates the amounts
+            assert_eq!(ledger.fulfill(Uint128::new(10)), Uint128::zero());
+            assert_eq!(
+                *status(&ledger, second),
+                ObligationStatus::PartiallyFulfilled {
+                    fulfilled: Uint128::new(30)
+                }
+            );
+            assert_eq!(ledger.remaining_total(), Uint128::new(20));
+        }
+
+        #[test]
+        fn surplus_over_the_remaining_total_is_returned() {
+            let mut ledger = WithdrawObligationLedger::default();
+            ledger.enqueue(Uint128::new(100), RECIPIENT);
+
+            assert_eq!(ledger.fulfill(Uint128::new(130)), Uint128::new(30));
+            assert_eq!(ledger.remaining_total(), Uint128::zero());
+            assert!(ledger.outstanding().is_empty());
+
+            // nothing is left to fulfill
+            assert_eq!(ledger.fulfill(Uint128::new(5)), Uint128::new(5));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deployment_round_trips_through_the_config_file() {
+        let cfg: StrategyConfig = toml::from_str(include_str!("example_strategy.toml")).unwrap();
+        let dir =
+            std::env::temp_dir().join(format!("valence_strategy_config_{}", std::process::id()));
+        // the deployment directory is created along with the file
+        let path = dir.join("deployment").join("strategy.toml");
+
+        cfg.to_file(&path).unwrap();
+        let reloaded = StrategyConfig::from_file(&path).unwrap();
+
+        assert_eq!(
+            toml::to_string(&reloaded).unwrap(),
+            toml::to_string(&cfg).unwrap()
+        );
+        assert_eq!(
+            reloaded.neutron.accounts.deposit,
+            cfg.neutron.accounts.deposit
+        );
+        assert_eq!(
+            reloaded.neutron.accounts.noble_inbound_ica.remote_addr,
+            cfg.neutron.accounts.noble_inbound_ica.remote_addr
+        );
+        assert_eq!(
+            reloaded.neutron.libraries.astroport_lper,
+            cfg.neutron.libraries.astroport_lper
+        );
+        assert_eq!(reloaded.neutron.chain_id, "localneutron-1");
+        assert_eq!(
+            reloaded.ethereum.libraries.valence_vault,
+            cfg.ethereum.libraries.valence_vault
+        );
+        // the temporary file used to write the config atomically is moved in place
+        assert!(!path.with_extension("toml.tmp").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
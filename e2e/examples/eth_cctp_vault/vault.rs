@@ -7,7 +7,7 @@ use std::{
 };
 
 use alloy::primitives::{Address, U256};
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
 use evm::{setup_eth_accounts, setup_eth_libraries};
 use localic_utils::{
     types::config::ConfigChain, utils::ethereum::EthClient, ConfigChainBuilder, TestContextBuilder,
@@ -17,11 +17,14 @@ use localic_utils::{
 
 use log::{info, warn};
 
-use program::{setup_neutron_accounts, setup_neutron_libraries, upload_neutron_contracts};
+use program::{
+    neutron_program_health, teardown_program, upload_neutron_contracts, ProgramBuilder,
+    VaultChainConfig,
+};
 
 use strategist::{
     strategy::Strategy,
-    strategy_config::{self, StrategyConfig},
+    strategy_config::{self, StrategyConfig, StrategyMode},
 };
 
 use valence_domain_clients::{
@@ -35,6 +38,7 @@ use valence_e2e::{
     utils::{
         astroport::{astroport_cl_swap, setup_astroport_cl_pool},
         authorization::set_up_authorization_and_processor,
+        batch::LibrarySetupMode,
         ethereum::{self as ethereum_utils, ANVIL_NAME, DEFAULT_ANVIL_PORT},
         mocks::cctp_relayer_evm_noble::MockCctpRelayerEvmNoble,
         parse::{get_chain_field_from_local_ic_log, get_grpc_address_and_port_from_url},
@@ -50,6 +54,8 @@ use valence_e2e::{
 const _PROVIDE_LIQUIDITY_AUTHORIZATIONS_LABEL: &str = "provide_liquidity";
 const _WITHDRAW_LIQUIDITY_AUTHORIZATIONS_LABEL: &str = "withdraw_liquidity";
 const VAULT_NEUTRON_CACHE_PATH: &str = "e2e/examples/eth_cctp_vault/neutron_contracts/";
+// share of the noble outbound ica balance that gets CCTP-transferred to ethereum
+const CCTP_SPLIT_RATIO: Decimal = Decimal::percent(50);
 
 mod evm;
 mod neutron;
@@ -113,10 +119,13 @@ fn main() -> Result<(), Box<dyn Error>> {
     // setup hyperlane between neutron and eth
     let program_hyperlane_contracts = vault::hyperlane_plumbing(&mut test_ctx, &eth)?;
 
+    // the local environment runs noble as the stablecoin hub
+    let chain_config = VaultChainConfig::default();
+
     let uusdc_on_neutron_denom = test_ctx
         .get_ibc_denom()
-        .base_denom(UUSDC_DENOM.to_owned())
-        .src(NOBLE_CHAIN_NAME)
+        .base_denom(chain_config.hub_denom.clone())
+        .src(&chain_config.hub_chain_name)
         .dest(NEUTRON_CHAIN_NAME)
         .get();
 
@@ -217,19 +226,27 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     upload_neutron_contracts(&mut test_ctx)?;
 
-    let neutron_program_accounts = setup_neutron_accounts(&mut test_ctx)?;
-
-    let neutron_program_libraries = setup_neutron_libraries(
+    // the setup report is logged once the libraries are set up
+    let (neutron_program_accounts, neutron_program_libraries, _) = ProgramBuilder::new()
+        .with_pool(&pool_addr, &lp_token)
+        .with_authorizations(&authorization_contract_address)
+        .with_processor(&neutron_processor_address)
+        .with_transfer_amount(amount_to_transfer)
+        .with_usdc_on_neutron(&uusdc_on_neutron_denom)
+        .with_eth_withdraw_account(&ethereum_program_accounts.withdraw)
+        .with_cctp_split_ratio(CCTP_SPLIT_RATIO)
+        .with_setup_mode(LibrarySetupMode::Batched)
+        .with_chain_config(chain_config)
+        .build(&mut test_ctx)?;
+
+    // readiness gate before wiring the strategist onto the neutron program.
+    // it also asserts that the batched setup instantiated and approved every library
+    neutron_program_health(
         &mut test_ctx,
         &neutron_program_accounts,
-        &pool_addr,
-        &authorization_contract_address,
-        &neutron_processor_address,
-        amount_to_transfer,
-        &uusdc_on_neutron_denom,
-        ethereum_program_accounts.withdraw.to_string(),
-        &lp_token,
-    )?;
+        &neutron_program_libraries,
+    )
+    .ensure_ready()?;
 
     let ethereum_program_libraries = setup_eth_libraries(
         &rt,
@@ -279,6 +296,9 @@ fn main() -> Result<(), Box<dyn Error>> {
     sleep(Duration::from_secs(3));
 
     let strategy_config = StrategyConfig {
+        mode: StrategyMode::Live,
+        liquidation: Default::default(),
+        confirmations: Default::default(),
         noble: strategy_config::noble::NobleStrategyConfig {
             grpc_url: noble_grpc_url,
             grpc_port: noble_grpc_port,
@@ -630,5 +650,14 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     }
 
+    // drain the program accounts back to the admin once the run is over
+    teardown_program(
+        &mut test_ctx,
+        &strategy_config.neutron.accounts,
+        &strategy_config.neutron.libraries,
+        NEUTRON_CHAIN_ADMIN_ADDR,
+        true,
+    )?;
+
     Ok(())
 }
@@ -1,10 +1,11 @@
-use std::{error::Error, path::Path};
+use std::error::Error;
 
 use localic_utils::{
     utils::test_context::TestContext, DEFAULT_KEY, GAIA_CHAIN_ADMIN_ADDR, GAIA_CHAIN_NAME,
     NEUTRON_CHAIN_ADMIN_ADDR, NEUTRON_CHAIN_DENOM, NEUTRON_CHAIN_NAME,
 };
 use log::info;
+use valence_astroport_utils::PoolKind;
 use valence_e2e::{
     async_run,
     utils::{
@@ -14,8 +15,9 @@ use valence_e2e::{
             ASTROPORT_LPER_NAME, ASTROPORT_WITHDRAWER_NAME, BASE_ACCOUNT_NAME, FORWARDER_NAME,
             NEUTRON_IBC_TRANSFER_NAME,
         },
+        setup_context::LibraryDeployment,
+        upload_contracts,
         vault::{setup_liquidation_fwd_lib, setup_neutron_ibc_transfer_lib},
-        LOCAL_CODE_ID_CACHE_PATH_NEUTRON,
     },
 };
 use valence_ibc_utils::types::EurekaConfig;
@@ -27,33 +29,18 @@ use crate::{
 };
 
 pub fn upload_neutron_contracts(test_ctx: &mut TestContext) -> Result<(), Box<dyn Error>> {
-    // copy over relevant contracts from artifacts/ to local path
-    let local_contracts_path = Path::new(VAULT_NEUTRON_CACHE_PATH);
-    if !local_contracts_path.exists() {
-        std::fs::create_dir(local_contracts_path)?;
-    }
-
-    for contract in [
-        ASTROPORT_LPER_NAME,
-        ASTROPORT_WITHDRAWER_NAME,
-        NEUTRON_IBC_TRANSFER_NAME,
-        FORWARDER_NAME,
-        BASE_ACCOUNT_NAME,
-    ] {
-        let contract_name = format!("{contract}.wasm");
-        let contract_path = Path::new(&contract_name);
-        let src = Path::new("artifacts/").join(contract_path);
-        let dest = local_contracts_path.join(contract_path);
-        std::fs::copy(src, dest)?;
-    }
-
-    let mut uploader = test_ctx.build_tx_upload_contracts();
-    uploader
-        .with_chain_name(NEUTRON_CHAIN_NAME)
-        .send_with_local_cache(
-            "e2e/examples/eth_eureka_vault/neutron_contracts/",
-            LOCAL_CODE_ID_CACHE_PATH_NEUTRON,
-        )?;
+    upload_contracts(
+        test_ctx,
+        NEUTRON_CHAIN_NAME,
+        &[
+            ASTROPORT_LPER_NAME,
+            ASTROPORT_WITHDRAWER_NAME,
+            NEUTRON_IBC_TRANSFER_NAME,
+            FORWARDER_NAME,
+            BASE_ACCOUNT_NAME,
+        ],
+        VAULT_NEUTRON_CACHE_PATH,
+    )?;
 
     Ok(())
 }
@@ -75,15 +62,15 @@ pub fn setup_neutron_accounts(
         base_account_code_id,
         NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         vec![],
-        4,
+        &["deposit", "position", "withdraw", "liquidation"],
         None,
     );
 
     let neutron_accounts = strategy_config::neutron::NeutronAccounts {
-        deposit: neutron_base_accounts[0].to_string(),
-        position: neutron_base_accounts[1].to_string(),
-        withdraw: neutron_base_accounts[2].to_string(),
-        liquidation: neutron_base_accounts[3].to_string(),
+        deposit: neutron_base_accounts["deposit"].to_string(),
+        position: neutron_base_accounts["position"].to_string(),
+        withdraw: neutron_base_accounts["withdraw"].to_string(),
+        liquidation: neutron_base_accounts["liquidation"].to_string(),
     };
 
     Ok(neutron_accounts)
@@ -103,6 +90,7 @@ pub fn setup_neutron_libraries(
     let astro_cl_pool_asset_data = AssetData {
         asset1: NEUTRON_CHAIN_DENOM.to_string(),
         asset2: wbtc_on_neutron.to_string(),
+        additional_assets: vec![],
     };
 
     // library to enter into the position from the deposit account
@@ -113,8 +101,12 @@ pub fn setup_neutron_libraries(
         neutron_program_accounts.position.to_string(),
         astro_cl_pool_asset_data.clone(),
         pool.to_string(),
+        PoolKind::ConcentratedLiquidity,
+        // leftovers of a provision already stay in the deposit account (the input account)
+        None,
         processor.to_string(),
         authorizations.to_string(),
+        &LibraryDeployment::default(),
     )?;
 
     // library to forward the required amount of shares, from the position account
@@ -124,6 +116,8 @@ pub fn setup_neutron_libraries(
         neutron_program_accounts.position.to_string(),
         neutron_program_accounts.liquidation.to_string(),
         lp_token_denom,
+        None,
+        &LibraryDeployment::default(),
     )?;
 
     // library to withdraw the position held by the position account
@@ -134,7 +128,9 @@ pub fn setup_neutron_libraries(
         neutron_program_accounts.withdraw.to_string(),
         astro_cl_pool_asset_data.clone(),
         pool.to_string(),
+        PoolKind::ConcentratedLiquidity,
         processor.to_string(),
+        &LibraryDeployment::default(),
     )?;
 
     info!("approving strategist on liquidation account...");
@@ -171,19 +167,20 @@ pub fn setup_neutron_libraries(
         authorizations.to_string(),
         processor.to_string(),
         GAIA_CHAIN_NAME, // dest chain name
-        Some(EurekaConfig {
-            // mainnet hub callback contract
-            // callback_contract: skip_api_response.callback_adapter_contract_address,
-            callback_contract: GAIA_CHAIN_ADMIN_ADDR.to_string(),
-            // mainnet hub action contract
-            action_contract: skip_api_response.entry_contract_address,
-            // hardcoded for now, in the future this should be updated to a program-owned ICA
-            recover_address: GAIA_CHAIN_ADMIN_ADDR.to_string(),
-            // mainnet hub
-            source_channel: skip_api_response.source_client,
-            memo: None,
-            timeout: None,
-        }),
+        Some(
+            EurekaConfig::builder()
+                // mainnet hub callback contract
+                // .callback(skip_api_response.callback_adapter_contract_address)
+                .callback(GAIA_CHAIN_ADMIN_ADDR)
+                // mainnet hub action contract
+                .action(skip_api_response.entry_contract_address)
+                // hardcoded for now, in the future this should be updated to a program-owned ICA
+                .recover_address(GAIA_CHAIN_ADMIN_ADDR)
+                // mainnet hub
+                .source_channel(skip_api_response.source_client)
+                .build()?,
+        ),
+        &LibraryDeployment::default(),
     )?;
 
     let libraries = strategy_config::neutron::NeutronLibraries {
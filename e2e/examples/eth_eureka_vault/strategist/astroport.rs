@@ -158,6 +158,7 @@ impl AstroportOps for Strategy {
             &valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
                 valence_astroport_withdrawer::msg::FunctionMsgs::WithdrawLiquidity {
                     expected_pool_ratio_range: None,
+                    value_provided: None,
                 },
             );
         let rx = self
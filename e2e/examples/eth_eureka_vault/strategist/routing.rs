@@ -93,9 +93,17 @@ impl EurekaVaultRouting for Strategy {
         >::UpdateConfig {
             new_config: valence_forwarder_library::msg::LibraryConfigUpdate {
                 input_addr: None,
+                additional_input_addrs: valence_library_utils::OptionUpdate::None,
                 output_addr: None,
                 forwarding_configs: Some(vec![updated_share_fwd_cfg]),
                 forwarding_constraints: None,
+                split_outputs: valence_library_utils::OptionUpdate::None,
+                rounding: None,
+                lp_redemption: valence_library_utils::OptionUpdate::None,
+                gas_reserve: valence_library_utils::OptionUpdate::None,
+                snapshot_balances: None,
+                precondition: valence_library_utils::OptionUpdate::None,
+                fee_skim: valence_library_utils::OptionUpdate::None,
             },
         };
 
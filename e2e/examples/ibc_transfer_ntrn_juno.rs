@@ -61,7 +61,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         .code_id
         .unwrap();
 
-    // Create 1 base accounts on Neutron, to be the input account for the IBC transfer library
+    // Create 1 base account on Neutron, to be the input account for the IBC transfer library
     let base_accounts = create_base_accounts(
         &mut test_ctx,
         DEFAULT_KEY,
@@ -69,10 +69,10 @@ fn main() -> Result<(), Box<dyn Error>> {
         code_id_base_account,
         NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         vec![],
-        1,
+        &["input"],
         None,
     );
-    let input_account = base_accounts[0].clone();
+    let input_account = base_accounts["input"].to_string();
     info!("Input account: {:?}", input_account);
 
     // Send native tokens to the input account
@@ -250,6 +250,11 @@ fn main() -> Result<(), Box<dyn Error>> {
         remote_chain_info: None,
         denom_to_pfm_map: None,
         eureka_config: valence_library_utils::OptionUpdate::None,
+        min_transfer_amount: valence_library_utils::OptionUpdate::None,
+        denom_selection: valence_library_utils::OptionUpdate::None,
+        gas_reserve: valence_library_utils::OptionUpdate::None,
+        precondition: valence_library_utils::OptionUpdate::None,
+        transfer_defaults: valence_library_utils::OptionUpdate::None,
     };
     let upd_cfg_msg =
         valence_library_utils::msg::ExecuteMsg::<FunctionMsgs, LibraryConfigUpdate>::UpdateConfig {
@@ -61,7 +61,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         .get("valence_base_account")
         .unwrap();
 
-    // Create 1 base accounts on Neutron, to be the input account for the IBC transfer library
+    // Create 1 base account on Neutron, to be the input account for the IBC transfer library
     let base_accounts = create_base_accounts(
         &mut test_ctx,
         DEFAULT_KEY,
@@ -69,10 +69,10 @@ fn main() -> Result<(), Box<dyn Error>> {
         code_id_base_account,
         NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         vec![],
-        1,
+        &["input"],
         None,
     );
-    let input_account = base_accounts[0].clone();
+    let input_account = base_accounts["input"].to_string();
     info!("Input account: {:?}", input_account);
 
     // Send some NTRN to the input account to pay for the IBC transfer fee
@@ -218,6 +218,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                         .dest(GAIA_CHAIN_NAME)
                         .get(),
                     hop_chain_receiver_address: Some(JUNO_CHAIN_ADMIN_ADDR.to_string()),
+                    retries: None,
+                    timeout: None,
                 },
             )]),
         ),
@@ -62,7 +62,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         .get("valence_base_account")
         .unwrap();
 
-    // Create 1 base accounts on Osmosis, to be the input account for the IBC transfer library
+    // Create 1 base account on Osmosis, to be the input account for the IBC transfer library
     let base_accounts = create_base_accounts(
         &mut test_ctx,
         DEFAULT_KEY,
@@ -70,10 +70,10 @@ fn main() -> Result<(), Box<dyn Error>> {
         code_id_base_account,
         OSMOSIS_CHAIN_ADMIN_ADDR.to_string(),
         vec![],
-        1,
+        &["input"],
         Some(coin(2000, OSMOSIS_CHAIN_DENOM)),
     );
-    let input_account = base_accounts[0].clone();
+    let input_account = base_accounts["input"].to_string();
     info!("Input account: {:?}", input_account);
 
     // Send native tokens to the input account
@@ -1,7 +1,7 @@
 use std::{collections::BTreeMap, env, error::Error, time::Duration};
 
 use cosmwasm_std::{Binary, Uint128, Uint64};
-use localic_std::modules::cosmwasm::{contract_execute, contract_instantiate, contract_query};
+use localic_std::modules::cosmwasm::{contract_execute, contract_instantiate};
 use localic_utils::{
     types::{config::ConfigChain, ibc::get_multihop_ibc_denom},
     ConfigChainBuilder, TestContextBuilder, DEFAULT_KEY, LOCAL_IC_API_URL,
@@ -14,7 +14,7 @@ use valence_domain_clients::{
     clients::neutron::NeutronClient, clients::noble::NobleClient, cosmos::base_client::BaseClient,
 };
 use valence_e2e::utils::{
-    ibc::poll_for_ica_state,
+    ibc::{assert_ica_registered, poll_for_ica_state},
     parse::{get_chain_field_from_local_ic_log, get_grpc_address_and_port_from_url},
     relayer::restart_relayer,
     ADMIN_MNEMONIC, GAS_FLAGS, LOGS_FILE_PATH, NOBLE_CHAIN_ADMIN_ADDR, NOBLE_CHAIN_DENOM,
@@ -165,24 +165,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     });
 
     // Get the remote address
-    let ica_state: IcaState = serde_json::from_value(
-        contract_query(
-            test_ctx
-                .get_request_builder()
-                .get_request_builder(NEUTRON_CHAIN_NAME),
-            &valence_ica.address,
-            &serde_json::to_string(&valence_account_utils::ica::QueryMsg::IcaState {}).unwrap(),
-        )["data"]
-            .clone(),
-    )
-    .unwrap();
-
-    let remote_address = match ica_state {
-        IcaState::Created(ica_info) => ica_info.address,
-        _ => {
-            unreachable!("Expected IcaState::Created variant");
-        }
-    };
+    let remote_address = assert_ica_registered(&mut test_ctx, &valence_ica.address)?.address;
     info!("Remote address created: {}", remote_address);
 
     info!("Start ICA CCTP transfer library test...");
@@ -227,10 +210,24 @@ fn main() -> Result<(), Box<dyn Error>> {
         processor: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         config: valence_ica_cctp_transfer::msg::LibraryConfig {
             input_addr: LibraryAccountType::Addr(valence_ica.address.clone()),
-            amount: Uint128::new(amount_to_transfer),
+            amount: valence_ica_cctp_transfer::msg::TransferAmount::Fixed(Uint128::new(
+                amount_to_transfer,
+            )),
             denom: UUSDC_DENOM.to_string(),
-            destination_domain_id: 0,
-            mint_recipient: Binary::from(&[0x01; 32]),
+            destination_domain: valence_ica_cctp_transfer::domain::DestinationDomain::Ethereum,
+            allow_unknown_domain: false,
+            // Ethereum address left-padded with zeroes to 32 bytes
+            mint_recipient: Binary::from([vec![0u8; 12], vec![0x01; 20]].concat()),
+            max_fee: None,
+            min_finality_threshold: None,
+            destination_caller: None,
+            max_amount: None,
+            max_amount_behavior: Default::default(),
+            rounding: Default::default(),
+            attestation_sla: None,
+            decimals: None,
+            burn_cooldown: None,
+            burn_legs: vec![],
         },
     };
 
@@ -323,6 +320,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             denom: UUSDC_DENOM.to_string(),
             receiver: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
             memo: "".to_string(),
+            memo_template: None,
             remote_chain_info: RemoteChainInfo {
                 channel_id: test_ctx
                     .get_transfer_channels()
@@ -330,6 +328,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                     .dest(OSMOSIS_CHAIN_NAME)
                     .get(),
                 ibc_transfer_timeout: None,
+                fallback_channel_ids: vec![],
+                counterparty_channel_ids: BTreeMap::default(),
             },
             denom_to_pfm_map: BTreeMap::from([(
                 UUSDC_DENOM.to_string(),
@@ -345,9 +345,16 @@ fn main() -> Result<(), Box<dyn Error>> {
                         .dest(NEUTRON_CHAIN_NAME)
                         .get(),
                     hop_chain_receiver_address: None,
+                    retries: None,
+                    timeout: None,
                 },
             )]),
             eureka_config: None,
+            min_transfer_amount: None,
+            recredit_failed_transfers: false,
+            expected_base_denom: None,
+            max_memo_bytes: None,
+            post_transfer_callback: None,
         },
     };
 
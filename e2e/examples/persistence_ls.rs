@@ -129,10 +129,10 @@ fn main() -> Result<(), Box<dyn Error>> {
         base_account_code_id,
         PERSISTENCE_CHAIN_ADMIN_ADDR.to_string(),
         vec![processor_on_persistence.clone()],
-        1,
+        &["liquid_staking"],
         None,
     );
-    let persistence_base_account = base_accounts.first().unwrap();
+    let persistence_base_account = &base_accounts["liquid_staking"].to_string();
 
     // Now that everything is set up, let's create the authorization that will be used to liquid stake from the base account
     let authorizations = vec![AuthorizationBuilder::new()
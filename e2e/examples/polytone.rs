@@ -39,6 +39,7 @@ use valence_e2e::utils::{
         predict_remote_contract_address, set_up_authorization_and_processor,
         verify_authorization_execution_result,
     },
+    invalidate_stale_code_ids,
     polytone::salt_for_proxy,
     processor::{get_processor_queue_items, tick_processor},
     relayer::restart_relayer,
@@ -96,6 +97,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         .send_single_contract(&processor_contract_path)?;
 
     // Upload all Polytone contracts to both Neutron and Osmosis
+    invalidate_stale_code_ids(POLYTONE_ARTIFACTS_PATH, LOCAL_CODE_ID_CACHE_PATH_NEUTRON)?;
+    invalidate_stale_code_ids(POLYTONE_ARTIFACTS_PATH, LOCAL_CODE_ID_CACHE_PATH_OSMOSIS)?;
     let mut uploader = test_ctx.build_tx_upload_contracts();
     uploader
         .send_with_local_cache(POLYTONE_ARTIFACTS_PATH, LOCAL_CODE_ID_CACHE_PATH_NEUTRON)
@@ -601,6 +604,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         },
         // We don't care about the execution result so we will just make it fail when ticking the processor
         contract_address: LibraryAccountType::Addr("any".to_string()),
+        gas_limit: None,
+        scope: None,
     };
     let mut authorization = AuthorizationInfo {
         label: "label".to_string(),
@@ -24,6 +24,7 @@ use valence_authorization_utils::{
     msg::ProcessorMessage,
 };
 use valence_e2e::utils::{
+    invalidate_stale_code_ids,
     manager::{
         setup_manager, use_manager_init, ASTROPORT_LPER_NAME, ASTROPORT_WITHDRAWER_NAME,
         FORWARDER_NAME, REVERSE_SPLITTER_NAME, SPLITTER_NAME,
@@ -58,6 +59,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let current_dir = env::current_dir()?;
     let astroport_contracts_path = format!("{}/{}", current_dir.display(), ASTROPORT_PATH);
 
+    invalidate_stale_code_ids(&astroport_contracts_path, LOCAL_CODE_ID_CACHE_PATH_NEUTRON)?;
     let mut uploader = test_ctx.build_tx_upload_contracts();
     uploader
         .with_chain_name(NEUTRON_CHAIN_NAME)
@@ -314,6 +316,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             config: LibraryConfig::ValenceForwarderLibrary(
                 valence_forwarder_library::msg::LibraryConfig {
                     input_addr: LibraryAccountType::AccountId(3),
+                    additional_input_addrs: None,
                     output_addr: LibraryAccountType::AccountId(4),
                     forwarding_configs: vec![
                         UncheckedForwardingConfig {
@@ -326,6 +329,13 @@ fn main() -> Result<(), Box<dyn Error>> {
                         },
                     ],
                     forwarding_constraints: ForwardingConstraints::new(None),
+                    split_outputs: None,
+                    rounding: Default::default(),
+                    lp_redemption: None,
+                    gas_reserve: None,
+                    snapshot_balances: false,
+                    precondition: None,
+                    fee_skim: None,
                 },
             ),
             addr: None,
@@ -349,9 +359,18 @@ fn main() -> Result<(), Box<dyn Error>> {
                         asset_data: AssetData {
                             asset1: NEUTRON_CHAIN_DENOM.to_string(),
                             asset2: token.clone(),
+                            additional_assets: vec![],
                         },
                         max_spread: None,
+                        slippage_tolerance: None,
+                        min_lp_tokens_out: None,
+                        provision_mode: valence_astroport_lper::msg::ProvisionMode::Balanced,
                     },
+                    refund_account: None,
+                    deposit_cap: None,
+                    pool_allocations: None,
+                    price_source: None,
+                    deposit_addr: None,
                 },
             ),
             addr: None,
@@ -366,12 +385,20 @@ fn main() -> Result<(), Box<dyn Error>> {
             config: LibraryConfig::ValenceForwarderLibrary(
                 valence_forwarder_library::msg::LibraryConfig {
                     input_addr: LibraryAccountType::AccountId(5),
+                    additional_input_addrs: None,
                     output_addr: LibraryAccountType::AccountId(6),
                     forwarding_configs: vec![UncheckedForwardingConfig {
                         denom: UncheckedDenom::Native(lp_token.to_string()),
                         max_amount: Uint128::new(u128::MAX),
                     }],
                     forwarding_constraints: ForwardingConstraints::new(None),
+                    split_outputs: None,
+                    rounding: Default::default(),
+                    lp_redemption: None,
+                    gas_reserve: None,
+                    snapshot_balances: false,
+                    precondition: None,
+                    fee_skim: None,
                 },
             ),
             addr: None,
@@ -386,12 +413,20 @@ fn main() -> Result<(), Box<dyn Error>> {
             config: LibraryConfig::ValenceForwarderLibrary(
                 valence_forwarder_library::msg::LibraryConfig {
                     input_addr: LibraryAccountType::AccountId(6),
+                    additional_input_addrs: None,
                     output_addr: LibraryAccountType::AccountId(7),
                     forwarding_configs: vec![UncheckedForwardingConfig {
                         denom: UncheckedDenom::Native(lp_token.to_string()),
                         max_amount: Uint128::new(u128::MAX),
                     }],
                     forwarding_constraints: ForwardingConstraints::new(None),
+                    split_outputs: None,
+                    rounding: Default::default(),
+                    lp_redemption: None,
+                    gas_reserve: None,
+                    snapshot_balances: false,
+                    precondition: None,
+                    fee_skim: None,
                 },
             ),
             addr: None,
@@ -416,7 +451,10 @@ fn main() -> Result<(), Box<dyn Error>> {
                             asset_data: AssetData {
                                 asset1: NEUTRON_CHAIN_DENOM.to_string(),
                                 asset2: token.clone(),
+                                additional_assets: vec![],
                             },
+                            withdraw_amount: valence_astroport_withdrawer::msg::WithdrawAmount::All,
+                            rounding: Default::default(),
                         },
                 },
             ),
@@ -997,6 +1035,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             &valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
                 valence_astroport_withdrawer::msg::FunctionMsgs::WithdrawLiquidity {
                     expected_pool_ratio_range: None,
+                    value_provided: None,
                 },
             ),
         )
@@ -15,17 +15,20 @@ use valence_astroport_utils::astroport_native_lp_token::{
     FactoryInstantiateMsg, FactoryQueryMsg, NativeCoinRegistryExecuteMsg,
     NativeCoinRegistryInstantiateMsg, PairConfig, PairType,
 };
+use valence_astroport_utils::{PoolKind, CONCENTRATED_PAIR_TYPE};
 use valence_library_utils::{liquidity_utils::AssetData, LibraryAccountType};
 
 use crate::utils::{
     base_account::approve_library,
+    error::{get_code_id, ValenceSetupError},
+    invalidate_stale_code_ids,
     manager::{ASTROPORT_LPER_NAME, ASTROPORT_WITHDRAWER_NAME},
+    setup_context::LibraryDeployment,
     ASTROPORT_PATH, GAS_FLAGS, LOCAL_CODE_ID_CACHE_PATH_NEUTRON,
 };
 
 const _PROVIDE_LIQUIDITY_AUTHORIZATIONS_LABEL: &str = "provide_liquidity";
 const _WITHDRAW_LIQUIDITY_AUTHORIZATIONS_LABEL: &str = "withdraw_liquidity";
-const ASTROPORT_CONCENTRATED_PAIR_TYPE: &str = "concentrated";
 
 pub fn deploy_astroport_contracts(
     test_ctx: &mut TestContext,
@@ -34,6 +37,7 @@ pub fn deploy_astroport_contracts(
     let current_dir = env::current_dir()?;
     let astroport_contracts_path = format!("{}/{}", current_dir.display(), ASTROPORT_PATH);
 
+    invalidate_stale_code_ids(&astroport_contracts_path, LOCAL_CODE_ID_CACHE_PATH_NEUTRON)?;
     let mut uploader = test_ctx.build_tx_upload_contracts();
     uploader
         .with_chain_name(NEUTRON_CHAIN_NAME)
@@ -134,7 +138,7 @@ pub fn setup_astroport_cl_pool(
     let astroport_factory_instantiate_msg = FactoryInstantiateMsg {
         pair_configs: vec![PairConfig {
             code_id: astroport_pair_concentrated_code_id,
-            pair_type: PairType::Custom(ASTROPORT_CONCENTRATED_PAIR_TYPE.to_string()),
+            pair_type: PairType::Custom(CONCENTRATED_PAIR_TYPE.to_string()),
             total_fee_bps: 0u16,
             maker_fee_bps: 0,
             is_disabled: false,
@@ -200,7 +204,7 @@ pub fn setup_astroport_cl_pool(
         DEFAULT_KEY,
         &serde_json::to_string(
             &valence_astroport_utils::astroport_native_lp_token::FactoryExecuteMsg::CreatePair {
-                pair_type: PairType::Custom(ASTROPORT_CONCENTRATED_PAIR_TYPE.to_string()),
+                pair_type: PairType::Custom(CONCENTRATED_PAIR_TYPE.to_string()),
                 asset_infos: pool_assets.clone(),
                 init_params: Some(to_json_binary(&default_params).unwrap()),
             },
@@ -310,30 +314,23 @@ pub fn astroport_cl_swap(
     Ok(())
 }
 
-pub fn setup_astroport_lper_lib(
-    test_ctx: &mut TestContext,
-    input_account: String,
-    output_account: String,
+/// Instantiate message of the astroport lper library for a pool of `pool_kind`. If `refund_account`
+/// is set, the assets left in the input account after a balanced provision are swept to it.
+pub fn astroport_lper_instantiate_msg(
+    input_account: &str,
+    output_account: &str,
     asset_data: AssetData,
     pool_addr: String,
-    _processor: String,
-    _authorizations: String,
-) -> Result<String, Box<dyn Error>> {
-    let lper_code_id = test_ctx
-        .get_contract()
-        .contract(ASTROPORT_LPER_NAME)
-        .get_cw()
-        .code_id
-        .unwrap();
-
-    let astro_cl_pair_type = valence_astroport_utils::astroport_native_lp_token::PairType::Custom(
-        ASTROPORT_CONCENTRATED_PAIR_TYPE.to_string(),
-    );
-
+    pool_kind: PoolKind,
+    refund_account: Option<String>,
+) -> valence_library_utils::msg::InstantiateMsg<valence_astroport_lper::msg::LibraryConfig> {
     let astro_lp_config = LiquidityProviderConfig {
-        pool_type: valence_astroport_utils::PoolType::NativeLpToken(astro_cl_pair_type.clone()),
+        pool_type: pool_kind.native_lp_token_pool_type(),
         asset_data,
         max_spread: None,
+        slippage_tolerance: None,
+        min_lp_tokens_out: None,
+        provision_mode: valence_astroport_lper::msg::ProvisionMode::Balanced,
     };
 
     let astro_lper_library_cfg = valence_astroport_lper::msg::LibraryConfig {
@@ -341,18 +338,50 @@ pub fn setup_astroport_lper_lib(
         output_addr: LibraryAccountType::Addr(output_account.to_string()),
         lp_config: astro_lp_config,
         pool_addr,
+        refund_account: refund_account.map(LibraryAccountType::Addr),
+        deposit_cap: None,
+        pool_allocations: None,
+        price_source: None,
+        deposit_addr: None,
     };
 
-    let astroport_lper_instantiate_msg =
-        valence_library_utils::msg::InstantiateMsg::<valence_astroport_lper::msg::LibraryConfig> {
-            // TODO: uncomment to not bypass authorizations/processor logic
-            // owner: authorizations.to_string(),
-            // processor: processor.to_string(),
-            owner: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
-            processor: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
-            config: astro_lper_library_cfg,
-        };
+    valence_library_utils::msg::InstantiateMsg::<valence_astroport_lper::msg::LibraryConfig> {
+        // TODO: uncomment to not bypass authorizations/processor logic
+        // owner: authorizations.to_string(),
+        // processor: processor.to_string(),
+        owner: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
+        processor: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
+        config: astro_lper_library_cfg,
+    }
+}
+
+/// Instantiates the astroport lper library, see `astroport_lper_instantiate_msg`,
+/// with the label and admin of `deployment`
+#[allow(clippy::too_many_arguments)]
+pub fn setup_astroport_lper_lib(
+    test_ctx: &mut TestContext,
+    input_account: String,
+    output_account: String,
+    asset_data: AssetData,
+    pool_addr: String,
+    pool_kind: PoolKind,
+    refund_account: Option<String>,
+    _processor: String,
+    _authorizations: String,
+    deployment: &LibraryDeployment,
+) -> Result<String, ValenceSetupError> {
+    let lper_code_id = get_code_id(test_ctx, NEUTRON_CHAIN_NAME, ASTROPORT_LPER_NAME)?;
+
+    let astroport_lper_instantiate_msg = astroport_lper_instantiate_msg(
+        &input_account,
+        &output_account,
+        asset_data,
+        pool_addr,
+        pool_kind,
+        refund_account,
+    );
 
+    let label = deployment.label("astro_lper");
     let astro_lper_lib = contract_instantiate(
         test_ctx
             .get_request_builder()
@@ -360,10 +389,14 @@ pub fn setup_astroport_lper_lib(
         DEFAULT_KEY,
         lper_code_id,
         &serde_json::to_string(&astroport_lper_instantiate_msg)?,
-        "astro_lper",
-        None,
+        &label,
+        deployment.admin.clone(),
         "",
-    )?;
+    )
+    .map_err(|e| ValenceSetupError::Instantiation {
+        label,
+        reason: e.to_string(),
+    })?;
     info!("astro lper lib: {}", astro_lper_lib.address);
 
     info!("approving astro lper library on deposit account...");
@@ -379,28 +412,20 @@ pub fn setup_astroport_lper_lib(
     Ok(astro_lper_lib.address)
 }
 
-pub fn setup_astroport_lwer_lib(
-    test_ctx: &mut TestContext,
-    input_account: String,
-    output_account: String,
+/// Instantiate message of the astroport lwer library for a pool of `pool_kind`,
+/// withdrawing the whole position
+pub fn astroport_lwer_instantiate_msg(
+    input_account: &str,
+    output_account: &str,
     asset_data: AssetData,
     pool_addr: String,
-    _processor: String,
-) -> Result<String, Box<dyn Error>> {
-    let lwer_code_id = test_ctx
-        .get_contract()
-        .contract(ASTROPORT_WITHDRAWER_NAME)
-        .get_cw()
-        .code_id
-        .unwrap();
-
-    let astro_cl_pair_type = valence_astroport_utils::astroport_native_lp_token::PairType::Custom(
-        ASTROPORT_CONCENTRATED_PAIR_TYPE.to_string(),
-    );
-
+    pool_kind: PoolKind,
+) -> valence_library_utils::msg::InstantiateMsg<valence_astroport_withdrawer::msg::LibraryConfig> {
     let astro_lw_config = valence_astroport_withdrawer::msg::LiquidityWithdrawerConfig {
-        pool_type: valence_astroport_utils::PoolType::NativeLpToken(astro_cl_pair_type),
+        pool_type: pool_kind.native_lp_token_pool_type(),
         asset_data,
+        withdraw_amount: valence_astroport_withdrawer::msg::WithdrawAmount::All,
+        rounding: Default::default(),
     };
     let astro_lwer_library_cfg = valence_astroport_withdrawer::msg::LibraryConfig {
         input_addr: LibraryAccountType::Addr(input_account.to_string()),
@@ -408,14 +433,37 @@ pub fn setup_astroport_lwer_lib(
         withdrawer_config: astro_lw_config,
         pool_addr: pool_addr.to_string(),
     };
-    let astroport_lwer_instantiate_msg = valence_library_utils::msg::InstantiateMsg::<
-        valence_astroport_withdrawer::msg::LibraryConfig,
-    > {
+    valence_library_utils::msg::InstantiateMsg::<valence_astroport_withdrawer::msg::LibraryConfig> {
         owner: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         processor: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         config: astro_lwer_library_cfg,
-    };
+    }
+}
 
+/// Instantiates the astroport lwer library, see `astroport_lwer_instantiate_msg`,
+/// with the label and admin of `deployment`
+#[allow(clippy::too_many_arguments)]
+pub fn setup_astroport_lwer_lib(
+    test_ctx: &mut TestContext,
+    input_account: String,
+    output_account: String,
+    asset_data: AssetData,
+    pool_addr: String,
+    pool_kind: PoolKind,
+    _processor: String,
+    deployment: &LibraryDeployment,
+) -> Result<String, ValenceSetupError> {
+    let lwer_code_id = get_code_id(test_ctx, NEUTRON_CHAIN_NAME, ASTROPORT_WITHDRAWER_NAME)?;
+
+    let astroport_lwer_instantiate_msg = astroport_lwer_instantiate_msg(
+        &input_account,
+        &output_account,
+        asset_data,
+        pool_addr,
+        pool_kind,
+    );
+
+    let label = deployment.label("astro_lwer");
     let astro_lwer_lib = contract_instantiate(
         test_ctx
             .get_request_builder()
@@ -423,10 +471,14 @@ pub fn setup_astroport_lwer_lib(
         DEFAULT_KEY,
         lwer_code_id,
         &serde_json::to_string(&astroport_lwer_instantiate_msg)?,
-        "astro_lwer",
-        None,
+        &label,
+        deployment.admin.clone(),
         "",
-    )?;
+    )
+    .map_err(|e| ValenceSetupError::Instantiation {
+        label,
+        reason: e.to_string(),
+    })?;
     info!("astro lwer lib: {}", astro_lwer_lib.address);
 
     info!("approving astro lwer library on position account...");
@@ -18,7 +18,9 @@ use valence_authorization_utils::{
 };
 use valence_processor_utils::msg::PolytoneContracts;
 
-use crate::utils::{polytone::salt_for_proxy, LOCAL_CODE_ID_CACHE_PATH_NEUTRON};
+use crate::utils::{
+    invalidate_stale_code_ids, polytone::salt_for_proxy, LOCAL_CODE_ID_CACHE_PATH_NEUTRON,
+};
 
 use super::{relayer::restart_relayer, POLYTONE_ARTIFACTS_PATH};
 const MAX_ATTEMPTS: u64 = 50;
@@ -132,12 +134,14 @@ pub fn set_up_external_domain_with_polytone(
     authorization_contract: &str,
 ) -> Result<String, Box<dyn Error>> {
     info!("Uploading polytone contracts to neutron");
+    invalidate_stale_code_ids(POLYTONE_ARTIFACTS_PATH, LOCAL_CODE_ID_CACHE_PATH_NEUTRON)?;
     let mut uploader = test_ctx.build_tx_upload_contracts();
     uploader
         .send_with_local_cache(POLYTONE_ARTIFACTS_PATH, LOCAL_CODE_ID_CACHE_PATH_NEUTRON)
         .unwrap();
 
     info!("Uploading polytone contracts to {}", chain_name);
+    invalidate_stale_code_ids(POLYTONE_ARTIFACTS_PATH, local_cache)?;
     let mut uploader = test_ctx.build_tx_upload_contracts();
     uploader
         .with_chain_name(chain_name)
@@ -1,12 +1,56 @@
-use cosmwasm_std::Coin;
-use localic_std::modules::cosmwasm::{contract_execute, contract_instantiate};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::{Duration, Instant},
+};
+
+use cosmwasm_std::{Addr, Coin, Uint128};
+use localic_std::modules::cosmwasm::{contract_execute, contract_instantiate, contract_query};
 use localic_utils::utils::test_context::TestContext;
 use log::info;
+use serde::Serialize;
+
+use crate::utils::{error::ValenceSetupError, setup_context::SetupContext, GAS_FLAGS};
 
-use crate::utils::GAS_FLAGS;
+const LIBRARY_APPROVAL_TIMEOUT: Duration = Duration::from_secs(60);
+const LIBRARY_APPROVAL_POLLING_PERIOD: Duration = Duration::from_secs(1);
+
+/// Approval of a library on an account, with the tx that approved it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApprovalReceipt {
+    pub account: String,
+    pub library: String,
+    pub tx_hash: String,
+    // height of the block that included the approval
+    pub height: u64,
+}
+
+impl ApprovalReceipt {
+    /// Receipt of the approval from the `q tx` output of its tx, or None if the tx is not included yet
+    pub fn from_tx(
+        account: &str,
+        library: &str,
+        tx_hash: &str,
+        tx: &serde_json::Value,
+    ) -> Option<Self> {
+        // the height is a string in the JSON output of the SDK
+        let height = match &tx["height"] {
+            serde_json::Value::String(height) => height.parse().ok(),
+            height => height.as_u64(),
+        }
+        .filter(|height| *height > 0)?;
+
+        Some(ApprovalReceipt {
+            account: account.to_string(),
+            library: library.to_string(),
+            tx_hash: tx_hash.to_string(),
+            height,
+        })
+    }
+}
 
 #[allow(clippy::too_many_arguments)]
-/// Creates valence base accounts on a specific chain for our libraries and returns their contract address
+/// Creates one valence base account per role on a specific chain for our libraries and returns
+/// their contract addresses keyed by role, so that callers wire the accounts by name
 pub fn create_base_accounts(
     test_ctx: &mut TestContext,
     key: &str,
@@ -14,12 +58,14 @@ pub fn create_base_accounts(
     code_id: u64,
     admin: String,
     approved_libraries: Vec<String>,
-    num_accounts: u64,
+    roles: &[&str],
     fees: Option<Coin>,
-) -> Vec<String> {
+) -> BTreeMap<String, Addr> {
     info!(
-        "Creating {} base accounts on {}...",
-        num_accounts, chain_name
+        "Creating {} base accounts ({}) on {}...",
+        roles.len(),
+        roles.join(", "),
+        chain_name
     );
     let instantiate_msg = valence_account_utils::msg::InstantiateMsg {
         admin,
@@ -30,8 +76,7 @@ pub fn create_base_accounts(
     } else {
         "".to_string()
     };
-    let mut accounts = Vec::new();
-    for _ in 0..num_accounts {
+    accounts_by_role(roles, |_| {
         let contract = contract_instantiate(
             test_ctx
                 .get_request_builder()
@@ -45,10 +90,28 @@ pub fn create_base_accounts(
         )
         .unwrap();
 
-        accounts.push(contract.address);
         std::thread::sleep(std::time::Duration::from_secs(2));
-    }
-    accounts
+        Addr::unchecked(contract.address)
+    })
+}
+
+/// Creates exactly one account per role with `create_account`, in the order of the roles,
+/// and returns them keyed by role. Panics if a role is requested twice
+fn accounts_by_role(
+    roles: &[&str],
+    mut create_account: impl FnMut(&str) -> Addr,
+) -> BTreeMap<String, Addr> {
+    let unique_roles: BTreeSet<&str> = roles.iter().copied().collect();
+    assert_eq!(
+        unique_roles.len(),
+        roles.len(),
+        "Base account roles must be distinct: {roles:?}"
+    );
+
+    roles
+        .iter()
+        .map(|role| (role.to_string(), create_account(role)))
+        .collect()
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -145,7 +208,7 @@ pub fn create_interchain_accounts(
     accounts
 }
 
-/// Approve a library for a base account
+/// Approve a library for a base account, returning the receipt of the approval once its tx is included
 pub fn approve_library(
     test_ctx: &mut TestContext,
     chain_name: &str,
@@ -153,10 +216,71 @@ pub fn approve_library(
     base_account: &str,
     library: String,
     flags: Option<String>,
-) {
+) -> ApprovalReceipt {
     let approve_msg = valence_account_utils::msg::ExecuteMsg::ApproveLibrary {
         library: library.clone(),
     };
+    let tx_hash = contract_execute(
+        test_ctx
+            .get_request_builder()
+            .get_request_builder(chain_name),
+        base_account,
+        key,
+        &serde_json::to_string(&approve_msg).unwrap(),
+        &format!(
+            "{}{}",
+            GAS_FLAGS,
+            flags
+                .map(|mut s| {
+                    if !s.starts_with(' ') {
+                        s.insert(0, ' ');
+                    }
+                    s
+                })
+                .unwrap_or_default()
+        ),
+    )
+    .unwrap()
+    .tx_hash
+    .expect("approval tx returned no hash");
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let start = Instant::now();
+    let receipt = loop {
+        let tx = test_ctx
+            .get_request_builder()
+            .get_request_builder(chain_name)
+            .query(&format!("q tx {tx_hash}"), false);
+        if let Some(receipt) = ApprovalReceipt::from_tx(base_account, &library, &tx_hash, &tx) {
+            break receipt;
+        }
+
+        assert!(
+            start.elapsed() < LIBRARY_APPROVAL_TIMEOUT,
+            "Approval tx {tx_hash} of library {library} not included after {LIBRARY_APPROVAL_TIMEOUT:?}"
+        );
+        std::thread::sleep(LIBRARY_APPROVAL_POLLING_PERIOD);
+    };
+
+    info!(
+        "Approved library {} for base account {} (tx {} at height {})",
+        library, base_account, receipt.tx_hash, receipt.height
+    );
+    receipt
+}
+
+/// Approve multiple libraries for a base account in a single transaction
+pub fn approve_libraries(
+    test_ctx: &mut TestContext,
+    chain_name: &str,
+    key: &str,
+    base_account: &str,
+    libraries: Vec<String>,
+    flags: Option<String>,
+) {
+    let approve_msg = valence_account_utils::msg::ExecuteMsg::ApproveLibraries {
+        libraries: libraries.clone(),
+    };
     contract_execute(
         test_ctx
             .get_request_builder()
@@ -180,8 +304,277 @@ pub fn approve_library(
     .unwrap();
 
     info!(
-        "Approved library {} for base account {}",
-        library, base_account
+        "Approved libraries {:?} for base account {}",
+        libraries, base_account
     );
     std::thread::sleep(std::time::Duration::from_secs(2));
 }
+
+/// Polls the approved libraries of an account until `library` is listed,
+/// erroring if the approval is still not visible once the timeout is elapsed
+pub fn wait_for_library_approval(
+    test_ctx: &mut TestContext,
+    chain_name: &str,
+    account: &str,
+    library: &str,
+) -> Result<(), ValenceSetupError> {
+    let query =
+        serde_json::to_string(&valence_account_utils::msg::QueryMsg::ListApprovedLibraries {})?;
+
+    poll_library_approval(
+        account,
+        library,
+        || {
+            let response = contract_query(
+                test_ctx
+                    .get_request_builder()
+                    .get_request_builder(chain_name),
+                account,
+                &query,
+            );
+            // The query fails while the account is not indexed yet, which counts as not approved
+            serde_json::from_value(response["data"].clone()).unwrap_or_default()
+        },
+        LIBRARY_APPROVAL_TIMEOUT,
+        LIBRARY_APPROVAL_POLLING_PERIOD,
+    )
+}
+
+/// Polls the libraries approved on `account` until `library` is one of them,
+/// erroring once `timeout` is elapsed
+fn poll_library_approval(
+    account: &str,
+    library: &str,
+    mut query_approved_libraries: impl FnMut() -> Vec<String>,
+    timeout: Duration,
+    polling_interval: Duration,
+) -> Result<(), ValenceSetupError> {
+    let start = Instant::now();
+
+    loop {
+        if query_approved_libraries()
+            .iter()
+            .any(|approved| approved == library)
+        {
+            info!("Library {library} approved on account {account}");
+            return Ok(());
+        }
+
+        if start.elapsed() >= timeout {
+            return Err(ValenceSetupError::ApprovalTimeout {
+                account: account.to_string(),
+                library: library.to_string(),
+                timeout,
+            });
+        }
+
+        std::thread::sleep(polling_interval);
+    }
+}
+
+/// Balances of each of the `denoms` held by each of the `accounts` on `chain_name`, keyed by account
+/// and then by denom. All the balances of an account are read with a single query, instead of one
+/// query per denom. Accounts that don't exist, or don't hold a denom, get a zero balance of it
+pub fn batch_balances(
+    test_ctx: &mut impl SetupContext,
+    chain_name: &str,
+    accounts: &[&str],
+    denoms: &[&str],
+) -> Result<BTreeMap<String, BTreeMap<String, Uint128>>, ValenceSetupError> {
+    let mut balances = BTreeMap::new();
+    for account in accounts {
+        if balances.contains_key(*account) {
+            continue;
+        }
+
+        let held = test_ctx.balances(chain_name, account)?;
+        let account_balances = denoms
+            .iter()
+            .map(|denom| {
+                let amount = held
+                    .iter()
+                    .find(|coin| coin.denom == *denom)
+                    .map(|coin| coin.amount)
+                    .unwrap_or_default();
+                (denom.to_string(), amount)
+            })
+            .collect();
+        balances.insert(account.to_string(), account_balances);
+    }
+
+    Ok(balances)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::coin;
+    use serde_json::json;
+
+    use crate::utils::mocks::test_context::MockTestContext;
+
+    use super::*;
+
+    #[test]
+    fn approval_receipt_is_populated_from_included_tx() {
+        let tx = json!({"height": "1234", "txhash": "ABCD", "code": 0});
+
+        let receipt =
+            ApprovalReceipt::from_tx("neutron1account", "neutron1library", "ABCD", &tx).unwrap();
+
+        assert_eq!(
+            receipt,
+            ApprovalReceipt {
+                account: "neutron1account".to_string(),
+                library: "neutron1library".to_string(),
+                tx_hash: "ABCD".to_string(),
+                height: 1234,
+            }
+        );
+    }
+
+    #[test]
+    fn every_role_gets_its_own_account() {
+        let roles = ["deposit", "position", "withdraw", "liquidation", "fee"];
+        let mut created = vec![];
+
+        let accounts = accounts_by_role(&roles, |_| {
+            let account = Addr::unchecked(format!("neutron1account{}", created.len()));
+            created.push(account.clone());
+            account
+        });
+
+        assert_eq!(created.len(), roles.len());
+        assert_eq!(accounts.len(), roles.len());
+        // accounts are created in the order of the roles, whatever the order of the map
+        for (role, account) in roles.iter().zip(&created) {
+            assert_eq!(&accounts[*role], account);
+        }
+        let distinct: BTreeSet<&Addr> = accounts.values().collect();
+        assert_eq!(distinct.len(), roles.len());
+    }
+
+    #[test]
+    fn returned_roles_match_the_requested_ones_whatever_the_creation_order() {
+        let roles = ["withdraw", "deposit", "position"];
+        let mut reversed = roles;
+        reversed.reverse();
+
+        let create = |role: &str| Addr::unchecked(format!("neutron1{role}"));
+        let accounts = accounts_by_role(&roles, create);
+        let accounts_created_reversed = accounts_by_role(&reversed, create);
+
+        let requested: BTreeSet<String> = roles.iter().map(|role| role.to_string()).collect();
+        assert_eq!(accounts.keys().cloned().collect::<BTreeSet<_>>(), requested);
+        // each role is wired to the account created for it, not to the one at its position
+        assert_eq!(accounts, accounts_created_reversed);
+        assert_eq!(accounts["deposit"], Addr::unchecked("neutron1deposit"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Base account roles must be distinct")]
+    fn duplicated_roles_are_rejected() {
+        accounts_by_role(&["deposit", "withdraw", "deposit"], |role| {
+            Addr::unchecked(role)
+        });
+    }
+
+    #[test]
+    fn approval_is_polled_until_visible() {
+        let mut polls = 0;
+
+        poll_library_approval(
+            "neutron1account",
+            "neutron1library",
+            || {
+                polls += 1;
+                // the first query doesn't see the approval yet
+                if polls == 1 {
+                    vec!["neutron1other".to_string()]
+                } else {
+                    vec!["neutron1other".to_string(), "neutron1library".to_string()]
+                }
+            },
+            Duration::from_secs(5),
+            Duration::from_millis(10),
+        )
+        .unwrap();
+
+        assert_eq!(polls, 2);
+    }
+
+    #[test]
+    fn approval_that_never_shows_times_out() {
+        let timeout = Duration::from_millis(50);
+        let mut polls = 0;
+
+        let err = poll_library_approval(
+            "neutron1account",
+            "neutron1library",
+            || {
+                polls += 1;
+                vec![]
+            },
+            timeout,
+            Duration::from_millis(10),
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            &err,
+            ValenceSetupError::ApprovalTimeout { account, library, timeout: t }
+                if account == "neutron1account" && library == "neutron1library" && *t == timeout
+        ));
+        assert!(err.is_transient());
+        assert!(polls > 1);
+    }
+
+    #[test]
+    fn approval_receipt_needs_the_tx_to_be_included() {
+        // the query errors while the tx is not indexed
+        let tx = json!({"error": "tx not found"});
+
+        assert_eq!(
+            ApprovalReceipt::from_tx("neutron1account", "neutron1library", "ABCD", &tx),
+            None
+        );
+    }
+
+    #[test]
+    fn batch_balances_match_the_balances_of_each_account() {
+        let mut test_ctx = MockTestContext::new()
+            .with_balances(
+                "neutron",
+                "neutron1deposit",
+                vec![coin(1_000, "uusdc"), coin(50, "untrn")],
+            )
+            .with_balances("neutron", "neutron1position", vec![coin(7, "lp")])
+            .with_balances("neutron", "neutron1withdraw", vec![coin(300, "uusdc")]);
+        let accounts = [
+            "neutron1deposit",
+            "neutron1position",
+            "neutron1withdraw",
+            "neutron1liquidation",
+        ];
+        let denoms = ["uusdc", "untrn", "lp"];
+
+        let balances = batch_balances(&mut test_ctx, "neutron", &accounts, &denoms).unwrap();
+
+        assert_eq!(balances.len(), accounts.len());
+        for account in accounts {
+            let held = test_ctx.balances("neutron", account).unwrap();
+            for denom in denoms {
+                let expected = held
+                    .iter()
+                    .find(|c| c.denom == denom)
+                    .map(|c| c.amount)
+                    .unwrap_or_default();
+                assert_eq!(balances[account][denom], expected, "{denom} of {account}");
+            }
+        }
+        assert_eq!(balances["neutron1deposit"]["uusdc"], Uint128::new(1_000));
+        // the liquidation account was never funded (nor created), so it holds nothing
+        assert!(balances["neutron1liquidation"]
+            .values()
+            .all(|amount| amount.is_zero()));
+    }
+}
@@ -0,0 +1,352 @@
+use std::{collections::BTreeMap, str::FromStr, time::SystemTime};
+
+use cosmos_grpc_client::{
+    cosmos_sdk_proto::cosmwasm::wasm::v1::{MsgExecuteContract, MsgInstantiateContract2},
+    BroadcastMode, CoinType, GrpcClient, ProstMsgToAny, Wallet,
+};
+use cosmwasm_std_old::Decimal;
+use localic_utils::{
+    utils::test_context::TestContext, NEUTRON_CHAIN_ADMIN_ADDR, NEUTRON_CHAIN_DENOM,
+    NEUTRON_CHAIN_ID, NEUTRON_CHAIN_NAME,
+};
+use log::info;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tokio::runtime::Runtime;
+
+use crate::utils::{
+    authorization::predict_remote_contract_address,
+    base_account::wait_for_library_approval,
+    error::ValenceSetupError,
+    parse::{get_chain_field_from_local_ic_log, get_grpc_address_and_port_from_url},
+    ADMIN_MNEMONIC,
+};
+
+const NEUTRON_CHAIN_PREFIX: &str = "neutron";
+const TX_POLLING_ATTEMPTS: u32 = 20;
+const TX_POLLING_PERIOD: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How the libraries of a program are set up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LibrarySetupMode {
+    /// One instantiate tx and one approval tx per library
+    #[default]
+    Serial,
+    /// All libraries instantiated in a single tx, then approved in a single tx
+    Batched,
+}
+
+/// Library to instantiate as part of a batch, approved on its input account once instantiated
+#[derive(Debug, Clone)]
+pub struct LibraryInstantiation {
+    pub label: String,
+    pub code_id: u64,
+    pub msg: serde_json::Value,
+    pub input_account: String,
+    // address allowed to migrate the library, if any
+    pub admin: Option<String>,
+}
+
+impl LibraryInstantiation {
+    pub fn new(
+        label: &str,
+        code_id: u64,
+        msg: &impl Serialize,
+        input_account: &str,
+    ) -> Result<Self, ValenceSetupError> {
+        Ok(LibraryInstantiation {
+            label: label.to_string(),
+            code_id,
+            msg: serde_json::to_value(msg)?,
+            input_account: input_account.to_string(),
+            admin: None,
+        })
+    }
+
+    pub fn with_admin(mut self, admin: Option<String>) -> Self {
+        self.admin = admin;
+        self
+    }
+}
+
+/// Instantiates all the libraries on Neutron in a single multi-message tx, followed by a single
+/// tx approving every library on its input account. The addresses are predicted with instantiate2,
+/// and returned in the order of `libraries`.
+/// If an instantiation fails the whole tx is reverted, and the error names the failing library.
+pub fn batch_instantiate_and_approve(
+    test_ctx: &mut TestContext,
+    libraries: &[LibraryInstantiation],
+) -> Result<Vec<String>, ValenceSetupError> {
+    // salts are unique per run, so the same program can be deployed again on a running chain
+    let nonce = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|e| ValenceSetupError::Other(e.to_string()))?
+        .as_nanos();
+
+    let mut addresses = Vec::with_capacity(libraries.len());
+    let mut instantiate_msgs = Vec::with_capacity(libraries.len());
+    for library in libraries {
+        let salt = Sha256::digest(format!("{}-{nonce}", library.label)).to_vec();
+        let address = predict_remote_contract_address(
+            test_ctx,
+            library.code_id,
+            NEUTRON_CHAIN_NAME,
+            NEUTRON_CHAIN_PREFIX,
+            NEUTRON_CHAIN_ADMIN_ADDR,
+            &salt,
+        )?;
+        info!("{} predicted address: {address}", library.label);
+
+        instantiate_msgs.push(
+            MsgInstantiateContract2 {
+                sender: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
+                admin: library.admin.clone().unwrap_or_default(),
+                code_id: library.code_id,
+                label: library.label.clone(),
+                msg: serde_json::to_vec(&library.msg)?,
+                funds: vec![],
+                salt,
+                fix_msg: false,
+            }
+            .build_any_with_type_url("/cosmwasm.wasm.v1.MsgInstantiateContract2"),
+        );
+        addresses.push(address);
+    }
+
+    let labels: Vec<&str> = libraries.iter().map(|l| l.label.as_str()).collect();
+    info!("instantiating libraries {labels:?} in a single tx...");
+    broadcast_batch(test_ctx, instantiate_msgs, &labels).map_err(|failure| match failure {
+        BatchFailure::Message { target, reason } => ValenceSetupError::Instantiation {
+            label: target,
+            reason,
+        },
+        BatchFailure::Tx(reason) => ValenceSetupError::Other(reason),
+    })?;
+
+    let libraries_by_account = approvals_by_account(libraries, &addresses);
+    let accounts: Vec<&str> = libraries_by_account.keys().copied().collect();
+    let mut approve_msgs = Vec::with_capacity(libraries_by_account.len());
+    for (account, account_libraries) in &libraries_by_account {
+        let approve_msg = valence_account_utils::msg::ExecuteMsg::ApproveLibraries {
+            libraries: account_libraries.clone(),
+        };
+        approve_msgs.push(
+            MsgExecuteContract {
+                sender: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
+                contract: account.to_string(),
+                msg: serde_json::to_vec(&approve_msg)?,
+                funds: vec![],
+            }
+            .build_any_with_type_url("/cosmwasm.wasm.v1.MsgExecuteContract"),
+        );
+    }
+
+    info!("approving libraries on accounts {accounts:?} in a single tx...");
+    broadcast_batch(test_ctx, approve_msgs, &accounts).map_err(|failure| match failure {
+        BatchFailure::Message { target, reason } => {
+            ValenceSetupError::Other(format!("Failed to approve libraries on {target}: {reason}"))
+        }
+        BatchFailure::Tx(reason) => ValenceSetupError::Other(reason),
+    })?;
+
+    for (library, address) in libraries.iter().zip(&addresses) {
+        wait_for_library_approval(
+            test_ctx,
+            NEUTRON_CHAIN_NAME,
+            &library.input_account,
+            address,
+        )?;
+    }
+
+    Ok(addresses)
+}
+
+// Groups the library addresses by input account, so that each account is executed once
+fn approvals_by_account<'a>(
+    libraries: &'a [LibraryInstantiation],
+    addresses: &[String],
+) -> BTreeMap<&'a str, Vec<String>> {
+    let mut libraries_by_account: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (library, address) in libraries.iter().zip(addresses) {
+        libraries_by_account
+            .entry(&library.input_account)
+            .or_default()
+            .push(address.clone());
+    }
+    libraries_by_account
+}
+
+// Failure of a batch tx, either attributed to one of its messages or to the tx as a whole
+enum BatchFailure {
+    Message { target: String, reason: String },
+    Tx(String),
+}
+
+impl From<Box<dyn std::error::Error>> for BatchFailure {
+    fn from(e: Box<dyn std::error::Error>) -> Self {
+        BatchFailure::Tx(e.to_string())
+    }
+}
+
+// Broadcasts `msgs` in a single tx signed by the Neutron admin, and waits for its inclusion.
+// `targets` names the target of each message, to identify the message that failed the tx.
+fn broadcast_batch(
+    test_ctx: &mut TestContext,
+    msgs: Vec<cosmos_grpc_client::cosmos_sdk_proto::Any>,
+    targets: &[&str],
+) -> Result<(), BatchFailure> {
+    let (grpc_url, grpc_port) = get_grpc_address_and_port_from_url(
+        &get_chain_field_from_local_ic_log(NEUTRON_CHAIN_ID, "grpc_address")?,
+    )?;
+
+    let rt = Runtime::new().map_err(|e| BatchFailure::Tx(e.to_string()))?;
+    let response = rt
+        .block_on(async {
+            let grpc_client = GrpcClient::new(&format!("{grpc_url}:{grpc_port}")).await?;
+            let mut wallet = Wallet::from_seed_phrase(
+                grpc_client,
+                ADMIN_MNEMONIC,
+                NEUTRON_CHAIN_PREFIX,
+                CoinType::Cosmos,
+                0,
+                Decimal::from_str("0.0025").unwrap(),
+                Decimal::from_str("1.5").unwrap(),
+                NEUTRON_CHAIN_DENOM,
+            )
+            .await?;
+
+            wallet
+                .broadcast_tx(msgs, None, None, BroadcastMode::Sync)
+                .await
+        })
+        // simulation errors are returned before broadcasting
+        .map_err(|e| batch_failure(targets, &e.to_string()))?;
+
+    let tx_response = response
+        .tx_response
+        .ok_or_else(|| BatchFailure::Tx("batch tx returned no response".to_string()))?;
+    if tx_response.code != 0 {
+        return Err(batch_failure(targets, &tx_response.raw_log));
+    }
+
+    // wait for the tx to be included in a block
+    for _ in 0..TX_POLLING_ATTEMPTS {
+        std::thread::sleep(TX_POLLING_PERIOD);
+
+        let tx = test_ctx
+            .get_request_builder()
+            .get_request_builder(NEUTRON_CHAIN_NAME)
+            .query(&format!("q tx {}", tx_response.txhash), false);
+
+        match tx["code"].as_u64() {
+            Some(0) => {
+                info!("batch tx {} included", tx_response.txhash);
+                return Ok(());
+            }
+            Some(_) => {
+                return Err(batch_failure(
+                    targets,
+                    tx["raw_log"].as_str().unwrap_or_default(),
+                ))
+            }
+            // not included yet
+            None => continue,
+        }
+    }
+
+    Err(BatchFailure::Tx(format!(
+        "batch tx {} not included after {TX_POLLING_ATTEMPTS} attempts",
+        tx_response.txhash
+    )))
+}
+
+// Attributes the failure of a batch tx to the message that failed, which the SDK reports
+// as `message index: {i}` in the logs
+fn batch_failure(targets: &[&str], log: &str) -> BatchFailure {
+    let failed_target = log
+        .split("message index: ")
+        .nth(1)
+        .and_then(|rest| {
+            let index: String = rest.chars().take_while(char::is_ascii_digit).collect();
+            index.parse::<usize>().ok()
+        })
+        .and_then(|index| targets.get(index));
+
+    match failed_target {
+        Some(target) => BatchFailure::Message {
+            target: target.to_string(),
+            reason: log.to_string(),
+        },
+        None => BatchFailure::Tx(format!("batch tx failed: {log}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(label: &str, input_account: &str) -> LibraryInstantiation {
+        LibraryInstantiation::new(label, 1, &serde_json::json!({}), input_account).unwrap()
+    }
+
+    #[test]
+    fn every_batched_library_is_approved_on_its_input_account() {
+        let libraries = vec![
+            library("astroport_lper", "neutron1deposit"),
+            library("astroport_withdrawer", "neutron1position"),
+            library("ica_ibc_transfer", "neutron1ica"),
+            library("neutron_ibc_transfer", "neutron1deposit"),
+        ];
+        let addresses: Vec<String> = (0..libraries.len())
+            .map(|i| format!("neutron1library{i}"))
+            .collect();
+
+        let approvals = approvals_by_account(&libraries, &addresses);
+
+        // one approval message per account, approving all of its libraries
+        assert_eq!(
+            approvals,
+            BTreeMap::from([
+                (
+                    "neutron1deposit",
+                    vec![
+                        "neutron1library0".to_string(),
+                        "neutron1library3".to_string()
+                    ]
+                ),
+                ("neutron1ica", vec!["neutron1library2".to_string()]),
+                ("neutron1position", vec!["neutron1library1".to_string()]),
+            ])
+        );
+        for (library, address) in libraries.iter().zip(&addresses) {
+            assert!(approvals[library.input_account.as_str()].contains(address));
+        }
+    }
+
+    #[test]
+    fn failed_instantiation_names_the_library() {
+        let labels = ["astroport_lper", "astroport_withdrawer", "ica_ibc_transfer"];
+        let log = "failed to execute message; message index: 1: Generic error: invalid config";
+
+        match batch_failure(&labels, log) {
+            BatchFailure::Message { target, reason } => {
+                assert_eq!(target, "astroport_withdrawer");
+                assert_eq!(reason, log);
+            }
+            BatchFailure::Tx(reason) => panic!("failure not attributed: {reason}"),
+        }
+    }
+
+    #[test]
+    fn unattributed_failure_fails_the_whole_batch() {
+        let labels = ["astroport_lper"];
+
+        // out of gas is not attributed to a message, and an index past the batch is ignored
+        for log in [
+            "out of gas in location: WriteFlat",
+            "message index: 3: unknown",
+        ] {
+            assert!(matches!(batch_failure(&labels, log), BatchFailure::Tx(_)));
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use std::time::{Duration, Instant};
+
+use log::info;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::utils::error::ValenceSetupError;
+
+// Circle's attestation services
+pub const CIRCLE_ATTESTATION_API: &str = "https://iris-api.circle.com";
+pub const CIRCLE_SANDBOX_ATTESTATION_API: &str = "https://iris-api-sandbox.circle.com";
+
+const ATTESTATION_POLLING_PERIOD: Duration = Duration::from_secs(5);
+const ATTESTATION_STATUS_COMPLETE: &str = "complete";
+
+/// Endpoint serving Circle's attestation API (v2), either Circle's own service
+/// or a local mock exposing the same routes
+#[derive(Debug, Clone)]
+pub struct CctpAttestationApi {
+    pub url: String,
+    // CCTP domain of the chain on which the burn happened
+    pub source_domain: u32,
+    pub polling_period: Duration,
+}
+
+impl CctpAttestationApi {
+    pub fn new(url: &str, source_domain: u32) -> Self {
+        CctpAttestationApi {
+            url: url.trim_end_matches('/').to_string(),
+            source_domain,
+            polling_period: ATTESTATION_POLLING_PERIOD,
+        }
+    }
+
+    pub fn with_polling_period(mut self, polling_period: Duration) -> Self {
+        self.polling_period = polling_period;
+        self
+    }
+
+    fn messages_url(&self, burn_tx_hash: &str) -> String {
+        format!(
+            "{}/v2/messages/{}?transactionHash={}",
+            self.url, self.source_domain, burn_tx_hash
+        )
+    }
+}
+
+/// Attested CCTP message, ready to be received on the destination domain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CctpAttestation {
+    pub message: String,
+    pub attestation: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    #[serde(default)]
+    messages: Vec<AttestationMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttestationMessage {
+    message: String,
+    attestation: String,
+    status: String,
+    decoded_message: Option<DecodedMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DecodedMessage {
+    destination_domain: String,
+}
+
+impl AttestationMessage {
+    fn is_for(&self, destination_domain: u32) -> bool {
+        // Messages that are not decoded yet can't be told apart, so they are accepted
+        self.decoded_message
+            .as_ref()
+            .is_none_or(|decoded| decoded.destination_domain == destination_domain.to_string())
+    }
+}
+
+/// Polls the attestation API until the message burnt in `burn_tx_hash` towards
+/// `destination_domain` is attested, erroring once `timeout` is elapsed.
+/// The burn is reported as pending while the API doesn't know about the transaction yet.
+pub async fn wait_for_cctp_attestation(
+    api: &CctpAttestationApi,
+    burn_tx_hash: &str,
+    destination_domain: u32,
+    timeout: Duration,
+) -> Result<CctpAttestation, ValenceSetupError> {
+    let client = reqwest::Client::new();
+    let url = api.messages_url(burn_tx_hash);
+    let start = Instant::now();
+
+    loop {
+        let response = client.get(&url).send().await?;
+
+        if response.status() != StatusCode::NOT_FOUND {
+            let messages: MessagesResponse = response.error_for_status()?.json().await?;
+            let attested = messages.messages.into_iter().find(|msg| {
+                msg.status == ATTESTATION_STATUS_COMPLETE && msg.is_for(destination_domain)
+            });
+
+            if let Some(msg) = attested {
+                info!(
+                    "CCTP burn {burn_tx_hash} attested after {:?}",
+                    start.elapsed()
+                );
+                return Ok(CctpAttestation {
+                    message: msg.message,
+                    attestation: msg.attestation,
+                });
+            }
+        }
+
+        if start.elapsed() + api.polling_period > timeout {
+            return Err(ValenceSetupError::AttestationTimeout {
+                tx_hash: burn_tx_hash.to_string(),
+                timeout,
+            });
+        }
+
+        info!("CCTP burn {burn_tx_hash} pending attestation, polling again...");
+        tokio::time::sleep(api.polling_period).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread::JoinHandle,
+    };
+
+    use serde_json::json;
+
+    use super::*;
+
+    const BURN_TX: &str = "0xburn";
+    const NOBLE_DOMAIN: u32 = 4;
+    const POLLING_PERIOD: Duration = Duration::from_millis(10);
+
+    // Attestation server answering each request with the next response, repeating the last one.
+    // Returns its url, the paths requested and the handle of the thread serving them
+    fn mock_attestation_api(
+        responses: Vec<(u16, serde_json::Value)>,
+    ) -> (String, Arc<Mutex<Vec<String>>>, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let served = requests.clone();
+
+        let handle = std::thread::spawn(move || {
+            for (i, stream) in listener.incoming().enumerate() {
+                let mut stream = stream.unwrap();
+                let mut request = vec![];
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = stream.read(&mut buf).unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..read]);
+                }
+                let request = String::from_utf8_lossy(&request).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                served.lock().unwrap().push(path.to_string());
+
+                let (status, body) = &responses[i.min(responses.len() - 1)];
+                let body = body.to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} Mock\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+
+                if i + 1 >= responses.len() {
+                    break;
+                }
+            }
+        });
+
+        (url, requests, handle)
+    }
+
+    fn message(status: &str) -> serde_json::Value {
+        json!({
+            "message": "0xmessage",
+            "attestation": if status == "complete" { "0xattestation" } else { "PENDING" },
+            "status": status,
+            "decodedMessage": { "destinationDomain": NOBLE_DOMAIN.to_string() },
+        })
+    }
+
+    #[tokio::test]
+    async fn pending_attestation_is_polled_until_complete() {
+        let (url, requests, server) = mock_attestation_api(vec![
+            // the burn is not indexed yet
+            (404, json!({ "error": "Message not found" })),
+            (
+                200,
+                json!({ "messages": [message("pending_confirmations")] }),
+            ),
+            (200, json!({ "messages": [message("complete")] })),
+        ]);
+        let api = CctpAttestationApi::new(&url, 0).with_polling_period(POLLING_PERIOD);
+
+        let attestation =
+            wait_for_cctp_attestation(&api, BURN_TX, NOBLE_DOMAIN, Duration::from_secs(5))
+                .await
+                .unwrap();
+        server.join().unwrap();
+
+        assert_eq!(
+            attestation,
+            CctpAttestation {
+                message: "0xmessage".to_string(),
+                attestation: "0xattestation".to_string(),
+            }
+        );
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests
+            .iter()
+            .all(|path| path == &format!("/v2/messages/0?transactionHash={BURN_TX}")));
+    }
+
+    #[tokio::test]
+    async fn attestation_that_never_completes_times_out() {
+        let pending = json!({ "messages": [message("pending_confirmations")] });
+        let (url, requests, _server) = mock_attestation_api(vec![(200, pending); 10]);
+        let api = CctpAttestationApi::new(&url, 0).with_polling_period(POLLING_PERIOD);
+        let timeout = Duration::from_millis(50);
+
+        let err = wait_for_cctp_attestation(&api, BURN_TX, NOBLE_DOMAIN, timeout)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            &err,
+            ValenceSetupError::AttestationTimeout { tx_hash, timeout: t }
+                if tx_hash == BURN_TX && *t == timeout
+        ));
+        assert!(err.is_transient());
+        assert!(requests.lock().unwrap().len() > 1);
+    }
+
+    #[tokio::test]
+    async fn attestation_towards_another_domain_is_ignored() {
+        let mut other_domain = message("complete");
+        other_domain["decodedMessage"]["destinationDomain"] = json!("0");
+        let responses = vec![(200, json!({ "messages": [other_domain] })); 10];
+        let (url, _requests, _server) = mock_attestation_api(responses);
+        let api = CctpAttestationApi::new(&url, 0).with_polling_period(POLLING_PERIOD);
+
+        let err = wait_for_cctp_attestation(&api, BURN_TX, NOBLE_DOMAIN, Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ValenceSetupError::AttestationTimeout { .. }));
+    }
+}
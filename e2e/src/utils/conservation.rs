@@ -0,0 +1,287 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use cosmwasm_std::{Coin, Decimal, Uint128};
+use log::info;
+use serde::Serialize;
+
+use crate::utils::{
+    denom_alias::DenomAliases, error::ValenceSetupError, setup_context::SetupContext,
+};
+
+/// Account on `chain_name` whose balances are part of the funds of a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedAccount {
+    pub role: String,
+    pub chain_name: String,
+    pub address: String,
+}
+
+/// Accounts holding the funds of a program: its base accounts, and the remote addresses of its ICAs.
+/// Denoms of the same asset on different chains (e.g. USDC on noble and its IBC denom on neutron)
+/// are aliased to a common symbol, so that funds moved across chains still count as that asset
+#[derive(Debug, Clone, Default)]
+pub struct FundsAccounts {
+    pub accounts: Vec<TrackedAccount>,
+    assets: DenomAliases,
+}
+
+impl FundsAccounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_account(mut self, role: &str, chain_name: &str, address: &str) -> Self {
+        self.accounts.push(TrackedAccount {
+            role: role.to_string(),
+            chain_name: chain_name.to_string(),
+            address: address.to_string(),
+        });
+        self
+    }
+
+    pub fn with_asset(mut self, denom: &str, symbol: &str) -> Self {
+        self.assets = self.assets.with_alias(denom, symbol);
+        self
+    }
+
+    /// Asset `denom` is counted as, the denom itself if it's not aliased
+    pub fn asset<'a>(&'a self, denom: &'a str) -> &'a str {
+        self.assets.display(denom)
+    }
+}
+
+/// Balances held by each tracked account at some point of a test, keyed by role
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FundsSnapshot {
+    pub balances: BTreeMap<String, Vec<Coin>>,
+}
+
+impl FundsSnapshot {
+    /// Reads the balances of every tracked account
+    pub fn take(
+        test_ctx: &mut impl SetupContext,
+        accounts: &FundsAccounts,
+    ) -> Result<Self, ValenceSetupError> {
+        let mut snapshot = FundsSnapshot::default();
+        for account in &accounts.accounts {
+            let held = test_ctx.balances(&account.chain_name, &account.address)?;
+            snapshot = snapshot.with_balances(&account.role, held);
+        }
+        Ok(snapshot)
+    }
+
+    /// Adds balances that can't be read through the test context, e.g. those of the withdraw
+    /// account on Ethereum once the funds were bridged with CCTP
+    pub fn with_balances(mut self, role: &str, coins: Vec<Coin>) -> Self {
+        self.balances
+            .entry(role.to_string())
+            .or_default()
+            .extend(coins);
+        self
+    }
+
+    /// Total amount of each asset held across the accounts
+    pub fn totals(&self, accounts: &FundsAccounts) -> BTreeMap<String, Uint128> {
+        let mut totals: BTreeMap<String, Uint128> = BTreeMap::new();
+        for coin in self.balances.values().flatten() {
+            *totals
+                .entry(accounts.asset(&coin.denom).to_string())
+                .or_default() += coin.amount;
+        }
+        totals
+    }
+}
+
+/// How far apart the totals of an asset can be before and after, to account for the fees paid
+/// along the way. The allowed difference is the largest of the relative and absolute tolerances
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FundsTolerance {
+    // share of the total held before
+    relative: Decimal,
+    // amount of each asset
+    absolute: BTreeMap<String, Uint128>,
+}
+
+impl FundsTolerance {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_relative(mut self, relative: Decimal) -> Self {
+        self.relative = relative;
+        self
+    }
+
+    pub fn with_absolute(mut self, asset: &str, amount: u128) -> Self {
+        self.absolute
+            .insert(asset.to_string(), Uint128::new(amount));
+        self
+    }
+
+    /// Difference allowed for `asset`, of which `before` was held
+    pub fn allowed(&self, asset: &str, before: Uint128) -> Uint128 {
+        let absolute = self.absolute.get(asset).copied().unwrap_or_default();
+        absolute.max(before.mul_ceil(self.relative))
+    }
+}
+
+/// Checks that the total of every asset held by `accounts` is the same in `before` and `after`,
+/// within `tolerance`. Funds stranded in an account that is not tracked (e.g. a library or an
+/// intermediate contract) show up as a difference. Without an `after` snapshot, the current
+/// balances of the accounts are read
+pub fn assert_funds_conserved(
+    test_ctx: &mut impl SetupContext,
+    accounts: &FundsAccounts,
+    before: &FundsSnapshot,
+    after: Option<&FundsSnapshot>,
+    tolerance: &FundsTolerance,
+) -> Result<(), ValenceSetupError> {
+    let current;
+    let after = match after {
+        Some(after) => after,
+        None => {
+            current = FundsSnapshot::take(test_ctx, accounts)?;
+            &current
+        }
+    };
+
+    let (before, after) = (before.totals(accounts), after.totals(accounts));
+    let assets: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+
+    let mut imbalances = vec![];
+    for asset in assets {
+        let held_before = before.get(asset).copied().unwrap_or_default();
+        let held_after = after.get(asset).copied().unwrap_or_default();
+        let allowed = tolerance.allowed(asset, held_before);
+
+        if held_before.abs_diff(held_after) > allowed {
+            imbalances.push(format!(
+                "{asset}: {held_before} before, {held_after} after, more than {allowed} apart"
+            ));
+        } else {
+            info!("Funds conserved: {held_before}{asset} before, {held_after}{asset} after");
+        }
+    }
+
+    if imbalances.is_empty() {
+        return Ok(());
+    }
+
+    Err(ValenceSetupError::FundsNotConserved(imbalances))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::coin;
+
+    use crate::utils::mocks::test_context::MockTestContext;
+
+    use super::*;
+
+    const USDC_ON_NEUTRON: &str = "ibc/usdc";
+
+    fn program_accounts() -> FundsAccounts {
+        FundsAccounts::new()
+            .with_account("deposit", "neutron", "neutron1deposit")
+            .with_account("position", "neutron", "neutron1position")
+            .with_account("withdraw", "neutron", "neutron1withdraw")
+            .with_account("noble_outbound_ica", "noble", "noble1outbound")
+            .with_asset(USDC_ON_NEUTRON, "USDC")
+            .with_asset("uusdc", "USDC")
+    }
+
+    // funds deposited on neutron, before the cycle
+    fn deposited() -> MockTestContext {
+        MockTestContext::new().with_balances(
+            "neutron",
+            "neutron1deposit",
+            vec![coin(1_000_000, USDC_ON_NEUTRON)],
+        )
+    }
+
+    #[test]
+    fn funds_moved_across_chains_are_conserved_within_the_fees() {
+        let accounts = program_accounts();
+        let before = FundsSnapshot::take(&mut deposited(), &accounts).unwrap();
+
+        // the funds went through the position and came back to the outbound ICA on noble,
+        // minus the fees of the route and some dust left in the withdraw account
+        let mut test_ctx = MockTestContext::new()
+            .with_balances(
+                "neutron",
+                "neutron1withdraw",
+                vec![coin(3, USDC_ON_NEUTRON)],
+            )
+            .with_balances("noble", "noble1outbound", vec![coin(999_000, "uusdc")]);
+        let tolerance = FundsTolerance::new().with_relative(Decimal::permille(1));
+
+        assert_eq!(
+            FundsSnapshot::take(&mut test_ctx, &accounts)
+                .unwrap()
+                .totals(&accounts),
+            BTreeMap::from([("USDC".to_string(), Uint128::new(999_003))])
+        );
+        assert_funds_conserved(&mut test_ctx, &accounts, &before, None, &tolerance).unwrap();
+    }
+
+    #[test]
+    fn funds_leaking_out_of_the_tracked_accounts_are_not_conserved() {
+        let accounts = program_accounts();
+        let before = FundsSnapshot::take(&mut deposited(), &accounts).unwrap();
+
+        // a deliberate leak: part of the deposit is stranded in a library that is not tracked
+        let mut test_ctx = MockTestContext::new()
+            .with_balances(
+                "neutron",
+                "neutron1library",
+                vec![coin(400_000, USDC_ON_NEUTRON)],
+            )
+            .with_balances("noble", "noble1outbound", vec![coin(600_000, "uusdc")]);
+        let tolerance = FundsTolerance::new()
+            .with_relative(Decimal::permille(1))
+            .with_absolute("USDC", 10);
+
+        let err = assert_funds_conserved(&mut test_ctx, &accounts, &before, None, &tolerance)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Funds not conserved: USDC: 1000000 before, 600000 after, more than 1000 apart"
+        );
+    }
+
+    #[test]
+    fn balances_outside_of_the_test_context_are_counted() {
+        let accounts = program_accounts().with_asset("eth:usdc", "USDC");
+        let before = FundsSnapshot::take(&mut deposited(), &accounts).unwrap();
+
+        // the funds were bridged to the withdraw account on ethereum, which can't be queried
+        let mut test_ctx = MockTestContext::new();
+        let after = FundsSnapshot::take(&mut test_ctx, &accounts)
+            .unwrap()
+            .with_balances("eth_withdraw", vec![coin(1_000_000, "eth:usdc")]);
+
+        assert_funds_conserved(
+            &mut test_ctx,
+            &accounts,
+            &before,
+            Some(&after),
+            &FundsTolerance::new(),
+        )
+        .unwrap();
+
+        // funds that appear out of nowhere are not conserved either
+        let after = after.with_balances("eth_withdraw", vec![coin(1, "lp")]);
+        let err = assert_funds_conserved(
+            &mut test_ctx,
+            &accounts,
+            &before,
+            Some(&after),
+            &FundsTolerance::new(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Funds not conserved: lp: 0 before, 1 after, more than 0 apart"
+        );
+    }
+}
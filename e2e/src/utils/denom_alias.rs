@@ -0,0 +1,132 @@
+use std::{collections::BTreeMap, fmt::Display, sync::OnceLock};
+
+use cosmwasm_std::Coin;
+
+// aliases used when rendering denoms in the logs, set once by the test that needs them
+static DENOM_ALIASES: OnceLock<DenomAliases> = OnceLock::new();
+
+/// Friendly symbols (e.g. USDC, NTRN) of canonical denoms (e.g. `ibc/...`, `factory/...`), only used
+/// to render denoms in logs. Messages sent on chain always keep the canonical denom
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DenomAliases {
+    // canonical denom -> symbol
+    aliases: BTreeMap<String, String>,
+}
+
+impl DenomAliases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_alias(mut self, denom: impl Into<String>, symbol: impl Into<String>) -> Self {
+        self.aliases.insert(denom.into(), symbol.into());
+        self
+    }
+
+    /// Symbol of `denom`, or the denom itself if it has no alias
+    pub fn display<'a>(&'a self, denom: &'a str) -> &'a str {
+        self.aliases.get(denom).map(String::as_str).unwrap_or(denom)
+    }
+
+    /// Renders `amount` of `denom` as `{amount}{symbol}`
+    pub fn display_coin(&self, amount: impl Display, denom: &str) -> String {
+        format!("{amount}{}", self.display(denom))
+    }
+}
+
+/// Sets the aliases used to render denoms in the logs. Returns false if they were already set
+pub fn set_denom_aliases(aliases: DenomAliases) -> bool {
+    DENOM_ALIASES.set(aliases).is_ok()
+}
+
+/// Symbol of `denom` in the configured aliases, or the denom itself if there are none
+pub fn display_denom(denom: &str) -> &str {
+    match DENOM_ALIASES.get() {
+        Some(aliases) => aliases.display(denom),
+        None => denom,
+    }
+}
+
+/// Renders `amount` of `denom` with the configured aliases
+pub fn display_coin(amount: impl Display, denom: &str) -> String {
+    format!("{amount}{}", display_denom(denom))
+}
+
+/// Renders `coins` with the configured aliases, separated by commas
+pub fn display_coins(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|coin| display_coin(coin.amount, &coin.denom))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use cosmwasm_std::{coin, BankMsg, CosmosMsg};
+    use log::{info, Level, Log, Metadata, Record};
+
+    use crate::utils::teardown::sweep_msg;
+
+    use super::*;
+
+    const USDC_IBC_DENOM: &str =
+        "ibc/4C19E7EC06C1AB2EC2D70C6855FEB6D48E9CE174913991DA0A517D21978E7E42";
+
+    // keeps the messages of the records logged by the tests
+    struct CapturingLogger {
+        records: Mutex<Vec<String>>,
+    }
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= Level::Info
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                self.records.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger {
+        records: Mutex::new(vec![]),
+    };
+
+    #[test]
+    fn denoms_without_alias_render_as_is() {
+        let aliases = DenomAliases::new().with_alias(USDC_IBC_DENOM, "USDC");
+
+        assert_eq!(aliases.display(USDC_IBC_DENOM), "USDC");
+        assert_eq!(aliases.display("untrn"), "untrn");
+        assert_eq!(aliases.display_coin(100u128, "untrn"), "100untrn");
+    }
+
+    #[test]
+    fn aliased_denom_is_logged_as_its_symbol_but_sent_as_canonical() {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Info);
+        assert!(set_denom_aliases(
+            DenomAliases::new()
+                .with_alias(USDC_IBC_DENOM, "USDC")
+                .with_alias("untrn", "NTRN")
+        ));
+
+        let balances = vec![coin(1_000_000, USDC_IBC_DENOM), coin(5, "untrn")];
+        let sweep = sweep_msg(&balances, "neutron1admin").unwrap();
+        info!("Swept {} to neutron1admin", display_coins(&balances));
+
+        let records = LOGGER.records.lock().unwrap();
+        assert!(records.contains(&"Swept 1000000USDC, 5NTRN to neutron1admin".to_string()));
+        // the transfer itself keeps the canonical denoms
+        let CosmosMsg::Bank(BankMsg::Send { amount, .. }) = sweep else {
+            panic!("expected a bank send");
+        };
+        assert_eq!(amount, balances);
+    }
+}
@@ -0,0 +1,117 @@
+use std::{error::Error, time::Duration};
+
+use localic_utils::utils::test_context::TestContext;
+use thiserror::Error;
+
+/// Errors returned by the e2e setup helpers.
+/// Being `Error + Send + Sync`, it converts into `Box<dyn Error>` so callers can keep using `?`
+#[derive(Error, Debug)]
+pub enum ValenceSetupError {
+    #[error("No transfer channel found from {src} to {dest}")]
+    ChannelNotFound { src: String, dest: String },
+
+    #[error("Code id for {contract} not found on {chain}")]
+    CodeIdNotFound { chain: String, contract: String },
+
+    #[error("No local code id cache for chain {0}")]
+    UnknownChain(String),
+
+    #[error("Failed to upload contracts to {chain}: {reason}")]
+    Upload { chain: String, reason: String },
+
+    #[error("Failed to instantiate {label}: {reason}")]
+    Instantiation { label: String, reason: String },
+
+    #[error(transparent)]
+    Artifact(#[from] std::io::Error),
+
+    #[error("Missing contract artifacts: {}", .0.join(", "))]
+    MissingArtifact(Vec<String>),
+
+    #[error(transparent)]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("Library {library} not approved on {account} within {timeout:?}")]
+    ApprovalTimeout {
+        account: String,
+        library: String,
+        timeout: Duration,
+    },
+
+    #[error("CCTP burn {tx_hash} not attested within {timeout:?}")]
+    AttestationTimeout { tx_hash: String, timeout: Duration },
+
+    #[error("ICA channel of {account} not open within {timeout:?}")]
+    RegistrationTimeout { account: String, timeout: Duration },
+
+    #[error("ICA of {account} not registered: {reason}")]
+    IcaNotRegistered { account: String, reason: String },
+
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    #[error("Program not ready: {}", .0.join("; "))]
+    ProgramNotReady(Vec<String>),
+
+    #[error("Funds not conserved: {}", .0.join("; "))]
+    FundsNotConserved(Vec<String>),
+
+    #[error("{0}")]
+    Other(String),
+}
+
+impl ValenceSetupError {
+    /// Returns true if the failure can go away by retrying the setup step
+    /// (e.g. the relayer has not created the channel yet or a tx did not land)
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ValenceSetupError::ChannelNotFound { .. }
+                | ValenceSetupError::Upload { .. }
+                | ValenceSetupError::Instantiation { .. }
+                | ValenceSetupError::ApprovalTimeout { .. }
+                | ValenceSetupError::AttestationTimeout { .. }
+                | ValenceSetupError::RegistrationTimeout { .. }
+        )
+    }
+}
+
+impl From<Box<dyn Error>> for ValenceSetupError {
+    fn from(e: Box<dyn Error>) -> Self {
+        ValenceSetupError::Other(e.to_string())
+    }
+}
+
+/// Returns the transfer channel id from `src` to `dest`
+pub fn get_transfer_channel(
+    test_ctx: &TestContext,
+    src: &str,
+    dest: &str,
+) -> Result<String, ValenceSetupError> {
+    test_ctx
+        .transfer_channel_ids
+        .get(&(src.to_string(), dest.to_string()))
+        .cloned()
+        .ok_or_else(|| ValenceSetupError::ChannelNotFound {
+            src: src.to_string(),
+            dest: dest.to_string(),
+        })
+}
+
+/// Returns the code id of a contract uploaded to `chain`
+pub fn get_code_id(
+    test_ctx: &mut TestContext,
+    chain: &str,
+    contract: &str,
+) -> Result<u64, ValenceSetupError> {
+    test_ctx
+        .get_contract()
+        .src(chain)
+        .contract(contract)
+        .get_cw()
+        .code_id
+        .ok_or_else(|| ValenceSetupError::CodeIdNotFound {
+            chain: chain.to_string(),
+            contract: contract.to_string(),
+        })
+}
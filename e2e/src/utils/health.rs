@@ -0,0 +1,370 @@
+use std::{collections::BTreeMap, fmt};
+
+use localic_std::modules::cosmwasm::contract_query;
+use localic_utils::utils::test_context::TestContext;
+use log::{info, warn};
+use serde::{de::DeserializeOwned, Serialize};
+use valence_account_utils::ica::IcaState;
+
+use crate::utils::error::ValenceSetupError;
+
+/// Components of a program deployed on a chain, checked by `program_health`
+#[derive(Debug, Clone, Default)]
+pub struct ProgramComponents {
+    // base accounts keyed by role
+    pub accounts: BTreeMap<String, String>,
+    // Valence interchain accounts keyed by role
+    pub interchain_accounts: BTreeMap<String, String>,
+    pub libraries: Vec<LibraryComponent>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LibraryComponent {
+    pub name: String,
+    pub address: String,
+    // account on which the library must be approved
+    pub input_account: String,
+}
+
+impl ProgramComponents {
+    pub fn with_account(mut self, role: &str, address: &str) -> Self {
+        self.accounts.insert(role.to_string(), address.to_string());
+        self
+    }
+
+    pub fn with_interchain_account(mut self, role: &str, address: &str) -> Self {
+        self.interchain_accounts
+            .insert(role.to_string(), address.to_string());
+        self
+    }
+
+    pub fn with_library(mut self, name: &str, address: &str, input_account: &str) -> Self {
+        self.libraries.push(LibraryComponent {
+            name: name.to_string(),
+            address: address.to_string(),
+            input_account: input_account.to_string(),
+        });
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentKind {
+    Account,
+    InterchainAccount,
+    Library,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentStatus {
+    Ready,
+    // the contract does not answer queries
+    NotInstantiated,
+    // the library is not in the approved libraries of its input account
+    NotApproved { account: String },
+    // the ICA is not (or no longer) created, with its current state
+    IcaNotRegistered { state: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ComponentHealth {
+    pub name: String,
+    pub address: String,
+    pub kind: ComponentKind,
+    pub status: ComponentStatus,
+}
+
+impl ComponentHealth {
+    pub fn is_ready(&self) -> bool {
+        self.status == ComponentStatus::Ready
+    }
+}
+
+impl fmt::Display for ComponentHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {} ({}): ", self.kind, self.name, self.address)?;
+        match &self.status {
+            ComponentStatus::Ready => write!(f, "ready"),
+            ComponentStatus::NotInstantiated => write!(f, "not instantiated"),
+            ComponentStatus::NotApproved { account } => write!(f, "not approved on {account}"),
+            ComponentStatus::IcaNotRegistered { state } => {
+                write!(f, "ICA not registered ({state})")
+            }
+        }
+    }
+}
+
+/// Per-component readiness of a program
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProgramHealthReport {
+    pub components: Vec<ComponentHealth>,
+}
+
+impl ProgramHealthReport {
+    pub fn is_ready(&self) -> bool {
+        self.components.iter().all(ComponentHealth::is_ready)
+    }
+
+    // returns the components that are not ready
+    pub fn unhealthy(&self) -> Vec<&ComponentHealth> {
+        self.components.iter().filter(|c| !c.is_ready()).collect()
+    }
+
+    fn push(&mut self, name: &str, address: &str, kind: ComponentKind, status: ComponentStatus) {
+        self.components.push(ComponentHealth {
+            name: name.to_string(),
+            address: address.to_string(),
+            kind,
+            status,
+        });
+    }
+
+    /// Readiness gate: errors with the components that are not ready, if any
+    pub fn ensure_ready(&self) -> Result<(), ValenceSetupError> {
+        let unhealthy = self.unhealthy();
+        if unhealthy.is_empty() {
+            return Ok(());
+        }
+
+        Err(ValenceSetupError::ProgramNotReady(
+            unhealthy.iter().map(ToString::to_string).collect(),
+        ))
+    }
+}
+
+/// Checks that every account and library of the program is instantiated, that every library
+/// is approved on its input account, and that every interchain account is registered
+pub fn program_health(
+    test_ctx: &mut TestContext,
+    chain_name: &str,
+    components: &ProgramComponents,
+) -> ProgramHealthReport {
+    check_program_health(components, |address, msg| {
+        let response = contract_query(
+            test_ctx
+                .get_request_builder()
+                .get_request_builder(chain_name),
+            address,
+            msg,
+        );
+        Some(response["data"].clone())
+    })
+}
+
+/// Same checks as `program_health`, sending the JSON smart queries of the contracts through
+/// `query_contract`, which returns the data of the response or None if the contract doesn't answer it
+fn check_program_health(
+    components: &ProgramComponents,
+    mut query_contract: impl FnMut(&str, &str) -> Option<serde_json::Value>,
+) -> ProgramHealthReport {
+    let mut report = ProgramHealthReport::default();
+    // approved libraries of the accounts, queried once per account
+    let mut approved_libraries: BTreeMap<String, Option<Vec<String>>> = BTreeMap::new();
+
+    for (role, address) in &components.accounts {
+        let approved = query_approved_libraries(&mut query_contract, address);
+        let status = match approved {
+            Some(_) => ComponentStatus::Ready,
+            None => ComponentStatus::NotInstantiated,
+        };
+        approved_libraries.insert(address.clone(), approved);
+        report.push(role, address, ComponentKind::Account, status);
+    }
+
+    for (role, address) in &components.interchain_accounts {
+        let status = match query::<IcaState>(
+            &mut query_contract,
+            address,
+            &valence_account_utils::ica::QueryMsg::IcaState {},
+        ) {
+            Some(IcaState::Created(_)) => ComponentStatus::Ready,
+            Some(state) => ComponentStatus::IcaNotRegistered {
+                state: format!("{state:?}"),
+            },
+            None => ComponentStatus::NotInstantiated,
+        };
+        approved_libraries.insert(
+            address.clone(),
+            query_approved_libraries(&mut query_contract, address),
+        );
+        report.push(role, address, ComponentKind::InterchainAccount, status);
+    }
+
+    for library in &components.libraries {
+        // every library answers the processor query
+        let instantiated = query::<String>(
+            &mut query_contract,
+            &library.address,
+            &serde_json::json!({ "get_processor": {} }),
+        )
+        .is_some();
+
+        // libraries report whether they are approved on their input accounts, the approved libraries
+        // of the input account are only queried for the libraries that don't answer the query
+        let approved = instantiated
+            && match query::<bool>(
+                &mut query_contract,
+                &library.address,
+                &serde_json::json!({ "is_approved": {} }),
+            ) {
+                Some(approved) => approved,
+                None => approved_libraries
+                    .entry(library.input_account.clone())
+                    .or_insert_with(|| {
+                        query_approved_libraries(&mut query_contract, &library.input_account)
+                    })
+                    .as_ref()
+                    .is_some_and(|approved| approved.contains(&library.address)),
+            };
+
+        let status = match (instantiated, approved) {
+            (false, _) => ComponentStatus::NotInstantiated,
+            (true, false) => ComponentStatus::NotApproved {
+                account: library.input_account.clone(),
+            },
+            (true, true) => ComponentStatus::Ready,
+        };
+        report.push(
+            &library.name,
+            &library.address,
+            ComponentKind::Library,
+            status,
+        );
+    }
+
+    for component in &report.components {
+        match component.is_ready() {
+            true => info!("{component}"),
+            false => warn!("{component}"),
+        }
+    }
+
+    report
+}
+
+// Returns the approved libraries of a (base or interchain) account, or None if it's not instantiated
+fn query_approved_libraries(
+    query_contract: &mut impl FnMut(&str, &str) -> Option<serde_json::Value>,
+    account: &str,
+) -> Option<Vec<String>> {
+    query(
+        query_contract,
+        account,
+        &valence_account_utils::msg::QueryMsg::ListApprovedLibraries {},
+    )
+}
+
+// Returns the response of a smart query, or None if the contract doesn't answer it
+fn query<T: DeserializeOwned>(
+    query_contract: &mut impl FnMut(&str, &str) -> Option<serde_json::Value>,
+    address: &str,
+    msg: &impl Serialize,
+) -> Option<T> {
+    let data = query_contract(address, &serde_json::to_string(msg).ok()?)?;
+    serde_json::from_value(data).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use valence_account_utils::ica::IcaInformation;
+
+    use super::*;
+
+    const DEPOSIT: &str = "neutron1deposit";
+    const ICA: &str = "neutron1ica";
+    const LPER: &str = "neutron1lper";
+    const FORWARDER: &str = "neutron1forwarder";
+
+    fn components() -> ProgramComponents {
+        ProgramComponents::default()
+            .with_account("deposit", DEPOSIT)
+            .with_interchain_account("noble_inbound_ica", ICA)
+            .with_library("astroport_lper", LPER, DEPOSIT)
+            .with_library("ica_ibc_transfer", FORWARDER, ICA)
+    }
+
+    // Program where every component is deployed, with the libraries approved on their accounts
+    // unless listed in `unapproved`
+    fn chain(unapproved: &[&str]) -> impl FnMut(&str, &str) -> Option<Value> + '_ {
+        move |address, msg| {
+            let msg: Value = serde_json::from_str(msg).unwrap();
+            let approved_on = |account: &str| -> Vec<&str> {
+                [(LPER, DEPOSIT), (FORWARDER, ICA)]
+                    .into_iter()
+                    .filter(|(library, input)| *input == account && !unapproved.contains(library))
+                    .map(|(library, _)| library)
+                    .collect()
+            };
+            match (address, msg.as_object()?.keys().next()?.as_str()) {
+                (DEPOSIT | ICA, "list_approved_libraries") => Some(json!(approved_on(address))),
+                (ICA, "ica_state") => Some(
+                    serde_json::to_value(IcaState::Created(IcaInformation {
+                        address: "noble1remote".to_string(),
+                        port_id: "icacontroller-neutron1ica".to_string(),
+                        controller_connection_id: "connection-0".to_string(),
+                    }))
+                    .unwrap(),
+                ),
+                (LPER | FORWARDER, "get_processor") => Some(json!("neutron1processor")),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn deployed_program_is_ready() {
+        let report = check_program_health(&components(), chain(&[]));
+
+        assert_eq!(report.components.len(), 4);
+        assert!(report.is_ready());
+        report.ensure_ready().unwrap();
+    }
+
+    #[test]
+    fn unapproved_library_is_the_only_unhealthy_component() {
+        let report = check_program_health(&components(), chain(&[FORWARDER]));
+
+        assert!(!report.is_ready());
+        assert_eq!(
+            report.unhealthy(),
+            vec![&ComponentHealth {
+                name: "ica_ibc_transfer".to_string(),
+                address: FORWARDER.to_string(),
+                kind: ComponentKind::Library,
+                status: ComponentStatus::NotApproved {
+                    account: ICA.to_string(),
+                },
+            }]
+        );
+        assert_eq!(
+            report.ensure_ready().unwrap_err().to_string(),
+            format!(
+                "Program not ready: Library ica_ibc_transfer ({FORWARDER}): not approved on {ICA}"
+            )
+        );
+    }
+
+    #[test]
+    fn missing_contracts_are_not_instantiated() {
+        let mut deployed = chain(&[]);
+        let report = check_program_health(&components(), |address, msg| {
+            // the lper was never instantiated
+            if address == LPER {
+                return None;
+            }
+            deployed(address, msg)
+        });
+
+        assert_eq!(
+            report
+                .unhealthy()
+                .iter()
+                .map(|component| (component.name.as_str(), component.status.clone()))
+                .collect::<Vec<_>>(),
+            vec![("astroport_lper", ComponentStatus::NotInstantiated)]
+        );
+    }
+}
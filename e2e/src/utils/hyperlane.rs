@@ -12,6 +12,7 @@ use log::{error, info};
 use serde_json::{json, Value};
 
 use super::{
+    invalidate_stale_code_ids,
     solidity_contracts::{
         InterchainGasPaymaster, Mailbox, MerkleTreeHook, PausableIsm, ValidatorAnnounce,
     },
@@ -72,7 +73,11 @@ pub fn set_up_cw_hyperlane_contracts(
 ) -> Result<HyperlaneContracts, Box<dyn std::error::Error>> {
     // Initialize contract uploader with test context
     let mut uploader = test_ctx.build_tx_upload_contracts();
-    // Upload contracts using local cache for optimization
+    // Upload contracts using local cache for optimization, uploading again the contracts that changed
+    invalidate_stale_code_ids(
+        HYPERLANE_COSMWASM_ARTIFACTS_PATH,
+        LOCAL_CODE_ID_CACHE_PATH_NEUTRON,
+    )?;
     uploader
         .send_with_local_cache(
             HYPERLANE_COSMWASM_ARTIFACTS_PATH,
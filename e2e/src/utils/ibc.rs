@@ -1,12 +1,15 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use cosmwasm_std_old::Uint128;
 use localic_std::modules::{bank::get_balance, cosmwasm::contract_query};
 use localic_utils::{utils::test_context::TestContext, NEUTRON_CHAIN_NAME};
 use log::info;
-use valence_account_utils::ica::IcaState;
+use valence_account_utils::ica::{IcaInformation, IcaState};
 
-use super::relayer::restart_relayer;
+use super::{
+    denom_alias::display_denom, error::ValenceSetupError, relayer::restart_relayer,
+    setup_context::SetupContext,
+};
 
 #[allow(clippy::too_many_arguments)]
 pub fn send_successful_ibc_transfer(
@@ -35,7 +38,8 @@ pub fn send_successful_ibc_transfer(
 
         info!(
             "Waiting to receive {} IBC transfer on {}...",
-            origin_denom, dest_chain
+            display_denom(origin_denom),
+            dest_chain
         );
         std::thread::sleep(std::time::Duration::from_secs(5));
         let balance = get_balance(
@@ -48,7 +52,7 @@ pub fn send_successful_ibc_transfer(
             .iter()
             .any(|c| c.denom == dest_denom && c.amount >= Uint128::new(amount))
         {
-            info!("Received {} IBC transfer!", origin_denom);
+            info!("Received {} IBC transfer!", display_denom(origin_denom));
             break;
         }
     }
@@ -95,3 +99,188 @@ where
         std::thread::sleep(Duration::from_secs(10));
     }
 }
+
+/// Polls the state of the ICA of `addr` until its channel is open, erroring once `timeout`
+/// is elapsed. A channel that closes while waiting fails the registration right away.
+pub fn wait_for_ica_channel_open<Q>(
+    addr: &str,
+    mut query_state: Q,
+    timeout: Duration,
+    polling_interval: Duration,
+) -> Result<IcaInformation, ValenceSetupError>
+where
+    Q: FnMut() -> Result<IcaState, ValenceSetupError>,
+{
+    let start = Instant::now();
+
+    loop {
+        match query_state()? {
+            IcaState::Created(ica_info) => {
+                info!("ICA channel of {addr} open after {:?}", start.elapsed());
+                return Ok(ica_info);
+            }
+            IcaState::Closed => {
+                return Err(ValenceSetupError::Other(format!(
+                    "ICA channel of {addr} closed during registration"
+                )));
+            }
+            state => info!("Waiting for the ICA channel of {addr} to open, current state: {state}"),
+        }
+
+        if start.elapsed() + polling_interval > timeout {
+            return Err(ValenceSetupError::RegistrationTimeout {
+                account: addr.to_string(),
+                timeout,
+            });
+        }
+
+        std::thread::sleep(polling_interval);
+    }
+}
+
+/// Checks that the interchain account `library_addr` on neutron is fully registered: its ICA
+/// channel is open and it holds a remote address, which is returned along with the channel info
+pub fn assert_ica_registered(
+    test_ctx: &mut impl SetupContext,
+    library_addr: &str,
+) -> Result<IcaInformation, ValenceSetupError> {
+    let not_registered = |reason: String| ValenceSetupError::IcaNotRegistered {
+        account: library_addr.to_string(),
+        reason,
+    };
+
+    match test_ctx.ica_state(NEUTRON_CHAIN_NAME, library_addr)? {
+        IcaState::Created(ica_info) if ica_info.address.is_empty() => Err(not_registered(format!(
+            "channel open on {} without a remote address",
+            ica_info.port_id
+        ))),
+        IcaState::Created(ica_info) if ica_info.controller_connection_id.is_empty() => Err(
+            not_registered("channel open without a controller connection".to_string()),
+        ),
+        IcaState::Created(ica_info) => Ok(ica_info),
+        state => Err(not_registered(format!(
+            "channel state is {state}, expected Created"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::mocks::test_context::MockTestContext;
+
+    use super::*;
+
+    const ICA_ADDR: &str = "neutron1ica";
+    const POLLING_INTERVAL: Duration = Duration::from_millis(10);
+
+    fn ica_info() -> IcaInformation {
+        IcaInformation {
+            address: "noble1remote".to_string(),
+            port_id: "icacontroller-neutron1ica".to_string(),
+            controller_connection_id: "connection-0".to_string(),
+        }
+    }
+
+    #[test]
+    fn registration_that_never_completes_times_out() {
+        let timeout = Duration::from_millis(50);
+        let mut polls = 0;
+
+        let err = wait_for_ica_channel_open(
+            ICA_ADDR,
+            || {
+                polls += 1;
+                Ok(IcaState::InProgress)
+            },
+            timeout,
+            POLLING_INTERVAL,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            &err,
+            ValenceSetupError::RegistrationTimeout { account, timeout: t }
+                if account == ICA_ADDR && *t == timeout
+        ));
+        assert!(err.is_transient());
+        // The state is polled until the timeout, not only once
+        assert!(polls > 1);
+    }
+
+    #[test]
+    fn returns_ica_once_channel_is_open() {
+        let mut states = vec![
+            IcaState::Created(ica_info()),
+            IcaState::InProgress,
+            IcaState::NotCreated,
+        ];
+
+        let info = wait_for_ica_channel_open(
+            ICA_ADDR,
+            || Ok(states.pop().unwrap()),
+            Duration::from_secs(10),
+            POLLING_INTERVAL,
+        )
+        .unwrap();
+
+        assert_eq!(info, ica_info());
+        assert!(states.is_empty());
+    }
+
+    #[test]
+    fn closed_channel_fails_registration() {
+        let err = wait_for_ica_channel_open(
+            ICA_ADDR,
+            || Ok(IcaState::Closed),
+            Duration::from_secs(10),
+            POLLING_INTERVAL,
+        )
+        .unwrap_err();
+
+        assert!(!err.is_transient());
+    }
+
+    #[test]
+    fn registered_ica_is_returned() {
+        let mut test_ctx = MockTestContext::new().with_ica_state(
+            NEUTRON_CHAIN_NAME,
+            ICA_ADDR,
+            IcaState::Created(ica_info()),
+        );
+
+        assert_eq!(
+            assert_ica_registered(&mut test_ctx, ICA_ADDR).unwrap(),
+            ica_info()
+        );
+    }
+
+    #[test]
+    fn unregistered_ica_is_reported() {
+        let mut test_ctx = MockTestContext::new()
+            .with_ica_state(NEUTRON_CHAIN_NAME, ICA_ADDR, IcaState::InProgress)
+            .with_ica_state(
+                NEUTRON_CHAIN_NAME,
+                "neutron1noremote",
+                IcaState::Created(IcaInformation {
+                    address: String::new(),
+                    ..ica_info()
+                }),
+            );
+
+        let err = assert_ica_registered(&mut test_ctx, ICA_ADDR).unwrap_err();
+        assert!(matches!(
+            &err,
+            ValenceSetupError::IcaNotRegistered { account, .. } if account == ICA_ADDR
+        ));
+        assert_eq!(
+            err.to_string(),
+            "ICA of neutron1ica not registered: channel state is InProgress, expected Created"
+        );
+
+        let err = assert_ica_registered(&mut test_ctx, "neutron1noremote").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ICA of neutron1noremote not registered: channel open on icacontroller-neutron1ica without a remote address"
+        );
+    }
+}
@@ -2,3 +2,4 @@ pub mod cctp_relayer_evm_evm;
 pub mod cctp_relayer_evm_noble;
 pub mod eureka_rly_evm_gaia_neutron;
 pub mod standard_bridge_relayer;
+pub mod test_context;
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::Coin;
+use valence_account_utils::ica::IcaState;
+
+use crate::utils::{error::ValenceSetupError, setup_context::SetupContext};
+
+/// Chain operation recorded by the `MockTestContext`
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockAction {
+    Instantiate {
+        chain_name: String,
+        key: String,
+        code_id: u64,
+        label: String,
+        admin: Option<String>,
+        msg: serde_json::Value,
+        // address returned for the contract
+        address: String,
+    },
+    ApproveLibrary {
+        chain_name: String,
+        key: String,
+        account: String,
+        library: String,
+    },
+}
+
+/// Deterministic `SetupContext` without any running chain: the code ids and transfer channels are
+/// registered up front, and the messages that would be sent are recorded in order instead.
+/// The n-th contract instantiated on a chain gets the `{chain_name}_contract{n}` address
+#[derive(Debug, Default)]
+pub struct MockTestContext {
+    code_ids: BTreeMap<(String, String), u64>,
+    transfer_channels: BTreeMap<(String, String), String>,
+    balances: BTreeMap<(String, String), Vec<Coin>>,
+    ica_states: BTreeMap<(String, String), IcaState>,
+    pub actions: Vec<MockAction>,
+}
+
+impl MockTestContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_code_id(mut self, chain_name: &str, contract: &str, code_id: u64) -> Self {
+        self.code_ids
+            .insert((chain_name.to_string(), contract.to_string()), code_id);
+        self
+    }
+
+    pub fn with_transfer_channel(mut self, src: &str, dest: &str, channel_id: &str) -> Self {
+        self.transfer_channels
+            .insert((src.to_string(), dest.to_string()), channel_id.to_string());
+        self
+    }
+
+    pub fn with_balances(mut self, chain_name: &str, address: &str, coins: Vec<Coin>) -> Self {
+        self.balances
+            .insert((chain_name.to_string(), address.to_string()), coins);
+        self
+    }
+
+    pub fn with_ica_state(mut self, chain_name: &str, address: &str, state: IcaState) -> Self {
+        self.ica_states
+            .insert((chain_name.to_string(), address.to_string()), state);
+        self
+    }
+
+    /// Returns the instantiate message recorded for `label`, if a contract was instantiated with it
+    pub fn instantiate_msg(&self, label: &str) -> Option<&serde_json::Value> {
+        self.actions.iter().find_map(|action| match action {
+            MockAction::Instantiate {
+                label: instantiated,
+                msg,
+                ..
+            } if instantiated == label => Some(msg),
+            _ => None,
+        })
+    }
+}
+
+impl SetupContext for MockTestContext {
+    fn code_id(&self, chain_name: &str, contract: &str) -> Result<u64, ValenceSetupError> {
+        self.code_ids
+            .get(&(chain_name.to_string(), contract.to_string()))
+            .copied()
+            .ok_or_else(|| ValenceSetupError::CodeIdNotFound {
+                chain: chain_name.to_string(),
+                contract: contract.to_string(),
+            })
+    }
+
+    fn transfer_channel(&self, src: &str, dest: &str) -> Result<String, ValenceSetupError> {
+        self.transfer_channels
+            .get(&(src.to_string(), dest.to_string()))
+            .cloned()
+            .ok_or_else(|| ValenceSetupError::ChannelNotFound {
+                src: src.to_string(),
+                dest: dest.to_string(),
+            })
+    }
+
+    fn instantiate_contract(
+        &mut self,
+        chain_name: &str,
+        key: &str,
+        code_id: u64,
+        msg: &str,
+        label: &str,
+        admin: Option<&str>,
+    ) -> Result<String, ValenceSetupError> {
+        let instantiated_on_chain = self
+            .actions
+            .iter()
+            .filter(|action| {
+                matches!(action, MockAction::Instantiate { chain_name: chain, .. } if chain == chain_name)
+            })
+            .count();
+        let address = format!("{chain_name}_contract{instantiated_on_chain}");
+
+        self.actions.push(MockAction::Instantiate {
+            chain_name: chain_name.to_string(),
+            key: key.to_string(),
+            code_id,
+            label: label.to_string(),
+            admin: admin.map(str::to_string),
+            msg: serde_json::from_str(msg)?,
+            address: address.clone(),
+        });
+        Ok(address)
+    }
+
+    fn approve_library(
+        &mut self,
+        chain_name: &str,
+        key: &str,
+        account: &str,
+        library: &str,
+    ) -> Result<(), ValenceSetupError> {
+        self.actions.push(MockAction::ApproveLibrary {
+            chain_name: chain_name.to_string(),
+            key: key.to_string(),
+            account: account.to_string(),
+            library: library.to_string(),
+        });
+        Ok(())
+    }
+
+    fn balances(
+        &mut self,
+        chain_name: &str,
+        address: &str,
+    ) -> Result<Vec<Coin>, ValenceSetupError> {
+        Ok(self
+            .balances
+            .get(&(chain_name.to_string(), address.to_string()))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn ica_state(
+        &mut self,
+        chain_name: &str,
+        address: &str,
+    ) -> Result<IcaState, ValenceSetupError> {
+        self.ica_states
+            .get(&(chain_name.to_string(), address.to_string()))
+            .cloned()
+            .ok_or_else(|| {
+                ValenceSetupError::Other(format!("No interchain account {address} on {chain_name}"))
+            })
+    }
+}
@@ -1,7 +1,21 @@
+use std::{collections::BTreeMap, path::Path};
+
+use error::{get_code_id, ValenceSetupError};
+use localic_utils::{
+    utils::test_context::TestContext, JUNO_CHAIN_NAME, NEUTRON_CHAIN_NAME, OSMOSIS_CHAIN_NAME,
+};
+use sha2::{Digest, Sha256};
+
 pub mod astroport;
 pub mod authorization;
 pub mod base_account;
+pub mod batch;
+pub mod cctp;
+pub mod conservation;
+pub mod denom_alias;
+pub mod error;
 pub mod ethereum;
+pub mod health;
 pub mod hyperlane;
 pub mod ibc;
 pub mod icq;
@@ -13,7 +27,10 @@ pub mod persistence;
 pub mod polytone;
 pub mod processor;
 pub mod relayer;
+pub mod setup_context;
+pub mod setup_report;
 pub mod solidity_contracts;
+pub mod teardown;
 pub mod vault;
 pub mod worker;
 
@@ -70,3 +87,371 @@ pub const ETHEREUM_HYPERLANE_DOMAIN: u32 = 1;
 pub const HYPERLANE_RELAYER_NEUTRON_ADDRESS: &str =
     "neutron14flvw0x8fstzly79tacgsulxvkpv858qdafme5";
 pub const HYPERLANE_RELAYER_CONTAINER_NAME: &str = "hyperlane-relayer";
+
+/// Returns the local code id cache file used for a specific chain
+pub fn local_code_id_cache_path(chain_name: &str) -> Result<&'static str, ValenceSetupError> {
+    match chain_name {
+        NEUTRON_CHAIN_NAME => Ok(LOCAL_CODE_ID_CACHE_PATH_NEUTRON),
+        JUNO_CHAIN_NAME => Ok(LOCAL_CODE_ID_CACHE_PATH_JUNO),
+        OSMOSIS_CHAIN_NAME => Ok(LOCAL_CODE_ID_CACHE_PATH_OSMOSIS),
+        PERSISTENCE_CHAIN_NAME => Ok(LOCAL_CODE_ID_CACHE_PATH_PERSISTENCE),
+        _ => Err(ValenceSetupError::UnknownChain(chain_name.to_string())),
+    }
+}
+
+/// Copies the given contracts from the artifacts into `cache_path` and uploads them to the chain,
+/// reusing the code ids of contracts that were already uploaded.
+/// Returns the code id of each contract by name
+pub fn upload_contracts(
+    test_ctx: &mut TestContext,
+    chain_name: &str,
+    contract_names: &[&str],
+    cache_path: &str,
+) -> Result<BTreeMap<String, u64>, ValenceSetupError> {
+    copy_artifacts(VALENCE_ARTIFACTS_PATH, contract_names, cache_path)?;
+
+    let code_id_cache_path = local_code_id_cache_path(chain_name)?;
+    invalidate_stale_code_ids(cache_path, code_id_cache_path)?;
+
+    let mut uploader = test_ctx.build_tx_upload_contracts();
+    uploader
+        .with_chain_name(chain_name)
+        .send_with_local_cache(cache_path, code_id_cache_path)
+        .map_err(|e| ValenceSetupError::Upload {
+            chain: chain_name.to_string(),
+            reason: e.to_string(),
+        })?;
+
+    collect_code_ids(chain_name, contract_names, |contract| {
+        get_code_id(test_ctx, chain_name, contract).ok()
+    })
+}
+
+/// Returns the code id of each contract by name, as found by `lookup`,
+/// failing on the first contract that has no code id on `chain_name`
+fn collect_code_ids(
+    chain_name: &str,
+    contract_names: &[&str],
+    mut lookup: impl FnMut(&str) -> Option<u64>,
+) -> Result<BTreeMap<String, u64>, ValenceSetupError> {
+    let mut code_ids = BTreeMap::new();
+    for contract in contract_names {
+        let code_id = lookup(contract).ok_or_else(|| ValenceSetupError::CodeIdNotFound {
+            chain: chain_name.to_string(),
+            contract: contract.to_string(),
+        })?;
+        code_ids.insert(contract.to_string(), code_id);
+    }
+
+    Ok(code_ids)
+}
+
+/// Copies the artifacts of the given contracts from `artifacts_path` into `cache_path`, skipping the ones
+/// already cached with the same content. Contracts listed more than once are only copied once, and every
+/// artifact is checked to exist before copying anything, so that all the missing ones are reported at once.
+/// Returns the names of the contracts copied
+pub fn copy_artifacts(
+    artifacts_path: &str,
+    contract_names: &[&str],
+    cache_path: &str,
+) -> Result<Vec<String>, ValenceSetupError> {
+    let mut contracts: Vec<&str> = vec![];
+    for contract in contract_names {
+        if !contracts.contains(contract) {
+            contracts.push(contract);
+        }
+    }
+
+    let missing: Vec<String> = contracts
+        .iter()
+        .map(|contract| Path::new(artifacts_path).join(format!("{contract}.wasm")))
+        .filter(|src| !src.exists())
+        .map(|src| src.display().to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(ValenceSetupError::MissingArtifact(missing));
+    }
+
+    let local_contracts_path = Path::new(cache_path);
+    if !local_contracts_path.exists() {
+        std::fs::create_dir_all(local_contracts_path)?;
+    }
+
+    let mut copied = vec![];
+    for contract in contracts {
+        let contract_name = format!("{contract}.wasm");
+        let src = Path::new(artifacts_path).join(&contract_name);
+        let dest = local_contracts_path.join(&contract_name);
+
+        // Skip contracts that are already cached with the same content
+        if dest.exists() && std::fs::read(&dest)? == std::fs::read(&src)? {
+            continue;
+        }
+        std::fs::copy(src, dest)?;
+        copied.push(contract.to_string());
+    }
+
+    Ok(copied)
+}
+
+/// Removes from the local code id cache the contracts of `artifacts_path` whose wasm changed since their code id was cached,
+/// so that `send_with_local_cache` uploads them again instead of reusing the code id of the old bytecode.
+/// The checksums of the cached contracts are kept next to the cache, e.g. `code_id_cache_neutron.checksums.json`.
+/// Returns the names of the contracts removed from the cache
+pub fn invalidate_stale_code_ids(
+    artifacts_path: &str,
+    code_id_cache_path: &str,
+) -> Result<Vec<String>, ValenceSetupError> {
+    let code_id_cache_path = Path::new(code_id_cache_path);
+    let checksums_path = code_id_cache_path.with_extension("checksums.json");
+
+    let mut code_ids: serde_json::Map<String, serde_json::Value> =
+        read_json_or_default(code_id_cache_path)?;
+    let mut checksums: BTreeMap<String, String> = read_json_or_default(&checksums_path)?;
+
+    let mut invalidated = vec![];
+    for entry in std::fs::read_dir(artifacts_path)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "wasm") {
+            continue;
+        }
+        let Some(contract) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        let checksum = hex::encode(Sha256::digest(std::fs::read(&path)?));
+        if checksums.get(contract) != Some(&checksum) {
+            // Code ids cached before their checksum was recorded are also considered stale
+            if code_ids.remove(contract).is_some() {
+                invalidated.push(contract.to_string());
+            }
+            checksums.insert(contract.to_string(), checksum);
+        }
+    }
+
+    if !invalidated.is_empty() {
+        std::fs::write(code_id_cache_path, serde_json::to_string_pretty(&code_ids)?)?;
+    }
+    std::fs::write(checksums_path, serde_json::to_string_pretty(&checksums)?)?;
+
+    invalidated.sort();
+    Ok(invalidated)
+}
+
+fn read_json_or_default<T: serde::de::DeserializeOwned + Default>(
+    path: &Path,
+) -> Result<T, ValenceSetupError> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde_json::json;
+
+    use super::*;
+
+    // Directory with the fixture wasm files and the code id cache of a test
+    fn fixture_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("valence_e2e_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("artifacts")).unwrap();
+        dir
+    }
+
+    fn cached_code_ids(cache_path: &Path) -> serde_json::Value {
+        serde_json::from_slice(&std::fs::read(cache_path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn changed_wasm_is_uploaded_again() {
+        let dir = fixture_dir("changed_wasm");
+        let artifacts = dir.join("artifacts");
+        let cache_path = dir.join("code_id_cache.json");
+        std::fs::write(artifacts.join("forwarder.wasm"), b"forwarder v1").unwrap();
+        std::fs::write(artifacts.join("splitter.wasm"), b"splitter v1").unwrap();
+
+        // First upload: nothing is cached yet, so the checksums are recorded along with the code ids
+        let invalidated =
+            invalidate_stale_code_ids(artifacts.to_str().unwrap(), cache_path.to_str().unwrap())
+                .unwrap();
+        assert!(invalidated.is_empty());
+        std::fs::write(
+            &cache_path,
+            json!({ "forwarder": 1, "splitter": 2 }).to_string(),
+        )
+        .unwrap();
+
+        // Unchanged artifacts reuse their cached code id
+        let invalidated =
+            invalidate_stale_code_ids(artifacts.to_str().unwrap(), cache_path.to_str().unwrap())
+                .unwrap();
+        assert!(invalidated.is_empty());
+        assert_eq!(
+            cached_code_ids(&cache_path),
+            json!({ "forwarder": 1, "splitter": 2 })
+        );
+
+        // A rebuilt artifact is removed from the cache, so that it is uploaded again
+        std::fs::write(artifacts.join("forwarder.wasm"), b"forwarder v2").unwrap();
+        let invalidated =
+            invalidate_stale_code_ids(artifacts.to_str().unwrap(), cache_path.to_str().unwrap())
+                .unwrap();
+        assert_eq!(invalidated, vec!["forwarder".to_string()]);
+        assert_eq!(cached_code_ids(&cache_path), json!({ "splitter": 2 }));
+
+        // Once uploaded again, the new code id is reused
+        std::fs::write(
+            &cache_path,
+            json!({ "forwarder": 3, "splitter": 2 }).to_string(),
+        )
+        .unwrap();
+        let invalidated =
+            invalidate_stale_code_ids(artifacts.to_str().unwrap(), cache_path.to_str().unwrap())
+                .unwrap();
+        assert!(invalidated.is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn code_ids_cached_without_checksum_are_uploaded_again() {
+        let dir = fixture_dir("legacy_cache");
+        let artifacts = dir.join("artifacts");
+        let cache_path = dir.join("code_id_cache.json");
+        std::fs::write(artifacts.join("forwarder.wasm"), b"forwarder").unwrap();
+        std::fs::write(artifacts.join("README.md"), b"not a contract").unwrap();
+        std::fs::write(
+            &cache_path,
+            json!({ "forwarder": 1, "unknown": 2 }).to_string(),
+        )
+        .unwrap();
+
+        let invalidated =
+            invalidate_stale_code_ids(artifacts.to_str().unwrap(), cache_path.to_str().unwrap())
+                .unwrap();
+        assert_eq!(invalidated, vec!["forwarder".to_string()]);
+        // Contracts that are not in the artifacts are left untouched
+        assert_eq!(cached_code_ids(&cache_path), json!({ "unknown": 2 }));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_artifacts_are_all_reported() {
+        let dir = fixture_dir("missing_artifacts");
+        let artifacts = dir.join("artifacts");
+        let cache = dir.join("cache");
+        std::fs::write(artifacts.join("forwarder.wasm"), b"forwarder").unwrap();
+
+        let err = copy_artifacts(
+            artifacts.to_str().unwrap(),
+            &["forwarder", "splitter", "lper"],
+            cache.to_str().unwrap(),
+        )
+        .unwrap_err();
+
+        let missing = |name: &str| artifacts.join(name).display().to_string();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Missing contract artifacts: {}, {}",
+                missing("splitter.wasm"),
+                missing("lper.wasm")
+            )
+        );
+        // Nothing is copied as long as an artifact is missing
+        assert!(!cache.exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn duplicate_contracts_are_copied_once() {
+        let dir = fixture_dir("duplicate_artifacts");
+        let artifacts = dir.join("artifacts");
+        let cache = dir.join("cache");
+        std::fs::write(artifacts.join("forwarder.wasm"), b"forwarder").unwrap();
+        std::fs::write(artifacts.join("splitter.wasm"), b"splitter").unwrap();
+
+        let copied = copy_artifacts(
+            artifacts.to_str().unwrap(),
+            &["forwarder", "splitter", "forwarder"],
+            cache.to_str().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            copied,
+            vec!["forwarder".to_string(), "splitter".to_string()]
+        );
+        assert_eq!(
+            std::fs::read(cache.join("forwarder.wasm")).unwrap(),
+            b"forwarder"
+        );
+
+        // Cached artifacts with the same content are not copied again
+        let copied = copy_artifacts(
+            artifacts.to_str().unwrap(),
+            &["forwarder"],
+            cache.to_str().unwrap(),
+        )
+        .unwrap();
+        assert!(copied.is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn uploaded_fixtures_get_their_code_ids() {
+        let dir = fixture_dir("uploaded_fixtures");
+        let artifacts = dir.join("artifacts");
+        let cache = dir.join("cache");
+        std::fs::write(artifacts.join("forwarder.wasm"), b"forwarder").unwrap();
+        std::fs::write(artifacts.join("splitter.wasm"), b"splitter").unwrap();
+        let contracts = ["forwarder", "splitter"];
+
+        copy_artifacts(
+            artifacts.to_str().unwrap(),
+            &contracts,
+            cache.to_str().unwrap(),
+        )
+        .unwrap();
+
+        // The chain assigns a code id to each wasm file of the cache
+        let uploaded: BTreeMap<String, u64> = std::fs::read_dir(&cache)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .enumerate()
+            .map(|(i, path)| {
+                let stem = path.file_stem().unwrap().to_str().unwrap().to_string();
+                (stem, i as u64 + 1)
+            })
+            .collect();
+
+        let code_ids = collect_code_ids(NEUTRON_CHAIN_NAME, &contracts, |contract| {
+            uploaded.get(contract).copied()
+        })
+        .unwrap();
+        assert_eq!(
+            code_ids.keys().collect::<Vec<_>>(),
+            vec!["forwarder", "splitter"]
+        );
+        assert!(code_ids.values().all(|code_id| *code_id > 0));
+
+        // A contract the chain knows nothing about is reported
+        let err = collect_code_ids(NEUTRON_CHAIN_NAME, &["forwarder", "lper"], |contract| {
+            uploaded.get(contract).copied()
+        })
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ValenceSetupError::CodeIdNotFound { ref contract, .. } if contract == "lper"
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
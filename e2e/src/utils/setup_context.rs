@@ -0,0 +1,163 @@
+use cosmwasm_std::Coin;
+use localic_std::modules::{
+    bank::get_balance,
+    cosmwasm::{contract_instantiate, contract_query},
+};
+use localic_utils::utils::test_context::TestContext;
+use valence_account_utils::ica::{IcaState, QueryMsg as IcaQueryMsg};
+
+use crate::utils::{
+    base_account::{approve_library, wait_for_library_approval},
+    error::{get_transfer_channel, ValenceSetupError},
+};
+
+/// Label and migrate admin of the libraries instantiated by the setup helpers. Without a label prefix
+/// the libraries are labelled with their name only, and without an admin they can't be migrated
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryDeployment {
+    // prepended to the name of every library, e.g. to tell apart the libraries of different programs
+    pub label_prefix: Option<String>,
+    // address allowed to migrate the libraries
+    pub admin: Option<String>,
+}
+
+impl LibraryDeployment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_label_prefix(mut self, label_prefix: &str) -> Self {
+        self.label_prefix = Some(label_prefix.to_string());
+        self
+    }
+
+    pub fn with_admin(mut self, admin: &str) -> Self {
+        self.admin = Some(admin.to_string());
+        self
+    }
+
+    /// Label of the library `name`, prefixed with the label prefix if any
+    pub fn label(&self, name: &str) -> String {
+        match &self.label_prefix {
+            Some(label_prefix) => format!("{label_prefix}-{name}"),
+            None => name.to_string(),
+        }
+    }
+}
+
+/// Chain operations of the setup helpers, so that they can run against a local-ic testnet through its
+/// `TestContext`, or against the `MockTestContext` that records them without any running chain
+pub trait SetupContext {
+    /// Returns the code id of `contract` uploaded to `chain_name`
+    fn code_id(&self, chain_name: &str, contract: &str) -> Result<u64, ValenceSetupError>;
+
+    /// Returns the transfer channel id from `src` to `dest`
+    fn transfer_channel(&self, src: &str, dest: &str) -> Result<String, ValenceSetupError>;
+
+    /// Instantiates `code_id` on `chain_name` with the JSON `msg`, returning the address of the contract.
+    /// The contract can be migrated by `admin`, if set
+    fn instantiate_contract(
+        &mut self,
+        chain_name: &str,
+        key: &str,
+        code_id: u64,
+        msg: &str,
+        label: &str,
+        admin: Option<&str>,
+    ) -> Result<String, ValenceSetupError>;
+
+    /// Approves `library` on `account`, returning once the approval is visible
+    fn approve_library(
+        &mut self,
+        chain_name: &str,
+        key: &str,
+        account: &str,
+        library: &str,
+    ) -> Result<(), ValenceSetupError>;
+
+    /// Returns every balance held by `address` on `chain_name`, none if the account doesn't exist
+    fn balances(&mut self, chain_name: &str, address: &str)
+        -> Result<Vec<Coin>, ValenceSetupError>;
+
+    /// Returns the state of the ICA held by the interchain account `address` on `chain_name`
+    fn ica_state(&mut self, chain_name: &str, address: &str)
+        -> Result<IcaState, ValenceSetupError>;
+}
+
+impl SetupContext for TestContext {
+    fn code_id(&self, chain_name: &str, contract: &str) -> Result<u64, ValenceSetupError> {
+        self.get_chain(chain_name)
+            .contract_codes
+            .get(contract)
+            .copied()
+            .ok_or_else(|| ValenceSetupError::CodeIdNotFound {
+                chain: chain_name.to_string(),
+                contract: contract.to_string(),
+            })
+    }
+
+    fn transfer_channel(&self, src: &str, dest: &str) -> Result<String, ValenceSetupError> {
+        get_transfer_channel(self, src, dest)
+    }
+
+    fn instantiate_contract(
+        &mut self,
+        chain_name: &str,
+        key: &str,
+        code_id: u64,
+        msg: &str,
+        label: &str,
+        admin: Option<&str>,
+    ) -> Result<String, ValenceSetupError> {
+        let contract = contract_instantiate(
+            self.get_request_builder().get_request_builder(chain_name),
+            key,
+            code_id,
+            msg,
+            label,
+            admin.map(str::to_string),
+            "",
+        )
+        .map_err(|e| ValenceSetupError::Instantiation {
+            label: label.to_string(),
+            reason: e.to_string(),
+        })?;
+
+        Ok(contract.address)
+    }
+
+    fn approve_library(
+        &mut self,
+        chain_name: &str,
+        key: &str,
+        account: &str,
+        library: &str,
+    ) -> Result<(), ValenceSetupError> {
+        approve_library(self, chain_name, key, account, library.to_string(), None);
+        wait_for_library_approval(self, chain_name, account, library)
+    }
+
+    fn balances(
+        &mut self,
+        chain_name: &str,
+        address: &str,
+    ) -> Result<Vec<Coin>, ValenceSetupError> {
+        Ok(get_balance(
+            self.get_request_builder().get_request_builder(chain_name),
+            address,
+        ))
+    }
+
+    fn ica_state(
+        &mut self,
+        chain_name: &str,
+        address: &str,
+    ) -> Result<IcaState, ValenceSetupError> {
+        let response = contract_query(
+            self.get_request_builder().get_request_builder(chain_name),
+            address,
+            &serde_json::to_string(&IcaQueryMsg::IcaState {})?,
+        );
+        Ok(serde_json::from_value(response["data"].clone())?)
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    error::Error,
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+/// Outcome of a step of a setup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum StepStatus {
+    Completed,
+    // completed by a previous attempt of the setup, whose output was reused
+    Resumed,
+    Failed,
+}
+
+/// Timing and outcome of a step of a setup
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SetupStep {
+    pub name: String,
+    pub status: StepStatus,
+    pub duration: Duration,
+    // what the step returned (e.g. the address of the library it set up), reused when resuming
+    pub output: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+/// Steps run by a setup, in the order they were run. A failed setup reports the steps completed
+/// before the failure, so that a retry resuming from the report only runs the remaining ones
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SetupReport {
+    pub steps: Vec<SetupStep>,
+    // report of the previous attempt of the setup, whose completed steps are not run again
+    #[serde(skip)]
+    previous: Option<Box<SetupReport>>,
+}
+
+impl SetupReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report of a retry of the setup of `previous`
+    pub fn resuming(previous: SetupReport) -> Self {
+        SetupReport {
+            steps: vec![],
+            previous: Some(Box::new(previous)),
+        }
+    }
+
+    /// Runs the step `name` and records how long it took and whether it failed. If it was completed
+    /// by the previous attempt of the setup, its output is returned without running it again
+    pub fn step<T, E>(
+        &mut self,
+        name: &str,
+        run: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, Box<dyn Error>>
+    where
+        T: Serialize + DeserializeOwned,
+        E: Into<Box<dyn Error>>,
+    {
+        if let Some(output) = self
+            .previous
+            .as_ref()
+            .and_then(|previous| previous.completed_output(name))
+        {
+            info!("Setup step {name} was completed by a previous attempt, resuming after it");
+            let resumed = serde_json::from_value(output.clone())?;
+            self.steps.push(SetupStep {
+                name: name.to_string(),
+                status: StepStatus::Resumed,
+                duration: Duration::ZERO,
+                output: Some(output.clone()),
+                error: None,
+            });
+            return Ok(resumed);
+        }
+
+        let start = Instant::now();
+        let result = run().map_err(Into::into);
+        let duration = start.elapsed();
+
+        match result {
+            Ok(output) => {
+                info!("Setup step {name} completed in {duration:?}");
+                self.steps.push(SetupStep {
+                    name: name.to_string(),
+                    status: StepStatus::Completed,
+                    duration,
+                    output: Some(serde_json::to_value(&output)?),
+                    error: None,
+                });
+                Ok(output)
+            }
+            Err(err) => {
+                warn!("Setup step {name} failed after {duration:?}: {err}");
+                self.steps.push(SetupStep {
+                    name: name.to_string(),
+                    status: StepStatus::Failed,
+                    duration,
+                    output: None,
+                    error: Some(err.to_string()),
+                });
+                Err(err)
+            }
+        }
+    }
+
+    /// Names of the steps completed by this attempt or a previous one
+    pub fn completed_steps(&self) -> Vec<&str> {
+        self.steps
+            .iter()
+            .filter(|step| step.status != StepStatus::Failed)
+            .map(|step| step.name.as_str())
+            .collect()
+    }
+
+    pub fn failed_step(&self) -> Option<&SetupStep> {
+        self.steps
+            .iter()
+            .find(|step| step.status == StepStatus::Failed)
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.steps.iter().map(|step| step.duration).sum()
+    }
+
+    // output of the step `name` if it was completed, by this attempt or a previous one
+    fn completed_output(&self, name: &str) -> Option<&serde_json::Value> {
+        self.steps
+            .iter()
+            .find(|step| step.name == name && step.status != StepStatus::Failed)
+            .and_then(|step| step.output.as_ref())
+    }
+}
+
+/// Error of a setup that failed partway, along with the report of the steps run before the failure
+#[derive(Error, Debug)]
+#[error("Setup failed after completing [{}]: {error}", .report.completed_steps().join(", "))]
+pub struct SetupFailure {
+    pub report: SetupReport,
+    #[source]
+    pub error: Box<dyn Error>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn steps_are_recorded_in_order_with_their_status() {
+        let mut report = SetupReport::new();
+
+        let lib = report
+            .step("astro_lper", || {
+                Ok::<_, Box<dyn Error>>("neutron1lper".to_string())
+            })
+            .unwrap();
+        assert_eq!(lib, "neutron1lper");
+        let err = report
+            .step("astro_lwer", || {
+                Err::<String, _>("instantiation failed".to_string())
+            })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "instantiation failed");
+
+        let steps: Vec<_> = report
+            .steps
+            .iter()
+            .map(|step| (step.name.as_str(), step.status))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                ("astro_lper", StepStatus::Completed),
+                ("astro_lwer", StepStatus::Failed)
+            ]
+        );
+        assert_eq!(report.completed_steps(), vec!["astro_lper"]);
+        assert_eq!(
+            report.failed_step().unwrap().error.as_deref(),
+            Some("instantiation failed")
+        );
+    }
+
+    #[test]
+    fn retries_resume_after_the_completed_steps() {
+        let mut report = SetupReport::new();
+        report
+            .step("astro_lper", || {
+                Ok::<_, Box<dyn Error>>("neutron1lper".to_string())
+            })
+            .unwrap();
+        report
+            .step("astro_lwer", || Err::<String, _>("timeout".to_string()))
+            .unwrap_err();
+
+        let mut retry = SetupReport::resuming(report);
+        let runs = Cell::new(0);
+        let lper = retry
+            .step("astro_lper", || {
+                runs.set(runs.get() + 1);
+                Ok::<_, Box<dyn Error>>("neutron1other".to_string())
+            })
+            .unwrap();
+        let lwer = retry
+            .step("astro_lwer", || {
+                runs.set(runs.get() + 1);
+                Ok::<_, Box<dyn Error>>("neutron1lwer".to_string())
+            })
+            .unwrap();
+
+        // Only the failed step is run again, the completed one returns its previous output
+        assert_eq!(runs.get(), 1);
+        assert_eq!(
+            (lper.as_str(), lwer.as_str()),
+            ("neutron1lper", "neutron1lwer")
+        );
+        assert_eq!(retry.steps[0].status, StepStatus::Resumed);
+        assert_eq!(retry.steps[1].status, StepStatus::Completed);
+        assert!(retry.failed_step().is_none());
+    }
+}
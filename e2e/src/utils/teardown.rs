@@ -0,0 +1,371 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use cosmwasm_std::{AnyMsg, BankMsg, Binary, Coin, CosmosMsg};
+use localic_std::modules::{bank::get_balance, cosmwasm::contract_execute};
+use localic_utils::utils::test_context::TestContext;
+use log::info;
+use osmosis_std::types::{
+    cosmos::base::v1beta1::Coin as ProtoCoin, ibc::applications::transfer::v1::MsgTransfer,
+};
+use serde::Serialize;
+
+use crate::utils::{
+    denom_alias::display_coins, error::ValenceSetupError, health::LibraryComponent, GAS_FLAGS,
+};
+
+// timeout of the transfers returning the ICA balances
+const ICA_RETURN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Accounts of a program drained by `teardown`, and the library approvals it removes
+#[derive(Debug, Clone, Default)]
+pub struct TeardownComponents {
+    // base accounts keyed by role, whose balances are sent to the admin
+    pub accounts: BTreeMap<String, String>,
+    // Valence interchain accounts keyed by role, whose remote balances are transferred back to the admin
+    pub interchain_accounts: BTreeMap<String, IcaReturnRoute>,
+    // approvals removed once the accounts are drained
+    pub libraries: Vec<LibraryComponent>,
+}
+
+/// Route back to the admin of the balances held by an ICA on the remote chain
+#[derive(Debug, Clone)]
+pub struct IcaReturnRoute {
+    // Valence interchain account controlling the ICA
+    pub library_account: String,
+    // address of the ICA on the remote chain
+    pub remote_addr: String,
+    // transfer channel from the remote chain to the chain of the admin
+    pub channel_id: String,
+}
+
+impl TeardownComponents {
+    pub fn with_account(mut self, role: &str, address: &str) -> Self {
+        self.accounts.insert(role.to_string(), address.to_string());
+        self
+    }
+
+    pub fn with_interchain_account(
+        mut self,
+        role: &str,
+        library_account: &str,
+        remote_addr: &str,
+        channel_id: &str,
+    ) -> Self {
+        self.interchain_accounts.insert(
+            role.to_string(),
+            IcaReturnRoute {
+                library_account: library_account.to_string(),
+                remote_addr: remote_addr.to_string(),
+                channel_id: channel_id.to_string(),
+            },
+        );
+        self
+    }
+
+    pub fn with_library(mut self, name: &str, address: &str, input_account: &str) -> Self {
+        self.libraries.push(LibraryComponent {
+            name: name.to_string(),
+            address: address.to_string(),
+            input_account: input_account.to_string(),
+        });
+        self
+    }
+}
+
+/// Funds moved back to the admin from an account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SweptAccount {
+    pub role: String,
+    pub address: String,
+    pub coins: Vec<Coin>,
+}
+
+/// What a teardown moved and removed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TeardownReport {
+    // base accounts emptied into the admin
+    pub swept: Vec<SweptAccount>,
+    // ICAs whose remote balances were transferred back to the admin
+    pub returned: Vec<SweptAccount>,
+    // libraries that are no longer approved on their input account
+    pub removed_approvals: Vec<String>,
+}
+
+/// Bank send of the whole (non zero) balance of an account to the admin,
+/// or None if the account holds nothing
+pub fn sweep_msg(balances: &[Coin], admin: &str) -> Option<CosmosMsg> {
+    let amount = non_zero(balances);
+    if amount.is_empty() {
+        return None;
+    }
+
+    Some(CosmosMsg::Bank(BankMsg::Send {
+        to_address: admin.to_string(),
+        amount,
+    }))
+}
+
+/// IBC transfers of the (non zero) remote balances of an ICA back to the admin, one per denom
+pub fn ica_return_msgs(
+    route: &IcaReturnRoute,
+    balances: &[Coin],
+    admin: &str,
+    timeout_timestamp: u64,
+) -> Vec<AnyMsg> {
+    non_zero(balances)
+        .into_iter()
+        .map(|coin| {
+            let msg_transfer = MsgTransfer {
+                source_port: "transfer".to_string(),
+                source_channel: route.channel_id.clone(),
+                token: Some(ProtoCoin {
+                    denom: coin.denom,
+                    amount: coin.amount.to_string(),
+                }),
+                sender: route.remote_addr.clone(),
+                receiver: admin.to_string(),
+                timeout_height: None,
+                timeout_timestamp,
+                memo: String::new(),
+            };
+            AnyMsg {
+                type_url: msg_transfer.to_any().type_url,
+                value: Binary::from(msg_transfer.to_proto_bytes()),
+            }
+        })
+        .collect()
+}
+
+/// Drains every account of the program into `admin`: base account balances are sent to it and
+/// ICA balances are transferred back to it over IBC. Accounts holding nothing are skipped. If
+/// `remove_approvals` is set, the libraries are then removed from the approved libraries of their
+/// input account, so that the program can't move funds anymore. `key` must be the owner of the accounts
+pub fn teardown(
+    test_ctx: &mut TestContext,
+    chain_name: &str,
+    remote_chain_name: &str,
+    key: &str,
+    components: &TeardownComponents,
+    admin: &str,
+    remove_approvals: bool,
+) -> Result<TeardownReport, ValenceSetupError> {
+    let mut report = TeardownReport::default();
+
+    for (role, address) in &components.accounts {
+        let balances = get_balance(
+            test_ctx
+                .get_request_builder()
+                .get_request_builder(chain_name),
+            address,
+        );
+        let Some(msg) = sweep_msg(&balances, admin) else {
+            info!("Account {role} ({address}) holds nothing, skipping it");
+            continue;
+        };
+
+        execute(
+            test_ctx,
+            chain_name,
+            address,
+            key,
+            &valence_account_utils::msg::ExecuteMsg::ExecuteMsg { msgs: vec![msg] },
+        )?;
+        info!(
+            "Swept {} from account {role} ({address}) to {admin}",
+            display_coins(&balances)
+        );
+        report.swept.push(SweptAccount {
+            role: role.clone(),
+            address: address.clone(),
+            coins: non_zero(&balances),
+        });
+    }
+
+    let timeout_timestamp = (SystemTime::now() + ICA_RETURN_TIMEOUT)
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| ValenceSetupError::Other(e.to_string()))?
+        .as_nanos() as u64;
+
+    for (role, route) in &components.interchain_accounts {
+        let balances = get_balance(
+            test_ctx
+                .get_request_builder()
+                .get_request_builder(remote_chain_name),
+            &route.remote_addr,
+        );
+        let msgs = ica_return_msgs(route, &balances, admin, timeout_timestamp);
+        if msgs.is_empty() {
+            info!(
+                "ICA {role} ({}) holds nothing, skipping it",
+                route.remote_addr
+            );
+            continue;
+        }
+
+        execute(
+            test_ctx,
+            chain_name,
+            &route.library_account,
+            key,
+            &valence_account_utils::ica::ExecuteMsg::ExecuteIcaMsg { msgs },
+        )?;
+        info!(
+            "Returning {} from ICA {role} ({}) to {admin}",
+            display_coins(&balances),
+            route.remote_addr
+        );
+        report.returned.push(SweptAccount {
+            role: role.clone(),
+            address: route.remote_addr.clone(),
+            coins: non_zero(&balances),
+        });
+    }
+
+    if remove_approvals {
+        for library in &components.libraries {
+            // base and interchain accounts share the same message
+            execute(
+                test_ctx,
+                chain_name,
+                &library.input_account,
+                key,
+                &valence_account_utils::msg::ExecuteMsg::RemoveLibrary {
+                    library: library.address.clone(),
+                },
+            )?;
+            info!(
+                "Removed library {} ({}) from account {}",
+                library.name, library.address, library.input_account
+            );
+            report.removed_approvals.push(library.address.clone());
+        }
+    }
+
+    Ok(report)
+}
+
+fn non_zero(balances: &[Coin]) -> Vec<Coin> {
+    balances
+        .iter()
+        .filter(|coin| !coin.amount.is_zero())
+        .cloned()
+        .collect()
+}
+
+fn execute(
+    test_ctx: &mut TestContext,
+    chain_name: &str,
+    contract: &str,
+    key: &str,
+    msg: &impl Serialize,
+) -> Result<(), ValenceSetupError> {
+    contract_execute(
+        test_ctx
+            .get_request_builder()
+            .get_request_builder(chain_name),
+        contract,
+        key,
+        &serde_json::to_string(msg)?,
+        GAS_FLAGS,
+    )
+    .map_err(|e| ValenceSetupError::Other(e.to_string()))?;
+    std::thread::sleep(Duration::from_secs(2));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::coin;
+
+    use super::*;
+
+    const ADMIN: &str = "neutron1admin";
+
+    // applies the sweeps of the accounts to their balances, returning the balances after the teardown
+    fn sweep_all(mut ledger: BTreeMap<String, Vec<Coin>>) -> BTreeMap<String, Vec<Coin>> {
+        let accounts: Vec<String> = ledger.keys().filter(|a| *a != ADMIN).cloned().collect();
+        for account in accounts {
+            let Some(CosmosMsg::Bank(BankMsg::Send { to_address, amount })) =
+                sweep_msg(&ledger[&account], ADMIN)
+            else {
+                continue;
+            };
+            for sent in amount {
+                let from = ledger.get_mut(&account).unwrap();
+                from.iter_mut()
+                    .find(|c| c.denom == sent.denom)
+                    .unwrap()
+                    .amount -= sent.amount;
+                from.retain(|c| !c.amount.is_zero());
+
+                let to = ledger.entry(to_address.clone()).or_default();
+                match to.iter_mut().find(|c| c.denom == sent.denom) {
+                    Some(c) => c.amount += sent.amount,
+                    None => to.push(sent),
+                }
+            }
+        }
+        ledger
+    }
+
+    #[test]
+    fn teardown_empties_funded_accounts_into_admin() {
+        let ledger = BTreeMap::from([
+            (
+                "neutron1deposit".to_string(),
+                vec![coin(1_000, "uusdc"), coin(50, "untrn")],
+            ),
+            ("neutron1withdraw".to_string(), vec![coin(300, "uusdc")]),
+            // accounts holding nothing are skipped
+            ("neutron1position".to_string(), vec![]),
+        ]);
+        assert_eq!(sweep_msg(&ledger["neutron1position"], ADMIN), None);
+        assert_eq!(sweep_msg(&[coin(0, "uusdc")], ADMIN), None);
+
+        let ledger = sweep_all(ledger);
+
+        assert!(ledger["neutron1deposit"].is_empty());
+        assert!(ledger["neutron1withdraw"].is_empty());
+        assert_eq!(ledger[ADMIN], vec![coin(1_300, "uusdc"), coin(50, "untrn")]);
+    }
+
+    #[test]
+    fn ica_balances_are_transferred_back_per_denom() {
+        let route = IcaReturnRoute {
+            library_account: "neutron1ica".to_string(),
+            remote_addr: "noble1ica".to_string(),
+            channel_id: "channel-1".to_string(),
+        };
+
+        let msgs = ica_return_msgs(
+            &route,
+            &[coin(700, "uusdc"), coin(0, "ustake")],
+            ADMIN,
+            1_000,
+        );
+
+        let expected = MsgTransfer {
+            source_port: "transfer".to_string(),
+            source_channel: "channel-1".to_string(),
+            token: Some(ProtoCoin {
+                denom: "uusdc".to_string(),
+                amount: "700".to_string(),
+            }),
+            sender: "noble1ica".to_string(),
+            receiver: ADMIN.to_string(),
+            timeout_height: None,
+            timeout_timestamp: 1_000,
+            memo: String::new(),
+        };
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(
+            msgs[0].type_url,
+            "/ibc.applications.transfer.v1.MsgTransfer"
+        );
+        assert_eq!(msgs[0].value, Binary::from(expected.to_proto_bytes()));
+        assert!(ica_return_msgs(&route, &[], ADMIN, 1_000).is_empty());
+    }
+}
@@ -16,7 +16,9 @@ use valence_domain_clients::{
     clients::ethereum::EthereumClient,
     evm::{base_client::EvmBaseClient, request_provider_client::RequestProviderClient},
 };
-use valence_forwarder_library::msg::{ForwardingConstraints, UncheckedForwardingConfig};
+use valence_forwarder_library::msg::{
+    ForwardingConstraints, LpRedemptionConfig, UncheckedForwardingConfig,
+};
 use valence_generic_ibc_transfer_library::msg::IbcTransferAmount;
 use valence_ibc_utils::types::EurekaConfig;
 use valence_library_utils::{denoms::UncheckedDenom, LibraryAccountType};
@@ -25,10 +27,12 @@ use crate::{
     async_run,
     utils::{
         base_account::approve_library,
+        error::{get_code_id, get_transfer_channel, ValenceSetupError},
         hyperlane::{
             set_up_cw_hyperlane_contracts, set_up_eth_hyperlane_contracts, set_up_hyperlane,
         },
         manager::{FORWARDER_NAME, NEUTRON_IBC_TRANSFER_NAME},
+        setup_context::LibraryDeployment,
         solidity_contracts::{
             ERC1967Proxy,
             ValenceVault::{self},
@@ -319,51 +323,71 @@ pub fn update() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-pub fn setup_liquidation_fwd_lib(
-    test_ctx: &mut TestContext,
-    input_account: String,
-    output_addr: String,
+/// Sets up the forwarder of the shares to liquidate.
+/// With `lp_redemption`, only the shares needed to redeem the target amount of underlying tokens are forwarded.
+pub fn liquidation_fwd_instantiate_msg(
+    input_account: &str,
+    output_addr: &str,
     shares_denom: &str,
-) -> Result<String, Box<dyn Error>> {
-    let fwd_code_id = *test_ctx
-        .get_chain(NEUTRON_CHAIN_NAME)
-        .contract_codes
-        .get(FORWARDER_NAME)
-        .unwrap();
-
-    let fwd_instantiate_msg = valence_library_utils::msg::InstantiateMsg::<
-        valence_forwarder_library::msg::LibraryConfig,
-    > {
+    lp_redemption: Option<LpRedemptionConfig>,
+) -> valence_library_utils::msg::InstantiateMsg<valence_forwarder_library::msg::LibraryConfig> {
+    valence_library_utils::msg::InstantiateMsg::<valence_forwarder_library::msg::LibraryConfig> {
         owner: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         processor: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         config: valence_forwarder_library::msg::LibraryConfig {
-            input_addr: LibraryAccountType::Addr(input_account.clone()),
-            output_addr: LibraryAccountType::Addr(output_addr.clone()),
+            input_addr: LibraryAccountType::Addr(input_account.to_string()),
+            additional_input_addrs: None,
+            output_addr: LibraryAccountType::Addr(output_addr.to_string()),
             forwarding_configs: vec![UncheckedForwardingConfig {
                 denom: UncheckedDenom::Native(shares_denom.to_string()),
                 max_amount: Uint128::MAX,
             }],
             forwarding_constraints: ForwardingConstraints::new(None),
+            split_outputs: None,
+            rounding: Default::default(),
+            lp_redemption,
+            gas_reserve: None,
+            snapshot_balances: false,
+            precondition: None,
+            fee_skim: None,
         },
-    };
+    }
+}
+
+pub fn setup_liquidation_fwd_lib(
+    test_ctx: &mut TestContext,
+    input_account: String,
+    output_addr: String,
+    shares_denom: &str,
+    lp_redemption: Option<LpRedemptionConfig>,
+    deployment: &LibraryDeployment,
+) -> Result<String, ValenceSetupError> {
+    let fwd_code_id = get_code_id(test_ctx, NEUTRON_CHAIN_NAME, FORWARDER_NAME)?;
+
+    let fwd_instantiate_msg =
+        liquidation_fwd_instantiate_msg(&input_account, &output_addr, shares_denom, lp_redemption);
 
     info!(
         "Neutron Forwarder instantiate message: {:?}",
         fwd_instantiate_msg
     );
 
+    let label = deployment.label("liquidation_forwarder");
     let liquidation_forwarder = contract_instantiate(
         test_ctx
             .get_request_builder()
             .get_request_builder(NEUTRON_CHAIN_NAME),
         DEFAULT_KEY,
         fwd_code_id,
-        &serde_json::to_string(&fwd_instantiate_msg).unwrap(),
-        "liquidation_forwarder",
-        None,
+        &serde_json::to_string(&fwd_instantiate_msg)?,
+        &label,
+        deployment.admin.clone(),
         "",
     )
-    .unwrap();
+    .map_err(|e| ValenceSetupError::Instantiation {
+        label,
+        reason: e.to_string(),
+    })?;
 
     info!(
         "Liquidation Forwarder library: {}",
@@ -371,7 +395,7 @@ pub fn setup_liquidation_fwd_lib(
     );
 
     // Approve the library for the base account
-    approve_library(
+    let receipt = approve_library(
         test_ctx,
         NEUTRON_CHAIN_NAME,
         DEFAULT_KEY,
@@ -379,35 +403,28 @@ pub fn setup_liquidation_fwd_lib(
         liquidation_forwarder.address.clone(),
         None,
     );
+    info!("liquidation_forwarder approval receipt: {receipt:?}");
 
     Ok(liquidation_forwarder.address)
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn setup_neutron_ibc_transfer_lib(
-    test_ctx: &mut TestContext,
-    input_account: String,
-    output_addr: String,
+/// Instantiate message of the neutron ibc transfer library, transferring the full
+/// balance of `denom` over the transfer channel towards `destination_chain_name`
+pub fn neutron_ibc_transfer_instantiate_msg(
+    test_ctx: &TestContext,
+    input_account: &str,
+    output_addr: &str,
     denom: &str,
-    _authorizations: String,
-    _processor: String,
     destination_chain_name: &str,
     eureka_config: Option<EurekaConfig>,
-) -> Result<String, Box<dyn Error>> {
-    let neutron_ibc_transfer_code_id = *test_ctx
-        .get_chain(NEUTRON_CHAIN_NAME)
-        .contract_codes
-        .get(NEUTRON_IBC_TRANSFER_NAME)
-        .unwrap();
-
-    info!("neutron ibc transfer code id: {neutron_ibc_transfer_code_id}");
-
+) -> Result<
+    valence_library_utils::msg::InstantiateMsg<
+        valence_neutron_ibc_transfer_library::msg::LibraryConfig,
+    >,
+    ValenceSetupError,
+> {
     let remote_chain_info = valence_generic_ibc_transfer_library::msg::RemoteChainInfo {
-        channel_id: test_ctx
-            .get_transfer_channels()
-            .src(NEUTRON_CHAIN_NAME)
-            .dest(destination_chain_name)
-            .get(),
+        channel_id: get_transfer_channel(test_ctx, NEUTRON_CHAIN_NAME, destination_chain_name)?,
         ibc_transfer_timeout: None,
     };
 
@@ -420,9 +437,14 @@ pub fn setup_neutron_ibc_transfer_lib(
         memo: "-".to_string(),
         denom_to_pfm_map: BTreeMap::default(),
         eureka_config,
+        min_transfer_amount: None,
+        denom_selection: None,
+        gas_reserve: None,
+        precondition: None,
+        transfer_defaults: None,
     };
 
-    let neutron_ibc_transfer_instantiate_msg = valence_library_utils::msg::InstantiateMsg::<
+    Ok(valence_library_utils::msg::InstantiateMsg::<
         valence_neutron_ibc_transfer_library::msg::LibraryConfig,
     > {
         // TODO: uncomment to not bypass authorizations/processor logic
@@ -431,25 +453,56 @@ pub fn setup_neutron_ibc_transfer_lib(
         owner: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         processor: NEUTRON_CHAIN_ADMIN_ADDR.to_string(),
         config: ibc_transfer_cfg,
-    };
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn setup_neutron_ibc_transfer_lib(
+    test_ctx: &mut TestContext,
+    input_account: String,
+    output_addr: String,
+    denom: &str,
+    _authorizations: String,
+    _processor: String,
+    destination_chain_name: &str,
+    eureka_config: Option<EurekaConfig>,
+    deployment: &LibraryDeployment,
+) -> Result<String, ValenceSetupError> {
+    let neutron_ibc_transfer_code_id =
+        get_code_id(test_ctx, NEUTRON_CHAIN_NAME, NEUTRON_IBC_TRANSFER_NAME)?;
+
+    info!("neutron ibc transfer code id: {neutron_ibc_transfer_code_id}");
+
+    let neutron_ibc_transfer_instantiate_msg = neutron_ibc_transfer_instantiate_msg(
+        test_ctx,
+        &input_account,
+        &output_addr,
+        denom,
+        destination_chain_name,
+        eureka_config,
+    )?;
 
     info!(
         "Neutron IBC Transfer instantiate message: {:?}",
         neutron_ibc_transfer_instantiate_msg
     );
 
+    let label = deployment.label("neutron_ibc_transfer");
     let ibc_transfer = contract_instantiate(
         test_ctx
             .get_request_builder()
             .get_request_builder(NEUTRON_CHAIN_NAME),
         DEFAULT_KEY,
         neutron_ibc_transfer_code_id,
-        &serde_json::to_string(&neutron_ibc_transfer_instantiate_msg).unwrap(),
-        "neutron_ibc_transfer",
-        None,
+        &serde_json::to_string(&neutron_ibc_transfer_instantiate_msg)?,
+        &label,
+        deployment.admin.clone(),
         "",
     )
-    .unwrap();
+    .map_err(|e| ValenceSetupError::Instantiation {
+        label,
+        reason: e.to_string(),
+    })?;
 
     info!(
         "Neutron IBC Transfer library: {}",
@@ -457,7 +510,7 @@ pub fn setup_neutron_ibc_transfer_lib(
     );
 
     // Approve the library for the base account
-    approve_library(
+    let receipt = approve_library(
         test_ctx,
         NEUTRON_CHAIN_NAME,
         DEFAULT_KEY,
@@ -465,6 +518,7 @@ pub fn setup_neutron_ibc_transfer_lib(
         ibc_transfer.address.clone(),
         None,
     );
+    info!("ibc_transfer approval receipt: {receipt:?}");
 
     Ok(ibc_transfer.address)
 }
@@ -44,6 +44,8 @@ pub trait ValenceWorker {
     }
 }
 
+/// TOML persistence of worker configs, e.g. to deploy a program once and run
+/// its strategist later on as a separate process reloading the deployment.
 pub trait ValenceWorkerTomlSerde: Sized + Serialize + DeserializeOwned {
     fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
         let contents = fs::read_to_string(path)?;
@@ -51,9 +53,20 @@ pub trait ValenceWorkerTomlSerde: Sized + Serialize + DeserializeOwned {
         Ok(config)
     }
 
+    /// Writes the config to `path`, creating its parent directories if needed.
+    /// The config is written to a temporary file first and then moved in place,
+    /// so a process reloading it never reads a partially written file.
     fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
         let toml_string = toml::to_string(self)?;
-        fs::write(path, toml_string)?;
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = path.with_extension("toml.tmp");
+        fs::write(&tmp_path, toml_string)?;
+        fs::rename(&tmp_path, path)?;
         Ok(())
     }
 }
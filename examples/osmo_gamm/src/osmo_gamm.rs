@@ -78,6 +78,7 @@ pub fn my_osmosis_gamm_program(
             asset_data: AssetData {
                 asset1: denom_1.to_string(),
                 asset2: denom_2.to_string(),
+                additional_assets: vec![],
             },
         },
     };
@@ -91,6 +92,7 @@ pub fn my_osmosis_gamm_program(
             asset_data: AssetData {
                 asset1: denom_1.to_string(),
                 asset2: denom_2.to_string(),
+                additional_assets: vec![],
             },
         },
     };
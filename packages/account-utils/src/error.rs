@@ -12,6 +12,9 @@ pub enum ContractError {
 
     #[error("Unauthorized: {0}")]
     Unauthorized(#[from] UnauthorizedReason),
+
+    #[error("Denom {denom} is not allowed on this account")]
+    DenomNotAllowed { denom: String },
 }
 
 #[derive(Error, Debug, PartialEq)]
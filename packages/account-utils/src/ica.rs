@@ -1,5 +1,5 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{AnyMsg, CosmosMsg, StdError, StdResult, Uint64};
+use cosmwasm_std::{AnyMsg, Binary, Coin, CosmosMsg, StdError, StdResult, Uint64};
 use cw_ownable::{cw_ownable_execute, cw_ownable_query};
 
 #[cw_serde]
@@ -16,7 +16,46 @@ pub enum ExecuteMsg {
     RemoveLibrary { library: String },  // Remove library from approved list (only admin)
     ExecuteMsg { msgs: Vec<CosmosMsg> }, // Execute a list of Cosmos messages, useful to retrieve funds that were sent here by the owner for example.
     ExecuteIcaMsg { msgs: Vec<AnyMsg> }, // Execute a protobuf message on the ICA
-    RegisterIca {},                      // Register the ICA on the remote chain
+    ExecuteIcaMsgWithCallback { msgs: Vec<AnyMsg> }, // Execute a protobuf message on the ICA, calling the sender back with an IcaCallbackMsg once the tx is acknowledged or times out
+    RegisterIca {},                                  // Register the ICA on the remote chain
+    ReopenChannel {}, // Re-register the ICA on its existing port after its channel closed
+    UpdateRemoteBalances { balances: Vec<Coin> }, // Record the last known balances of the ICA on the remote chain (only admin)
+    DeductRemoteBalances { balances: Vec<Coin> }, // Deduct amounts spent by the ICA from its last known balances, until they are refreshed (only admin or approved library)
+    RegisterBalancesQuery { denoms: Vec<String>, period: u64 }, // Register an ICQ for the ICA balances updated every `period` blocks, kept until removed (only admin)
+    RefreshRemoteBalances {}, // Update the last known balances with the latest results of the active balances ICQs
+    RemoveQuery { query_id: u64 }, // Remove an active ICQ, refunding its deposit to the account (only admin)
+}
+
+/// Message sent by the interchain account to the library that submitted an ICA tx
+/// with `ExecuteIcaMsgWithCallback`, once the outcome of the tx is known
+#[cw_serde]
+pub enum IcaCallbackMsg {
+    IcaTxCallback {
+        channel_id: String,
+        sequence: u64,
+        result: IcaTxResult,
+    },
+}
+
+/// Data of the response to `ExecuteIcaMsgWithCallback`: the ICA packet carrying the tx,
+/// which the `IcaCallbackMsg` reporting the outcome of the tx refers to
+#[cw_serde]
+pub struct IcaTxSubmission {
+    pub channel_id: String,
+    pub sequence: u64,
+}
+
+#[cw_serde]
+pub enum IcaTxResult {
+    // The tx was executed on the remote chain, `data` being the acknowledged TxMsgData with the responses of its messages
+    Success {
+        #[serde(default)]
+        data: Binary,
+    },
+    Error {
+        details: String,
+    }, // The tx failed on the remote chain (error acknowledgement)
+    Timeout {}, // The packet timed out, which also closes the ICA channel
 }
 
 #[cw_ownable_query]
@@ -27,13 +66,21 @@ pub enum QueryMsg {
     ListApprovedLibraries {}, // Get list of approved libraries
     #[returns(IcaState)]
     IcaState {}, // Get the state of the ICA
+    #[returns(Option<IcaInformation>)]
+    IcaInformation {}, // Get the last known information of the ICA, retained after its channel closes
     #[returns(RemoteDomainInfo)]
     RemoteDomainInfo {}, // Get the remote domain information
+    #[returns(Coin)]
+    RemoteBalance { denom: String }, // Get the last known balance of a denom held by the ICA on the remote chain
+    #[returns(Option<BalancesQuery>)]
+    BalancesQuery {}, // Get the latest balances interchain query registered for the ICA
+    #[returns(Vec<BalancesQuery>)]
+    ActiveQueries {}, // Get all the interchain queries registered by the account that were not removed
 }
 
 #[cw_serde]
 pub struct RemoteDomainInfo {
-    pub connection_id: String,
+    pub connection_id: String, // connection over which the ICA is registered, a chain can have several connections to the remote chain
     pub ica_timeout_seconds: Uint64, // relative timeout in seconds after which the packet times out
 }
 
@@ -42,6 +89,19 @@ impl RemoteDomainInfo {
         if self.connection_id.is_empty() {
             return Err(StdError::generic_err("connection_id cannot be empty"));
         }
+        // Connection identifiers are always of the form connection-{sequence}
+        if !self
+            .connection_id
+            .strip_prefix("connection-")
+            .is_some_and(|sequence| {
+                !sequence.is_empty() && sequence.bytes().all(|byte| byte.is_ascii_digit())
+            })
+        {
+            return Err(StdError::generic_err(format!(
+                "Invalid connection_id {}, expected connection-{{sequence}}",
+                self.connection_id
+            )));
+        }
         if self.ica_timeout_seconds.is_zero() {
             return Err(StdError::generic_err("ica_timeout_seconds cannot be zero"));
         }
@@ -50,6 +110,13 @@ impl RemoteDomainInfo {
     }
 }
 
+#[cw_serde]
+pub struct BalancesQuery {
+    pub query_id: Option<u64>, // Assigned by the interchainqueries module once the registration is confirmed
+    pub denoms: Vec<String>,
+    pub update_period: u64,
+}
+
 #[cw_serde]
 pub enum IcaState {
     NotCreated, // Not created yet
@@ -24,6 +24,12 @@ pub enum ExecuteMsg {
     RemoveLibrary {
         library: String,
     }, // Remove library from approved list (only admin)
+    ApproveLibraries {
+        libraries: Vec<String>,
+    }, // Add multiple libraries to approved list atomically (only admin)
+    RemoveLibraries {
+        libraries: Vec<String>,
+    }, // Remove multiple libraries from approved list atomically (only admin)
     ExecuteMsg {
         msgs: Vec<CosmosMsg>,
     }, // Execute any CosmosMsg (approved libraries or admin)
@@ -32,6 +38,9 @@ pub enum ExecuteMsg {
         // json encoded
         payload: Option<String>,
     },
+    UpdateDenomAllowlist {
+        denoms: Option<Vec<String>>,
+    }, // Only accept funds of these denoms, or any denom if None (only admin)
 }
 
 #[cw_ownable_query]
@@ -40,6 +49,13 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(Vec<String>)]
     ListApprovedLibraries {}, // Get list of approved libraries
+    #[returns(Vec<String>)]
+    Libraries {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    }, // Get a page of approved libraries, ordered by address
+    #[returns(Option<Vec<String>>)]
+    DenomAllowlist {}, // Get the denoms accepted by the account, if restricted
 }
 
 #[cw_serde]
@@ -1,6 +1,6 @@
-use cosmwasm_schema::cw_serde;
-use cosmwasm_std::DepsMut;
-use valence_library_utils::error::LibraryError;
+use cosmwasm_schema::{cw_serde, serde::Deserialize};
+use cosmwasm_std::{Decimal, Deps};
+use valence_library_utils::{error::LibraryError, liquidity_utils::AssetData};
 
 pub mod astroport_cw20_lp_token;
 pub mod astroport_native_lp_token;
@@ -34,8 +34,126 @@ pub enum PoolType {
     Cw20LpToken(astroport_cw20_lp_token::PairType),
 }
 
+/// Pair type of Astroport concentrated liquidity pools, which are deployed as custom pairs
+pub const CONCENTRATED_PAIR_TYPE: &str = "concentrated";
+
+/// Liquidity model of an Astroport pool, which determines the parameters supported
+/// by the provide and withdraw liquidity messages
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum PoolKind {
+    Xyk,
+    ConcentratedLiquidity,
+    Stable,
+}
+
+impl PoolKind {
+    /// Pool type of a pool of this kind minting native LP tokens
+    pub fn native_lp_token_pool_type(&self) -> PoolType {
+        PoolType::NativeLpToken(match self {
+            PoolKind::Xyk => astroport_native_lp_token::PairType::Xyk {},
+            PoolKind::ConcentratedLiquidity => {
+                astroport_native_lp_token::PairType::Custom(CONCENTRATED_PAIR_TYPE.to_string())
+            }
+            PoolKind::Stable => astroport_native_lp_token::PairType::Stable {},
+        })
+    }
+
+    /// Pool type of a pool of this kind minting Cw20 LP tokens
+    pub fn cw20_lp_token_pool_type(&self) -> PoolType {
+        PoolType::Cw20LpToken(match self {
+            PoolKind::Xyk => astroport_cw20_lp_token::PairType::Xyk {},
+            PoolKind::ConcentratedLiquidity => {
+                astroport_cw20_lp_token::PairType::Custom(CONCENTRATED_PAIR_TYPE.to_string())
+            }
+            PoolKind::Stable => astroport_cw20_lp_token::PairType::Stable {},
+        })
+    }
+
+    /// Stable pools ignore the slippage tolerance when providing liquidity
+    pub fn supports_slippage_tolerance(&self) -> bool {
+        !matches!(self, PoolKind::Stable)
+    }
+}
+
+impl PoolType {
+    /// Liquidity model of the pool. Only the concentrated liquidity custom pairs are supported,
+    /// other custom pairs (e.g. with a sale tax) may not accept the same messages
+    pub fn kind(&self) -> Result<PoolKind, LibraryError> {
+        match self {
+            PoolType::NativeLpToken(astroport_native_lp_token::PairType::Xyk {})
+            | PoolType::Cw20LpToken(astroport_cw20_lp_token::PairType::Xyk {}) => Ok(PoolKind::Xyk),
+            PoolType::NativeLpToken(astroport_native_lp_token::PairType::Stable {})
+            | PoolType::Cw20LpToken(astroport_cw20_lp_token::PairType::Stable {}) => {
+                Ok(PoolKind::Stable)
+            }
+            PoolType::NativeLpToken(astroport_native_lp_token::PairType::Custom(pair_type))
+            | PoolType::Cw20LpToken(astroport_cw20_lp_token::PairType::Custom(pair_type)) => {
+                if pair_type == CONCENTRATED_PAIR_TYPE {
+                    Ok(PoolKind::ConcentratedLiquidity)
+                } else {
+                    Err(LibraryError::ConfigurationError(format!(
+                        "Unsupported custom pair type {pair_type}, only {CONCENTRATED_PAIR_TYPE} custom pairs are supported"
+                    )))
+                }
+            }
+        }
+    }
+}
+
+/// Max spread passed to the pool when swapping if none is configured, the same value Astroport defaults to
+pub const DEFAULT_MAX_SPREAD: Decimal = Decimal::permille(5);
+
+/// Checks that a configured max spread is strictly between 0 and 1
+pub fn validate_max_spread(max_spread: Option<Decimal>) -> Result<(), LibraryError> {
+    if let Some(max_spread) = max_spread {
+        if max_spread.is_zero() || max_spread >= Decimal::one() {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Max spread {max_spread} must be between 0 and 1 (exclusive)"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Max spread sent in swap messages, so that a swap never relies on the default of the pool contract
+pub fn max_spread_or_default(max_spread: Option<Decimal>) -> Decimal {
+    max_spread.unwrap_or(DEFAULT_MAX_SPREAD)
+}
+
+/// Checks that the pool at `pool_addr` has the pair type of `pool_type`
+pub fn ensure_pool_type(
+    deps: Deps,
+    pool_addr: &str,
+    pool_type: &PoolType,
+) -> Result<(), LibraryError> {
+    let matches = match pool_type {
+        PoolType::NativeLpToken(pair_type) => {
+            let pair: astroport_native_lp_token::PairInfo = deps
+                .querier
+                .query_wasm_smart(pool_addr, &astroport_native_lp_token::PoolQueryMsg::Pair {})?;
+            pair.pair_type == *pair_type
+        }
+        PoolType::Cw20LpToken(pair_type) => {
+            let pair: astroport_cw20_lp_token::PairInfo = deps
+                .querier
+                .query_wasm_smart(pool_addr, &astroport_cw20_lp_token::PoolQueryMsg::Pair {})?;
+            pair.pair_type == *pair_type
+        }
+    };
+
+    if !matches {
+        return Err(LibraryError::ConfigurationError(
+            "Pool type does not match the expected pair type".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 pub fn query_pool(
-    deps: &DepsMut,
+    deps: Deps,
     pool_addr: &str,
     pool_type: &PoolType,
 ) -> Result<Vec<Box<dyn AssetTrait>>, LibraryError> {
@@ -57,32 +175,77 @@ pub fn query_pool(
     }
 }
 
+// Both pool versions describe their assets in the same format, so the pair query
+// is deserialized leniently and only the assets are kept
+#[derive(Deserialize)]
+#[serde(crate = "cosmwasm_schema::serde")]
+struct PairAssets {
+    asset_infos: Vec<astroport_native_lp_token::AssetInfo>,
+}
+
+/// Returns the denoms of the pool assets, in the same order as in the pool
+pub fn query_pool_denoms(deps: Deps, pool_addr: &str) -> Result<Vec<String>, LibraryError> {
+    let pair: PairAssets = deps
+        .querier
+        .query_wasm_smart(pool_addr, &astroport_native_lp_token::PoolQueryMsg::Pair {})?;
+
+    pair.asset_infos
+        .into_iter()
+        .map(|asset_info| match asset_info {
+            astroport_native_lp_token::AssetInfo::NativeToken { denom } => Ok(denom),
+            astroport_native_lp_token::AssetInfo::Token { .. } => Err(
+                LibraryError::ConfigurationError("Pool asset is not a native token".to_string()),
+            ),
+        })
+        .collect()
+}
+
+/// Validation of the configured assets against the Astroport pool they are used with
+pub trait PoolAssetsValidation {
+    /// Checks that the configured denoms are exactly the assets of the pool, in any order
+    fn validate_against_pool(&self, deps: Deps, pool_addr: &str) -> Result<(), LibraryError>;
+}
+
+impl PoolAssetsValidation for AssetData {
+    fn validate_against_pool(&self, deps: Deps, pool_addr: &str) -> Result<(), LibraryError> {
+        let pool_assets = query_pool_denoms(deps, pool_addr)?;
+        self.ensure_matches_pool_assets(&pool_assets)
+    }
+}
+
 pub fn get_pool_asset_amounts(
     assets: Vec<Box<dyn AssetTrait>>,
     asset1_denom: &str,
     asset2_denom: &str,
 ) -> Result<(u128, u128), LibraryError> {
-    let (mut asset1_balance, mut asset2_balance) = (0, 0);
+    let balances = get_pool_assets_amounts(assets, &[asset1_denom, asset2_denom])?;
+    Ok((balances[0], balances[1]))
+}
+
+/// Returns the pool balance of each of the denoms, in the same order they are given
+pub fn get_pool_assets_amounts(
+    assets: Vec<Box<dyn AssetTrait>>,
+    denoms: &[&str],
+) -> Result<Vec<u128>, LibraryError> {
+    let mut balances = vec![0; denoms.len()];
 
     for asset in assets {
         let coin = asset
             .as_coin()
             .map_err(|error| LibraryError::ExecutionError(error.to_string()))?;
 
-        if coin.denom == asset1_denom {
-            asset1_balance = coin.amount.u128();
-        } else if coin.denom == asset2_denom {
-            asset2_balance = coin.amount.u128();
+        if let Some(index) = denoms.iter().position(|denom| coin.denom == *denom) {
+            balances[index] = coin.amount.u128();
         }
     }
 
-    if asset1_balance == 0 || asset2_balance == 0 {
+    if balances.iter().any(|balance| *balance == 0) {
         return Err(LibraryError::ExecutionError(
             "All pool assets must be non-zero".to_string(),
         ));
     }
 
-    Ok((asset1_balance, asset2_balance))
+    Ok(balances)
 }
 
 // Implemented in the astroport crate for Decimal
@@ -138,6 +138,16 @@ impl Subroutine {
             .unwrap_or_default()
     }
 
+    pub fn get_gas_limit_by_function_index(&self, index: usize) -> Option<u64> {
+        self.get_function_by_index(index)
+            .and_then(|function| function.gas_limit())
+    }
+
+    pub fn get_scope_by_function_index(&self, index: usize) -> Option<&[String]> {
+        self.get_function_by_index(index)
+            .and_then(|function| function.scope())
+    }
+
     pub fn get_function_by_index(&self, index: usize) -> Option<&dyn Function> {
         match self {
             Subroutine::Atomic(config) => config.functions.get(index).map(|a| a as &dyn Function),
@@ -177,6 +187,8 @@ pub enum Priority {
     #[default]
     Medium,
     High,
+    // Only processed when both the High and Medium priority queues are empty
+    Low,
 }
 
 #[cw_serde]
@@ -207,6 +219,20 @@ pub enum AuthorizationMsg {
         priority: Priority,
         expiration_time: Option<u64>,
     },
+    // Removes a pending batch from whatever queue it is in
+    RemoveMsg {
+        id: u64,
+    },
+    // Removes all pending batches from all queues
+    Flush {},
+    // Removes batches from the dead letter queue without executing them again
+    PurgeDeadLetters {
+        ids: Vec<u64>,
+    },
+    // Moves a batch from the dead letter queue back to its queue, with its retries reset
+    RequeueDeadLetter {
+        id: u64,
+    },
     Pause {},
     Resume {},
 }
@@ -179,6 +179,8 @@ pub struct AtomicFunctionBuilder {
     domain: Domain,
     message_details: MessageDetails,
     contract_address: LibraryAccountType,
+    gas_limit: Option<u64>,
+    scope: Option<Vec<String>>,
 }
 
 impl Default for AtomicFunctionBuilder {
@@ -199,6 +201,8 @@ impl AtomicFunctionBuilder {
                 },
             },
             contract_address: LibraryAccountType::Addr("address".to_string()),
+            gas_limit: None,
+            scope: None,
         }
     }
     pub fn with_domain(mut self, domain: Domain) -> Self {
@@ -216,11 +220,23 @@ impl AtomicFunctionBuilder {
         self
     }
 
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    pub fn with_scope(mut self, scope: Vec<String>) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
     pub fn build(self) -> AtomicFunction {
         AtomicFunction {
             domain: self.domain,
             message_details: self.message_details,
             contract_address: self.contract_address,
+            gas_limit: self.gas_limit,
+            scope: self.scope,
         }
     }
 }
@@ -231,6 +247,8 @@ pub struct NonAtomicFunctionBuilder {
     contract_address: LibraryAccountType,
     retry_logic: Option<RetryLogic>,
     callback_confirmation: Option<FunctionCallback>,
+    gas_limit: Option<u64>,
+    scope: Option<Vec<String>>,
 }
 
 impl Default for NonAtomicFunctionBuilder {
@@ -253,6 +271,8 @@ impl NonAtomicFunctionBuilder {
             contract_address: LibraryAccountType::Addr("address".to_string()),
             retry_logic: None,
             callback_confirmation: None,
+            gas_limit: None,
+            scope: None,
         }
     }
 
@@ -281,6 +301,16 @@ impl NonAtomicFunctionBuilder {
         self
     }
 
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    pub fn with_scope(mut self, scope: Vec<String>) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
     pub fn build(self) -> NonAtomicFunction {
         NonAtomicFunction {
             domain: self.domain,
@@ -288,6 +318,8 @@ impl NonAtomicFunctionBuilder {
             contract_address: self.contract_address,
             retry_logic: self.retry_logic,
             callback_confirmation: self.callback_confirmation,
+            gas_limit: self.gas_limit,
+            scope: self.scope,
         }
     }
 }
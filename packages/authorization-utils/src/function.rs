@@ -12,6 +12,13 @@ pub struct AtomicFunction {
     pub message_details: MessageDetails,
     // We use String instead of Addr because it can be a contract address in other execution environments
     pub contract_address: LibraryAccountType,
+    // Gas limit of the message dispatched by the processor for this function. If not provided, the message can use all the gas left.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<u64>,
+    // Variants of the message that can be called through this function, e.g. `forward` for a `process_function` message.
+    // If not provided, any variant of the message can be called.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Vec<String>>,
 }
 
 #[cw_serde]
@@ -26,12 +33,21 @@ pub struct NonAtomicFunction {
     // An function might need to receive a callback to be confirmed, in that case we will include the callback confirmation.
     // If not provided, we assume that correct execution of the message implies confirmation.
     pub callback_confirmation: Option<FunctionCallback>,
+    // Gas limit of the message dispatched by the processor for this function. If not provided, the message can use all the gas left.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<u64>,
+    // Variants of the message that can be called through this function, e.g. `forward` for a `process_function` message.
+    // If not provided, any variant of the message can be called.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Vec<String>>,
 }
 
 pub trait Function {
     fn domain(&self) -> &Domain;
     fn message_details(&self) -> &MessageDetails;
     fn get_contract_address(&self) -> String;
+    fn gas_limit(&self) -> Option<u64>;
+    fn scope(&self) -> Option<&[String]>;
 }
 
 // Implement this trait for both AtomicFunction and NonAtomicFunction
@@ -47,6 +63,14 @@ impl Function for AtomicFunction {
     fn get_contract_address(&self) -> String {
         self.contract_address.to_string().unwrap()
     }
+
+    fn gas_limit(&self) -> Option<u64> {
+        self.gas_limit
+    }
+
+    fn scope(&self) -> Option<&[String]> {
+        self.scope.as_deref()
+    }
 }
 
 impl Function for NonAtomicFunction {
@@ -61,12 +85,54 @@ impl Function for NonAtomicFunction {
     fn get_contract_address(&self) -> String {
         self.contract_address.to_string().unwrap()
     }
+
+    fn gas_limit(&self) -> Option<u64> {
+        self.gas_limit
+    }
+
+    fn scope(&self) -> Option<&[String]> {
+        self.scope.as_deref()
+    }
 }
 
 #[cw_serde]
 pub struct RetryLogic {
     pub times: RetryTimes,
     pub interval: Duration,
+    // How the interval grows between consecutive retries. Defaults to a fixed interval.
+    #[serde(default)]
+    pub backoff: RetryBackoff,
+}
+
+impl RetryLogic {
+    /// Returns the cooldown to apply before the next retry, given how many retries were already done
+    pub fn cooldown(&self, retry_amounts: u64) -> Duration {
+        let scale = |value: u64| match &self.backoff {
+            RetryBackoff::Fixed => value,
+            RetryBackoff::Exponential { max_interval } => {
+                let factor = 2u64.saturating_pow(u32::try_from(retry_amounts).unwrap_or(u32::MAX));
+                let scaled = value.saturating_mul(factor);
+                max_interval.map_or(scaled, |max| scaled.min(max))
+            }
+        };
+
+        match self.interval {
+            Duration::Height(blocks) => Duration::Height(scale(blocks)),
+            Duration::Time(seconds) => Duration::Time(scale(seconds)),
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum RetryBackoff {
+    // Always wait `interval` between retries
+    #[default]
+    Fixed,
+    // Double the interval after every failed retry, optionally capped at `max_interval` (same unit as `interval`)
+    Exponential {
+        max_interval: Option<u64>,
+    },
 }
 
 #[cw_serde]
@@ -82,3 +148,32 @@ pub struct FunctionCallback {
     // What we should receive from the callback to consider the function completed
     pub callback_message: Binary,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_logic_cooldown() {
+        let fixed = RetryLogic {
+            times: RetryTimes::Amount(3),
+            interval: Duration::Time(10),
+            backoff: RetryBackoff::Fixed,
+        };
+        assert_eq!(fixed.cooldown(0), Duration::Time(10));
+        assert_eq!(fixed.cooldown(2), Duration::Time(10));
+
+        let exponential = RetryLogic {
+            times: RetryTimes::Indefinitely,
+            interval: Duration::Height(5),
+            backoff: RetryBackoff::Exponential {
+                max_interval: Some(30),
+            },
+        };
+        assert_eq!(exponential.cooldown(0), Duration::Height(5));
+        assert_eq!(exponential.cooldown(1), Duration::Height(10));
+        assert_eq!(exponential.cooldown(2), Duration::Height(20));
+        assert_eq!(exponential.cooldown(3), Duration::Height(30));
+        assert_eq!(exponential.cooldown(u64::MAX), Duration::Height(30));
+    }
+}
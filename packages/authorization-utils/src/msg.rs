@@ -217,6 +217,24 @@ pub enum PermissionedMsg {
         // what queue we are targetting
         priority: Priority,
     },
+    // Method to remove a pending batch from the queues of any domain, identified by its execution ID
+    RemoveMsg {
+        domain: Domain,
+        id: u64,
+    },
+    // Method to remove all pending batches from the queues of any domain
+    Flush {
+        domain: Domain,
+    },
+    // Method to drop batches from the dead letter queue of the processor of any domain
+    PurgeDeadLetters {
+        domain: Domain,
+        ids: Vec<u64>,
+    },
+    // Method to move a batch from the dead letter queue of its processor back to its queue
+    RequeueDeadLetter {
+        id: u64,
+    },
     // Method to insert messages from an authorization to any queue
     InsertMsgs {
         // The authorization label
@@ -329,6 +347,27 @@ impl ProcessorMessage {
         }
     }
 
+    /// Variant called by a CosmWasm message, which is the single key of the message body.
+    /// E.g. `forward` for `{"process_function": {"forward": {}}}`.
+    /// Returns None for EVM messages and for bodies that are not a single variant.
+    pub fn called_variant(&self) -> Option<String> {
+        let msg = match self {
+            ProcessorMessage::CosmwasmExecuteMsg { msg }
+            | ProcessorMessage::CosmwasmMigrateMsg { msg, .. } => msg,
+            ProcessorMessage::EvmCall { .. } | ProcessorMessage::EvmRawCall { .. } => return None,
+        };
+
+        let json: serde_json::Value = serde_json::from_slice(msg).ok()?;
+        let body = match json.as_object()? {
+            map if map.len() == 1 => map.values().next()?.as_object()?,
+            _ => return None,
+        };
+        match body.keys().collect::<Vec<_>>().as_slice() {
+            [variant] if body[*variant].is_object() => Some(variant.to_string()),
+            _ => None,
+        }
+    }
+
     pub fn to_wasm_message(&self, contract_addr: &str) -> Result<WasmMsg, StdError> {
         match self {
             ProcessorMessage::CosmwasmExecuteMsg { msg } => Ok(WasmMsg::Execute {
@@ -368,6 +407,12 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    // Same as Authorizations but skipping the ones that are already expired
+    #[returns(Vec<Authorization>)]
+    ActiveAuthorizations {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     #[returns(Vec<ZkAuthorization>)]
     ZkAuthorizations {
         start_after: Option<String>,
@@ -454,4 +499,33 @@ mod tests {
         let msg_type = MessageType::EvmRawCall;
         assert!(!msg.eq(&msg_type));
     }
+
+    #[test]
+    fn test_called_variant() {
+        let msg = |json: &str| ProcessorMessage::CosmwasmExecuteMsg {
+            msg: Binary::from(json.as_bytes()),
+        };
+
+        assert_eq!(
+            msg(r#"{"process_function":{"forward":{"execution_id":1}}}"#).called_variant(),
+            Some("forward".to_string())
+        );
+        // Messages without a variant in their body
+        assert_eq!(msg(r#"{"will_succeed":{}}"#).called_variant(), None);
+        assert_eq!(
+            msg(r#"{"will_error":{"error":"failed"}}"#).called_variant(),
+            None
+        );
+        assert_eq!(
+            msg(r#"{"process_function":{"forward":{},"withdraw":{}}}"#).called_variant(),
+            None
+        );
+        assert_eq!(
+            ProcessorMessage::EvmRawCall {
+                msg: Binary::from(vec![1, 2, 3]),
+            }
+            .called_variant(),
+            None
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use alloy_sol_types::sol;
+use cosmwasm_std::StdError;
 
 // The types below is what our processor messages will be decoded into on the EVM processor, with all the required information to build the batches
 // and apply the logic
@@ -138,11 +139,19 @@ sol! {
     }
 }
 
-impl From<valence_authorization_utils::authorization::Priority> for Priority {
-    fn from(priority: valence_authorization_utils::authorization::Priority) -> Self {
+impl TryFrom<valence_authorization_utils::authorization::Priority> for Priority {
+    type Error = StdError;
+
+    fn try_from(
+        priority: valence_authorization_utils::authorization::Priority,
+    ) -> Result<Self, Self::Error> {
         match priority {
-            valence_authorization_utils::authorization::Priority::Medium => Priority::Medium,
-            valence_authorization_utils::authorization::Priority::High => Priority::High,
+            valence_authorization_utils::authorization::Priority::Medium => Ok(Priority::Medium),
+            valence_authorization_utils::authorization::Priority::High => Ok(Priority::High),
+            // The EVM processor only has High and Medium priority queues
+            valence_authorization_utils::authorization::Priority::Low => Err(
+                StdError::generic_err("Low priority is not supported by the EVM processor"),
+            ),
         }
     }
 }
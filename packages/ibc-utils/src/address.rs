@@ -0,0 +1,154 @@
+use std::{fmt, str::FromStr};
+
+use bech32::{primitives::decode::CheckedHrpstring, Bech32, Hrp};
+use cosmwasm_schema::schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use cosmwasm_std::{StdError, StdResult};
+use serde::{Deserialize, Serialize};
+
+/// Bech32 address on a remote chain, with any human readable part (e.g. `noble`, `neutron`, `osmo`).
+/// It is (de)serialized as the bech32 string and can only be built from a well-formed address,
+/// so packets are never built with destination addresses that the remote chain would reject.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RemoteAddress {
+    hrp: String,
+    data: Vec<u8>,
+}
+
+impl RemoteAddress {
+    pub fn new(hrp: &str, data: Vec<u8>) -> StdResult<Self> {
+        let parsed_hrp = Hrp::parse(hrp)
+            .map_err(|e| StdError::generic_err(format!("Invalid bech32 prefix {hrp}: {e}")))?;
+        if data.is_empty() {
+            return Err(StdError::generic_err(
+                "Invalid bech32 address: data cannot be empty",
+            ));
+        }
+
+        // Addresses that can't be encoded (e.g. too long) can't be used as receivers either
+        bech32::encode::<Bech32>(parsed_hrp, &data)
+            .map_err(|e| StdError::generic_err(format!("Invalid bech32 address: {e}")))?;
+
+        Ok(RemoteAddress {
+            hrp: parsed_hrp.to_lowercase(),
+            data,
+        })
+    }
+
+    /// Human readable part of the address, in lowercase
+    pub fn hrp(&self) -> &str {
+        &self.hrp
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl FromStr for RemoteAddress {
+    type Err = StdError;
+
+    fn from_str(address: &str) -> StdResult<Self> {
+        let checked = CheckedHrpstring::new::<Bech32>(address)
+            .map_err(|e| StdError::generic_err(format!("Invalid bech32 address {address}: {e}")))?;
+
+        RemoteAddress::new(checked.hrp().as_str(), checked.byte_iter().collect())
+    }
+}
+
+impl fmt::Display for RemoteAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Both parts were validated on construction, so encoding can't fail
+        let hrp = Hrp::parse(&self.hrp).map_err(|_| fmt::Error)?;
+        let address = bech32::encode::<Bech32>(hrp, &self.data).map_err(|_| fmt::Error)?;
+        f.write_str(&address)
+    }
+}
+
+impl TryFrom<String> for RemoteAddress {
+    type Error = StdError;
+
+    fn try_from(address: String) -> StdResult<Self> {
+        address.parse()
+    }
+}
+
+impl From<RemoteAddress> for String {
+    fn from(address: RemoteAddress) -> Self {
+        address.to_string()
+    }
+}
+
+impl JsonSchema for RemoteAddress {
+    fn schema_name() -> String {
+        "RemoteAddress".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::MockApi;
+
+    use super::*;
+
+    #[test]
+    fn decodes_noble_and_neutron_addresses() {
+        for hrp in ["noble", "neutron"] {
+            let api = MockApi::default().with_prefix(hrp);
+            let address = api.addr_make("ica").to_string();
+
+            let remote: RemoteAddress = address.parse().unwrap();
+            assert_eq!(remote.hrp(), hrp);
+            assert_eq!(remote.data().len(), 32);
+            assert_eq!(remote.to_string(), address);
+        }
+    }
+
+    #[test]
+    fn decodes_arbitrary_hrp_address() {
+        let remote = RemoteAddress::new("mychain", vec![0x2a; 20]).unwrap();
+        let address = remote.to_string();
+        assert!(address.starts_with("mychain1"));
+
+        let decoded: RemoteAddress = address.to_uppercase().parse().unwrap();
+        assert_eq!(decoded, remote);
+        assert_eq!(decoded.hrp(), "mychain");
+    }
+
+    #[test]
+    fn rejects_corrupted_address() {
+        let address = MockApi::default()
+            .with_prefix("noble")
+            .addr_make("ica")
+            .to_string();
+
+        // Flip the last character, which breaks the checksum
+        let mut corrupted = address[..address.len() - 1].to_string();
+        corrupted.push(if address.ends_with('q') { 'p' } else { 'q' });
+
+        for invalid in [corrupted.as_str(), "noble", "noble1", "not an address", ""] {
+            assert!(invalid.parse::<RemoteAddress>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn serializes_as_bech32_string() {
+        let address = MockApi::default()
+            .with_prefix("noble")
+            .addr_make("ica")
+            .to_string();
+
+        let remote: RemoteAddress =
+            cosmwasm_std::from_json(format!("\"{address}\"").as_bytes()).unwrap();
+        assert_eq!(
+            cosmwasm_std::to_json_string(&remote).unwrap(),
+            format!("\"{address}\"")
+        );
+
+        assert!(cosmwasm_std::from_json::<RemoteAddress>(b"\"noble1invalid\"").is_err());
+    }
+}
@@ -3,13 +3,11 @@ use std::collections::BTreeMap;
 use cosmwasm_std::{coin, to_json_string, CosmosMsg, Env, IbcDstCallback, IbcTimeout, StdResult};
 
 use crate::types::{
-    ActionData, ActionWrapper, EurekaConfig, EurekaFee, EurekaMemo, ForwardMetadata, IbcInfo,
-    IbcTransfer, PacketForwardMiddlewareConfig, PacketMetadata, WasmData, WasmMessage,
+    ActionData, ActionWrapper, EurekaConfig, EurekaFee, EurekaMemo, IbcInfo, IbcTransfer,
+    PacketForwardMiddlewareConfig, PacketMetadata, WasmData, WasmMessage,
+    DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS,
 };
 
-// Default timeout for IbcTransfer is 600 seconds
-const DEFAULT_TIMEOUT_SECONDS: u64 = 600;
-
 // Default timeout for EurekaTransfers is 12 hours
 const DEFAULT_EUREKA_TIMEOUT_SECONDS: u64 = 43200;
 
@@ -32,7 +30,7 @@ pub fn ibc_send_message(
             timeout: IbcTimeout::with_timestamp(
                 env.block
                     .time
-                    .plus_seconds(timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS)),
+                    .plus_seconds(timeout_seconds.unwrap_or(DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS)),
             ),
             memo: Some(memo),
         }),
@@ -46,15 +44,10 @@ pub fn ibc_send_message(
             timeout: IbcTimeout::with_timestamp(
                 env.block
                     .time
-                    .plus_seconds(timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS)),
+                    .plus_seconds(timeout_seconds.unwrap_or(DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS)),
             ),
             memo: Some(to_json_string(&PacketMetadata {
-                forward: Some(ForwardMetadata {
-                    receiver: to.clone(),
-                    port: "transfer".to_string(),
-                    // hop chain to final receiver chain channel
-                    channel: pfm_config.hop_to_destination_chain_channel_id.to_string(),
-                }),
+                forward: Some(pfm_config.forward_metadata(to.clone(), None)),
             })?),
         }),
     };
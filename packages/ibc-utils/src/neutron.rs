@@ -15,11 +15,11 @@ use neutron_sdk::{
     query::min_ibc_fee::query_min_ibc_fee,
 };
 
-use crate::types::{ForwardMetadata, PacketForwardMiddlewareConfig, PacketMetadata};
+use crate::types::{
+    PacketForwardMiddlewareConfig, PacketMetadata, DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS,
+};
 
-// Default timeout for IbcTransfer is 600 seconds
-const DEFAULT_TIMEOUT_SECONDS: u64 = 600;
-const NTRN_DENOM: &str = "untrn";
+pub const NTRN_DENOM: &str = "untrn";
 
 #[allow(clippy::too_many_arguments)]
 pub fn ibc_send_message(
@@ -85,38 +85,31 @@ pub fn ibc_send_message(
             timeout_timestamp: env
                 .block
                 .time
-                .plus_seconds(timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS))
+                .plus_seconds(timeout_seconds.unwrap_or(DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS))
                 .nanos(),
             memo,
             fee: Some(get_transfer_fee(ibc_fee)),
         },
-        Some(pfm_config) => {
-            neutron_sdk::proto_types::neutron::transfer::MsgTransfer {
-                source_port: "transfer".to_string(),
-                source_channel: pfm_config.local_to_hop_chain_channel_id.to_string(),
-                sender: sender.to_string(),
-                receiver: pfm_config
-                    .hop_chain_receiver_address
-                    .clone()
-                    .unwrap_or("pfm".to_string()),
-                token: Some(coin),
-                timeout_height: None,
-                timeout_timestamp: env
-                    .block
-                    .time
-                    .plus_seconds(timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS))
-                    .nanos(),
-                memo: to_json_string(&PacketMetadata {
-                    forward: Some(ForwardMetadata {
-                        receiver: to.clone(),
-                        port: "transfer".to_string(),
-                        // hop chain to final receiver chain channel
-                        channel: pfm_config.hop_to_destination_chain_channel_id.to_string(),
-                    }),
-                })?,
-                fee: Some(get_transfer_fee(ibc_fee)),
-            }
-        }
+        Some(pfm_config) => neutron_sdk::proto_types::neutron::transfer::MsgTransfer {
+            source_port: "transfer".to_string(),
+            source_channel: pfm_config.local_to_hop_chain_channel_id.to_string(),
+            sender: sender.to_string(),
+            receiver: pfm_config
+                .hop_chain_receiver_address
+                .clone()
+                .unwrap_or("pfm".to_string()),
+            token: Some(coin),
+            timeout_height: None,
+            timeout_timestamp: env
+                .block
+                .time
+                .plus_seconds(timeout_seconds.unwrap_or(DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS))
+                .nanos(),
+            memo: to_json_string(&PacketMetadata {
+                forward: Some(pfm_config.forward_metadata(to.clone(), None)),
+            })?,
+            fee: Some(get_transfer_fee(ibc_fee)),
+        },
     };
 
     #[allow(deprecated)]
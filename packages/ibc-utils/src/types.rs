@@ -1,11 +1,122 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Coin, IbcDstCallback};
+use cosmwasm_std::{Coin, IbcDstCallback, StdError, StdResult};
+
+// Timeout of the IBC transfers that don't set one, unless the library is deployed with other transfer defaults
+pub const DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS: u64 = 600;
+
+// Bounds of the retries and timeout of the packets forwarded by the hop chain that can be configured
+pub const MAX_PFM_RETRIES: u8 = 10;
+pub const MIN_PFM_TIMEOUT_SECONDS: u64 = 60;
+pub const MAX_PFM_TIMEOUT_SECONDS: u64 = 604_800;
+
+/// Defaults applied by the transfer libraries to the transfers that don't set their own parameters,
+/// so that a deployment can tune them once instead of in every transfer
+#[cw_serde]
+pub struct TransferDefaults {
+    /// Timeout in seconds of the IBC transfers that don't set one
+    #[serde(default = "default_ibc_transfer_timeout")]
+    pub ibc_transfer_timeout: u64,
+}
+
+fn default_ibc_transfer_timeout() -> u64 {
+    DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS
+}
+
+impl Default for TransferDefaults {
+    fn default() -> Self {
+        TransferDefaults {
+            ibc_transfer_timeout: DEFAULT_IBC_TRANSFER_TIMEOUT_SECONDS,
+        }
+    }
+}
+
+impl TransferDefaults {
+    pub fn new(ibc_transfer_timeout: u64) -> Self {
+        TransferDefaults {
+            ibc_transfer_timeout,
+        }
+    }
+
+    /// Timeout of a transfer: `timeout_seconds` if it's set, the default timeout otherwise
+    pub fn ibc_transfer_timeout_or(&self, timeout_seconds: Option<u64>) -> u64 {
+        timeout_seconds.unwrap_or(self.ibc_transfer_timeout)
+    }
+}
 
 #[cw_serde]
 pub struct PacketForwardMiddlewareConfig {
     pub local_to_hop_chain_channel_id: String,
     pub hop_to_destination_chain_channel_id: String,
     pub hop_chain_receiver_address: Option<String>,
+    /// How many times the hop chain retries forwarding the packet after a timeout.
+    /// If not set, it's left out of the forward memo and the hop chain applies its own default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u8>,
+    /// Timeout in seconds of the packet forwarded by the hop chain.
+    /// If not set, it's left out of the forward memo and the hop chain applies its own default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+impl PacketForwardMiddlewareConfig {
+    /// Config of a hop leaving the retries and timeout to the hop chain
+    pub fn new(
+        local_to_hop_chain_channel_id: impl Into<String>,
+        hop_to_destination_chain_channel_id: impl Into<String>,
+        hop_chain_receiver_address: Option<String>,
+    ) -> Self {
+        PacketForwardMiddlewareConfig {
+            local_to_hop_chain_channel_id: local_to_hop_chain_channel_id.into(),
+            hop_to_destination_chain_channel_id: hop_to_destination_chain_channel_id.into(),
+            hop_chain_receiver_address,
+            retries: None,
+            timeout: None,
+        }
+    }
+
+    pub fn with_retries(mut self, retries: u8) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout_seconds: u64) -> Self {
+        self.timeout = Some(timeout_seconds);
+        self
+    }
+
+    pub fn validate(&self) -> StdResult<()> {
+        if self
+            .retries
+            .is_some_and(|retries| retries > MAX_PFM_RETRIES)
+        {
+            return Err(StdError::generic_err(format!(
+                "retries cannot be greater than {MAX_PFM_RETRIES}"
+            )));
+        }
+        if self.timeout.is_some_and(|timeout| {
+            !(MIN_PFM_TIMEOUT_SECONDS..=MAX_PFM_TIMEOUT_SECONDS).contains(&timeout)
+        }) {
+            return Err(StdError::generic_err(format!(
+                "timeout must be between {MIN_PFM_TIMEOUT_SECONDS} and {MAX_PFM_TIMEOUT_SECONDS} seconds"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Forward metadata sending the packet from the hop chain to the receiver on the destination chain
+    pub fn forward_metadata(&self, receiver: String, next: Option<String>) -> ForwardMetadata {
+        ForwardMetadata {
+            receiver,
+            port: "transfer".to_string(),
+            // hop chain to final receiver chain channel
+            channel: self.hop_to_destination_chain_channel_id.clone(),
+            // PFM parses the timeout as a duration
+            timeout: self.timeout.map(|timeout| format!("{timeout}s")),
+            retries: self.retries,
+            next,
+        }
+    }
 }
 
 // https://github.com/strangelove-ventures/packet-forward-middleware/blob/main/router/types/forward.go
@@ -19,6 +130,13 @@ pub struct ForwardMetadata {
     pub receiver: String,
     pub port: String,
     pub channel: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u8>,
+    // Memo of the forwarded packet, a JSON object encoded as a string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
 }
 
 #[cw_serde]
@@ -37,6 +155,91 @@ pub struct EurekaConfig {
     pub timeout: Option<u64>,
 }
 
+impl EurekaConfig {
+    pub fn builder() -> EurekaConfigBuilder {
+        EurekaConfigBuilder::default()
+    }
+
+    pub fn validate(&self) -> StdResult<()> {
+        for (field, value) in [
+            ("callback_contract", &self.callback_contract),
+            ("action_contract", &self.action_contract),
+            ("recover_address", &self.recover_address),
+            ("source_channel", &self.source_channel),
+        ] {
+            if value.is_empty() {
+                return Err(StdError::generic_err(format!("{field} cannot be empty")));
+            }
+        }
+        if self.timeout == Some(0) {
+            return Err(StdError::generic_err("timeout cannot be zero"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Builder for an `EurekaConfig`, which checks that all required fields are set
+#[derive(Default)]
+pub struct EurekaConfigBuilder {
+    callback_contract: Option<String>,
+    action_contract: Option<String>,
+    recover_address: Option<String>,
+    source_channel: Option<String>,
+    memo: Option<String>,
+    timeout: Option<u64>,
+}
+
+impl EurekaConfigBuilder {
+    pub fn callback(mut self, callback_contract: impl Into<String>) -> Self {
+        self.callback_contract = Some(callback_contract.into());
+        self
+    }
+
+    pub fn action(mut self, action_contract: impl Into<String>) -> Self {
+        self.action_contract = Some(action_contract.into());
+        self
+    }
+
+    pub fn recover_address(mut self, recover_address: impl Into<String>) -> Self {
+        self.recover_address = Some(recover_address.into());
+        self
+    }
+
+    pub fn source_channel(mut self, source_channel: impl Into<String>) -> Self {
+        self.source_channel = Some(source_channel.into());
+        self
+    }
+
+    pub fn memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    pub fn timeout(mut self, timeout_seconds: u64) -> Self {
+        self.timeout = Some(timeout_seconds);
+        self
+    }
+
+    pub fn build(self) -> StdResult<EurekaConfig> {
+        let required = |value: Option<String>, field: &str| {
+            value.ok_or_else(|| StdError::generic_err(format!("{field} is required")))
+        };
+
+        let config = EurekaConfig {
+            callback_contract: required(self.callback_contract, "callback_contract")?,
+            action_contract: required(self.action_contract, "action_contract")?,
+            recover_address: required(self.recover_address, "recover_address")?,
+            source_channel: required(self.source_channel, "source_channel")?,
+            memo: self.memo,
+            timeout: self.timeout,
+        };
+        config.validate()?;
+
+        Ok(config)
+    }
+}
+
 // Used for IBC Eureka transfers
 // Leverages https://github.com/cosmos/ibc-go/blob/16f51eb5635bc16c6361c44f2a963f4736d1cf8b/docs/docs/04-middleware/01-callbacks/05-end-users.md
 #[cw_serde]
@@ -124,6 +327,124 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_eureka_config_builder() {
+        let config = EurekaConfig::builder()
+            .callback("cosmos1callback")
+            .action("cosmos1action")
+            .recover_address("cosmos1recover")
+            .source_channel("08-wasm-1369")
+            .timeout(43200)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            config,
+            EurekaConfig {
+                callback_contract: "cosmos1callback".to_string(),
+                action_contract: "cosmos1action".to_string(),
+                recover_address: "cosmos1recover".to_string(),
+                source_channel: "08-wasm-1369".to_string(),
+                memo: None,
+                timeout: Some(43200),
+            }
+        );
+
+        // Serde round trip
+        let json = to_json_string(&config).unwrap();
+        let deserialized: EurekaConfig = from_json(&json).unwrap();
+        assert_eq!(deserialized, config);
+
+        // Optional fields can be omitted from the JSON
+        let deserialized: EurekaConfig = from_json(
+            r#"{"callback_contract":"cosmos1callback","action_contract":"cosmos1action","recover_address":"cosmos1recover","source_channel":"08-wasm-1369"}"#,
+        )
+        .unwrap();
+        assert_eq!(deserialized.memo, None);
+        assert_eq!(deserialized.timeout, None);
+    }
+
+    #[test]
+    fn test_eureka_config_builder_requires_fields() {
+        let err = EurekaConfig::builder()
+            .callback("cosmos1callback")
+            .action("cosmos1action")
+            .source_channel("08-wasm-1369")
+            .build()
+            .unwrap_err();
+        assert_eq!(err, StdError::generic_err("recover_address is required"));
+
+        let err = EurekaConfig::builder()
+            .callback("cosmos1callback")
+            .action("cosmos1action")
+            .recover_address("cosmos1recover")
+            .source_channel("08-wasm-1369")
+            .timeout(0)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, StdError::generic_err("timeout cannot be zero"));
+    }
+
+    #[test]
+    fn test_pfm_forward_memo_includes_retries_and_timeout() {
+        let pfm_config = PacketForwardMiddlewareConfig::new("channel-1", "channel-2", None)
+            .with_retries(5)
+            .with_timeout(1800);
+        pfm_config.validate().unwrap();
+
+        let memo = to_json_string(&PacketMetadata {
+            forward: Some(pfm_config.forward_metadata("cosmos1receiver".to_string(), None)),
+        })
+        .unwrap();
+        assert_eq!(
+            memo,
+            r#"{"forward":{"receiver":"cosmos1receiver","port":"transfer","channel":"channel-2","timeout":"1800s","retries":5}}"#
+        );
+
+        // Entries without retries and timeout keep the forward memo they had before the fields existed
+        let deserialized: PacketForwardMiddlewareConfig = from_json(
+            r#"{"local_to_hop_chain_channel_id":"channel-1","hop_to_destination_chain_channel_id":"channel-2","hop_chain_receiver_address":null}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            deserialized,
+            PacketForwardMiddlewareConfig::new("channel-1", "channel-2", None)
+        );
+        deserialized.validate().unwrap();
+        assert_eq!(
+            to_json_string(&PacketMetadata {
+                forward: Some(deserialized.forward_metadata("cosmos1receiver".to_string(), None)),
+            })
+            .unwrap(),
+            r#"{"forward":{"receiver":"cosmos1receiver","port":"transfer","channel":"channel-2"}}"#
+        );
+    }
+
+    #[test]
+    fn test_pfm_config_bounds() {
+        let pfm_config = PacketForwardMiddlewareConfig::new("channel-1", "channel-2", None);
+
+        assert_eq!(
+            pfm_config.clone().with_retries(11).validate().unwrap_err(),
+            StdError::generic_err("retries cannot be greater than 10")
+        );
+        for timeout in [0, 59, 604_801] {
+            assert_eq!(
+                pfm_config
+                    .clone()
+                    .with_timeout(timeout)
+                    .validate()
+                    .unwrap_err(),
+                StdError::generic_err("timeout must be between 60 and 604800 seconds")
+            );
+        }
+        pfm_config
+            .with_retries(MAX_PFM_RETRIES)
+            .with_timeout(MAX_PFM_TIMEOUT_SECONDS)
+            .validate()
+            .unwrap();
+    }
+
     #[test]
     fn test_eureka_memo_serialization() {
         // Create a sample struct
@@ -1,7 +1,19 @@
-use cosmwasm_std::{Addr, Storage};
-use valence_library_utils::error::{LibraryError, UnauthorizedReason};
+use std::collections::BTreeSet;
 
-use crate::state::PROCESSOR;
+use cosmwasm_std::{to_json_string, Addr, Api, Storage, Timestamp};
+use serde::Serialize;
+use valence_library_utils::{
+    error::{LibraryError, UnauthorizedReason},
+    msg::OwnerThreshold,
+};
+
+use crate::{
+    state::{
+        CheckedOwnerThreshold, PendingApproval, NEXT_APPROVAL_NONCE, OWNER_THRESHOLD,
+        PENDING_APPROVALS, PENDING_APPROVAL_NONCES, PROCESSOR,
+    },
+    APPROVAL_EXPIRY_SECONDS,
+};
 
 pub fn assert_processor(store: &dyn Storage, sender: &Addr) -> Result<(), LibraryError> {
     let processor = PROCESSOR.load(store)?;
@@ -12,3 +24,143 @@ pub fn assert_processor(store: &dyn Storage, sender: &Addr) -> Result<(), Librar
     }
     Ok(())
 }
+
+/// Outcome of the approval of a privileged action by its sender
+pub enum Approval {
+    // The action can be executed
+    Approved,
+    // The approval was recorded, but more owners must send the same action before it is executed
+    Pending {
+        approvals: usize,
+        threshold: u64,
+        nonce: u64,
+        expires_at: Timestamp,
+    },
+}
+
+/// Approves a privileged `action` on behalf of `sender`. Without an owner threshold the sender must be
+/// the owner, otherwise it must be one of the owners and the action is only approved once the threshold is met.
+/// The first approval of an action gives it a new nonce, and the approvals of the action expire
+/// `APPROVAL_EXPIRY_SECONDS` after it, so that old approvals can't complete a later identical action.
+pub fn approve_privileged(
+    store: &mut dyn Storage,
+    now: Timestamp,
+    sender: &Addr,
+    action: &impl Serialize,
+) -> Result<Approval, LibraryError> {
+    let Some(owner_threshold) = OWNER_THRESHOLD.may_load(store)? else {
+        cw_ownable::assert_owner(store, sender)?;
+        return Ok(Approval::Approved);
+    };
+
+    if !owner_threshold.owners.contains(sender) {
+        return Err(LibraryError::Unauthorized(
+            UnauthorizedReason::NotAllowed {},
+        ));
+    }
+
+    let key = to_json_string(action)?;
+    let pending = match PENDING_APPROVAL_NONCES.may_load(store, &key)? {
+        Some(nonce) => {
+            let pending = PENDING_APPROVALS.load(store, nonce)?;
+            if now < pending.expires_at {
+                Some((nonce, pending))
+            } else {
+                remove_pending_approval(store, nonce, &pending);
+                None
+            }
+        }
+        None => None,
+    };
+    let (nonce, mut pending) = match pending {
+        Some(pending) => pending,
+        None => {
+            let nonce = NEXT_APPROVAL_NONCE.may_load(store)?.unwrap_or_default();
+            NEXT_APPROVAL_NONCE.save(store, &(nonce + 1))?;
+            let pending = PendingApproval {
+                action: key.clone(),
+                approvers: vec![],
+                expires_at: now.plus_seconds(APPROVAL_EXPIRY_SECONDS),
+            };
+            (nonce, pending)
+        }
+    };
+
+    if pending.approvers.contains(sender) {
+        return Err(LibraryError::ExecutionError(format!(
+            "Action was already approved by {sender}"
+        )));
+    }
+    pending.approvers.push(sender.clone());
+
+    if (pending.approvers.len() as u64) < owner_threshold.threshold {
+        PENDING_APPROVALS.save(store, nonce, &pending)?;
+        PENDING_APPROVAL_NONCES.save(store, &key, &nonce)?;
+        return Ok(Approval::Pending {
+            approvals: pending.approvers.len(),
+            threshold: owner_threshold.threshold,
+            nonce,
+            expires_at: pending.expires_at,
+        });
+    }
+
+    remove_pending_approval(store, nonce, &pending);
+    Ok(Approval::Approved)
+}
+
+/// Drops the pending approval with `nonce` on behalf of `sender`, which must be one of the owners
+pub fn cancel_approval(
+    store: &mut dyn Storage,
+    sender: &Addr,
+    nonce: u64,
+) -> Result<PendingApproval, LibraryError> {
+    let is_owner = OWNER_THRESHOLD
+        .may_load(store)?
+        .is_some_and(|owner_threshold| owner_threshold.owners.contains(sender));
+    if !is_owner {
+        return Err(LibraryError::Unauthorized(
+            UnauthorizedReason::NotAllowed {},
+        ));
+    }
+
+    let pending = PENDING_APPROVALS.may_load(store, nonce)?.ok_or_else(|| {
+        LibraryError::ExecutionError(format!("No pending approval with nonce {nonce}"))
+    })?;
+    remove_pending_approval(store, nonce, &pending);
+    Ok(pending)
+}
+
+fn remove_pending_approval(store: &mut dyn Storage, nonce: u64, pending: &PendingApproval) {
+    PENDING_APPROVALS.remove(store, nonce);
+    PENDING_APPROVAL_NONCES.remove(store, &pending.action);
+}
+
+/// Checks that the owners are valid and distinct, and that the threshold can be met by them
+pub fn validate_owner_threshold(
+    api: &dyn Api,
+    owner_threshold: &OwnerThreshold,
+) -> Result<CheckedOwnerThreshold, LibraryError> {
+    let mut owners = vec![];
+    let mut distinct = BTreeSet::new();
+    for owner in &owner_threshold.owners {
+        let owner = api.addr_validate(owner)?;
+        if !distinct.insert(owner.clone()) {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Invalid owner threshold: {owner} is an owner more than once."
+            )));
+        }
+        owners.push(owner);
+    }
+
+    if owner_threshold.threshold == 0 || owner_threshold.threshold > owners.len() as u64 {
+        return Err(LibraryError::ConfigurationError(format!(
+            "Invalid owner threshold: threshold must be between 1 and the {} owners.",
+            owners.len()
+        )));
+    }
+
+    Ok(CheckedOwnerThreshold {
+        owners,
+        threshold: owner_threshold.threshold,
+    })
+}
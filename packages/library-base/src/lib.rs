@@ -1,20 +1,31 @@
-use cosmwasm_std::{CustomQuery, DepsMut, Env, MessageInfo, Response};
-use helpers::assert_processor;
+use cosmwasm_std::{Addr, CustomQuery, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use helpers::{
+    approve_privileged, assert_processor, cancel_approval, validate_owner_threshold, Approval,
+};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use state::PROCESSOR;
+use state::{
+    LastError, LAST_ERROR, LAST_EXECUTION_NONCES, MIGRATION_AUTHORIZED, OWNER_THRESHOLD, PAUSED,
+    PENDING_APPROVALS, PENDING_APPROVAL_NONCES, PROCESSOR,
+};
 use valence_library_utils::{
     error::LibraryError,
-    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation},
+    msg::{ExecuteMsg, InstantiateMsg, LibraryConfigValidation, MigrateMsg},
     raw_config::save_raw_library_config,
-    LibraryConfigUpdateTrait,
+    LibraryAccountType, LibraryConfigUpdateTrait,
 };
 
 pub mod helpers;
 pub mod state;
 
-pub use crate::state::{get_ownership, get_processor, load_config, load_raw_config, save_config};
+/// Time after which the approvals of a privileged action that didn't reach the owner threshold are dropped (7 days)
+pub const APPROVAL_EXPIRY_SECONDS: u64 = 604_800;
+
+pub use crate::state::{
+    get_last_error, get_owner_threshold, get_ownership, get_processor, is_paused, load_config,
+    load_raw_config, save_config,
+};
 
 pub fn instantiate<T, U>(
     deps: DepsMut,
@@ -57,29 +68,122 @@ pub fn execute<M, Q, T, U, V>(
 ) -> Result<Response<M>, LibraryError>
 where
     Q: CustomQuery,
+    T: Serialize,
     U: Serialize + DeserializeOwned,
     V: LibraryConfigUpdateTrait + Serialize + DeserializeOwned,
 {
+    if msg.is_privileged() {
+        if let Approval::Pending {
+            approvals,
+            threshold,
+            nonce,
+            expires_at,
+        } = approve_privileged(deps.storage, env.block.time, &info.sender, &msg)?
+        {
+            return Ok(Response::default()
+                .add_attribute("method", "approve")
+                .add_attribute("approver", info.sender)
+                .add_attribute("approvals", format!("{approvals}/{threshold}"))
+                .add_attribute("approval_nonce", nonce.to_string())
+                .add_attribute("expires_at", expires_at.seconds().to_string()));
+        }
+    }
+
     match msg {
         ExecuteMsg::ProcessFunction(function) => {
             assert_processor(deps.as_ref().storage, &info.sender)?;
+            if is_paused(deps.storage)? {
+                return Err(LibraryError::LibraryPaused {});
+            }
             let config = load_config(deps.storage)?;
             process_function(deps, env, info, function, config)
         }
+        ExecuteMsg::ProcessFunctionWithNonce { function, nonce } => {
+            assert_processor(deps.as_ref().storage, &info.sender)?;
+            if is_paused(deps.storage)? {
+                return Err(LibraryError::LibraryPaused {});
+            }
+            if let Some(last_nonce) = LAST_EXECUTION_NONCES.may_load(deps.storage, &info.sender)? {
+                if nonce <= last_nonce {
+                    return Err(LibraryError::ExecutionError(format!(
+                        "Execution nonce {nonce} must be greater than the last executed nonce {last_nonce}"
+                    )));
+                }
+            }
+            // The nonce is recorded before executing, the whole execution is reverted if the function fails
+            LAST_EXECUTION_NONCES.save(deps.storage, &info.sender, &nonce)?;
+            let config = load_config(deps.storage)?;
+            let response = process_function(deps, env, info, function, config)?;
+            Ok(response.add_attribute("nonce", nonce.to_string()))
+        }
         ExecuteMsg::UpdateConfig { new_config } => {
-            cw_ownable::assert_owner(deps.as_ref().storage, &info.sender)?;
             // We update the raw storage
             new_config.update_raw(deps.storage)?;
             update_config(deps, env, info, new_config)?;
             Ok(Response::new().add_attribute("method", "update_config"))
         }
         ExecuteMsg::UpdateProcessor { processor } => {
-            cw_ownable::assert_owner(deps.as_ref().storage, &info.sender)?;
             PROCESSOR.save(deps.storage, &deps.api.addr_validate(&processor)?)?;
             Ok(Response::default()
                 .add_attribute("method", "update_processor")
                 .add_attribute("processor", processor))
         }
+        ExecuteMsg::Pause {} => {
+            PAUSED.save(deps.storage, &true)?;
+            Ok(Response::default().add_attribute("method", "pause"))
+        }
+        ExecuteMsg::Unpause {} => {
+            PAUSED.remove(deps.storage);
+            Ok(Response::default().add_attribute("method", "unpause"))
+        }
+        ExecuteMsg::UpdateOwnerThreshold { owner_threshold } => {
+            // Approvals given by the previous owners are dropped
+            PENDING_APPROVALS.clear(deps.storage);
+            PENDING_APPROVAL_NONCES.clear(deps.storage);
+            let response = Response::default().add_attribute("method", "update_owner_threshold");
+            match owner_threshold {
+                Some(owner_threshold) => {
+                    let owner_threshold = validate_owner_threshold(deps.api, &owner_threshold)?;
+                    OWNER_THRESHOLD.save(deps.storage, &owner_threshold)?;
+                    Ok(response.add_attribute(
+                        "owner_threshold",
+                        format!(
+                            "{}/{}",
+                            owner_threshold.threshold,
+                            owner_threshold.owners.len()
+                        ),
+                    ))
+                }
+                None => {
+                    OWNER_THRESHOLD.remove(deps.storage);
+                    Ok(response.add_attribute("owner_threshold", "none"))
+                }
+            }
+        }
+        ExecuteMsg::CancelApproval { nonce } => {
+            cancel_approval(deps.storage, &info.sender, nonce)?;
+            Ok(Response::default()
+                .add_attribute("method", "cancel_approval")
+                .add_attribute("approval_nonce", nonce.to_string()))
+        }
+        ExecuteMsg::AuthorizeMigration {} => {
+            MIGRATION_AUTHORIZED.save(deps.storage, &true)?;
+            Ok(Response::default().add_attribute("method", "authorize_migration"))
+        }
+        ExecuteMsg::RecordError { error } => {
+            // Recorded even if the library is paused, as the failure may come from the pause
+            assert_processor(deps.as_ref().storage, &info.sender)?;
+            LAST_ERROR.save(
+                deps.storage,
+                &LastError {
+                    error,
+                    height: env.block.height,
+                },
+            )?;
+            Ok(Response::default()
+                .add_attribute("method", "record_error")
+                .add_attribute("height", env.block.height.to_string()))
+        }
         ExecuteMsg::UpdateOwnership(action) => {
             let result = cw_ownable::update_ownership(
                 deps.into_empty(),
@@ -94,3 +198,67 @@ where
         }
     }
 }
+
+/// Migrates the library in place, keeping its owner and processor.
+/// Migrations from another contract or from a newer version are rejected, as well as the migrations
+/// of a library with an owner threshold that its owners did not authorize.
+pub fn migrate<Q, V>(
+    deps: DepsMut<Q>,
+    env: Env,
+    contract_name: &str,
+    contract_version: &str,
+    msg: MigrateMsg<V>,
+    update_config: UpdateConfig<Q, V>,
+) -> Result<Response, LibraryError>
+where
+    Q: CustomQuery,
+    V: LibraryConfigUpdateTrait + Serialize + DeserializeOwned,
+{
+    // An authorization is only valid for a single migration
+    let migration_authorized = MIGRATION_AUTHORIZED
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    MIGRATION_AUTHORIZED.remove(deps.storage);
+    if OWNER_THRESHOLD.may_load(deps.storage)?.is_some() && !migration_authorized {
+        return Err(LibraryError::ExecutionError(
+            "Migration was not authorized by the owners".to_string(),
+        ));
+    }
+
+    let previous_version = cw2::get_contract_version(deps.storage)?.version;
+    cw2::ensure_from_older_version(deps.storage, contract_name, contract_version)?;
+
+    let mut response = Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", previous_version)
+        .add_attribute("to_version", contract_version);
+
+    if let Some(config_update) = msg.config_update {
+        // The config is patched on behalf of the owner, as with a regular config update
+        let sender = get_ownership(deps.storage)?
+            .owner
+            .unwrap_or_else(|| env.contract.address.clone());
+        config_update.update_raw(deps.storage)?;
+        update_config(
+            deps,
+            env,
+            MessageInfo {
+                sender,
+                funds: vec![],
+            },
+            config_update,
+        )?;
+        response = response.add_attribute("config_updated", "true");
+    }
+
+    Ok(response)
+}
+
+/// Resolves `account` into the address it references, for the `ResolveAccount` query. Nothing is stored,
+/// so external tooling can resolve the accounts of a config without executing the library
+pub fn resolve_account<C: CustomQuery>(
+    deps: Deps<C>,
+    account: &LibraryAccountType,
+) -> StdResult<Addr> {
+    account.resolve(deps.api, &deps.querier)
+}
@@ -1,13 +1,50 @@
 use std::any::type_name;
 
-use cosmwasm_std::{from_json, to_json_vec, Addr, StdError, StdResult, Storage};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{from_json, to_json_vec, Addr, StdError, StdResult, Storage, Timestamp};
 use cw_ownable::Ownership;
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 use serde::{de::DeserializeOwned, Serialize};
-use valence_library_utils::raw_config::load_raw_library_config;
+use valence_library_utils::{msg::LastErrorResponse, raw_config::load_raw_library_config};
 
 pub const CONFIG_KEY: &[u8] = b"config";
 pub const PROCESSOR: Item<Addr> = Item::new("processor");
+pub const PAUSED: Item<bool> = Item::new("paused");
+// Last execution nonce executed for each sender, nonces only go up
+pub const LAST_EXECUTION_NONCES: Map<&Addr, u64> = Map::new("last_execution_nonces");
+// Owners approving the privileged actions, the single owner of cw_ownable is used if not set
+pub const OWNER_THRESHOLD: Item<CheckedOwnerThreshold> = Item::new("owner_threshold");
+// Privileged actions waiting for the approvals of the owners, by the nonce of their approval
+pub const PENDING_APPROVALS: Map<u64, PendingApproval> = Map::new("pending_approvals_by_nonce");
+// Nonce of the pending approval of each privileged action, keyed by the JSON of the action
+pub const PENDING_APPROVAL_NONCES: Map<&str, u64> = Map::new("pending_approval_nonces");
+// Nonce given to the next privileged action submitted for approval, never reused
+pub const NEXT_APPROVAL_NONCE: Item<u64> = Item::new("next_approval_nonce");
+// Whether the owners authorized the next migration of a library with an owner threshold
+pub const MIGRATION_AUTHORIZED: Item<bool> = Item::new("migration_authorized");
+// Error of the last failed execution of the library, recorded by the processor
+pub const LAST_ERROR: Item<LastError> = Item::new("last_error");
+
+#[cw_serde]
+pub struct CheckedOwnerThreshold {
+    pub owners: Vec<Addr>,
+    pub threshold: u64,
+}
+
+#[cw_serde]
+pub struct PendingApproval {
+    // JSON of the privileged action
+    pub action: String,
+    pub approvers: Vec<Addr>,
+    // Approvals not completed by then are dropped, the action must be approved again
+    pub expires_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct LastError {
+    pub error: String,
+    pub height: u64,
+}
 
 pub fn get_ownership(store: &dyn Storage) -> StdResult<Ownership<Addr>> {
     cw_ownable::get_ownership(store)
@@ -17,6 +54,24 @@ pub fn get_processor(store: &dyn Storage) -> StdResult<Addr> {
     PROCESSOR.load(store)
 }
 
+pub fn get_owner_threshold(store: &dyn Storage) -> StdResult<Option<CheckedOwnerThreshold>> {
+    OWNER_THRESHOLD.may_load(store)
+}
+
+pub fn get_last_error(store: &dyn Storage) -> StdResult<LastErrorResponse> {
+    Ok(LAST_ERROR
+        .may_load(store)?
+        .map(|last_error| LastErrorResponse {
+            last_error: Some(last_error.error),
+            last_error_height: Some(last_error.height),
+        })
+        .unwrap_or_default())
+}
+
+pub fn is_paused(store: &dyn Storage) -> StdResult<bool> {
+    Ok(PAUSED.may_load(store)?.unwrap_or_default())
+}
+
 pub fn save_config<T>(store: &mut dyn Storage, config: &T) -> StdResult<()>
 where
     T: Serialize + DeserializeOwned,
@@ -0,0 +1,73 @@
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, CustomQuery, QuerierWrapper, Reply, ReplyOn, Response,
+};
+
+use crate::{
+    error::LibraryError,
+    events::{BalanceSnapshotEvent, STAGE_AFTER, STAGE_BEFORE},
+};
+
+/// Reply id of the last message of an operation whose balances are snapshotted,
+/// kept out of the range of the reply ids used by the libraries
+pub const BALANCE_SNAPSHOT_REPLY_ID: u64 = u64::MAX;
+
+/// Adds the snapshots of the balances of the accounts before the operation to its response, and
+/// replies to its last message to snapshot them again once the operation is executed. The library
+/// must handle `BALANCE_SNAPSHOT_REPLY_ID` with `reply_balance_snapshots`. If the operation sends
+/// no message, nothing moves and the balances after are snapshotted right away.
+pub fn with_balance_snapshots<C: CustomQuery>(
+    querier: &QuerierWrapper<C>,
+    mut response: Response,
+    accounts: &[&Addr],
+) -> Result<Response, LibraryError> {
+    let before = query_snapshots(querier, accounts, STAGE_BEFORE)?;
+
+    match response.messages.last_mut() {
+        Some(last) if last.reply_on == ReplyOn::Never => {
+            last.id = BALANCE_SNAPSHOT_REPLY_ID;
+            last.reply_on = ReplyOn::Success;
+            last.payload = to_json_binary(accounts)?;
+            Ok(response.add_events(before))
+        }
+        Some(_) => Err(LibraryError::ExecutionError(
+            "Balances can't be snapshotted after a message that is already replied to".to_string(),
+        )),
+        None => {
+            let after: Vec<BalanceSnapshotEvent> = before
+                .iter()
+                .cloned()
+                .map(|snapshot| BalanceSnapshotEvent {
+                    stage: STAGE_AFTER.to_string(),
+                    ..snapshot
+                })
+                .collect();
+            Ok(response.add_events(before).add_events(after))
+        }
+    }
+}
+
+/// Snapshots the balances of the accounts once the operation is executed, on the reply to its last message
+pub fn reply_balance_snapshots<C: CustomQuery>(
+    querier: &QuerierWrapper<C>,
+    msg: Reply,
+) -> Result<Response, LibraryError> {
+    let accounts: Vec<Addr> = from_json(&msg.payload)?;
+    let accounts: Vec<&Addr> = accounts.iter().collect();
+
+    Ok(Response::new().add_events(query_snapshots(querier, &accounts, STAGE_AFTER)?))
+}
+
+fn query_snapshots<C: CustomQuery>(
+    querier: &QuerierWrapper<C>,
+    accounts: &[&Addr],
+    stage: &str,
+) -> Result<Vec<BalanceSnapshotEvent>, LibraryError> {
+    accounts
+        .iter()
+        .map(|account| {
+            #[allow(deprecated)]
+            let balances = querier.query_all_balances(*account)?;
+            Ok(BalanceSnapshotEvent::new(stage, account.as_str(), balances))
+        })
+        .collect()
+}
@@ -18,6 +18,9 @@ pub enum LibraryError {
 
     #[error("Execution error: {0}")]
     ExecutionError(String),
+
+    #[error("Library is paused")]
+    LibraryPaused {},
 }
 
 #[derive(Error, Debug, PartialEq)]
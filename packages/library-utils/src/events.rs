@@ -0,0 +1,178 @@
+use cosmwasm_std::{Coin, Event, Uint128};
+
+/// Type of the event emitted by the transfer libraries on every transfer execution.
+/// Once emitted by a contract, the event type is prefixed with `wasm-`.
+pub const TRANSFER_EVENT_TYPE: &str = "valence.transfer";
+
+pub const METHOD_KEY: &str = "method";
+pub const STATUS_KEY: &str = "status";
+pub const DENOM_KEY: &str = "denom";
+pub const INPUT_BALANCE_KEY: &str = "input_balance";
+pub const AMOUNT_KEY: &str = "amount";
+pub const CHANNEL_ID_KEY: &str = "channel_id";
+pub const DESTINATION_DOMAIN_KEY: &str = "destination_domain";
+pub const SKIP_REASON_KEY: &str = "skip_reason";
+
+pub const STATUS_TRANSFERRED: &str = "transferred";
+pub const STATUS_SKIPPED: &str = "skipped";
+
+/// Type of the event emitted by the liquidity libraries when a config update repoints them to another pool
+pub const POOL_UPDATE_EVENT_TYPE: &str = "valence.pool_update";
+
+pub const OLD_POOL_KEY: &str = "old_pool";
+pub const NEW_POOL_KEY: &str = "new_pool";
+
+/// Structured description of a transfer execution, emitted as a `valence.transfer` event.
+/// Optional attributes are only emitted when known, e.g. the input balance is not queried
+/// by every library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferEvent {
+    pub method: String,
+    pub denom: String,
+    pub input_balance: Option<Uint128>,
+    pub amount: Option<Uint128>,
+    pub channel_id: Option<String>,
+    pub destination_domain: Option<u32>,
+    pub skip_reason: Option<String>,
+}
+
+impl TransferEvent {
+    pub fn new(method: impl Into<String>, denom: impl Into<String>) -> Self {
+        TransferEvent {
+            method: method.into(),
+            denom: denom.into(),
+            input_balance: None,
+            amount: None,
+            channel_id: None,
+            destination_domain: None,
+            skip_reason: None,
+        }
+    }
+
+    pub fn with_input_balance(mut self, input_balance: Uint128) -> Self {
+        self.input_balance = Some(input_balance);
+        self
+    }
+
+    pub fn with_amount(mut self, amount: Uint128) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn with_channel_id(mut self, channel_id: impl Into<String>) -> Self {
+        self.channel_id = Some(channel_id.into());
+        self
+    }
+
+    pub fn with_destination_domain(mut self, destination_domain: u32) -> Self {
+        self.destination_domain = Some(destination_domain);
+        self
+    }
+
+    /// Marks the transfer as a no-op for the given reason
+    pub fn skipped(mut self, reason: impl Into<String>) -> Self {
+        self.skip_reason = Some(reason.into());
+        self
+    }
+}
+
+impl From<TransferEvent> for Event {
+    fn from(transfer: TransferEvent) -> Self {
+        let status = match transfer.skip_reason {
+            Some(_) => STATUS_SKIPPED,
+            None => STATUS_TRANSFERRED,
+        };
+
+        let mut event = Event::new(TRANSFER_EVENT_TYPE)
+            .add_attribute(METHOD_KEY, transfer.method)
+            .add_attribute(STATUS_KEY, status)
+            .add_attribute(DENOM_KEY, transfer.denom);
+
+        if let Some(input_balance) = transfer.input_balance {
+            event = event.add_attribute(INPUT_BALANCE_KEY, input_balance);
+        }
+        if let Some(amount) = transfer.amount {
+            event = event.add_attribute(AMOUNT_KEY, amount);
+        }
+        if let Some(channel_id) = transfer.channel_id {
+            event = event.add_attribute(CHANNEL_ID_KEY, channel_id);
+        }
+        if let Some(destination_domain) = transfer.destination_domain {
+            event = event.add_attribute(DESTINATION_DOMAIN_KEY, destination_domain.to_string());
+        }
+        if let Some(skip_reason) = transfer.skip_reason {
+            event = event.add_attribute(SKIP_REASON_KEY, skip_reason);
+        }
+
+        event
+    }
+}
+
+/// Pool of a liquidity library changed by a config update, emitted as a `valence.pool_update` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolUpdateEvent {
+    pub old_pool: String,
+    pub new_pool: String,
+}
+
+impl PoolUpdateEvent {
+    pub fn new(old_pool: impl Into<String>, new_pool: impl Into<String>) -> Self {
+        PoolUpdateEvent {
+            old_pool: old_pool.into(),
+            new_pool: new_pool.into(),
+        }
+    }
+}
+
+impl From<PoolUpdateEvent> for Event {
+    fn from(pool_update: PoolUpdateEvent) -> Self {
+        Event::new(POOL_UPDATE_EVENT_TYPE)
+            .add_attribute(OLD_POOL_KEY, pool_update.old_pool)
+            .add_attribute(NEW_POOL_KEY, pool_update.new_pool)
+    }
+}
+
+/// Type of the event emitted by the libraries configured to snapshot the balances of the accounts they touch
+pub const BALANCE_SNAPSHOT_EVENT_TYPE: &str = "valence.balance_snapshot";
+
+pub const STAGE_KEY: &str = "stage";
+pub const ACCOUNT_KEY: &str = "account";
+pub const BALANCES_KEY: &str = "balances";
+
+pub const STAGE_BEFORE: &str = "before";
+pub const STAGE_AFTER: &str = "after";
+
+/// Balances of all the denoms held by an account before or after an operation,
+/// emitted as a `valence.balance_snapshot` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceSnapshotEvent {
+    pub stage: String,
+    pub account: String,
+    pub balances: Vec<Coin>,
+}
+
+impl BalanceSnapshotEvent {
+    pub fn new(stage: impl Into<String>, account: impl Into<String>, balances: Vec<Coin>) -> Self {
+        BalanceSnapshotEvent {
+            stage: stage.into(),
+            account: account.into(),
+            balances,
+        }
+    }
+}
+
+impl From<BalanceSnapshotEvent> for Event {
+    fn from(snapshot: BalanceSnapshotEvent) -> Self {
+        let balances = snapshot
+            .balances
+            .iter()
+            .map(|coin| coin.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        Event::new(BALANCE_SNAPSHOT_EVENT_TYPE)
+            .add_attribute(STAGE_KEY, snapshot.stage)
+            .add_attribute(ACCOUNT_KEY, snapshot.account)
+            .add_attribute(BALANCES_KEY, balances)
+    }
+}
@@ -0,0 +1,92 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Decimal, Uint128};
+
+use crate::{denoms::CheckedDenom, error::LibraryError};
+
+#[cw_serde]
+/// Struct representing the amount of the chain gas denom always left in the input account,
+/// so that it can keep paying fees for the subsequent messages of a program.
+pub struct GasReserve {
+    /// The gas denom of the chain the library is deployed on.
+    pub denom: String,
+    /// The amount of the gas denom that is never spent by the library.
+    pub amount: Uint128,
+    /// The estimated fee of the message sent by the library, reserved on top of the amount.
+    #[serde(default)]
+    pub estimated_fee: Option<GasFeeEstimate>,
+}
+
+#[cw_serde]
+/// Struct representing the estimated fee of a message sent by a library, paid in the gas denom.
+pub struct GasFeeEstimate {
+    /// The gas used by the message.
+    pub gas_limit: u64,
+    /// The price of a unit of gas, in the gas denom.
+    pub gas_price: Decimal,
+}
+
+impl GasReserve {
+    pub fn new(denom: impl Into<String>, amount: impl Into<Uint128>) -> Self {
+        GasReserve {
+            denom: denom.into(),
+            amount: amount.into(),
+            estimated_fee: None,
+        }
+    }
+
+    pub fn with_estimated_fee(mut self, gas_limit: u64, gas_price: Decimal) -> Self {
+        self.estimated_fee = Some(GasFeeEstimate {
+            gas_limit,
+            gas_price,
+        });
+        self
+    }
+
+    /// Returns the amount of the gas denom put aside: the reserve and the estimated fee of the message,
+    /// rounded up so that it always covers the fee.
+    pub fn reserved_amount(&self) -> Uint128 {
+        let fee = self
+            .estimated_fee
+            .as_ref()
+            .map(|estimate| Uint128::from(estimate.gas_limit).mul_ceil(estimate.gas_price))
+            .unwrap_or_default();
+        self.amount.saturating_add(fee)
+    }
+
+    /// Returns the part of `balance` that can be spent for `denom`,
+    /// the reserve only being put aside for the gas denom.
+    pub fn available_balance(&self, denom: &CheckedDenom, balance: Uint128) -> Uint128 {
+        match denom {
+            CheckedDenom::Native(denom) if *denom == self.denom => {
+                balance.saturating_sub(self.reserved_amount())
+            }
+            _ => balance,
+        }
+    }
+
+    /// Validates the denom of the reserve, and that it puts something aside
+    pub fn validate(&self) -> Result<(), LibraryError> {
+        if self.denom.is_empty() {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid gas reserve: denom cannot be empty.".to_string(),
+            ));
+        }
+        match &self.estimated_fee {
+            // The estimated fee alone can be reserved
+            Some(estimate) if estimate.gas_limit == 0 || estimate.gas_price.is_zero() => {
+                return Err(LibraryError::ConfigurationError(
+                    "Invalid gas reserve: estimated gas limit and gas price cannot be zero."
+                        .to_string(),
+                ));
+            }
+            Some(_) => {}
+            None if self.amount.is_zero() => {
+                return Err(LibraryError::ConfigurationError(
+                    "Invalid gas reserve: amount cannot be zero.".to_string(),
+                ));
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
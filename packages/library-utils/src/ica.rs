@@ -1,4 +1,7 @@
-use cosmwasm_std::{to_json_binary, Addr, AnyMsg, CosmosMsg, Deps, StdError, StdResult, WasmMsg};
+use cosmwasm_std::{
+    to_json_binary, Addr, AnyMsg, Coin, CosmosMsg, CustomQuery, Deps, QuerierWrapper, StdError,
+    StdResult, WasmMsg,
+};
 use valence_account_utils::ica::{IcaState, QueryMsg};
 
 /// Helper function to execute proto messages using the Valence interchain account
@@ -10,14 +13,57 @@ pub fn execute_on_behalf_of(msgs: Vec<AnyMsg>, account: &Addr) -> StdResult<Cosm
     }))
 }
 
+/// Same as `execute_on_behalf_of`, with the library being called back with an `IcaCallbackMsg`
+/// once the ICA tx is acknowledged or times out
+pub fn execute_on_behalf_of_with_callback(
+    msgs: Vec<AnyMsg>,
+    account: &Addr,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: account.to_string(),
+        msg: to_json_binary(
+            &valence_account_utils::ica::ExecuteMsg::ExecuteIcaMsgWithCallback { msgs },
+        )?,
+        funds: vec![],
+    }))
+}
+
+/// Helper to deduct the amounts spent by the ICA from its last known balances, so that balance
+/// based amounts don't spend them again before the balances are refreshed
+pub fn deduct_remote_ica_balances(balances: Vec<Coin>, account: &Addr) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: account.to_string(),
+        msg: to_json_binary(
+            &valence_account_utils::ica::ExecuteMsg::DeductRemoteBalances { balances },
+        )?,
+        funds: vec![],
+    }))
+}
+
 /// Helper to get the remote address of the ICA after verifying it's created
 pub fn get_remote_ica_address(deps: Deps, contract_addr: &str) -> StdResult<String> {
-    let ica_state: IcaState = deps
-        .querier
-        .query_wasm_smart(contract_addr, &QueryMsg::IcaState {})?;
+    query_remote_ica_address(&deps.querier, contract_addr)
+}
+
+/// Same as `get_remote_ica_address`, for libraries using a custom querier
+pub fn query_remote_ica_address<C: CustomQuery>(
+    querier: &QuerierWrapper<C>,
+    contract_addr: &str,
+) -> StdResult<String> {
+    let ica_state: IcaState = querier.query_wasm_smart(contract_addr, &QueryMsg::IcaState {})?;
 
     match ica_state {
         IcaState::Created(ica_information) => Ok(ica_information.address),
         _ => Err(StdError::generic_err("ICA not created")),
     }
 }
+
+/// Helper to get the last known balance of a denom held by the ICA on the remote chain
+pub fn get_remote_ica_balance(deps: Deps, contract_addr: &str, denom: &str) -> StdResult<Coin> {
+    deps.querier.query_wasm_smart(
+        contract_addr,
+        &QueryMsg::RemoteBalance {
+            denom: denom.to_string(),
+        },
+    )
+}
@@ -1,23 +1,36 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{to_json_binary, Addr, CosmosMsg, StdResult, Storage, SubMsg, WasmMsg};
+use cosmwasm_std::{
+    to_json_binary, Addr, CosmosMsg, CustomQuery, QuerierWrapper, StdResult, Storage, SubMsg,
+    WasmMsg,
+};
 
 pub mod denoms {
     pub use cw_denom::{CheckedDenom, DenomError, UncheckedDenom};
 }
 
+pub mod balance_snapshot;
 pub mod error;
+pub mod events;
+pub mod gas_reserve;
 pub mod ica;
 pub mod library_account_type;
 pub mod liquidity_utils;
 pub mod msg;
+pub mod nft;
+pub mod pfm;
+pub mod predicate;
+pub mod price_source;
 pub mod raw_config;
+pub mod rounding;
+pub mod stats;
+pub mod units;
 
 #[cfg(feature = "testing")]
 pub mod testing;
 
 pub type Id = u64;
 
-pub use library_account_type::LibraryAccountType;
+pub use library_account_type::{LibraryAccountType, ProgramRoles};
 
 pub trait LibraryConfigUpdateTrait {
     fn update_raw(&self, storage: &mut dyn Storage) -> StdResult<()>;
@@ -40,6 +53,21 @@ pub fn execute_on_behalf_of(msgs: Vec<CosmosMsg>, account: &Addr) -> StdResult<C
     }))
 }
 
+// This is a helper function to check whether a library is approved on a (base or interchain) account
+pub fn is_library_approved<Q: CustomQuery>(
+    querier: &QuerierWrapper<Q>,
+    account: &Addr,
+    library: &Addr,
+) -> StdResult<bool> {
+    let approved_libraries: Vec<String> = querier.query_wasm_smart(
+        account,
+        &valence_account_utils::msg::QueryMsg::ListApprovedLibraries {},
+    )?;
+    Ok(approved_libraries
+        .iter()
+        .any(|approved| approved == library.as_str()))
+}
+
 pub fn execute_submsgs_on_behalf_of(
     msgs: Vec<SubMsg>,
     payload: Option<String>,
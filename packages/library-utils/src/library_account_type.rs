@@ -1,9 +1,11 @@
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, StdError, StdResult};
+use cosmwasm_std::{Addr, Api, CustomQuery, QuerierWrapper, StdError, StdResult};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 
-use crate::Id;
+use crate::{ica::query_remote_ica_address, Id};
 
 pub const LIBRARY_ACCOUNT_RAW_PLACEHOLDER: &str = "|lib_acc_placeholder|";
 
@@ -19,6 +21,13 @@ pub enum LibraryAccountType {
     AccountId(Id),
     #[serde(rename = "|library_id|", alias = "library_id")]
     LibraryId(Id),
+    /// Account referenced by its role in the program (e.g. "deposit"), resolved through a `ProgramRoles` registry
+    #[serde(rename = "|role|", alias = "role")]
+    Role(String),
+    /// Address on the remote chain of the ICA of a Valence interchain account, queried from it when the
+    /// library executes so that it follows the ICA if it is registered again. Only usable where a remote address is expected
+    #[serde(rename = "|ica_remote|", alias = "ica_remote")]
+    IcaRemote { ica_library: Addr },
 }
 
 impl LibraryAccountType {
@@ -29,6 +38,10 @@ impl LibraryAccountType {
             LibraryAccountType::AccountId(_) | LibraryAccountType::LibraryId(_) => Err(
                 StdError::generic_err("LibraryAccountType must be an address"),
             ),
+            LibraryAccountType::Role(role) => Err(unresolved_role(role)),
+            LibraryAccountType::IcaRemote { ica_library } => {
+                Err(unresolved_ica_remote(ica_library))
+            }
         }
     }
 
@@ -39,9 +52,48 @@ impl LibraryAccountType {
             LibraryAccountType::AccountId(_) | LibraryAccountType::LibraryId(_) => Err(
                 StdError::generic_err("LibraryAccountType must be an address"),
             ),
+            LibraryAccountType::Role(role) => Err(unresolved_role(role)),
+            LibraryAccountType::IcaRemote { ica_library } => {
+                Err(unresolved_ica_remote(ica_library))
+            }
+        }
+    }
+
+    /// Returns the address as string, querying the current remote address of the ICA for `IcaRemote`.
+    /// Meant to resolve remote receivers at execution time, other references must already be addresses
+    pub fn to_remote_addr<C: CustomQuery>(&self, querier: &QuerierWrapper<C>) -> StdResult<String> {
+        match self {
+            LibraryAccountType::IcaRemote { ica_library } => {
+                query_remote_ica_address(querier, ica_library.as_str())
+            }
+            _ => self.to_string(),
         }
     }
 
+    /// Resolves the account type into the address it references without side effects, querying the current
+    /// remote address of the ICA for `IcaRemote`. Ids and roles are only resolved when the program is
+    /// instantiated, so the references that are left fail to resolve
+    pub fn resolve<C: CustomQuery>(
+        &self,
+        api: &dyn Api,
+        querier: &QuerierWrapper<C>,
+    ) -> StdResult<Addr> {
+        match self {
+            LibraryAccountType::IcaRemote { ica_library } => {
+                query_remote_ica_address(querier, ica_library.as_str()).map(Addr::unchecked)
+            }
+            _ => self.to_addr(api),
+        }
+    }
+
+    /// Resolves a list of account types into addresses, in the same order
+    pub fn resolve_many(accounts: &[LibraryAccountType], api: &dyn Api) -> StdResult<Vec<Addr>> {
+        accounts
+            .iter()
+            .map(|account| account.to_addr(api))
+            .collect()
+    }
+
     /// There are cases where a library config expects a string, but we still want to use the
     /// id replacement functionality of the manager.
     /// Using this function will use a placeholder that can be replaced by the manager
@@ -54,12 +106,109 @@ impl LibraryAccountType {
             LibraryAccountType::LibraryId(_) => {
                 panic!("Only accounts can use raw_placeholder functionality")
             }
+            LibraryAccountType::Role(role) => {
+                panic!("Role {role} must be resolved before using raw_placeholder functionality")
+            }
+            LibraryAccountType::IcaRemote { .. } => {
+                panic!("ICA remote addresses can't use raw_placeholder functionality")
+            }
         };
 
         format!("{LIBRARY_ACCOUNT_RAW_PLACEHOLDER}:{value}")
     }
 }
 
+fn unresolved_role(role: &str) -> StdError {
+    StdError::generic_err(format!(
+        "LibraryAccountType must be an address, role {role} was not resolved"
+    ))
+}
+
+fn unresolved_ica_remote(ica_library: &Addr) -> StdError {
+    StdError::generic_err(format!(
+        "LibraryAccountType must be an address, the remote address of ICA {ica_library} is only resolved at execution time"
+    ))
+}
+
+/// Registry of the accounts of a program by role, so that configs can reference them symbolically
+/// and be used across deployments where the addresses differ
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ProgramRoles {
+    roles: BTreeMap<String, LibraryAccountType>,
+}
+
+impl ProgramRoles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the account of `role`. Roles can only be registered once, and can't reference other roles
+    pub fn register(
+        &mut self,
+        role: impl Into<String>,
+        account: impl Into<LibraryAccountType>,
+    ) -> StdResult<()> {
+        let role = role.into();
+        let account = account.into();
+
+        if role.is_empty() {
+            return Err(StdError::generic_err("Role name cannot be empty"));
+        }
+        if let LibraryAccountType::Role(other) = &account {
+            return Err(StdError::generic_err(format!(
+                "Role {role} cannot reference role {other}"
+            )));
+        }
+        if self.roles.contains_key(&role) {
+            return Err(StdError::generic_err(format!(
+                "Role {role} is already registered"
+            )));
+        }
+
+        self.roles.insert(role, account);
+        Ok(())
+    }
+
+    /// Returns the registered account of a role reference, other account types are returned as they are
+    pub fn resolve(&self, account: &LibraryAccountType) -> StdResult<LibraryAccountType> {
+        match account {
+            LibraryAccountType::Role(role) => self.roles.get(role).cloned().ok_or_else(|| {
+                StdError::generic_err(format!("Role {role} is not registered in the program"))
+            }),
+            _ => Ok(account.clone()),
+        }
+    }
+
+    /// Replaces every role reference of a config (at any depth) with the registered account
+    pub fn resolve_config<T: Serialize + DeserializeOwned>(&self, config: &T) -> StdResult<T> {
+        let mut value =
+            serde_json::to_value(config).map_err(|e| StdError::generic_err(e.to_string()))?;
+        self.resolve_value(&mut value)?;
+        serde_json::from_value(value).map_err(|e| StdError::generic_err(e.to_string()))
+    }
+
+    fn resolve_value(&self, value: &mut Value) -> StdResult<()> {
+        match value {
+            Value::Object(map) => {
+                if map.len() == 1 {
+                    if let Some(Value::String(role)) = map.get("|role|") {
+                        let account = self.resolve(&LibraryAccountType::Role(role.clone()))?;
+                        *value = serde_json::to_value(account)
+                            .map_err(|e| StdError::generic_err(e.to_string()))?;
+                        return Ok(());
+                    }
+                }
+                map.values_mut()
+                    .try_for_each(|value| self.resolve_value(value))
+            }
+            Value::Array(values) => values
+                .iter_mut()
+                .try_for_each(|value| self.resolve_value(value)),
+            _ => Ok(()),
+        }
+    }
+}
+
 impl From<&Addr> for LibraryAccountType {
     fn from(addr: &Addr) -> Self {
         LibraryAccountType::Addr(addr.to_string())
@@ -92,6 +241,13 @@ impl From<&str> for LibraryAccountType {
                     .parse()
                     .expect("Failed parsing addr into LibraryAccountType"),
             )
+        } else if input.starts_with("{\"|role|\":\"") {
+            LibraryAccountType::Role(
+                input
+                    .trim_start_matches("{\"|role|\":\"")
+                    .trim_end_matches("\"}")
+                    .to_string(),
+            )
         } else {
             // Defaulting to `Addr` allows easily converting an address to LibraryAccountType
             // by doing `"addr1234".into()`
@@ -128,6 +284,13 @@ impl FromStr for LibraryAccountType {
                     .parse()
                     .map_err(|_| "Failed parsing library_account_addr into LibraryAccountType")?,
             ))
+        } else if input.starts_with("{\"|role|\":\"") {
+            Ok(LibraryAccountType::Role(
+                input
+                    .trim_start_matches("{\"|role|\":\"")
+                    .trim_end_matches("\"}")
+                    .to_string(),
+            ))
         } else {
             Ok(LibraryAccountType::Addr(input.to_string()))
         }
@@ -147,6 +310,10 @@ impl GetId for LibraryAccountType {
             }
             LibraryAccountType::AccountId(id) => *id,
             LibraryAccountType::LibraryId(_) => panic!("LibraryAccountType is a library id"),
+            LibraryAccountType::Role(_) => panic!("LibraryAccountType is a role"),
+            LibraryAccountType::IcaRemote { .. } => {
+                panic!("LibraryAccountType is an ICA remote address")
+            }
         }
     }
 
@@ -157,6 +324,10 @@ impl GetId for LibraryAccountType {
             }
             LibraryAccountType::AccountId(_) => panic!("LibraryAccountType is a account id"),
             LibraryAccountType::LibraryId(id) => *id,
+            LibraryAccountType::Role(_) => panic!("LibraryAccountType is a role"),
+            LibraryAccountType::IcaRemote { .. } => {
+                panic!("LibraryAccountType is an ICA remote address")
+            }
         }
     }
 }
@@ -195,9 +366,15 @@ impl GetId for u32 {
 mod test {
     use std::str::FromStr;
 
-    use cosmwasm_std::to_json_string;
+    use cosmwasm_std::{
+        from_json,
+        testing::{mock_dependencies, MockApi, MockQuerier, MockStorage},
+        to_json_binary, to_json_string, Addr, ContractResult, Empty, OwnedDeps, QuerierWrapper,
+        SystemError, SystemResult, WasmQuery,
+    };
+    use valence_account_utils::ica::{IcaInformation, IcaState, QueryMsg as IcaQueryMsg};
 
-    use super::LibraryAccountType;
+    use super::{LibraryAccountType, ProgramRoles};
 
     #[test]
     fn serde_serialize() {
@@ -272,4 +449,255 @@ mod test {
         // Should panic if we try to get the raw placeholder of a library
         library.to_raw_placeholder();
     }
+
+    #[test]
+    fn resolve_many() {
+        let api = MockApi::default();
+        let addr1 = api.addr_make("addr1");
+        let addr2 = api.addr_make("addr2");
+
+        let accounts = vec![
+            LibraryAccountType::from(&addr1),
+            LibraryAccountType::from(&addr2),
+            LibraryAccountType::from(&addr1),
+        ];
+
+        let resolved = LibraryAccountType::resolve_many(&accounts, &api).unwrap();
+        let expected = accounts
+            .iter()
+            .map(|account| account.to_addr(&api).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(resolved, expected);
+        assert_eq!(resolved, vec![addr1.clone(), addr2, addr1]);
+    }
+
+    #[test]
+    fn resolve_many_fails_for_ids() {
+        let api = MockApi::default();
+        let accounts = vec![
+            LibraryAccountType::from(&api.addr_make("addr1")),
+            LibraryAccountType::AccountId(1),
+        ];
+
+        let err = LibraryAccountType::resolve_many(&accounts, &api).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("LibraryAccountType must be an address"));
+
+        // Invalid addresses are still rejected
+        let err = LibraryAccountType::resolve_many(
+            &[LibraryAccountType::Addr("invalid".to_string())],
+            &api,
+        );
+        assert!(err.is_err());
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    struct SplitConfig {
+        input_addr: LibraryAccountType,
+        splits: Vec<(LibraryAccountType, u64)>,
+        label: String,
+    }
+
+    fn program_roles() -> ProgramRoles {
+        let mut roles = ProgramRoles::new();
+        roles.register("deposit", "neutron1deposit").unwrap();
+        roles
+            .register("withdraw", LibraryAccountType::AccountId(2))
+            .unwrap();
+        roles
+    }
+
+    #[test]
+    fn role_serde_and_from_str() {
+        let role = LibraryAccountType::Role("deposit".to_string());
+        let json = to_json_string(&role).unwrap();
+        assert_eq!(json, r#"{"|role|":"deposit"}"#);
+
+        let role_type_into: LibraryAccountType = json.as_str().into();
+        assert_eq!(role_type_into, role);
+        assert_eq!(LibraryAccountType::from_str(&json).unwrap(), role);
+
+        // Roles are not addresses until they are resolved
+        assert_eq!(
+            role.to_addr(&MockApi::default()).unwrap_err().to_string(),
+            "Generic error: LibraryAccountType must be an address, role deposit was not resolved"
+        );
+    }
+
+    #[test]
+    fn resolve_config_with_roles() {
+        let roles = program_roles();
+        let config = SplitConfig {
+            input_addr: LibraryAccountType::Role("deposit".to_string()),
+            splits: vec![
+                (LibraryAccountType::Role("withdraw".to_string()), 1),
+                (LibraryAccountType::Addr("neutron1fees".to_string()), 2),
+            ],
+            label: "deposit".to_string(),
+        };
+
+        assert_eq!(
+            roles.resolve_config(&config).unwrap(),
+            SplitConfig {
+                input_addr: LibraryAccountType::Addr("neutron1deposit".to_string()),
+                splits: vec![
+                    (LibraryAccountType::AccountId(2), 1),
+                    (LibraryAccountType::Addr("neutron1fees".to_string()), 2),
+                ],
+                // Strings matching a role name are left untouched
+                label: "deposit".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn unregistered_roles_fail_to_resolve() {
+        let roles = program_roles();
+        let config = SplitConfig {
+            input_addr: LibraryAccountType::Role("deposit".to_string()),
+            splits: vec![(LibraryAccountType::Role("strategist".to_string()), 1)],
+            label: "split".to_string(),
+        };
+
+        assert_eq!(
+            roles.resolve_config(&config).unwrap_err().to_string(),
+            "Generic error: Role strategist is not registered in the program"
+        );
+    }
+
+    #[test]
+    fn roles_are_registered_once() {
+        let mut roles = program_roles();
+
+        assert!(roles.register("deposit", "neutron1other").is_err());
+        assert!(roles
+            .register(
+                "settlement",
+                LibraryAccountType::Role("deposit".to_string())
+            )
+            .is_err());
+        assert!(roles.register("", "neutron1other").is_err());
+
+        // The registry is unchanged by the failed registrations
+        assert_eq!(roles, program_roles());
+    }
+
+    const ICA_LIBRARY: &str = "ica_library";
+
+    fn deps_with_ica(state: IcaState) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == ICA_LIBRARY => {
+                match from_json(msg).unwrap() {
+                    IcaQueryMsg::IcaState {} => {
+                        SystemResult::Ok(ContractResult::Ok(to_json_binary(&state).unwrap()))
+                    }
+                    _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                        kind: "ica query".to_string(),
+                    }),
+                }
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm query".to_string(),
+            }),
+        });
+        deps
+    }
+
+    #[test]
+    fn ica_remote_resolves_through_ica_library() {
+        let deps = deps_with_ica(IcaState::Created(IcaInformation {
+            address: "noble1ica".to_string(),
+            port_id: "icacontroller-ica_library".to_string(),
+            controller_connection_id: "connection-0".to_string(),
+        }));
+        let querier = QuerierWrapper::<Empty>::new(&deps.querier);
+        let remote = LibraryAccountType::IcaRemote {
+            ica_library: Addr::unchecked(ICA_LIBRARY),
+        };
+
+        assert_eq!(remote.to_remote_addr(&querier).unwrap(), "noble1ica");
+        // Addresses are returned as they are
+        assert_eq!(
+            LibraryAccountType::Addr("noble1receiver".to_string())
+                .to_remote_addr(&querier)
+                .unwrap(),
+            "noble1receiver"
+        );
+
+        // The remote address can't be resolved without querying the ICA library
+        assert!(remote.to_string().is_err());
+        assert!(remote.to_addr(&deps.api).is_err());
+
+        let json = to_json_string(&remote).unwrap();
+        assert_eq!(json, r#"{"|ica_remote|":{"ica_library":"ica_library"}}"#);
+        assert_eq!(from_json::<LibraryAccountType>(json).unwrap(), remote);
+    }
+
+    #[test]
+    fn resolve_each_variant_without_side_effects() {
+        let deps = deps_with_ica(IcaState::Created(IcaInformation {
+            address: "noble1ica".to_string(),
+            port_id: "icacontroller-ica_library".to_string(),
+            controller_connection_id: "connection-0".to_string(),
+        }));
+        let querier = QuerierWrapper::<Empty>::new(&deps.querier);
+        let deposit = deps.api.addr_make("deposit");
+
+        assert_eq!(
+            LibraryAccountType::from(&deposit)
+                .resolve(&deps.api, &querier)
+                .unwrap(),
+            deposit
+        );
+        assert_eq!(
+            LibraryAccountType::IcaRemote {
+                ica_library: Addr::unchecked(ICA_LIBRARY),
+            }
+            .resolve(&deps.api, &querier)
+            .unwrap(),
+            Addr::unchecked("noble1ica")
+        );
+
+        // Ids and roles are replaced when the program is instantiated, so they can't be resolved anymore
+        for account in [
+            LibraryAccountType::AccountId(1),
+            LibraryAccountType::LibraryId(2),
+        ] {
+            assert_eq!(
+                account
+                    .resolve(&deps.api, &querier)
+                    .unwrap_err()
+                    .to_string(),
+                "Generic error: LibraryAccountType must be an address"
+            );
+        }
+        assert_eq!(
+            LibraryAccountType::Role("strategist".to_string())
+                .resolve(&deps.api, &querier)
+                .unwrap_err()
+                .to_string(),
+            "Generic error: LibraryAccountType must be an address, role strategist was not resolved"
+        );
+
+        // Invalid addresses are rejected
+        assert!(LibraryAccountType::Addr("invalid".to_string())
+            .resolve(&deps.api, &querier)
+            .is_err());
+    }
+
+    #[test]
+    fn ica_remote_fails_if_ica_not_created() {
+        let deps = deps_with_ica(IcaState::NotCreated);
+        let querier = QuerierWrapper::<Empty>::new(&deps.querier);
+
+        let err = LibraryAccountType::IcaRemote {
+            ica_library: Addr::unchecked(ICA_LIBRARY),
+        }
+        .to_remote_addr(&querier)
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: ICA not created");
+    }
 }
@@ -1,5 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{ensure, Decimal};
+use cosmwasm_std::{ensure, Addr, Decimal, Deps, Uint128};
 
 use crate::error::LibraryError;
 
@@ -9,6 +9,86 @@ pub struct AssetData {
     pub asset1: String,
     /// Denom of the second asset
     pub asset2: String,
+    /// Denoms of the remaining assets, for pools with more than two assets (e.g. stable pools)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_assets: Vec<String>,
+}
+
+impl AssetData {
+    pub fn new(asset1: impl Into<String>, asset2: impl Into<String>) -> Self {
+        AssetData {
+            asset1: asset1.into(),
+            asset2: asset2.into(),
+            additional_assets: vec![],
+        }
+    }
+
+    pub fn with_additional_assets(mut self, additional_assets: Vec<String>) -> Self {
+        self.additional_assets = additional_assets;
+        self
+    }
+
+    /// Returns the denoms of all the assets, starting with `asset1` and `asset2`
+    pub fn assets(&self) -> Vec<&String> {
+        [&self.asset1, &self.asset2]
+            .into_iter()
+            .chain(self.additional_assets.iter())
+            .collect()
+    }
+
+    pub fn contains(&self, denom: &str) -> bool {
+        self.assets().iter().any(|asset| asset.as_str() == denom)
+    }
+
+    /// Checks that no denom is empty or repeated
+    pub fn validate(&self) -> Result<(), LibraryError> {
+        let assets = self.assets();
+        for (i, asset) in assets.iter().enumerate() {
+            if asset.is_empty() {
+                return Err(LibraryError::ConfigurationError(
+                    "Asset denom cannot be empty".to_string(),
+                ));
+            }
+            if assets[..i].contains(asset) {
+                return Err(LibraryError::ConfigurationError(format!(
+                    "Duplicate asset {asset} in asset data"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the assets are exactly the assets of the pool, in any order
+    pub fn ensure_matches_pool_assets(&self, pool_assets: &[String]) -> Result<(), LibraryError> {
+        let assets = self.assets();
+        ensure!(
+            assets.len() == pool_assets.len()
+                && pool_assets
+                    .iter()
+                    .all(|pool_asset| self.contains(pool_asset)),
+            LibraryError::ConfigurationError(format!(
+                "Configured assets [{}] do not match the pool assets [{}]",
+                assets
+                    .iter()
+                    .map(|asset| asset.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                pool_assets.join(", ")
+            ))
+        );
+        Ok(())
+    }
+
+    /// For libraries that only support pools with exactly two assets
+    pub fn ensure_two_assets(&self) -> Result<(), LibraryError> {
+        ensure!(
+            self.additional_assets.is_empty(),
+            LibraryError::ConfigurationError(
+                "Only pools with two assets are supported".to_string()
+            )
+        );
+        Ok(())
+    }
 }
 
 #[cw_serde]
@@ -32,3 +112,215 @@ impl DecimalRange {
         Ok(())
     }
 }
+
+/// Query of the (Astroport compatible) pools returning their reserves and total shares
+#[cw_serde]
+pub enum PoolQueryMsg {
+    Pool {},
+}
+
+#[cw_serde]
+pub struct PoolReservesResponse {
+    pub assets: Vec<PoolAsset>,
+    pub total_share: Uint128,
+}
+
+#[cw_serde]
+pub struct PoolAsset {
+    pub info: PoolAssetInfo,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub enum PoolAssetInfo {
+    Token { contract_addr: String },
+    NativeToken { denom: String },
+}
+
+/// Amounts of the two assets of the pool, in the order of the pool assets, that `lp_amount` LP shares
+/// redeem at the current pool reserves. Rounded down, like the pool rounds the withdrawn assets
+pub fn lp_to_underlying(
+    deps: Deps,
+    pool: &Addr,
+    lp_amount: Uint128,
+) -> Result<(Uint128, Uint128), LibraryError> {
+    let pool_reserves: PoolReservesResponse = deps
+        .querier
+        .query_wasm_smart(pool, &PoolQueryMsg::Pool {})?;
+
+    let [asset1, asset2] = pool_reserves.assets.as_slice() else {
+        return Err(LibraryError::ExecutionError(format!(
+            "Pool {pool} has {} assets, only pools with two assets are supported",
+            pool_reserves.assets.len()
+        )));
+    };
+    if lp_amount.is_zero() {
+        return Ok((Uint128::zero(), Uint128::zero()));
+    }
+    ensure!(
+        lp_amount <= pool_reserves.total_share,
+        LibraryError::ExecutionError(format!(
+            "LP amount {lp_amount} is above the total shares {} of pool {pool}",
+            pool_reserves.total_share
+        ))
+    );
+
+    let share_of = |reserve: Uint128| {
+        reserve
+            .checked_multiply_ratio(lp_amount, pool_reserves.total_share)
+            .map_err(|e| LibraryError::ExecutionError(e.to_string()))
+    };
+    Ok((share_of(asset1.amount)?, share_of(asset2.amount)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        from_json,
+        testing::{mock_dependencies, MockApi, MockQuerier},
+        to_json_binary, ContractResult, SystemResult, WasmQuery,
+    };
+
+    use super::*;
+
+    #[test]
+    fn two_asset_data() {
+        let asset_data = AssetData::new("untrn", "uatom");
+
+        assert_eq!(asset_data.assets(), vec!["untrn", "uatom"]);
+        assert!(asset_data.contains("uatom"));
+        assert!(!asset_data.contains("uusdc"));
+        asset_data.validate().unwrap();
+        asset_data.ensure_two_assets().unwrap();
+
+        // Serializes exactly like before additional assets existed
+        let json = cosmwasm_std::to_json_string(&asset_data).unwrap();
+        assert_eq!(json, r#"{"asset1":"untrn","asset2":"uatom"}"#);
+        assert_eq!(
+            cosmwasm_std::from_json::<AssetData>(json).unwrap(),
+            asset_data
+        );
+    }
+
+    #[test]
+    fn three_asset_data() {
+        let asset_data =
+            AssetData::new("uusdc", "uusdt").with_additional_assets(vec!["udai".to_string()]);
+
+        assert_eq!(asset_data.assets(), vec!["uusdc", "uusdt", "udai"]);
+        assert!(asset_data.contains("udai"));
+        asset_data.validate().unwrap();
+        assert_eq!(
+            asset_data.ensure_two_assets().unwrap_err().to_string(),
+            "Configuration error: Only pools with two assets are supported"
+        );
+
+        let duplicated =
+            AssetData::new("uusdc", "uusdt").with_additional_assets(vec!["uusdc".to_string()]);
+        assert_eq!(
+            duplicated.validate().unwrap_err().to_string(),
+            "Configuration error: Duplicate asset uusdc in asset data"
+        );
+    }
+
+    #[test]
+    fn asset_data_matches_pool_assets_in_any_order() {
+        let asset_data = AssetData::new("untrn", "uatom");
+
+        asset_data
+            .ensure_matches_pool_assets(&["untrn".to_string(), "uatom".to_string()])
+            .unwrap();
+        asset_data
+            .ensure_matches_pool_assets(&["uatom".to_string(), "untrn".to_string()])
+            .unwrap();
+    }
+
+    #[test]
+    fn asset_data_mismatching_pool_assets() {
+        let asset_data = AssetData::new("untrn", "uatom");
+
+        assert_eq!(
+            asset_data
+                .ensure_matches_pool_assets(&["untrn".to_string(), "uusdc".to_string()])
+                .unwrap_err()
+                .to_string(),
+            "Configuration error: Configured assets [untrn, uatom] do not match the pool assets [untrn, uusdc]"
+        );
+        assert!(asset_data
+            .ensure_matches_pool_assets(&[
+                "untrn".to_string(),
+                "uatom".to_string(),
+                "uusdc".to_string()
+            ])
+            .is_err());
+    }
+
+    fn mock_pool_reserves(querier: &mut MockQuerier, reserves: Vec<u128>, total_share: u128) {
+        querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let PoolQueryMsg::Pool {} = from_json(msg).unwrap();
+                let assets = reserves
+                    .iter()
+                    .enumerate()
+                    .map(|(i, reserve)| PoolAsset {
+                        info: PoolAssetInfo::NativeToken {
+                            denom: format!("denom{i}"),
+                        },
+                        amount: Uint128::new(*reserve),
+                    })
+                    .collect();
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&PoolReservesResponse {
+                        assets,
+                        total_share: Uint128::new(total_share),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => unimplemented!(),
+        });
+    }
+
+    #[test]
+    fn lp_shares_redeem_their_share_of_the_reserves() {
+        let mut deps = mock_dependencies();
+        let pool = MockApi::default().addr_make("pool");
+        mock_pool_reserves(&mut deps.querier, vec![1_000_000, 3_000_001], 2_000_000);
+
+        let cases = [
+            (0, (0, 0)),
+            (500_000, (250_000, 750_000)),
+            // Rounded down
+            (3, (1, 4)),
+            (2_000_000, (1_000_000, 3_000_001)),
+        ];
+        for (lp_amount, (amount1, amount2)) in cases {
+            assert_eq!(
+                lp_to_underlying(deps.as_ref(), &pool, Uint128::new(lp_amount)).unwrap(),
+                (Uint128::new(amount1), Uint128::new(amount2)),
+                "{lp_amount} LP shares"
+            );
+        }
+
+        assert_eq!(
+            lp_to_underlying(deps.as_ref(), &pool, Uint128::new(2_000_001))
+                .unwrap_err()
+                .to_string(),
+            format!("Execution error: LP amount 2000001 is above the total shares 2000000 of pool {pool}")
+        );
+    }
+
+    #[test]
+    fn lp_to_underlying_requires_two_asset_pools() {
+        let mut deps = mock_dependencies();
+        let pool = MockApi::default().addr_make("pool");
+        mock_pool_reserves(&mut deps.querier, vec![100, 100, 100], 300);
+
+        assert_eq!(
+            lp_to_underlying(deps.as_ref(), &pool, Uint128::new(30))
+                .unwrap_err()
+                .to_string(),
+            format!("Execution error: Pool {pool} has 3 assets, only pools with two assets are supported")
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Api, Decimal, Deps};
+use cosmwasm_std::{Api, Decimal, Deps, Uint128};
 use cw_ownable::{cw_ownable_execute, cw_ownable_query};
 
 use crate::error::LibraryError;
@@ -13,6 +13,13 @@ pub struct InstantiateMsg<T> {
     pub config: T,
 }
 
+#[cw_serde]
+pub struct MigrateMsg<U> {
+    // Optional patch applied to the library config during the migration.
+    // Owner and processor are always preserved.
+    pub config_update: Option<U>,
+}
+
 pub trait LibraryConfigValidation<T> {
     #[cfg(not(target_arch = "wasm32"))]
     fn pre_validate(&self, api: &dyn Api) -> Result<(), LibraryError>;
@@ -23,8 +30,96 @@ pub trait LibraryConfigValidation<T> {
 #[cw_serde]
 pub enum ExecuteMsg<T, U> {
     ProcessFunction(T),
-    UpdateConfig { new_config: U },
-    UpdateProcessor { processor: String },
+    // Same as ProcessFunction, but rejected unless the nonce is greater than the last nonce executed
+    // for the sender, so that retried messages are not executed twice
+    ProcessFunctionWithNonce {
+        function: T,
+        nonce: u64,
+    },
+    UpdateConfig {
+        new_config: U,
+    },
+    UpdateProcessor {
+        processor: String,
+    },
+    // Pausing blocks the execution of functions until the library is unpaused (only owner)
+    Pause {},
+    Unpause {},
+    // Requires privileged actions to be approved by a threshold of owners instead of the single owner,
+    // or goes back to the single owner if None
+    UpdateOwnerThreshold {
+        owner_threshold: Option<OwnerThreshold>,
+    },
+    // Drops the pending approvals of a privileged action, given by its approval nonce (only one of the owners of the threshold)
+    CancelApproval {
+        nonce: u64,
+    },
+    // Allows the next migration of a library with an owner threshold (only owner)
+    AuthorizeMigration {},
+    // Sent by the processor when one of the functions of the library failed, to record the error (only processor)
+    RecordError {
+        error: String,
+    },
+}
+
+impl<T, U> ExecuteMsg<T, U> {
+    /// Whether the message can only be executed by the owner, or with the approvals of the owners
+    /// when an owner threshold is set
+    pub fn is_privileged(&self) -> bool {
+        matches!(
+            self,
+            ExecuteMsg::UpdateConfig { .. }
+                | ExecuteMsg::UpdateProcessor { .. }
+                | ExecuteMsg::Pause {}
+                | ExecuteMsg::Unpause {}
+                | ExecuteMsg::UpdateOwnerThreshold { .. }
+                | ExecuteMsg::AuthorizeMigration {}
+        )
+    }
+}
+
+/// M-of-N owners of a library: a privileged action is only executed once `threshold` of the `owners`
+/// have sent the exact same message, each message sent before that being recorded as an approval
+#[cw_serde]
+pub struct OwnerThreshold {
+    pub owners: Vec<String>,
+    pub threshold: u64,
+}
+
+/// Last failed execution of a library recorded by its processor, returned by the `GetLastError` query.
+#[cw_serde]
+#[derive(Default)]
+pub struct LastErrorResponse {
+    pub last_error: Option<String>,
+    // Height of the block in which the execution failed
+    pub last_error_height: Option<u64>,
+}
+
+/// Amount of a denom that a library would move if its function was executed now, returned by the `SimulateAmount` queries.
+#[cw_serde]
+pub struct SimulatedAmount {
+    pub denom: String,
+    pub amount: Uint128,
+    // Why the execution would be a no-op for this denom, in which case the amount is zero
+    pub skip_reason: Option<String>,
+}
+
+impl SimulatedAmount {
+    pub fn new(denom: impl Into<String>, amount: Uint128) -> Self {
+        SimulatedAmount {
+            denom: denom.into(),
+            amount,
+            skip_reason: None,
+        }
+    }
+
+    pub fn skipped(denom: impl Into<String>, reason: impl Into<String>) -> Self {
+        SimulatedAmount {
+            denom: denom.into(),
+            amount: Uint128::zero(),
+            skip_reason: Some(reason.into()),
+        }
+    }
 }
 
 #[cw_ownable_query]
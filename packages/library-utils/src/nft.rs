@@ -0,0 +1,100 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_json_binary, Addr, Api, Binary, CosmosMsg, CustomQuery, Empty, QuerierWrapper, StdResult,
+    WasmMsg,
+};
+use cw721::msg::{Cw721ExecuteMsg, OwnerOfResponse};
+
+use crate::error::LibraryError;
+
+#[cw_serde]
+/// cw721 token representing a position that can't be held as a fungible balance, e.g. a concentrated liquidity position
+pub struct PositionNft {
+    /// The cw721 contract of the token
+    pub contract_addr: String,
+    pub token_id: String,
+}
+
+impl PositionNft {
+    pub fn new(contract_addr: impl Into<String>, token_id: impl Into<String>) -> Self {
+        PositionNft {
+            contract_addr: contract_addr.into(),
+            token_id: token_id.into(),
+        }
+    }
+
+    /// Validates the token contract and id, and that the token is currently owned by `owner`
+    pub fn to_checked<C: CustomQuery>(
+        &self,
+        api: &dyn Api,
+        querier: &QuerierWrapper<C>,
+        owner: &Addr,
+    ) -> Result<CheckedPositionNft, LibraryError> {
+        let contract_addr = api.addr_validate(&self.contract_addr)?;
+        if self.token_id.is_empty() {
+            return Err(LibraryError::ExecutionError(
+                "Invalid position NFT: token id cannot be empty".to_string(),
+            ));
+        }
+
+        let owner_response: OwnerOfResponse = querier
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &cw721_base::msg::QueryMsg::OwnerOf {
+                    token_id: self.token_id.clone(),
+                    include_expired: None,
+                },
+            )
+            .map_err(|_| {
+                LibraryError::ExecutionError(format!(
+                    "Invalid position NFT: token {} does not exist in {contract_addr}",
+                    self.token_id
+                ))
+            })?;
+        if owner_response.owner != owner.as_str() {
+            return Err(LibraryError::ExecutionError(format!(
+                "Invalid position NFT: token {} is not owned by {owner}",
+                self.token_id
+            )));
+        }
+
+        Ok(CheckedPositionNft {
+            contract_addr,
+            token_id: self.token_id.clone(),
+        })
+    }
+}
+
+#[cw_serde]
+/// Position NFT owned by the account it was validated against
+pub struct CheckedPositionNft {
+    pub contract_addr: Addr,
+    pub token_id: String,
+}
+
+impl CheckedPositionNft {
+    /// Message transferring the token to `recipient`
+    pub fn transfer_msg(&self, recipient: &Addr) -> StdResult<CosmosMsg> {
+        self.execute_msg(Cw721ExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: self.token_id.clone(),
+        })
+    }
+
+    /// Message sending the token to `contract`, which is called with the `msg` hook
+    pub fn send_msg(&self, contract: &Addr, msg: Binary) -> StdResult<CosmosMsg> {
+        self.execute_msg(Cw721ExecuteMsg::SendNft {
+            contract: contract.to_string(),
+            token_id: self.token_id.clone(),
+            msg,
+        })
+    }
+
+    fn execute_msg(&self, msg: Cw721ExecuteMsg<Empty, Empty, Empty>) -> StdResult<CosmosMsg> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: self.contract_addr.to_string(),
+            msg: to_json_binary(&msg)?,
+            funds: vec![],
+        }))
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::cw_serde;
+use valence_ibc_utils::types::PacketForwardMiddlewareConfig;
+
+use crate::error::LibraryError;
+
+/// Chain at one of the ends (or the hop) of a route, along with the account receiving the transfers on it
+#[cw_serde]
+pub struct RouteEndpoint {
+    pub chain_id: String,
+    /// Receiver on this chain. Optional for the hop chain, where PFM ignores the receiver
+    pub receiver: Option<String>,
+}
+
+/// Both ends of an IBC channel, seen from the chain sending the packets
+#[cw_serde]
+pub struct ChannelEnds {
+    /// Channel on the sending chain
+    pub channel_id: String,
+    /// Channel on the receiving chain
+    pub counterparty_channel_id: String,
+}
+
+impl ChannelEnds {
+    /// The same channel seen from the receiving chain
+    pub fn inverted(&self) -> Self {
+        ChannelEnds {
+            channel_id: self.counterparty_channel_id.clone(),
+            counterparty_channel_id: self.channel_id.clone(),
+        }
+    }
+}
+
+/// Route of a transfer forwarded once through a hop chain using the packet-forward-middleware (PFM)
+#[cw_serde]
+pub struct PfmRoute {
+    pub source: RouteEndpoint,
+    pub source_to_hop: ChannelEnds,
+    pub hop: RouteEndpoint,
+    pub hop_to_destination: ChannelEnds,
+    pub destination: RouteEndpoint,
+}
+
+impl PfmRoute {
+    pub fn validate(&self) -> Result<(), LibraryError> {
+        for (field, value) in [
+            ("source chain id", &self.source.chain_id),
+            ("hop chain id", &self.hop.chain_id),
+            ("destination chain id", &self.destination.chain_id),
+            ("source to hop channel", &self.source_to_hop.channel_id),
+            (
+                "source to hop counterparty channel",
+                &self.source_to_hop.counterparty_channel_id,
+            ),
+            (
+                "hop to destination channel",
+                &self.hop_to_destination.channel_id,
+            ),
+            (
+                "hop to destination counterparty channel",
+                &self.hop_to_destination.counterparty_channel_id,
+            ),
+        ] {
+            if value.is_empty() {
+                return Err(LibraryError::ConfigurationError(format!(
+                    "Invalid PFM route: {field} cannot be empty"
+                )));
+            }
+        }
+
+        if self.hop.chain_id == self.source.chain_id
+            || self.hop.chain_id == self.destination.chain_id
+        {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Invalid PFM route: hop chain {} must differ from the source and destination chains",
+                self.hop.chain_id
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Route of the return path, from the destination back to the source through the same hop.
+    /// Every channel is used from its other end, and the receivers of both ends are swapped.
+    pub fn reverse(&self) -> Self {
+        PfmRoute {
+            source: self.destination.clone(),
+            source_to_hop: self.hop_to_destination.inverted(),
+            hop: self.hop.clone(),
+            hop_to_destination: self.source_to_hop.inverted(),
+            destination: self.source.clone(),
+        }
+    }
+
+    /// PFM config used by the transfer libraries on the source chain of the route
+    pub fn pfm_config(&self) -> PacketForwardMiddlewareConfig {
+        PacketForwardMiddlewareConfig::new(
+            self.source_to_hop.channel_id.clone(),
+            self.hop_to_destination.channel_id.clone(),
+            self.hop.receiver.clone(),
+        )
+    }
+}
+
+/// Route taken by a denom, which is known under a different denom on each end of the route
+#[cw_serde]
+pub struct DenomRoute {
+    pub source_denom: String,
+    pub destination_denom: String,
+    pub route: PfmRoute,
+}
+
+/// Checks that no denom of a `denom_to_pfm_map` is routed more than once. The hash of IBC denoms is not case sensitive,
+/// so keys only differing by the case of their hash (e.g. `ibc/ABC` and `ibc/abc`) are overlapping routes of the same denom,
+/// and which one applies would depend on the exact denom of the transferred funds.
+/// Returns the first conflicting denom, in the iteration order of the map, along with the denom it overlaps with.
+pub fn find_overlapping_pfm_route(
+    denom_to_pfm_map: &BTreeMap<String, PacketForwardMiddlewareConfig>,
+) -> Option<(&str, &str)> {
+    let mut routed = BTreeMap::new();
+    for denom in denom_to_pfm_map.keys() {
+        if let Some(other) = routed.insert(denom.to_ascii_lowercase(), denom.as_str()) {
+            return Some((denom.as_str(), other));
+        }
+    }
+
+    None
+}
+
+/// Derives the `denom_to_pfm_map` of both directions from the forward routes.
+/// Returns the map of the source chain followed by the map of the destination chain, used for the return path.
+#[allow(clippy::type_complexity)]
+pub fn round_trip_pfm_maps(
+    routes: &[DenomRoute],
+) -> Result<
+    (
+        BTreeMap<String, PacketForwardMiddlewareConfig>,
+        BTreeMap<String, PacketForwardMiddlewareConfig>,
+    ),
+    LibraryError,
+> {
+    let mut outbound = BTreeMap::new();
+    let mut inbound = BTreeMap::new();
+
+    for denom_route in routes {
+        denom_route.route.validate()?;
+
+        if outbound
+            .insert(
+                denom_route.source_denom.clone(),
+                denom_route.route.pfm_config(),
+            )
+            .is_some()
+            || inbound
+                .insert(
+                    denom_route.destination_denom.clone(),
+                    denom_route.route.reverse().pfm_config(),
+                )
+                .is_some()
+        {
+            return Err(LibraryError::ConfigurationError(format!(
+                "Invalid PFM route: more than one route for denom {}",
+                denom_route.source_denom
+            )));
+        }
+    }
+
+    Ok((outbound, inbound))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Neutron -> Noble -> Cosmos Hub route, with the channels of each end
+    fn forward_route() -> PfmRoute {
+        PfmRoute {
+            source: RouteEndpoint {
+                chain_id: "neutron-1".to_string(),
+                receiver: Some("neutron1vault".to_string()),
+            },
+            source_to_hop: ChannelEnds {
+                channel_id: "channel-30".to_string(),
+                counterparty_channel_id: "channel-18".to_string(),
+            },
+            hop: RouteEndpoint {
+                chain_id: "noble-1".to_string(),
+                receiver: Some("noble1forwarder".to_string()),
+            },
+            hop_to_destination: ChannelEnds {
+                channel_id: "channel-4".to_string(),
+                counterparty_channel_id: "channel-141".to_string(),
+            },
+            destination: RouteEndpoint {
+                chain_id: "cosmoshub-4".to_string(),
+                receiver: Some("cosmos1ica".to_string()),
+            },
+        }
+    }
+
+    #[test]
+    fn reverse_route_inverts_channels_and_receivers() {
+        let forward = forward_route();
+        let reverse = forward.reverse();
+
+        assert_eq!(reverse.source, forward.destination);
+        assert_eq!(reverse.destination, forward.source);
+        assert_eq!(reverse.hop, forward.hop);
+        assert_eq!(
+            reverse.source_to_hop,
+            ChannelEnds {
+                channel_id: "channel-141".to_string(),
+                counterparty_channel_id: "channel-4".to_string(),
+            }
+        );
+        assert_eq!(
+            reverse.hop_to_destination,
+            ChannelEnds {
+                channel_id: "channel-18".to_string(),
+                counterparty_channel_id: "channel-30".to_string(),
+            }
+        );
+
+        assert_eq!(
+            reverse.pfm_config(),
+            PacketForwardMiddlewareConfig::new(
+                "channel-141",
+                "channel-18",
+                Some("noble1forwarder".to_string())
+            )
+        );
+
+        // Reversing twice gives back the forward route
+        assert_eq!(reverse.reverse(), forward);
+    }
+
+    #[test]
+    fn round_trip_maps_are_keyed_by_the_denom_of_each_end() {
+        let (outbound, inbound) = round_trip_pfm_maps(&[DenomRoute {
+            source_denom: "ibc/ATOM_ON_NEUTRON".to_string(),
+            destination_denom: "uatom".to_string(),
+            route: forward_route(),
+        }])
+        .unwrap();
+
+        assert_eq!(
+            outbound,
+            BTreeMap::from([(
+                "ibc/ATOM_ON_NEUTRON".to_string(),
+                PacketForwardMiddlewareConfig::new(
+                    "channel-30",
+                    "channel-4",
+                    Some("noble1forwarder".to_string())
+                )
+            )])
+        );
+        assert_eq!(
+            inbound,
+            BTreeMap::from([("uatom".to_string(), forward_route().reverse().pfm_config())])
+        );
+    }
+
+    #[test]
+    fn overlapping_pfm_routes_are_found() {
+        let pfm_config = forward_route().pfm_config();
+        let mut denom_to_pfm_map = BTreeMap::from([
+            ("ibc/ABC123".to_string(), pfm_config.clone()),
+            ("uatom".to_string(), pfm_config.clone()),
+        ]);
+        assert_eq!(find_overlapping_pfm_route(&denom_to_pfm_map), None);
+
+        // The lowercase hash is iterated after the uppercase one
+        denom_to_pfm_map.insert("ibc/abc123".to_string(), pfm_config);
+        assert_eq!(
+            find_overlapping_pfm_route(&denom_to_pfm_map),
+            Some(("ibc/abc123", "ibc/ABC123"))
+        );
+    }
+
+    #[test]
+    fn invalid_routes_are_rejected() {
+        let mut route = forward_route();
+        route.hop_to_destination.counterparty_channel_id = String::new();
+        assert_eq!(
+            route.validate().unwrap_err().to_string(),
+            "Configuration error: Invalid PFM route: hop to destination counterparty channel cannot be empty"
+        );
+
+        let mut route = forward_route();
+        route.hop.chain_id = route.destination.chain_id.clone();
+        assert!(route.validate().is_err());
+
+        let denom_route = DenomRoute {
+            source_denom: "ibc/ATOM_ON_NEUTRON".to_string(),
+            destination_denom: "uatom".to_string(),
+            route: forward_route(),
+        };
+        assert_eq!(
+            round_trip_pfm_maps(&[denom_route.clone(), denom_route])
+                .unwrap_err()
+                .to_string(),
+            "Configuration error: Invalid PFM route: more than one route for denom ibc/ATOM_ON_NEUTRON"
+        );
+    }
+}
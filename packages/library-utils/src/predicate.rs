@@ -0,0 +1,118 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Api, CustomQuery, QuerierWrapper, Uint128};
+
+use crate::{error::LibraryError, LibraryAccountType};
+
+#[cw_serde]
+#[derive(Copy)]
+/// How the balance of the account of a predicate is compared to its amount
+pub enum Comparator {
+    LessThan,
+    LessThanOrEqual,
+    Equal,
+    GreaterThanOrEqual,
+    GreaterThan,
+}
+
+impl Comparator {
+    /// Returns true if `balance` compares to `amount` as required
+    pub fn compare(&self, balance: Uint128, amount: Uint128) -> bool {
+        match self {
+            Comparator::LessThan => balance < amount,
+            Comparator::LessThanOrEqual => balance <= amount,
+            Comparator::Equal => balance == amount,
+            Comparator::GreaterThanOrEqual => balance >= amount,
+            Comparator::GreaterThan => balance > amount,
+        }
+    }
+}
+
+#[cw_serde]
+/// Condition on the balance of an account, e.g. a sentinel balance signaling pending obligations,
+/// that a library checks at execution time before doing anything
+pub struct BalancePredicate {
+    /// The account whose balance is checked
+    pub account: LibraryAccountType,
+    /// The native denom of the balance
+    pub denom: String,
+    pub comparator: Comparator,
+    pub amount: Uint128,
+}
+
+impl BalancePredicate {
+    pub fn new(
+        account: impl Into<LibraryAccountType>,
+        denom: impl Into<String>,
+        comparator: Comparator,
+        amount: impl Into<Uint128>,
+    ) -> Self {
+        BalancePredicate {
+            account: account.into(),
+            denom: denom.into(),
+            comparator,
+            amount: amount.into(),
+        }
+    }
+
+    /// Validates the predicate and resolves its account
+    pub fn to_checked(&self, api: &dyn Api) -> Result<CheckedBalancePredicate, LibraryError> {
+        if self.denom.is_empty() {
+            return Err(LibraryError::ConfigurationError(
+                "Invalid precondition: denom cannot be empty.".to_string(),
+            ));
+        }
+
+        Ok(CheckedBalancePredicate {
+            account: self.account.to_addr(api)?,
+            denom: self.denom.clone(),
+            comparator: self.comparator,
+            amount: self.amount,
+        })
+    }
+}
+
+#[cw_serde]
+/// Validated balance predicate
+pub struct CheckedBalancePredicate {
+    pub account: Addr,
+    pub denom: String,
+    pub comparator: Comparator,
+    pub amount: Uint128,
+}
+
+impl CheckedBalancePredicate {
+    /// Queries the balance of the account and returns true if the predicate is met
+    pub fn is_met<C: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<C>,
+    ) -> Result<bool, LibraryError> {
+        let balance = querier.query_balance(&self.account, &self.denom)?;
+        Ok(self.comparator.compare(balance.amount, self.amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balances_are_compared_to_the_amount() {
+        let cases = [
+            (Comparator::LessThan, [true, false, false]),
+            (Comparator::LessThanOrEqual, [true, true, false]),
+            (Comparator::Equal, [false, true, false]),
+            (Comparator::GreaterThanOrEqual, [false, true, true]),
+            (Comparator::GreaterThan, [false, false, true]),
+        ];
+
+        for (comparator, expected) in cases {
+            for (balance, met) in [99u128, 100, 101].into_iter().zip(expected) {
+                assert_eq!(
+                    comparator.compare(Uint128::new(balance), Uint128::new(100)),
+                    met,
+                    "{balance} {comparator:?} 100"
+                );
+            }
+        }
+    }
+}
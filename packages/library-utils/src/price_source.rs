@@ -0,0 +1,286 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Api, Decimal, QuerierWrapper, Uint128};
+
+use crate::{error::LibraryError, rounding::RoundingPolicy};
+
+/// Converts denom amounts to a value in a common unit (e.g. USD), so that amounts of different denoms can be compared and added up
+pub trait PriceOracle {
+    /// Price of one unit of the denom, in the unit of the oracle
+    fn price(&self, querier: &QuerierWrapper, denom: &str) -> Result<Decimal, LibraryError>;
+
+    /// Value of the amount of the denom, in the unit of the oracle and rounded down
+    fn value(
+        &self,
+        querier: &QuerierWrapper,
+        denom: &str,
+        amount: Uint128,
+    ) -> Result<Uint128, LibraryError> {
+        RoundingPolicy::Down.apply(amount, self.price(querier, denom)?)
+    }
+
+    /// Sum of the values of the amounts of each of the denoms
+    fn total_value(
+        &self,
+        querier: &QuerierWrapper,
+        amounts: &[(&str, Uint128)],
+    ) -> Result<Uint128, LibraryError> {
+        amounts
+            .iter()
+            .try_fold(Uint128::zero(), |total, (denom, amount)| {
+                total
+                    .checked_add(self.value(querier, denom, *amount)?)
+                    .map_err(|e| LibraryError::ExecutionError(e.to_string()))
+            })
+    }
+}
+
+/// Where the prices used by a library to value amounts come from
+#[cw_serde]
+pub enum PriceSource {
+    /// Time weighted average price of an Astroport pool with an oracle (e.g. concentrated liquidity pools).
+    /// The base denom is priced in units of the quote denom, which is worth one.
+    /// Only the two pool assets can be priced
+    AstroportTwap {
+        pool_addr: String,
+        base_denom: String,
+        quote_denom: String,
+        // How far back the average price is observed
+        seconds_ago: u64,
+    },
+    /// External oracle contract answering `OracleQueryMsg::Price` with the price of any denom it supports
+    Oracle { contract_addr: String },
+}
+
+impl PriceSource {
+    pub fn to_checked(&self, api: &dyn Api) -> Result<CheckedPriceSource, LibraryError> {
+        match self {
+            PriceSource::AstroportTwap {
+                pool_addr,
+                base_denom,
+                quote_denom,
+                seconds_ago,
+            } => {
+                if base_denom.is_empty() || quote_denom.is_empty() {
+                    return Err(LibraryError::ConfigurationError(
+                        "Invalid price source: base and quote denoms cannot be empty".to_string(),
+                    ));
+                }
+                if base_denom == quote_denom {
+                    return Err(LibraryError::ConfigurationError(
+                        "Invalid price source: base and quote denoms must be different".to_string(),
+                    ));
+                }
+
+                Ok(CheckedPriceSource::AstroportTwap {
+                    pool_addr: api.addr_validate(pool_addr)?,
+                    base_denom: base_denom.clone(),
+                    quote_denom: quote_denom.clone(),
+                    seconds_ago: *seconds_ago,
+                })
+            }
+            PriceSource::Oracle { contract_addr } => Ok(CheckedPriceSource::Oracle {
+                contract_addr: api.addr_validate(contract_addr)?,
+            }),
+        }
+    }
+}
+
+/// Validated price source
+#[cw_serde]
+pub enum CheckedPriceSource {
+    AstroportTwap {
+        pool_addr: Addr,
+        base_denom: String,
+        quote_denom: String,
+        seconds_ago: u64,
+    },
+    Oracle {
+        contract_addr: Addr,
+    },
+}
+
+impl CheckedPriceSource {
+    /// Denoms that can be priced, if they can be known without querying the source
+    pub fn priced_denoms(&self) -> Option<Vec<&str>> {
+        match self {
+            CheckedPriceSource::AstroportTwap {
+                base_denom,
+                quote_denom,
+                ..
+            } => Some(vec![base_denom.as_str(), quote_denom.as_str()]),
+            CheckedPriceSource::Oracle { .. } => None,
+        }
+    }
+}
+
+impl PriceOracle for CheckedPriceSource {
+    fn price(&self, querier: &QuerierWrapper, denom: &str) -> Result<Decimal, LibraryError> {
+        match self {
+            CheckedPriceSource::AstroportTwap {
+                pool_addr,
+                base_denom,
+                quote_denom,
+                seconds_ago,
+            } => {
+                if denom == quote_denom {
+                    return Ok(Decimal::one());
+                }
+                if denom != base_denom {
+                    return Err(LibraryError::ExecutionError(format!(
+                        "Price source can't price {denom}: pool {pool_addr} only prices {base_denom} in {quote_denom}"
+                    )));
+                }
+
+                let observation: OracleObservation = querier.query_wasm_smart(
+                    pool_addr,
+                    &AstroportOracleQueryMsg::Observe {
+                        seconds_ago: *seconds_ago,
+                    },
+                )?;
+                Ok(observation.price)
+            }
+            CheckedPriceSource::Oracle { contract_addr } => {
+                let response: PriceResponse = querier.query_wasm_smart(
+                    contract_addr,
+                    &OracleQueryMsg::Price {
+                        denom: denom.to_string(),
+                    },
+                )?;
+                Ok(response.price)
+            }
+        }
+    }
+}
+
+/// Query that external oracle contracts used as price source must answer
+#[cw_serde]
+pub enum OracleQueryMsg {
+    Price { denom: String },
+}
+
+#[cw_serde]
+pub struct PriceResponse {
+    pub price: Decimal,
+}
+
+/// Query of the Astroport pools keeping price observations
+#[cw_serde]
+pub enum AstroportOracleQueryMsg {
+    Observe { seconds_ago: u64 },
+}
+
+#[cw_serde]
+pub struct OracleObservation {
+    pub timestamp: u64,
+    pub price: Decimal,
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        from_json,
+        testing::{mock_dependencies, MockApi},
+        to_json_binary, ContractResult, SystemResult, WasmQuery,
+    };
+
+    use super::*;
+
+    #[test]
+    fn oracle_values_amounts_at_the_queried_price() {
+        let mut deps = mock_dependencies();
+        let oracle = MockApi::default().addr_make("oracle");
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let OracleQueryMsg::Price { denom } = from_json(msg).unwrap();
+                let price = match denom.as_str() {
+                    "untrn" => Decimal::percent(25),
+                    _ => Decimal::one(),
+                };
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&PriceResponse { price }).unwrap(),
+                ))
+            }
+            _ => unimplemented!(),
+        });
+
+        let source = PriceSource::Oracle {
+            contract_addr: oracle.to_string(),
+        }
+        .to_checked(&deps.api)
+        .unwrap();
+        let querier = QuerierWrapper::new(&deps.querier);
+
+        assert_eq!(
+            source
+                .value(&querier, "untrn", Uint128::new(1_001))
+                .unwrap(),
+            Uint128::new(250)
+        );
+        assert_eq!(
+            source
+                .total_value(
+                    &querier,
+                    &[("untrn", Uint128::new(400)), ("uusdc", Uint128::new(50))]
+                )
+                .unwrap(),
+            Uint128::new(150)
+        );
+        assert_eq!(source.priced_denoms(), None);
+    }
+
+    #[test]
+    fn astroport_twap_prices_the_base_denom_in_the_quote_denom() {
+        let mut deps = mock_dependencies();
+        let pool = MockApi::default().addr_make("pool");
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let AstroportOracleQueryMsg::Observe { seconds_ago } = from_json(msg).unwrap();
+                assert_eq!(seconds_ago, 3_600);
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&OracleObservation {
+                        timestamp: 1_000,
+                        price: Decimal::percent(200),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => unimplemented!(),
+        });
+
+        let source = PriceSource::AstroportTwap {
+            pool_addr: pool.to_string(),
+            base_denom: "untrn".to_string(),
+            quote_denom: "uusdc".to_string(),
+            seconds_ago: 3_600,
+        }
+        .to_checked(&deps.api)
+        .unwrap();
+        let querier = QuerierWrapper::new(&deps.querier);
+
+        assert_eq!(
+            source.price(&querier, "untrn").unwrap(),
+            Decimal::percent(200)
+        );
+        assert_eq!(source.price(&querier, "uusdc").unwrap(), Decimal::one());
+        assert!(source.price(&querier, "uatom").is_err());
+        assert_eq!(source.priced_denoms(), Some(vec!["untrn", "uusdc"]));
+    }
+
+    #[test]
+    fn astroport_twap_denoms_are_validated() {
+        let api = MockApi::default();
+
+        for (base_denom, quote_denom) in [("", "uusdc"), ("untrn", ""), ("untrn", "untrn")] {
+            let source = PriceSource::AstroportTwap {
+                pool_addr: api.addr_make("pool").to_string(),
+                base_denom: base_denom.to_string(),
+                quote_denom: quote_denom.to_string(),
+                seconds_ago: 0,
+            };
+            assert!(matches!(
+                source.to_checked(&api).unwrap_err(),
+                LibraryError::ConfigurationError(_)
+            ));
+        }
+    }
+}
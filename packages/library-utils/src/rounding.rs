@@ -0,0 +1,176 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Decimal, Uint128, Uint256};
+
+use crate::error::LibraryError;
+
+#[cw_serde]
+#[derive(Default, Copy)]
+/// How the fractional part of an amount computed from a percentage of a balance is rounded
+pub enum RoundingPolicy {
+    /// Round towards zero, so that no more than the percentage of the balance is ever spent
+    #[default]
+    Down,
+    /// Round to the closest amount, halves being rounded up
+    Nearest,
+    /// Round away from zero
+    Up,
+}
+
+impl RoundingPolicy {
+    /// Applies `percentage` to `amount`, rounding the result with this policy
+    pub fn apply(&self, amount: Uint128, percentage: Decimal) -> Result<Uint128, LibraryError> {
+        let denominator = Uint256::from(Decimal::one().atomics());
+        let product = amount.full_mul(percentage.atomics());
+        let quotient = product / denominator;
+        let remainder = product % denominator;
+
+        let round_up = match self {
+            RoundingPolicy::Down => false,
+            RoundingPolicy::Nearest => remainder >= denominator - remainder,
+            RoundingPolicy::Up => !remainder.is_zero(),
+        };
+        let rounded = if round_up {
+            quotient + Uint256::one()
+        } else {
+            quotient
+        };
+
+        Uint128::try_from(rounded).map_err(|e| LibraryError::ExecutionError(e.to_string()))
+    }
+}
+
+/// Splits `amount` into one leg per ratio, each leg being rounded with `policy`.
+/// Legs are capped to what is left of `amount`, so their sum never exceeds it.
+pub fn split_amount(
+    amount: Uint128,
+    ratios: &[Decimal],
+    policy: RoundingPolicy,
+) -> Result<Vec<Uint128>, LibraryError> {
+    let mut remaining = amount;
+    ratios
+        .iter()
+        .map(|ratio| {
+            let leg = policy.apply(amount, *ratio)?.min(remaining);
+            remaining -= leg;
+            Ok(leg)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Deterministic xorshift generator, so that failures can be reproduced
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn balance(&mut self) -> Uint128 {
+            // Mix small balances, where dust matters the most, with large ones
+            match self.next() % 3 {
+                0 => Uint128::new((self.next() % 100) as u128),
+                1 => Uint128::new(self.next() as u128),
+                _ => Uint128::new(((self.next() as u128) << 64) | self.next() as u128),
+            }
+        }
+
+        // Between 1 and 8 nonzero ratios adding up to exactly 1
+        fn ratios(&mut self) -> Vec<Decimal> {
+            let legs = 1 + (self.next() % 8) as usize;
+            let weights: Vec<u128> = (0..legs)
+                .map(|_| 1 + (self.next() % 1_000) as u128)
+                .collect();
+            let total: u128 = weights.iter().sum();
+
+            let mut ratios: Vec<Decimal> = weights[..legs - 1]
+                .iter()
+                .map(|weight| Decimal::from_ratio(*weight, total))
+                .collect();
+            let allocated = ratios
+                .iter()
+                .fold(Decimal::zero(), |sum, ratio| sum + ratio);
+            ratios.push(Decimal::one() - allocated);
+            ratios
+        }
+    }
+
+    #[test]
+    fn policies_round_the_fractional_part() {
+        for (amount, percentage, down, nearest, up) in [
+            (10, Decimal::from_ratio(1u128, 3u128), 3, 3, 4),
+            (5, Decimal::percent(50), 2, 3, 3),
+            (7, Decimal::percent(90), 6, 6, 7),
+            (100, Decimal::percent(25), 25, 25, 25),
+            (0, Decimal::percent(10), 0, 0, 0),
+            (u128::MAX, Decimal::one(), u128::MAX, u128::MAX, u128::MAX),
+        ] {
+            let amount = Uint128::new(amount);
+            assert_eq!(
+                RoundingPolicy::Down.apply(amount, percentage).unwrap(),
+                Uint128::new(down)
+            );
+            assert_eq!(
+                RoundingPolicy::Nearest.apply(amount, percentage).unwrap(),
+                Uint128::new(nearest)
+            );
+            assert_eq!(
+                RoundingPolicy::Up.apply(amount, percentage).unwrap(),
+                Uint128::new(up)
+            );
+        }
+
+        // Down matches the rounding of `mul_floor`, used before policies were configurable
+        let amount = Uint128::new(1_234_567);
+        let percentage = Decimal::from_ratio(2u128, 7u128);
+        assert_eq!(
+            RoundingPolicy::Down.apply(amount, percentage).unwrap(),
+            amount.mul_floor(percentage)
+        );
+
+        assert!(RoundingPolicy::Down
+            .apply(Uint128::MAX, Decimal::percent(200))
+            .is_err());
+    }
+
+    #[test]
+    fn split_legs_rounded_down_never_exceed_the_balance() {
+        let mut rng = Rng(0x5eed_cafe_f00d_beef);
+
+        for _ in 0..2_000 {
+            let balance = rng.balance();
+            let ratios = rng.ratios();
+            let legs = split_amount(balance, &ratios, RoundingPolicy::Down).unwrap();
+
+            let total = legs.iter().fold(Uint128::zero(), |sum, leg| sum + leg);
+            assert!(total <= balance, "{legs:?} exceed {balance}");
+            // Rounding down never needs capping, and leaves less than one unit of dust per leg
+            for (leg, ratio) in legs.iter().zip(&ratios) {
+                assert_eq!(*leg, balance.mul_floor(*ratio));
+            }
+            assert!((balance - total).u128() < ratios.len() as u128);
+        }
+    }
+
+    #[test]
+    fn split_legs_rounded_up_are_capped_to_the_balance() {
+        let mut rng = Rng(0x0dd_ba11_5ca1_ab1e);
+
+        for _ in 0..2_000 {
+            let balance = rng.balance();
+            let ratios = rng.ratios();
+
+            for policy in [RoundingPolicy::Nearest, RoundingPolicy::Up] {
+                let legs = split_amount(balance, &ratios, policy).unwrap();
+                let total = legs.iter().fold(Uint128::zero(), |sum, leg| sum + leg);
+                assert!(total <= balance, "{legs:?} exceed {balance}");
+            }
+        }
+    }
+}
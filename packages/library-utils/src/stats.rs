@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{StdError, StdResult, Storage, Uint128};
+use cw_storage_plus::Item;
+
+/// Cumulative counters of the transfers executed by a library
+pub const TRANSFER_STATS: Item<TransferStats> = Item::new("transfer_stats");
+
+#[cw_serde]
+#[derive(Default)]
+/// Throughput of a transfer library since its instantiation. Transfers are recorded when they are executed
+/// by the library or, for libraries called back with the outcome of their transfers, once they succeeded
+pub struct TransferStats {
+    // Total amount transferred, per denom since the denom of a library can be updated
+    pub total_amounts: BTreeMap<String, Uint128>,
+    // Number of executions that transferred funds, skipped transfers are not counted
+    pub executions: u64,
+    // Height of the last execution that transferred funds
+    pub last_execution_height: Option<u64>,
+}
+
+impl TransferStats {
+    /// Adds one execution transferring `transfers` (denom and amount) at `height`, erroring on overflow
+    pub fn record(&mut self, height: u64, transfers: &[(String, Uint128)]) -> StdResult<()> {
+        for (denom, amount) in transfers {
+            let total = self.total_amounts.entry(denom.clone()).or_default();
+            *total = total.checked_add(*amount)?;
+        }
+
+        self.executions = self
+            .executions
+            .checked_add(1)
+            .ok_or_else(|| StdError::generic_err("transfer executions overflow"))?;
+        self.last_execution_height = Some(height);
+        Ok(())
+    }
+}
+
+/// Records an execution of the library transferring `transfers` (denom and amount) at `height`
+pub fn record_transfers(
+    storage: &mut dyn Storage,
+    height: u64,
+    transfers: &[(String, Uint128)],
+) -> StdResult<()> {
+    let mut stats = query_transfer_stats(storage)?;
+    stats.record(height, transfers)?;
+    TRANSFER_STATS.save(storage, &stats)
+}
+
+/// Returns the transfer stats of the library, which are empty until its first transfer
+pub fn query_transfer_stats(storage: &dyn Storage) -> StdResult<TransferStats> {
+    Ok(TRANSFER_STATS.may_load(storage)?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::MockStorage;
+
+    use super::*;
+
+    #[test]
+    fn transfers_accumulate_per_denom() {
+        let mut storage = MockStorage::new();
+        assert_eq!(
+            query_transfer_stats(&storage).unwrap(),
+            TransferStats::default()
+        );
+
+        record_transfers(
+            &mut storage,
+            10,
+            &[("uusdc".to_string(), Uint128::new(100))],
+        )
+        .unwrap();
+        record_transfers(
+            &mut storage,
+            12,
+            &[
+                ("uusdc".to_string(), Uint128::new(50)),
+                ("untrn".to_string(), Uint128::new(7)),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            query_transfer_stats(&storage).unwrap(),
+            TransferStats {
+                total_amounts: BTreeMap::from([
+                    ("untrn".to_string(), Uint128::new(7)),
+                    ("uusdc".to_string(), Uint128::new(150)),
+                ]),
+                executions: 2,
+                last_execution_height: Some(12),
+            }
+        );
+    }
+
+    #[test]
+    fn overflowing_total_is_rejected() {
+        let mut storage = MockStorage::new();
+        record_transfers(&mut storage, 1, &[("uusdc".to_string(), Uint128::MAX)]).unwrap();
+
+        record_transfers(&mut storage, 2, &[("uusdc".to_string(), Uint128::one())]).unwrap_err();
+
+        // The failed execution is not recorded
+        let stats = query_transfer_stats(&storage).unwrap();
+        assert_eq!(stats.executions, 1);
+        assert_eq!(stats.total_amounts["uusdc"], Uint128::MAX);
+    }
+}
@@ -0,0 +1,71 @@
+use cosmwasm_std::{Decimal, Uint128};
+
+use crate::error::LibraryError;
+
+/// Maximum number of decimals of a denom that amounts in display units can be converted with
+pub const MAX_DECIMALS: u8 = Decimal::DECIMAL_PLACES as u8;
+
+/// Converts an amount in display units (e.g. 1.5 USDC) into the base units of a denom with `decimals`
+/// decimals (1500000 for the 6 decimals of USDC). Amounts with more decimal places than the denom
+/// are rejected instead of being rounded, so that a mistyped amount is never silently altered
+pub fn to_base_units(amount: Decimal, decimals: u8) -> Result<Uint128, LibraryError> {
+    if decimals > MAX_DECIMALS {
+        return Err(LibraryError::ConfigurationError(format!(
+            "Invalid decimals: {decimals} is above the maximum of {MAX_DECIMALS}."
+        )));
+    }
+
+    // Decimal atomics are in units of 10^-18, one base unit is 10^(18 - decimals) of them
+    let atomics_per_unit = Uint128::new(10u128.pow((MAX_DECIMALS - decimals) as u32));
+    if !(amount.atomics() % atomics_per_unit).is_zero() {
+        return Err(LibraryError::ConfigurationError(format!(
+            "Invalid amount: {amount} has more than {decimals} decimals, it can't be converted to base units without losing precision."
+        )));
+    }
+
+    Ok(amount.atomics() / atomics_per_unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn display_amounts_are_converted_to_base_units() {
+        let cases = [
+            ("1000", 6, 1_000_000_000),
+            ("1.5", 6, 1_500_000),
+            ("0.000001", 6, 1),
+            ("42", 0, 42),
+            ("0.000000000000000001", 18, 1),
+        ];
+
+        for (amount, decimals, base_units) in cases {
+            assert_eq!(
+                to_base_units(Decimal::from_str(amount).unwrap(), decimals).unwrap(),
+                Uint128::new(base_units),
+                "{amount} with {decimals} decimals"
+            );
+        }
+    }
+
+    #[test]
+    fn lossy_conversions_are_rejected() {
+        let err = to_base_units(Decimal::from_str("1.0000005").unwrap(), 6).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: Invalid amount: 1.0000005 has more than 6 decimals, it can't be converted to base units without losing precision."
+        );
+
+        let err = to_base_units(Decimal::from_str("0.5").unwrap(), 0).unwrap_err();
+        assert!(err.to_string().contains("has more than 0 decimals"));
+
+        let err = to_base_units(Decimal::one(), 19).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: Invalid decimals: 19 is above the maximum of 18."
+        );
+    }
+}
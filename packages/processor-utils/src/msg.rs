@@ -5,7 +5,7 @@ use valence_gmp_utils::polytone::CallbackMessage;
 
 use crate::{
     callback::PendingPolytoneCallbackInfo,
-    processor::{Config, MessageBatch},
+    processor::{Config, DeadLetter, MessageBatch, QueueGasEstimate},
 };
 
 #[cw_serde]
@@ -59,6 +59,12 @@ pub enum QueryMsg {
     },
     #[returns(bool)]
     IsQueueEmpty {},
+    // Positions are counted across all the queues, in the order the batches will be processed
+    #[returns(QueuesResponse)]
+    GetQueues {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     #[returns(Vec<PendingPolytoneCallbackInfo>)]
     PendingPolytoneCallbacks {
         start_after: Option<u64>,
@@ -66,4 +72,21 @@ pub enum QueryMsg {
     },
     #[returns(PendingPolytoneCallbackInfo)]
     PendingPolytoneCallback { execution_id: u64 },
+    #[returns(Vec<DeadLetter>)]
+    DeadLetterQueue {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // Estimated gas of all the enqueued batches, and whether it likely exceeds the gas limit of a block
+    // (the default block gas limit is used if none is given)
+    #[returns(QueueGasEstimate)]
+    EstimateQueueGas { block_gas_limit: Option<u64> },
+}
+
+// A page of the batches currently enqueued, grouped by priority and in the order they will be processed
+#[cw_serde]
+pub struct QueuesResponse {
+    pub high: Vec<MessageBatch>,
+    pub medium: Vec<MessageBatch>,
+    pub low: Vec<MessageBatch>,
 }
@@ -79,6 +79,34 @@ fn create_cosmos_msg(
 }
 
 impl MessageBatch {
+    /// Messages of an atomic batch, executed together by the processor.
+    /// The gas limit of each function (if any) is attached to its message, but there is no reply since any failure reverts the whole batch
+    pub fn create_atomic_messages(&self) -> StdResult<Vec<SubMsg>> {
+        self.msgs
+            .iter()
+            .enumerate()
+            .map(|(index, msg)| {
+                let contract_address = self
+                    .subroutine
+                    .get_contract_address_by_function_index(index);
+                Ok(
+                    self.with_gas_limit(
+                        SubMsg::new(msg.to_wasm_message(&contract_address)?),
+                        index,
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    // Attaches the gas limit of the function at `index` to its submessage, when the function has one
+    fn with_gas_limit(&self, submessage: SubMsg, index: usize) -> SubMsg {
+        match self.subroutine.get_gas_limit_by_function_index(index) {
+            Some(gas_limit) => submessage.with_gas_limit(gas_limit),
+            None => submessage,
+        }
+    }
+
     /// This is used for non-atomic batches. We need to catch the reply always because we need to know if the message was successful to continue
     /// with the next message in the batch or apply the retry logic
     pub fn create_message_by_index(&self, index: usize) -> Result<Vec<SubMsg>, StdError> {
@@ -89,7 +117,7 @@ impl MessageBatch {
             self.msgs[index].to_wasm_message(&contract_address)?,
             self.id,
         );
-        Ok(vec![submessage])
+        Ok(vec![self.with_gas_limit(submessage, index)])
     }
 
     /// Very similar to create_message_by_index, but we append an execution id to the message
@@ -123,7 +151,7 @@ impl MessageBatch {
 
         let submessage =
             SubMsg::reply_always(new_msg.to_wasm_message(&contract_address)?, execution_id);
-        Ok(vec![submessage])
+        Ok(vec![self.with_gas_limit(submessage, index)])
     }
 }
 
@@ -132,3 +160,243 @@ pub struct CurrentRetry {
     pub retry_amounts: u64,
     pub retry_cooldown: Expiration,
 }
+
+// Batches that failed after exhausting all their retries are kept here so that operators can inspect them
+#[cw_serde]
+pub struct DeadLetter {
+    pub batch: MessageBatch,
+    // Index of the function that exhausted its retries (always 0 for atomic batches)
+    pub function_index: usize,
+    pub error: String,
+}
+
+// Conservative gas estimates of the messages of the functions that don't have a gas limit, per message type
+pub const DEFAULT_EXECUTE_GAS: u64 = 1_000_000;
+pub const DEFAULT_MIGRATE_GAS: u64 = 2_000_000;
+pub const DEFAULT_EVM_CALL_GAS: u64 = 1_000_000;
+// Gas limit of a block used by the `EstimateQueueGas` query when none is given
+pub const DEFAULT_BLOCK_GAS_LIMIT: u64 = 30_000_000;
+
+impl MessageBatch {
+    /// Estimated gas of the whole batch: the gas limit of each function, or the default of its message type if it has none
+    pub fn estimate_gas(&self) -> u64 {
+        self.msgs
+            .iter()
+            .enumerate()
+            .map(|(index, msg)| {
+                self.subroutine
+                    .get_gas_limit_by_function_index(index)
+                    .unwrap_or(match msg {
+                        ProcessorMessage::CosmwasmExecuteMsg { .. } => DEFAULT_EXECUTE_GAS,
+                        ProcessorMessage::CosmwasmMigrateMsg { .. } => DEFAULT_MIGRATE_GAS,
+                        ProcessorMessage::EvmCall { .. } | ProcessorMessage::EvmRawCall { .. } => {
+                            DEFAULT_EVM_CALL_GAS
+                        }
+                    })
+            })
+            .fold(0, u64::saturating_add)
+    }
+}
+
+#[cw_serde]
+pub struct BatchGasEstimate {
+    pub id: u64,
+    pub gas: u64,
+}
+
+// Estimated gas of the enqueued batches, returned by the `EstimateQueueGas` query
+#[cw_serde]
+pub struct QueueGasEstimate {
+    // Estimate of every batch, in the order they will be processed
+    pub batches: Vec<BatchGasEstimate>,
+    pub total_gas: u64,
+    pub block_gas_limit: u64,
+    // Whether processing all the batches likely doesn't fit in a single block
+    pub exceeds_block_limit: bool,
+}
+
+impl QueueGasEstimate {
+    pub fn new<'a>(
+        batches: impl IntoIterator<Item = &'a MessageBatch>,
+        block_gas_limit: u64,
+    ) -> Self {
+        let batches: Vec<BatchGasEstimate> = batches
+            .into_iter()
+            .map(|batch| BatchGasEstimate {
+                id: batch.id,
+                gas: batch.estimate_gas(),
+            })
+            .collect();
+        let total_gas = batches
+            .iter()
+            .map(|batch| batch.gas)
+            .fold(0, u64::saturating_add);
+
+        QueueGasEstimate {
+            batches,
+            total_gas,
+            block_gas_limit,
+            exceeds_block_limit: total_gas > block_gas_limit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{to_json_binary, ReplyOn};
+    use valence_authorization_utils::builders::{
+        AtomicFunctionBuilder, AtomicSubroutineBuilder, NonAtomicFunctionBuilder,
+        NonAtomicSubroutineBuilder,
+    };
+    use valence_library_utils::LibraryAccountType;
+
+    use super::*;
+
+    // A heavy provide with a gas limit, followed by a light forward without one
+    fn batch(subroutine: Subroutine) -> MessageBatch {
+        let msg =
+            to_json_binary(&json!({ "process_function": { "provide_liquidity": {} } })).unwrap();
+        MessageBatch {
+            id: 7,
+            msgs: vec![
+                ProcessorMessage::CosmwasmExecuteMsg { msg: msg.clone() },
+                ProcessorMessage::CosmwasmExecuteMsg { msg },
+            ],
+            subroutine,
+            priority: Priority::Medium,
+            expiration_time: None,
+            retry: None,
+        }
+    }
+
+    #[test]
+    fn non_atomic_messages_carry_the_function_gas_limit() {
+        let batch = batch(
+            NonAtomicSubroutineBuilder::new()
+                .with_function(
+                    NonAtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr("lper".to_string()))
+                        .with_gas_limit(2_000_000)
+                        .build(),
+                )
+                .with_function(
+                    NonAtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr("forwarder".to_string()))
+                        .build(),
+                )
+                .build(),
+        );
+
+        let heavy = batch.create_message_by_index(0).unwrap();
+        assert_eq!(heavy[0].gas_limit, Some(2_000_000));
+        assert_eq!(heavy[0].reply_on, ReplyOn::Always);
+        assert_eq!(heavy[0].id, 7);
+
+        let heavy = batch
+            .create_message_by_index_with_execution_id(0, 7)
+            .unwrap();
+        assert_eq!(heavy[0].gas_limit, Some(2_000_000));
+
+        // Without a gas limit the message can use all the gas left, as before
+        let light = batch.create_message_by_index(1).unwrap();
+        assert_eq!(light[0].gas_limit, None);
+    }
+
+    #[test]
+    fn queue_gas_estimate_sums_gas_limits_and_defaults() {
+        // An atomic batch with a gas limited provide and a forward without gas limit
+        let atomic = batch(
+            AtomicSubroutineBuilder::new()
+                .with_function(
+                    AtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr("lper".to_string()))
+                        .with_gas_limit(2_500_000)
+                        .build(),
+                )
+                .with_function(
+                    AtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr("forwarder".to_string()))
+                        .build(),
+                )
+                .build(),
+        );
+        // A non atomic batch migrating a library without gas limit
+        let migrate = MessageBatch {
+            id: 8,
+            msgs: vec![ProcessorMessage::CosmwasmMigrateMsg {
+                code_id: 3,
+                msg: to_json_binary(&json!({})).unwrap(),
+            }],
+            subroutine: NonAtomicSubroutineBuilder::new()
+                .with_function(
+                    NonAtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr("forwarder".to_string()))
+                        .build(),
+                )
+                .build(),
+            priority: Priority::High,
+            expiration_time: None,
+            retry: None,
+        };
+
+        assert_eq!(atomic.estimate_gas(), 2_500_000 + DEFAULT_EXECUTE_GAS);
+        assert_eq!(migrate.estimate_gas(), DEFAULT_MIGRATE_GAS);
+
+        let estimate = QueueGasEstimate::new([&migrate, &atomic], DEFAULT_BLOCK_GAS_LIMIT);
+        assert_eq!(
+            estimate.batches,
+            vec![
+                BatchGasEstimate {
+                    id: 8,
+                    gas: 2_000_000
+                },
+                BatchGasEstimate {
+                    id: 7,
+                    gas: 3_500_000
+                }
+            ]
+        );
+        assert_eq!(estimate.total_gas, 5_500_000);
+        assert!(!estimate.exceeds_block_limit);
+
+        // The same queue doesn't fit in a smaller block
+        let estimate = QueueGasEstimate::new([&migrate, &atomic], 5_000_000);
+        assert!(estimate.exceeds_block_limit);
+    }
+
+    #[test]
+    fn atomic_messages_carry_the_function_gas_limit() {
+        let batch = batch(
+            AtomicSubroutineBuilder::new()
+                .with_function(
+                    AtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr("lper".to_string()))
+                        .with_gas_limit(2_000_000)
+                        .build(),
+                )
+                .with_function(
+                    AtomicFunctionBuilder::new()
+                        .with_contract_address(LibraryAccountType::Addr("forwarder".to_string()))
+                        .build(),
+                )
+                .build(),
+        );
+
+        let messages = batch.create_atomic_messages().unwrap();
+        assert_eq!(
+            messages
+                .iter()
+                .map(|msg| (msg.gas_limit, msg.reply_on.clone()))
+                .collect::<Vec<_>>(),
+            vec![(Some(2_000_000), ReplyOn::Never), (None, ReplyOn::Never)]
+        );
+        assert_eq!(
+            messages[1].msg,
+            CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
+                contract_addr: "forwarder".to_string(),
+                msg: batch.msgs[1].get_msg().clone(),
+                funds: vec![],
+            })
+        );
+    }
+}
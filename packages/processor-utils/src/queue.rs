@@ -162,6 +162,30 @@ where
         range_result
     }
 
+    // Lazily iterates over the elements of the queue, so that callers that stop early only load the elements they consume
+    pub fn iter<'a>(
+        &self,
+        storage: &'a dyn Storage,
+        order: Order,
+    ) -> StdResult<Box<dyn Iterator<Item = StdResult<T>> + 'a>>
+    where
+        T: 'a,
+    {
+        let start_index = self.start_index(storage)?;
+        let end_index = self.end_index(storage)?;
+
+        Ok(Box::new(
+            self.elements
+                .range(
+                    storage,
+                    Some(Bound::exclusive(start_index)),
+                    Some(Bound::inclusive(end_index)),
+                    order,
+                )
+                .map(|item| item.map(|(_, v)| v)),
+        ))
+    }
+
     pub fn len(&self, storage: &dyn Storage) -> StdResult<u64> {
         let start_index = self.start_index(storage)?;
         let end_index = self.end_index(storage)?;
@@ -317,4 +341,40 @@ mod tests {
         let items = queue.query(storage, None, None, Order::Ascending).unwrap();
         assert_eq!(items, vec!["2"]);
     }
+
+    #[test]
+    fn test_iter() {
+        let mut deps = mock_dependencies();
+        let storage = &mut deps.storage;
+        let mut queue = QueueMap::new("elements", "elements_start_index", "elements_end_index");
+
+        for i in 0..5 {
+            queue.push_back(storage, &i).unwrap();
+        }
+        queue.pop_front(storage).unwrap();
+        queue.remove_at(storage, 1).unwrap();
+
+        let items: Vec<i32> = queue
+            .iter(storage, Order::Ascending)
+            .unwrap()
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(items, vec![1, 3, 4]);
+
+        let items: Vec<i32> = queue
+            .iter(storage, Order::Descending)
+            .unwrap()
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(items, vec![4, 3, 1]);
+
+        // Stopping early only consumes the elements needed
+        let items: Vec<i32> = queue
+            .iter(storage, Order::Ascending)
+            .unwrap()
+            .take(2)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(items, vec![1, 3]);
+    }
 }
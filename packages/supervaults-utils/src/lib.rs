@@ -11,6 +11,8 @@ pub fn ensure_correct_vault(
     asset_data: &AssetData,
     lp_denom: &str,
 ) -> Result<(), LibraryError> {
+    asset_data.ensure_two_assets()?;
+
     let vault_config: mmvault::state::Config = deps
         .querier
         .query_wasm_smart(vault_addr, &mmvault::msg::QueryMsg::GetConfig {})?;
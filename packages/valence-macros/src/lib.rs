@@ -342,6 +342,20 @@ pub fn valence_library_query(metadata: TokenStream, input: TokenStream) -> Token
                 /// Query to get the processor address.
                 #[returns(Addr)]
                 GetProcessor {},
+                /// Query to get whether the library is paused.
+                #[returns(bool)]
+                IsPaused {},
+                /// Query to get the error of the last failed execution of the library recorded by the processor.
+                #[returns(valence_library_utils::msg::LastErrorResponse)]
+                GetLastError {},
+                /// Query to get whether the library is approved on the accounts it executes messages from.
+                #[returns(bool)]
+                IsApproved {},
+                /// Query to resolve an account reference (e.g. the remote address of an ICA) into the address it references, without side effects.
+                #[returns(Addr)]
+                ResolveAccount {
+                    account: valence_library_utils::LibraryAccountType,
+                },
                 /// Query to get the library configuration.
                 #[returns(Config)]
                 GetLibraryConfig {},
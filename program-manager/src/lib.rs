@@ -11,13 +11,16 @@ pub mod mock_api;
 pub mod program_config;
 pub mod program_config_builder;
 pub mod program_migration;
+pub mod program_topology;
 pub mod program_update;
 pub mod tests;
 
 use connectors::Connectors;
-use error::ManagerResult;
+use domain::Domain;
+use error::{ManagerError, ManagerResult};
 use program_config::ProgramConfig;
 use program_migration::{MigrateResponse, ProgramConfigMigrate};
+use program_topology::ProgramTopology;
 use program_update::{ProgramConfigUpdate, UpdateResponse};
 
 // Main chain name
@@ -49,3 +52,18 @@ pub async fn migrate_program(
 
     program_config.migrate(&connectors).await
 }
+
+/// Assembles the topology of a program from its config saved in the registry
+pub async fn get_program_topology(program_id: u64) -> ManagerResult<ProgramTopology> {
+    // 0 is not a valid id of a program
+    if program_id == 0 {
+        return Err(ManagerError::InvalidProgramId);
+    }
+
+    let connectors = Connectors::default();
+    let neutron_domain = Domain::CosmosCosmwasm(NEUTRON_CHAIN.to_string());
+    let mut neutron_connector = connectors.get_or_create_connector(&neutron_domain).await?;
+    let config = neutron_connector.get_program_config(program_id).await?;
+
+    Ok(ProgramTopology::from(&config))
+}
@@ -103,6 +103,10 @@ pub enum LibraryConfig {
     ValenceMagmaLper(valence_magma_lper::msg::LibraryConfig),
     ValenceMagmaWithdrawer(valence_magma_withdrawer::msg::LibraryConfig),
     ValenceVortexLper(valence_vortex_lper::msg::LibraryConfig),
+    ValenceSweeperLibrary(valence_sweeper_library::msg::LibraryConfig),
+    ValenceAstroportSwap(valence_astroport_swap::msg::LibraryConfig),
+    ValenceOrchestratorLibrary(valence_orchestrator_library::msg::LibraryConfig),
+    ValenceDenomRouterLibrary(valence_denom_router_library::msg::LibraryConfig),
 }
 
 impl LibraryConfig {
@@ -459,6 +459,14 @@ impl ProgramConfig {
                                     .clone()
                                     .unwrap()
                             }
+                            valence_library_utils::LibraryAccountType::Role(role) => {
+                                panic!(
+                                    "Role {role} must be resolved before instantiating the program"
+                                )
+                            }
+                            valence_library_utils::LibraryAccountType::IcaRemote { .. } => {
+                                panic!("A function can't be executed on an ICA remote address")
+                            }
                         };
                         function.contract_address =
                             valence_library_utils::LibraryAccountType::Addr(addr);
@@ -489,6 +497,14 @@ impl ProgramConfig {
                                     .addr
                                     .clone()
                                     .unwrap(),
+                                valence_library_utils::LibraryAccountType::Role(role) => {
+                                    panic!("Role {role} must be resolved before instantiating the program")
+                                }
+                                valence_library_utils::LibraryAccountType::IcaRemote {
+                                    ..
+                                } => {
+                                    panic!("A function can't be executed on an ICA remote address")
+                                }
                             };
                             function.contract_address =
                                 valence_library_utils::LibraryAccountType::Addr(addr);
@@ -0,0 +1,152 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use cosmwasm_schema::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use valence_library_utils::Id;
+
+use crate::{domain::Domain, program_config::ProgramConfig};
+
+/// A component of the program graph, by its id in the program config
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[schemars(crate = "cosmwasm_schema::schemars")]
+#[serde(rename_all = "snake_case")]
+pub enum TopologyNode {
+    Account(Id),
+    Library(Id),
+}
+
+/// Funds flow from an input account to its library, and from a library to its output accounts
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(crate = "cosmwasm_schema::schemars")]
+pub struct TopologyEdge {
+    pub from: TopologyNode,
+    pub to: TopologyNode,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(crate = "cosmwasm_schema::schemars")]
+pub struct AccountNode {
+    pub name: String,
+    pub domain: Domain,
+    /// None if the account is not instantiated yet
+    pub addr: Option<String>,
+    /// Libraries approved on the account, which are the libraries it is an input of
+    pub approved_libraries: Vec<Id>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(crate = "cosmwasm_schema::schemars")]
+pub struct LibraryNode {
+    pub name: String,
+    pub domain: Domain,
+    /// Name of the library config, e.g. `valence_forwarder_library`
+    pub library_type: String,
+    /// None if the library is not instantiated yet
+    pub addr: Option<String>,
+    pub input_accounts: Vec<Id>,
+    pub output_accounts: Vec<Id>,
+}
+
+/// The full graph of a program: its accounts, its libraries and how funds move between them
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(crate = "cosmwasm_schema::schemars")]
+pub struct ProgramTopology {
+    pub program_id: u64,
+    pub name: String,
+    pub accounts: BTreeMap<Id, AccountNode>,
+    pub libraries: BTreeMap<Id, LibraryNode>,
+    pub edges: Vec<TopologyEdge>,
+    /// Components referenced by a link that are not part of the program config.
+    /// Their edges are kept so that the graph shows where the program is incomplete
+    pub missing: Vec<TopologyNode>,
+}
+
+impl From<&ProgramConfig> for ProgramTopology {
+    fn from(config: &ProgramConfig) -> Self {
+        let mut accounts: BTreeMap<Id, AccountNode> = config
+            .accounts
+            .iter()
+            .map(|(id, account)| {
+                (
+                    *id,
+                    AccountNode {
+                        name: account.name.clone(),
+                        domain: account.domain.clone(),
+                        addr: account.addr.clone(),
+                        approved_libraries: vec![],
+                    },
+                )
+            })
+            .collect();
+        let mut libraries: BTreeMap<Id, LibraryNode> = config
+            .libraries
+            .iter()
+            .map(|(id, library)| {
+                (
+                    *id,
+                    LibraryNode {
+                        name: library.name.clone(),
+                        domain: library.domain.clone(),
+                        library_type: library.config.to_string(),
+                        addr: library.addr.clone(),
+                        input_accounts: vec![],
+                        output_accounts: vec![],
+                    },
+                )
+            })
+            .collect();
+
+        let mut edges = vec![];
+        let mut missing = BTreeSet::new();
+
+        for link in config.links.values() {
+            let library = TopologyNode::Library(link.library_id);
+            match libraries.get_mut(&link.library_id) {
+                Some(node) => {
+                    node.input_accounts.extend(&link.input_accounts_id);
+                    node.output_accounts.extend(&link.output_accounts_id);
+                }
+                None => {
+                    missing.insert(library.clone());
+                }
+            }
+
+            for account_id in &link.input_accounts_id {
+                match accounts.get_mut(account_id) {
+                    Some(node) => node.approved_libraries.push(link.library_id),
+                    None => {
+                        missing.insert(TopologyNode::Account(*account_id));
+                    }
+                }
+                edges.push(TopologyEdge {
+                    from: TopologyNode::Account(*account_id),
+                    to: library.clone(),
+                });
+            }
+
+            for account_id in &link.output_accounts_id {
+                if !accounts.contains_key(account_id) {
+                    missing.insert(TopologyNode::Account(*account_id));
+                }
+                edges.push(TopologyEdge {
+                    from: library.clone(),
+                    to: TopologyNode::Account(*account_id),
+                });
+            }
+        }
+
+        for account in accounts.values_mut() {
+            account.approved_libraries.sort_unstable();
+            account.approved_libraries.dedup();
+        }
+
+        ProgramTopology {
+            program_id: config.id,
+            name: config.name.clone(),
+            accounts,
+            libraries,
+            edges,
+            missing: missing.into_iter().collect(),
+        }
+    }
+}
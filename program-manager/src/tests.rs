@@ -10,6 +10,8 @@ mod test {
         domain::Domain,
         library::{LibraryConfig, LibraryInfo},
         program_config::{Link, ProgramConfig},
+        program_config_builder::ProgramConfigBuilder,
+        program_topology::{ProgramTopology, TopologyEdge, TopologyNode},
     };
     use serde_json_any_key::MapIterToJson;
     use valence_authorization_utils::{
@@ -59,6 +61,7 @@ mod test {
         let config =
             LibraryConfig::ValenceForwarderLibrary(valence_forwarder_library::msg::LibraryConfig {
                 input_addr: "|account_id|:1".into(),
+                additional_input_addrs: None,
                 output_addr: "|account_id|:2".into(),
                 forwarding_configs: vec![
                     valence_forwarder_library::msg::UncheckedForwardingConfig {
@@ -69,6 +72,13 @@ mod test {
                 forwarding_constraints: valence_forwarder_library::msg::ForwardingConstraints::new(
                     None,
                 ),
+                split_outputs: None,
+                rounding: Default::default(),
+                lp_redemption: None,
+                gas_reserve: None,
+                snapshot_balances: false,
+                precondition: None,
+                fee_skim: None,
             });
 
         let account_ids = config.get_account_ids().unwrap();
@@ -204,6 +214,7 @@ mod test {
                 config: LibraryConfig::ValenceForwarderLibrary(
                     valence_forwarder_library::msg::LibraryConfig {
                         input_addr: LibraryAccountType::AccountId(1),
+                        additional_input_addrs: None,
                         output_addr: LibraryAccountType::AccountId(2),
                         forwarding_configs: vec![
                             valence_forwarder_library::msg::UncheckedForwardingConfig {
@@ -213,6 +224,13 @@ mod test {
                         ],
                         forwarding_constraints:
                             valence_forwarder_library::msg::ForwardingConstraints::new(None),
+                        split_outputs: None,
+                        rounding: Default::default(),
+                        lp_redemption: None,
+                        gas_reserve: None,
+                        snapshot_balances: false,
+                        precondition: None,
+                        fee_skim: None,
                     },
                 ),
                 addr: None,
@@ -247,6 +265,8 @@ mod test {
                             },
                         },
                         contract_address: LibraryAccountType::LibraryId(1),
+                        gas_limit: None,
+                        scope: None,
                     }],
                     retry_logic: None,
                     expiration_time: None,
@@ -284,4 +304,105 @@ mod test {
         //     Err(_) => println!("Program initialization timed out after 60 seconds"),
         // }
     }
+
+    #[test]
+    fn test_program_topology() {
+        let neutron_domain = Domain::CosmosCosmwasm("neutron".to_string());
+        let mut builder = ProgramConfigBuilder::new("topology", "owner");
+
+        let deposit = builder.add_account(AccountInfo::new(
+            "deposit".to_string(),
+            &neutron_domain,
+            AccountType::default(),
+        ));
+        let position = builder.add_account(AccountInfo::new(
+            "position".to_string(),
+            &neutron_domain,
+            AccountType::default(),
+        ));
+        let withdraw = builder.add_account(AccountInfo::new(
+            "withdraw".to_string(),
+            &neutron_domain,
+            AccountType::new_addr("neutron1withdraw".to_string()),
+        ));
+
+        let lper = builder.add_library(LibraryInfo::new(
+            "lper".to_string(),
+            &neutron_domain,
+            LibraryConfig::None,
+        ));
+        let lwer = builder.add_library(LibraryInfo::new(
+            "lwer".to_string(),
+            &neutron_domain,
+            LibraryConfig::None,
+        ));
+
+        builder.add_link(&lper, vec![&deposit], vec![&position]);
+        builder.add_link(&lwer, vec![&position], vec![&withdraw]);
+        // A link to an account that was never added to the program
+        builder.add_link(
+            &lwer,
+            vec![&LibraryAccountType::AccountId(7)],
+            vec![&withdraw],
+        );
+
+        let topology = ProgramTopology::from(&builder.build());
+
+        assert_eq!(topology.name, "topology");
+        assert_eq!(
+            topology
+                .accounts
+                .values()
+                .map(|account| (account.name.as_str(), account.approved_libraries.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("deposit", vec![0]),
+                ("position", vec![1]),
+                ("withdraw", vec![])
+            ]
+        );
+
+        let lwer = &topology.libraries[&1];
+        assert_eq!(lwer.library_type, "none");
+        assert_eq!(lwer.input_accounts, vec![1, 7]);
+        assert_eq!(lwer.output_accounts, vec![2, 2]);
+
+        assert_eq!(
+            topology.edges,
+            vec![
+                TopologyEdge {
+                    from: TopologyNode::Account(0),
+                    to: TopologyNode::Library(0),
+                },
+                TopologyEdge {
+                    from: TopologyNode::Library(0),
+                    to: TopologyNode::Account(1),
+                },
+                TopologyEdge {
+                    from: TopologyNode::Account(1),
+                    to: TopologyNode::Library(1),
+                },
+                TopologyEdge {
+                    from: TopologyNode::Library(1),
+                    to: TopologyNode::Account(2),
+                },
+                TopologyEdge {
+                    from: TopologyNode::Account(7),
+                    to: TopologyNode::Library(1),
+                },
+                TopologyEdge {
+                    from: TopologyNode::Library(1),
+                    to: TopologyNode::Account(2),
+                },
+            ]
+        );
+        assert_eq!(topology.missing, vec![TopologyNode::Account(7)]);
+
+        // The topology can be served as is to dashboards
+        let json = serde_json::to_string(&topology).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ProgramTopology>(&json).unwrap(),
+            topology
+        );
+    }
 }